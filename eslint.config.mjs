@@ -7,7 +7,7 @@ import globals from "globals";
 export default [
   {
     files: ["src/**/*.{ts,tsx}"],
-    ignores: ["dist/**", "node_modules/**", "src-tauri/target/**"],
+    ignores: ["dist/**", "node_modules/**", "src-tauri/target/**", "src/bindings.ts"],
     languageOptions: {
       parser: tsParser,
       parserOptions: {
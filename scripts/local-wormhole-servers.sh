@@ -0,0 +1,32 @@
+#!/usr/bin/env sh
+# Runs a local magic-wormhole mailbox (rendezvous) server and transit relay for the
+# wyrmhole-core loopback tests (src-tauri/crates/wyrmhole-core/tests/loopback.rs).
+#
+#   pip install magic-wormhole-mailbox-server magic-wormhole-transit-relay
+#   ./scripts/local-wormhole-servers.sh
+#
+# Mailbox: ws://127.0.0.1:4000/v1   Relay: tcp://127.0.0.1:4001
+# Override the ports with MAILBOX_PORT / RELAY_PORT (and point the tests at them with
+# WYRMHOLE_TEST_MAILBOX / WYRMHOLE_TEST_RELAY). Ctrl-C stops both.
+
+set -eu
+
+MAILBOX_PORT="${MAILBOX_PORT:-4000}"
+RELAY_PORT="${RELAY_PORT:-4001}"
+
+if ! command -v twist >/dev/null 2>&1; then
+  echo "twist not found; install the servers with:" >&2
+  echo "  pip install magic-wormhole-mailbox-server magic-wormhole-transit-relay" >&2
+  exit 1
+fi
+
+twist wormhole-mailbox --port "tcp:${MAILBOX_PORT}:interface=127.0.0.1" &
+MAILBOX_PID=$!
+twist transit-relay --port "tcp:${RELAY_PORT}:interface=127.0.0.1" &
+RELAY_PID=$!
+
+trap 'kill "$MAILBOX_PID" "$RELAY_PID" 2>/dev/null' EXIT INT TERM
+
+echo "Mailbox: ws://127.0.0.1:${MAILBOX_PORT}/v1"
+echo "Relay:   tcp://127.0.0.1:${RELAY_PORT}"
+wait
@@ -0,0 +1,461 @@
+// This file implements the optional localhost API (the `local_api_enabled` setting, off by
+// default): a small HTTP server on 127.0.0.1 that lets scripts and other local apps start
+// transfers and check on them without going through the CLI. Every request must carry the
+// `local_api_token` setting as `Authorization: Bearer <token>`.
+//
+//   GET  /status                  -> the ids of everything in flight (`TransferSnapshot`)
+//   POST /send                    {"paths": ["/home/me/report.pdf"]} -> {"send_id": "..."}
+//                                 (sensitive selections, see sensitive.rs, answer 409 with
+//                                 the findings unless "confirm_sensitive" is set)
+//   GET  /send/<send_id>/code     -> {"code": "7-guitarist-revenge"} once the send has one
+//   POST /send/<send_id>/cancel
+//   POST /receive                 {"code": "7-guitarist-revenge"} -> the offer (`FileOffer`)
+//   POST /offer/<id>/accept       {"destination": null} -> starts the download (offers over
+//                                 the size limit, or programs and scripts, answer 409 unless
+//                                 "confirm_oversize" / "confirm_content" is set; a
+//                                 destination must be an existing folder inside the
+//                                 download directory)
+//   POST /offer/<id>/deny
+//
+// Sends and accepted downloads run in the background once their checks pass; their progress
+// and errors go through the event sink and history like any other transfer. Clients run as
+// the user and could read any file themselves, so sends aren't limited to picked folders the
+// way the desktop app's are, but the settings file holding the token is never sent.
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::context::AppContext;
+use crate::files;
+use crate::sensitive;
+
+// Requests are small JSON bodies; anything bigger is refused rather than buffered.
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+// How long a client gets to send its whole request, so idle connections don't pile up.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The running server's address and the token that stops it, kept on the `AppContext`.
+#[derive(Default)]
+pub(crate) struct ApiServer(Mutex<Option<(SocketAddr, CancellationToken)>>);
+
+/// A fresh random token for the `local_api_token` setting.
+pub fn generate_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// Start, restart or stop the server to match the settings, returning the address it's
+/// listening on (None when the API is off). Hosts call this at launch; the settings that
+/// affect it call it again when they change.
+pub async fn restart(ctx: &AppContext) -> Result<Option<SocketAddr>, String> {
+    let mut server = ctx.api_server().0.lock().await;
+    if let Some((_, stop)) = server.take() {
+        stop.cancel();
+    }
+
+    let app_settings_lock = ctx.settings().lock().await;
+    let enabled = app_settings_lock.get_local_api_enabled();
+    let port = app_settings_lock.get_local_api_port();
+    let token = app_settings_lock.get_local_api_token().to_string();
+    drop(app_settings_lock);
+    if !enabled || token.is_empty() {
+        return Ok(None);
+    }
+
+    // Loopback only: the API is for apps on this machine.
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .await
+        .map_err(|e| format!("Failed to start the local API on port {}: {}", port, e))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to start the local API: {}", e))?;
+    let stop = CancellationToken::new();
+    tokio::spawn(serve(ctx.clone(), listener, token, stop.clone()));
+    *server = Some((addr, stop));
+    log_line!(
+        "[magic-wormhole][api][info] Local API listening on {}",
+        addr
+    );
+    Ok(Some(addr))
+}
+
+/// The address the server is listening on, if it's running.
+pub async fn address(ctx: &AppContext) -> Option<SocketAddr> {
+    ctx.api_server()
+        .0
+        .lock()
+        .await
+        .as_ref()
+        .map(|(addr, _)| *addr)
+}
+
+async fn serve(ctx: AppContext, listener: TcpListener, token: String, stop: CancellationToken) {
+    loop {
+        let stream = tokio::select! {
+            _ = stop.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log_line!("[magic-wormhole][api][error] Failed to accept a connection: {}", e);
+                    continue;
+                }
+            },
+        };
+        tokio::spawn(handle_connection(ctx.clone(), stream, token.clone()));
+    }
+    log_line!("[magic-wormhole][api][info] Local API stopped");
+}
+
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+// Read one HTTP/1.1 request: the request line, headers, and a `content-length` body.
+async fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0; 4096];
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() > MAX_REQUEST_BYTES {
+            return Err("Request headers too large".to_string());
+        }
+        let len = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if len == 0 {
+            return Err("Connection closed mid-request".to_string());
+        }
+        buffer.extend_from_slice(&chunk[..len]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let mut authorization = None;
+    let mut content_length = 0;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "authorization" => authorization = Some(value.trim().to_string()),
+            "content-length" => {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| "Invalid content-length".to_string())?
+            }
+            _ => {}
+        }
+    }
+    if content_length > MAX_REQUEST_BYTES {
+        return Err("Request body too large".to_string());
+    }
+
+    let mut body = buffer[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        let len = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if len == 0 {
+            return Err("Connection closed mid-request".to_string());
+        }
+        body.extend_from_slice(&chunk[..len]);
+    }
+    body.truncate(content_length);
+
+    Ok(Request {
+        method,
+        path,
+        authorization,
+        body,
+    })
+}
+
+// Compare in time independent of where the strings differ, so the token can't be guessed
+// a character at a time.
+fn token_matches(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn handle_connection(ctx: AppContext, mut stream: TcpStream, token: String) {
+    let (status, body) =
+        match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+            Err(_) => (408, json!({ "error": "Timed out reading the request" })),
+            Ok(Ok(request)) => {
+                let authorized = request
+                    .authorization
+                    .as_deref()
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                    .is_some_and(|given| token_matches(given.trim(), &token));
+                if authorized {
+                    route(ctx, request).await
+                } else {
+                    (401, json!({ "error": "Missing or wrong API token" }))
+                }
+            }
+            Ok(Err(e)) => (400, json!({ "error": e })),
+        };
+
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        _ => "Bad Request",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+#[derive(Deserialize)]
+struct SendBody {
+    paths: Vec<String>,
+    #[serde(default)]
+    folder_name: Option<String>,
+    #[serde(default)]
+    confirm_sensitive: bool,
+}
+
+#[derive(Deserialize)]
+struct ReceiveBody {
+    code: String,
+}
+
+#[derive(Deserialize, Default)]
+struct AcceptBody {
+    #[serde(default)]
+    destination: Option<String>,
+    #[serde(default)]
+    confirm_oversize: bool,
+    #[serde(default)]
+    confirm_content: bool,
+}
+
+fn parse_body<T: for<'de> Deserialize<'de>>(body: &[u8]) -> Result<T, (u16, Value)> {
+    serde_json::from_slice(body).map_err(|e| (400, json!({ "error": e.to_string() })))
+}
+
+fn result(outcome: Result<Value, String>) -> (u16, Value) {
+    match outcome {
+        Ok(value) => (200, value),
+        Err(e) => (400, json!({ "error": e })),
+    }
+}
+
+async fn route(ctx: AppContext, request: Request) -> (u16, Value) {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    let handled = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["status"]) => Ok(result(
+            serde_json::to_value(files::active_transfers(ctx).await).map_err(|e| e.to_string()),
+        )),
+        ("POST", ["send"]) => match parse_body::<SendBody>(&request.body) {
+            Ok(body) => Ok(match check_send(&ctx, &body).await {
+                Err(refusal) => refusal,
+                Ok(()) => {
+                    let send_id = Uuid::new_v4().to_string();
+                    tokio::spawn(run_send(ctx, body, send_id.clone()));
+                    (202, json!({ "send_id": send_id }))
+                }
+            }),
+            Err(e) => Err(e),
+        },
+        ("GET", ["send", send_id, "code"]) => match ctx.transfers().send_code(send_id).await {
+            Some(code) => Ok((200, json!({ "code": code }))),
+            // Queued, offline or still connecting: no code yet.
+            None if ctx
+                .transfers()
+                .snapshot()
+                .await
+                .sends
+                .iter()
+                .any(|id| id == send_id) =>
+            {
+                Ok((202, json!({ "code": null })))
+            }
+            None => Ok((404, json!({ "error": "No active send found for this ID" }))),
+        },
+        ("POST", ["send", send_id, "cancel"]) => Ok(result(
+            files::cancel_send(send_id.to_string(), ctx)
+                .await
+                .map(Value::from),
+        )),
+        ("POST", ["receive"]) => match parse_body::<ReceiveBody>(&request.body) {
+            Ok(body) => {
+                let connection_id = Uuid::new_v4().to_string();
+                Ok(result(
+                    files::request_file_call(ctx, &body.code, connection_id)
+                        .await
+                        .and_then(|offer| serde_json::to_value(offer).map_err(|e| e.to_string())),
+                ))
+            }
+            Err(e) => Err(e),
+        },
+        ("POST", ["offer", id, "accept"]) => {
+            let body = if request.body.is_empty() {
+                Ok(AcceptBody::default())
+            } else {
+                parse_body::<AcceptBody>(&request.body)
+            };
+            match body {
+                Ok(body) => Ok(accept_offer(ctx, id, body).await),
+                Err(e) => Err(e),
+            }
+        }
+        ("POST", ["offer", id, "deny"]) => Ok(result(
+            files::receiving_file_deny(id.to_string(), ctx, None)
+                .await
+                .map(Value::from),
+        )),
+        _ => Ok((404, json!({ "error": "Unknown endpoint" }))),
+    };
+    handled.unwrap_or_else(|e| e)
+}
+
+// Checks run before the send is started, so the client hears about them: paths must exist,
+// the settings file (which holds the API token) is refused, and sensitive selections need
+// `confirm_sensitive`.
+async fn check_send(ctx: &AppContext, body: &SendBody) -> Result<(), (u16, Value)> {
+    if body.paths.is_empty() {
+        return Err((400, json!({ "error": "No paths to send" })));
+    }
+    let settings_file = std::fs::canonicalize(ctx.paths().config_dir.join("settings.json")).ok();
+    for path in &body.paths {
+        if !Path::new(path).is_absolute() {
+            return Err((
+                400,
+                json!({ "error": format!("{} isn't an absolute path", path) }),
+            ));
+        }
+        let resolved = std::fs::canonicalize(path).map_err(|e| {
+            (
+                400,
+                json!({ "error": format!("Can't open {}: {}", path, e) }),
+            )
+        })?;
+        if settings_file
+            .as_ref()
+            .is_some_and(|file| file.starts_with(&resolved))
+        {
+            let error = format!("{} holds wyrmhole's settings and can't be sent", path);
+            return Err((403, json!({ "error": error })));
+        }
+    }
+    if !body.confirm_sensitive {
+        let found = sensitive::check_sensitive_paths(body.paths.clone())
+            .await
+            .map_err(|e| (400, json!({ "error": e })))?;
+        if !found.is_empty() {
+            let error = "This looks sensitive; send again with confirm_sensitive to go ahead";
+            return Err((409, json!({ "error": error, "sensitive": found })));
+        }
+    }
+    Ok(())
+}
+
+// The offer is looked up and its confirmations checked here, so a wrong id or a missing
+// confirmation is answered rather than lost in the background; the download itself runs on.
+async fn accept_offer(ctx: AppContext, id: &str, body: AcceptBody) -> (u16, Value) {
+    if !ctx
+        .transfers()
+        .snapshot()
+        .await
+        .offers
+        .iter()
+        .any(|offer| offer == id)
+    {
+        return (
+            404,
+            json!({ "error": "No pending offer found for this ID" }),
+        );
+    }
+    if let Err(refusal) = check_destination(&ctx, body.destination.as_deref()).await {
+        return refusal;
+    }
+    if let Some(refusal) =
+        files::unconfirmed(&ctx, id, body.confirm_oversize, body.confirm_content).await
+    {
+        return (409, json!({ "error": refusal }));
+    }
+    let id = id.to_string();
+    tokio::spawn(async move {
+        let _ = files::receiving_file_accept(
+            id,
+            ctx,
+            body.destination,
+            body.confirm_oversize,
+            body.confirm_content,
+        )
+        .await;
+    });
+    (202, json!({ "accepted": true }))
+}
+
+// Unlike sends, downloads could write anywhere the user can, the settings file included, so
+// a destination has to resolve inside the download directory.
+async fn check_destination(
+    ctx: &AppContext,
+    destination: Option<&str>,
+) -> Result<(), (u16, Value)> {
+    let Some(destination) =
+        files::check_destination(destination).map_err(|e| (400, json!({ "error": e })))?
+    else {
+        return Ok(());
+    };
+    if !destination.is_absolute() {
+        let error = format!("{} isn't an absolute path", destination.display());
+        return Err((400, json!({ "error": error })));
+    }
+    let download_dir = ctx.settings().lock().await.get_download_directory().clone();
+    let inside = std::fs::canonicalize(&download_dir)
+        .ok()
+        .zip(std::fs::canonicalize(&destination).ok())
+        .is_some_and(|(root, resolved)| resolved.starts_with(root));
+    if !inside {
+        let error = format!(
+            "{} isn't a folder inside the download directory",
+            destination.display()
+        );
+        return Err((403, json!({ "error": error })));
+    }
+    Ok(())
+}
+
+// A single path goes out as itself (a folder as a tarball); several as one bundle.
+async fn run_send(ctx: AppContext, body: SendBody, send_id: String) {
+    let outcome = if body.paths.len() == 1 && body.folder_name.is_none() {
+        files::send_file_call(ctx, &body.paths[0], send_id).await
+    } else {
+        files::send_multiple_files_call(ctx, body.paths, send_id, body.folder_name, None).await
+    };
+    if let Err(e) = outcome {
+        log_line!("[magic-wormhole][api][error] Send failed: {}", e);
+    }
+}
@@ -0,0 +1,164 @@
+// This file packages files and folders as a password-protected (AES-256) zip before sending,
+// for senders who want the contents protected beyond the wormhole's own encryption. The
+// password never goes through wyrmhole: the sender shares it out-of-band and the receiver
+// opens the zip with any AES-capable archive tool (7-Zip, Keka, `7z x`, ...).
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use tokio_util::sync::CancellationToken;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipWriter};
+
+use crate::context::AppContext;
+use crate::files::{self, CancellableWriter, SendOptions};
+use crate::special::{self, EntryKind, SkippedFile};
+
+// Entries are named `folder_name/...` like the tarballs, so the zip extracts into one folder.
+struct ArchiveWriter<'a> {
+    zip: ZipWriter<CancellableWriter<fs::File>>,
+    options: SimpleFileOptions,
+    password: &'a str,
+    skipped: &'a mut Vec<SkippedFile>,
+}
+
+impl ArchiveWriter<'_> {
+    fn add_file(&mut self, src: &Path, name: &str) -> Result<(), String> {
+        let options = self
+            .options
+            .with_aes_encryption(AesMode::Aes256, self.password);
+        self.zip
+            .start_file(name, options)
+            .map_err(|e| format!("Failed to add file to archive: {}", e))?;
+        let mut file =
+            fs::File::open(src).map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+        io::copy(&mut file, &mut self.zip)
+            .map_err(|e| format!("Failed to add file to archive: {}", e))?;
+        Ok(())
+    }
+
+    fn add_dir(&mut self, src: &Path, name: &str) -> Result<(), String> {
+        self.zip
+            .add_directory(name, self.options)
+            .map_err(|e| format!("Failed to add directory to archive: {}", e))?;
+        let entries =
+            fs::read_dir(src).map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+            let entry_name = format!("{}/{}", name, entry.file_name().to_string_lossy());
+            let path = entry.path();
+            let kind = special::classify(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            match kind {
+                EntryKind::Dir => self.add_dir(&path, &entry_name)?,
+                EntryKind::File(_) => self.add_file(&path, &entry_name)?,
+                EntryKind::Skip(reason) => self.skipped.push(SkippedFile::new(&path, reason)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write `paths` into an AES-256 encrypted zip at `output_path`, all under `folder_name/`.
+/// Returns the archive's size. Special files inside folders are left out and added to
+/// `skipped`. Stops once `cancel` is cancelled; a failed or cancelled archive is removed.
+pub fn create_encrypted_zip(
+    paths: &[String],
+    output_path: &Path,
+    folder_name: &str,
+    password: &str,
+    cancel: CancellationToken,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<u64, String> {
+    let result = build_encrypted_zip(
+        paths,
+        output_path,
+        folder_name,
+        password,
+        cancel.clone(),
+        skipped,
+    );
+    files::packaging_result(result, output_path, &cancel)
+}
+
+// Body of `create_encrypted_zip`.
+fn build_encrypted_zip(
+    paths: &[String],
+    output_path: &Path,
+    folder_name: &str,
+    password: &str,
+    cancel: CancellationToken,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<u64, String> {
+    let file = fs::File::create(output_path)
+        .map_err(|e| format!("Failed to create archive file: {}", e))?;
+    let mut writer = ArchiveWriter {
+        zip: ZipWriter::new(CancellableWriter::new(file, cancel)),
+        options: SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+        password,
+        skipped,
+    };
+
+    for file_path in paths {
+        let src_path = Path::new(file_path);
+        if !src_path.exists() {
+            return Err(format!("File or folder does not exist: {}", file_path));
+        }
+        let name = src_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "file".to_string());
+        let entry_name = format!("{}/{}", folder_name, name);
+        if src_path.is_dir() {
+            writer.add_dir(src_path, &entry_name)?;
+        } else {
+            writer.add_file(src_path, &entry_name)?;
+        }
+    }
+
+    let mut file = writer
+        .zip
+        .finish()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+    file.flush()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+
+    let size = fs::metadata(output_path)
+        .map_err(|e| format!("Failed to get archive metadata: {}", e))?
+        .len();
+    log_line!(
+        "[magic-wormhole][perf][archive] Created encrypted archive: {} bytes (folder: {})",
+        size,
+        folder_name
+    );
+    Ok(size)
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+/// Send `file_paths` (files and/or folders) as `<folder name>.zip`, encrypted with
+/// `password`. The folder name follows the same rules as `send_multiple_files_call`; like
+/// its tarball, the zip is packed as a job (see jobs.rs) while the code waits for the
+/// receiver, and the history lists `file_paths` rather than the temporary zip.
+pub async fn send_encrypted_archive_call(
+    ctx: AppContext,
+    file_paths: Vec<String>,
+    send_id: String,
+    folder_name: Option<String>,
+    password: String,
+) -> Result<String, String> {
+    if file_paths.is_empty() {
+        return Err("No files provided".to_string());
+    }
+    if password.is_empty() {
+        return Err("A password is required for an encrypted archive".to_string());
+    }
+    let options = SendOptions {
+        archive: true,
+        folder_name,
+        password: Some(password),
+        ..SendOptions::default()
+    };
+    files::queued_send(ctx, file_paths, send_id, options).await
+}
@@ -0,0 +1,177 @@
+// This file keeps recently packaged tarballs in a cache under the data directory, capped at
+// `package_cache_mib` (0 turns it off). Entries are keyed by a hash of what went into the
+// tarball: its name, how it was built, and the content hash of every selected file and folder.
+// Sending the same selection again, say to a second person, links the cached tarball into
+// place instead of packaging it again, so both receivers get the exact same bytes. The least
+// recently used tarballs are dropped to stay under the cap.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use specta::Type;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::context::AppContext;
+use crate::files::TarballOptions;
+use crate::files_json::FileSnapshot;
+use crate::verify;
+
+/// Directory under the data directory holding cached tarballs, one file per key.
+pub const CACHE_DIR: &str = "package-cache";
+
+/// What's in the cache, for the settings page.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct PackageCacheInfo {
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+pub(crate) struct PackageCache {
+    dir: PathBuf,
+    limit_bytes: u64,
+}
+
+fn cache_dir(ctx: &AppContext) -> PathBuf {
+    ctx.paths().data_dir.join(CACHE_DIR)
+}
+
+impl PackageCache {
+    /// The cache, unless `package_cache_mib` turns it off.
+    pub(crate) async fn from_settings(ctx: &AppContext) -> Option<PackageCache> {
+        let limit_mib = ctx.settings().lock().await.get_package_cache_mib();
+        (limit_mib > 0).then(|| PackageCache {
+            dir: cache_dir(ctx),
+            limit_bytes: limit_mib * 1024 * 1024,
+        })
+    }
+
+    /// Put the tarball for `sources` at `output`: the cached one when there is one,
+    /// otherwise whatever `build` packages, keeping a copy for next time. Returns its size.
+    pub(crate) fn build_cached(
+        &self,
+        sources: &[PathBuf],
+        name: &str,
+        options: TarballOptions,
+        output: &Path,
+        build: impl FnOnce() -> Result<u64, String>,
+    ) -> Result<u64, String> {
+        // A selection that can't be fully hashed just isn't cached
+        let Some(key) = cache_key(sources, name, options) else {
+            return build();
+        };
+        let cached = self.dir.join(&key);
+        if cached.is_file() && link_or_copy(&cached, output).is_ok() {
+            // Touched, so eviction sees it as recently used
+            let _ = fs::File::options()
+                .write(true)
+                .open(&cached)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+            log_line!(
+                "[magic-wormhole][cache][info] Reusing the packaged tarball for {}",
+                name
+            );
+            return fs::metadata(output)
+                .map(|metadata| metadata.len())
+                .map_err(|e| format!("Failed to read cached tarball: {}", e));
+        }
+
+        let size = build()?;
+        if size <= self.limit_bytes {
+            let stored = fs::create_dir_all(&self.dir).and_then(|_| link_or_copy(output, &cached));
+            if let Err(e) = stored {
+                log_line!(
+                    "[magic-wormhole][cache][warn] Failed to cache the tarball for {}: {}",
+                    name,
+                    e
+                );
+            }
+            trim(&self.dir, self.limit_bytes);
+        }
+        Ok(size)
+    }
+}
+
+// SHA-256 over the tarball's name, build options, and each source's name and content hash.
+fn cache_key(sources: &[PathBuf], name: &str, options: TarballOptions) -> Option<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(name.as_bytes());
+    hasher.update([options.deterministic as u8, options.compression_level as u8]);
+    for source in sources {
+        let file_name = source.file_name()?.to_string_lossy().into_owned();
+        let content = FileSnapshot::take(source).sha256?;
+        hasher.update(file_name.as_bytes());
+        hasher.update([0]);
+        hasher.update(content.as_bytes());
+    }
+    Some(verify::hex(hasher))
+}
+
+// A hard link where the filesystem allows (the cache and temp dir are often on the same
+// disk), a copy where it doesn't.
+fn link_or_copy(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::hard_link(from, to).or_else(|_| fs::copy(from, to).map(|_| ()))
+}
+
+fn entries(dir: &Path) -> Vec<(PathBuf, u64, SystemTime)> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let metadata = entry.metadata().ok()?;
+                    metadata.is_file().then(|| {
+                        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                        (entry.path(), metadata.len(), modified)
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Remove the least recently used tarballs until the cache fits in `limit_bytes`.
+fn trim(dir: &Path, limit_bytes: u64) {
+    let mut entries = entries(dir);
+    entries.sort_by_key(|(_, _, modified)| *modified);
+    let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    for (path, size, _) in entries {
+        if total <= limit_bytes {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+}
+
+pub async fn package_cache_info(ctx: &AppContext) -> Result<PackageCacheInfo, String> {
+    let dir = cache_dir(ctx);
+    tokio::task::spawn_blocking(move || {
+        let entries = entries(&dir);
+        PackageCacheInfo {
+            entries: entries.len() as u64,
+            bytes: entries.iter().map(|(_, size, _)| size).sum(),
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to read the package cache: {}", e))
+}
+
+pub async fn clear_package_cache(ctx: &AppContext) -> Result<(), String> {
+    let dir = cache_dir(ctx);
+    tokio::task::spawn_blocking(move || match fs::remove_dir_all(&dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to clear the package cache: {}", e))
+        }
+        _ => Ok(()),
+    })
+    .await
+    .map_err(|e| format!("Failed to clear the package cache: {}", e))?
+}
+
+/// Drop tarballs until the cache fits a new `package_cache_mib` (everything, for 0).
+pub(crate) async fn trim_to(ctx: &AppContext, limit_mib: u64) {
+    let dir = cache_dir(ctx);
+    let _ = tokio::task::spawn_blocking(move || trim(&dir, limit_mib * 1024 * 1024)).await;
+}
@@ -0,0 +1,226 @@
+// This file flags received content that can run code: executables, scripts, and archives
+// holding either. A wormhole code says nothing about who is on the other end, so every
+// sender counts as unknown. Offers are flagged by name before they're accepted (accepting
+// one then needs `confirm_content`), and once the bytes are on disk the receive pipeline
+// sniffs them too, catching a program saved under an innocent name or tucked inside an
+// archive; such a file is kept as it was saved rather than extracted or handed to the After
+// Receiving rules. An archive that can't be checked through (too many entries, or ones that
+// can't be read) is flagged the same way, as it could hide anything past that point.
+// Extensions in the `content_allowlist` setting never warn, and "*" turns the checks off
+// for users who send binaries on purpose.
+
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tar::Archive;
+
+use crate::extract::{self, ArchiveFormat};
+
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "msi", "dll", "com", "scr", "cpl", "app", "dmg", "pkg", "deb", "rpm", "apk", "appimage",
+    "jar",
+];
+const SCRIPT_EXTENSIONS: &[&str] = &[
+    "sh", "bash", "zsh", "command", "ps1", "psm1", "bat", "cmd", "vbs", "vbe", "js", "jse", "wsf",
+    "hta", "lnk", "reg",
+];
+
+// Archive entries looked at before giving up, so a huge archive can't stall the receive. An
+// archive with more is flagged as unchecked.
+const MAX_SCANNED_ENTRIES: usize = 10_000;
+
+/// Why a received file needs a second look.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContentWarning {
+    /// A program: by extension, or an ELF, PE or Mach-O header.
+    Executable,
+    /// A script, by extension or a `#!` line.
+    Script,
+    /// An archive with a program or script among its entries.
+    ArchiveWithExecutable,
+    /// An archive with too many entries to check, or entries that couldn't be read.
+    UncheckedArchive,
+}
+
+impl ContentWarning {
+    pub fn message(self) -> &'static str {
+        match self {
+            ContentWarning::Executable => "a program",
+            ContentWarning::Script => "a script",
+            ContentWarning::ArchiveWithExecutable => "an archive with programs or scripts in it",
+            ContentWarning::UncheckedArchive => "an archive too large or damaged to check",
+        }
+    }
+}
+
+/// Whether `name`'s extension is in `allowlist` (case-insensitive, leading dots ignored), or
+/// the allowlist holds "*".
+pub fn allowed(name: &str, allowlist: &[String]) -> bool {
+    let extension = extension(name);
+    allowlist.iter().any(|entry| {
+        let entry = entry.trim().trim_start_matches('.');
+        entry == "*" || (!extension.is_empty() && entry.eq_ignore_ascii_case(&extension))
+    })
+}
+
+/// The warning an offer named `name` gets before it's accepted, from its extension.
+pub fn warning_for_name(name: &str, allowlist: &[String]) -> Option<ContentWarning> {
+    if allowed(name, allowlist) {
+        return None;
+    }
+    name_warning(name)
+}
+
+/// The warning the file at `path` gets from its contents: its first bytes, for a tar (plain,
+/// gzip or zstd) or zip archive each entry's name, mode and first bytes, and for a single
+/// gzipped file the name it unpacks under and its first bytes once decompressed.
+pub fn sniff(path: &Path, allowlist: &[String]) -> Option<ContentWarning> {
+    let name = path.file_name()?.to_string_lossy();
+    if allowed(&name, allowlist) {
+        return None;
+    }
+    let file = File::open(path).ok()?;
+    if let Some(warning) = magic_warning(file) {
+        return Some(warning);
+    }
+    match extract::detect(path)? {
+        ArchiveFormat::Tar => scan_tar(File::open(path).ok()?, allowlist),
+        ArchiveFormat::TarGz => scan_tar(GzDecoder::new(File::open(path).ok()?), allowlist),
+        ArchiveFormat::TarZst => match zstd::stream::read::Decoder::new(File::open(path).ok()?) {
+            Ok(decoder) => scan_tar(decoder, allowlist),
+            Err(_) => Some(ContentWarning::UncheckedArchive),
+        },
+        ArchiveFormat::Zip => scan_zip(path, allowlist),
+        ArchiveFormat::Gzip => {
+            let unpacked = extract::gunzipped_name(path);
+            if allowed(&unpacked, allowlist) {
+                return None;
+            }
+            name_warning(&unpacked)
+                .or_else(|| magic_warning(GzDecoder::new(File::open(path).ok()?)))
+        }
+        ArchiveFormat::SevenZip => None,
+    }
+}
+
+fn extension(name: &str) -> String {
+    Path::new(name)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+fn name_warning(name: &str) -> Option<ContentWarning> {
+    let extension = extension(name);
+    if EXECUTABLE_EXTENSIONS.contains(&extension.as_str()) {
+        Some(ContentWarning::Executable)
+    } else if SCRIPT_EXTENSIONS.contains(&extension.as_str()) {
+        Some(ContentWarning::Script)
+    } else {
+        None
+    }
+}
+
+// ELF, PE ("MZ"), Mach-O (32/64-bit, either byte order, and universal) and `#!` scripts.
+fn magic_warning(mut reader: impl Read) -> Option<ContentWarning> {
+    let mut magic = [0u8; 4];
+    let mut read = 0;
+    while read < magic.len() {
+        match reader.read(&mut magic[read..]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => read += n,
+        }
+    }
+    let magic = &magic[..read];
+    if magic.starts_with(b"#!") {
+        return Some(ContentWarning::Script);
+    }
+    let executable = magic.starts_with(b"\x7fELF")
+        || magic.starts_with(b"MZ")
+        || [
+            b"\xfe\xed\xfa\xce",
+            b"\xfe\xed\xfa\xcf",
+            b"\xce\xfa\xed\xfe",
+            b"\xcf\xfa\xed\xfe",
+            b"\xca\xfe\xba\xbe",
+        ]
+        .iter()
+        .any(|mach_o| magic == *mach_o);
+    executable.then_some(ContentWarning::Executable)
+}
+
+// Whether an archive entry named `name` with unix `mode` is a program or script. Entries
+// whose extension is allowlisted don't count.
+fn entry_flagged(name: &str, mode: Option<u32>, head: impl Read, allowlist: &[String]) -> bool {
+    if allowed(name, allowlist) {
+        return false;
+    }
+    name_warning(name).is_some()
+        || mode.is_some_and(|mode| mode & 0o111 != 0)
+        || magic_warning(head).is_some()
+}
+
+// `ArchiveWithExecutable` for a tarball holding a program or script, `UncheckedArchive` if
+// it couldn't be read to the end within `MAX_SCANNED_ENTRIES`.
+fn scan_tar(reader: impl Read, allowlist: &[String]) -> Option<ContentWarning> {
+    let mut archive = Archive::new(reader);
+    let Ok(entries) = archive.entries() else {
+        return Some(ContentWarning::UncheckedArchive);
+    };
+    for (index, entry) in entries.enumerate() {
+        if index == MAX_SCANNED_ENTRIES {
+            return Some(ContentWarning::UncheckedArchive);
+        }
+        let Ok(mut entry) = entry else {
+            return Some(ContentWarning::UncheckedArchive);
+        };
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry
+            .path()
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mode = entry.header().mode().ok();
+        if entry_flagged(&name, mode, &mut entry, allowlist) {
+            return Some(ContentWarning::ArchiveWithExecutable);
+        }
+    }
+    None
+}
+
+// `scan_tar` for zip archives.
+fn scan_zip(path: &Path, allowlist: &[String]) -> Option<ContentWarning> {
+    let Some(mut zip) = File::open(path)
+        .ok()
+        .and_then(|file| zip::ZipArchive::new(file).ok())
+    else {
+        return Some(ContentWarning::UncheckedArchive);
+    };
+    if zip.len() > MAX_SCANNED_ENTRIES {
+        return Some(ContentWarning::UncheckedArchive);
+    }
+    for i in 0..zip.len() {
+        let Ok(entry) = zip.by_index_raw(i) else {
+            return Some(ContentWarning::UncheckedArchive);
+        };
+        if entry.is_dir() {
+            continue;
+        }
+        let (name, mode) = (entry.name().to_string(), entry.unix_mode());
+        drop(entry);
+        // Encrypted entries can't be read, so only their names and modes are checked
+        let flagged = match zip.by_index(i) {
+            Ok(mut entry) => entry_flagged(&name, mode, &mut entry, allowlist),
+            Err(_) => entry_flagged(&name, mode, std::io::empty(), allowlist),
+        };
+        if flagged {
+            return Some(ContentWarning::ArchiveWithExecutable);
+        }
+    }
+    None
+}
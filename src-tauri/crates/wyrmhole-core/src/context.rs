@@ -0,0 +1,181 @@
+// This file defines the runtime context shared by the transfer, settings and history modules.
+// Hosts (the Tauri app, headless mode, the CLI) plug in an event sink and a settings store,
+// so the engine never depends on a particular frontend.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+
+use crate::api::ApiServer;
+use crate::extract::{ArchiveFormat, Extractor};
+use crate::files_json;
+use crate::profiles;
+use crate::receive::ReceiveStep;
+use crate::settings::{AppSettings, JsonSettingsStore, SettingsStore};
+use crate::transfers::TransferManager;
+use crate::wormhole::{MagicWormhole, WormholeBackend};
+
+// Bundle identifier from tauri.conf.json. Tauri derives its per-app config and data
+// directories from it, so standalone hosts (headless mode, the CLI) resolve the same locations.
+pub const APP_IDENTIFIER: &str = "com.wyrmhole";
+
+/// Receives every event emitted by the engine (progress, errors, history updates).
+pub trait EventSink: Send + Sync {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) -> Result<(), String>;
+}
+
+/// OS locations the engine reads and writes.
+#[derive(Debug, Clone)]
+pub struct AppPaths {
+    pub config_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub default_download_dir: PathBuf,
+}
+
+impl AppPaths {
+    /// Resolve the same directories Tauri would, without needing a running app.
+    pub fn from_os() -> Self {
+        let base = |dir: Option<PathBuf>| {
+            dir.map(|d| d.join(APP_IDENTIFIER)).unwrap_or_else(|| {
+                log_line!("[magic-wormhole][context][error] Could not resolve an OS directory");
+                PathBuf::from(".")
+            })
+        };
+        AppPaths {
+            config_dir: base(dirs::config_dir()),
+            data_dir: base(dirs::data_dir()),
+            default_download_dir: dirs::download_dir().unwrap_or_else(|| PathBuf::from(".")),
+        }
+    }
+}
+
+/// Cheaply cloneable handle to the event sink, settings and paths.
+#[derive(Clone)]
+pub struct AppContext {
+    events: Arc<dyn EventSink>,
+    settings: Arc<Mutex<AppSettings>>,
+    store: Arc<dyn SettingsStore>,
+    paths: Arc<AppPaths>,
+    // Sync mirror of the `history_directory` setting (resolved), read by the history
+    // writers, which aren't async.
+    history_dir: Arc<RwLock<PathBuf>>,
+    wormhole: Arc<dyn WormholeBackend>,
+    receive_steps: Arc<Vec<Arc<dyn ReceiveStep>>>,
+    extractors: Arc<Vec<(ArchiveFormat, Arc<dyn Extractor>)>>,
+    transfers: Arc<TransferManager>,
+    api_server: Arc<ApiServer>,
+}
+
+impl AppContext {
+    /// `settings` is the initial state, normally `store.load()`; hosts that need to read
+    /// it before building the context (e.g. for window setup) load it themselves.
+    pub fn new(
+        events: Arc<dyn EventSink>,
+        paths: AppPaths,
+        store: Arc<dyn SettingsStore>,
+        settings: AppSettings,
+    ) -> Self {
+        let history_dir = settings
+            .get_history_directory()
+            .cloned()
+            .unwrap_or_else(|| paths.data_dir.clone());
+        AppContext {
+            events,
+            settings: Arc::new(Mutex::new(settings)),
+            store,
+            paths: Arc::new(paths),
+            history_dir: Arc::new(RwLock::new(history_dir)),
+            wormhole: Arc::new(MagicWormhole::default()),
+            receive_steps: Arc::default(),
+            extractors: Arc::default(),
+            transfers: Arc::default(),
+            api_server: Arc::default(),
+        }
+    }
+
+    /// Replace the wormhole layer (e.g. with a fake peer in tests).
+    pub fn with_wormhole(mut self, wormhole: Arc<dyn WormholeBackend>) -> Self {
+        self.wormhole = wormhole;
+        self
+    }
+
+    /// Add a step to the receive pipeline (see receive.rs), run after the built-in ones and
+    /// before the user's After Receiving rules.
+    pub fn with_receive_step(mut self, step: Arc<dyn ReceiveStep>) -> Self {
+        Arc::make_mut(&mut self.receive_steps).push(step);
+        self
+    }
+
+    /// Unpack received `format` archives with `extractor` (see extract.rs), replacing the
+    /// built-in one if there is one.
+    pub fn with_extractor(mut self, format: ArchiveFormat, extractor: Arc<dyn Extractor>) -> Self {
+        Arc::make_mut(&mut self.extractors).push((format, extractor));
+        self
+    }
+
+    /// Context for running outside the Tauri app (headless mode, the CLI): OS paths for
+    /// the active profile, settings loaded from the same settings.json the GUI uses, and
+    /// shared history.
+    pub fn standalone(events: Arc<dyn EventSink>) -> Self {
+        let paths = profiles::resolve_paths(&AppPaths::from_os());
+        let store = Arc::new(JsonSettingsStore::new(paths.clone()));
+        let app_settings = store.load();
+        let ctx = AppContext::new(events, paths, store, app_settings);
+        files_json::init_received_files(&ctx);
+        files_json::init_sent_files(&ctx);
+        ctx
+    }
+
+    /// Serialize `payload` and forward it to the attached frontend.
+    pub fn emit<S: Serialize>(&self, event: &str, payload: S) -> Result<(), String> {
+        let value = serde_json::to_value(payload).map_err(|e| e.to_string())?;
+        self.events.emit_value(event, value)
+    }
+
+    pub fn settings(&self) -> &Mutex<AppSettings> {
+        &self.settings
+    }
+
+    /// Persist `settings` through the configured store.
+    pub fn save_settings(&self, settings: &AppSettings) -> Result<(), String> {
+        self.store.save(settings)
+    }
+
+    pub fn paths(&self) -> &AppPaths {
+        &self.paths
+    }
+
+    /// Directory holding received_files.json and sent_files.json.
+    pub fn history_dir(&self) -> PathBuf {
+        self.history_dir
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub(crate) fn set_history_dir(&self, dir: PathBuf) {
+        *self.history_dir.write().unwrap_or_else(|e| e.into_inner()) = dir;
+    }
+
+    pub fn wormhole(&self) -> &dyn WormholeBackend {
+        self.wormhole.as_ref()
+    }
+
+    /// Offers, sends, downloads and connections in flight.
+    pub fn transfers(&self) -> &TransferManager {
+        &self.transfers
+    }
+
+    pub(crate) fn api_server(&self) -> &ApiServer {
+        &self.api_server
+    }
+
+    pub(crate) fn receive_steps(&self) -> &[Arc<dyn ReceiveStep>] {
+        &self.receive_steps
+    }
+
+    pub(crate) fn extractors(&self) -> &[(ArchiveFormat, Arc<dyn Extractor>)] {
+        &self.extractors
+    }
+}
@@ -0,0 +1,143 @@
+// This file implements opt-in crash reporting. A panic hook writes a plain-text report
+// (app version, platform, panic message, backtrace, recent log lines) into the app data
+// directory; on the next launch the host lists pending reports and offers to export them.
+// Nothing is sent anywhere. Reports are only written while `crash_reports_enabled` is on.
+
+use chrono::prelude::*;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::context::{AppContext, AppPaths};
+
+// How many recent log lines a crash report includes.
+const RECENT_LOG_CAPACITY: usize = 200;
+
+static RECENT_LOGS: Lazy<Mutex<VecDeque<String>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(RECENT_LOG_CAPACITY)));
+
+// Mirrors the setting so the (sync) panic hook can check it without the settings lock.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// A crash report waiting in the crash directory.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CrashReport {
+    pub file_name: String,
+    pub created: DateTime<Local>,
+}
+
+/// Keep `line` for the next crash report. Called by `log_line!`.
+pub fn remember_log_line(line: String) {
+    // A panic while logging must not poison crash reporting.
+    let mut logs = RECENT_LOGS.lock().unwrap_or_else(|e| e.into_inner());
+    if logs.len() == RECENT_LOG_CAPACITY {
+        logs.pop_front();
+    }
+    logs.push_back(line);
+}
+
+pub fn set_enabled(value: bool) {
+    ENABLED.store(value, Ordering::Relaxed);
+}
+
+pub fn get_crash_dir(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join("crashes")
+}
+
+/// Install the panic hook. `app_version` is the host's version, recorded in each report.
+/// The previous hook still runs afterwards, so panics are printed as usual.
+pub fn install_panic_hook(paths: &AppPaths, app_version: &'static str) {
+    let crash_dir = get_crash_dir(paths);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if ENABLED.load(Ordering::Relaxed) {
+            let now = Local::now();
+            let thread = std::thread::current();
+            // try_lock: the panic may have happened while this thread held the buffer.
+            let logs = match RECENT_LOGS.try_lock() {
+                Ok(logs) => logs.iter().cloned().collect::<Vec<_>>().join("\n"),
+                Err(_) => "(unavailable)".to_string(),
+            };
+            let report = format!(
+                "wyrmhole crash report\n\
+                 version: {}\n\
+                 time: {}\n\
+                 platform: {} {}\n\
+                 thread: {}\n\n\
+                 {}\n\n\
+                 backtrace:\n{}\n\n\
+                 recent log lines:\n{}\n",
+                app_version,
+                now.to_rfc3339(),
+                std::env::consts::OS,
+                std::env::consts::ARCH,
+                thread.name().unwrap_or("<unnamed>"),
+                info,
+                std::backtrace::Backtrace::force_capture(),
+                logs,
+            );
+
+            let path = crash_dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S%.3f")));
+            if fs::create_dir_all(&crash_dir).is_ok() && fs::write(&path, report).is_ok() {
+                eprintln!(
+                    "[magic-wormhole][crash][error] Crash report written to {}",
+                    path.display()
+                );
+            }
+        }
+        previous(info);
+    }));
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+pub async fn list_crash_reports(ctx: &AppContext) -> Result<Vec<CrashReport>, String> {
+    let crash_dir = get_crash_dir(ctx.paths());
+    if !crash_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries =
+        fs::read_dir(&crash_dir).map_err(|e| format!("Failed to read crash reports: {}", e))?;
+    let mut reports: Vec<CrashReport> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with(".txt"))
+        .map(|entry| CrashReport {
+            file_name: entry.file_name().to_string_lossy().into_owned(),
+            created: entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .map(DateTime::<Local>::from)
+                .unwrap_or_else(|_| Local::now()),
+        })
+        .collect();
+    reports.sort_by_key(|r| r.created);
+    Ok(reports)
+}
+
+pub async fn export_crash_report(
+    ctx: &AppContext,
+    file_name: String,
+    file_path: String,
+) -> Result<(), String> {
+    // Only plain names from `list_crash_reports`; never a path out of the crash directory.
+    if file_name.contains(['/', '\\']) || file_name.contains("..") {
+        return Err("Invalid crash report name".to_string());
+    }
+    let report = get_crash_dir(ctx.paths()).join(&file_name);
+    fs::copy(&report, &file_path).map_err(|e| format!("Failed to export crash report: {}", e))?;
+    Ok(())
+}
+
+pub async fn clear_crash_reports(ctx: &AppContext) -> Result<(), String> {
+    let crash_dir = get_crash_dir(ctx.paths());
+    if crash_dir.exists() {
+        fs::remove_dir_all(&crash_dir)
+            .map_err(|e| format!("Failed to clear crash reports: {}", e))?;
+    }
+    Ok(())
+}
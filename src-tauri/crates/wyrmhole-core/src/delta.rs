@@ -0,0 +1,331 @@
+// This file implements delta sends for large files that change a little between sends (VM
+// images, database dumps). Each send records the file's block hashes; the next one sends
+// only the blocks that differ, as `<name>.wyrmdelta`, and a wyrmhole receiver with
+// `apply_file_deltas` on patches its earlier copy in place.
+//
+// magic-wormhole's file transfer has no side channel to ask the receiver for its hashes, so
+// the sender diffs against what it sent last time instead. The delta names the hash of that
+// copy, and the receiver refuses to patch a file that doesn't match. Blocks are fixed-size,
+// which suits in-place edits; an insertion near the start shifts everything after it.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::context::AppContext;
+use crate::events;
+use crate::files;
+
+/// File name suffix marking a file as a delta against an earlier copy.
+pub const DELTA_SUFFIX: &str = ".wyrmdelta";
+
+pub const BLOCK_SIZE: u64 = 1024 * 1024;
+
+/// What a file looked like when it was last sent: its size and the SHA-256 (hex) of the
+/// whole file and of each `BLOCK_SIZE` block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Signature {
+    pub size: u64,
+    pub file_hash: String,
+    pub blocks: Vec<String>,
+}
+
+impl Signature {
+    pub fn build(path: &Path) -> Result<Signature, String> {
+        let read_error = |e: io::Error| format!("Failed to read {}: {}", path.display(), e);
+        let mut file = fs::File::open(path).map_err(read_error)?;
+        let mut whole = Sha256::new();
+        let mut blocks = Vec::new();
+        let mut size = 0;
+        let mut buffer = vec![0; BLOCK_SIZE as usize];
+        loop {
+            let len = read_block(&mut file, &mut buffer).map_err(read_error)?;
+            if len == 0 {
+                break;
+            }
+            whole.update(&buffer[..len]);
+            blocks.push(format!("{:x}", Sha256::digest(&buffer[..len])));
+            size += len as u64;
+        }
+        Ok(Signature {
+            size,
+            file_hash: format!("{:x}", whole.finalize()),
+            blocks,
+        })
+    }
+
+    /// Indices of the blocks in `self` that differ from (or are missing in) `previous`.
+    pub fn changed_blocks(&self, previous: &Signature) -> Vec<u64> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter(|(i, hash)| previous.blocks.get(*i) != Some(*hash))
+            .map(|(i, _)| i as u64)
+            .collect()
+    }
+}
+
+// Fill `buffer` as far as the file allows; short only at the end of the file.
+fn read_block(file: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buffer.len() {
+        match file.read(&mut buffer[len..])? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(len)
+}
+
+/// The first line of a delta file, as JSON; the changed blocks' bytes follow in order.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeltaHeader {
+    /// SHA-256 of the copy the delta applies to.
+    pub base_hash: String,
+    pub target_size: u64,
+    pub target_hash: String,
+    pub block_size: u64,
+    pub changed: Vec<u64>,
+}
+
+/// Write a delta turning the file `previous` describes into `path` (described by `current`).
+pub fn create_delta(
+    path: &Path,
+    previous: &Signature,
+    current: &Signature,
+    output_path: &Path,
+) -> Result<u64, String> {
+    let header = DeltaHeader {
+        base_hash: previous.file_hash.clone(),
+        target_size: current.size,
+        target_hash: current.file_hash.clone(),
+        block_size: BLOCK_SIZE,
+        changed: current.changed_blocks(previous),
+    };
+    let write_error = |e: io::Error| format!("Failed to write delta: {}", e);
+    let mut source =
+        fs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut output = io::BufWriter::new(fs::File::create(output_path).map_err(write_error)?);
+    let json = serde_json::to_string(&header).map_err(|e| e.to_string())?;
+    writeln!(output, "{}", json).map_err(write_error)?;
+    let mut buffer = vec![0; BLOCK_SIZE as usize];
+    for index in &header.changed {
+        source
+            .seek(SeekFrom::Start(index * BLOCK_SIZE))
+            .map_err(write_error)?;
+        let len = read_block(&mut source, &mut buffer).map_err(write_error)?;
+        output.write_all(&buffer[..len]).map_err(write_error)?;
+    }
+    output.flush().map_err(write_error)?;
+    let size = fs::metadata(output_path).map_err(write_error)?.len();
+    Ok(size)
+}
+
+/// Patch `base_path` in place with the delta at `delta_path`, returning the new size. The
+/// base is left untouched unless it's the copy the delta was made from and the result
+/// matches the sender's hash. Blocking; call from `spawn_blocking`.
+pub fn apply_delta(delta_path: &Path, base_path: &Path) -> Result<u64, String> {
+    let read_error = |e: io::Error| format!("Failed to read delta: {}", e);
+    let mut delta = BufReader::new(fs::File::open(delta_path).map_err(read_error)?);
+    let mut line = String::new();
+    delta.read_line(&mut line).map_err(read_error)?;
+    let header: DeltaHeader =
+        serde_json::from_str(&line).map_err(|e| format!("Not a wyrmhole delta: {}", e))?;
+    let base_name = base_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if !base_path.is_file() {
+        return Err(format!(
+            "{} isn't in the download folder; ask for the full file",
+            base_name
+        ));
+    }
+    let base_hash = Signature::build(base_path)?.file_hash;
+    if base_hash != header.base_hash {
+        return Err(format!(
+            "{} isn't the copy this update was made from; ask for the full file",
+            base_name
+        ));
+    }
+    if header.block_size == 0 {
+        return Err("Not a wyrmhole delta: block size is 0".to_string());
+    }
+
+    let temp_path = base_path.with_file_name(format!(".{}.{}.part", base_name, Uuid::new_v4()));
+    let written = write_patched(&mut delta, base_path, &header, &temp_path);
+    match written {
+        Ok(hash) if hash == header.target_hash => {
+            fs::rename(&temp_path, base_path)
+                .map_err(|e| format!("Failed to replace {}: {}", base_name, e))?;
+            Ok(header.target_size)
+        }
+        Ok(_) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(format!("{} didn't match after the update", base_name))
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(e)
+        }
+    }
+}
+
+// Write the patched file to `output_path`, returning its SHA-256.
+fn write_patched(
+    delta: &mut impl Read,
+    base_path: &Path,
+    header: &DeltaHeader,
+    output_path: &Path,
+) -> Result<String, String> {
+    let write_error = |e: io::Error| format!("Failed to apply delta: {}", e);
+    let mut base = fs::File::open(base_path).map_err(write_error)?;
+    let mut output = io::BufWriter::new(fs::File::create(output_path).map_err(write_error)?);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; header.block_size as usize];
+    let block_count = header.target_size.div_ceil(header.block_size);
+    let mut changed = header.changed.iter().peekable();
+    for index in 0..block_count {
+        let offset = index * header.block_size;
+        let len = header.block_size.min(header.target_size - offset) as usize;
+        let block = &mut buffer[..len];
+        if changed.next_if_eq(&&index).is_some() {
+            delta.read_exact(block).map_err(write_error)?;
+        } else {
+            base.seek(SeekFrom::Start(offset)).map_err(write_error)?;
+            base.read_exact(block).map_err(write_error)?;
+        }
+        hasher.update(&*block);
+        output.write_all(block).map_err(write_error)?;
+    }
+    output.flush().map_err(write_error)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Signatures live in the data directory, named by a hash of the file's absolute path.
+fn signature_path(ctx: &AppContext, path: &Path) -> PathBuf {
+    let key = Sha256::digest(path.to_string_lossy().as_bytes());
+    ctx.paths()
+        .data_dir
+        .join("delta_signatures")
+        .join(format!("{:x}.json", key))
+}
+
+fn load_signature(path: &Path) -> Option<Signature> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn save_signature(path: &Path, signature: &Signature) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to save file signature: {}", e))?;
+    }
+    let json = serde_json::to_string(signature)
+        .map_err(|e| format!("Failed to save file signature: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to save file signature: {}", e))
+}
+
+/// Forget what was sent from `file_path`, so its next send is a full one.
+pub fn forget_signature(ctx: &AppContext, file_path: &Path) -> io::Result<()> {
+    match fs::remove_file(signature_path(ctx, file_path)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Send the file at `file_path` in full the first time, then as a delta against what was
+/// last sent. The signature is only updated once the send succeeds. Called through
+/// `sync::send_changes_call`.
+pub async fn send_file_changes(
+    ctx: AppContext,
+    file_path: &Path,
+    send_id: String,
+) -> Result<String, String> {
+    let file_name = file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "file".to_string());
+    let delta_name = format!("{}{}", file_name, DELTA_SUFFIX);
+    let _ = ctx.emit(
+        events::SEND_PROGRESS,
+        events::SendProgress {
+            id: send_id.clone(),
+            file_name: file_name.clone(),
+            sent: 0,
+            total: 0,
+            percentage: 0,
+            code: String::new(),
+            status: events::TransferState::Packaging,
+        },
+    );
+
+    let signature_path = signature_path(&ctx, file_path);
+    let temp_dir = std::env::temp_dir().join(format!("wyrmhole_delta_{}", Uuid::new_v4()));
+    let delta_path = temp_dir.join(&delta_name);
+    // `None` for the delta when there's nothing to diff against and the file goes in full.
+    let prepared = tokio::task::spawn_blocking({
+        let file_path = file_path.to_path_buf();
+        let signature_path = signature_path.clone();
+        let temp_dir = temp_dir.clone();
+        let delta_path = delta_path.clone();
+        move || {
+            let current = Signature::build(&file_path)?;
+            let Some(previous) = load_signature(&signature_path) else {
+                return Ok((current, None));
+            };
+            if previous == current {
+                return Err(format!("{} hasn't changed since the last send", file_name));
+            }
+            fs::create_dir_all(&temp_dir)
+                .map_err(|e| format!("Failed to create delta folder: {}", e))?;
+            let size = create_delta(&file_path, &previous, &current, &delta_path)?;
+            Ok((current, Some(size)))
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to create delta: {}", e))
+    .and_then(|result| result);
+
+    let result = match prepared {
+        Ok((signature, delta_size)) => {
+            let send_path = match delta_size {
+                Some(size) => {
+                    log_line!(
+                        "[magic-wormhole][delta][info] Sending {} as a {} byte delta",
+                        file_path.display(),
+                        size
+                    );
+                    delta_path
+                }
+                None => file_path.to_path_buf(),
+            };
+            let sent =
+                files::send_file_call(ctx.clone(), &send_path.to_string_lossy(), send_id).await;
+            if sent.is_ok()
+                && let Err(e) = save_signature(&signature_path, &signature)
+            {
+                log_line!("[magic-wormhole][delta][error] {}", e);
+            }
+            sent
+        }
+        Err(error) => {
+            log_line!("[magic-wormhole][delta][error] {}", error);
+            let _ = ctx.emit(
+                events::SEND_ERROR,
+                events::SendError {
+                    id: send_id,
+                    file_name: delta_name,
+                    error: error.clone(),
+                    code: None,
+                },
+            );
+            Err(error)
+        }
+    };
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    result
+}
@@ -0,0 +1,308 @@
+// This file implements demo mode: a simulated wormhole layer for UI development. It hands out
+// plausible codes, "connects" after a short wait, and streams progress at a believable pace,
+// so the frontend can be exercised on one machine without a rendezvous server. The engine
+// runs unchanged on top of it, so history entries and events are real.
+//
+// Scripted outcomes:
+//   - every 4th send and every 4th receive fail partway through the transfer
+//   - receive codes containing "fail" fail to connect
+//   - receive codes containing "empty" connect but offer nothing
+//   - receive codes containing "text" offer a text message
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use futures::future::{BoxFuture, Either};
+use futures::{AsyncRead, AsyncWrite, AsyncWriteExt, FutureExt};
+use magic_wormhole::Code;
+use magic_wormhole::transit::{Abilities, RelayHint};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::context::AppPaths;
+use crate::wormhole::{
+    CancelFuture, IncomingOffer, PeerConnection, PeerError, PeerOffer, ProgressHandler,
+    SendMailbox, TransitDetails, TransitHandler, WormholeBackend,
+};
+
+// Progress is reported in this many steps, one per tick.
+const STEPS: u64 = 40;
+const TICK: Duration = Duration::from_millis(100);
+
+const WORDS: &[&str] = &[
+    "guitarist",
+    "revenge",
+    "tumbler",
+    "crossover",
+    "uncut",
+    "eyetooth",
+    "snowcap",
+    "adrift",
+    "bedlamp",
+    "ratchet",
+    "sawdust",
+    "tactics",
+    "upshot",
+    "woodlark",
+    "beehive",
+    "choking",
+];
+
+// Message offered to demo receivers whose code contains "text".
+const DEMO_TEXT: &str = "Here's the Wi-Fi password for the office: correct-horse-battery-staple";
+
+// Files offered to demo receivers. Names ending in .tar.gz are real tarballs so
+// auto-extract works; everything else is filler of the given size.
+const OFFERS: &[(&str, u64)] = &[
+    ("meeting-notes.pdf", 850_000),
+    ("vacation-photos.tar.gz", 0),
+    ("song.mp3", 4_200_000),
+    ("dataset.csv", 2_300_000),
+];
+
+/// Directories for demo mode, kept apart from the real settings, history and downloads.
+pub fn demo_paths() -> AppPaths {
+    let root = std::env::temp_dir().join("wyrmhole-demo");
+    AppPaths {
+        config_dir: root.join("config"),
+        data_dir: root.join("data"),
+        default_download_dir: root.join("downloads"),
+    }
+}
+
+/// The simulated wormhole layer. Counters drive which transfers fail.
+#[derive(Clone, Default)]
+pub struct DemoWormhole {
+    sends: Arc<AtomicU64>,
+    receives: Arc<AtomicU64>,
+}
+
+impl WormholeBackend for DemoWormhole {
+    fn create_mailbox(&self) -> BoxFuture<'static, Result<Box<dyn SendMailbox>, PeerError>> {
+        let n = self.sends.fetch_add(1, Ordering::Relaxed) + 1;
+        async move {
+            tokio::time::sleep(Duration::from_millis(400)).await;
+            let code = random_code();
+            log_line!("[magic-wormhole][demo][info] Created demo mailbox {}", code);
+            Ok(Box::new(DemoPeer {
+                code,
+                fail: n % 4 == 0,
+                relay: n % 2 == 0,
+            }) as Box<dyn SendMailbox>)
+        }
+        .boxed()
+    }
+
+    fn connect(
+        &self,
+        code: Code,
+    ) -> BoxFuture<'static, Result<Box<dyn PeerConnection>, PeerError>> {
+        let n = self.receives.fetch_add(1, Ordering::Relaxed) + 1;
+        async move {
+            tokio::time::sleep(Duration::from_millis(800)).await;
+            let code = code.to_string();
+            if code.contains("fail") {
+                return Err(PeerError::Other("Mailbox not found (demo)".to_string()));
+            }
+            Ok(Box::new(DemoPeer {
+                code,
+                fail: n % 4 == 0,
+                relay: n % 2 == 0,
+            }) as Box<dyn PeerConnection>)
+        }
+        .boxed()
+    }
+}
+
+struct DemoPeer {
+    code: String,
+    fail: bool,
+    relay: bool,
+}
+
+impl DemoPeer {
+    fn transit(&self) -> TransitDetails {
+        TransitDetails {
+            connection_type: if self.relay {
+                "relay (demo)".to_string()
+            } else {
+                "direct".to_string()
+            },
+            peer_address: SocketAddr::from(([192, 0, 2, 10], 4001)),
+        }
+    }
+}
+
+impl SendMailbox for DemoPeer {
+    fn code(&self) -> String {
+        self.code.clone()
+    }
+
+    fn wait_for_peer(
+        self: Box<Self>,
+    ) -> BoxFuture<'static, Result<Box<dyn PeerConnection>, PeerError>> {
+        async move {
+            // Give the UI time to show the code before the "receiver" arrives.
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            Ok(self as Box<dyn PeerConnection>)
+        }
+        .boxed()
+    }
+}
+
+impl PeerConnection for DemoPeer {
+    fn send_file<'a>(
+        self: Box<Self>,
+        _relay_hints: Vec<RelayHint>,
+        _abilities: Abilities,
+        _file: &'a mut (dyn AsyncRead + Unpin + Send),
+        _file_name: String,
+        file_size: u64,
+        on_transit: TransitHandler<'a>,
+        mut progress: ProgressHandler,
+        mut cancel: CancelFuture,
+    ) -> BoxFuture<'a, Result<(), PeerError>> {
+        async move {
+            on_transit(self.transit());
+            for step in 1..=STEPS {
+                tick(&mut cancel).await?;
+                if self.fail && step == STEPS * 3 / 5 {
+                    return Err(PeerError::Other("Peer disconnected (demo)".to_string()));
+                }
+                progress(file_size * step / STEPS, file_size);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn request_file(
+        self: Box<Self>,
+        _relay_hints: Vec<RelayHint>,
+        _abilities: Abilities,
+        _cancel: CancelFuture,
+    ) -> BoxFuture<'static, Result<Option<PeerOffer>, PeerError>> {
+        async move {
+            if self.code.contains("empty") {
+                return Ok(None);
+            }
+            if self.code.contains("text") {
+                return Ok(Some(PeerOffer::Text(DEMO_TEXT.to_string())));
+            }
+            let (name, size) = OFFERS[(hash(&self.code) % OFFERS.len() as u64) as usize];
+            let data = if name.ends_with(".tar.gz") {
+                demo_tarball(name.trim_end_matches(".tar.gz"))
+            } else {
+                vec![b'.'; size as usize]
+            };
+            Ok(Some(PeerOffer::File(Box::new(DemoOffer {
+                peer: *self,
+                file_name: name.to_string(),
+                data,
+            }))))
+        }
+        .boxed()
+    }
+}
+
+struct DemoOffer {
+    peer: DemoPeer,
+    file_name: String,
+    data: Vec<u8>,
+}
+
+impl IncomingOffer for DemoOffer {
+    fn file_name(&self) -> String {
+        self.file_name.clone()
+    }
+
+    fn file_size(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    fn accept<'a>(
+        self: Box<Self>,
+        on_transit: TransitHandler<'a>,
+        mut progress: ProgressHandler,
+        writer: &'a mut (dyn AsyncWrite + Unpin + Send),
+        mut cancel: CancelFuture,
+    ) -> BoxFuture<'a, Result<(), PeerError>> {
+        async move {
+            on_transit(self.peer.transit());
+            let total = self.data.len() as u64;
+            let chunk = self.data.len().div_ceil(STEPS as usize).max(1);
+            let mut written = 0u64;
+            for (step, bytes) in self.data.chunks(chunk).enumerate() {
+                tick(&mut cancel).await?;
+                if self.peer.fail && step as u64 == STEPS * 3 / 5 {
+                    return Err(PeerError::Other("Peer disconnected (demo)".to_string()));
+                }
+                writer
+                    .write_all(bytes)
+                    .await
+                    .map_err(|e| PeerError::Other(e.to_string()))?;
+                written += bytes.len() as u64;
+                progress(written, total);
+            }
+            writer
+                .flush()
+                .await
+                .map_err(|e| PeerError::Other(e.to_string()))
+        }
+        .boxed()
+    }
+
+    fn reject(self: Box<Self>) -> BoxFuture<'static, Result<(), PeerError>> {
+        async { Ok(()) }.boxed()
+    }
+}
+
+// Wait one tick, or fail if the transfer is cancelled first.
+async fn tick(cancel: &mut CancelFuture) -> Result<(), PeerError> {
+    let sleep = Box::pin(tokio::time::sleep(TICK));
+    match futures::future::select(cancel, sleep).await {
+        Either::Left(_) => Err(PeerError::Other("Transfer cancelled".to_string())),
+        Either::Right(_) => Ok(()),
+    }
+}
+
+// A small real tarball, so the receive side's auto-extract path has something to unpack.
+fn demo_tarball(folder_name: &str) -> Vec<u8> {
+    let mut tar = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+    for (name, contents) in [
+        ("beach.txt", "Pretend this is a photo of a beach.\n"),
+        ("sunset.txt", "Pretend this is a photo of a sunset.\n"),
+        (
+            "notes/itinerary.txt",
+            "Day 1: arrive. Day 2: beach. Day 3: home.\n",
+        ),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        let path = PathBuf::from(folder_name).join(name);
+        let _ = tar.append_data(&mut header, path, contents.as_bytes());
+    }
+    tar.into_inner()
+        .and_then(|gz| gz.finish())
+        .unwrap_or_default()
+}
+
+fn random_code() -> String {
+    let seed = hash(&format!(
+        "{:?}",
+        SystemTime::now().duration_since(UNIX_EPOCH)
+    ));
+    let word = |shift: u32| WORDS[((seed >> shift) % WORDS.len() as u64) as usize];
+    format!("{}-{}-{}", seed % 99 + 1, word(8), word(24))
+}
+
+// FNV-1a; only used to pick demo values, not for anything security-related.
+fn hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
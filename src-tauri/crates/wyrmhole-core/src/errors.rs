@@ -0,0 +1,189 @@
+// This file maps well-known wormhole failures, and the disk errors a receive runs into, to
+// stable error codes with a message and a hint on what to do about it, so users see "That
+// code didn't match..." rather than a raw `WormholeError`, or "The disk is full." rather than
+// "os error 112". Anything not recognised keeps its original text.
+
+use magic_wormhole::WormholeError;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::io;
+use std::path::Path;
+
+use crate::wormhole::PeerError;
+
+/// A failure the user can act on. Sent as `code` on `send-error` and `download-error`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCode {
+    /// The code was mistyped, already used, or expired.
+    WrongCode,
+    /// Someone else already joined the mailbox for this code.
+    CodeClaimed,
+    /// The rendezvous server couldn't be reached or dropped the connection.
+    RendezvousUnreachable,
+    /// Neither a direct connection nor the relay worked.
+    RelayUnreachable,
+    /// The peer's wormhole client doesn't speak what this transfer needs.
+    PeerTooOld,
+    /// The receiver declined the offer.
+    OfferDeclined,
+    /// The download folder is read-only, missing, or on a drive that's gone. The offer is
+    /// still pending, so it can be accepted again into another folder.
+    DestinationUnwritable,
+    /// The disk (or the user's quota on it) ran out of space while saving.
+    DiskFull,
+    /// The received file's path is longer than the OS allows.
+    PathTooLong,
+    /// The OS refused to let wyrmhole write the file.
+    PermissionDenied,
+}
+
+// OS error numbers behind `DiskFull`, `PathTooLong` and `PermissionDenied`.
+#[cfg(windows)]
+mod os {
+    pub const DISK_FULL: &[i32] = &[39, 112, 1295]; // HANDLE_DISK_FULL, DISK_FULL, DISK_QUOTA_EXCEEDED
+    pub const PATH_TOO_LONG: &[i32] = &[206]; // FILENAME_EXCED_RANGE
+    pub const READ_ONLY: &[i32] = &[19]; // WRITE_PROTECT
+    // PATH_NOT_FOUND, which is also what a path past MAX_PATH comes back as
+    pub const PATH_NOT_FOUND: i32 = 3;
+}
+#[cfg(target_os = "linux")]
+mod os {
+    pub const DISK_FULL: &[i32] = &[28, 122]; // ENOSPC, EDQUOT
+    pub const PATH_TOO_LONG: &[i32] = &[36]; // ENAMETOOLONG
+    pub const READ_ONLY: &[i32] = &[30]; // EROFS
+    pub const PATH_NOT_FOUND: i32 = -1;
+}
+#[cfg(all(unix, not(target_os = "linux")))]
+mod os {
+    pub const DISK_FULL: &[i32] = &[28, 69]; // ENOSPC, EDQUOT
+    pub const PATH_TOO_LONG: &[i32] = &[63]; // ENAMETOOLONG
+    pub const READ_ONLY: &[i32] = &[30]; // EROFS
+    pub const PATH_NOT_FOUND: i32 = -1;
+}
+
+impl ErrorCode {
+    /// The code for `error`, if it's one of the well-known failures.
+    pub fn classify(error: &PeerError) -> Option<ErrorCode> {
+        let text = error.to_string().to_lowercase();
+        match error {
+            PeerError::Mailbox(WormholeError::UnclaimedNameplate(_))
+            | PeerError::Wormhole(WormholeError::PakeFailed) => Some(ErrorCode::WrongCode),
+            PeerError::Mailbox(_) | PeerError::Wormhole(_) if text.contains("crowded") => {
+                Some(ErrorCode::CodeClaimed)
+            }
+            PeerError::Mailbox(WormholeError::ServerError(_))
+            | PeerError::Wormhole(WormholeError::ServerError(_))
+            | PeerError::Disconnected(_) => Some(ErrorCode::RendezvousUnreachable),
+            // A receive's writes fail inside the transfer, so only the OS's text is left
+            PeerError::Transfer(_)
+                if text.contains("no space left")
+                    || text.contains("not enough space")
+                    || text.contains("quota") =>
+            {
+                Some(ErrorCode::DiskFull)
+            }
+            // magic-wormhole hands the sender the receiver's "transfer rejected" as a peer error
+            PeerError::Transfer(_) if text.contains("rejected") => Some(ErrorCode::OfferDeclined),
+            PeerError::Transfer(_) | PeerError::Wormhole(_)
+                if text.contains("unsupported") || text.contains("version") =>
+            {
+                Some(ErrorCode::PeerTooOld)
+            }
+            PeerError::Transfer(_)
+                if text.contains("transit")
+                    || text.contains("relay")
+                    || text.contains("handshake") =>
+            {
+                Some(ErrorCode::RelayUnreachable)
+            }
+            _ => None,
+        }
+    }
+
+    /// The code for `error`, hit while writing to `path`, if it's one of the well-known disk
+    /// failures.
+    pub fn classify_io(error: &io::Error, path: &Path) -> Option<ErrorCode> {
+        if error.kind() == io::ErrorKind::PermissionDenied {
+            return Some(ErrorCode::PermissionDenied);
+        }
+        let code = error.raw_os_error()?;
+        if os::DISK_FULL.contains(&code) {
+            Some(ErrorCode::DiskFull)
+        } else if os::PATH_TOO_LONG.contains(&code)
+            || (code == os::PATH_NOT_FOUND && path.as_os_str().len() >= crate::files::MAX_PATH)
+        {
+            Some(ErrorCode::PathTooLong)
+        } else if os::READ_ONLY.contains(&code) {
+            Some(ErrorCode::PermissionDenied)
+        } else {
+            None
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            ErrorCode::WrongCode => "That code didn't match a waiting sender.",
+            ErrorCode::CodeClaimed => "Someone else is already using that code.",
+            ErrorCode::RendezvousUnreachable => "Couldn't reach the rendezvous server.",
+            ErrorCode::RelayUnreachable => "Couldn't connect to the other side.",
+            ErrorCode::PeerTooOld => "The other side's wormhole client is too old.",
+            ErrorCode::OfferDeclined => "The receiver declined the transfer.",
+            ErrorCode::DestinationUnwritable => "The download folder can't be written to.",
+            ErrorCode::DiskFull => "The disk is full.",
+            ErrorCode::PathTooLong => "The file's path is too long.",
+            ErrorCode::PermissionDenied => "wyrmhole isn't allowed to write there.",
+        }
+    }
+
+    pub fn hint(self) -> &'static str {
+        match self {
+            ErrorCode::WrongCode => {
+                "Check it for typos; each code works once, so ask for a new one if it was used."
+            }
+            ErrorCode::CodeClaimed => "Ask the sender for a new code.",
+            ErrorCode::RendezvousUnreachable => {
+                "Check your internet connection, or whether a firewall blocks the server."
+            }
+            ErrorCode::RelayUnreachable => {
+                "A firewall may block direct connections; check the relay server in Settings."
+            }
+            ErrorCode::PeerTooOld => "Ask them to update their wormhole app and try again.",
+            ErrorCode::OfferDeclined => "Check with them before sending it again.",
+            ErrorCode::DestinationUnwritable => {
+                "Reconnect its drive, or save this file to another folder."
+            }
+            ErrorCode::DiskFull => "Free up some space, or save to a folder on another drive.",
+            ErrorCode::PathTooLong => {
+                "Pick a download folder with a shorter path, or turn on long paths in Windows."
+            }
+            ErrorCode::PermissionDenied => {
+                "Check that the folder isn't read-only, or save to another folder."
+            }
+        }
+    }
+}
+
+/// What to show for a failed write to `path`: the mapped message and hint, followed by the
+/// OS's own text, when it's recognised; otherwise `context` and the error as they are.
+pub fn describe_io(context: &str, error: &io::Error, path: &Path) -> String {
+    match ErrorCode::classify_io(error, path) {
+        Some(code) => format!(
+            "{} {} ({}: {})",
+            code.message(),
+            code.hint(),
+            path.display(),
+            error
+        ),
+        None => format!("{} {}: {}", context, path.display(), error),
+    }
+}
+
+/// What to show for `error`: the mapped message and hint when it's recognised, otherwise
+/// the original error text.
+pub fn describe(error: &PeerError) -> String {
+    match ErrorCode::classify(error) {
+        Some(code) => format!("{} {}", code.message(), code.hint()),
+        None => error.to_string(),
+    }
+}
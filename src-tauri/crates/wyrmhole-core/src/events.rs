@@ -0,0 +1,367 @@
+// This file defines the typed payloads for every event emitted to the frontend and
+// every structured command response. Deriving `specta::Type` lets tauri-specta
+// generate matching TypeScript types (see `specta_builder` in lib.rs), so the
+// frontend and backend can't silently drift apart.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::content::ContentWarning;
+use crate::errors::ErrorCode;
+use crate::files_json::{ReceivedFile, SentFile};
+use crate::special::SkippedFile;
+
+// Event names. Exported to the frontend as constants alongside the payload types.
+pub const SEND_PROGRESS: &str = "send-progress";
+pub const SEND_ERROR: &str = "send-error";
+pub const CONNECTION_CODE: &str = "connection-code";
+pub const DOWNLOAD_PROGRESS: &str = "download-progress";
+pub const DOWNLOAD_ERROR: &str = "download-error";
+pub const DOWNLOAD_COMPLETE: &str = "download-complete";
+pub const TEXT_OFFER: &str = "text-offer";
+pub const TRANSFER_STALLED: &str = "transfer-stalled";
+pub const NETWORK_CHANGED: &str = "network-changed";
+pub const CODE_AGE: &str = "code-age";
+pub const CODE_EXPIRED: &str = "code-expired";
+pub const PEER_CONNECTED: &str = "peer-connected";
+pub const OFFER_DECLINED: &str = "offer-declined";
+pub const OFFER_EXPIRED: &str = "offer-expired";
+pub const DUPLICATE_DETECTED: &str = "duplicate-detected";
+pub const CONTENT_FLAGGED: &str = "content-flagged";
+pub const VERIFICATION_COMPLETE: &str = "verification-complete";
+pub const PATH_SIZE_PROGRESS: &str = "path-size-progress";
+pub const PREPARING_DETAILS: &str = "preparing-details";
+pub const FILES_SKIPPED: &str = "files-skipped";
+pub const EXPORT_PROGRESS: &str = "export-progress";
+pub const PACKAGING_JOB: &str = "packaging-job";
+pub const RECEIVED_FILE_ADDED: &str = "received-file-added";
+pub const RECEIVED_FILES_ADDED: &str = "received-files-added";
+pub const SENT_FILE_ADDED: &str = "sent-file-added";
+pub const DEFAULT_FOLDER_NAME_FORMAT_UPDATED: &str = "default-folder-name-format-updated";
+
+/// Where a transfer is. Sends go `queued` (only when over the concurrent send limit),
+/// `preparing`, `connecting`, `waiting` (for the receiver, via `reconnecting` if the
+/// rendezvous connection drops), `packaging` (folders and bundles), `transit-negotiating`,
+/// `sending`, `verifying`, `finalizing`. Downloads go `transit-negotiating`, `receiving`,
+/// `verifying`, `finalizing`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransferState {
+    /// Waiting for a free send slot.
+    Queued,
+    Preparing,
+    /// Creating the mailbox on the rendezvous server.
+    Connecting,
+    /// The code is out; waiting for the receiver to join.
+    Waiting,
+    /// Rejoining the rendezvous server after the connection dropped.
+    Reconnecting,
+    /// The rendezvous server can't be reached; retrying until the network is back.
+    Offline,
+    /// Building the tarball or archive to send.
+    Packaging,
+    /// The peers are agreeing on a direct or relayed connection.
+    TransitNegotiating,
+    Sending,
+    Receiving,
+    /// All bytes are through; waiting for the other side to confirm them.
+    Verifying,
+    /// Cleaning up and recording history (and extracting, on receive).
+    Finalizing,
+}
+
+impl fmt::Display for TransferState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TransferState::Queued => "queued",
+            TransferState::Preparing => "preparing",
+            TransferState::Connecting => "connecting",
+            TransferState::Waiting => "waiting",
+            TransferState::Reconnecting => "reconnecting",
+            TransferState::Offline => "offline",
+            TransferState::Packaging => "packaging",
+            TransferState::TransitNegotiating => "transit-negotiating",
+            TransferState::Sending => "sending",
+            TransferState::Receiving => "receiving",
+            TransferState::Verifying => "verifying",
+            TransferState::Finalizing => "finalizing",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Progress and status updates for an outgoing transfer.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct SendProgress {
+    pub id: String,
+    pub file_name: String,
+    pub sent: u64,
+    pub total: u64,
+    pub percentage: u64,
+    pub code: String,
+    pub status: TransferState,
+}
+
+/// An outgoing transfer failed or was cancelled. `code` is set for the well-known
+/// failures (see errors.rs), whose `error` then carries a hint.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct SendError {
+    pub id: String,
+    pub file_name: String,
+    pub error: String,
+    pub code: Option<ErrorCode>,
+}
+
+/// Result of creating the mailbox for a send. Serialized with a `status` tag so
+/// the payload shape matches what the frontend already checks for.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum ConnectionCode {
+    Success { code: String, send_id: String },
+    Error { message: String },
+}
+
+/// Progress updates for an incoming transfer.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct DownloadProgress {
+    pub id: String,
+    pub file_name: String,
+    pub transferred: u64,
+    pub total: u64,
+    pub percentage: u64,
+    pub status: TransferState,
+}
+
+/// An incoming transfer failed. `code` is as for `SendError`.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct DownloadError {
+    pub id: String,
+    pub file_name: String,
+    pub error: String,
+    pub code: Option<ErrorCode>,
+}
+
+/// An incoming transfer finished. `path` is the saved file, or the folder it was
+/// extracted into.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct DownloadComplete {
+    pub id: String,
+    pub file_name: String,
+    pub path: PathBuf,
+}
+
+/// The sender sent a text message instead of a file. `saved_path` is set when the
+/// `save_text_offers` setting already saved it; otherwise accepting the offer saves it
+/// and denying discards it.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct TextOffer {
+    pub id: String,
+    pub text: String,
+    pub saved_path: Option<PathBuf>,
+}
+
+/// A send or download (by its id) has moved no data for `idle_secs`. Cleared by its next
+/// progress event.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct TransferStalled {
+    pub id: String,
+    pub idle_secs: u64,
+}
+
+/// This machine's network changed (a new local address, or none) while transfer `id` ran.
+/// The transfer's connection was made on the old one, so it may stall or fail.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct NetworkChanged {
+    pub id: String,
+    pub previous: Option<String>,
+    pub current: Option<String>,
+}
+
+/// How long send `id`'s code has been waiting for the receiver, every second while it waits.
+/// `expires_in_secs` is set when `code_expiry_mins` will close the mailbox.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct CodeAge {
+    pub id: String,
+    pub age_secs: u64,
+    pub expires_in_secs: Option<u64>,
+}
+
+/// Send `id` ended without delivering, so `code` no longer leads anywhere. Hosts should stop
+/// showing it as usable, so nobody reads out a stale code for a retry.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct CodeExpired {
+    pub id: String,
+    pub code: String,
+}
+
+/// The receiver claimed send `id`'s code and the wormhole is up; transit (and packaging, if
+/// still running) comes next.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct PeerConnected {
+    pub id: String,
+    pub file_name: String,
+}
+
+/// The receiver declined send `id`. Follows its `send-error`, whose `code` is `offer-declined`.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct OfferDeclined {
+    pub id: String,
+    pub file_name: String,
+}
+
+/// Incoming offer `id` went unanswered for `offer_expiry_mins` and was declined, closing the
+/// sender's wormhole. Hosts should drop it from their pending offers.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct OfferExpired {
+    pub id: String,
+    pub file_name: String,
+}
+
+/// Download `id` saved a file with the same checksum as one already in the received history,
+/// still at `existing_path`. With `skip_duplicate_receives` on, the new copy was removed
+/// (`skipped`) and the history entry points at the existing one.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct DuplicateDetected {
+    pub id: String,
+    pub file_name: String,
+    pub existing_path: PathBuf,
+    pub checksum: String,
+    pub skipped: bool,
+}
+
+/// Download `id` turned out to hold code its name didn't warn about (see content.rs). It was
+/// kept at `path` as saved, without extracting it or running the After Receiving rules.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct ContentFlagged {
+    pub id: String,
+    pub file_name: String,
+    pub path: PathBuf,
+    pub warning: ContentWarning,
+}
+
+/// `checksum` (SHA-256, hex) of what send or download `id` transferred is known, with
+/// `direction` "sent" or "received"; or received history entry `id` was re-read by
+/// `verify_received_file`.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct VerificationComplete {
+    pub id: String,
+    pub direction: String,
+    pub file_name: String,
+    pub checksum: String,
+    /// Whether the saved file hashed to `checksum` when read back; `None` when nothing was
+    /// read back (sends, and downloads without `verify_disk_writes`).
+    pub intact: Option<bool>,
+}
+
+/// Where a packaging job is (see jobs.rs).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    Done,
+    /// Waiting for a retry or cancel; `error` says why.
+    Failed,
+    Cancelled,
+}
+
+/// A packaging job for send `send_id`, building `name`. `written` is the tarball's size
+/// so far. Emitted as it runs and when its state changes, and listed by `jobs::list_jobs`.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct PackagingJob {
+    pub job_id: String,
+    pub send_id: String,
+    pub name: String,
+    pub state: JobState,
+    pub written: u64,
+    pub error: Option<String>,
+}
+
+/// Running total of a `compute_path_size` call (by its id); the last one has `done` set.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct PathSizeProgress {
+    pub id: String,
+    pub bytes: u64,
+    pub files: u64,
+    pub done: bool,
+}
+
+/// Send `id` is reading the metadata of `path`, the `index`th of its `count` selected paths.
+/// Sent before each one, so a stat stuck on a slow network share shows which path it's on.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct PreparingDetails {
+    pub id: String,
+    pub path: String,
+    pub index: u64,
+    pub count: u64,
+}
+
+/// Entries of a folder send (by its id) left out of the archive: special files and broken
+/// symlinks (see special.rs). Sent once, when there are any.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct FilesSkipped {
+    pub id: String,
+    pub files: Vec<SkippedFile>,
+}
+
+/// Progress of a history JSON export (by its id): entries matching the range, bytes written
+/// so far and time taken. The last one has `done` set.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct ExportProgress {
+    pub id: String,
+    pub entries: u64,
+    pub bytes: u64,
+    pub elapsed_ms: u64,
+    pub done: bool,
+}
+
+/// A new entry was appended to the received files history.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct ReceivedFileAdded {
+    pub file: ReceivedFile,
+}
+
+/// Several entries were appended to the received files history at once (e.g. the
+/// contents of an extracted folder).
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct ReceivedFilesAdded {
+    pub files: Vec<ReceivedFile>,
+}
+
+/// A new entry was appended to the sent files history.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct SentFileAdded {
+    pub file: SentFile,
+}
+
+/// The default folder name format setting changed.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct DefaultFolderNameFormatUpdated {
+    pub value: String,
+}
+
+/// Response of `request_file_call`: an incoming offer waiting to be accepted or denied.
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct FileOffer {
+    pub id: String,
+    pub file_name: String,
+    pub file_size: u64,
+    /// The message, when the sender offered text rather than a file (also sent as a
+    /// `TEXT_OFFER` event).
+    pub text: Option<String>,
+    /// Bigger than `max_incoming_size_mib`; accepting it needs `confirm_oversize`.
+    pub over_size_limit: bool,
+    /// A program, script or the like by its name (see content.rs); accepting it needs
+    /// `confirm_content`.
+    pub content_warning: Option<ContentWarning>,
+}
+
+/// Integer percentage of `done` out of `total`, 0 when the total is unknown.
+pub fn percentage(done: u64, total: u64) -> u64 {
+    if total > 0 {
+        (done as f64 / total as f64 * 100.0) as u64
+    } else {
+        0
+    }
+}
@@ -0,0 +1,312 @@
+// This file unpacks received archives. The format is told by the file's first bytes rather
+// than its name, so a `.zip` from another tool, a plain `.tar` or a `.tar.zst` unpacks the
+// same as wyrmhole's own `.tar.gz` folders. Each format has an `Extractor`; hosts add their
+// own (e.g. for 7z, which has none built in) with `AppContext::with_extractor`.
+
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tar::Archive;
+
+use crate::context::AppContext;
+use crate::files;
+
+/// Archive formats recognised on receive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    TarZst,
+    Zip,
+    SevenZip,
+    /// One gzipped file (e.g. `data.csv.gz`), not a tarball.
+    Gzip,
+}
+
+/// The archive format of the file at `path`, from its magic bytes. A zstd stream only counts
+/// when it holds a tarball.
+pub fn detect(path: &Path) -> Option<ArchiveFormat> {
+    let mut magic = [0u8; 6];
+    let read = File::open(path).ok()?.read(&mut magic).ok()?;
+    let magic = &magic[..read];
+    if magic.starts_with(b"PK\x03\x04") || magic.starts_with(b"PK\x05\x06") {
+        Some(ArchiveFormat::Zip)
+    } else if magic.starts_with(b"7z\xbc\xaf\x27\x1c") {
+        Some(ArchiveFormat::SevenZip)
+    } else if magic.starts_with(b"\x1f\x8b") {
+        if has_tar_header(GzDecoder::new(File::open(path).ok()?)) {
+            Some(ArchiveFormat::TarGz)
+        } else {
+            Some(ArchiveFormat::Gzip)
+        }
+    } else if magic.starts_with(b"\x28\xb5\x2f\xfd") {
+        let decoder = zstd::stream::read::Decoder::new(File::open(path).ok()?).ok()?;
+        has_tar_header(decoder).then_some(ArchiveFormat::TarZst)
+    } else if has_tar_header(File::open(path).ok()?) {
+        Some(ArchiveFormat::Tar)
+    } else {
+        None
+    }
+}
+
+// A tar header is 512 bytes with "ustar" at offset 257.
+fn has_tar_header(mut reader: impl Read) -> bool {
+    let mut header = [0u8; 512];
+    reader.read_exact(&mut header).is_ok() && header[257..262] == *b"ustar"
+}
+
+/// Unpacks one archive format.
+pub trait Extractor: Send + Sync {
+    /// False for archives that can't be unpacked unattended (e.g. password-protected zips),
+    /// which are then kept as they arrived.
+    fn can_extract(&self, _archive: &Path) -> bool {
+        true
+    }
+
+    /// Unpack `archive` into `output_dir`, returning the name and size of each file. A
+    /// top-level entry that already exists there gets a `(1)` suffix; with `merge`, it's
+    /// written into instead, overwriting what's there.
+    fn extract(
+        &self,
+        archive: &Path,
+        output_dir: &Path,
+        merge: bool,
+    ) -> Result<Vec<(String, u64)>, String>;
+}
+
+/// The extractor for `format`: the last one the host added for it, else the built-in one.
+pub(crate) fn extractor_for(ctx: &AppContext, format: ArchiveFormat) -> Option<Arc<dyn Extractor>> {
+    if let Some((_, extractor)) = ctx.extractors().iter().rev().find(|(f, _)| *f == format) {
+        return Some(extractor.clone());
+    }
+    match format {
+        ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarZst => {
+            Some(Arc::new(TarExtractor(format)))
+        }
+        ArchiveFormat::Zip => Some(Arc::new(ZipExtractor)),
+        ArchiveFormat::Gzip => Some(Arc::new(GzipExtractor)),
+        ArchiveFormat::SevenZip => None,
+    }
+}
+
+/// Extract a wyrmhole (gzipped) tarball and return the list of extracted files. With
+/// `merge`, top-level entries go straight into `output_dir`, overwriting what's there.
+pub(crate) fn extract_tarball(
+    tarball_path: &Path,
+    output_dir: &Path,
+    merge: bool,
+) -> Result<Vec<(String, u64)>, String> {
+    TarExtractor(ArchiveFormat::TarGz).extract(tarball_path, output_dir, merge)
+}
+
+// Where each entry of an archive lands under `output_dir`. Each top-level name is placed
+// once, the first time it's seen, so an existing "photos" folder yields "photos(1)" instead
+// of a merge.
+struct Placer<'a> {
+    output_dir: &'a Path,
+    merge: bool,
+    top_level: HashMap<String, PathBuf>,
+}
+
+impl<'a> Placer<'a> {
+    fn new(output_dir: &'a Path, merge: bool) -> Self {
+        Placer {
+            output_dir,
+            merge,
+            top_level: HashMap::new(),
+        }
+    }
+
+    // Where to write `path`, or None when it would escape the output directory (entries
+    // come from the peer).
+    fn place(&mut self, path: &Path) -> Option<PathBuf> {
+        if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+            log_line!(
+                "[magic-wormhole][extract][warn] Skipping unsafe archive entry {}",
+                path.display()
+            );
+            return None;
+        }
+        let mut components = path.components();
+        let first = components.next()?.as_os_str().to_string_lossy().to_string();
+        let rest = components.as_path().to_path_buf();
+        let (output_dir, merge) = (self.output_dir, self.merge);
+        let top = self
+            .top_level
+            .entry(first.clone())
+            .or_insert_with(|| {
+                if merge {
+                    output_dir.join(&first)
+                } else if rest.as_os_str().is_empty() {
+                    files::find_unique_file_path(output_dir, &first)
+                } else {
+                    files::find_unique_dir_path(output_dir, &first)
+                }
+            })
+            .clone();
+        Some(if rest.as_os_str().is_empty() {
+            top
+        } else {
+            top.join(&rest)
+        })
+    }
+}
+
+// Write `contents` to `output_path`, returning the entry's display name and size.
+fn write_entry(
+    path: &Path,
+    output_path: &Path,
+    contents: &mut impl Read,
+) -> Result<(String, u64), String> {
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let mut outfile =
+        File::create(output_path).map_err(|e| format!("Failed to create output file: {}", e))?;
+    let size = std::io::copy(contents, &mut outfile)
+        .map_err(|e| format!("Failed to extract file: {}", e))?;
+    let display_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned());
+    Ok((display_name, size))
+}
+
+// Tarballs, plain or compressed with gzip or zstd.
+struct TarExtractor(ArchiveFormat);
+
+impl TarExtractor {
+    fn unpack(
+        reader: impl Read,
+        output_dir: &Path,
+        merge: bool,
+    ) -> Result<Vec<(String, u64)>, String> {
+        let mut archive = Archive::new(reader);
+        let mut placer = Placer::new(output_dir, merge);
+        let mut extracted_files = Vec::new();
+        for entry_result in archive
+            .entries()
+            .map_err(|e| format!("Failed to read tarball entries: {}", e))?
+        {
+            let mut entry = entry_result.map_err(|e| format!("Failed to read entry: {}", e))?;
+            if entry.header().entry_type().is_dir() {
+                continue;
+            }
+            let path = entry
+                .path()
+                .map_err(|e| format!("Failed to get entry path: {}", e))?
+                .into_owned();
+            let Some(output_path) = placer.place(&path) else {
+                continue;
+            };
+            extracted_files.push(write_entry(&path, &output_path, &mut entry)?);
+        }
+        Ok(extracted_files)
+    }
+}
+
+impl Extractor for TarExtractor {
+    fn extract(
+        &self,
+        archive: &Path,
+        output_dir: &Path,
+        merge: bool,
+    ) -> Result<Vec<(String, u64)>, String> {
+        let file = File::open(archive).map_err(|e| format!("Failed to open tarball: {}", e))?;
+        match self.0 {
+            ArchiveFormat::TarGz => Self::unpack(GzDecoder::new(file), output_dir, merge),
+            ArchiveFormat::TarZst => {
+                let decoder = zstd::stream::read::Decoder::new(file)
+                    .map_err(|e| format!("Failed to open tarball: {}", e))?;
+                Self::unpack(decoder, output_dir, merge)
+            }
+            _ => Self::unpack(file, output_dir, merge),
+        }
+    }
+}
+
+struct ZipExtractor;
+
+impl ZipExtractor {
+    fn open(archive: &Path) -> Result<zip::ZipArchive<File>, String> {
+        let file = File::open(archive).map_err(|e| format!("Failed to open zip: {}", e))?;
+        zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip: {}", e))
+    }
+}
+
+impl Extractor for ZipExtractor {
+    // Password-protected zips (like archive.rs sends) are left for the user to open.
+    fn can_extract(&self, archive: &Path) -> bool {
+        let Ok(mut zip) = Self::open(archive) else {
+            return false;
+        };
+        (0..zip.len()).all(|i| zip.by_index_raw(i).is_ok_and(|entry| !entry.encrypted()))
+    }
+
+    fn extract(
+        &self,
+        archive: &Path,
+        output_dir: &Path,
+        merge: bool,
+    ) -> Result<Vec<(String, u64)>, String> {
+        let mut zip = Self::open(archive)?;
+        let mut placer = Placer::new(output_dir, merge);
+        let mut extracted_files = Vec::new();
+        for i in 0..zip.len() {
+            let mut entry = zip
+                .by_index(i)
+                .map_err(|e| format!("Failed to read entry: {}", e))?;
+            if entry.is_dir() {
+                continue;
+            }
+            let path = PathBuf::from(entry.name());
+            let Some(output_path) = placer.place(&path) else {
+                continue;
+            };
+            extracted_files.push(write_entry(&path, &output_path, &mut entry)?);
+        }
+        Ok(extracted_files)
+    }
+}
+
+/// The name a single gzipped file at `path` unpacks under: its own without `.gz`. The name
+/// in the gzip header comes from the peer and isn't checked when the offer is, so it's
+/// never used.
+pub fn gunzipped_name(path: &Path) -> String {
+    let own_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    own_name
+        .strip_suffix(".gz")
+        .filter(|name| !name.is_empty())
+        .unwrap_or(&own_name)
+        .to_string()
+}
+
+// A single gzipped file is unpacked under `gunzipped_name`.
+struct GzipExtractor;
+
+impl Extractor for GzipExtractor {
+    fn extract(
+        &self,
+        archive: &Path,
+        output_dir: &Path,
+        _merge: bool,
+    ) -> Result<Vec<(String, u64)>, String> {
+        let file = File::open(archive).map_err(|e| format!("Failed to open gzip file: {}", e))?;
+        let mut decoder = GzDecoder::new(file);
+        let name = gunzipped_name(archive);
+        let output_path = files::find_unique_file_path(output_dir, &name);
+        let (_, size) = write_entry(Path::new(&name), &output_path, &mut decoder)?;
+        let saved_name = output_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or(name);
+        Ok(vec![(saved_name, size)])
+    }
+}
@@ -0,0 +1,3056 @@
+// This file contains all file transfer logic for wyrmhole.
+// It handles sending files, receiving files, tarball operations, and transfer state management.
+
+use chrono::prelude::*;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use futures::future::{self, BoxFuture, Either};
+use futures::{AsyncWriteExt, FutureExt};
+use magic_wormhole::{Code, transit};
+use sha2::{Digest, Sha256};
+use std::{net::SocketAddr, path::Path, path::PathBuf, time::Duration, time::Instant};
+use tar::{Builder, HeaderMode};
+use tokio::fs::File;
+use tokio::task::JoinHandle;
+use tokio_util::compat::TokioAsyncReadCompatExt;
+use tokio_util::compat::TokioAsyncWriteCompatExt;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::archive;
+use crate::cache::PackageCache;
+use crate::content::{self, ContentWarning};
+use crate::context::AppContext;
+use crate::errors::{self, ErrorCode};
+use crate::events::{self, TransferState};
+use crate::files_json;
+use crate::jobs;
+use crate::metrics::{self, Metric};
+use crate::parts;
+use crate::receive;
+use crate::relays;
+use crate::settings::AppSettings;
+use crate::special::{self, EntryKind, SkippedFile};
+use crate::stream::ArchivePlan;
+use crate::throttle::{LimitLookup, RateLimit, ThrottledReader, ThrottledWriter};
+use crate::transfers::{SendSlot, TransferSnapshot};
+use crate::verify::{self, HashingReader, HashingWriter};
+use crate::watchdog::Watchdog;
+use crate::webhook;
+use crate::wormhole::{
+    IncomingOffer, PeerConnection, PeerError, PeerOffer, SendMailbox, TransitDetails,
+};
+
+// Wait for a send slot, reporting the send as `queued` meanwhile. While queued it's among
+// the active sends without a code, so `cancel_send` and `cancel_all_transfers` reach it.
+async fn wait_for_send_slot(
+    ctx: &AppContext,
+    send_id: &str,
+    file_name: &str,
+) -> Result<SendSlot, String> {
+    let limit = ctx.settings().lock().await.get_max_concurrent_sends();
+    if let Some(slot) = ctx.transfers().try_take_send_slot(limit) {
+        return Ok(slot);
+    }
+
+    let cancel = CancellationToken::new();
+    ctx.transfers()
+        .add_send(send_id, String::new(), cancel.clone())
+        .await;
+    log_line!(
+        "[magic-wormhole][files][info] Send {} queued ({} running)",
+        send_id,
+        ctx.transfers().running_sends()
+    );
+    let _ = ctx.emit(
+        events::SEND_PROGRESS,
+        events::SendProgress {
+            id: send_id.to_string(),
+            file_name: file_name.to_string(),
+            sent: 0,
+            total: 0,
+            percentage: 0,
+            code: String::new(),
+            status: TransferState::Queued,
+        },
+    );
+
+    loop {
+        // Register for the wakeup before checking, so a slot freed in between isn't missed.
+        let freed = ctx.transfers().send_slot_freed();
+        let limit = ctx.settings().lock().await.get_max_concurrent_sends();
+        if let Some(slot) = ctx.transfers().try_take_send_slot(limit) {
+            ctx.transfers().remove_send(send_id).await;
+            return Ok(slot);
+        }
+        if let Either::Right(_) =
+            futures::future::select(Box::pin(freed), Box::pin(cancel.cancelled())).await
+        {
+            return Err("Transfer cancelled".to_string());
+        }
+    }
+}
+
+// The code as carried by send-progress events: empty when `hide_send_codes` is on, so it only
+// reaches the host through the one-off `connection-code` event and `reveal_code`.
+async fn send_progress_code(ctx: &AppContext, code: String) -> String {
+    if ctx.settings().lock().await.get_hide_send_codes() {
+        String::new()
+    } else {
+        code
+    }
+}
+
+// How many times a send rejoins the rendezvous server after its connection drops before
+// giving up, waiting 1s, 2s, 4s... between attempts.
+const MAILBOX_RECONNECT_ATTEMPTS: u32 = 3;
+
+/// Wait for the receiver on `mailbox`, reconnecting (status `reconnecting`) when the
+/// rendezvous connection drops. The same code is reclaimed if possible so the one already
+/// shared keeps working; otherwise a new mailbox is made and its code announced again.
+async fn wait_for_receiver(
+    ctx: &AppContext,
+    send_id: &str,
+    file_name: &str,
+    mut mailbox: Box<dyn SendMailbox>,
+) -> Result<Box<dyn PeerConnection>, PeerError> {
+    let expiry_mins = ctx.settings().lock().await.get_code_expiry_mins();
+    let expiry = (expiry_mins > 0).then(|| Duration::from_secs(expiry_mins.saturating_mul(60)));
+    let mut attempt = 0;
+    // When the current code was first handed out; a reclaimed code keeps its age.
+    let mut opened = (mailbox.code(), tokio::time::Instant::now());
+    loop {
+        let code = mailbox.code();
+        if opened.0 != code {
+            opened = (code.clone(), tokio::time::Instant::now());
+        }
+        let waiting = wait_with_code_age(ctx, send_id, mailbox.wait_for_peer(), opened.1, expiry);
+        let reason = match waiting.await {
+            Err(PeerError::Disconnected(reason)) => reason,
+            result => return result,
+        };
+        log_line!(
+            "[magic-wormhole][files][warn] Rendezvous connection dropped for send {}: {}",
+            send_id,
+            reason
+        );
+
+        mailbox = loop {
+            attempt += 1;
+            if attempt > MAILBOX_RECONNECT_ATTEMPTS {
+                return Err(PeerError::Disconnected(reason));
+            }
+            let _ = ctx.emit(
+                events::SEND_PROGRESS,
+                events::SendProgress {
+                    id: send_id.to_string(),
+                    file_name: file_name.to_string(),
+                    sent: 0,
+                    total: 0,
+                    percentage: 0,
+                    code: send_progress_code(ctx, code.clone()).await,
+                    status: TransferState::Reconnecting,
+                },
+            );
+            tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await;
+            // Cancelling removes the send; don't reconnect for nobody.
+            if !ctx.transfers().has_send(send_id).await {
+                return Err(PeerError::Other("Transfer cancelled by user".to_string()));
+            }
+            match reconnect_mailbox(ctx, send_id, &code).await {
+                Ok(mailbox) => break mailbox,
+                Err(e) => log_line!(
+                    "[magic-wormhole][files][warn] Reconnect attempt {} for send {} failed: {}",
+                    attempt,
+                    send_id,
+                    e
+                ),
+            }
+        };
+
+        let code = mailbox.code();
+        let _ = ctx.emit(
+            events::SEND_PROGRESS,
+            events::SendProgress {
+                id: send_id.to_string(),
+                file_name: file_name.to_string(),
+                sent: 0,
+                total: 0,
+                percentage: 0,
+                code: send_progress_code(ctx, code).await,
+                status: TransferState::Waiting,
+            },
+        );
+    }
+}
+
+// How often a waiting code reports its age.
+const CODE_AGE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `waiting` (a mailbox's `wait_for_peer`), emitting `code-age` every `CODE_AGE_INTERVAL`
+/// and giving up once the code, handed out at `opened`, is older than `expiry`.
+async fn wait_with_code_age(
+    ctx: &AppContext,
+    send_id: &str,
+    waiting: BoxFuture<'static, Result<Box<dyn PeerConnection>, PeerError>>,
+    opened: tokio::time::Instant,
+    expiry: Option<Duration>,
+) -> Result<Box<dyn PeerConnection>, PeerError> {
+    let ticker = async {
+        loop {
+            tokio::time::sleep(CODE_AGE_INTERVAL).await;
+            let age = opened.elapsed();
+            let _ = ctx.emit(
+                events::CODE_AGE,
+                events::CodeAge {
+                    id: send_id.to_string(),
+                    age_secs: age.as_secs(),
+                    expires_in_secs: expiry.map(|expiry| expiry.saturating_sub(age).as_secs()),
+                },
+            );
+            if let Some(expiry) = expiry
+                && age >= expiry
+            {
+                return expiry;
+            }
+        }
+    };
+    match future::select(waiting, Box::pin(ticker)).await {
+        Either::Left((result, _)) => result,
+        Either::Right((expiry, _)) => {
+            log_line!(
+                "[magic-wormhole][files][info] Code for send {} expired unclaimed",
+                send_id
+            );
+            Err(PeerError::Other(format!(
+                "Code expired: nobody used it within {} minutes",
+                expiry.as_secs() / 60
+            )))
+        }
+    }
+}
+
+async fn reconnect_mailbox(
+    ctx: &AppContext,
+    send_id: &str,
+    code: &str,
+) -> Result<Box<dyn SendMailbox>, PeerError> {
+    if let Ok(parsed) = code.parse::<Code>() {
+        match ctx.wormhole().reclaim_mailbox(parsed).await {
+            Ok(mailbox) => {
+                log_line!(
+                    "[magic-wormhole][files][info] Reclaimed mailbox for send {}",
+                    send_id
+                );
+                return Ok(mailbox);
+            }
+            Err(e) => log_line!(
+                "[magic-wormhole][files][warn] Could not reclaim {} for send {}: {}",
+                code,
+                send_id,
+                e
+            ),
+        }
+    }
+
+    let mailbox = ctx.wormhole().create_mailbox().await?;
+    let new_code = mailbox.code();
+    ctx.transfers()
+        .set_send_code(send_id, new_code.clone())
+        .await;
+    let _ = ctx.emit(
+        events::CONNECTION_CODE,
+        events::ConnectionCode::Success {
+            code: new_code,
+            send_id: send_id.to_string(),
+        },
+    );
+    Ok(mailbox)
+}
+
+// How long an offline send waits before retrying the rendezvous server, doubling each time up
+// to `OFFLINE_RETRY_MAX`.
+const OFFLINE_RETRY_START: Duration = Duration::from_secs(1);
+const OFFLINE_RETRY_MAX: Duration = Duration::from_secs(30);
+
+fn is_offline(result: &Result<Box<dyn SendMailbox>, PeerError>) -> bool {
+    matches!(result, Err(e) if ErrorCode::classify(e) == Some(ErrorCode::RendezvousUnreachable))
+}
+
+/// Create the send's mailbox, or claim `reply_to`'s. With `queue_offline_sends` on, a send
+/// that can't reach the rendezvous server waits as `offline` and keeps retrying until the
+/// server answers (the network is back) or the send is cancelled.
+async fn open_send_mailbox(
+    ctx: &AppContext,
+    send_id: &str,
+    file_name: &str,
+    reply_to: Option<Code>,
+) -> Result<Box<dyn SendMailbox>, PeerError> {
+    let open = move || {
+        let reply_to = reply_to.clone();
+        async move {
+            match reply_to {
+                Some(code) => ctx.wormhole().claim_mailbox(code).await,
+                None => ctx.wormhole().create_mailbox().await,
+            }
+        }
+    };
+    let mut result = open().await;
+    if !is_offline(&result) || !ctx.settings().lock().await.get_queue_offline_sends() {
+        return result;
+    }
+
+    let cancel = CancellationToken::new();
+    ctx.transfers()
+        .add_send(send_id, String::new(), cancel.clone())
+        .await;
+    let _ = ctx.emit(
+        events::SEND_PROGRESS,
+        events::SendProgress {
+            id: send_id.to_string(),
+            file_name: file_name.to_string(),
+            sent: 0,
+            total: 0,
+            percentage: 0,
+            code: String::new(),
+            status: TransferState::Offline,
+        },
+    );
+
+    let mut delay = OFFLINE_RETRY_START;
+    while is_offline(&result) {
+        log_line!(
+            "[magic-wormhole][files][warn] Rendezvous server unreachable for send {}, retrying in {}s",
+            send_id,
+            delay.as_secs()
+        );
+        let sleep = Box::pin(tokio::time::sleep(delay));
+        if let Either::Right(_) = futures::future::select(sleep, Box::pin(cancel.cancelled())).await
+        {
+            return Err(PeerError::Other("Transfer cancelled by user".to_string()));
+        }
+        delay = (delay * 2).min(OFFLINE_RETRY_MAX);
+        result = open().await;
+    }
+    ctx.transfers().remove_send(send_id).await;
+    result
+}
+
+/// The code for replying to a transfer whose connection had the session secret `secret`.
+/// Both sides derive it, so a reply needs no new code passed on.
+pub fn reply_code(secret: &str) -> String {
+    follow_up_code(secret, "reply")
+}
+
+/// A code both sides of a finished transfer can work out from its session secret (see
+/// `PeerConnection::session_secret`), one per `purpose`. Its nameplate is a long hex string,
+/// never one the server hands out or the one the transfer used, and nobody who only saw the
+/// transfer's code can derive it.
+pub(crate) fn follow_up_code(secret: &str, purpose: &str) -> String {
+    let digest = format!(
+        "{:x}",
+        Sha256::digest(format!("wyrmhole-follow-up:{}:{}", purpose, secret).as_bytes())
+    );
+    let (nameplate, password) = digest.split_at(16);
+    format!("{}-{}", nameplate, password)
+}
+
+/// The session secret of the finished send or received offer `transfer_id`.
+pub(crate) async fn transfer_secret(ctx: &AppContext, transfer_id: &str) -> Option<String> {
+    ctx.transfers().secret(transfer_id).await
+}
+
+/// True while any send, receive, pending offer or connection attempt is in flight.
+pub async fn has_active_transfers(ctx: &AppContext) -> bool {
+    ctx.transfers().is_busy().await
+}
+
+/// The ids of every send, download, connection attempt and pending offer in flight.
+pub async fn active_transfers(ctx: AppContext) -> TransferSnapshot {
+    ctx.transfers().snapshot().await
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+pub async fn send_file_call(
+    ctx: AppContext,
+    file_path: &str,
+    send_id: String,
+) -> Result<String, String> {
+    let part_size = ctx
+        .settings()
+        .lock()
+        .await
+        .get_archive_part_size_mib()
+        .saturating_mul(1024 * 1024);
+    if part_size > 0 && Path::new(file_path).is_dir() {
+        return parts::send_folder_in_parts(ctx, Path::new(file_path), send_id, part_size).await;
+    }
+    queued_send(
+        ctx,
+        vec![file_path.to_string()],
+        send_id,
+        SendOptions::default(),
+    )
+    .await
+}
+
+/// Send `file_path` back to the other side of the finished transfer `transfer_id` (a send id
+/// or a received offer's id) on its reply code, with no new code to pass on.
+pub async fn send_reply_call(
+    ctx: AppContext,
+    transfer_id: &str,
+    file_path: &str,
+    send_id: String,
+) -> Result<String, String> {
+    let code = reply_code_for(&ctx, transfer_id).await?;
+    let options = SendOptions {
+        reply_to: Some(code),
+        ..SendOptions::default()
+    };
+    queued_send(ctx, vec![file_path.to_string()], send_id, options).await
+}
+
+/// Send the file of received history entry `received_entry_id` on as a new send with its own
+/// code, e.g. a document someone sent you, after editing it.
+pub async fn send_back(
+    ctx: AppContext,
+    received_entry_id: &str,
+    send_id: String,
+) -> Result<String, String> {
+    let file_path = files_json::received_file_path(&ctx, received_entry_id).await?;
+    send_file_call(ctx, &file_path.to_string_lossy(), send_id).await
+}
+
+/// Wait for the other side of the finished transfer `transfer_id` to reply, resolving with
+/// their offer like `request_file_call`. Cancelled with `cancel_connection`.
+pub async fn await_reply_call(
+    ctx: AppContext,
+    transfer_id: &str,
+    connection_id: String,
+) -> Result<events::FileOffer, String> {
+    let code = reply_code_for(&ctx, transfer_id).await?;
+    request_offer(ctx, code, connection_id, true).await
+}
+
+async fn reply_code_for(ctx: &AppContext, transfer_id: &str) -> Result<Code, String> {
+    let secret = transfer_secret(ctx, transfer_id)
+        .await
+        .ok_or("No finished transfer to reply to")?;
+    reply_code(&secret)
+        .parse::<Code>()
+        .map_err(|e| format!("Invalid reply code: {}", e))
+}
+
+/// How a send goes out. The defaults send a single path as it is, on a new code.
+#[derive(Default)]
+pub(crate) struct SendOptions {
+    /// Pack the paths into one tarball even when there's only one.
+    pub archive: bool,
+    /// The folder inside that tarball (see `archive_display_name`).
+    pub folder_name: Option<String>,
+    /// Send on this agreed code (a reply or part code) instead of a new one.
+    pub reply_to: Option<Code>,
+    /// gzip level for the tarball in place of the `compression_level` setting.
+    pub compression: Option<u32>,
+    /// Pack the paths into an AES-256 encrypted zip with this password (see archive.rs)
+    /// instead of a tarball.
+    pub password: Option<String>,
+}
+
+// What a send puts on the wire.
+enum Payload {
+    // One file, sent as it is.
+    File(PathBuf),
+    // A folder, packed into a tarball named after it.
+    Folder(PathBuf),
+    // Several paths (or one, with `SendOptions::archive`), packed into a tarball holding a
+    // folder named `folder_name`.
+    Bundle {
+        paths: Vec<String>,
+        folder_name: String,
+    },
+    // Like a bundle, but packed into an encrypted zip (see archive.rs).
+    EncryptedArchive {
+        paths: Vec<String>,
+        folder_name: String,
+        password: String,
+    },
+}
+
+impl Payload {
+    // `inspected` is what `inspect_paths` found for `paths`.
+    async fn of(
+        ctx: &AppContext,
+        paths: Vec<String>,
+        inspected: &[Option<std::fs::Metadata>],
+        options: &SendOptions,
+    ) -> Result<Self, String> {
+        match paths.as_slice() {
+            [] => Err("No files provided".to_string()),
+            // Absolute, but not canonicalized: that adds a \\?\ prefix on Windows and turns
+            // mapped network drives into UNC paths
+            [single] if !options.archive => {
+                let path = std::path::absolute(single)
+                    .map(simplified)
+                    .map_err(|e| format!("Failed to get current directory: {}", e))?;
+                let is_dir = inspected
+                    .first()
+                    .is_some_and(|metadata| metadata.as_ref().is_some_and(|m| m.is_dir()));
+                Ok(if is_dir {
+                    Payload::Folder(path)
+                } else {
+                    Payload::File(path)
+                })
+            }
+            _ => {
+                let folder_name =
+                    archive_display_name(ctx, &paths, options.folder_name.clone()).await;
+                Ok(match options.password.clone() {
+                    Some(password) => Payload::EncryptedArchive {
+                        paths,
+                        folder_name,
+                        password,
+                    },
+                    None => Payload::Bundle { paths, folder_name },
+                })
+            }
+        }
+    }
+
+    // The name shown for the send and in its errors.
+    fn name(&self) -> String {
+        match self {
+            Payload::File(path) | Payload::Folder(path) => path
+                .file_name()
+                .and_then(|os| os.to_str())
+                .unwrap_or("unknown")
+                .to_string(),
+            Payload::Bundle { folder_name, .. } | Payload::EncryptedArchive { folder_name, .. } => {
+                folder_name.clone()
+            }
+        }
+    }
+
+    // The name it goes over the wire as.
+    fn wire_name(&self) -> String {
+        match self {
+            Payload::File(_) => self.name(),
+            Payload::EncryptedArchive { .. } => format!("{}.zip", self.name()),
+            _ => format!("{}.tar.gz", self.name()),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Payload::File(_) => "file",
+            Payload::Folder(_) => "folder",
+            Payload::Bundle { .. } => "files",
+            Payload::EncryptedArchive { .. } => "encrypted archive",
+        }
+    }
+
+    // What the sent history lists as the send's files.
+    fn source_paths(&self) -> Vec<PathBuf> {
+        match self {
+            Payload::File(path) | Payload::Folder(path) => vec![path.clone()],
+            Payload::Bundle { paths, .. } | Payload::EncryptedArchive { paths, .. } => paths
+                .iter()
+                .map(|path| simplified(std::path::absolute(path).unwrap_or_else(|_| path.into())))
+                .collect(),
+        }
+    }
+
+    // List the entries of a streamed folder or bundle archive, on a blocking task.
+    async fn plan_stream(
+        &self,
+        ctx: &AppContext,
+        send_id: &str,
+    ) -> JoinHandle<Result<ArchivePlan, String>> {
+        let deterministic = ctx.settings().lock().await.get_deterministic_archives();
+        let name = self.name();
+        let payload = match self {
+            Payload::Folder(folder) => Ok(folder.clone()),
+            Payload::Bundle { paths, .. } => Err(paths.clone()),
+            Payload::File(_) | Payload::EncryptedArchive { .. } => {
+                unreachable!("only plain folders and bundles are streamed")
+            }
+        };
+        let (ctx, send_id) = (ctx.clone(), send_id.to_string());
+        tokio::task::spawn_blocking(move || {
+            let mut skipped = Vec::new();
+            let plan = match payload {
+                Ok(folder) => ArchivePlan::of_folder(&folder, &name, deterministic, &mut skipped),
+                Err(paths) => ArchivePlan::of_paths(&paths, &name, deterministic, &mut skipped),
+            };
+            report_skipped(&ctx, &send_id, skipped);
+            plan
+        })
+    }
+
+    // Start packing a folder or bundle into `tarball_path` (see `start_packaging`).
+    async fn start_packaging(
+        &self,
+        ctx: &AppContext,
+        send_id: &str,
+        tarball_path: &Path,
+        compression: Option<u32>,
+        cancel: CancellationToken,
+    ) -> Option<JoinHandle<Result<u64, String>>> {
+        let options = TarballOptions::from_settings(ctx, compression).await;
+        let tarball = tarball_path.to_path_buf();
+        let name = self.name();
+        // Re-sends of the same selection reuse a cached tarball (see cache.rs)
+        let cache = PackageCache::from_settings(ctx)
+            .await
+            .map(|cache| (cache, self.source_paths()));
+        let (report_ctx, report_id) = (ctx.clone(), send_id.to_string());
+        let packaging = match self {
+            Payload::File(_) => return None,
+            Payload::Folder(folder) => {
+                let folder = folder.clone();
+                start_packaging(
+                    ctx,
+                    send_id,
+                    &self.wire_name(),
+                    tarball_path,
+                    cancel,
+                    move |cancel| {
+                        let mut skipped = Vec::new();
+                        let build = || {
+                            create_tarball_from_folder(
+                                &folder,
+                                &tarball,
+                                &name,
+                                options,
+                                cancel,
+                                &mut skipped,
+                            )
+                        };
+                        let size = match &cache {
+                            Some((cache, sources)) => {
+                                cache.build_cached(sources, &name, options, &tarball, build)
+                            }
+                            None => build(),
+                        };
+                        report_skipped(&report_ctx, &report_id, skipped);
+                        size
+                    },
+                )
+            }
+            Payload::Bundle { paths, .. } => {
+                let paths = paths.clone();
+                start_packaging(
+                    ctx,
+                    send_id,
+                    &self.wire_name(),
+                    tarball_path,
+                    cancel,
+                    move |cancel| {
+                        let mut skipped = Vec::new();
+                        let build = || {
+                            create_tarball_from_paths(
+                                &paths,
+                                &tarball,
+                                &name,
+                                options,
+                                cancel,
+                                &mut skipped,
+                            )
+                        };
+                        let size = match &cache {
+                            Some((cache, sources)) => {
+                                cache.build_cached(sources, &name, options, &tarball, build)
+                            }
+                            None => build(),
+                        };
+                        report_skipped(&report_ctx, &report_id, skipped);
+                        size
+                    },
+                )
+            }
+            // Not cached (see cache.rs): a copy would outlive the send, and no two encrypted
+            // zips are alike anyway.
+            Payload::EncryptedArchive {
+                paths, password, ..
+            } => {
+                let (paths, password) = (paths.clone(), password.clone());
+                start_packaging(
+                    ctx,
+                    send_id,
+                    &self.wire_name(),
+                    tarball_path,
+                    cancel,
+                    move |cancel| {
+                        let mut skipped = Vec::new();
+                        let size = archive::create_encrypted_zip(
+                            &paths,
+                            &tarball,
+                            &name,
+                            &password,
+                            cancel,
+                            &mut skipped,
+                        );
+                        report_skipped(&report_ctx, &report_id, skipped);
+                        size
+                    },
+                )
+            }
+        };
+        Some(packaging)
+    }
+}
+
+pub(crate) async fn queued_send(
+    ctx: AppContext,
+    paths: Vec<String>,
+    send_id: String,
+    options: SendOptions,
+) -> Result<String, String> {
+    // Named before the send knows its payload, for the queue and history
+    let name = match paths.as_slice() {
+        [single] if !options.archive => Path::new(single)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| single.clone()),
+        _ => options
+            .folder_name
+            .clone()
+            .unwrap_or_else(|| format!("{} files", paths.len())),
+    };
+    let unsent_paths = paths.clone();
+    let id = send_id.clone();
+    let result = match wait_for_send_slot(&ctx, &send_id, &name).await {
+        Ok(_slot) => {
+            metrics::record(&ctx, Metric::SendStarted).await;
+            let result = send_paths(ctx.clone(), paths, send_id, options).await;
+            metrics::record_outcome(&ctx, Metric::SendCompleted, Metric::SendFailed, &result).await;
+            result
+        }
+        Err(e) => Err(e),
+    };
+    let shown_code = ctx.transfers().take_shown_code(&id).await;
+    if let Err(e) = &result {
+        // Whatever path the send failed on, it's no longer active and its code is dead
+        if let Some(cancel) = ctx.transfers().remove_send(&id).await {
+            cancel.cancel();
+        }
+        if let Some(code) = shown_code {
+            let _ = ctx.emit(events::CODE_EXPIRED, events::CodeExpired { id, code });
+        }
+        let paths: Vec<&str> = unsent_paths.iter().map(String::as_str).collect();
+        record_unsent(&ctx, &name, &paths, e).await;
+    }
+    result
+}
+
+// Adds a send that didn't complete (cancelled or failed with `error`) to the sent history.
+async fn record_unsent(ctx: &AppContext, name: &str, file_paths: &[&str], error: &str) {
+    let (file_name, file_extension) = name
+        .rsplit_once('.')
+        .map(|(name, ext)| (name.to_string(), ext.to_string()))
+        .unwrap_or_else(|| (name.to_string(), String::new()));
+    let file_paths: Vec<PathBuf> = file_paths
+        .iter()
+        .map(|path| std::path::absolute(path).unwrap_or_else(|_| PathBuf::from(path)))
+        .collect();
+    let file_size = file_paths
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum();
+    let entry = files_json::SentFile {
+        file_name,
+        file_size,
+        file_extension,
+        file_paths: files_json::sent_paths_for_history(ctx, file_paths).await,
+        send_time: Local::now(),
+        connection_code: String::new(),
+        connection_type: String::new(),
+        peer_address: "0.0.0.0:0".parse().unwrap(),
+        status: files_json::TransferStatus::of_error(error),
+        reason: Some(error.to_string()),
+        id: files_json::new_entry_id(),
+        snapshots: Vec::new(),
+        checksum: None,
+    };
+    if let Err(e) = files_json::add_sent_file(ctx, entry).await {
+        log_line!("[magic-wormhole][files][warn] {}", e);
+    }
+}
+
+// How long opening or stat-ing one path may take before a send gives up on it. A network
+// share that has gone away can otherwise hold a send in "preparing" for minutes.
+const INSPECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Read the metadata of each of `paths` on a blocking task, announcing each as
+// `PREPARING_DETAILS` first. Paths that can't be read are `None`, left for the send to report
+// as it does today; one that takes longer than `INSPECT_TIMEOUT`, or is a FIFO, socket or
+// device node, fails the send.
+async fn inspect_paths(
+    ctx: &AppContext,
+    send_id: &str,
+    paths: &[String],
+) -> Result<Vec<Option<std::fs::Metadata>>, String> {
+    let mut inspected = Vec::with_capacity(paths.len());
+    for (index, path) in paths.iter().enumerate() {
+        let _ = ctx.emit(
+            events::PREPARING_DETAILS,
+            events::PreparingDetails {
+                id: send_id.to_string(),
+                path: path.clone(),
+                index: index as u64,
+                count: paths.len() as u64,
+            },
+        );
+        let owned = PathBuf::from(path);
+        let metadata = tokio::time::timeout(
+            INSPECT_TIMEOUT,
+            tokio::task::spawn_blocking(move || std::fs::metadata(owned)),
+        )
+        .await
+        .map_err(|_| {
+            format!(
+                "Timed out reading {} (is its drive or network share still connected?)",
+                path
+            )
+        })?
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        // Special files inside folders are skipped, but one picked directly is refused
+        if let Ok(metadata) = &metadata
+            && let EntryKind::Skip(reason) = special::classify_metadata(metadata.clone())
+        {
+            return Err(format!("Can't send {}: it's a {}", path, reason.message()));
+        }
+        inspected.push(metadata.ok());
+    }
+    Ok(inspected)
+}
+
+// The send pipeline behind every send: opens the mailbox (or claims `reply_to`'s), packs
+// folders and bundles while waiting for the receiver, sends, and records the result.
+async fn send_paths(
+    ctx: AppContext,
+    paths: Vec<String>,
+    send_id: String,
+    options: SendOptions,
+) -> Result<String, String> {
+    let overall_start = Instant::now();
+    let inspected = match inspect_paths(&ctx, &send_id, &paths).await {
+        Ok(inspected) => inspected,
+        Err(e) => {
+            let _ = ctx.emit(
+                events::SEND_ERROR,
+                events::SendError {
+                    id: send_id.clone(),
+                    file_name: paths
+                        .first()
+                        .and_then(|path| Path::new(path).file_name())
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    error: e.clone(),
+                    code: None,
+                },
+            );
+            return Err(e);
+        }
+    };
+    let payload = Payload::of(&ctx, paths, &inspected, &options).await?;
+    let file_name = payload.name();
+    // Folders and bundles go out as a plain tar written while it's sent, when enabled. A
+    // stream can't be compressed (its size is announced before it's written), so a send
+    // asking for a compression level is packed at that level instead.
+    let streamed = matches!(payload, Payload::Folder(_) | Payload::Bundle { .. })
+        && options.compression.is_none()
+        && ctx.settings().lock().await.get_stream_archives();
+    let wire_name = if streamed {
+        format!("{}.tar", file_name)
+    } else {
+        payload.wire_name()
+    };
+
+    // Emit "Preparing..." status before mailbox connection
+    let _ = ctx.emit(
+        events::SEND_PROGRESS,
+        events::SendProgress {
+            id: send_id.clone(),
+            file_name: wire_name.clone(),
+            sent: 0,
+            total: 0,
+            percentage: 0,
+            code: String::new(),
+            status: TransferState::Preparing,
+        },
+    );
+
+    // Cancellation token for this send, shared with its packaging job
+    let cancel = CancellationToken::new();
+
+    let _ = ctx.emit(
+        events::SEND_PROGRESS,
+        events::SendProgress {
+            id: send_id.clone(),
+            file_name: wire_name.clone(),
+            sent: 0,
+            total: 0,
+            percentage: 0,
+            code: String::new(),
+            status: TransferState::Connecting,
+        },
+    );
+
+    // Create the mailbox connection
+    let mailbox_start = Instant::now();
+    let mailbox = open_send_mailbox(&ctx, &send_id, &wire_name, options.reply_to).await;
+    let mailbox_connection = match mailbox {
+        Ok(conn) => {
+            let code_string = conn.code();
+
+            // Store the connection code and cancellation token for this send
+            ctx.transfers()
+                .add_send(&send_id, code_string.clone(), cancel.clone())
+                .await;
+
+            let _ = ctx.emit(
+                events::CONNECTION_CODE,
+                events::ConnectionCode::Success {
+                    code: code_string.clone(),
+                    send_id: send_id.clone(),
+                },
+            );
+
+            // Emit "Waiting..." status after mailbox connection is established
+            let progress_code = send_progress_code(&ctx, code_string.clone()).await;
+            let _ = ctx.emit(
+                events::SEND_PROGRESS,
+                events::SendProgress {
+                    id: send_id.clone(),
+                    file_name: wire_name.clone(),
+                    sent: 0,
+                    total: 0,
+                    percentage: 0,
+                    code: progress_code,
+                    status: TransferState::Waiting,
+                },
+            );
+
+            conn
+        }
+        Err(e) => {
+            let error_code = ErrorCode::classify(&e);
+            let error_msg = format!("Failed to connect: {}", errors::describe(&e));
+            let _ = ctx.emit(
+                events::CONNECTION_CODE,
+                events::ConnectionCode::Error {
+                    message: error_msg.clone(),
+                },
+            );
+            let _ = ctx.emit(
+                events::SEND_ERROR,
+                events::SendError {
+                    id: send_id.clone(),
+                    file_name: file_name.clone(),
+                    error: error_msg.clone(),
+                    code: error_code,
+                },
+            );
+            return Err(error_msg);
+        }
+    };
+
+    // Construct relay hints, preferring a user-configured relay server if available.
+    let relay_hints = build_relay_hints(&ctx).await;
+    let abilities = transit_abilities(&ctx).await;
+
+    let cancel_call = cancel.clone().cancelled_owned();
+
+    // Verify the path exists (a bundle's paths are checked as they're packed)
+    if let Payload::File(path) | Payload::Folder(path) = &payload
+        && !path.exists()
+    {
+        let error_msg = format!("File or folder does not exist: {}", path.display());
+        let _ = ctx.emit(
+            events::SEND_ERROR,
+            events::SendError {
+                id: send_id.clone(),
+                file_name: file_name.clone(),
+                error: error_msg.clone(),
+                code: None,
+            },
+        );
+        return Err(error_msg);
+    }
+
+    // Folders and bundles are packed while the code waits for the receiver, so neither
+    // holds up the other.
+    // Use a unique temp filename per send to avoid races when sends share the same name.
+    let tarball_path =
+        std::env::temp_dir().join(format!("wyrmhole_send_{}_{}", Uuid::new_v4(), &wire_name));
+    let packaging = match streamed {
+        true => None,
+        false => {
+            payload
+                .start_packaging(
+                    &ctx,
+                    &send_id,
+                    &tarball_path,
+                    options.compression,
+                    cancel.clone(),
+                )
+                .await
+        }
+    };
+    // A streamed archive only needs its entries listed (and sized) in the meantime
+    let plan = match streamed {
+        true => Some(payload.plan_stream(&ctx, &send_id).await),
+        false => None,
+    };
+
+    // Connect the wormhole - this will wait until the receiver connects
+    let wormhole = match wait_for_receiver(&ctx, &send_id, &wire_name, mailbox_connection).await {
+        Ok(wormhole) => wormhole,
+        Err(e) => {
+            if let Some(packaging) = packaging {
+                abandon_packaging(&ctx, &send_id, &cancel, packaging).await;
+            }
+            let error_code = ErrorCode::classify(&e);
+            let msg = format!("Failed to connect to Wormhole: {}", errors::describe(&e));
+            log_line!("[magic-wormhole][files][error] {}", msg);
+            let _ = ctx.emit(
+                events::SEND_ERROR,
+                events::SendError {
+                    id: send_id.clone(),
+                    file_name: file_name.clone(),
+                    error: msg.clone(),
+                    code: error_code,
+                },
+            );
+            return Err(msg);
+        }
+    };
+
+    log_line!(
+        "[magic-wormhole][perf][files] Mailbox + wormhole established for {} send in {:?}",
+        payload.kind(),
+        mailbox_start.elapsed()
+    );
+    let session_secret = wormhole.session_secret();
+    let _ = ctx.emit(
+        events::PEER_CONNECTED,
+        events::PeerConnected {
+            id: send_id.clone(),
+            file_name: wire_name.clone(),
+        },
+    );
+
+    // Get the connection code before the closures (since they can't be async)
+    let send_code = ctx
+        .transfers()
+        .send_code(&send_id)
+        .await
+        .unwrap_or_default();
+    let send_code = send_progress_code(&ctx, send_code).await;
+
+    // What goes over the wire: the file itself, or the tarball once it's packed
+    let (source, tarball) = match packaging {
+        Some(packaging) => {
+            // The receiver is here; show any packaging that's still going
+            let _ = ctx.emit(
+                events::SEND_PROGRESS,
+                events::SendProgress {
+                    id: send_id.clone(),
+                    file_name: wire_name.clone(),
+                    sent: 0,
+                    total: 0,
+                    percentage: 0,
+                    code: send_code.clone(),
+                    status: TransferState::Packaging,
+                },
+            );
+            let tarball_size = finish_packaging(packaging).await?;
+            log_line!(
+                "[magic-wormhole][perf][files] Created tarball: {} ({} bytes)",
+                tarball_path.display(),
+                tarball_size
+            );
+            (tarball_path.clone(), Some(tarball_path))
+        }
+        None => match &payload {
+            Payload::File(path) => (path.clone(), None),
+            // Streamed; shown by the folder or first path it's built from
+            _ => (payload.source_paths().swap_remove(0), None),
+        },
+    };
+    // A tarball is only ever temporary
+    let remove_tarball = |tarball: &Option<PathBuf>| {
+        if let Some(tarball) = tarball.clone() {
+            tokio::spawn(async move {
+                let _ = tokio::fs::remove_file(&tarball).await;
+            });
+        }
+    };
+
+    log_line!(
+        "[magic-wormhole][files][info] Sending {}: {} (from {})",
+        payload.kind(),
+        wire_name,
+        source.display()
+    );
+
+    // The reader the send pulls from, and how many bytes it offers
+    let (reader, file_size): (Box<dyn futures::AsyncRead + Unpin + Send>, u64) = match plan {
+        Some(plan) => {
+            let planned = plan
+                .await
+                .map_err(|e| format!("Failed to plan archive: {}", e))
+                .and_then(|plan| plan);
+            let plan = match planned {
+                Ok(plan) => plan,
+                Err(e) => {
+                    ctx.transfers().remove_send(&send_id).await;
+                    let _ = ctx.emit(
+                        events::SEND_ERROR,
+                        events::SendError {
+                            id: send_id.clone(),
+                            file_name: file_name.clone(),
+                            error: e.clone(),
+                            code: None,
+                        },
+                    );
+                    return Err(e);
+                }
+            };
+            let size = plan.size();
+            (Box::new(plan.stream(cancel.clone())), size)
+        }
+        None => {
+            // Open the file for sending, giving up on a share that stopped answering
+            let opened = tokio::time::timeout(INSPECT_TIMEOUT, File::open(&source))
+                .await
+                .unwrap_or_else(|_| Err(std::io::ErrorKind::TimedOut.into()));
+            let file = opened.map_err(|e| {
+                let error_msg = format!("Failed to open {}: {}", source.display(), e);
+                let _ = ctx.emit(
+                    events::SEND_ERROR,
+                    events::SendError {
+                        id: send_id.clone(),
+                        file_name: file_name.clone(),
+                        error: error_msg.clone(),
+                        code: None,
+                    },
+                );
+                remove_tarball(&tarball);
+                error_msg
+            })?;
+
+            // The size of what's actually opened, in case it changed since
+            let file_size = file
+                .metadata()
+                .await
+                .map_err(|e| {
+                    remove_tarball(&tarball);
+                    format!("Failed to get file metadata: {}", e)
+                })?
+                .len();
+
+            (Box::new(file.compat()), file_size)
+        }
+    };
+
+    // Paced to the upload limit, picking up changes to it while the send runs, and
+    // checksummed on the way out for the history
+    let upload_limit = ctx.settings().lock().await.upload_rate_limit();
+    let mut reader = HashingReader::new(ThrottledReader::live(
+        reader,
+        upload_limit,
+        live_rate_limit(&ctx, AppSettings::upload_rate_limit),
+    ));
+
+    let _ = ctx.emit(
+        events::SEND_PROGRESS,
+        events::SendProgress {
+            id: send_id.clone(),
+            file_name: wire_name.clone(),
+            sent: 0,
+            total: file_size,
+            percentage: 0,
+            code: send_code.clone(),
+            status: TransferState::TransitNegotiating,
+        },
+    );
+
+    // Clone values needed for progress handler
+    let progress_id = send_id.clone();
+    let progress_file_name = wire_name.clone();
+    let progress_ctx = ctx.clone();
+    let progress_code = send_code.clone();
+
+    let mut connection_type = String::new();
+    let mut peer_address: SocketAddr = "0.0.0.0:0".parse().unwrap();
+    let watchdog = Watchdog::new(&ctx).await;
+    let activity = watchdog.activity();
+    let transfer_start = Instant::now();
+    wormhole
+        .send_file(
+            relay_hints,
+            abilities,
+            &mut reader,
+            wire_name.clone(),
+            file_size,
+            Box::new(|info: TransitDetails| {
+                log_line!(
+                    "[magic-wormhole][files][info] Transit established for {} send",
+                    payload.kind()
+                );
+                metrics::record_connection(&ctx, &info.connection_type);
+                connection_type = info.connection_type;
+                peer_address = info.peer_address;
+            }),
+            // Progress handler (no per-chunk logging for performance)
+            Box::new(move |sent: u64, total: u64| {
+                activity.touch();
+                let percentage = events::percentage(sent, total);
+
+                let _ = progress_ctx.emit(
+                    events::SEND_PROGRESS,
+                    events::SendProgress {
+                        id: progress_id.clone(),
+                        file_name: progress_file_name.clone(),
+                        sent,
+                        total,
+                        percentage,
+                        code: progress_code.clone(),
+                        status: if sent < total {
+                            TransferState::Sending
+                        } else {
+                            TransferState::Verifying
+                        },
+                    },
+                );
+            }),
+            watchdog.guard(ctx.clone(), send_id.clone(), cancel_call.boxed()),
+        )
+        .await
+        .map_err(|e| {
+            let error_code = ErrorCode::classify(&e);
+            let e = watchdog.describe_failure(errors::describe(&e));
+            let error_message = format!(
+                "Failed to send {}: {} (path: {})",
+                payload.kind(),
+                e,
+                source.display()
+            );
+            log_line!(
+                "[magic-wormhole][files][error] Send failed: {}",
+                error_message
+            );
+            let _ = ctx.emit(
+                events::SEND_ERROR,
+                events::SendError {
+                    id: send_id.clone(),
+                    file_name: file_name.clone(),
+                    error: error_message.clone(),
+                    code: error_code,
+                },
+            );
+            if error_code == Some(ErrorCode::OfferDeclined) {
+                let _ = ctx.emit(
+                    events::OFFER_DECLINED,
+                    events::OfferDeclined {
+                        id: send_id.clone(),
+                        file_name: file_name.clone(),
+                    },
+                );
+            }
+            remove_tarball(&tarball);
+            error_message
+        })?;
+
+    let elapsed = transfer_start.elapsed();
+    if elapsed.as_secs_f64() > 0.0 {
+        let mb = file_size as f64 / (1024.0 * 1024.0);
+        let mbps = mb / elapsed.as_secs_f64();
+        log_line!(
+            "[magic-wormhole][perf][files] Transfer complete: {:.2} MiB in {:?} ({:.2} MiB/s)",
+            mb,
+            elapsed,
+            mbps
+        );
+    }
+
+    let _ = ctx.emit(
+        events::SEND_PROGRESS,
+        events::SendProgress {
+            id: send_id.clone(),
+            file_name: wire_name.clone(),
+            sent: file_size,
+            total: file_size,
+            percentage: 100,
+            code: send_code.clone(),
+            status: TransferState::Finalizing,
+        },
+    );
+
+    let checksum = reader.checksum();
+    let _ = ctx.emit(
+        events::VERIFICATION_COMPLETE,
+        events::VerificationComplete {
+            id: send_id.clone(),
+            direction: "sent".to_string(),
+            file_name: wire_name.clone(),
+            checksum: checksum.clone(),
+            intact: None,
+        },
+    );
+
+    // Clean up temporary tarball
+    if let Some(tarball) = &tarball {
+        let _ = tokio::fs::remove_file(tarball).await;
+    }
+
+    // Remove from active sends when complete and get the code
+    let connection_code = ctx
+        .transfers()
+        .send_code(&send_id)
+        .await
+        .unwrap_or_default();
+    ctx.transfers().remove_send(&send_id).await;
+    if let Some(secret) = session_secret {
+        ctx.transfers().remember_secret(&send_id, secret).await;
+    }
+
+    // Add to sent files history: a file under its own name and extension, a tarball
+    // under its name without `.tar.gz`
+    let (history_name, file_extension) = match &payload {
+        Payload::File(path) => {
+            let file_extension = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or("")
+                .to_string();
+            let name = file_name
+                .strip_suffix(&format!(".{}", file_extension))
+                .filter(|_| !file_extension.is_empty())
+                .unwrap_or(&file_name)
+                .to_string();
+            (name, file_extension)
+        }
+        Payload::EncryptedArchive { .. } => (file_name.clone(), "zip".to_string()),
+        _ if streamed => (file_name.clone(), "tar".to_string()),
+        _ => (file_name.clone(), "tar.gz".to_string()),
+    };
+    webhook::notify(
+        &ctx,
+        "sent",
+        &send_id,
+        &wire_name,
+        file_size,
+        Some(checksum.clone()),
+        overall_start.elapsed(),
+    );
+    let _ = files_json::add_sent_file(
+        &ctx,
+        files_json::SentFile {
+            file_name: history_name,
+            file_size,
+            file_extension,
+            file_paths: files_json::sent_paths_for_history(&ctx, payload.source_paths()).await,
+            send_time: Local::now(),
+            connection_code,
+            connection_type,
+            peer_address,
+            status: files_json::TransferStatus::Completed,
+            reason: None,
+            id: files_json::new_entry_id(),
+            snapshots: files_json::snapshots_for_history(&ctx, payload.source_paths()).await,
+            checksum: Some(checksum),
+        },
+    )
+    .await;
+
+    log_line!(
+        "[magic-wormhole][perf][files] Send of '{}' finished in {:?}",
+        wire_name,
+        overall_start.elapsed()
+    );
+
+    Ok(match &payload {
+        Payload::File(path) | Payload::Folder(path) => format!(
+            "Successfully sent {} '{}' ({} bytes)",
+            payload.kind(),
+            path.display(),
+            file_size
+        ),
+        Payload::Bundle { paths, .. } => format!("Successfully sent {} file(s)", paths.len()),
+        Payload::EncryptedArchive { paths, .. } => format!(
+            "Successfully sent {} file(s) as an encrypted archive",
+            paths.len()
+        ),
+    })
+}
+
+/// Send `file_paths` as one tarball. `compression` overrides the `compression_level` setting
+/// for this send, e.g. 0 to only store media that's already compressed.
+pub async fn send_multiple_files_call(
+    ctx: AppContext,
+    file_paths: Vec<String>,
+    send_id: String,
+    folder_name: Option<String>,
+    compression: Option<u32>,
+) -> Result<String, String> {
+    if let Some(level) = compression
+        && level > MAX_COMPRESSION_LEVEL
+    {
+        return Err(format!(
+            "Compression level must be 0 to {}",
+            MAX_COMPRESSION_LEVEL
+        ));
+    }
+    let options = SendOptions {
+        archive: true,
+        folder_name,
+        compression,
+        ..SendOptions::default()
+    };
+    queued_send(ctx, file_paths, send_id, options).await
+}
+
+/// The folder name an archive of `file_paths` is sent under: `folder_name` if given, the
+/// entry's own name for a single path, or the default folder name format otherwise.
+pub(crate) async fn archive_display_name(
+    ctx: &AppContext,
+    file_paths: &[String],
+    folder_name: Option<String>,
+) -> String {
+    if let Some(custom_name) = folder_name {
+        // Use custom name if provided
+        custom_name
+    } else if file_paths.len() == 1 {
+        // Check if it's a single folder - if so, use the folder name
+        let path = Path::new(&file_paths[0]);
+        if path.is_dir() {
+            // Single folder - use the folder name
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("folder")
+                .to_string()
+        } else {
+            // Single file - use the file name
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("file")
+                .to_string()
+        }
+    } else {
+        // Multiple files/folders - use the default format from settings
+        let app_settings_lock = ctx.settings().lock().await;
+        let format_template = app_settings_lock.get_default_folder_name_format().clone();
+        drop(app_settings_lock);
+
+        // If the format is empty or just whitespace, default to "#-files-via-wyrmhole"
+        let format_template = if format_template.trim().is_empty() {
+            "#-files-via-wyrmhole".to_string()
+        } else {
+            format_template
+        };
+
+        // Replace # with the number of files
+        format_template.replace("#", &file_paths.len().to_string())
+    }
+}
+
+/// The connection code of an active send, for hosts that keep it out of send-progress events
+/// (`hide_send_codes`) and show it only on request.
+pub async fn reveal_code(send_id: String, ctx: AppContext) -> Result<String, String> {
+    ctx.transfers()
+        .send_code(&send_id)
+        .await
+        .ok_or_else(|| "No active send found for this ID".to_string())
+}
+
+pub async fn cancel_send(send_id: String, ctx: AppContext) -> Result<String, String> {
+    ctx.transfers().cancel_multipart(&send_id).await;
+    // Remove from active sends and cancel its token
+    let Some(cancel) = ctx.transfers().remove_send(&send_id).await else {
+        return Err("No active send found for this ID".to_string());
+    };
+    cancel.cancel();
+    log_line!("Cancelled send with id: {}", send_id);
+
+    // Emit a send-error event to notify the frontend
+    let _ = ctx.emit(
+        events::SEND_ERROR,
+        events::SendError {
+            id: send_id.clone(),
+            file_name: "Transfer cancelled".to_string(),
+            error: "Transfer cancelled by user".to_string(),
+            code: None,
+        },
+    );
+
+    Ok("Send cancelled".to_string())
+}
+
+pub async fn request_file_call(
+    ctx: AppContext,
+    receive_code: &str,
+    connection_id: String,
+) -> Result<events::FileOffer, String> {
+    // Parsing input
+    let mut code_string = receive_code.trim();
+    let prefix = "wormhole receive ";
+    if code_string.starts_with(prefix) {
+        code_string = &code_string[prefix.len()..];
+        code_string = code_string.trim_start();
+    }
+    if code_string.is_empty() {
+        log_line!("[magic-wormhole][files][error] No code provided for receiving file");
+        return Err("No code provided for receiving file.".to_string());
+    }
+    let code = code_string.parse::<Code>().map_err(|err| {
+        let error_message = format!("Error parsing code: {}", err);
+        log_line!("[magic-wormhole][files][error] {}", error_message);
+        error_message
+    })?;
+    log_line!(
+        "[magic-wormhole][files][info] Parsed receive code: {:?}",
+        code
+    );
+    request_offer(ctx, code, connection_id, false).await
+}
+
+// Connects on `code` and waits for the sender's offer. With `claim` (replies) the mailbox is
+// claimed rather than joined, and it waits for the other side to turn up until cancelled.
+pub(crate) async fn request_offer(
+    ctx: AppContext,
+    code: Code,
+    connection_id: String,
+    claim: bool,
+) -> Result<events::FileOffer, String> {
+    // Cancellation token for this connection
+    let cancel = CancellationToken::new();
+    ctx.transfers()
+        .add_connection(&connection_id, cancel.clone())
+        .await;
+
+    // Connecting to the mailbox and establishing the Wormhole with the sender
+    let connected = if claim {
+        let waiting = async {
+            let mailbox = ctx.wormhole().claim_mailbox(code).await?;
+            mailbox.wait_for_peer().await
+        };
+        match future::select(waiting.boxed(), Box::pin(cancel.cancelled())).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(PeerError::Other("Cancelled while waiting".to_string())),
+        }
+    } else {
+        ctx.wormhole().connect(code).await
+    };
+    let wormhole = match connected {
+        Ok(wormhole) => {
+            log_line!(
+                "[magic-wormhole][files][info] Connected to mailbox and established Wormhole"
+            );
+            wormhole
+        }
+        Err(e) => {
+            // Remove from active connections on error
+            ctx.transfers().remove_connection(&connection_id).await;
+            let msg = match e {
+                PeerError::Mailbox(_) => {
+                    format!("Failed to create mailbox: {}", errors::describe(&e))
+                }
+                _ => format!("Failed to connect to Wormhole: {}", errors::describe(&e)),
+            };
+            log_line!("[magic-wormhole][files][error] {}", msg);
+            return Err(msg);
+        }
+    };
+
+    // Construct relay hints, preferring a user-configured relay server if available.
+    let relay_hints = build_relay_hints(&ctx).await;
+    let abilities = transit_abilities(&ctx).await;
+
+    let session_secret = wormhole.session_secret();
+    let requested = wormhole
+        .request_file(relay_hints, abilities, cancel.cancelled_owned().boxed())
+        .await;
+    // Done connecting, either way
+    ctx.transfers().remove_connection(&connection_id).await;
+    let maybe_request =
+        requested.map_err(|e| format!("Failed to request file: {}", errors::describe(&e)))?;
+    match maybe_request {
+        Some(PeerOffer::File(receive_request)) => {
+            let file_name = receive_request.file_name();
+            let file_size = receive_request.file_size();
+
+            // Store the ReceiveRequest for answering later.
+            let id = Uuid::new_v4().to_string();
+            ctx.transfers()
+                .add_offer(&id, receive_request, session_secret)
+                .await;
+            expire_offer_later(&ctx, &id).await;
+
+            log_line!(
+                "[magic-wormhole][files][info] Incoming file offer: {} ({} bytes)",
+                file_name,
+                file_size
+            );
+
+            let over_size_limit = over_size_limit(&ctx, file_size).await;
+            let content_warning = content_warning(&ctx, &file_name).await;
+            Ok(events::FileOffer {
+                id,
+                file_name,
+                file_size,
+                text: None,
+                over_size_limit,
+                content_warning,
+            })
+        }
+        Some(PeerOffer::Text(text)) => receive_text_offer(&ctx, text).await,
+        None => {
+            log_line!(
+                "[magic-wormhole][files][info] No file offered by sender (canceled or empty)"
+            );
+            Err("No file was offered by the sender (canceled or empty).".to_string())
+        }
+    }
+}
+
+// A text message from the sender. Saved right away under `save_text_offers`, otherwise
+// kept until the user accepts (saves) or denies (dismisses) it.
+async fn receive_text_offer(ctx: &AppContext, text: String) -> Result<events::FileOffer, String> {
+    let id = Uuid::new_v4().to_string();
+    let file_name = format!("message-{}.txt", Local::now().format("%Y-%m-%d-%H%M%S"));
+    let file_size = text.len() as u64;
+    log_line!(
+        "[magic-wormhole][files][info] Incoming text message ({} bytes)",
+        file_size
+    );
+
+    let save_now = ctx.settings().lock().await.get_save_text_offers();
+    let saved_path = if save_now {
+        Some(save_text_offer(ctx, &file_name, &text, None).await?)
+    } else {
+        ctx.transfers()
+            .add_text_offer(&id, file_name.clone(), text.clone())
+            .await;
+        None
+    };
+
+    let _ = ctx.emit(
+        events::TEXT_OFFER,
+        events::TextOffer {
+            id: id.clone(),
+            text: text.clone(),
+            saved_path,
+        },
+    );
+    Ok(events::FileOffer {
+        id,
+        file_name,
+        file_size,
+        text: Some(text),
+        over_size_limit: false,
+        content_warning: None,
+    })
+}
+
+// Writes a text message into the download directory (or `destination`), named like a
+// received file, and records it in history. Returns where it was saved.
+async fn save_text_offer(
+    ctx: &AppContext,
+    file_name: &str,
+    text: &str,
+    destination: Option<&Path>,
+) -> Result<PathBuf, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    let download_dir = receive_dir(&app_settings_lock, destination);
+    let template = app_settings_lock
+        .get_received_file_name_template()
+        .to_string();
+    drop(app_settings_lock);
+
+    tokio::fs::create_dir_all(&download_dir)
+        .await
+        .map_err(|e| {
+            errors::describe_io("Failed to create download directory", &e, &download_dir)
+        })?;
+    let file_path =
+        find_unique_file_path(&download_dir, &apply_name_template(&template, file_name));
+    tokio::fs::write(&file_path, text)
+        .await
+        .map_err(|e| errors::describe_io("Failed to save message to", &e, &file_path))?;
+
+    let final_name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (name, extension) = final_name
+        .rsplit_once('.')
+        .map(|(name, ext)| (name.to_string(), ext.to_string()))
+        .unwrap_or_else(|| (final_name.clone(), String::new()));
+    files_json::add_received_file(
+        ctx,
+        files_json::ReceivedFile {
+            file_name: name,
+            file_size: text.len() as u64,
+            file_extension: extension,
+            download_url: download_dir,
+            download_time: Local::now(),
+            // Text goes through the mailbox server; there's no transit connection.
+            connection_type: "mailbox".to_string(),
+            peer_address: "0.0.0.0:0".parse().unwrap(),
+            status: files_json::TransferStatus::Completed,
+            reason: None,
+            checksum: None,
+            id: files_json::new_entry_id(),
+        },
+    )
+    .await?;
+
+    log_line!(
+        "[magic-wormhole][files][info] Saved text message to {}",
+        file_path.display()
+    );
+    Ok(file_path)
+}
+
+pub async fn cancel_connection(connection_id: String, ctx: AppContext) -> Result<String, String> {
+    // Remove from active connections and cancel its token
+    let Some(cancel) = ctx.transfers().remove_connection(&connection_id).await else {
+        return Err("No active connection found for this ID".to_string());
+    };
+    cancel.cancel();
+    log_line!(
+        "[magic-wormhole][files][info] Cancelled connection with id: {}",
+        connection_id
+    );
+
+    Ok("Connection cancelled".to_string())
+}
+
+// Longest rejection reason kept in history.
+const MAX_REJECT_REASON_CHARS: usize = 200;
+
+/// Decline the offer `id` and record it in the received history as `rejected`, with `reason`
+/// if given. The sender only hears "transfer rejected"; magic-wormhole has no field for a reason.
+pub async fn receiving_file_deny(
+    id: String,
+    ctx: AppContext,
+    reason: Option<String>,
+) -> Result<String, String> {
+    // This function is called when the user denies the file offer.
+    // It will close the Wormhole connection associated with the given ID.
+    if ctx.transfers().take_text_offer(&id).await.is_some() {
+        log_line!(
+            "[magic-wormhole][files][info] Dismissed text message {}",
+            id
+        );
+        return Ok("Message dismissed".to_string());
+    }
+    let request = ctx.transfers().take_offer(&id).await;
+    let Some(request) = request else {
+        return Err("No request found for this ID".to_string());
+    };
+    let (file_name, file_size) = (request.file_name(), request.file_size());
+    reject_request(&ctx, &id, request).await?;
+
+    let reason = reason
+        .map(|reason| {
+            reason
+                .trim()
+                .chars()
+                .take(MAX_REJECT_REASON_CHARS)
+                .collect()
+        })
+        .filter(|reason: &String| !reason.is_empty());
+    record_unreceived(
+        &ctx,
+        &file_name,
+        file_size,
+        PathBuf::new(),
+        files_json::TransferStatus::Rejected,
+        reason,
+    )
+    .await;
+    Ok("File offer denied and request closed".to_string())
+}
+
+/// Accept every pending file offer at once, each as `receiving_file_accept` would into
+/// `destination` (or the download directory), downloading them side by side. Offers that
+/// need confirming (over `max_incoming_size_mib`, or programs and scripts) are left pending
+/// to be looked at one by one. Each download reports its own progress and errors; this
+/// resolves once they've all finished, with a count of each outcome.
+pub async fn accept_all_pending(
+    ctx: AppContext,
+    destination: Option<String>,
+) -> Result<String, String> {
+    let mut ids = Vec::new();
+    let mut held = 0;
+    for id in ctx.transfers().offer_ids().await {
+        if unconfirmed(&ctx, &id, false, false).await.is_some() {
+            held += 1;
+        } else {
+            ids.push(id);
+        }
+    }
+    if ids.is_empty() && held == 0 {
+        return Err("No pending offers".to_string());
+    }
+    log_line!(
+        "[magic-wormhole][files][info] Accepting {} pending offers ({} need confirming)",
+        ids.len(),
+        held
+    );
+
+    let results = future::join_all(
+        ids.into_iter()
+            .map(|id| receiving_file_accept(id, ctx.clone(), destination.clone(), false, false)),
+    )
+    .await;
+    let failed = results.iter().filter(|result| result.is_err()).count();
+    let mut summary = format!("Accepted {} offer(s)", results.len() - failed);
+    if failed > 0 {
+        summary.push_str(&format!(", {} failed", failed));
+    }
+    if held > 0 {
+        summary.push_str(&format!(", {} left to confirm", held));
+    }
+    Ok(summary)
+}
+
+/// Deny every pending file offer, as `receiving_file_deny` would, each recorded with
+/// `reason`. Resolves with how many were denied.
+pub async fn deny_all_pending(ctx: AppContext, reason: Option<String>) -> Result<String, String> {
+    let ids = ctx.transfers().offer_ids().await;
+    if ids.is_empty() {
+        return Err("No pending offers".to_string());
+    }
+    let mut denied = 0;
+    for id in ids {
+        // Another caller may have answered it meanwhile
+        if receiving_file_deny(id, ctx.clone(), reason.clone())
+            .await
+            .is_ok()
+        {
+            denied += 1;
+        }
+    }
+    Ok(format!("Denied {} offer(s)", denied))
+}
+
+// Decline offer `id` if it's still unanswered after `offer_expiry_mins`, so a forgotten offer
+// doesn't hold the sender's wormhole open indefinitely. It's recorded like a denied one.
+async fn expire_offer_later(ctx: &AppContext, id: &str) {
+    let expiry_mins = ctx.settings().lock().await.get_offer_expiry_mins();
+    if expiry_mins == 0 {
+        return;
+    }
+    let (ctx, id) = (ctx.clone(), id.to_string());
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(expiry_mins.saturating_mul(60))).await;
+        // Gone if it was answered meanwhile
+        let Some(request) = ctx.transfers().take_offer(&id).await else {
+            return;
+        };
+        let (file_name, file_size) = (request.file_name(), request.file_size());
+        log_line!(
+            "[magic-wormhole][files][info] Offer {} ({}) expired after {} minutes unanswered",
+            id,
+            file_name,
+            expiry_mins
+        );
+        let _ = reject_request(&ctx, &id, request).await;
+        record_unreceived(
+            &ctx,
+            &file_name,
+            file_size,
+            PathBuf::new(),
+            files_json::TransferStatus::Rejected,
+            Some(format!("Expired after {} minutes unanswered", expiry_mins)),
+        )
+        .await;
+        let _ = ctx.emit(
+            events::OFFER_EXPIRED,
+            events::OfferExpired { id, file_name },
+        );
+    });
+}
+
+/// Decline the offer `id` without recording it, for offers wyrmhole turns down itself
+/// (e.g. the wrong part of a multi-part transfer).
+pub(crate) async fn discard_offer(ctx: &AppContext, id: &str) -> Result<(), String> {
+    let request = ctx.transfers().take_offer(id).await;
+    match request {
+        Some(request) => reject_request(ctx, id, request).await,
+        None => Err("No request found for this ID".to_string()),
+    }
+}
+
+async fn reject_request(
+    ctx: &AppContext,
+    id: &str,
+    request: Box<dyn IncomingOffer>,
+) -> Result<(), String> {
+    ctx.transfers().forget_secret(id).await;
+    if let Err(e) = request.reject().await {
+        log_line!(
+            "[magic-wormhole][files][error] Failed to close request: {}",
+            e
+        );
+        return Err(format!("Failed to close request: {}", e));
+    }
+    log_line!(
+        "[magic-wormhole][files][info] receiving_file_deny closed request with id: {}",
+        id
+    );
+    Ok(())
+}
+
+// Whether an offer of `file_size` bytes is over the `max_incoming_size_mib` limit.
+async fn over_size_limit(ctx: &AppContext, file_size: u64) -> bool {
+    let limit_mib = ctx.settings().lock().await.get_max_incoming_size_mib();
+    limit_mib > 0 && file_size > limit_mib.saturating_mul(1024 * 1024)
+}
+
+// The warning an offer named `file_name` gets, unless its extension is allowlisted.
+async fn content_warning(ctx: &AppContext, file_name: &str) -> Option<ContentWarning> {
+    let app_settings_lock = ctx.settings().lock().await;
+    content::warning_for_name(file_name, app_settings_lock.get_content_allowlist())
+}
+
+// Why offer `id` can't be accepted without a confirmation it doesn't have, if it can't.
+pub(crate) async fn unconfirmed(
+    ctx: &AppContext,
+    id: &str,
+    confirm_oversize: bool,
+    confirm_content: bool,
+) -> Option<String> {
+    if !confirm_oversize {
+        let offered_size = ctx.transfers().offer_size(id).await;
+        if let Some(size) = offered_size
+            && over_size_limit(ctx, size).await
+        {
+            let limit = ctx.settings().lock().await.get_max_incoming_size_mib();
+            return Some(format!(
+                "This file is {} MiB, over the {} MiB limit for incoming files; confirm to \
+                 receive it anyway",
+                size.div_ceil(1024 * 1024),
+                limit
+            ));
+        }
+    }
+
+    if !confirm_content
+        && let Some(file_name) = ctx.transfers().offer_name(id).await
+        && let Some(warning) = content_warning(ctx, &file_name).await
+    {
+        return Some(format!(
+            "{} is {} from an unknown sender; confirm to receive it anyway",
+            file_name,
+            warning.message()
+        ));
+    }
+    None
+}
+
+/// The folder `destination` names for `receiving_file_accept`, if any (blank means the
+/// download directory); it may not exist yet, but mustn't be a file.
+pub(crate) fn check_destination(destination: Option<&str>) -> Result<Option<PathBuf>, String> {
+    let destination = destination
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from);
+    if let Some(dir) = &destination
+        && dir.exists()
+        && !dir.is_dir()
+    {
+        return Err(format!("{} is not a folder", dir.display()));
+    }
+    Ok(destination)
+}
+
+/// Accept the offer `id`, saving it in `destination` when given instead of the download
+/// directory (for this transfer only; settings are left alone). Offers over
+/// `max_incoming_size_mib` are only accepted with `confirm_oversize`, and programs and
+/// scripts (see content.rs) only with `confirm_content`; without them they're left pending.
+pub async fn receiving_file_accept(
+    id: String,
+    ctx: AppContext,
+    destination: Option<String>,
+    confirm_oversize: bool,
+    confirm_content: bool,
+) -> Result<String, String> {
+    let destination = check_destination(destination.as_deref())?;
+
+    let text_offer = ctx.transfers().take_text_offer(&id).await;
+    if let Some((file_name, text)) = text_offer {
+        let path = save_text_offer(&ctx, &file_name, &text, destination.as_deref()).await?;
+        return Ok(format!("Message saved to {}", path.display()));
+    }
+
+    if let Some(refusal) = unconfirmed(&ctx, &id, confirm_oversize, confirm_content).await {
+        return Err(refusal);
+    }
+
+    // A read-only folder or unplugged drive is caught before the offer is taken, so it stays
+    // pending and can be accepted again with another `destination`.
+    let planned_dir = receive_dir(&*ctx.settings().lock().await, destination.as_deref());
+    if let Some(file_name) = ctx.transfers().offer_name(&id).await
+        && let Err(e) = check_writable(&planned_dir).await
+    {
+        let code = ErrorCode::DestinationUnwritable;
+        let msg = format!(
+            "{} ({}: {}) {}",
+            code.message(),
+            planned_dir.display(),
+            e,
+            code.hint()
+        );
+        log_line!("[magic-wormhole][files][error] {}", msg);
+        let _ = ctx.emit(
+            events::DOWNLOAD_ERROR,
+            events::DownloadError {
+                id: id.clone(),
+                file_name,
+                error: msg.clone(),
+                code: Some(code),
+            },
+        );
+        return Err(msg);
+    }
+
+    // Take the offer out up front so the map isn't locked for the whole download.
+    let request = ctx.transfers().take_offer(&id).await;
+    let Some(request) = request else {
+        return Err("No request found for this id".to_string());
+    };
+    let (offered_name, offered_size) = (request.file_name(), request.file_size());
+    let part = parts::PartName::parse(&offered_name);
+
+    metrics::record(&ctx, Metric::ReceiveStarted).await;
+    let result = match accept_offer(id.clone(), request, ctx.clone(), destination.clone()).await {
+        // Part 1 of a multi-part transfer: the rest follow on their own codes
+        Ok(path) if part.as_ref().is_some_and(|part| part.index == 1) => {
+            let part = part.unwrap();
+            parts::receive_remaining_parts(ctx.clone(), id, PathBuf::from(path), part, destination)
+                .await
+        }
+        result => result,
+    };
+    metrics::record_outcome(
+        &ctx,
+        Metric::ReceiveCompleted,
+        Metric::ReceiveFailed,
+        &result,
+    )
+    .await;
+    if let Err(e) = &result {
+        record_unreceived(
+            &ctx,
+            &offered_name,
+            offered_size,
+            planned_dir,
+            files_json::TransferStatus::of_error(e),
+            Some(e.clone()),
+        )
+        .await;
+    }
+    result
+}
+
+// Adds an offer that didn't arrive (declined, cancelled or failed) to the received history.
+// `download_url` is where it would have gone, if anywhere.
+async fn record_unreceived(
+    ctx: &AppContext,
+    file_name: &str,
+    file_size: u64,
+    download_url: PathBuf,
+    status: files_json::TransferStatus,
+    reason: Option<String>,
+) {
+    let (name, extension) = file_name
+        .rsplit_once('.')
+        .map(|(name, ext)| (name.to_string(), ext.to_string()))
+        .unwrap_or_else(|| (file_name.to_string(), String::new()));
+    let entry = files_json::ReceivedFile {
+        file_name: name,
+        file_size,
+        file_extension: extension,
+        download_url,
+        download_time: Local::now(),
+        connection_type: String::new(),
+        peer_address: "0.0.0.0:0".parse().unwrap(),
+        status,
+        reason,
+        checksum: None,
+        id: files_json::new_entry_id(),
+    };
+    if let Err(e) = files_json::add_received_file(ctx, entry).await {
+        log_line!("[magic-wormhole][files][warn] {}", e);
+    }
+}
+
+/// Accept the offer `offer_id` as a part of the multi-part download `download_id`, reporting
+/// progress under the latter. Returns where the part was saved.
+pub(crate) async fn accept_part(
+    ctx: AppContext,
+    offer_id: &str,
+    download_id: String,
+    destination: Option<PathBuf>,
+) -> Result<PathBuf, String> {
+    let request = ctx.transfers().take_offer(offer_id).await;
+    let Some(request) = request else {
+        return Err("No request found for this id".to_string());
+    };
+    ctx.transfers().forget_secret(offer_id).await;
+    accept_offer(download_id, request, ctx, destination)
+        .await
+        .map(PathBuf::from)
+}
+
+async fn accept_offer(
+    id: String,
+    request: Box<dyn IncomingOffer>,
+    ctx: AppContext,
+    destination: Option<PathBuf>,
+) -> Result<String, String> {
+    let started = Instant::now();
+    log_line!(
+        "[magic-wormhole][files][info] receiving_file_accept for id: {}, file: {}",
+        id,
+        request.file_name()
+    );
+
+    // Build the transit handler and get variables available for JSON metadata file.
+    // connection_type is mapped to String because I don't know if ConnectionType struct will be needed and serde doesn't have a default serializer for it.
+    let mut connection_type: String = String::new();
+    let mut peer_address: SocketAddr = "0.0.0.0:0".parse().unwrap();
+    let transit_handler = |info: TransitDetails| {
+        log_line!("[magic-wormhole][files][info] Transit info: {:?}", info);
+        metrics::record_connection(&ctx, &info.connection_type);
+        connection_type = info.connection_type;
+        peer_address = info.peer_address;
+    };
+
+    // Build the full file path by joining the directory and the filename
+    // Get the download directory from the shared settings
+    let app_settings_lock = ctx.settings().lock().await;
+    let download_dir = receive_dir(&app_settings_lock, destination.as_deref());
+    let template = app_settings_lock
+        .get_received_file_name_template()
+        .to_string();
+    let rate_limit = app_settings_lock.download_rate_limit();
+    drop(app_settings_lock); // Drop lock so settings can be read again later.
+    let offered_name = request.file_name();
+    let file_name_with_extension = apply_name_template(&template, &offered_name);
+
+    // Clone values needed for progress handler and error handling
+    let progress_id = id.clone();
+    let progress_file_name = file_name_with_extension.clone();
+    let progress_ctx = ctx.clone();
+    let error_ctx = ctx.clone();
+    let error_id = id.clone();
+    let error_file_name = file_name_with_extension.clone();
+
+    let watchdog = Watchdog::new(&ctx).await;
+    let activity = watchdog.activity();
+    let progress_handler = move |transferred: u64, total: u64| {
+        activity.touch();
+        let percentage = events::percentage(transferred, total);
+        let _ = progress_ctx.emit(
+            events::DOWNLOAD_PROGRESS,
+            events::DownloadProgress {
+                id: progress_id.clone(),
+                file_name: progress_file_name.clone(),
+                transferred,
+                total,
+                percentage,
+                status: if transferred < total {
+                    TransferState::Receiving
+                } else {
+                    TransferState::Verifying
+                },
+            },
+        );
+    };
+    let file_size = request.file_size();
+
+    // Check and create the download directory if it doesn't exist
+    if let Err(e) = tokio::fs::create_dir_all(&download_dir).await {
+        let error_msg =
+            errors::describe_io("Failed to create download directory", &e, &download_dir);
+        let _ = error_ctx.emit(
+            events::DOWNLOAD_ERROR,
+            events::DownloadError {
+                id: error_id.clone(),
+                file_name: error_file_name.clone(),
+                error: error_msg.clone(),
+                code: ErrorCode::classify_io(&e, &download_dir),
+            },
+        );
+        return Err(error_msg);
+    }
+
+    // Find a unique file path (adds number incrementer if file already exists). The file is
+    // created before anything else gets to run, so downloads of one name side by side (see
+    // `accept_all_pending`) can't pick the same path.
+    let file_path = find_unique_file_path(&download_dir, &file_name_with_extension);
+
+    // Get the final filename (may have been modified with incrementer)
+    let final_file_name_with_extension = file_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(&file_name_with_extension)
+        .to_string();
+
+    // Create the file at the full, correct path
+    let file = std::fs::File::create_new(&file_path)
+        .map(tokio::fs::File::from_std)
+        .map_err(|e| {
+            let error_msg = errors::describe_io("Failed to create file at path:", &e, &file_path);
+            let _ = error_ctx.emit(
+                events::DOWNLOAD_ERROR,
+                events::DownloadError {
+                    id: error_id.clone(),
+                    file_name: error_file_name.clone(),
+                    error: error_msg.clone(),
+                    code: ErrorCode::classify_io(&e, &file_path),
+                },
+            );
+            error_msg
+        })?;
+
+    // Pace the write stream to the download limit, as set or scheduled now and as it changes
+    // later; the peer slows down to match.
+    let mut throttled = ThrottledWriter::live(
+        file.compat_write(),
+        rate_limit,
+        live_rate_limit(&ctx, AppSettings::download_rate_limit),
+    );
+    // Checksummed on the way to disk, for the history and `verify_disk_writes`.
+    let mut hashing = HashingWriter::new(&mut throttled);
+
+    // Cancellation token for this download, kept with the active downloads
+    let cancel = CancellationToken::new();
+    ctx.transfers().add_download(&id, cancel.clone()).await;
+
+    let _ = ctx.emit(
+        events::DOWNLOAD_PROGRESS,
+        events::DownloadProgress {
+            id: id.clone(),
+            file_name: file_name_with_extension.clone(),
+            transferred: 0,
+            total: file_size,
+            percentage: 0,
+            status: TransferState::TransitNegotiating,
+        },
+    );
+
+    let accepted = request
+        .accept(
+            Box::new(transit_handler),
+            Box::new(progress_handler),
+            &mut hashing,
+            watchdog.guard(ctx.clone(), id.clone(), cancel.cancelled_owned().boxed()),
+        )
+        .await;
+    // Remove from active downloads, finished or not
+    ctx.transfers().remove_download(&id).await;
+    accepted.map_err(|e| {
+        let error_code = ErrorCode::classify(&e);
+        let error_message = format!(
+            "Error accepting file: {}",
+            watchdog.describe_failure(errors::describe(&e))
+        );
+        log_line!("[magic-wormhole][files][error] {}", error_message);
+        let _ = error_ctx.emit(
+            events::DOWNLOAD_ERROR,
+            events::DownloadError {
+                id: error_id.clone(),
+                file_name: error_file_name.clone(),
+                error: error_message.clone(),
+                code: error_code,
+            },
+        );
+        error_message
+    })?;
+
+    let checksum = match hashing.flush().await {
+        Ok(()) => hashing.checksum(),
+        Err(e) => {
+            let error_message = errors::describe_io("Failed to write", &e, &file_path);
+            log_line!("[magic-wormhole][files][error] {}", error_message);
+            let _ = ctx.emit(
+                events::DOWNLOAD_ERROR,
+                events::DownloadError {
+                    id: id.clone(),
+                    file_name: file_name_with_extension.clone(),
+                    error: error_message.clone(),
+                    code: ErrorCode::classify_io(&e, &file_path),
+                },
+            );
+            return Err(error_message);
+        }
+    };
+    let verified = if ctx.settings().lock().await.get_verify_disk_writes() {
+        Some(verify::verify_written(&file_path, &checksum).await)
+    } else {
+        None
+    };
+    let _ = ctx.emit(
+        events::VERIFICATION_COMPLETE,
+        events::VerificationComplete {
+            id: id.clone(),
+            direction: "received".to_string(),
+            file_name: file_name_with_extension.clone(),
+            checksum: checksum.clone(),
+            intact: verified.as_ref().map(Result::is_ok),
+        },
+    );
+    if let Some(Err(e)) = verified {
+        // The file stays where it is, so the user can look at what did land on disk
+        log_line!("[magic-wormhole][files][error] {}", e);
+        let _ = ctx.emit(
+            events::DOWNLOAD_ERROR,
+            events::DownloadError {
+                id: id.clone(),
+                file_name: file_name_with_extension.clone(),
+                error: e.clone(),
+                code: None,
+            },
+        );
+        return Err(e);
+    }
+
+    receive::run(
+        &ctx,
+        receive::Download {
+            id,
+            offered_name,
+            file_name: final_file_name_with_extension,
+            path: file_path,
+            download_dir,
+            size: file_size,
+            checksum,
+            connection_type,
+            peer_address,
+            started,
+        },
+    )
+    .await
+}
+
+pub async fn cancel_download(download_id: String, ctx: AppContext) -> Result<String, String> {
+    ctx.transfers().cancel_multipart(&download_id).await;
+    // Remove from active downloads and cancel its token
+    let Some(cancel) = ctx.transfers().remove_download(&download_id).await else {
+        return Err("No active download found for this ID".to_string());
+    };
+    cancel.cancel();
+    log_line!(
+        "[magic-wormhole][files][info] Cancelled download with id: {}",
+        download_id
+    );
+
+    // Don't emit error event for user cancellations - frontend handles dismissal directly
+    // The frontend's onDismiss callback will remove it from the UI immediately
+
+    Ok("Download cancelled".to_string())
+}
+
+pub async fn cancel_all_transfers(ctx: AppContext) -> Result<String, String> {
+    let cancelled = ctx.transfers().cancel_all().await;
+    for send_id in cancelled.sends {
+        log_line!(
+            "[magic-wormhole][files][info] Cancelled send with id (cancel all): {}",
+            send_id
+        );
+        let _ = ctx.emit(
+            events::SEND_ERROR,
+            events::SendError {
+                id: send_id,
+                file_name: "Transfer cancelled".to_string(),
+                error: "Transfer cancelled by user".to_string(),
+                code: None,
+            },
+        );
+    }
+    for download_id in cancelled.downloads {
+        log_line!(
+            "[magic-wormhole][files][info] Cancelled download with id (cancel all): {}",
+            download_id
+        );
+    }
+    for connection_id in cancelled.connections {
+        log_line!(
+            "[magic-wormhole][files][info] Cancelled connection with id (cancel all): {}",
+            connection_id
+        );
+    }
+
+    Ok("All active transfers and connections cancelled".to_string())
+}
+
+// Helper functions
+
+/// Values of the `transit_mode` setting: connect however works, only directly, or only
+/// through a relay.
+pub const TRANSIT_MODES: &[&str] = &["any", "direct", "relay"];
+
+// The transit abilities offered to the peer, from the `transit_mode` setting.
+async fn transit_abilities(ctx: &AppContext) -> transit::Abilities {
+    match ctx.settings().lock().await.get_transit_mode() {
+        "direct" => transit::Abilities::FORCE_DIRECT,
+        "relay" => transit::Abilities::FORCE_RELAY,
+        _ => transit::Abilities::ALL,
+    }
+}
+
+/// Build relay hints based on user configuration (`relays::configured_relays`), one per relay,
+/// falling back to DEFAULT_RELAY_SERVER. With several relays, they're pinged first and the
+/// hints ordered by latency; ones that don't answer go last, as the peer may still reach them.
+async fn build_relay_hints(ctx: &AppContext) -> Vec<transit::RelayHint> {
+    let mut configured = relays::configured_relays(&*ctx.settings().lock().await);
+    if configured.len() > 1 {
+        configured = relays::rank(configured, relays::PING_TIMEOUT)
+            .await
+            .into_iter()
+            .map(|r| r.url)
+            .collect();
+    }
+
+    let mut hints = Vec::new();
+    for custom in configured {
+        let hint = match custom.parse() {
+            Ok(url) => transit::RelayHint::from_urls(None, [url]).map_err(|e| format!("{}", e)),
+            Err(e) => Err(format!("{}", e)),
+        };
+        match hint {
+            Ok(hint) => hints.push(hint),
+            Err(e) => log_line!(
+                "[magic-wormhole][files][warn] Invalid relay URL in settings, skipping {}: {}",
+                custom,
+                e
+            ),
+        }
+    }
+
+    if hints.is_empty() {
+        let url = transit::DEFAULT_RELAY_SERVER.parse().unwrap();
+        hints.push(transit::RelayHint::from_urls(None, [url]).unwrap());
+    }
+    hints
+}
+
+/// Validate the currently configured relay URL or the default relay configuration.
+/// This is used by the Settings UI "Test relay" button.
+pub async fn test_relay_server(ctx: AppContext) -> Result<String, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    let user_relay = app_settings_lock
+        .get_relay_server_url()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    drop(app_settings_lock);
+
+    if let Some(custom) = user_relay {
+        // Validate custom relay URL
+        let url = custom
+            .parse()
+            .map_err(|e| format!("Invalid relay URL: {}", e))?;
+
+        transit::RelayHint::from_urls(None, [url])
+            .map_err(|e| format!("Invalid relay configuration: {}", e))?;
+
+        Ok(format!(
+            "Custom relay URL looks valid and will be used: {}",
+            custom
+        ))
+    } else {
+        // No custom URL: validate the default relay configuration and report it
+        let default_url = transit::DEFAULT_RELAY_SERVER
+            .parse()
+            .map_err(|e| format!("Internal error parsing default relay URL: {}", e))?;
+
+        transit::RelayHint::from_urls(None, [default_url])
+            .map_err(|e| format!("Default relay configuration appears invalid: {}", e))?;
+
+        Ok(format!(
+            "No custom relay configured. Default relay will be used: {}",
+            transit::DEFAULT_RELAY_SERVER
+        ))
+    }
+}
+
+// Start packaging a send's tarball as a `jobs` job in the background, so it runs while the
+// code waits for the receiver. If it fails for good (not cancelled, which the user already
+// knows about), the send is over.
+fn start_packaging<F>(
+    ctx: &AppContext,
+    send_id: &str,
+    tarball_name: &str,
+    tarball_path: &Path,
+    cancel: CancellationToken,
+    build: F,
+) -> JoinHandle<Result<u64, String>>
+where
+    F: Fn(CancellationToken) -> Result<u64, String> + Send + Sync + 'static,
+{
+    let ctx = ctx.clone();
+    let send_id = send_id.to_string();
+    let tarball_name = tarball_name.to_string();
+    let tarball_path = tarball_path.to_path_buf();
+    tokio::spawn(async move {
+        let result = jobs::run(&ctx, &send_id, &tarball_name, &tarball_path, cancel, build).await;
+        if let Err(e) = &result
+            && ctx.transfers().remove_send(&send_id).await.is_some()
+        {
+            let _ = ctx.emit(
+                events::SEND_ERROR,
+                events::SendError {
+                    id: send_id.clone(),
+                    file_name: tarball_name.clone(),
+                    error: e.clone(),
+                    code: None,
+                },
+            );
+        }
+        result
+    })
+}
+
+// Wait for `start_packaging` to finish, once the receiver has connected.
+async fn finish_packaging(packaging: JoinHandle<Result<u64, String>>) -> Result<u64, String> {
+    packaging
+        .await
+        .map_err(|e| format!("Failed to create tarball: {}", e))?
+}
+
+// The receiver never came: stop packaging and let it remove its tarball. The send's own
+// error is reported by the caller, so it's taken out of the active sends first.
+async fn abandon_packaging(
+    ctx: &AppContext,
+    send_id: &str,
+    cancel: &CancellationToken,
+    packaging: JoinHandle<Result<u64, String>>,
+) {
+    ctx.transfers().remove_send(send_id).await;
+    cancel.cancel();
+    let _ = packaging.await;
+}
+
+/// Why packaging stopped when its cancellation token was cancelled.
+const PACKAGING_CANCELLED: &str = "Transfer cancelled";
+
+// The tarball file, refusing further writes once `cancel` is cancelled so packaging stops at
+// its next write, even partway through a large file.
+pub(crate) struct CancellableWriter<W> {
+    inner: W,
+    cancel: CancellationToken,
+}
+
+impl<W> CancellableWriter<W> {
+    pub(crate) fn new(inner: W, cancel: CancellationToken) -> Self {
+        CancellableWriter { inner, cancel }
+    }
+}
+
+// Zip archives seek back to patch their headers.
+impl<W: std::io::Seek> std::io::Seek for CancellableWriter<W> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for CancellableWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.cancel.is_cancelled() {
+            return Err(std::io::Error::other(PACKAGING_CANCELLED));
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// How a tarball is built, from the `deterministic_archives` and `compression_level` settings.
+#[derive(Debug, Clone, Copy)]
+pub struct TarballOptions {
+    pub deterministic: bool,
+    /// gzip level, from 0 (store only, for already-compressed media) to 9.
+    pub compression_level: u32,
+}
+
+impl TarballOptions {
+    /// The options from the settings, with `compression` in place of `compression_level`
+    /// when a send gives its own.
+    pub(crate) async fn from_settings(ctx: &AppContext, compression: Option<u32>) -> Self {
+        let settings = ctx.settings().lock().await;
+        TarballOptions {
+            deterministic: settings.get_deterministic_archives(),
+            compression_level: compression
+                .unwrap_or(settings.get_compression_level())
+                .min(MAX_COMPRESSION_LEVEL),
+        }
+    }
+}
+
+/// Highest gzip level a tarball can be built at.
+pub const MAX_COMPRESSION_LEVEL: u32 = 9;
+
+/// Start a gzipped tarball at `output_path`, which stops with an error once `cancel` is
+/// cancelled. With `deterministic` every header gets the same mtime, owner and permissions,
+/// so together with `append_folder`'s sorted walk, unchanged content always archives to
+/// identical bytes.
+pub(crate) fn tarball_builder(
+    output_path: &Path,
+    options: TarballOptions,
+    cancel: CancellationToken,
+) -> Result<Builder<GzEncoder<CancellableWriter<std::fs::File>>>, String> {
+    let tar_gz = std::fs::File::create(output_path)
+        .map_err(|e| format!("Failed to create tarball file: {}", e))?;
+    let tar_gz = CancellableWriter {
+        inner: tar_gz,
+        cancel,
+    };
+
+    // The level defaults to fast: transfer is usually bottlenecked by network, not CPU.
+    // The gzip header's mtime is left at 0, so it doesn't vary between runs either.
+    let enc = GzEncoder::new(tar_gz, Compression::new(options.compression_level));
+    let mut tar = Builder::new(enc);
+    if options.deterministic {
+        tar.mode(HeaderMode::Deterministic);
+    }
+    Ok(tar)
+}
+
+/// Add the folder at `src` to `tar` as `dest`. When `sorted`, entries are visited in name
+/// order rather than the order the OS lists them in. Special files and broken symlinks are
+/// left out and listed in `skipped` (see special.rs); empty files are archived as usual.
+pub(crate) fn append_folder<W: std::io::Write>(
+    tar: &mut Builder<W>,
+    dest: &Path,
+    src: &Path,
+    sorted: bool,
+    skipped: &mut Vec<SkippedFile>,
+) -> std::io::Result<()> {
+    tar.append_dir(dest, src)?;
+    let mut entries = std::fs::read_dir(src)?.collect::<Result<Vec<_>, _>>()?;
+    if sorted {
+        entries.sort_by_key(|entry| entry.file_name());
+    }
+    for entry in entries {
+        let path = entry.path();
+        let entry_dest = dest.join(entry.file_name());
+        match special::classify(&path)? {
+            EntryKind::Dir => append_folder(tar, &entry_dest, &path, sorted, skipped)?,
+            EntryKind::File(_) => tar.append_path_with_name(&path, &entry_dest)?,
+            EntryKind::Skip(reason) => skipped.push(SkippedFile::new(&path, reason)),
+        }
+    }
+    Ok(())
+}
+
+/// Tell the frontend which entries of send `send_id` a folder walk left out, if any.
+pub(crate) fn report_skipped(ctx: &AppContext, send_id: &str, files: Vec<SkippedFile>) {
+    if files.is_empty() {
+        return;
+    }
+    log_line!(
+        "[magic-wormhole][files][warn] Left {} special file(s) out of send {}",
+        files.len(),
+        send_id
+    );
+    let _ = ctx.emit(
+        events::FILES_SKIPPED,
+        events::FilesSkipped {
+            id: send_id.to_string(),
+            files,
+        },
+    );
+}
+
+/// Helper function to create a tarball from a folder
+/// Wraps files in a folder with a friendly name (e.g., "4_files_wyrmhole_send")
+/// Entries left out are added to `skipped`.
+pub(crate) fn create_tarball_from_folder(
+    folder_path: &Path,
+    output_path: &Path,
+    folder_name: &str,
+    options: TarballOptions,
+    cancel: CancellationToken,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<u64, String> {
+    let result = build_tarball_from_folder(
+        folder_path,
+        output_path,
+        folder_name,
+        options,
+        cancel.clone(),
+        skipped,
+    );
+    packaging_result(result, output_path, &cancel)
+}
+
+// Body of `create_tarball_from_folder`.
+fn build_tarball_from_folder(
+    folder_path: &Path,
+    output_path: &Path,
+    folder_name: &str,
+    options: TarballOptions,
+    cancel: CancellationToken,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<u64, String> {
+    let mut tar = tarball_builder(output_path, options, cancel)?;
+
+    // Add the entire folder to the tarball with the friendly folder name
+    append_folder(
+        &mut tar,
+        Path::new(folder_name),
+        folder_path,
+        options.deterministic,
+        skipped,
+    )
+    .map_err(|e| format!("Failed to add folder to tarball: {}", e))?;
+
+    // Finish the tarball - this closes and flushes everything
+    tar.finish()
+        .map_err(|e| format!("Failed to finish tarball: {}", e))?;
+
+    // Get the file size after everything is written
+    // tar.finish() already closes and flushes the file, so we can safely read metadata
+    let metadata = std::fs::metadata(output_path)
+        .map_err(|e| format!("Failed to get tarball metadata: {}", e))?;
+
+    let size = metadata.len();
+    log_line!(
+        "[magic-wormhole][perf][files] Tarball created: {} bytes (folder: {})",
+        size,
+        folder_name
+    );
+
+    Ok(size)
+}
+
+/// Helper function to create a tarball directly from a list of file and directory paths.
+/// All entries are wrapped under a single top-level folder in the archive (`folder_name`).
+/// Entries left out of folders are added to `skipped`.
+fn create_tarball_from_paths(
+    paths: &[String],
+    output_path: &Path,
+    folder_name: &str,
+    options: TarballOptions,
+    cancel: CancellationToken,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<u64, String> {
+    let result = build_tarball_from_paths(
+        paths,
+        output_path,
+        folder_name,
+        options,
+        cancel.clone(),
+        skipped,
+    );
+    packaging_result(result, output_path, &cancel)
+}
+
+// Body of `create_tarball_from_paths`.
+fn build_tarball_from_paths(
+    paths: &[String],
+    output_path: &Path,
+    folder_name: &str,
+    options: TarballOptions,
+    cancel: CancellationToken,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<u64, String> {
+    let mut tar = tarball_builder(output_path, options, cancel.clone())?;
+
+    let mut paths = paths.to_vec();
+    if options.deterministic {
+        paths.sort();
+    }
+    for file_path in &paths {
+        if cancel.is_cancelled() {
+            return Err(PACKAGING_CANCELLED.to_string());
+        }
+        let src_path = Path::new(file_path);
+        if !src_path.exists() {
+            return Err(format!("File or folder does not exist: {}", file_path));
+        }
+
+        if src_path.is_dir() {
+            // Add the directory and its contents under folder_name/<dir_name>
+            let name = src_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("folder");
+            let dest_prefix = Path::new(folder_name).join(name);
+            append_folder(
+                &mut tar,
+                &dest_prefix,
+                src_path,
+                options.deterministic,
+                skipped,
+            )
+            .map_err(|e| format!("Failed to add directory to tarball: {}", e))?;
+        } else {
+            // Add a single file under folder_name/<file_name>
+            let name = src_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("file");
+            let dest = Path::new(folder_name).join(name);
+            tar.append_path_with_name(src_path, &dest)
+                .map_err(|e| format!("Failed to add file to tarball: {}", e))?;
+        }
+    }
+
+    tar.finish()
+        .map_err(|e| format!("Failed to finish tarball: {}", e))?;
+
+    let metadata = std::fs::metadata(output_path)
+        .map_err(|e| format!("Failed to get tarball metadata: {}", e))?;
+
+    let size = metadata.len();
+    log_line!(
+        "[magic-wormhole][perf][files] Tarball created from paths: {} bytes (folder: {})",
+        size,
+        folder_name
+    );
+
+    Ok(size)
+}
+
+// A failed or cancelled tarball is removed, not left behind in the temp directory.
+pub(crate) fn packaging_result(
+    result: Result<u64, String>,
+    output_path: &Path,
+    cancel: &CancellationToken,
+) -> Result<u64, String> {
+    if result.is_err() {
+        let _ = std::fs::remove_file(output_path);
+    }
+    if cancel.is_cancelled() {
+        log_line!(
+            "[magic-wormhole][files][info] Packaging {} cancelled",
+            output_path.display()
+        );
+        let _ = std::fs::remove_file(output_path);
+        return Err(PACKAGING_CANCELLED.to_string());
+    }
+    result
+}
+
+/// Split `file_name` into its name and extension at the last dot, keeping `.tar.gz` whole:
+/// "photos.tar.gz" is ("photos", "tar.gz"), not ("photos.tar", "gz").
+pub(crate) fn split_extension(file_name: &str) -> (&str, &str) {
+    if let Some(name) = file_name.strip_suffix(".tar.gz")
+        && !name.is_empty()
+    {
+        return (name, "tar.gz");
+    }
+    file_name.rsplit_once('.').unwrap_or((file_name, ""))
+}
+
+// Placeholders accepted in `received_file_name_template`. magic-wormhole doesn't identify
+// the sender, so there's nothing sender-specific to offer.
+const NAME_TEMPLATE_PLACEHOLDERS: [&str; 3] = ["{name}", "{date}", "{time}"];
+
+/// Check a received-file name template: only known placeholders, no path separators.
+pub fn validate_name_template(template: &str) -> Result<(), String> {
+    if template.contains(['/', '\\']) {
+        return Err("File name template can't contain path separators".to_string());
+    }
+    let mut rest = template.to_string();
+    for placeholder in NAME_TEMPLATE_PLACEHOLDERS {
+        rest = rest.replace(placeholder, "");
+    }
+    if rest.contains(['{', '}']) {
+        return Err(format!(
+            "Unknown placeholder in file name template; use {}",
+            NAME_TEMPLATE_PLACEHOLDERS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Name an incoming file with `template`, keeping its extension. An empty template keeps
+/// the offered name. "report.pdf" with "{date}_{name}" becomes "2025-01-31_report.pdf".
+fn apply_name_template(template: &str, file_name_with_extension: &str) -> String {
+    if template.is_empty() {
+        return file_name_with_extension.to_string();
+    }
+    // Split at the first dot so double extensions like .tar.gz stay intact.
+    let (name, ext) = match file_name_with_extension.split_once('.') {
+        Some((name, ext)) if !name.is_empty() => (name, ext),
+        _ => (file_name_with_extension, ""),
+    };
+    let now = Local::now();
+    let stem = template
+        .replace("{name}", name)
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H%M%S").to_string());
+    if ext.is_empty() {
+        stem
+    } else {
+        format!("{}.{}", stem, ext)
+    }
+}
+
+// Looks `limit` up in the settings for a throttled stream to follow as it runs; a stream
+// that finds the settings locked keeps its rate until the next look.
+fn live_rate_limit(ctx: &AppContext, limit: fn(&AppSettings) -> RateLimit) -> LimitLookup {
+    let ctx = ctx.clone();
+    Box::new(move || {
+        ctx.settings()
+            .try_lock()
+            .ok()
+            .map(|settings| limit(&settings))
+    })
+}
+
+/// Where a received file goes: `destination` when one was picked when accepting, otherwise the
+/// download directory (today's subfolder of it under `date_subfolders`).
+fn receive_dir(settings: &AppSettings, destination: Option<&Path>) -> PathBuf {
+    match destination {
+        Some(destination) => destination.to_path_buf(),
+        None if settings.get_date_subfolders() => {
+            dated_download_dir(settings.get_download_directory())
+        }
+        None => settings.get_download_directory().to_path_buf(),
+    }
+}
+
+/// Whether a file can be saved under `dir`: the nearest folder at or above it that exists
+/// (receiving creates the rest) must take a new file. Tried with a probe file, since
+/// permission bits miss read-only mounts and ACLs, and bounded by `INSPECT_TIMEOUT` for a
+/// network share that's gone away.
+async fn check_writable(dir: &Path) -> Result<(), String> {
+    let owned = dir.to_path_buf();
+    let probe = tokio::task::spawn_blocking(move || {
+        let existing = owned
+            .ancestors()
+            .find(|dir| dir.exists())
+            .filter(|dir| dir.is_dir())
+            .ok_or_else(|| "not a folder, or not reachable".to_string())?;
+        let probe = existing.join(format!(".wyrmhole-write-test-{}", Uuid::new_v4()));
+        std::fs::File::create_new(&probe).map_err(|e| e.to_string())?;
+        let _ = std::fs::remove_file(&probe);
+        Ok(())
+    });
+    match tokio::time::timeout(INSPECT_TIMEOUT, probe).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("timed out".to_string()),
+    }
+}
+
+/// Today's subfolder of the download directory: `<download_dir>/wyrmhole/YYYY-MM-DD`.
+fn dated_download_dir(download_dir: &Path) -> PathBuf {
+    download_dir
+        .join("wyrmhole")
+        .join(Local::now().format("%Y-%m-%d").to_string())
+}
+
+/// Windows paths longer than this only work in verbatim (`\\?\`) form.
+pub(crate) const MAX_PATH: usize = 260;
+
+/// The usual form of a Windows verbatim path: `\\?\C:\x` as `C:\x` and
+/// `\\?\UNC\server\share\x` as `\\server\share\x`. `None` for paths that aren't verbatim, and
+/// for verbatim ones that only work as they are: volume GUID paths (`\\?\Volume{...}\`) and
+/// anything longer than `MAX_PATH` once simplified.
+pub fn simplify_verbatim_path(path: &str) -> Option<String> {
+    let simple = if let Some(share) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", share)
+    } else {
+        let local = path.strip_prefix(r"\\?\")?;
+        let mut chars = local.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(drive), Some(':'), None | Some('\\')) if drive.is_ascii_alphabetic() => {
+                local.to_string()
+            }
+            _ => return None,
+        }
+    };
+    (simple.len() < MAX_PATH).then_some(simple)
+}
+
+/// `path` in its usual form on Windows (see `simplify_verbatim_path`), as the webview and the
+/// history show it; unchanged elsewhere.
+pub(crate) fn simplified(path: PathBuf) -> PathBuf {
+    if cfg!(windows)
+        && let Some(simple) = path.to_str().and_then(simplify_verbatim_path)
+    {
+        return PathBuf::from(simple);
+    }
+    path
+}
+
+// Whether nothing is at `path`. A path that can't be checked (a network share that dropped
+// out, no permission) counts as taken, so a file that couldn't be seen is never overwritten.
+fn is_free(path: &Path) -> bool {
+    matches!(path.try_exists(), Ok(false))
+}
+
+// Whether `dir` can be looked into at all. When it can't, the unique-name searches stop at
+// the first name instead of checking thousands, and creating it reports the actual error.
+fn is_reachable(dir: &Path) -> bool {
+    matches!(dir.try_exists(), Ok(true))
+}
+
+/// Like `find_unique_file_path`, for a folder: "photos", then "photos(1)", "photos(2)"...
+pub(crate) fn find_unique_dir_path(parent: &Path, dir_name: &str) -> PathBuf {
+    let base_path = parent.join(dir_name);
+    if is_free(&base_path) || !is_reachable(parent) {
+        return base_path;
+    }
+    (1..=10000)
+        .map(|counter| parent.join(format!("{}({})", dir_name, counter)))
+        .find(|path| is_free(path))
+        .unwrap_or(base_path)
+}
+
+/// Helper function to find a unique filename by appending a number if the file already exists
+pub(crate) fn find_unique_file_path(
+    download_dir: &Path,
+    file_name_with_extension: &str,
+) -> PathBuf {
+    let base_path = download_dir.join(file_name_with_extension);
+
+    // If the file doesn't exist (or the folder can't be read), return the original path
+    if is_free(&base_path) || !is_reachable(download_dir) {
+        return base_path;
+    }
+
+    // Split filename and extension, so "photos.tar.gz" becomes "photos(1).tar.gz"
+    let (file_name, extension) = match split_extension(file_name_with_extension) {
+        (name, "") => (name.to_string(), String::new()),
+        (name, ext) => (name.to_string(), format!(".{}", ext)),
+    };
+
+    // Try incrementing numbers until we find a unique filename
+    let mut counter = 1;
+    loop {
+        let new_file_name = format!("{}({}){}", file_name, counter, extension);
+        let new_path = download_dir.join(&new_file_name);
+
+        if is_free(&new_path) {
+            return new_path;
+        }
+
+        counter += 1;
+
+        // Safety check to prevent infinite loops (unlikely but good practice)
+        if counter > 10000 {
+            // Fall back to adding a timestamp
+            let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+            let fallback_name = format!("{}_{}{}", file_name, timestamp, extension);
+            return download_dir.join(fallback_name);
+        }
+    }
+}
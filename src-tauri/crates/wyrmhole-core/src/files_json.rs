@@ -0,0 +1,720 @@
+// This file creates and modifies the file receive and sent card history for wyrmhole.
+// Every read-modify-write of the history holds an advisory lock on `history.lock` in the
+// history directory, so a second instance or the CLI companion can't drop entries by
+// writing at the same time, and files are replaced by rename so readers never see half a file.
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use specta::Type;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use crate::context::AppContext;
+use crate::events;
+use crate::settings;
+use crate::verify;
+
+/// How a transfer in the history ended. Entries written before statuses were recorded are
+/// `completed`, as only finished transfers were kept then.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferStatus {
+    #[default]
+    Completed,
+    Cancelled,
+    Failed,
+    /// A declined offer (received history only).
+    Rejected,
+}
+
+impl TransferStatus {
+    /// `Cancelled` or `Failed` for a transfer that ended with `error`.
+    pub fn of_error(error: &str) -> Self {
+        if error.to_lowercase().contains("cancelled") {
+            TransferStatus::Cancelled
+        } else {
+            TransferStatus::Failed
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct ReceivedFile {
+    pub file_name: String,
+    pub file_size: u64,
+    pub file_extension: String,
+    pub download_url: PathBuf,
+    pub download_time: DateTime<Local>,
+    pub connection_type: String, // Cast from ConnectionType to String because serde doesn't have a serializer for ConnectionType and I don't know if it will even matter.
+    pub peer_address: SocketAddr,
+    #[serde(default)]
+    pub status: TransferStatus,
+    /// The error for cancelled and failed transfers, the reason given for rejected ones.
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// SHA-256 (hex) of the saved file; `None` for folders, extracted archives and entries
+    /// from before checksums were recorded.
+    #[serde(default)]
+    pub checksum: Option<String>,
+    /// Identifies the entry for commands like `send_back` and `verify_received_file`. Entries
+    /// from before ids were recorded get one the first time the history is loaded.
+    #[serde(default)]
+    pub id: String,
+}
+
+impl ReceivedFile {
+    /// Where the file was saved (or, for an extracted folder's entries, each extracted file).
+    pub fn saved_path(&self) -> PathBuf {
+        if self.file_extension.is_empty() {
+            self.download_url.join(&self.file_name)
+        } else {
+            self.download_url
+                .join(format!("{}.{}", self.file_name, self.file_extension))
+        }
+    }
+}
+
+/// A fresh id for a history entry.
+pub fn new_entry_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct SentFile {
+    pub file_name: String,
+    pub file_size: u64,
+    pub file_extension: String,
+    pub file_paths: Vec<PathBuf>,
+    pub send_time: DateTime<Local>,
+    pub connection_code: String,
+    // Sent entries from before this was recorded have no connection and a 0.0.0.0:0 address.
+    #[serde(default)]
+    pub connection_type: String,
+    #[serde(default = "unknown_peer_address")]
+    pub peer_address: SocketAddr,
+    #[serde(default)]
+    pub status: TransferStatus,
+    /// The error for cancelled and failed sends.
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Identifies the entry for commands like `verify_sent_entry`. Entries from before ids
+    /// were recorded get one the first time the history is loaded.
+    #[serde(default)]
+    pub id: String,
+    /// Each of `file_paths` as it was when sent, in the same order. Empty for unfinished
+    /// sends, entries from before snapshots were recorded, and when `sent_path_privacy`
+    /// doesn't keep full paths (there'd be nothing to check them against).
+    #[serde(default)]
+    pub snapshots: Vec<FileSnapshot>,
+    /// SHA-256 (hex) of the bytes sent, taken as they went out: the file, or for a folder or
+    /// bundle the archive it was sent as. `None` for unfinished sends and entries from before
+    /// checksums were recorded.
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+/// A sent file or folder at the time of the send: its modified time, and the SHA-256 (hex)
+/// of the file, or for a folder of its manifest (every file's relative path and hash).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+pub struct FileSnapshot {
+    pub modified: Option<DateTime<Local>>,
+    pub sha256: Option<String>,
+}
+
+impl FileSnapshot {
+    /// Snapshot `path` as it is now; both fields are `None` if it can't be read.
+    pub fn take(path: &Path) -> FileSnapshot {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(DateTime::<Local>::from);
+        let sha256 = if path.is_dir() {
+            crate::sync::Manifest::build(path)
+                .ok()
+                .and_then(|manifest| {
+                    serde_json::to_vec(&manifest.files)
+                        .ok()
+                        .map(|bytes| format!("{:x}", Sha256::digest(bytes)))
+                })
+        } else {
+            verify::hash_file(path).ok()
+        };
+        FileSnapshot { modified, sha256 }
+    }
+}
+
+/// How a sent path compares with its snapshot, as reported by `verify_sent_entry`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "kebab-case")]
+pub enum SnapshotState {
+    /// Same contents and modified time.
+    Unchanged,
+    /// Same contents, but saved again since (the modified time moved).
+    Touched,
+    /// The contents are different.
+    Changed,
+    /// Nothing is at the path any more.
+    Missing,
+    /// No snapshot was taken for this entry.
+    NotRecorded,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct SentPathCheck {
+    pub path: PathBuf,
+    pub state: SnapshotState,
+}
+
+fn unknown_peer_address() -> SocketAddr {
+    "0.0.0.0:0".parse().unwrap()
+}
+
+/// Values of the `sent_path_privacy` setting: keep sent paths in full, only their file names,
+/// or a salted hash of each (enough to tell whether a given file was sent, nothing more).
+pub const SENT_PATH_MODES: &[&str] = &["full", "names", "hashed"];
+
+/// `paths` as they should be written to the sent history under `sent_path_privacy`.
+pub(crate) async fn sent_paths_for_history(ctx: &AppContext, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut settings = ctx.settings().lock().await;
+    match settings.get_sent_path_privacy() {
+        "names" => paths
+            .iter()
+            .map(|path| path.file_name().map(PathBuf::from).unwrap_or_default())
+            .collect(),
+        "hashed" => {
+            if settings.get_sent_path_salt().is_empty() {
+                settings.set_sent_path_salt(uuid::Uuid::new_v4().simple().to_string());
+                if let Err(e) = ctx.save_settings(&settings) {
+                    log_line!(
+                        "[magic-wormhole][history][warn] Failed to save path salt: {}",
+                        e
+                    );
+                }
+            }
+            let salt = settings.get_sent_path_salt();
+            paths
+                .iter()
+                .map(|path| PathBuf::from(hash_sent_path(salt, path)))
+                .collect()
+        }
+        _ => paths,
+    }
+}
+
+/// Snapshots of `paths` for a finished send's history entry (see `SentFile::snapshots`).
+pub(crate) async fn snapshots_for_history(
+    ctx: &AppContext,
+    paths: Vec<PathBuf>,
+) -> Vec<FileSnapshot> {
+    if ctx.settings().lock().await.get_sent_path_privacy() != "full" {
+        return Vec::new();
+    }
+    tokio::task::spawn_blocking(move || paths.iter().map(|path| FileSnapshot::take(path)).collect())
+        .await
+        .unwrap_or_default()
+}
+
+/// Compare the paths of sent entry `id` with the snapshots taken when they were sent.
+pub async fn verify_sent_entry(ctx: &AppContext, id: &str) -> Result<Vec<SentPathCheck>, String> {
+    let entry = on_history(ctx, init_sent_files)
+        .await
+        .into_iter()
+        .find(|file| file.id == id)
+        .ok_or_else(|| "That sent entry is no longer in the history".to_string())?;
+    tokio::task::spawn_blocking(move || {
+        entry
+            .file_paths
+            .iter()
+            .enumerate()
+            .map(|(index, path)| {
+                let state = match entry.snapshots.get(index) {
+                    None => SnapshotState::NotRecorded,
+                    Some(_) if !path.exists() => SnapshotState::Missing,
+                    Some(then) => {
+                        let now = FileSnapshot::take(path);
+                        if now.sha256 != then.sha256 {
+                            SnapshotState::Changed
+                        } else if now.modified != then.modified {
+                            SnapshotState::Touched
+                        } else {
+                            SnapshotState::Unchanged
+                        }
+                    }
+                };
+                SentPathCheck {
+                    path: path.clone(),
+                    state,
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to check sent files: {}", e))
+}
+
+/// Re-hash the file saved for received entry `id` and check it against the checksum taken
+/// while it arrived, emitting `VERIFICATION_COMPLETE`. `Ok(false)` means the file on disk has
+/// been corrupted or altered since.
+pub async fn verify_received_file(ctx: &AppContext, id: &str) -> Result<bool, String> {
+    let entry = on_history(ctx, init_received_files)
+        .await
+        .into_iter()
+        .find(|file| file.id == id)
+        .ok_or_else(|| "That received entry is no longer in the history".to_string())?;
+    let expected = entry
+        .checksum
+        .clone()
+        .ok_or_else(|| "No checksum was recorded for this file".to_string())?;
+    let path = entry.saved_path();
+    let on_disk = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || verify::hash_file(&path).ok()
+    })
+    .await
+    .map_err(|e| format!("Failed to check received file: {}", e))?
+    .ok_or_else(|| format!("{} is no longer there", path.display()))?;
+    let intact = on_disk == expected;
+    if !intact {
+        log_line!(
+            "[magic-wormhole][history][warn] {} no longer matches its checksum",
+            path.display()
+        );
+    }
+    let _ = ctx.emit(
+        events::VERIFICATION_COMPLETE,
+        events::VerificationComplete {
+            id: id.to_string(),
+            direction: "received".to_string(),
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            checksum: expected,
+            intact: Some(intact),
+        },
+    );
+    Ok(intact)
+}
+
+/// How a "hashed" sent path is stored: `sha256:` and the hex digest of salt and path.
+pub fn hash_sent_path(salt: &str, path: &Path) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(path.to_string_lossy().as_bytes());
+    format!("sha256:{:x}", hasher.finalize())
+}
+
+const HISTORY_LOCK_FILE: &str = "history.lock";
+
+// Blocks until this process holds the history lock for `dir`; released when the returned
+// file is dropped. If locking isn't possible (e.g. a filesystem without lock support) the
+// failure is logged and the caller carries on unlocked, as before locking existed. Async
+// code goes through `on_history` rather than calling this directly.
+pub(crate) fn lock_history(dir: &Path) -> Option<fs::File> {
+    let lock = fs::create_dir_all(dir)
+        .and_then(|_| {
+            fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(dir.join(HISTORY_LOCK_FILE))
+        })
+        .and_then(|file| file.lock().map(|_| file));
+    match lock {
+        Ok(file) => Some(file),
+        Err(e) => {
+            log_line!(
+                "[magic-wormhole][history][warn] Failed to lock history in {}: {}",
+                dir.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+// Runs `f` on the blocking pool. Taking the history lock can wait for as long as another
+// instance holds it, and everything done under it is plain file IO, so neither may run on
+// an async worker.
+pub(crate) async fn on_history<T, F>(ctx: &AppContext, f: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce(&AppContext) -> T + Send + 'static,
+{
+    let ctx = ctx.clone();
+    match tokio::task::spawn_blocking(move || f(&ctx)).await {
+        Ok(value) => value,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+// Writes `contents` next to `path` and renames it into place.
+fn write_replacing(path: &Path, contents: &str) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)
+}
+
+// Initializes a received_files.json file.
+// It attempts to load existing file data; if unsuccessful, it creates an empty array.
+// Blocks on the history lock; from async code, run it through `on_history`.
+pub fn init_received_files(ctx: &AppContext) -> Vec<ReceivedFile> {
+    let _lock = lock_history(&ctx.history_dir());
+    load_received_files(ctx)
+}
+
+// Body of `init_received_files`; the caller holds the history lock.
+fn load_received_files(ctx: &AppContext) -> Vec<ReceivedFile> {
+    // Pulls the value from the settings.rs AppSettings struct instead of calling directly to the OS to allow user reassignments.
+    let received_files_path = settings::get_received_files_path(&ctx.history_dir());
+
+    // Attempt to load received files from the JSON file.
+    if received_files_path.exists() {
+        if let Ok(content) = fs::read_to_string(&received_files_path) {
+            if let Ok(mut files) = serde_json::from_str::<Vec<ReceivedFile>>(&content) {
+                log_line!(
+                    "[magic-wormhole][history][info] Received files loaded from {}",
+                    received_files_path.display()
+                );
+                // Give entries from before ids were recorded one, and keep it
+                if files.iter().any(|file| file.id.is_empty()) {
+                    for file in files.iter_mut().filter(|file| file.id.is_empty()) {
+                        file.id = new_entry_id();
+                    }
+                    if let Err(e) = save_received_files(&files, &received_files_path) {
+                        log_line!(
+                            "[magic-wormhole][history][warn] Failed to save received file ids: {}",
+                            e
+                        );
+                    }
+                }
+                return files;
+            } else {
+                log_line!(
+                    "[magic-wormhole][history][error] Failed to parse received_files.json; creating empty file at {}",
+                    received_files_path.display()
+                );
+            }
+        } else {
+            log_line!(
+                "[magic-wormhole][history][error] Failed to read received_files.json; creating empty file at {}",
+                received_files_path.display()
+            );
+        }
+    } else {
+        log_line!(
+            "[magic-wormhole][history][info] received_files.json not found; creating empty file at {}",
+            received_files_path.display()
+        );
+    }
+
+    // If loading failed or file didn't exist, create and save an empty list.
+    let default_files = Vec::new(); // Initialize as an empty vector (functions as an empty JSON array)
+    if let Err(e) = save_received_files(&default_files, &received_files_path) {
+        log_line!(
+            "[magic-wormhole][history][error] Failed to save initial empty received files: {}",
+            e
+        );
+    }
+    default_files
+}
+
+// Saves the current list of `ReceivedFile` structs to the `received_files.json` file.
+pub fn save_received_files(
+    files: &Vec<ReceivedFile>,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(files)?;
+    write_replacing(path, &json)?;
+    Ok(())
+}
+
+/// Where the newest completed receive with `checksum` was saved, if that file is still there.
+pub(crate) async fn find_received_checksum(ctx: &AppContext, checksum: &str) -> Option<PathBuf> {
+    let checksum = checksum.to_string();
+    on_history(ctx, move |ctx| {
+        init_received_files(ctx)
+            .into_iter()
+            .rev()
+            .filter(|file| {
+                file.status == TransferStatus::Completed
+                    && file.checksum.as_deref() == Some(checksum.as_str())
+            })
+            .map(|file| file.saved_path())
+            .find(|path| path.is_file())
+    })
+    .await
+}
+
+/// The saved file of completed received history entry `id`, if it's still there.
+pub async fn received_file_path(ctx: &AppContext, id: &str) -> Result<PathBuf, String> {
+    let file = on_history(ctx, init_received_files)
+        .await
+        .into_iter()
+        .find(|file| file.id == id)
+        .ok_or_else(|| format!("No received file with id {}", id))?;
+    if file.status != TransferStatus::Completed {
+        return Err(format!("{} was never saved", file.file_name));
+    }
+    let path = file.saved_path();
+    if !path.exists() {
+        return Err(format!("{} is no longer there", path.display()));
+    }
+    Ok(path)
+}
+
+/// Remove received history entry `id`. With `delete_file`, its saved file (or extracted
+/// folder) is moved to the OS trash first rather than deleted outright, so it can still be
+/// restored from there; if that fails the entry stays.
+pub async fn remove_received_entry(
+    ctx: &AppContext,
+    id: &str,
+    delete_file: bool,
+) -> Result<(), String> {
+    let file = on_history(ctx, init_received_files)
+        .await
+        .into_iter()
+        .find(|file| file.id == id)
+        .ok_or_else(|| format!("No received file with id {}", id))?;
+    let path = file.saved_path();
+    if delete_file && file.status == TransferStatus::Completed && path.exists() {
+        tokio::task::spawn_blocking(move || trash::delete(&path).map_err(|e| (path, e)))
+            .await
+            .map_err(|e| format!("Failed to move the file to the trash: {}", e))?
+            .map_err(|(path, e)| format!("Couldn't move {} to the trash: {}", path.display(), e))?;
+    }
+
+    let id = id.to_string();
+    on_history(ctx, move |ctx| {
+        let history_dir = ctx.history_dir();
+        let _lock = lock_history(&history_dir);
+        let mut files = load_received_files(ctx);
+        files.retain(|file| file.id != id);
+        save_received_files(&files, &settings::get_received_files_path(&history_dir))
+            .map_err(|e| format!("Failed to save received files: {}", e))
+    })
+    .await
+}
+
+// Adds a new received file to the list and saves the updated list.
+pub async fn add_received_file(
+    ctx: &AppContext,
+    new_file: ReceivedFile,
+) -> Result<Vec<ReceivedFile>, String> {
+    on_history(ctx, move |ctx| append_received_file(ctx, new_file)).await
+}
+
+fn append_received_file(
+    ctx: &AppContext,
+    new_file: ReceivedFile,
+) -> Result<Vec<ReceivedFile>, String> {
+    let history_dir = ctx.history_dir();
+    let _lock = lock_history(&history_dir);
+    let path = settings::get_received_files_path(&history_dir);
+    let mut files = load_received_files(ctx); // Load current files
+
+    files.push(new_file.clone()); // Add the new file
+
+    match save_received_files(&files, &path) {
+        Ok(_) => {
+            // Emit event to notify frontend
+            let _ = ctx.emit(
+                events::RECEIVED_FILE_ADDED,
+                events::ReceivedFileAdded { file: new_file },
+            );
+            Ok(files) // Return updated list on success
+        }
+        Err(e) => Err(format!("Failed to save received files: {}", e)),
+    }
+}
+
+// Adds several received files with a single write and a single event, so extracting a
+// folder of hundreds of files doesn't rewrite the history once per file.
+pub async fn add_received_files(
+    ctx: &AppContext,
+    new_files: Vec<ReceivedFile>,
+) -> Result<Vec<ReceivedFile>, String> {
+    on_history(ctx, move |ctx| append_received_files(ctx, new_files)).await
+}
+
+fn append_received_files(
+    ctx: &AppContext,
+    new_files: Vec<ReceivedFile>,
+) -> Result<Vec<ReceivedFile>, String> {
+    let history_dir = ctx.history_dir();
+    let _lock = lock_history(&history_dir);
+    let path = settings::get_received_files_path(&history_dir);
+    let mut files = load_received_files(ctx); // Load current files
+    if new_files.is_empty() {
+        return Ok(files);
+    }
+
+    files.extend(new_files.iter().cloned());
+
+    match save_received_files(&files, &path) {
+        Ok(_) => {
+            let _ = ctx.emit(
+                events::RECEIVED_FILES_ADDED,
+                events::ReceivedFilesAdded { files: new_files },
+            );
+            Ok(files)
+        }
+        Err(e) => Err(format!("Failed to save received files: {}", e)),
+    }
+}
+
+/// `path` if it lies in the download directory or a folder wyrmhole has received into,
+/// so hosts only open or reveal files that came through wyrmhole.
+pub async fn check_received_location(ctx: &AppContext, path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    if path
+        .components()
+        .any(|c| c == std::path::Component::ParentDir)
+    {
+        return Err("Invalid path".to_string());
+    }
+    let download_dir = ctx.settings().lock().await.get_download_directory().clone();
+    // Only completed entries: the others may point nowhere (an empty path matches everything).
+    let received = path.starts_with(&download_dir)
+        || on_history(ctx, init_received_files)
+            .await
+            .iter()
+            .any(|file| {
+                file.status == TransferStatus::Completed && path.starts_with(&file.download_url)
+            });
+    if received {
+        Ok(path)
+    } else {
+        Err("Not a received file".to_string())
+    }
+}
+
+pub async fn get_received_files_json_data(ctx: &AppContext) -> Result<Vec<ReceivedFile>, String> {
+    on_history(ctx, |ctx| {
+        let history_dir = ctx.history_dir();
+        let _lock = lock_history(&history_dir);
+        let received_files_path = settings::get_received_files_path(&history_dir);
+        // Loading first gives older entries their ids
+        load_received_files(ctx);
+        // Read the file contents into a string
+        let contents = fs::read_to_string(&received_files_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+
+        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse JSON: {}", e))
+    })
+    .await
+}
+
+// Initializes a sent_files.json file.
+// It attempts to load existing file data; if unsuccessful, it creates an empty array.
+// Blocks on the history lock; from async code, run it through `on_history`.
+pub fn init_sent_files(ctx: &AppContext) -> Vec<SentFile> {
+    let _lock = lock_history(&ctx.history_dir());
+    load_sent_files(ctx)
+}
+
+// Body of `init_sent_files`; the caller holds the history lock.
+fn load_sent_files(ctx: &AppContext) -> Vec<SentFile> {
+    let sent_files_path = settings::get_sent_files_path(&ctx.history_dir());
+
+    // Attempt to load sent files from the JSON file.
+    if sent_files_path.exists() {
+        if let Ok(content) = fs::read_to_string(&sent_files_path) {
+            if let Ok(mut files) = serde_json::from_str::<Vec<SentFile>>(&content) {
+                log_line!(
+                    "[magic-wormhole][history][info] Sent files loaded from {}",
+                    sent_files_path.display()
+                );
+                // Give entries from before ids were recorded one, and keep it
+                if files.iter().any(|file| file.id.is_empty()) {
+                    for file in files.iter_mut().filter(|file| file.id.is_empty()) {
+                        file.id = new_entry_id();
+                    }
+                    if let Err(e) = save_sent_files(&files, &sent_files_path) {
+                        log_line!(
+                            "[magic-wormhole][history][warn] Failed to save sent file ids: {}",
+                            e
+                        );
+                    }
+                }
+                return files;
+            } else {
+                log_line!(
+                    "[magic-wormhole][history][error] Failed to parse sent_files.json; creating empty file at {}",
+                    sent_files_path.display()
+                );
+            }
+        } else {
+            log_line!(
+                "[magic-wormhole][history][error] Failed to read sent_files.json; creating empty file at {}",
+                sent_files_path.display()
+            );
+        }
+    } else {
+        log_line!(
+            "[magic-wormhole][history][info] sent_files.json not found; creating empty file at {}",
+            sent_files_path.display()
+        );
+    }
+
+    // If loading failed or file didn't exist, create and save an empty list.
+    let default_files = Vec::new();
+    if let Err(e) = save_sent_files(&default_files, &sent_files_path) {
+        log_line!(
+            "[magic-wormhole][history][error] Failed to save initial empty sent files: {}",
+            e
+        );
+    }
+    default_files
+}
+
+// Saves the current list of `SentFile` structs to the `sent_files.json` file.
+pub fn save_sent_files(
+    files: &Vec<SentFile>,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(files)?;
+    write_replacing(path, &json)?;
+    Ok(())
+}
+
+// Adds a new sent file to the list and saves the updated list.
+pub async fn add_sent_file(ctx: &AppContext, new_file: SentFile) -> Result<Vec<SentFile>, String> {
+    on_history(ctx, move |ctx| append_sent_file(ctx, new_file)).await
+}
+
+fn append_sent_file(ctx: &AppContext, new_file: SentFile) -> Result<Vec<SentFile>, String> {
+    let history_dir = ctx.history_dir();
+    let _lock = lock_history(&history_dir);
+    let path = settings::get_sent_files_path(&history_dir);
+    let mut files = load_sent_files(ctx); // Load current files
+
+    files.push(new_file.clone()); // Add the new file
+
+    match save_sent_files(&files, &path) {
+        Ok(_) => {
+            // Emit event to notify frontend
+            let _ = ctx.emit(
+                events::SENT_FILE_ADDED,
+                events::SentFileAdded { file: new_file },
+            );
+            Ok(files) // Return updated list on success
+        }
+        Err(e) => Err(format!("Failed to save sent files: {}", e)),
+    }
+}
+
+pub async fn get_sent_files_json_data(ctx: &AppContext) -> Result<Vec<SentFile>, String> {
+    on_history(ctx, |ctx| {
+        let history_dir = ctx.history_dir();
+        let _lock = lock_history(&history_dir);
+        let sent_files_path = settings::get_sent_files_path(&history_dir);
+        // Read the file contents into a string
+        let contents = fs::read_to_string(&sent_files_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+
+        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse JSON: {}", e))
+    })
+    .await
+}
@@ -0,0 +1,282 @@
+// This file implements post-receive rules: after a file is received, every rule whose pattern
+// matches its name runs in order, e.g. "*.pdf" -> move to ~/Documents/Invoices, then open it.
+// Rules live in the `receive_hooks` setting and are run by receive.rs, on the file as it was
+// saved: archives unpacked by `auto_extract_tarballs` and duplicates removed by
+// `skip_duplicate_receives` finish before the rules and are left alone.
+//
+// File names come from the peer, so commands are never run through a shell: the template is
+// split into arguments first and the placeholders are substituted into each one. `{name}` is
+// still whatever the sender chose, so an argument starting with it gets `./` in front (a
+// name like `-rf` would otherwise be read as an option) and commands run in the file's folder.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::files;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum HookAction {
+    /// Move the file into the `target` folder ("~/" is the home directory).
+    MoveTo,
+    /// Run the `target` command in the file's folder; `{path}`, `{name}` and `{dir}` are
+    /// replaced in each argument. `{name}` comes from the sender: an argument starting with it
+    /// becomes `./<name>`, so it can't pass as an option.
+    RunCommand,
+    /// Open the file with the `target` application.
+    OpenWith,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+pub struct ReceiveHook {
+    /// Comma-separated file name globs (`*` and `?`, case-insensitive), e.g. "*.jpg, *.png".
+    pub pattern: String,
+    pub action: HookAction,
+    pub target: String,
+}
+
+impl ReceiveHook {
+    pub fn matches(&self, file_name: &str) -> bool {
+        self.pattern
+            .split(',')
+            .map(str::trim)
+            .filter(|glob| !glob.is_empty())
+            .any(|glob| glob_matches(glob, file_name))
+    }
+}
+
+/// Case-insensitive glob match where `*` matches any run of characters and `?` any one.
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // The last `*` seen and where in `name` it's currently matched up to, for backtracking.
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Check a rule before it's saved.
+pub fn validate(hook: &ReceiveHook) -> Result<(), String> {
+    if hook.pattern.split(',').all(|glob| glob.trim().is_empty()) {
+        return Err("A rule needs a file pattern, e.g. *.pdf".to_string());
+    }
+    if hook.target.trim().is_empty() {
+        return Err(match hook.action {
+            HookAction::MoveTo => format!("Choose a folder to move {} files to", hook.pattern),
+            HookAction::RunCommand => format!("Enter a command to run for {}", hook.pattern),
+            HookAction::OpenWith => format!("Choose an app to open {} with", hook.pattern),
+        });
+    }
+    if hook.action == HookAction::RunCommand && split_command(&hook.target).is_empty() {
+        return Err(format!("Enter a command to run for {}", hook.pattern));
+    }
+    Ok(())
+}
+
+/// `target` with a leading "~/" replaced by the home directory.
+pub fn expand_home(target: &str) -> PathBuf {
+    match target.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => PathBuf::from(target),
+    }
+}
+
+// Whitespace-separated arguments; double quotes group an argument containing spaces.
+fn split_command(template: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+    for c in template.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// The command line `template` runs for the file at `path`, as program and arguments.
+pub fn command_args(template: &str, path: &Path) -> Vec<String> {
+    let path_str = path.to_string_lossy();
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let dir = path
+        .parent()
+        .map(|d| d.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let placeholders = [
+        ("{path}", path_str.as_ref()),
+        ("{name}", name.as_str()),
+        ("{dir}", dir.as_str()),
+    ];
+    split_command(template)
+        .into_iter()
+        .map(|arg| {
+            let arg = match arg.strip_prefix("{name}") {
+                Some(rest) => format!("./{{name}}{}", rest),
+                None => arg,
+            };
+            substitute(&arg, &placeholders)
+        })
+        .collect()
+}
+
+// Replace the placeholders in one pass over `arg`, so text that came from a value (a name
+// like `{dir}x`) is never read as a placeholder itself.
+fn substitute(arg: &str, placeholders: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while !rest.is_empty() {
+        match placeholders
+            .iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder))
+        {
+            Some((placeholder, value)) => {
+                out.push_str(value);
+                rest = &rest[placeholder.len()..];
+            }
+            None => {
+                let c = rest.chars().next().unwrap_or_default();
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    out
+}
+
+fn move_into(path: &Path, folder: &Path) -> Result<PathBuf, String> {
+    fs::create_dir_all(folder)
+        .map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let destination = files::find_unique_file_path(folder, &name);
+    // Across drives a rename fails, so fall back to copying.
+    if fs::rename(path, &destination).is_err() {
+        fs::copy(path, &destination)
+            .map_err(|e| format!("Failed to move to {}: {}", folder.display(), e))?;
+        let _ = fs::remove_file(path);
+    }
+    Ok(destination)
+}
+
+// Start `program` without waiting for it; a thread reaps it and logs a failure exit.
+fn spawn_detached(mut command: Command, description: String) -> Result<(), String> {
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", description, e))?;
+    std::thread::spawn(move || {
+        if let Ok(status) = child.wait()
+            && !status.success()
+        {
+            log_line!(
+                "[magic-wormhole][hooks][warn] {} exited with {}",
+                description,
+                status
+            );
+        }
+    });
+    Ok(())
+}
+
+fn open_with(app: &str, path: &Path) -> Result<(), String> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg("-a").arg(app);
+        command
+    } else {
+        Command::new(expand_home(app))
+    };
+    command.arg(path);
+    spawn_detached(command, app.to_string())
+}
+
+fn run_one(hook: &ReceiveHook, path: &Path) -> Result<PathBuf, String> {
+    match hook.action {
+        HookAction::MoveTo => move_into(path, &expand_home(hook.target.trim())),
+        HookAction::RunCommand => {
+            let args = command_args(&hook.target, path);
+            let (program, rest) = args.split_first().ok_or("Empty command")?;
+            let mut command = Command::new(expand_home(program));
+            command.args(rest);
+            // Where `./<name>` arguments resolve.
+            if let Some(dir) = path.parent() {
+                command.current_dir(dir);
+            }
+            spawn_detached(command, program.clone())?;
+            Ok(path.to_path_buf())
+        }
+        HookAction::OpenWith => {
+            open_with(hook.target.trim(), path)?;
+            Ok(path.to_path_buf())
+        }
+    }
+}
+
+/// Run every rule in `hooks` that matches the received file at `path`, returning where the
+/// file ended up. A failing rule is logged and skipped. Blocking; call from `spawn_blocking`.
+pub fn run_receive_hooks(hooks: &[ReceiveHook], path: &Path) -> PathBuf {
+    let mut path = path.to_path_buf();
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    for hook in hooks.iter().filter(|hook| hook.matches(&file_name)) {
+        match run_one(hook, &path) {
+            Ok(new_path) => {
+                log_line!(
+                    "[magic-wormhole][hooks][info] {:?} rule for {} applied to {}",
+                    hook.action,
+                    hook.pattern,
+                    new_path.display()
+                );
+                path = new_path;
+            }
+            Err(e) => log_line!(
+                "[magic-wormhole][hooks][error] {:?} rule for {} failed: {}",
+                hook.action,
+                hook.pattern,
+                e
+            ),
+        }
+    }
+    path
+}
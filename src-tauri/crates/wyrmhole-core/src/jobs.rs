@@ -0,0 +1,212 @@
+// This file runs the packaging step of folder and bundle sends as jobs. Each job has its own
+// id, reports the tarball's size as it grows, and can be cancelled or, once it has failed,
+// retried: the send it belongs to keeps its mailbox and receiver while the job waits, so a
+// missing file or full disk doesn't burn a code that's already been shared.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::context::AppContext;
+use crate::events::{self, JobState};
+use crate::files;
+
+// How often a running job reports its progress.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A packaging job, kept by the `TransferManager`.
+pub(crate) struct Job {
+    send_id: String,
+    name: String,
+    state: JobState,
+    output_path: PathBuf,
+    error: Option<String>,
+    retries: mpsc::UnboundedSender<()>,
+}
+
+impl Job {
+    // The job as reported, still without `written`, and the file to measure it from. Taken
+    // under the jobs lock, so the filesystem is only touched once that's released.
+    fn describe(&self, job_id: &str) -> (events::PackagingJob, PathBuf) {
+        let job = events::PackagingJob {
+            job_id: job_id.to_string(),
+            send_id: self.send_id.clone(),
+            name: self.name.clone(),
+            state: self.state,
+            written: 0,
+            error: self.error.clone(),
+        };
+        (job, self.output_path.clone())
+    }
+}
+
+// Fill in how much of `output_path` has been written so far.
+async fn with_written(
+    (mut job, output_path): (events::PackagingJob, PathBuf),
+) -> events::PackagingJob {
+    job.written = tokio::fs::metadata(&output_path)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    job
+}
+
+// Public API functions - called by the host (Tauri commands, headless JSON calls, the CLI)
+
+/// Every packaging job that's running or waiting for a retry.
+pub async fn list_jobs(ctx: &AppContext) -> Vec<events::PackagingJob> {
+    let described: Vec<_> = ctx
+        .transfers()
+        .with_jobs(|jobs| jobs.iter().map(|(id, job)| job.describe(id)).collect())
+        .await;
+    let mut listed = Vec::with_capacity(described.len());
+    for job in described {
+        listed.push(with_written(job).await);
+    }
+    listed.sort_by(|a, b| a.send_id.cmp(&b.send_id));
+    listed
+}
+
+/// Package again after a failure, with the same send and code.
+pub async fn retry_job(ctx: &AppContext, job_id: String) -> Result<(), String> {
+    ctx.transfers()
+        .with_jobs(|jobs| {
+            let job = jobs
+                .get(&job_id)
+                .ok_or_else(|| format!("No packaging job with id {}", job_id))?;
+            if job.state != JobState::Failed {
+                return Err("Only a failed packaging job can be retried".to_string());
+            }
+            job.retries
+                .send(())
+                .map_err(|_| "Packaging job has already finished".to_string())
+        })
+        .await
+}
+
+/// Stop a job and cancel the send it was packaging for.
+pub async fn cancel_job(job_id: String, ctx: AppContext) -> Result<String, String> {
+    let send_id = ctx
+        .transfers()
+        .with_jobs(|jobs| jobs.get(&job_id).map(|job| job.send_id.clone()))
+        .await
+        .ok_or_else(|| format!("No packaging job with id {}", job_id))?;
+    files::cancel_send(send_id, ctx).await
+}
+
+// Internal functions
+
+/// Run `build` (which writes `output_path`) as a job for send `send_id`. A failure waits up
+/// to `packaging_retry_secs` for `retry_job`, running `build` again if it comes; cancelling
+/// `cancel` (the send's token) stops it either way.
+pub(crate) async fn run<F>(
+    ctx: &AppContext,
+    send_id: &str,
+    name: &str,
+    output_path: &Path,
+    cancel: CancellationToken,
+    build: F,
+) -> Result<u64, String>
+where
+    F: Fn(CancellationToken) -> Result<u64, String> + Send + Sync + 'static,
+{
+    let job_id = Uuid::new_v4().to_string();
+    let (retries, mut retry_rx) = mpsc::unbounded_channel();
+    let job = Job {
+        send_id: send_id.to_string(),
+        name: name.to_string(),
+        state: JobState::Running,
+        output_path: output_path.to_path_buf(),
+        error: None,
+        retries,
+    };
+    ctx.transfers()
+        .with_jobs(|jobs| jobs.insert(job_id.clone(), job))
+        .await;
+    let build = Arc::new(build);
+    let result = loop {
+        set_state(ctx, &job_id, JobState::Running, None).await;
+        let started = Instant::now();
+        let mut task = tokio::task::spawn_blocking({
+            let build = build.clone();
+            let cancel = cancel.clone();
+            move || build(cancel)
+        });
+        let attempt = loop {
+            tokio::select! {
+                joined = &mut task => {
+                    break joined.unwrap_or_else(|e| Err(format!("Failed to create tarball: {}", e)));
+                }
+                _ = tokio::time::sleep(PROGRESS_INTERVAL) => emit(ctx, &job_id).await,
+            }
+        };
+        let error = match attempt {
+            Ok(size) => {
+                log_line!(
+                    "[magic-wormhole][perf][jobs] Packaged {} ({} bytes) in {:?}",
+                    name,
+                    size,
+                    started.elapsed()
+                );
+                break Ok(size);
+            }
+            Err(error) if cancel.is_cancelled() => break Err(error),
+            Err(error) => error,
+        };
+
+        log_line!(
+            "[magic-wormhole][jobs][warn] Packaging {} failed: {}",
+            name,
+            error
+        );
+        // Without a retry window nothing would come of offering one.
+        let window = ctx.settings().lock().await.get_packaging_retry_secs();
+        if window == 0 {
+            break Err(error);
+        }
+        set_state(ctx, &job_id, JobState::Failed, Some(error.clone())).await;
+        let retry = tokio::select! {
+            retry = tokio::time::timeout(Duration::from_secs(window), retry_rx.recv()) => {
+                matches!(retry, Ok(Some(())))
+            }
+            _ = cancel.cancelled() => false,
+        };
+        if !retry {
+            break Err(error);
+        }
+        log_line!("[magic-wormhole][jobs][info] Retrying packaging {}", name);
+    };
+
+    if result.is_err() && cancel.is_cancelled() {
+        set_state(ctx, &job_id, JobState::Cancelled, None).await;
+    } else if result.is_ok() {
+        set_state(ctx, &job_id, JobState::Done, None).await;
+    }
+    ctx.transfers().with_jobs(|jobs| jobs.remove(&job_id)).await;
+    result
+}
+
+async fn set_state(ctx: &AppContext, job_id: &str, state: JobState, error: Option<String>) {
+    ctx.transfers()
+        .with_jobs(|jobs| {
+            if let Some(job) = jobs.get_mut(job_id) {
+                job.state = state;
+                job.error = error;
+            }
+        })
+        .await;
+    emit(ctx, job_id).await;
+}
+
+async fn emit(ctx: &AppContext, job_id: &str) {
+    let job = ctx
+        .transfers()
+        .with_jobs(|jobs| jobs.get(job_id).map(|job| job.describe(job_id)))
+        .await;
+    if let Some(job) = job {
+        let _ = ctx.emit(events::PACKAGING_JOB, with_written(job).await);
+    }
+}
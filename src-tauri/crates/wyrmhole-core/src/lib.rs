@@ -0,0 +1,49 @@
+// wyrmhole-core: the transfer engine behind the wyrmhole app, free of any Tauri dependency.
+// Hosts build an `AppContext` with their own event sink and settings store and call the
+// public API functions in `files`, `settings` and `files_json`.
+
+/// `eprintln!` that also keeps the line for crash reports (see `crash`).
+#[macro_export]
+macro_rules! log_line {
+    ($($arg:tt)*) => {{
+        let line = format!($($arg)*);
+        eprintln!("{}", line);
+        $crate::crash::remember_log_line(line);
+    }};
+}
+
+pub mod api;
+pub mod archive;
+pub mod cache;
+pub mod content;
+pub mod context;
+pub mod crash;
+pub mod delta;
+pub mod demo;
+pub mod errors;
+pub mod events;
+pub mod extract;
+pub mod files;
+pub mod files_json;
+pub mod hooks;
+pub mod jobs;
+pub mod metrics;
+pub mod parts;
+pub mod profiles;
+pub mod receive;
+pub mod relays;
+pub mod report;
+pub mod sensitive;
+pub mod settings;
+pub mod share;
+pub mod sizes;
+pub mod special;
+pub mod stream;
+pub mod sync;
+pub mod throttle;
+pub mod transfers;
+pub mod updates;
+pub mod verify;
+pub mod watchdog;
+pub mod webhook;
+pub mod wormhole;
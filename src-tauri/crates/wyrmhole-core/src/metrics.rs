@@ -0,0 +1,140 @@
+// This file keeps wyrmhole's opt-in usage metrics: a handful of coarse counters (transfers
+// started/completed/failed, direct vs relay connections) stored locally in usage_metrics.json.
+// Nothing is ever uploaded; users can export the file to share it when asked while debugging.
+// Recording is a no-op unless `usage_metrics_enabled` is on in settings.
+
+use chrono::prelude::*;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs;
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+use crate::context::{AppContext, AppPaths};
+
+// Serializes read-modify-write of the counters file across concurrent transfers.
+static METRICS_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// The counters kept on disk. Contains no file names, codes, paths or addresses.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Type)]
+pub struct UsageMetrics {
+    /// When counting started (first recorded event after enabling or resetting).
+    pub since: Option<DateTime<Local>>,
+    pub sends_started: u64,
+    pub sends_completed: u64,
+    pub sends_failed: u64,
+    pub receives_started: u64,
+    pub receives_completed: u64,
+    pub receives_failed: u64,
+    pub direct_connections: u64,
+    pub relay_connections: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Metric {
+    SendStarted,
+    SendCompleted,
+    SendFailed,
+    ReceiveStarted,
+    ReceiveCompleted,
+    ReceiveFailed,
+    DirectConnection,
+    RelayConnection,
+}
+
+impl UsageMetrics {
+    fn increment(&mut self, metric: Metric) {
+        let counter = match metric {
+            Metric::SendStarted => &mut self.sends_started,
+            Metric::SendCompleted => &mut self.sends_completed,
+            Metric::SendFailed => &mut self.sends_failed,
+            Metric::ReceiveStarted => &mut self.receives_started,
+            Metric::ReceiveCompleted => &mut self.receives_completed,
+            Metric::ReceiveFailed => &mut self.receives_failed,
+            Metric::DirectConnection => &mut self.direct_connections,
+            Metric::RelayConnection => &mut self.relay_connections,
+        };
+        *counter += 1;
+        self.since.get_or_insert_with(Local::now);
+    }
+}
+
+pub fn get_usage_metrics_path(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join("usage_metrics.json")
+}
+
+fn load(paths: &AppPaths) -> UsageMetrics {
+    fs::read_to_string(get_usage_metrics_path(paths))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(paths: &AppPaths, metrics: &UsageMetrics) -> Result<(), String> {
+    fs::create_dir_all(&paths.data_dir)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    let json = serde_json::to_string_pretty(metrics).map_err(|e| e.to_string())?;
+    fs::write(get_usage_metrics_path(paths), json)
+        .map_err(|e| format!("Failed to save usage metrics: {}", e))
+}
+
+/// Count `metric` if the user has opted in.
+pub async fn record(ctx: &AppContext, metric: Metric) {
+    if !ctx.settings().lock().await.get_usage_metrics_enabled() {
+        return;
+    }
+    let _guard = METRICS_LOCK.lock().await;
+    let mut metrics = load(ctx.paths());
+    metrics.increment(metric);
+    if let Err(e) = save(ctx.paths(), &metrics) {
+        log_line!("[magic-wormhole][metrics][error] {}", e);
+    }
+}
+
+/// Count `completed` or `failed` depending on how a transfer ended.
+pub async fn record_outcome<T>(
+    ctx: &AppContext,
+    completed: Metric,
+    failed: Metric,
+    result: &Result<T, String>,
+) {
+    let metric = if result.is_ok() { completed } else { failed };
+    record(ctx, metric).await;
+}
+
+/// Count the connection type reported by a transit handler. Transit handlers aren't
+/// async, so the write happens on a spawned task.
+pub fn record_connection(ctx: &AppContext, connection_type: &str) {
+    let metric = if connection_type == "direct" {
+        Metric::DirectConnection
+    } else if connection_type.starts_with("relay") {
+        Metric::RelayConnection
+    } else {
+        return;
+    };
+    let ctx = ctx.clone();
+    tokio::spawn(async move { record(&ctx, metric).await });
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+pub async fn get_usage_metrics(ctx: &AppContext) -> Result<UsageMetrics, String> {
+    let _guard = METRICS_LOCK.lock().await;
+    Ok(load(ctx.paths()))
+}
+
+pub async fn export_usage_metrics(ctx: &AppContext, file_path: String) -> Result<(), String> {
+    let metrics = get_usage_metrics(ctx).await?;
+    let json = serde_json::to_string_pretty(&metrics).map_err(|e| e.to_string())?;
+    fs::write(&file_path, json).map_err(|e| format!("Failed to write exported file: {}", e))
+}
+
+pub async fn reset_usage_metrics(ctx: &AppContext) -> Result<(), String> {
+    let _guard = METRICS_LOCK.lock().await;
+    let path = get_usage_metrics_path(ctx.paths());
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to reset usage metrics: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,477 @@
+// This file implements multi-part folder sends. With `archive_part_size_mib` set, a folder
+// whose tarball is larger than that is split into `<name>.tar.gz.part1of3` and so on. Part
+// 1 goes out on the usual code; the rest follow on codes both sides derive from its session
+// secret (`part_code`, claimed like reply codes). A part that fails is retried on its own instead
+// of starting the whole archive over. The receiver fetches the remaining parts as soon as
+// part 1 is accepted, then joins them and handles the result like any received tarball.
+
+use chrono::Local;
+use magic_wormhole::Code;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::context::AppContext;
+use crate::events;
+use crate::extract;
+use crate::files::{self, SendOptions};
+use crate::files_json;
+use crate::receive;
+use crate::webhook;
+
+/// How many times each part after the first is tried before the transfer gives up.
+pub const PART_ATTEMPTS: u32 = 3;
+
+/// The most parts a transfer may be split into. The count comes from the sender's file
+/// name, and the receiver waits for that many parts, so larger ones are refused outright.
+pub const MAX_PARTS: u32 = 1000;
+
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// How long the receiver waits for the sender to offer the next part.
+const PART_WAIT: Duration = Duration::from_secs(120);
+
+/// A part's file name, `<base>.part<index>of<count>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartName {
+    pub base: String,
+    pub index: u32,
+    pub count: u32,
+}
+
+impl PartName {
+    pub fn parse(file_name: &str) -> Option<PartName> {
+        let (base, suffix) = file_name.rsplit_once(".part")?;
+        let (index, count) = suffix.split_once("of")?;
+        let (index, count) = (index.parse().ok()?, count.parse().ok()?);
+        let valid = !base.is_empty() && (2..=MAX_PARTS).contains(&count);
+        (valid && (1..=count).contains(&index)).then(|| PartName {
+            base: base.to_string(),
+            index,
+            count,
+        })
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("{}.part{}of{}", self.base, self.index, self.count)
+    }
+
+    fn with_index(&self, index: u32) -> PartName {
+        PartName {
+            index,
+            ..self.clone()
+        }
+    }
+}
+
+/// The code part `index` of a multi-part transfer goes out on, from the session secret of
+/// part 1's connection (see `files::follow_up_code`).
+pub fn part_code(secret: &str, index: u32) -> String {
+    files::follow_up_code(secret, &format!("part{}", index))
+}
+
+/// Split `path` into parts of at most `part_size` bytes next to it, named from `name`, and
+/// remove it. Returns the parts in order. Blocking; call from `spawn_blocking`.
+pub fn split_file(path: &Path, name: &str, part_size: u64) -> Result<Vec<PathBuf>, String> {
+    let size = fs::metadata(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .len();
+    let count = size.div_ceil(part_size).max(1);
+    if count > u64::from(MAX_PARTS) {
+        return Err(format!(
+            "{} would be split into {} parts, more than the {} allowed; use a larger part size",
+            name, count, MAX_PARTS
+        ));
+    }
+    let count = count as u32;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut source =
+        fs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut parts = Vec::new();
+    for index in 1..=count {
+        let part = PartName {
+            base: name.to_string(),
+            index,
+            count,
+        };
+        let part_path = dir.join(part.file_name());
+        let mut output = fs::File::create(&part_path)
+            .map_err(|e| format!("Failed to create {}: {}", part.file_name(), e))?;
+        io::copy(&mut (&mut source).take(part_size), &mut output)
+            .map_err(|e| format!("Failed to write {}: {}", part.file_name(), e))?;
+        parts.push(part_path);
+    }
+    let _ = fs::remove_file(path);
+    Ok(parts)
+}
+
+/// Join `parts` in order into `output_path`, removing them once it's written. Blocking.
+pub fn join_parts(parts: &[PathBuf], output_path: &Path) -> Result<u64, String> {
+    let mut output = fs::File::create(output_path)
+        .map_err(|e| format!("Failed to create {}: {}", output_path.display(), e))?;
+    let mut size = 0;
+    for part in parts {
+        let mut input = fs::File::open(part)
+            .map_err(|e| format!("Failed to read {}: {}", part.display(), e))?;
+        size += io::copy(&mut input, &mut output)
+            .map_err(|e| format!("Failed to join {}: {}", part.display(), e))?;
+    }
+    for part in parts {
+        let _ = fs::remove_file(part);
+    }
+    Ok(size)
+}
+
+/// Send the folder at `folder` as a tarball, split into `part_size` parts when it's bigger
+/// than that. Called by `files::send_file_call` when `archive_part_size_mib` is set.
+pub(crate) async fn send_folder_in_parts(
+    ctx: AppContext,
+    folder: &Path,
+    send_id: String,
+    part_size: u64,
+) -> Result<String, String> {
+    let folder_name = folder
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "folder".to_string());
+    let tarball_name = format!("{}.tar.gz", folder_name);
+    let _ = ctx.emit(
+        events::SEND_PROGRESS,
+        events::SendProgress {
+            id: send_id.clone(),
+            file_name: tarball_name.clone(),
+            sent: 0,
+            total: 0,
+            percentage: 0,
+            code: String::new(),
+            status: events::TransferState::Packaging,
+        },
+    );
+
+    let options = files::TarballOptions::from_settings(&ctx, None).await;
+    let temp_dir = std::env::temp_dir().join(format!("wyrmhole_parts_{}", Uuid::new_v4()));
+    let packaged = tokio::task::spawn_blocking({
+        let folder = folder.to_path_buf();
+        let temp_dir = temp_dir.clone();
+        let tarball_name = tarball_name.clone();
+        let (ctx, send_id) = (ctx.clone(), send_id.clone());
+        move || {
+            fs::create_dir_all(&temp_dir)
+                .map_err(|e| format!("Failed to create tarball folder: {}", e))?;
+            let tarball_path = temp_dir.join(&tarball_name);
+            let mut skipped = Vec::new();
+            let size = files::create_tarball_from_folder(
+                &folder,
+                &tarball_path,
+                &folder_name,
+                options,
+                Default::default(),
+                &mut skipped,
+            )?;
+            files::report_skipped(&ctx, &send_id, skipped);
+            if size <= part_size {
+                return Ok(vec![tarball_path]);
+            }
+            split_file(&tarball_path, &tarball_name, part_size)
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to create tarball: {}", e))
+    .and_then(|result| result);
+
+    let result = match packaged {
+        Ok(parts) => {
+            ctx.transfers().add_multipart(&send_id).await;
+            let sent = send_parts(&ctx, &parts, &send_id).await;
+            ctx.transfers().remove_multipart(&send_id).await;
+            sent
+        }
+        Err(error) => {
+            log_line!("[magic-wormhole][parts][error] {}", error);
+            let _ = ctx.emit(
+                events::SEND_ERROR,
+                events::SendError {
+                    id: send_id,
+                    file_name: tarball_name,
+                    error: error.clone(),
+                    code: None,
+                },
+            );
+            Err(error)
+        }
+    };
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    result
+}
+
+async fn send_parts(ctx: &AppContext, parts: &[PathBuf], send_id: &str) -> Result<String, String> {
+    let Some((first, rest)) = parts.split_first() else {
+        return Err("Nothing to send".to_string());
+    };
+    let first_sent = files::queued_send(
+        ctx.clone(),
+        vec![first.to_string_lossy().into_owned()],
+        send_id.to_string(),
+        SendOptions::default(),
+    )
+    .await?;
+    if rest.is_empty() {
+        return Ok(first_sent);
+    }
+    let secret = files::transfer_secret(ctx, send_id)
+        .await
+        .ok_or("Lost the session of the first part")?;
+
+    for (offset, part) in rest.iter().enumerate() {
+        let index = offset as u32 + 2;
+        let part_code = part_code(&secret, index);
+        let mut attempt = 1;
+        loop {
+            let code = part_code
+                .parse::<Code>()
+                .map_err(|e| format!("Invalid part code: {}", e))?;
+            let options = SendOptions {
+                reply_to: Some(code),
+                ..SendOptions::default()
+            };
+            let sent = files::queued_send(
+                ctx.clone(),
+                vec![part.to_string_lossy().into_owned()],
+                send_id.to_string(),
+                options,
+            )
+            .await;
+            match sent {
+                Ok(_) => break,
+                Err(e)
+                    if attempt < PART_ATTEMPTS
+                        && !ctx.transfers().multipart_cancelled(send_id).await =>
+                {
+                    log_line!(
+                        "[magic-wormhole][parts][warn] Part {} failed (attempt {}), retrying: {}",
+                        index,
+                        attempt,
+                        e
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    Ok(format!("Sent {} parts", parts.len()))
+}
+
+/// After part 1 of a multi-part download (saved at `first_path`) is accepted, fetch the
+/// remaining parts, join them and finish the download like any received tarball. Progress
+/// for every part is reported under the first offer's `id`.
+pub(crate) async fn receive_remaining_parts(
+    ctx: AppContext,
+    id: String,
+    first_path: PathBuf,
+    first: PartName,
+    destination: Option<PathBuf>,
+) -> Result<String, String> {
+    let started = Instant::now();
+    ctx.transfers().add_multipart(&id).await;
+    let received = receive_parts(&ctx, &id, first_path, &first, destination.as_deref()).await;
+    ctx.transfers().remove_multipart(&id).await;
+    let joined = match received {
+        Ok(parts) => finish_download(&ctx, &id, &parts, &first.base, started).await,
+        Err(e) => Err(e),
+    };
+    joined.inspect_err(|error| {
+        log_line!("[magic-wormhole][parts][error] {}", error);
+        let _ = ctx.emit(
+            events::DOWNLOAD_ERROR,
+            events::DownloadError {
+                id: id.clone(),
+                file_name: first.base.clone(),
+                error: error.clone(),
+                code: None,
+            },
+        );
+    })
+}
+
+async fn receive_parts(
+    ctx: &AppContext,
+    id: &str,
+    first_path: PathBuf,
+    first: &PartName,
+    destination: Option<&Path>,
+) -> Result<Vec<PathBuf>, String> {
+    let secret = files::transfer_secret(ctx, id)
+        .await
+        .ok_or("Lost the session of the first part")?;
+    let mut parts = vec![first_path];
+    for index in 2..=first.count {
+        let expected = first.with_index(index).file_name();
+        let mut attempt = 1;
+        let path = loop {
+            match receive_part(ctx, id, &part_code(&secret, index), &expected, destination).await {
+                Ok(path) => break path,
+                Err(e)
+                    if attempt < PART_ATTEMPTS
+                        && !ctx.transfers().multipart_cancelled(id).await =>
+                {
+                    log_line!(
+                        "[magic-wormhole][parts][warn] Part {} failed (attempt {}), retrying: {}",
+                        index,
+                        attempt,
+                        e
+                    );
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+        parts.push(path);
+    }
+    Ok(parts)
+}
+
+async fn receive_part(
+    ctx: &AppContext,
+    id: &str,
+    code: &str,
+    expected: &str,
+    destination: Option<&Path>,
+) -> Result<PathBuf, String> {
+    let code = code
+        .parse::<Code>()
+        .map_err(|e| format!("Invalid part code: {}", e))?;
+    let connection_id = Uuid::new_v4().to_string();
+    let requested = tokio::time::timeout(
+        PART_WAIT,
+        files::request_offer(ctx.clone(), code, connection_id.clone(), true),
+    )
+    .await;
+    let offer = match requested {
+        Ok(offer) => offer?,
+        Err(_) => {
+            let _ = files::cancel_connection(connection_id, ctx.clone()).await;
+            return Err(format!("Timed out waiting for {}", expected));
+        }
+    };
+    if offer.file_name != expected {
+        let _ = files::discard_offer(ctx, &offer.id).await;
+        return Err(format!("Expected {}, got {}", expected, offer.file_name));
+    }
+    files::accept_part(
+        ctx.clone(),
+        &offer.id,
+        id.to_string(),
+        destination.map(Path::to_path_buf),
+    )
+    .await
+}
+
+async fn finish_download(
+    ctx: &AppContext,
+    id: &str,
+    parts: &[PathBuf],
+    base_name: &str,
+    started: Instant,
+) -> Result<String, String> {
+    let download_dir = parts[0].parent().map(Path::to_path_buf).unwrap_or_default();
+    let output_path = files::find_unique_file_path(&download_dir, base_name);
+    let auto_extract = ctx.settings().lock().await.get_auto_extract_tarballs();
+    let is_tarball = base_name.ends_with(".tar.gz") || base_name.ends_with(".tgz");
+
+    let size = tokio::task::spawn_blocking({
+        let parts = parts.to_vec();
+        let output_path = output_path.clone();
+        move || join_parts(&parts, &output_path)
+    })
+    .await
+    .map_err(|e| format!("Failed to join parts: {}", e))??;
+    // No part was checked on its own (see receive.rs), so the whole is; a flagged one is kept
+    // as joined.
+    let flagged = receive::flag_content(ctx, id, base_name, &output_path).await?;
+    let extract = auto_extract && is_tarball && flagged.is_none();
+
+    let joined = tokio::task::spawn_blocking({
+        let output_path = output_path.clone();
+        let download_dir = download_dir.clone();
+        move || -> Result<Vec<(String, u64)>, String> {
+            if extract {
+                let extracted = extract::extract_tarball(&output_path, &download_dir, false)?;
+                let _ = fs::remove_file(&output_path);
+                return Ok(extracted);
+            }
+            let name = output_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Ok(vec![(name, size)])
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to extract archive: {}", e))??;
+
+    let file_count = joined.len();
+    let total_size = joined.iter().map(|(_, size)| size).sum();
+    let download_time = Local::now();
+    let received = joined
+        .into_iter()
+        .map(|(file_name, file_size)| {
+            let (name, ext) = file_name
+                .rsplit_once('.')
+                .map(|(n, e)| (n.to_string(), e.to_string()))
+                .unwrap_or_else(|| (file_name.clone(), String::new()));
+            files_json::ReceivedFile {
+                file_name: name,
+                file_size,
+                file_extension: ext,
+                download_url: download_dir.clone(),
+                download_time,
+                // Each part had its own connection; none of them speaks for the whole.
+                connection_type: "multi-part".to_string(),
+                peer_address: "0.0.0.0:0".parse().unwrap(),
+                status: files_json::TransferStatus::Completed,
+                reason: None,
+                checksum: None,
+                id: files_json::new_entry_id(),
+            }
+        })
+        .collect();
+    if let Err(e) = files_json::add_received_files(ctx, received).await {
+        log_line!(
+            "[magic-wormhole][parts][error] Failed to add received files: {}",
+            e
+        );
+    }
+
+    let path = if extract {
+        download_dir.clone()
+    } else {
+        output_path.clone()
+    };
+    let _ = ctx.emit(
+        events::DOWNLOAD_COMPLETE,
+        events::DownloadComplete {
+            id: id.to_string(),
+            file_name: base_name.to_string(),
+            path: path.clone(),
+        },
+    );
+    webhook::notify(
+        ctx,
+        "received",
+        id,
+        base_name,
+        total_size,
+        None,
+        started.elapsed(),
+    );
+    Ok(format!(
+        "Joined {} parts: {} file(s) saved to {}",
+        parts.len(),
+        file_count,
+        path.display()
+    ))
+}
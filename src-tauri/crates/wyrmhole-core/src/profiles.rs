@@ -0,0 +1,125 @@
+// This file implements profiles (e.g. "work" and "personal"), each with its own settings and
+// transfer history. The "default" profile uses the top-level config and data directories, so
+// installs from before profiles existed keep their files; every other profile lives under
+// `profiles/<name>/` in both. The active profile is remembered in `active_profile` in the
+// top-level config directory and applied when a host resolves its paths at startup, so
+// switching takes effect on the next launch (the Tauri app restarts itself).
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::context::{AppContext, AppPaths};
+use crate::files;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+const MAX_PROFILE_NAME_LEN: usize = 32;
+
+fn active_profile_path(base: &AppPaths) -> PathBuf {
+    base.config_dir.join("active_profile")
+}
+
+fn profiles_dir(dir: &std::path::Path) -> PathBuf {
+    dir.join("profiles")
+}
+
+// Profile names become directory names, so keep them to a safe, portable alphabet.
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PROFILE_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid profile name '{}': use up to {} letters, digits, '-' or '_'",
+            name, MAX_PROFILE_NAME_LEN
+        ))
+    }
+}
+
+/// The directories for profile `name`, given the top-level (`base`) directories.
+/// Downloads are shared; each profile can still pick its own download directory.
+pub fn profile_paths(base: &AppPaths, name: &str) -> AppPaths {
+    if name == DEFAULT_PROFILE {
+        return base.clone();
+    }
+    AppPaths {
+        config_dir: profiles_dir(&base.config_dir).join(name),
+        data_dir: profiles_dir(&base.data_dir).join(name),
+        default_download_dir: base.default_download_dir.clone(),
+    }
+}
+
+/// The profile recorded as active, falling back to the default profile.
+pub fn active_profile(base: &AppPaths) -> String {
+    fs::read_to_string(active_profile_path(base))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| validate_profile_name(name).is_ok())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// The active profile's directories. Hosts call this once at startup.
+pub fn resolve_paths(base: &AppPaths) -> AppPaths {
+    let name = active_profile(base);
+    if name != DEFAULT_PROFILE {
+        log_line!("[magic-wormhole][profiles][info] Using profile '{}'", name);
+    }
+    profile_paths(base, &name)
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+/// Every profile, default first, then the rest alphabetically.
+pub fn list_profiles(base: &AppPaths) -> Result<Vec<String>, String> {
+    let mut profiles = Vec::new();
+    let dir = profiles_dir(&base.config_dir);
+    if dir.exists() {
+        let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read profiles: {}", e))?;
+        profiles = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| name != DEFAULT_PROFILE && validate_profile_name(name).is_ok())
+            .collect();
+        profiles.sort();
+    }
+    profiles.insert(0, DEFAULT_PROFILE.to_string());
+    Ok(profiles)
+}
+
+/// The settings file of every profile. They hold the local API token, so hosts keep them
+/// out of anything the user could send or export over.
+pub fn settings_files(base: &AppPaths) -> Vec<PathBuf> {
+    list_profiles(base)
+        .unwrap_or_else(|_| vec![DEFAULT_PROFILE.to_string()])
+        .iter()
+        .map(|name| profile_paths(base, name).config_dir.join("settings.json"))
+        .collect()
+}
+
+/// Make `name` the active profile, creating it if needed. Refused while transfers are
+/// running, since their history would be written to the profile being left.
+pub async fn switch_profile(ctx: &AppContext, base: &AppPaths, name: &str) -> Result<(), String> {
+    validate_profile_name(name)?;
+    if files::has_active_transfers(ctx).await {
+        return Err("Finish or cancel active transfers before switching profiles".to_string());
+    }
+
+    let paths = profile_paths(base, name);
+    fs::create_dir_all(&paths.config_dir)
+        .and_then(|_| fs::create_dir_all(&paths.data_dir))
+        .map_err(|e| format!("Failed to create profile '{}': {}", name, e))?;
+    fs::create_dir_all(&base.config_dir)
+        .and_then(|_| fs::write(active_profile_path(base), name))
+        .map_err(|e| format!("Failed to switch profile: {}", e))?;
+
+    log_line!(
+        "[magic-wormhole][profiles][info] Switched to profile '{}'",
+        name
+    );
+    Ok(())
+}
@@ -0,0 +1,514 @@
+// This file is the receive pipeline a download goes through once its bytes are on disk (see
+// `files::accept_offer`). Each step gets the download and passes it on, finishes it, or stops
+// it: parts wait to be joined, deltas patch the file they update, programs and scripts the
+// offer didn't warn about are held back, archives are extracted, duplicates are dropped, the user's After Receiving rules run, and whatever's left is
+// recorded as a plain file. Hosts and tests add their own steps with
+// `AppContext::with_receive_step`; they run after the built-in ones and before the rules.
+
+use chrono::Local;
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::content;
+use crate::context::AppContext;
+use crate::delta;
+use crate::events::{self, TransferState};
+use crate::extract;
+use crate::files;
+use crate::files_json::{self, ReceivedFile};
+use crate::hooks;
+use crate::parts;
+use crate::sync;
+use crate::webhook;
+
+/// A download that has finished arriving.
+pub struct Download {
+    pub id: String,
+    /// The name the sender offered it under.
+    pub offered_name: String,
+    /// The name it was saved under, after the name template and any `(1)` suffix.
+    pub file_name: String,
+    pub path: PathBuf,
+    pub download_dir: PathBuf,
+    pub size: u64,
+    /// SHA-256 (hex) of the bytes as they were written.
+    pub checksum: String,
+    pub connection_type: String,
+    pub peer_address: SocketAddr,
+    pub started: Instant,
+}
+
+/// A download that's done, ready to be recorded and reported.
+pub struct Finished {
+    /// Received history entries to add (an extracted tarball adds one per file).
+    pub entries: Vec<ReceivedFile>,
+    /// The name and path `DOWNLOAD_COMPLETE` reports.
+    pub file_name: String,
+    pub path: PathBuf,
+    /// The size and checksum the completion webhook reports (see webhook.rs).
+    pub size: u64,
+    pub checksum: Option<String>,
+    /// What accepting the offer resolves with.
+    pub message: String,
+}
+
+/// What a step did with a download.
+pub enum Flow {
+    /// Hand it on to the next step.
+    Next(Download),
+    /// It's done; record and report it.
+    Finish(Finished),
+    /// Stop without recording anything, resolving with this (e.g. a part, joined later).
+    Stop(String),
+}
+
+/// One step of the pipeline. A step that fails ends the download with a `DOWNLOAD_ERROR`;
+/// the file stays where it is, so nothing received is lost.
+pub trait ReceiveStep: Send + Sync {
+    fn run<'a>(
+        &'a self,
+        ctx: &'a AppContext,
+        download: Download,
+    ) -> BoxFuture<'a, Result<Flow, String>>;
+}
+
+/// Run `download` through the steps and record it, returning what accepting it resolves with.
+pub(crate) async fn run(ctx: &AppContext, mut download: Download) -> Result<String, String> {
+    let _ = ctx.emit(
+        events::DOWNLOAD_PROGRESS,
+        events::DownloadProgress {
+            id: download.id.clone(),
+            file_name: download.file_name.clone(),
+            transferred: download.size,
+            total: download.size,
+            percentage: 100,
+            status: TransferState::Finalizing,
+        },
+    );
+
+    let (id, file_name, started) = (
+        download.id.clone(),
+        download.file_name.clone(),
+        download.started,
+    );
+    let built_in: [&dyn ReceiveStep; 5] = [&Parts, &Delta, &Content, &Extract, &Duplicates];
+    let steps = built_in
+        .into_iter()
+        .chain(ctx.receive_steps().iter().map(|step| step.as_ref()))
+        .chain([&Rules as &dyn ReceiveStep]);
+    for step in steps {
+        match step.run(ctx, download).await {
+            Ok(Flow::Next(next)) => download = next,
+            Ok(Flow::Finish(finished)) => return record(ctx, &id, started, finished).await,
+            Ok(Flow::Stop(message)) => return Ok(message),
+            Err(e) => {
+                log_line!("[magic-wormhole][receive][error] {}", e);
+                let _ = ctx.emit(
+                    events::DOWNLOAD_ERROR,
+                    events::DownloadError {
+                        id: id.clone(),
+                        file_name,
+                        error: e.clone(),
+                        code: None,
+                    },
+                );
+                return Err(e);
+            }
+        }
+    }
+    record(ctx, &id, started, saved_file(download)).await
+}
+
+// Add a finished download to the history and report it.
+async fn record(
+    ctx: &AppContext,
+    id: &str,
+    started: Instant,
+    finished: Finished,
+) -> Result<String, String> {
+    let added = match <[ReceivedFile; 1]>::try_from(finished.entries) {
+        Ok([entry]) => files_json::add_received_file(ctx, entry).await,
+        Err(entries) => files_json::add_received_files(ctx, entries).await,
+    };
+    added.map_err(|e| {
+        log_line!(
+            "[magic-wormhole][receive][error] Failed to add received file: {}",
+            e
+        );
+        e
+    })?;
+    let _ = ctx.emit(
+        events::DOWNLOAD_COMPLETE,
+        events::DownloadComplete {
+            id: id.to_string(),
+            file_name: finished.file_name.clone(),
+            path: finished.path,
+        },
+    );
+    webhook::notify(
+        ctx,
+        "received",
+        id,
+        &finished.file_name,
+        finished.size,
+        finished.checksum,
+        started.elapsed(),
+    );
+    Ok(finished.message)
+}
+
+// The history entry for a file named `name` saved in `dir`.
+fn entry(download: &Download, name: &str, dir: &Path, size: u64) -> ReceivedFile {
+    let (file_name, file_extension) = split_name(name);
+    ReceivedFile {
+        file_name,
+        file_size: size,
+        file_extension,
+        download_url: dir.to_path_buf(),
+        download_time: Local::now(),
+        connection_type: download.connection_type.clone(),
+        peer_address: download.peer_address,
+        status: files_json::TransferStatus::Completed,
+        reason: None,
+        checksum: None,
+        id: files_json::new_entry_id(),
+    }
+}
+
+// The history entry for the file at `path`, with the download's checksum.
+fn saved_entry(download: &Download, name: &str, path: &Path) -> ReceivedFile {
+    let dir = path.parent().unwrap_or(&download.download_dir);
+    ReceivedFile {
+        checksum: Some(download.checksum.clone()),
+        ..entry(download, name, dir, download.size)
+    }
+}
+
+// A download that no step finished: the file as it was saved (and moved by any rules).
+fn saved_file(download: Download) -> Finished {
+    let saved = saved_entry(&download, &download.file_name, &download.path);
+    let message = if extract::detect(&download.path).is_some() {
+        format!(
+            "File transfer completed! Archive saved to {} (not extracted)",
+            download.path.display()
+        )
+    } else {
+        format!(
+            "File transfer completed! File saved to {}",
+            download.path.display()
+        )
+    };
+    Finished {
+        entries: vec![saved],
+        file_name: download.file_name,
+        path: download.path.clone(),
+        size: download.size,
+        checksum: Some(download.checksum),
+        message,
+    }
+}
+
+// The name and extension a history entry stores for `name` (see `files::split_extension`).
+fn split_name(name: &str) -> (String, String) {
+    let (name, extension) = files::split_extension(name);
+    (name.to_string(), extension.to_string())
+}
+
+// Parts of a multi-part transfer are joined once all have arrived (see parts.rs); until
+// then they skip history and rules, and the caller gets the part's path.
+struct Parts;
+
+impl ReceiveStep for Parts {
+    fn run<'a>(
+        &'a self,
+        _ctx: &'a AppContext,
+        download: Download,
+    ) -> BoxFuture<'a, Result<Flow, String>> {
+        async move {
+            if parts::PartName::parse(&download.offered_name).is_some() {
+                return Ok(Flow::Stop(download.path.to_string_lossy().into_owned()));
+            }
+            Ok(Flow::Next(download))
+        }
+        .boxed()
+    }
+}
+
+// A delta (see delta.rs) patches the earlier copy of the file it updates, with
+// `apply_file_deltas` on. If that fails the delta is kept. The patched file is checked like
+// any other (see `Content`), since a delta can turn a harmless file into a program.
+struct Delta;
+
+impl ReceiveStep for Delta {
+    fn run<'a>(
+        &'a self,
+        ctx: &'a AppContext,
+        download: Download,
+    ) -> BoxFuture<'a, Result<Flow, String>> {
+        async move {
+            let Some(base_name) = download
+                .offered_name
+                .strip_suffix(delta::DELTA_SUFFIX)
+                .and_then(|name| Path::new(name).file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+            else {
+                return Ok(Flow::Next(download));
+            };
+            if !ctx.settings().lock().await.get_apply_file_deltas() {
+                return Ok(Flow::Next(download));
+            }
+
+            let base_path = download.download_dir.join(&base_name);
+            let new_size = tokio::task::spawn_blocking({
+                let file_path = download.path.clone();
+                let base_path = base_path.clone();
+                move || delta::apply_delta(&file_path, &base_path)
+            })
+            .await
+            .map_err(|e| format!("Failed to apply delta: {}", e))??;
+            let _ = tokio::fs::remove_file(&download.path).await;
+            flag_content(ctx, &download.id, &base_name, &base_path).await?;
+
+            Ok(Flow::Finish(Finished {
+                entries: vec![entry(
+                    &download,
+                    &base_name,
+                    &download.download_dir,
+                    new_size,
+                )],
+                message: format!("File updated in place at {}", base_path.display()),
+                file_name: base_name,
+                path: base_path.clone(),
+                size: new_size,
+                checksum: None,
+            }))
+        }
+        .boxed()
+    }
+}
+
+// A file that turns out to be a program or script, or an archive holding one (see content.rs),
+// is flagged with `CONTENT_FLAGGED` and kept where it was saved: it isn't extracted or handed
+// to the After Receiving rules. Offers whose name already warned were confirmed when they
+// were accepted, so they carry on.
+struct Content;
+
+impl ReceiveStep for Content {
+    fn run<'a>(
+        &'a self,
+        ctx: &'a AppContext,
+        download: Download,
+    ) -> BoxFuture<'a, Result<Flow, String>> {
+        async move {
+            let allowlist = ctx.settings().lock().await.get_content_allowlist().clone();
+            if content::warning_for_name(&download.offered_name, &allowlist).is_some() {
+                return Ok(Flow::Next(download));
+            }
+            let flagged =
+                flag_content(ctx, &download.id, &download.file_name, &download.path).await?;
+            if flagged.is_none() {
+                return Ok(Flow::Next(download));
+            }
+            Ok(Flow::Finish(saved_file(download)))
+        }
+        .boxed()
+    }
+}
+
+/// Check the received file at `path` by name and contents (see content.rs) and, if it's a
+/// program, a script or an archive holding either, report it with `CONTENT_FLAGGED` for
+/// download `id`. Callers keep a flagged file as it is: not extracted, and not handed to the
+/// After Receiving rules.
+pub(crate) async fn flag_content(
+    ctx: &AppContext,
+    id: &str,
+    file_name: &str,
+    path: &Path,
+) -> Result<Option<content::ContentWarning>, String> {
+    let allowlist = ctx.settings().lock().await.get_content_allowlist().clone();
+    let warning = tokio::task::spawn_blocking({
+        let (name, path) = (file_name.to_string(), path.to_path_buf());
+        move || {
+            content::warning_for_name(&name, &allowlist)
+                .or_else(|| content::sniff(&path, &allowlist))
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to check the received file: {}", e))?;
+    if let Some(warning) = warning {
+        log_line!(
+            "[magic-wormhole][receive][warn] {} is {}; keeping it as saved",
+            path.display(),
+            warning.message()
+        );
+        let _ = ctx.emit(
+            events::CONTENT_FLAGGED,
+            events::ContentFlagged {
+                id: id.to_string(),
+                file_name: file_name.to_string(),
+                path: path.to_path_buf(),
+                warning,
+            },
+        );
+    }
+    Ok(warning)
+}
+
+// Archives (any format extract.rs recognises and has an extractor for) are extracted into the
+// download directory with `auto_extract_tarballs` on, and folder sync updates (see sync.rs)
+// merged into the folder they update with `merge_folder_updates` on. Archives that can't be
+// extracted unattended, like password-protected zips, are kept as they are. Extracted files
+// finish here, so the rules don't run on them.
+struct Extract;
+
+impl ReceiveStep for Extract {
+    fn run<'a>(
+        &'a self,
+        ctx: &'a AppContext,
+        download: Download,
+    ) -> BoxFuture<'a, Result<Flow, String>> {
+        async move {
+            let app_settings_lock = ctx.settings().lock().await;
+            let auto_extract = app_settings_lock.get_auto_extract_tarballs();
+            let merge = app_settings_lock.get_merge_folder_updates()
+                && download.file_name.ends_with(sync::SYNC_SUFFIX);
+            drop(app_settings_lock);
+            if !auto_extract && !merge {
+                return Ok(Flow::Next(download));
+            }
+
+            let extracted = tokio::task::spawn_blocking({
+                let ctx = ctx.clone();
+                let file_path = download.path.clone();
+                let download_dir = download.download_dir.clone();
+                move || {
+                    let Some(extractor) = extract::detect(&file_path)
+                        .and_then(|format| extract::extractor_for(&ctx, format))
+                        .filter(|extractor| extractor.can_extract(&file_path))
+                    else {
+                        return Ok(None);
+                    };
+                    extractor
+                        .extract(&file_path, &download_dir, merge)
+                        .map(Some)
+                }
+            })
+            .await
+            .map_err(|e| format!("Failed to extract archive: {}", e))??;
+            let Some(extracted_files) = extracted else {
+                return Ok(Flow::Next(download));
+            };
+
+            // Each extracted file gets an entry, all added in one write
+            let entries: Vec<ReceivedFile> = extracted_files
+                .iter()
+                .map(|(name, size)| entry(&download, name, &download.download_dir, *size))
+                .collect();
+
+            // Remove the archive after extraction
+            let _ = tokio::fs::remove_file(&download.path).await;
+
+            Ok(Flow::Finish(Finished {
+                message: format!(
+                    "Archive extracted! {} file(s) saved to {}",
+                    entries.len(),
+                    download.download_dir.display()
+                ),
+                entries,
+                file_name: download.file_name,
+                path: download.download_dir,
+                size: download.size,
+                checksum: None,
+            }))
+        }
+        .boxed()
+    }
+}
+
+// A file matching one already in the received history (by checksum) is flagged with
+// `DUPLICATE_DETECTED`; with `skip_duplicate_receives` on, the new copy is removed and the
+// entry points at the earlier one, which the rules don't run on again.
+struct Duplicates;
+
+impl ReceiveStep for Duplicates {
+    fn run<'a>(
+        &'a self,
+        ctx: &'a AppContext,
+        download: Download,
+    ) -> BoxFuture<'a, Result<Flow, String>> {
+        async move {
+            let Some(existing_path) =
+                files_json::find_received_checksum(ctx, &download.checksum).await
+            else {
+                return Ok(Flow::Next(download));
+            };
+
+            let skip = ctx.settings().lock().await.get_skip_duplicate_receives();
+            let skipped = skip && tokio::fs::remove_file(&download.path).await.is_ok();
+            log_line!(
+                "[magic-wormhole][receive][info] {} duplicates {}{}",
+                download.path.display(),
+                existing_path.display(),
+                if skipped {
+                    ", keeping the earlier copy"
+                } else {
+                    ""
+                }
+            );
+            let _ = ctx.emit(
+                events::DUPLICATE_DETECTED,
+                events::DuplicateDetected {
+                    id: download.id.clone(),
+                    file_name: download.file_name.clone(),
+                    existing_path: existing_path.clone(),
+                    checksum: download.checksum.clone(),
+                    skipped,
+                },
+            );
+            if !skipped {
+                return Ok(Flow::Next(download));
+            }
+
+            let existing_name = existing_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let existing = saved_entry(&download, &existing_name, &existing_path);
+            let mut saved = saved_file(Download {
+                path: existing_path,
+                ..download
+            });
+            saved.entries = vec![existing];
+            Ok(Flow::Finish(saved))
+        }
+        .boxed()
+    }
+}
+
+// The user's After Receiving rules (see hooks.rs), which may move the file.
+struct Rules;
+
+impl ReceiveStep for Rules {
+    fn run<'a>(
+        &'a self,
+        ctx: &'a AppContext,
+        mut download: Download,
+    ) -> BoxFuture<'a, Result<Flow, String>> {
+        async move {
+            let receive_hooks = ctx.settings().lock().await.get_receive_hooks().clone();
+            if !receive_hooks.is_empty() {
+                download.path = tokio::task::spawn_blocking({
+                    let file_path = download.path.clone();
+                    move || hooks::run_receive_hooks(&receive_hooks, &file_path)
+                })
+                .await
+                .unwrap_or(download.path);
+            }
+            Ok(Flow::Next(download))
+        }
+        .boxed()
+    }
+}
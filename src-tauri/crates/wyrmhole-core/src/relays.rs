@@ -0,0 +1,135 @@
+// This file measures the configured transit relays. `benchmark_relays` times a TCP connect to
+// each one and saves them fastest first, so the relay hints offered to peers start with the
+// relay nearest to this machine; with several relays configured, each transfer also pings
+// them quickly (`PING_TIMEOUT`) and puts the fastest first for that transfer. Only the connect is timed: a relay only forwards data between
+// the two sides of a transfer, so there's nothing to probe throughput against on our own.
+
+use magic_wormhole::transit;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+use crate::context::AppContext;
+use crate::settings::AppSettings;
+
+/// How long a relay gets to accept a connection before it counts as unreachable.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The same for the ping at the start of a transfer, which holds the transfer up.
+pub(crate) const PING_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Result of timing one relay.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+pub struct RelayBenchmark {
+    pub url: String,
+    /// Time to open a TCP connection, or `None` if it failed (see `error`).
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// The relays in use, in order: `relay_server_url` (or the default relay), then
+/// `extra_relay_urls`. Blank entries and repeats are dropped.
+pub(crate) fn configured_relays(settings: &AppSettings) -> Vec<String> {
+    let first = settings
+        .get_relay_server_url()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| transit::DEFAULT_RELAY_SERVER.to_string());
+    let mut urls = vec![first];
+    for url in settings.get_extra_relay_urls() {
+        let url = url.trim().to_string();
+        if !url.is_empty() && !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// Host and port of a relay URL: `tcp://host:port`, `tcp:host:port` or
+/// `ws(s)://host[:port]/path`.
+pub fn relay_address(url: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = url.trim().split_once(':')?;
+    let authority = rest.trim_start_matches("//").split('/').next()?;
+    let default_port = match scheme {
+        "ws" => Some(80),
+        "wss" => Some(443),
+        _ => None,
+    };
+    // The last colon separates the port, except inside a bracketed IPv6 address.
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.ends_with(']') => (host, port.parse().ok()?),
+        _ => (authority, default_port?),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_string(), port))
+}
+
+/// Time a TCP connect to the relay at `url`.
+pub(crate) async fn measure(url: &str, timeout: Duration) -> Result<Duration, String> {
+    let (host, port) = relay_address(url).ok_or_else(|| format!("Not a relay URL: {}", url))?;
+    let started = Instant::now();
+    match tokio::time::timeout(timeout, TcpStream::connect((host.as_str(), port))).await {
+        Ok(Ok(_)) => Ok(started.elapsed()),
+        Ok(Err(e)) => Err(format!("Couldn't connect: {}", e)),
+        Err(_) => Err(format!("No answer within {}s", timeout.as_secs())),
+    }
+}
+
+/// Time every relay at once and return them fastest first, unreachable ones last.
+pub(crate) async fn rank(urls: Vec<String>, timeout: Duration) -> Vec<RelayBenchmark> {
+    let mut results = futures::future::join_all(urls.into_iter().map(|url| async move {
+        match measure(&url, timeout).await {
+            Ok(latency) => RelayBenchmark {
+                url,
+                latency_ms: Some(latency.as_millis() as u64),
+                error: None,
+            },
+            Err(e) => RelayBenchmark {
+                url,
+                latency_ms: None,
+                error: Some(e),
+            },
+        }
+    }))
+    .await;
+    // Stable, so unreachable relays keep their configured order.
+    results.sort_by_key(|r| r.latency_ms.unwrap_or(u64::MAX));
+    results
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+/// Time each configured relay and, when there's more than one, save them fastest first (the
+/// fastest becomes `relay_server_url`, the rest `extra_relay_urls`).
+pub async fn benchmark_relays(ctx: AppContext) -> Result<Vec<RelayBenchmark>, String> {
+    let urls = configured_relays(&*ctx.settings().lock().await);
+    let results = rank(urls.clone(), CONNECT_TIMEOUT).await;
+    for result in &results {
+        match (result.latency_ms, &result.error) {
+            (Some(ms), _) => log_line!(
+                "[magic-wormhole][relays][info] {} answered in {} ms",
+                result.url,
+                ms
+            ),
+            (None, e) => log_line!(
+                "[magic-wormhole][relays][warn] {} unreachable: {}",
+                result.url,
+                e.as_deref().unwrap_or("unknown error")
+            ),
+        }
+    }
+
+    let ordered: Vec<String> = results.iter().map(|r| r.url.clone()).collect();
+    if ordered.len() > 1 && ordered != urls {
+        let mut settings = ctx.settings().lock().await;
+        settings.set_relay_server_url(Some(ordered[0].clone()));
+        settings.set_extra_relay_urls(ordered[1..].to_vec());
+        ctx.save_settings(&settings)
+            .map_err(|e| format!("Failed to save settings: {}", e))?;
+    }
+    Ok(results)
+}
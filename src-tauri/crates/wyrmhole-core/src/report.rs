@@ -0,0 +1,192 @@
+// This file renders the transfer history as a self-contained HTML report (inline styles, no
+// scripts or external assets) that can be opened in any browser, shared or archived.
+
+use chrono::Local;
+use std::fmt::Write;
+use std::fs;
+
+use crate::context::AppContext;
+use crate::files_json::{self, ReceivedFile, SentFile, TransferStatus};
+
+const STYLE: &str = "\
+body{font-family:system-ui,-apple-system,'Segoe UI',sans-serif;color:#1f2937;margin:2rem auto;max-width:960px;padding:0 1rem}\
+h1{font-size:1.5rem;margin-bottom:.25rem}h2{font-size:1.1rem;margin-top:2rem}\
+.meta{color:#6b7280;font-size:.85rem}\
+.totals{display:flex;gap:1rem;margin-top:1rem}\
+.total{background:#eff6ff;border-radius:.5rem;padding:.75rem 1rem}\
+.total b{display:block;font-size:1.25rem}\
+table{border-collapse:collapse;width:100%;font-size:.85rem}\
+th,td{text-align:left;padding:.4rem .6rem;border-bottom:1px solid #e5e7eb}\
+th{background:#f9fafb}td.num{text-align:right;white-space:nowrap}\
+.empty{color:#9ca3af;font-style:italic}";
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+// "completed", or the other status with its reason.
+fn status_cell(status: TransferStatus, reason: &Option<String>) -> String {
+    let status = serde_json::to_value(status)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default();
+    match reason {
+        Some(reason) => format!("{}: {}", escape(&status), escape(reason)),
+        None => escape(&status),
+    }
+}
+
+fn display_name(name: &str, extension: &str) -> String {
+    if extension.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", name, extension)
+    }
+}
+
+// `<h2>`, then a table of `rows` (already escaped cells) or a placeholder when empty.
+fn write_table(html: &mut String, title: &str, headers: &[&str], rows: Vec<Vec<String>>) {
+    let _ = write!(html, "<h2>{} ({})</h2>", title, rows.len());
+    if rows.is_empty() {
+        html.push_str("<p class=\"empty\">No transfers yet.</p>");
+        return;
+    }
+    html.push_str("<table><thead><tr>");
+    for header in headers {
+        let _ = write!(html, "<th>{}</th>", header);
+    }
+    html.push_str("</tr></thead><tbody>");
+    for row in rows {
+        html.push_str("<tr>");
+        for (i, cell) in row.iter().enumerate() {
+            // The size column is right-aligned.
+            let class = if i == 1 { " class=\"num\"" } else { "" };
+            let _ = write!(html, "<td{}>{}</td>", class, cell);
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</tbody></table>");
+}
+
+/// The report for the given history, newest transfers first.
+pub fn render_history_html(mut sent: Vec<SentFile>, mut received: Vec<ReceivedFile>) -> String {
+    sent.sort_by(|a, b| b.send_time.cmp(&a.send_time));
+    received.sort_by(|a, b| b.download_time.cmp(&a.download_time));
+    // The totals are of completed transfers; the tables list every attempt.
+    let sent_done: Vec<&SentFile> = sent
+        .iter()
+        .filter(|f| f.status == TransferStatus::Completed)
+        .collect();
+    let received_done: Vec<&ReceivedFile> = received
+        .iter()
+        .filter(|f| f.status == TransferStatus::Completed)
+        .collect();
+    let sent_bytes: u64 = sent_done.iter().map(|f| f.file_size).sum();
+    let received_bytes: u64 = received_done.iter().map(|f| f.file_size).sum();
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"utf-8\">\
+         <title>wyrmhole transfer history</title><style>{}</style></head><body>\
+         <h1>wyrmhole transfer history</h1><p class=\"meta\">Generated {}</p>",
+        STYLE,
+        Local::now().format("%Y-%m-%d %H:%M")
+    );
+    let _ = write!(
+        html,
+        "<div class=\"totals\">\
+         <div class=\"total\"><b>{}</b>sent &middot; {}</div>\
+         <div class=\"total\"><b>{}</b>received &middot; {}</div></div>",
+        sent_done.len(),
+        format_size(sent_bytes),
+        received_done.len(),
+        format_size(received_bytes)
+    );
+
+    let sent_rows = sent
+        .iter()
+        .map(|file| {
+            vec![
+                escape(&display_name(&file.file_name, &file.file_extension)),
+                format_size(file.file_size),
+                file.send_time.format("%Y-%m-%d %H:%M").to_string(),
+                escape(&file.connection_code),
+                escape(&file.connection_type),
+                status_cell(file.status, &file.reason),
+            ]
+        })
+        .collect();
+    write_table(
+        &mut html,
+        "Sent",
+        &["File", "Size", "Sent", "Code", "Connection", "Status"],
+        sent_rows,
+    );
+
+    let received_rows = received
+        .iter()
+        .map(|file| {
+            vec![
+                escape(&display_name(&file.file_name, &file.file_extension)),
+                format_size(file.file_size),
+                file.download_time.format("%Y-%m-%d %H:%M").to_string(),
+                escape(&file.download_url.display().to_string()),
+                escape(&file.connection_type),
+                status_cell(file.status, &file.reason),
+            ]
+        })
+        .collect();
+    write_table(
+        &mut html,
+        "Received",
+        &[
+            "File",
+            "Size",
+            "Received",
+            "Saved to",
+            "Connection",
+            "Status",
+        ],
+        received_rows,
+    );
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+pub async fn export_history_html(ctx: &AppContext, file_path: String) -> Result<(), String> {
+    let html = render_history_html(
+        files_json::on_history(ctx, files_json::init_sent_files).await,
+        files_json::on_history(ctx, files_json::init_received_files).await,
+    );
+    fs::write(&file_path, html).map_err(|e| format!("Failed to write exported file: {}", e))
+}
@@ -0,0 +1,214 @@
+// Heuristics for send selections that are probably a mistake: a whole home folder, a browser
+// profile, SSH or other key directories, or folders holding obvious secrets (private keys,
+// `.env` files, password databases). Hosts show the findings and only send after the user
+// confirms; nothing here refuses a send by itself.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs;
+use std::path::{Component, Path};
+
+// How much of a selected folder is looked through for secrets files. Deep trees are cut
+// short rather than walked in full before every send.
+const MAX_DEPTH: usize = 4;
+const MAX_ENTRIES: usize = 20_000;
+
+/// What makes a path worth a second look.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "kebab-case")]
+pub enum SensitiveKind {
+    /// The user's whole home folder.
+    HomeDirectory,
+    /// A browser profile: saved passwords, cookies and history.
+    BrowserProfile,
+    /// SSH, GPG or cloud credential directories.
+    KeysDirectory,
+    /// A private key, `.env`, password database or similar file.
+    SecretsFile,
+}
+
+impl SensitiveKind {
+    pub fn message(self) -> &'static str {
+        match self {
+            SensitiveKind::HomeDirectory => "This is your whole home folder.",
+            SensitiveKind::BrowserProfile => {
+                "This is a browser profile, with saved passwords and cookies."
+            }
+            SensitiveKind::KeysDirectory => "This folder holds SSH, GPG or cloud credentials.",
+            SensitiveKind::SecretsFile => "This looks like a private key or secrets file.",
+        }
+    }
+}
+
+/// One finding: `path` is what matched, which may be a file inside a selected folder.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+pub struct SensitivePath {
+    pub path: String,
+    pub kind: SensitiveKind,
+    pub message: String,
+}
+
+impl SensitivePath {
+    fn new(path: &Path, kind: SensitiveKind) -> Self {
+        SensitivePath {
+            path: path.to_string_lossy().into_owned(),
+            kind,
+            message: kind.message().to_string(),
+        }
+    }
+}
+
+const KEY_DIRECTORIES: &[&str] = &[".ssh", ".gnupg", ".aws", ".azure", ".kube", ".docker"];
+
+// Path fragments (lowercased, `/`-separated) of browser profile directories.
+const BROWSER_PROFILES: &[&str] = &[
+    ".mozilla/firefox",
+    "mozilla/firefox/profiles",
+    "library/application support/firefox",
+    ".config/google-chrome",
+    ".config/chromium",
+    ".config/bravesoftware",
+    ".config/microsoft-edge",
+    "google/chrome/user data",
+    "microsoft/edge/user data",
+    "bravesoftware/brave-browser/user data",
+    "library/application support/google/chrome",
+    "library/application support/bravesoftware",
+    "library/application support/microsoft edge",
+    "library/safari",
+];
+
+const SECRET_FILE_NAMES: &[&str] = &[
+    "id_rsa",
+    "id_dsa",
+    "id_ecdsa",
+    "id_ed25519",
+    ".env",
+    ".netrc",
+    ".npmrc",
+    ".pgpass",
+    ".git-credentials",
+    "credentials",
+    "credentials.json",
+    "login data",
+    "key4.db",
+    "logins.json",
+];
+
+const SECRET_EXTENSIONS: &[&str] = &["pem", "key", "p12", "pfx", "kdbx", "keychain", "gpg"];
+
+fn is_secret_file(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SECRET_FILE_NAMES.contains(&name.as_str())
+        || name.starts_with(".env.")
+        || Path::new(&name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| SECRET_EXTENSIONS.contains(&e))
+}
+
+fn lowercase_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_lowercase()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// The kind of `path` itself, from its location alone.
+fn classify(path: &Path, home: Option<&Path>) -> Option<SensitiveKind> {
+    if home.is_some_and(|home| path == home) {
+        return Some(SensitiveKind::HomeDirectory);
+    }
+    let lowered = lowercase_path(path);
+    if BROWSER_PROFILES
+        .iter()
+        .any(|fragment| lowered.contains(fragment))
+    {
+        return Some(SensitiveKind::BrowserProfile);
+    }
+    let in_keys_dir = path.components().any(|c| {
+        matches!(c, Component::Normal(part)
+            if KEY_DIRECTORIES.contains(&part.to_string_lossy().to_lowercase().as_str()))
+    });
+    if in_keys_dir {
+        return Some(SensitiveKind::KeysDirectory);
+    }
+    if path.is_file()
+        && path
+            .file_name()
+            .is_some_and(|name| is_secret_file(&name.to_string_lossy()))
+    {
+        return Some(SensitiveKind::SecretsFile);
+    }
+    None
+}
+
+// Look inside a selected folder for key directories and secrets files, bounded by
+// `MAX_DEPTH` and `MAX_ENTRIES`. Stops at the first finding.
+fn scan_folder(dir: &Path, depth: usize, seen: &mut usize) -> Option<SensitivePath> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let entries = fs::read_dir(dir).ok()?;
+    for entry in entries.flatten() {
+        *seen += 1;
+        if *seen > MAX_ENTRIES {
+            return None;
+        }
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if KEY_DIRECTORIES.contains(&name.as_str()) {
+                return Some(SensitivePath::new(&path, SensitiveKind::KeysDirectory));
+            }
+            if let Some(found) = scan_folder(&path, depth + 1, seen) {
+                return Some(found);
+            }
+        } else if file_type.is_file() && is_secret_file(&name) {
+            return Some(SensitivePath::new(&path, SensitiveKind::SecretsFile));
+        }
+    }
+    None
+}
+
+/// Findings for a send selection, at most one per selected path. `home` is the user's home
+/// directory, when known.
+pub fn find_sensitive(paths: &[String], home: Option<&Path>) -> Vec<SensitivePath> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let path = Path::new(path);
+            if let Some(kind) = classify(path, home) {
+                return Some(SensitivePath::new(path, kind));
+            }
+            path.is_dir()
+                .then(|| scan_folder(path, 0, &mut 0))
+                .flatten()
+        })
+        .collect()
+}
+
+/// Check a send selection before it goes out (see `find_sensitive`), against the OS home
+/// directory.
+pub async fn check_sensitive_paths(paths: Vec<String>) -> Result<Vec<SensitivePath>, String> {
+    tokio::task::spawn_blocking(move || {
+        let home = dirs::home_dir().and_then(|home| fs::canonicalize(home).ok());
+        let paths: Vec<String> = paths
+            .into_iter()
+            .map(|p| {
+                fs::canonicalize(&p)
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .unwrap_or(p)
+            })
+            .collect();
+        find_sensitive(&paths, home.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Failed to check the selection: {}", e))
+}
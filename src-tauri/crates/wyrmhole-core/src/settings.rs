@@ -0,0 +1,2068 @@
+// This file contains all settings logic for wyrmhole.
+// Creates and modifies the settings file, and provides public API functions for settings operations.
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::api;
+use crate::cache;
+use crate::context::{AppContext, AppPaths};
+use crate::crash;
+use crate::events;
+use crate::files;
+use crate::files_json;
+use crate::hooks::{self, ReceiveHook};
+use crate::throttle::{self, RateLimit, RateWindow};
+use crate::updates;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppSettings {
+    pub download_directory: PathBuf,
+    /// Extract received archives (tarballs, zips; see extract.rs). Kept under its old name.
+    #[serde(default = "default_auto_extract")]
+    pub auto_extract_tarballs: bool,
+    /// Receive into `<download dir>/wyrmhole/YYYY-MM-DD/` instead of the download dir itself.
+    #[serde(default = "default_date_subfolders")]
+    pub date_subfolders: bool,
+    /// Save received text messages as .txt files without asking.
+    #[serde(default = "default_save_text_offers")]
+    pub save_text_offers: bool,
+    /// Rules run on each received file, in order (see hooks.rs).
+    #[serde(default = "default_receive_hooks")]
+    pub receive_hooks: Vec<ReceiveHook>,
+    #[serde(default = "default_folder_name_format")]
+    pub default_folder_name_format: String,
+    /// Template for naming received files, e.g. "{date}_{name}"; empty keeps the sender's name.
+    #[serde(default = "default_received_file_name_template")]
+    pub received_file_name_template: String,
+    #[serde(default = "default_relay_server_url")]
+    pub relay_server_url: Option<String>,
+    /// Download speed cap in KiB/s; None means unlimited.
+    #[serde(default = "default_download_rate_limit_kib")]
+    pub download_rate_limit_kib: Option<u64>,
+    /// Upload (send) speed cap in KiB/s; None means unlimited.
+    #[serde(default = "default_upload_rate_limit_kib")]
+    pub upload_rate_limit_kib: Option<u64>,
+    /// Time windows with their own cap, overriding both `download_rate_limit_kib` and
+    /// `upload_rate_limit_kib` while they're on (see throttle.rs). The first window
+    /// containing the time wins.
+    #[serde(default = "default_rate_limit_schedule")]
+    pub rate_limit_schedule: Vec<RateWindow>,
+    /// How many sends may run at once; further sends wait as `queued`. 0 means no limit.
+    #[serde(default = "default_max_concurrent_sends")]
+    pub max_concurrent_sends: u32,
+    /// Leave the code out of send-progress events; hosts fetch it with `files::reveal_code`.
+    #[serde(default = "default_hide_send_codes")]
+    pub hide_send_codes: bool,
+    /// Abort a transfer after this many seconds without progress. 0 means never.
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+    /// Extract folder sync updates (`.sync.tar.gz`, see sync.rs) into the folder they update,
+    /// overwriting files there, instead of alongside it.
+    #[serde(default = "default_merge_folder_updates")]
+    pub merge_folder_updates: bool,
+    /// Patch the earlier copy of a file with a received delta (`.wyrmdelta`, see delta.rs).
+    #[serde(default = "default_apply_file_deltas")]
+    pub apply_file_deltas: bool,
+    /// Build folder tarballs reproducibly (sorted entries, fixed mtimes and owners), so sending
+    /// unchanged content twice gives byte-identical archives.
+    #[serde(default = "default_deterministic_archives")]
+    pub deterministic_archives: bool,
+    /// Send folders and bundles as a plain tar written while it's sent (see stream.rs),
+    /// instead of packing a gzipped tarball into a temp file first. Sends that ask for a
+    /// compression level are packed anyway, since a stream's size must be known up front.
+    #[serde(default = "default_stream_archives")]
+    pub stream_archives: bool,
+    /// Split folder tarballs bigger than this many MiB into parts sent one after another (see
+    /// parts.rs). 0 means never split.
+    #[serde(default = "default_archive_part_size_mib")]
+    pub archive_part_size_mib: u64,
+    /// gzip level for folder and multi-file tarballs, 0 (store only) to 9; sends can override
+    /// it (see `files::send_multiple_files_call`).
+    #[serde(default = "default_compression_level")]
+    pub compression_level: u32,
+    /// More relays offered to peers after `relay_server_url` (or the default relay), in order.
+    /// `benchmark_relays` reorders them all fastest first.
+    #[serde(default = "default_extra_relay_urls")]
+    pub extra_relay_urls: Vec<String>,
+    /// How peers may connect, for sends and receives alike: "any", "direct" (never through a
+    /// relay) or "relay" (always through one, hiding each side's address; see
+    /// `files::TRANSIT_MODES`).
+    #[serde(default = "default_transit_mode")]
+    pub transit_mode: String,
+    /// Keep sends waiting (status `offline`) when the rendezvous server can't be reached and
+    /// retry until it can, instead of failing them.
+    #[serde(default = "default_queue_offline_sends")]
+    pub queue_offline_sends: bool,
+    /// URL to POST a JSON summary of each completed transfer to (see webhook.rs).
+    #[serde(default = "default_webhook_url")]
+    pub webhook_url: Option<String>,
+    /// Close a send's mailbox when nobody has used its code after this many minutes (0 = never).
+    #[serde(default = "default_code_expiry_mins")]
+    pub code_expiry_mins: u64,
+    /// Decline an incoming offer nobody has answered after this many minutes, closing the
+    /// sender's wormhole (0 = never).
+    #[serde(default = "default_offer_expiry_mins")]
+    pub offer_expiry_mins: u64,
+    /// How sent files' paths are kept in history: "full", "names" (file names only) or
+    /// "hashed" (salted SHA-256 of each path; see `files_json::SENT_PATH_MODES`).
+    #[serde(default = "default_sent_path_privacy")]
+    pub sent_path_privacy: String,
+    /// Salt for "hashed" sent paths, made on first use so hashes differ between installs.
+    #[serde(default)]
+    pub sent_path_salt: String,
+    /// Remove a received file when its checksum matches one already in the received history
+    /// and still on disk, pointing its history entry at the existing copy instead.
+    #[serde(default = "default_skip_duplicate_receives")]
+    pub skip_duplicate_receives: bool,
+    /// Re-read every received file after it is written and check it against the checksum
+    /// taken while receiving, failing the download on a mismatch.
+    #[serde(default = "default_verify_disk_writes")]
+    pub verify_disk_writes: bool,
+    /// Offers bigger than this many MiB are flagged and need confirming when accepted; 0 for no
+    /// limit.
+    #[serde(default = "default_max_incoming_size_mib")]
+    pub max_incoming_size_mib: u64,
+    /// File extensions (e.g. "exe", "sh") received without a content warning (see content.rs);
+    /// "*" allows everything.
+    #[serde(default = "default_content_allowlist")]
+    pub content_allowlist: Vec<String>,
+    /// How long a send whose packaging failed waits for `jobs::retry_job` before giving up,
+    /// keeping its code and receiver. 0 fails the send straight away.
+    #[serde(default = "default_packaging_retry_secs")]
+    pub packaging_retry_secs: u64,
+    /// Keep up to this many MiB of packaged tarballs for re-sends (see cache.rs); 0 keeps none.
+    #[serde(default = "default_package_cache_mib")]
+    pub package_cache_mib: u64,
+    /// Serve the localhost API (see api.rs) on 127.0.0.1:`local_api_port`.
+    #[serde(default = "default_local_api_enabled")]
+    pub local_api_enabled: bool,
+    #[serde(default = "default_local_api_port")]
+    pub local_api_port: u16,
+    /// Bearer token every local API request must carry, made when the API is first enabled.
+    #[serde(default)]
+    pub local_api_token: String,
+    #[serde(default = "default_minimize_on_start")]
+    pub minimize_on_start: bool,
+    #[serde(default = "default_minimize_on_close")]
+    pub minimize_on_close: bool,
+    #[serde(default = "default_usage_metrics_enabled")]
+    pub usage_metrics_enabled: bool,
+    #[serde(default = "default_crash_reports_enabled")]
+    pub crash_reports_enabled: bool,
+    /// Play a sound for incoming offers and finished transfers (played by the host).
+    #[serde(default = "default_sounds_enabled")]
+    pub sounds_enabled: bool,
+    /// Sound volume, 0-100.
+    #[serde(default = "default_sound_volume")]
+    pub sound_volume: u8,
+    #[serde(default = "default_check_updates_automatically")]
+    pub check_updates_automatically: bool,
+    #[serde(default = "default_auto_download_updates")]
+    pub auto_download_updates: bool,
+    #[serde(default = "default_release_channel")]
+    pub release_channel: String,
+    /// Where received_files.json and sent_files.json live; None means the app data directory.
+    #[serde(default = "default_history_directory")]
+    pub history_directory: Option<PathBuf>,
+}
+
+fn default_auto_extract() -> bool {
+    false
+}
+
+fn default_date_subfolders() -> bool {
+    false
+}
+
+fn default_save_text_offers() -> bool {
+    false
+}
+
+fn default_receive_hooks() -> Vec<ReceiveHook> {
+    Vec::new()
+}
+
+fn default_download_rate_limit_kib() -> Option<u64> {
+    None
+}
+
+fn default_upload_rate_limit_kib() -> Option<u64> {
+    None
+}
+
+fn default_rate_limit_schedule() -> Vec<RateWindow> {
+    Vec::new()
+}
+
+fn default_max_concurrent_sends() -> u32 {
+    0
+}
+
+fn default_hide_send_codes() -> bool {
+    false
+}
+
+fn default_idle_timeout_secs() -> u64 {
+    0
+}
+
+fn default_merge_folder_updates() -> bool {
+    false
+}
+
+fn default_apply_file_deltas() -> bool {
+    false
+}
+
+fn default_deterministic_archives() -> bool {
+    false
+}
+
+fn default_stream_archives() -> bool {
+    false
+}
+
+fn default_archive_part_size_mib() -> u64 {
+    0
+}
+
+fn default_compression_level() -> u32 {
+    1
+}
+
+fn default_extra_relay_urls() -> Vec<String> {
+    Vec::new()
+}
+
+fn default_transit_mode() -> String {
+    "any".to_string()
+}
+
+fn default_queue_offline_sends() -> bool {
+    false
+}
+
+fn default_webhook_url() -> Option<String> {
+    None
+}
+
+fn default_code_expiry_mins() -> u64 {
+    0
+}
+
+fn default_offer_expiry_mins() -> u64 {
+    0
+}
+
+fn default_sent_path_privacy() -> String {
+    "full".to_string()
+}
+
+fn default_skip_duplicate_receives() -> bool {
+    false
+}
+
+fn default_verify_disk_writes() -> bool {
+    false
+}
+
+fn default_max_incoming_size_mib() -> u64 {
+    0
+}
+
+fn default_content_allowlist() -> Vec<String> {
+    Vec::new()
+}
+
+fn default_packaging_retry_secs() -> u64 {
+    0
+}
+
+fn default_package_cache_mib() -> u64 {
+    0
+}
+
+fn default_local_api_enabled() -> bool {
+    false
+}
+
+fn default_local_api_port() -> u16 {
+    47613
+}
+
+fn default_minimize_on_start() -> bool {
+    false
+}
+
+fn default_minimize_on_close() -> bool {
+    true
+}
+
+fn default_usage_metrics_enabled() -> bool {
+    false
+}
+
+fn default_crash_reports_enabled() -> bool {
+    false
+}
+
+fn default_sounds_enabled() -> bool {
+    false
+}
+
+fn default_sound_volume() -> u8 {
+    70
+}
+
+fn default_check_updates_automatically() -> bool {
+    false
+}
+
+fn default_auto_download_updates() -> bool {
+    false
+}
+
+fn default_release_channel() -> String {
+    "stable".to_string()
+}
+
+fn default_history_directory() -> Option<PathBuf> {
+    None
+}
+
+fn default_folder_name_format() -> String {
+    "#-files-via-wyrmhole".to_string()
+}
+
+fn default_received_file_name_template() -> String {
+    String::new()
+}
+
+fn default_relay_server_url() -> Option<String> {
+    None
+}
+
+/// A public relay server offered in settings so it doesn't have to be typed in.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+pub struct RelayPreset {
+    pub id: String,
+    pub name: String,
+    /// `None` for magic-wormhole's built-in default relay.
+    pub url: Option<String>,
+    pub description: String,
+}
+
+// (id, name, url, description); an empty url means the built-in default.
+const RELAY_PRESETS: &[(&str, &str, &str, &str)] = &[
+    (
+        "default",
+        "magic-wormhole (default)",
+        "",
+        "The relay built into magic-wormhole, run by its maintainers",
+    ),
+    (
+        "least-authority",
+        "Least Authority",
+        "tcp://relay.mw.leastauthority.com:4001",
+        "Public relay run by Least Authority for the Winden app",
+    ),
+    (
+        "least-authority-wss",
+        "Least Authority (WebSocket)",
+        "wss://relay.mw.leastauthority.com",
+        "The same relay over WebSockets, for networks that only allow HTTPS",
+    ),
+];
+
+impl AppSettings {
+    /// Default settings, saving downloads into `download_directory`.
+    pub fn defaults(download_directory: PathBuf) -> Self {
+        AppSettings {
+            download_directory,
+            auto_extract_tarballs: false,
+            date_subfolders: default_date_subfolders(),
+            save_text_offers: default_save_text_offers(),
+            receive_hooks: default_receive_hooks(),
+            default_folder_name_format: default_folder_name_format(),
+            received_file_name_template: default_received_file_name_template(),
+            relay_server_url: default_relay_server_url(),
+            download_rate_limit_kib: default_download_rate_limit_kib(),
+            upload_rate_limit_kib: default_upload_rate_limit_kib(),
+            rate_limit_schedule: default_rate_limit_schedule(),
+            max_concurrent_sends: default_max_concurrent_sends(),
+            hide_send_codes: default_hide_send_codes(),
+            idle_timeout_secs: default_idle_timeout_secs(),
+            merge_folder_updates: default_merge_folder_updates(),
+            apply_file_deltas: default_apply_file_deltas(),
+            deterministic_archives: default_deterministic_archives(),
+            stream_archives: default_stream_archives(),
+            archive_part_size_mib: default_archive_part_size_mib(),
+            compression_level: default_compression_level(),
+            extra_relay_urls: default_extra_relay_urls(),
+            transit_mode: default_transit_mode(),
+            queue_offline_sends: default_queue_offline_sends(),
+            webhook_url: default_webhook_url(),
+            code_expiry_mins: default_code_expiry_mins(),
+            offer_expiry_mins: default_offer_expiry_mins(),
+            sent_path_privacy: default_sent_path_privacy(),
+            sent_path_salt: String::new(),
+            skip_duplicate_receives: default_skip_duplicate_receives(),
+            verify_disk_writes: default_verify_disk_writes(),
+            max_incoming_size_mib: default_max_incoming_size_mib(),
+            content_allowlist: default_content_allowlist(),
+            packaging_retry_secs: default_packaging_retry_secs(),
+            package_cache_mib: default_package_cache_mib(),
+            local_api_enabled: default_local_api_enabled(),
+            local_api_port: default_local_api_port(),
+            local_api_token: String::new(),
+            minimize_on_start: default_minimize_on_start(),
+            minimize_on_close: default_minimize_on_close(),
+            usage_metrics_enabled: default_usage_metrics_enabled(),
+            crash_reports_enabled: default_crash_reports_enabled(),
+            sounds_enabled: default_sounds_enabled(),
+            sound_volume: default_sound_volume(),
+            check_updates_automatically: default_check_updates_automatically(),
+            auto_download_updates: default_auto_download_updates(),
+            release_channel: default_release_channel(),
+            history_directory: default_history_directory(),
+        }
+    }
+
+    pub fn get_download_directory(&self) -> &PathBuf {
+        &self.download_directory
+    }
+
+    pub fn set_download_directory(&mut self, path: PathBuf) {
+        self.download_directory = path;
+    }
+
+    pub fn get_auto_extract_tarballs(&self) -> bool {
+        self.auto_extract_tarballs
+    }
+
+    pub fn set_auto_extract_tarballs(&mut self, value: bool) {
+        self.auto_extract_tarballs = value;
+    }
+
+    pub fn get_date_subfolders(&self) -> bool {
+        self.date_subfolders
+    }
+
+    pub fn set_date_subfolders(&mut self, value: bool) {
+        self.date_subfolders = value;
+    }
+
+    pub fn get_save_text_offers(&self) -> bool {
+        self.save_text_offers
+    }
+
+    pub fn set_save_text_offers(&mut self, value: bool) {
+        self.save_text_offers = value;
+    }
+
+    pub fn get_receive_hooks(&self) -> &Vec<ReceiveHook> {
+        &self.receive_hooks
+    }
+
+    pub fn set_receive_hooks(&mut self, value: Vec<ReceiveHook>) {
+        self.receive_hooks = value;
+    }
+
+    pub fn get_default_folder_name_format(&self) -> &String {
+        &self.default_folder_name_format
+    }
+
+    pub fn set_default_folder_name_format(&mut self, value: String) {
+        self.default_folder_name_format = value;
+    }
+
+    pub fn get_received_file_name_template(&self) -> &str {
+        &self.received_file_name_template
+    }
+
+    pub fn set_received_file_name_template(&mut self, value: String) {
+        self.received_file_name_template = value;
+    }
+
+    pub fn get_relay_server_url(&self) -> Option<&str> {
+        self.relay_server_url.as_deref()
+    }
+
+    pub fn set_relay_server_url(&mut self, value: Option<String>) {
+        self.relay_server_url = value;
+    }
+
+    pub fn get_download_rate_limit_kib(&self) -> Option<u64> {
+        self.download_rate_limit_kib
+    }
+
+    pub fn set_download_rate_limit_kib(&mut self, value: Option<u64>) {
+        self.download_rate_limit_kib = value;
+    }
+
+    pub fn get_upload_rate_limit_kib(&self) -> Option<u64> {
+        self.upload_rate_limit_kib
+    }
+
+    pub fn set_upload_rate_limit_kib(&mut self, value: Option<u64>) {
+        self.upload_rate_limit_kib = value;
+    }
+
+    /// The cap downloads follow: `download_rate_limit_kib`, or the schedule's window.
+    pub fn download_rate_limit(&self) -> RateLimit {
+        RateLimit {
+            default_kib: self.download_rate_limit_kib,
+            schedule: self.rate_limit_schedule.clone(),
+        }
+    }
+
+    /// The cap sends follow: `upload_rate_limit_kib`, or the schedule's window.
+    pub fn upload_rate_limit(&self) -> RateLimit {
+        RateLimit {
+            default_kib: self.upload_rate_limit_kib,
+            schedule: self.rate_limit_schedule.clone(),
+        }
+    }
+
+    pub fn get_rate_limit_schedule(&self) -> &Vec<RateWindow> {
+        &self.rate_limit_schedule
+    }
+
+    pub fn set_rate_limit_schedule(&mut self, value: Vec<RateWindow>) {
+        self.rate_limit_schedule = value;
+    }
+
+    pub fn get_max_concurrent_sends(&self) -> u32 {
+        self.max_concurrent_sends
+    }
+
+    pub fn set_max_concurrent_sends(&mut self, value: u32) {
+        self.max_concurrent_sends = value;
+    }
+
+    pub fn get_hide_send_codes(&self) -> bool {
+        self.hide_send_codes
+    }
+
+    pub fn set_hide_send_codes(&mut self, value: bool) {
+        self.hide_send_codes = value;
+    }
+
+    pub fn get_idle_timeout_secs(&self) -> u64 {
+        self.idle_timeout_secs
+    }
+
+    pub fn set_idle_timeout_secs(&mut self, value: u64) {
+        self.idle_timeout_secs = value;
+    }
+
+    pub fn get_merge_folder_updates(&self) -> bool {
+        self.merge_folder_updates
+    }
+
+    pub fn set_merge_folder_updates(&mut self, value: bool) {
+        self.merge_folder_updates = value;
+    }
+
+    pub fn get_apply_file_deltas(&self) -> bool {
+        self.apply_file_deltas
+    }
+
+    pub fn set_apply_file_deltas(&mut self, value: bool) {
+        self.apply_file_deltas = value;
+    }
+
+    pub fn get_deterministic_archives(&self) -> bool {
+        self.deterministic_archives
+    }
+
+    pub fn set_deterministic_archives(&mut self, value: bool) {
+        self.deterministic_archives = value;
+    }
+
+    pub fn get_stream_archives(&self) -> bool {
+        self.stream_archives
+    }
+
+    pub fn set_stream_archives(&mut self, value: bool) {
+        self.stream_archives = value;
+    }
+
+    pub fn get_archive_part_size_mib(&self) -> u64 {
+        self.archive_part_size_mib
+    }
+
+    pub fn set_archive_part_size_mib(&mut self, value: u64) {
+        self.archive_part_size_mib = value;
+    }
+
+    pub fn get_compression_level(&self) -> u32 {
+        self.compression_level
+    }
+
+    pub fn set_compression_level(&mut self, value: u32) {
+        self.compression_level = value;
+    }
+
+    pub fn get_extra_relay_urls(&self) -> &Vec<String> {
+        &self.extra_relay_urls
+    }
+
+    pub fn set_extra_relay_urls(&mut self, value: Vec<String>) {
+        self.extra_relay_urls = value;
+    }
+
+    pub fn get_transit_mode(&self) -> &str {
+        &self.transit_mode
+    }
+
+    pub fn set_transit_mode(&mut self, value: String) {
+        self.transit_mode = value;
+    }
+
+    pub fn get_queue_offline_sends(&self) -> bool {
+        self.queue_offline_sends
+    }
+
+    pub fn set_queue_offline_sends(&mut self, value: bool) {
+        self.queue_offline_sends = value;
+    }
+
+    pub fn get_webhook_url(&self) -> Option<&str> {
+        self.webhook_url.as_deref()
+    }
+
+    pub fn set_webhook_url(&mut self, value: Option<String>) {
+        self.webhook_url = value;
+    }
+
+    pub fn get_code_expiry_mins(&self) -> u64 {
+        self.code_expiry_mins
+    }
+
+    pub fn set_code_expiry_mins(&mut self, value: u64) {
+        self.code_expiry_mins = value;
+    }
+
+    pub fn get_offer_expiry_mins(&self) -> u64 {
+        self.offer_expiry_mins
+    }
+
+    pub fn set_offer_expiry_mins(&mut self, value: u64) {
+        self.offer_expiry_mins = value;
+    }
+
+    pub fn get_sent_path_privacy(&self) -> &str {
+        &self.sent_path_privacy
+    }
+
+    pub fn set_sent_path_privacy(&mut self, value: String) {
+        self.sent_path_privacy = value;
+    }
+
+    pub fn get_sent_path_salt(&self) -> &str {
+        &self.sent_path_salt
+    }
+
+    pub fn set_sent_path_salt(&mut self, value: String) {
+        self.sent_path_salt = value;
+    }
+
+    pub fn get_skip_duplicate_receives(&self) -> bool {
+        self.skip_duplicate_receives
+    }
+
+    pub fn set_skip_duplicate_receives(&mut self, value: bool) {
+        self.skip_duplicate_receives = value;
+    }
+
+    pub fn get_verify_disk_writes(&self) -> bool {
+        self.verify_disk_writes
+    }
+
+    pub fn set_verify_disk_writes(&mut self, value: bool) {
+        self.verify_disk_writes = value;
+    }
+
+    pub fn get_max_incoming_size_mib(&self) -> u64 {
+        self.max_incoming_size_mib
+    }
+
+    pub fn set_max_incoming_size_mib(&mut self, value: u64) {
+        self.max_incoming_size_mib = value;
+    }
+
+    pub fn get_content_allowlist(&self) -> &Vec<String> {
+        &self.content_allowlist
+    }
+
+    pub fn set_content_allowlist(&mut self, value: Vec<String>) {
+        self.content_allowlist = value;
+    }
+
+    pub fn get_packaging_retry_secs(&self) -> u64 {
+        self.packaging_retry_secs
+    }
+
+    pub fn set_packaging_retry_secs(&mut self, value: u64) {
+        self.packaging_retry_secs = value;
+    }
+
+    pub fn get_package_cache_mib(&self) -> u64 {
+        self.package_cache_mib
+    }
+
+    pub fn set_package_cache_mib(&mut self, value: u64) {
+        self.package_cache_mib = value;
+    }
+
+    pub fn get_local_api_enabled(&self) -> bool {
+        self.local_api_enabled
+    }
+
+    pub fn set_local_api_enabled(&mut self, value: bool) {
+        self.local_api_enabled = value;
+    }
+
+    pub fn get_local_api_port(&self) -> u16 {
+        self.local_api_port
+    }
+
+    pub fn set_local_api_port(&mut self, value: u16) {
+        self.local_api_port = value;
+    }
+
+    pub fn get_local_api_token(&self) -> &str {
+        &self.local_api_token
+    }
+
+    pub fn set_local_api_token(&mut self, value: String) {
+        self.local_api_token = value;
+    }
+
+    pub fn get_minimize_on_start(&self) -> bool {
+        self.minimize_on_start
+    }
+
+    pub fn set_minimize_on_start(&mut self, value: bool) {
+        self.minimize_on_start = value;
+    }
+
+    pub fn get_minimize_on_close(&self) -> bool {
+        self.minimize_on_close
+    }
+
+    pub fn set_minimize_on_close(&mut self, value: bool) {
+        self.minimize_on_close = value;
+    }
+
+    pub fn get_usage_metrics_enabled(&self) -> bool {
+        self.usage_metrics_enabled
+    }
+
+    pub fn set_usage_metrics_enabled(&mut self, value: bool) {
+        self.usage_metrics_enabled = value;
+    }
+
+    pub fn get_crash_reports_enabled(&self) -> bool {
+        self.crash_reports_enabled
+    }
+
+    pub fn set_crash_reports_enabled(&mut self, value: bool) {
+        self.crash_reports_enabled = value;
+    }
+
+    pub fn get_sounds_enabled(&self) -> bool {
+        self.sounds_enabled
+    }
+
+    pub fn set_sounds_enabled(&mut self, value: bool) {
+        self.sounds_enabled = value;
+    }
+
+    pub fn get_sound_volume(&self) -> u8 {
+        self.sound_volume
+    }
+
+    pub fn set_sound_volume(&mut self, value: u8) {
+        self.sound_volume = value.min(100);
+    }
+
+    pub fn get_check_updates_automatically(&self) -> bool {
+        self.check_updates_automatically
+    }
+
+    pub fn set_check_updates_automatically(&mut self, value: bool) {
+        self.check_updates_automatically = value;
+    }
+
+    pub fn get_auto_download_updates(&self) -> bool {
+        self.auto_download_updates
+    }
+
+    pub fn set_auto_download_updates(&mut self, value: bool) {
+        self.auto_download_updates = value;
+    }
+
+    pub fn get_release_channel(&self) -> &str {
+        &self.release_channel
+    }
+
+    pub fn set_release_channel(&mut self, value: String) {
+        self.release_channel = value;
+    }
+
+    pub fn get_history_directory(&self) -> Option<&PathBuf> {
+        self.history_directory.as_ref()
+    }
+
+    pub fn set_history_directory(&mut self, value: Option<PathBuf>) {
+        self.history_directory = value;
+    }
+}
+
+// Gets the config path of the applications operating system and appends a settings.json.
+pub fn get_settings_path(paths: &AppPaths) -> PathBuf {
+    let mut path = paths.config_dir.clone();
+
+    // Ensure the config directory exists before writing to it.
+    if !path.exists()
+        && let Err(e) = fs::create_dir_all(&path)
+    {
+        log_line!(
+            "[magic-wormhole][settings][error] Failed to create config directory: {}",
+            e
+        );
+    }
+
+    path.push("settings.json");
+    path
+}
+
+// Appends received_files.json to the history directory (see `AppContext::history_dir`).
+pub fn get_received_files_path(history_dir: &Path) -> PathBuf {
+    let mut path = history_dir.to_path_buf();
+
+    // Ensure the history directory exists before writing to it.
+    if !path.exists()
+        && let Err(e) = fs::create_dir_all(&path)
+    {
+        log_line!(
+            "[magic-wormhole][settings][error] Failed to create history directory: {}",
+            e
+        );
+    }
+
+    path.push("received_files.json");
+    path
+}
+
+// Appends sent_files.json to the history directory (see `AppContext::history_dir`).
+pub fn get_sent_files_path(history_dir: &Path) -> PathBuf {
+    let mut path = history_dir.to_path_buf();
+
+    // Ensure the history directory exists before writing to it.
+    if !path.exists()
+        && let Err(e) = fs::create_dir_all(&path)
+    {
+        log_line!(
+            "[magic-wormhole][settings][error] Failed to create history directory: {}",
+            e
+        );
+    }
+
+    path.push("sent_files.json");
+    path
+}
+
+const HISTORY_FILES: [&str; 2] = ["received_files.json", "sent_files.json"];
+
+// Move the history files from `from` to `to`. Checks every file before moving any so a
+// conflict never leaves history split between the two directories.
+fn migrate_history(from: &Path, to: &Path) -> Result<(), String> {
+    let _lock = files_json::lock_history(from);
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create history directory: {}", e))?;
+    for name in HISTORY_FILES {
+        if from.join(name).exists() && to.join(name).exists() {
+            return Err(format!(
+                "{} already exists in {}; move or remove it first",
+                name,
+                to.display()
+            ));
+        }
+    }
+    for name in HISTORY_FILES {
+        let (source, target) = (from.join(name), to.join(name));
+        if !source.exists() {
+            continue;
+        }
+        // rename fails across filesystems; fall back to copy and delete.
+        if fs::rename(&source, &target).is_err() {
+            fs::copy(&source, &target)
+                .and_then(|_| fs::remove_file(&source))
+                .map_err(|e| format!("Failed to move {}: {}", name, e))?;
+        }
+        log_line!(
+            "[magic-wormhole][settings][info] Moved {} to {}",
+            name,
+            to.display()
+        );
+    }
+    Ok(())
+}
+
+// Creates an instance of AppSettings with default values.
+fn create_default_settings(paths: &AppPaths) -> AppSettings {
+    AppSettings::defaults(paths.default_download_dir.clone())
+}
+
+// Initializes wyrmhole settings.json.
+pub fn init_settings(paths: &AppPaths) -> AppSettings {
+    let settings_path = get_settings_path(paths);
+
+    // Attempt to load settings from file.
+    if settings_path.exists() {
+        if let Ok(content) = fs::read_to_string(&settings_path) {
+            if let Ok(settings) = serde_json::from_str::<AppSettings>(&content) {
+                log_line!(
+                    "[magic-wormhole][settings][info] Settings loaded from {}",
+                    settings_path.display()
+                );
+                return settings;
+            } else {
+                log_line!(
+                    "[magic-wormhole][settings][error] Failed to parse settings.json; creating defaults at {}",
+                    settings_path.display()
+                );
+            }
+        } else {
+            log_line!(
+                "[magic-wormhole][settings][error] Failed to read settings.json; creating defaults at {}",
+                settings_path.display()
+            );
+        }
+    } else {
+        log_line!(
+            "[magic-wormhole][settings][info] settings.json not found; creating defaults at {}",
+            settings_path.display()
+        );
+    }
+
+    // If loading failed or file didn't exist, create and save default settings.
+    let default_settings = create_default_settings(paths);
+    if let Err(e) = save_settings(&default_settings, &settings_path) {
+        log_line!(
+            "[magic-wormhole][settings][error] Failed to save default settings: {}",
+            e
+        );
+    }
+    default_settings
+}
+
+// Saves the current AppSettings struct information to the settings.json file.
+pub fn save_settings(
+    settings: &AppSettings,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(settings)?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// Loads and persists AppSettings. The engine only goes through this trait, so a host
+/// can keep settings somewhere other than settings.json (or purely in memory).
+pub trait SettingsStore: Send + Sync {
+    fn load(&self) -> AppSettings;
+    fn save(&self, settings: &AppSettings) -> Result<(), String>;
+}
+
+/// The default store: settings.json in the app config directory.
+pub struct JsonSettingsStore {
+    paths: AppPaths,
+}
+
+impl JsonSettingsStore {
+    pub fn new(paths: AppPaths) -> Self {
+        JsonSettingsStore { paths }
+    }
+}
+
+impl SettingsStore for JsonSettingsStore {
+    fn load(&self) -> AppSettings {
+        init_settings(&self.paths)
+    }
+
+    fn save(&self, settings: &AppSettings) -> Result<(), String> {
+        save_settings(settings, &get_settings_path(&self.paths)).map_err(|e| e.to_string())
+    }
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+pub async fn set_download_directory(ctx: &AppContext, new_path: String) -> Result<(), String> {
+    let new_path_buf = PathBuf::from(&new_path);
+
+    // Check if path exists and is a directory
+    if !new_path_buf.exists() {
+        return Err("Provided path does not exist.".to_string());
+    }
+    if !new_path_buf.is_dir() {
+        return Err("Provided path is not a directory.".to_string());
+    }
+
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_download_directory(new_path_buf);
+
+    // Save settings
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_download_path(ctx: &AppContext) -> Result<String, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    let dir = app_settings_lock
+        .get_download_directory()
+        .to_string_lossy()
+        .to_string();
+    Ok(dir)
+}
+
+pub async fn get_auto_extract_tarballs(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_auto_extract_tarballs())
+}
+
+pub async fn set_auto_extract_tarballs(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_auto_extract_tarballs(value);
+
+    // Save settings
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_date_subfolders(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_date_subfolders())
+}
+
+pub async fn set_date_subfolders(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_date_subfolders(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_save_text_offers(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_save_text_offers())
+}
+
+pub async fn set_save_text_offers(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_save_text_offers(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_receive_hooks(ctx: &AppContext) -> Result<Vec<ReceiveHook>, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_receive_hooks().clone())
+}
+
+pub async fn set_receive_hooks(ctx: &AppContext, value: Vec<ReceiveHook>) -> Result<(), String> {
+    value.iter().try_for_each(hooks::validate)?;
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_receive_hooks(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_default_folder_name_format(ctx: &AppContext) -> Result<String, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_default_folder_name_format().clone())
+}
+
+pub async fn set_default_folder_name_format(ctx: &AppContext, value: String) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_default_folder_name_format(value.clone());
+
+    // Save settings
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    // Emit event to notify frontend that the setting has been updated
+    let _ = ctx.emit(
+        events::DEFAULT_FOLDER_NAME_FORMAT_UPDATED,
+        events::DefaultFolderNameFormatUpdated { value },
+    );
+
+    Ok(())
+}
+
+pub async fn get_received_file_name_template(ctx: &AppContext) -> Result<String, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock
+        .get_received_file_name_template()
+        .to_string())
+}
+
+pub async fn set_received_file_name_template(
+    ctx: &AppContext,
+    value: String,
+) -> Result<(), String> {
+    files::validate_name_template(&value)?;
+
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_received_file_name_template(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_relay_server_url(ctx: &AppContext) -> Result<Option<String>, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock
+        .get_relay_server_url()
+        .map(|s| s.to_string()))
+}
+
+pub async fn set_relay_server_url(ctx: &AppContext, value: Option<String>) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_relay_server_url(value);
+
+    // Save settings
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub fn list_relay_presets() -> Vec<RelayPreset> {
+    RELAY_PRESETS
+        .iter()
+        .map(|(id, name, url, description)| RelayPreset {
+            id: id.to_string(),
+            name: name.to_string(),
+            url: Some(url.to_string()).filter(|u| !u.is_empty()),
+            description: description.to_string(),
+        })
+        .collect()
+}
+
+/// Switch the relay to the preset `id`, returning the relay URL it set.
+pub async fn apply_relay_preset(ctx: &AppContext, id: String) -> Result<Option<String>, String> {
+    let preset = list_relay_presets()
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Unknown relay preset: {}", id))?;
+    set_relay_server_url(ctx, preset.url.clone()).await?;
+    Ok(preset.url)
+}
+
+/// Go back to magic-wormhole's built-in default relay.
+pub async fn reset_relay_server(ctx: &AppContext) -> Result<(), String> {
+    set_relay_server_url(ctx, default_relay_server_url()).await
+}
+
+pub async fn get_download_rate_limit_kib(ctx: &AppContext) -> Result<Option<u64>, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_download_rate_limit_kib())
+}
+
+/// Running downloads switch to the new limit within a few seconds. 0 is treated as unlimited.
+pub async fn set_download_rate_limit_kib(
+    ctx: &AppContext,
+    value: Option<u64>,
+) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_download_rate_limit_kib(value.filter(|&kib| kib > 0));
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_upload_rate_limit_kib(ctx: &AppContext) -> Result<Option<u64>, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_upload_rate_limit_kib())
+}
+
+/// Running sends switch to the new limit within a few seconds. 0 is treated as unlimited.
+pub async fn set_upload_rate_limit_kib(ctx: &AppContext, value: Option<u64>) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_upload_rate_limit_kib(value.filter(|&kib| kib > 0));
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_rate_limit_schedule(ctx: &AppContext) -> Result<Vec<RateWindow>, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_rate_limit_schedule().clone())
+}
+
+/// Running downloads switch to a new window's limit within a few seconds of it starting.
+pub async fn set_rate_limit_schedule(
+    ctx: &AppContext,
+    value: Vec<RateWindow>,
+) -> Result<(), String> {
+    value.iter().try_for_each(throttle::validate)?;
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_rate_limit_schedule(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_max_concurrent_sends(ctx: &AppContext) -> Result<u32, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_max_concurrent_sends())
+}
+
+pub async fn set_max_concurrent_sends(ctx: &AppContext, value: u32) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_max_concurrent_sends(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+    drop(app_settings_lock);
+
+    // A higher limit may let queued sends start now.
+    ctx.transfers().wake_queued_sends();
+    Ok(())
+}
+
+pub async fn get_hide_send_codes(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_hide_send_codes())
+}
+
+pub async fn set_hide_send_codes(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_hide_send_codes(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_idle_timeout_secs(ctx: &AppContext) -> Result<u64, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_idle_timeout_secs())
+}
+
+pub async fn set_idle_timeout_secs(ctx: &AppContext, value: u64) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_idle_timeout_secs(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_merge_folder_updates(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_merge_folder_updates())
+}
+
+pub async fn set_merge_folder_updates(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_merge_folder_updates(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_apply_file_deltas(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_apply_file_deltas())
+}
+
+pub async fn set_apply_file_deltas(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_apply_file_deltas(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_deterministic_archives(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_deterministic_archives())
+}
+
+pub async fn set_deterministic_archives(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_deterministic_archives(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_stream_archives(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_stream_archives())
+}
+
+pub async fn set_stream_archives(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_stream_archives(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_archive_part_size_mib(ctx: &AppContext) -> Result<u64, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_archive_part_size_mib())
+}
+
+pub async fn set_archive_part_size_mib(ctx: &AppContext, value: u64) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_archive_part_size_mib(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_compression_level(ctx: &AppContext) -> Result<u32, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_compression_level())
+}
+
+pub async fn set_compression_level(ctx: &AppContext, value: u32) -> Result<(), String> {
+    if value > files::MAX_COMPRESSION_LEVEL {
+        return Err(format!(
+            "Compression level must be 0 to {}",
+            files::MAX_COMPRESSION_LEVEL
+        ));
+    }
+
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_compression_level(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_extra_relay_urls(ctx: &AppContext) -> Result<Vec<String>, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_extra_relay_urls().clone())
+}
+
+pub async fn set_extra_relay_urls(ctx: &AppContext, value: Vec<String>) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_extra_relay_urls(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_transit_mode(ctx: &AppContext) -> Result<String, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_transit_mode().to_string())
+}
+
+pub async fn set_transit_mode(ctx: &AppContext, value: String) -> Result<(), String> {
+    if !files::TRANSIT_MODES.contains(&value.as_str()) {
+        return Err(format!("Unknown transit mode '{}'", value));
+    }
+
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_transit_mode(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_queue_offline_sends(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_queue_offline_sends())
+}
+
+pub async fn set_queue_offline_sends(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_queue_offline_sends(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_webhook_url(ctx: &AppContext) -> Result<Option<String>, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_webhook_url().map(|s| s.to_string()))
+}
+
+pub async fn set_webhook_url(ctx: &AppContext, value: Option<String>) -> Result<(), String> {
+    let value = value
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    if let Some(url) = &value
+        && !url.starts_with("http://")
+        && !url.starts_with("https://")
+    {
+        return Err("Webhook URL must start with http:// or https://".to_string());
+    }
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_webhook_url(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_code_expiry_mins(ctx: &AppContext) -> Result<u64, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_code_expiry_mins())
+}
+
+pub async fn set_code_expiry_mins(ctx: &AppContext, value: u64) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_code_expiry_mins(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_offer_expiry_mins(ctx: &AppContext) -> Result<u64, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_offer_expiry_mins())
+}
+
+/// Applies to offers that arrive after the change.
+pub async fn set_offer_expiry_mins(ctx: &AppContext, value: u64) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_offer_expiry_mins(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_sent_path_privacy(ctx: &AppContext) -> Result<String, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_sent_path_privacy().to_string())
+}
+
+pub async fn set_sent_path_privacy(ctx: &AppContext, value: String) -> Result<(), String> {
+    if !files_json::SENT_PATH_MODES.contains(&value.as_str()) {
+        return Err(format!("Unknown path privacy mode '{}'", value));
+    }
+
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_sent_path_privacy(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_skip_duplicate_receives(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_skip_duplicate_receives())
+}
+
+pub async fn set_skip_duplicate_receives(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_skip_duplicate_receives(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_verify_disk_writes(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_verify_disk_writes())
+}
+
+pub async fn set_verify_disk_writes(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_verify_disk_writes(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_max_incoming_size_mib(ctx: &AppContext) -> Result<u64, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_max_incoming_size_mib())
+}
+
+pub async fn set_max_incoming_size_mib(ctx: &AppContext, value: u64) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_max_incoming_size_mib(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_content_allowlist(ctx: &AppContext) -> Result<Vec<String>, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_content_allowlist().clone())
+}
+
+pub async fn set_content_allowlist(ctx: &AppContext, value: Vec<String>) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_content_allowlist(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_package_cache_mib(ctx: &AppContext) -> Result<u64, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_package_cache_mib())
+}
+
+/// Set the package cache's size, dropping tarballs that no longer fit.
+pub async fn set_package_cache_mib(ctx: &AppContext, value: u64) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_package_cache_mib(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+    drop(app_settings_lock);
+
+    cache::trim_to(ctx, value).await;
+    Ok(())
+}
+
+pub async fn get_packaging_retry_secs(ctx: &AppContext) -> Result<u64, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_packaging_retry_secs())
+}
+
+pub async fn set_packaging_retry_secs(ctx: &AppContext, value: u64) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_packaging_retry_secs(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_local_api_enabled(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_local_api_enabled())
+}
+
+/// Starts or stops the local API right away; the first time it's enabled a token is made.
+pub async fn set_local_api_enabled(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_local_api_enabled(value);
+    if value && app_settings_lock.get_local_api_token().is_empty() {
+        app_settings_lock.set_local_api_token(api::generate_token());
+    }
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+    drop(app_settings_lock);
+
+    api::restart(ctx).await.map(|_| ())
+}
+
+pub async fn get_local_api_port(ctx: &AppContext) -> Result<u16, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_local_api_port())
+}
+
+pub async fn set_local_api_port(ctx: &AppContext, value: u16) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_local_api_port(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+    drop(app_settings_lock);
+
+    api::restart(ctx).await.map(|_| ())
+}
+
+pub async fn get_local_api_token(ctx: &AppContext) -> Result<String, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_local_api_token().to_string())
+}
+
+/// Replace the local API token, cutting off apps that had the old one. Returns the new token.
+pub async fn regenerate_local_api_token(ctx: &AppContext) -> Result<String, String> {
+    let token = api::generate_token();
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_local_api_token(token.clone());
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+    drop(app_settings_lock);
+
+    api::restart(ctx).await?;
+    Ok(token)
+}
+
+pub async fn get_minimize_on_start(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_minimize_on_start())
+}
+
+pub async fn set_minimize_on_start(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_minimize_on_start(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_minimize_on_close(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_minimize_on_close())
+}
+
+pub async fn set_minimize_on_close(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_minimize_on_close(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_usage_metrics_enabled(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_usage_metrics_enabled())
+}
+
+pub async fn set_usage_metrics_enabled(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_usage_metrics_enabled(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_crash_reports_enabled(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_crash_reports_enabled())
+}
+
+pub async fn set_crash_reports_enabled(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_crash_reports_enabled(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    // Keep the panic hook's sync mirror in step with the setting.
+    crash::set_enabled(value);
+    Ok(())
+}
+
+pub async fn get_sounds_enabled(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_sounds_enabled())
+}
+
+pub async fn set_sounds_enabled(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_sounds_enabled(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_sound_volume(ctx: &AppContext) -> Result<u8, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_sound_volume())
+}
+
+pub async fn set_sound_volume(ctx: &AppContext, value: u8) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_sound_volume(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_check_updates_automatically(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_check_updates_automatically())
+}
+
+pub async fn set_check_updates_automatically(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_check_updates_automatically(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_auto_download_updates(ctx: &AppContext) -> Result<bool, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_auto_download_updates())
+}
+
+pub async fn set_auto_download_updates(ctx: &AppContext, value: bool) -> Result<(), String> {
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_auto_download_updates(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_release_channel(ctx: &AppContext) -> Result<String, String> {
+    let app_settings_lock = ctx.settings().lock().await;
+    Ok(app_settings_lock.get_release_channel().to_string())
+}
+
+pub async fn set_release_channel(ctx: &AppContext, value: String) -> Result<(), String> {
+    if !updates::RELEASE_CHANNELS.contains(&value.as_str()) {
+        return Err(format!("Unknown release channel '{}'", value));
+    }
+
+    let mut app_settings_lock = ctx.settings().lock().await;
+    app_settings_lock.set_release_channel(value);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+pub async fn get_history_directory(ctx: &AppContext) -> Result<String, String> {
+    Ok(ctx.history_dir().to_string_lossy().to_string())
+}
+
+/// Change where history is kept (None restores the app data directory), moving the
+/// existing history files along.
+pub async fn set_history_directory(
+    ctx: &AppContext,
+    new_path: Option<String>,
+) -> Result<(), String> {
+    let new_dir = match &new_path {
+        Some(path) => {
+            let dir = PathBuf::from(path);
+            if !dir.is_dir() {
+                return Err("Provided path is not a directory.".to_string());
+            }
+            dir
+        }
+        None => ctx.paths().data_dir.clone(),
+    };
+    // History is appended as transfers finish; don't move it out from under one.
+    if files::has_active_transfers(ctx).await {
+        return Err("Finish or cancel active transfers before moving history".to_string());
+    }
+
+    let mut app_settings_lock = ctx.settings().lock().await;
+    let old_dir = ctx.history_dir();
+    if old_dir != new_dir {
+        // Off the runtime: the history lock can be held by another instance for a while.
+        let to = new_dir.clone();
+        tokio::task::spawn_blocking(move || migrate_history(&old_dir, &to))
+            .await
+            .map_err(|e| format!("Failed to move history: {}", e))??;
+    }
+    app_settings_lock.set_history_directory(new_path.map(PathBuf::from));
+    ctx.set_history_dir(new_dir);
+
+    if let Err(e) = ctx.save_settings(&app_settings_lock) {
+        return Err(format!("Failed to save settings: {}", e));
+    }
+
+    Ok(())
+}
+
+/// Inclusive date range (local dates) for history exports; either end may be left open.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Type)]
+pub struct ExportRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl ExportRange {
+    /// True when neither end is set, so everything is exported.
+    pub fn is_unbounded(&self) -> bool {
+        self.from.is_none() && self.to.is_none()
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from.is_none_or(|from| date >= from) && self.to.is_none_or(|to| date <= to)
+    }
+}
+
+// Counts what's written and reports it as `export-progress`, at most every 100ms.
+struct ProgressWriter<'a, W: Write> {
+    inner: W,
+    ctx: &'a AppContext,
+    id: &'a str,
+    entries: u64,
+    bytes: u64,
+    started: Instant,
+    last_report: Instant,
+}
+
+impl<W: Write> ProgressWriter<'_, W> {
+    fn report(&mut self, done: bool) {
+        self.last_report = Instant::now();
+        let _ = self.ctx.emit(
+            events::EXPORT_PROGRESS,
+            events::ExportProgress {
+                id: self.id.to_string(),
+                entries: self.entries,
+                bytes: self.bytes,
+                elapsed_ms: self.started.elapsed().as_millis() as u64,
+                done,
+            },
+        );
+    }
+}
+
+impl<W: Write> Write for ProgressWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written as u64;
+        if self.last_report.elapsed() >= Duration::from_millis(100) {
+            self.report(false);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Copy the entries of `history_path` whose `time_field` falls in `range` to `file_path`,
+// encrypted with `passphrase` (as an age file) when one is given. Runs off the async runtime
+// since histories can be large; progress is reported as `export-progress` events tagged
+// with `id`.
+async fn export_history_json(
+    ctx: &AppContext,
+    history_path: PathBuf,
+    time_field: &'static str,
+    file_path: String,
+    range: ExportRange,
+    passphrase: Option<String>,
+    id: String,
+) -> Result<(), String> {
+    let history_dir = ctx.history_dir();
+    let ctx = ctx.clone();
+    tokio::task::spawn_blocking(move || {
+        let started = Instant::now();
+        let entries: Vec<serde_json::Value> = {
+            let _lock = files_json::lock_history(&history_dir);
+            let json_content = fs::read_to_string(&history_path)
+                .map_err(|e| format!("Failed to read history JSON: {}", e))?;
+            serde_json::from_str(&json_content)
+                .map_err(|e| format!("Failed to parse history JSON: {}", e))?
+        };
+        // Without a range everything goes, including entries whose time doesn't parse.
+        let entries: Vec<_> = entries
+            .into_iter()
+            .filter(|entry| {
+                range.is_unbounded()
+                    || entry
+                        .get(time_field)
+                        .and_then(|time| time.as_str())
+                        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+                        .is_some_and(|time| range.contains(time.with_timezone(&Local).date_naive()))
+            })
+            .collect();
+
+        let file = fs::File::create(&file_path)
+            .map_err(|e| format!("Failed to write exported file: {}", e))?;
+        let mut writer = ProgressWriter {
+            inner: std::io::BufWriter::new(file),
+            ctx: &ctx,
+            id: &id,
+            entries: entries.len() as u64,
+            bytes: 0,
+            started,
+            last_report: started,
+        };
+        let write_error =
+            |e: &dyn std::fmt::Display| format!("Failed to write exported file: {}", e);
+        match passphrase.filter(|p| !p.is_empty()) {
+            Some(passphrase) => {
+                let encryptor = age::Encryptor::with_user_passphrase(
+                    age::secrecy::SecretString::from(passphrase),
+                );
+                let mut encrypted = encryptor
+                    .wrap_output(&mut writer)
+                    .map_err(|e| write_error(&e))?;
+                serde_json::to_writer_pretty(&mut encrypted, &entries)
+                    .map_err(|e| write_error(&e))?;
+                encrypted.finish().map_err(|e| write_error(&e))?;
+            }
+            None => {
+                serde_json::to_writer_pretty(&mut writer, &entries).map_err(|e| write_error(&e))?;
+            }
+        }
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to write exported file: {}", e))?;
+        writer.report(true);
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to export history: {}", e))?
+}
+
+/// Export the received history (entries downloaded within `range`) to `file_path`,
+/// encrypted when a `passphrase` is given.
+pub async fn export_received_files_json(
+    ctx: &AppContext,
+    file_path: String,
+    range: ExportRange,
+    passphrase: Option<String>,
+    id: String,
+) -> Result<(), String> {
+    let history_path = get_received_files_path(&ctx.history_dir());
+    export_history_json(
+        ctx,
+        history_path,
+        "download_time",
+        file_path,
+        range,
+        passphrase,
+        id,
+    )
+    .await
+}
+
+/// Export the sent history (entries sent within `range`) to `file_path`, encrypted when a
+/// `passphrase` is given.
+pub async fn export_sent_files_json(
+    ctx: &AppContext,
+    file_path: String,
+    range: ExportRange,
+    passphrase: Option<String>,
+    id: String,
+) -> Result<(), String> {
+    let history_path = get_sent_files_path(&ctx.history_dir());
+    export_history_json(
+        ctx,
+        history_path,
+        "send_time",
+        file_path,
+        range,
+        passphrase,
+        id,
+    )
+    .await
+}
@@ -0,0 +1,80 @@
+// This file composes ready-to-send messages carrying a send's code, so passing it to someone
+// who has never heard of wormhole codes is one click: a mailto: link, an sms: link, or plain
+// text for the clipboard.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::context::AppContext;
+use crate::files;
+
+/// Where to get wyrmhole, linked from every shared message.
+pub const DOWNLOAD_PAGE: &str = "https://github.com/ClaytonWas/wyrmhole/releases/latest";
+
+/// Channels `compose_code_share` understands.
+pub const SHARE_CHANNELS: &[&str] = &["email", "sms", "clipboard"];
+
+/// A composed message. `url` opens the message in a mail or messaging app; it's `None` for
+/// the clipboard, where `text` is what to copy.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+pub struct CodeShare {
+    pub channel: String,
+    pub text: String,
+    pub url: Option<String>,
+}
+
+/// The message for `code`, for someone who may not have wyrmhole yet.
+pub fn share_text(code: &str) -> String {
+    format!(
+        "I'm sending you a file with wyrmhole. Open wyrmhole and receive it with the code: {}\n\n\
+         Don't have it? Get wyrmhole at {} (any magic-wormhole app works too, e.g. `wormhole receive {}`).",
+        code, DOWNLOAD_PAGE, code
+    )
+}
+
+// Percent-encode everything but RFC 3986 unreserved characters, for mailto:/sms: query values.
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Compose the message for `code` on `channel` (one of `SHARE_CHANNELS`).
+pub fn compose(code: &str, channel: &str) -> Result<CodeShare, String> {
+    let text = share_text(code);
+    let url = match channel {
+        "email" => Some(format!(
+            "mailto:?subject={}&body={}",
+            encode("A file for you"),
+            encode(&text)
+        )),
+        "sms" => Some(format!("sms:?&body={}", encode(&text))),
+        "clipboard" => None,
+        other => return Err(format!("Unknown share channel '{}'", other)),
+    };
+    Ok(CodeShare {
+        channel: channel.to_string(),
+        text,
+        url,
+    })
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+/// Compose a message sharing the code of the active send `send_id` on `channel`. Works with
+/// `hide_send_codes` on too, like `reveal_code`.
+pub async fn compose_code_share(
+    send_id: String,
+    channel: String,
+    ctx: AppContext,
+) -> Result<CodeShare, String> {
+    let code = files::reveal_code(send_id, ctx).await?;
+    compose(&code, &channel)
+}
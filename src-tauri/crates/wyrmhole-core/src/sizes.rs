@@ -0,0 +1,173 @@
+// This file computes the size of a file or folder for previews before sending. The walk runs
+// on a blocking thread, reports a running total as `path-size-progress` events so the UI can
+// show "calculating... 3.1 GB so far", and stops early when cancelled (e.g. the selection
+// changed before it finished). It also checks folders picked by the host's native dialog.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::context::AppContext;
+use crate::events;
+use crate::files;
+
+/// How often a running computation reports its total so far.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Response of `compute_path_size`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+pub struct PathSize {
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// A folder checked by `check_folder_for_send`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+pub struct FolderForSend {
+    /// Absolute path with symlinks resolved; on Windows without the `\\?\` prefix, so UNC
+    /// shares come back as `\\server\share\...`.
+    pub path: String,
+    pub size: PathSize,
+}
+
+// `canonicalize` returns verbatim (`\\?\`) paths on Windows, which the webview and the
+// paths shown in history handle poorly; turn them back into the usual form where there is one.
+fn normalize(path: &Path) -> Result<PathBuf, String> {
+    fs::canonicalize(path)
+        .map(files::simplified)
+        .map_err(|e| format!("Can't open {}: {}", path.display(), e))
+}
+
+// Adds up `path` into `total`, calling `report` at most every `PROGRESS_INTERVAL`. Entries
+// that can't be read are skipped rather than failing the whole walk.
+fn walk(
+    path: &Path,
+    total: &mut PathSize,
+    cancel: &CancellationToken,
+    last_report: &mut Instant,
+    report: &mut dyn FnMut(PathSize),
+) -> Result<(), String> {
+    if cancel.is_cancelled() {
+        return Err("Size computation cancelled".to_string());
+    }
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(());
+    };
+    if metadata.is_dir() {
+        let Ok(entries) = fs::read_dir(path) else {
+            return Ok(());
+        };
+        for entry in entries.flatten() {
+            walk(&entry.path(), total, cancel, last_report, report)?;
+        }
+    } else {
+        total.bytes += metadata.len();
+        total.files += 1;
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            *last_report = Instant::now();
+            report(*total);
+        }
+    }
+    Ok(())
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+/// The total size and file count of `path` (a file or a folder, walked recursively). `id`
+/// names the computation for its `path-size-progress` events and `cancel_path_size`.
+pub async fn compute_path_size(
+    ctx: AppContext,
+    path: String,
+    id: String,
+) -> Result<PathSize, String> {
+    if !Path::new(&path).exists() {
+        return Err(format!("File or folder does not exist: {}", path));
+    }
+    let cancel = CancellationToken::new();
+    ctx.transfers()
+        .add_size_computation(&id, cancel.clone())
+        .await;
+
+    let result = tokio::task::spawn_blocking({
+        let ctx = ctx.clone();
+        let id = id.clone();
+        let path = path.clone();
+        move || {
+            let mut total = PathSize { bytes: 0, files: 0 };
+            let mut report = |so_far: PathSize| {
+                let _ = ctx.emit(
+                    events::PATH_SIZE_PROGRESS,
+                    events::PathSizeProgress {
+                        id: id.clone(),
+                        bytes: so_far.bytes,
+                        files: so_far.files,
+                        done: false,
+                    },
+                );
+            };
+            walk(
+                Path::new(&path),
+                &mut total,
+                &cancel,
+                &mut Instant::now(),
+                &mut report,
+            )
+            .map(|_| total)
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to compute size: {}", e))
+    .and_then(|result| result);
+    ctx.transfers().remove_size_computation(&id).await;
+
+    if let Ok(total) = result {
+        let _ = ctx.emit(
+            events::PATH_SIZE_PROGRESS,
+            events::PathSizeProgress {
+                id,
+                bytes: total.bytes,
+                files: total.files,
+                done: true,
+            },
+        );
+    }
+    result
+}
+
+/// Stop the size computation `id`; it resolves with an error.
+pub async fn cancel_path_size(ctx: &AppContext, id: String) -> Result<(), String> {
+    if ctx.transfers().cancel_size_computation(&id).await {
+        Ok(())
+    } else {
+        Err("No size computation found for this ID".to_string())
+    }
+}
+
+/// Check that `path` is a folder that can be read and sent, returning its normalized path
+/// and size. Called by the host after its native folder dialog (`pick_folder_for_send`).
+pub async fn check_folder_for_send(path: String) -> Result<FolderForSend, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = normalize(Path::new(&path))?;
+        if !path.is_dir() {
+            return Err(format!("{} isn't a folder", path.display()));
+        }
+        fs::read_dir(&path).map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+        let mut size = PathSize { bytes: 0, files: 0 };
+        walk(
+            &path,
+            &mut size,
+            &CancellationToken::new(),
+            &mut Instant::now(),
+            &mut |_| {},
+        )?;
+        Ok(FolderForSend {
+            path: path.to_string_lossy().into_owned(),
+            size,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to check folder: {}", e))?
+}
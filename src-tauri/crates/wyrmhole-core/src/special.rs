@@ -0,0 +1,107 @@
+// What folder walkers do with entries that aren't plain files or folders. Empty files are
+// ordinary files and are sent like any other. FIFOs, sockets and device nodes have no
+// contents to send (opening a FIFO blocks until something writes to it), and a broken
+// symlink points at nothing, so walkers leave these out and the send reports each one with
+// a `FILES_SKIPPED` event instead of failing or hanging partway through packaging.
+//
+// A special file selected directly, rather than found inside a folder, is refused up front.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Why a folder entry was left out.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipReason {
+    /// A named pipe.
+    Fifo,
+    /// A Unix domain socket.
+    Socket,
+    /// A block or character device node.
+    Device,
+    /// A symlink whose target doesn't exist.
+    BrokenLink,
+    /// Anything else that's neither a file nor a folder.
+    Other,
+}
+
+impl SkipReason {
+    pub fn message(self) -> &'static str {
+        match self {
+            SkipReason::Fifo => "named pipe",
+            SkipReason::Socket => "socket",
+            SkipReason::Device => "device node",
+            SkipReason::BrokenLink => "broken symlink",
+            SkipReason::Other => "not a regular file",
+        }
+    }
+}
+
+/// An entry a walker left out of a send.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: SkipReason,
+}
+
+impl SkippedFile {
+    pub(crate) fn new(path: &Path, reason: SkipReason) -> Self {
+        SkippedFile {
+            path: path.to_string_lossy().into_owned(),
+            reason,
+        }
+    }
+}
+
+/// A walked entry, with symlinks followed.
+pub(crate) enum EntryKind {
+    Dir,
+    File(fs::Metadata),
+    Skip(SkipReason),
+}
+
+/// What `path` is. Errors are only for entries that exist but can't be read.
+pub(crate) fn classify(path: &Path) -> io::Result<EntryKind> {
+    match fs::metadata(path) {
+        Ok(metadata) => Ok(classify_metadata(metadata)),
+        Err(e)
+            if e.kind() == io::ErrorKind::NotFound
+                && fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()) =>
+        {
+            Ok(EntryKind::Skip(SkipReason::BrokenLink))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub(crate) fn classify_metadata(metadata: fs::Metadata) -> EntryKind {
+    if metadata.is_dir() {
+        EntryKind::Dir
+    } else if metadata.is_file() {
+        EntryKind::File(metadata)
+    } else {
+        EntryKind::Skip(special_reason(&metadata.file_type()))
+    }
+}
+
+#[cfg(unix)]
+fn special_reason(file_type: &fs::FileType) -> SkipReason {
+    use std::os::unix::fs::FileTypeExt;
+    if file_type.is_fifo() {
+        SkipReason::Fifo
+    } else if file_type.is_socket() {
+        SkipReason::Socket
+    } else if file_type.is_block_device() || file_type.is_char_device() {
+        SkipReason::Device
+    } else {
+        SkipReason::Other
+    }
+}
+
+#[cfg(not(unix))]
+fn special_reason(_file_type: &fs::FileType) -> SkipReason {
+    SkipReason::Other
+}
@@ -0,0 +1,265 @@
+// Streamed archives: with `stream_archives` on, a folder or bundle is tarred straight into
+// the transfer instead of being packed into a temp file first, so sending starts right away
+// and no second copy of the data lands on disk.
+//
+// The wormhole offer carries the exact size before any data moves, and a gzip stream's size
+// isn't known until it's compressed, so streamed archives are plain `.tar`. Their size comes
+// from an `ArchivePlan`: a metadata walk that lists every entry, after which the tar bytes
+// follow from the header layout alone. The archive is then written from the plan on a
+// blocking task into a bounded in-memory pipe that the send reads from. A file that grows
+// or shrinks after planning is cut off or zero-padded to its planned size, keeping the
+// stream exactly as long as offered.
+
+use futures::channel::mpsc;
+use futures::{AsyncRead, SinkExt, TryStreamExt};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use tar::{Builder, EntryType, Header, HeaderMode};
+use tokio_util::sync::CancellationToken;
+
+use crate::files::CancellableWriter;
+use crate::special::{self, EntryKind, SkippedFile};
+
+// The pipe holds at most this many chunks of `CHUNK_SIZE` bytes (4 MiB), so a slow receiver
+// holds up the reading of files rather than filling memory.
+const CHUNK_SIZE: usize = 256 * 1024;
+const PIPE_CHUNKS: usize = 16;
+
+enum PlannedEntry {
+    Dir {
+        dest: PathBuf,
+        src: PathBuf,
+    },
+    File {
+        dest: PathBuf,
+        src: PathBuf,
+        size: u64,
+    },
+}
+
+/// Every entry of a streamed archive, in order, and the archive's exact size.
+pub(crate) struct ArchivePlan {
+    entries: Vec<PlannedEntry>,
+    mode: HeaderMode,
+    size: u64,
+}
+
+impl ArchivePlan {
+    /// Plan the folder at `folder`, archived under `name` (as `create_tarball_from_folder`
+    /// lays it out). Entries left out are added to `skipped`.
+    pub(crate) fn of_folder(
+        folder: &Path,
+        name: &str,
+        deterministic: bool,
+        skipped: &mut Vec<SkippedFile>,
+    ) -> Result<Self, String> {
+        let mut entries = Vec::new();
+        plan_folder(
+            &mut entries,
+            Path::new(name),
+            folder,
+            deterministic,
+            skipped,
+        )
+        .map_err(|e| format!("Failed to read folder: {}", e))?;
+        Self::new(entries, deterministic)
+    }
+
+    /// Plan `paths` under a folder named `name` (as `create_tarball_from_paths` lays it out).
+    /// Entries left out of folders are added to `skipped`.
+    pub(crate) fn of_paths(
+        paths: &[String],
+        name: &str,
+        deterministic: bool,
+        skipped: &mut Vec<SkippedFile>,
+    ) -> Result<Self, String> {
+        let mut paths = paths.to_vec();
+        if deterministic {
+            paths.sort();
+        }
+        let mut entries = Vec::new();
+        for path in &paths {
+            let src = Path::new(path);
+            let metadata = fs::metadata(src)
+                .map_err(|_| format!("File or folder does not exist: {}", path))?;
+            let file_name = src.file_name().and_then(|n| n.to_str());
+            if metadata.is_dir() {
+                let dest = Path::new(name).join(file_name.unwrap_or("folder"));
+                plan_folder(&mut entries, &dest, src, deterministic, skipped)
+                    .map_err(|e| format!("Failed to read folder {}: {}", path, e))?;
+            } else {
+                entries.push(PlannedEntry::File {
+                    dest: Path::new(name).join(file_name.unwrap_or("file")),
+                    src: src.to_path_buf(),
+                    size: metadata.len(),
+                });
+            }
+        }
+        Self::new(entries, deterministic)
+    }
+
+    fn new(entries: Vec<PlannedEntry>, deterministic: bool) -> Result<Self, String> {
+        let mode = if deterministic {
+            HeaderMode::Deterministic
+        } else {
+            HeaderMode::Complete
+        };
+        let size = archive_size(&entries).map_err(|e| format!("Failed to plan archive: {}", e))?;
+        Ok(ArchivePlan {
+            entries,
+            mode,
+            size,
+        })
+    }
+
+    /// The number of bytes `stream` will produce.
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Start writing the archive on a blocking task. The returned reader yields exactly
+    /// `size()` bytes, or an error if a file can't be read; dropping it, or cancelling
+    /// `cancel`, stops the writing.
+    pub(crate) fn stream(self, cancel: CancellationToken) -> impl AsyncRead + Unpin + Send {
+        let (tx, rx) = mpsc::channel::<io::Result<Vec<u8>>>(PIPE_CHUNKS);
+        tokio::task::spawn_blocking(move || {
+            let mut pipe = PipeWriter {
+                tx: tx.clone(),
+                buf: Vec::with_capacity(CHUNK_SIZE),
+            };
+            if let Err(e) = write_archive(&self, &mut pipe, cancel) {
+                let mut tx = tx;
+                let _ = futures::executor::block_on(tx.send(Err(e)));
+            }
+        });
+        rx.into_async_read()
+    }
+}
+
+fn plan_folder(
+    entries: &mut Vec<PlannedEntry>,
+    dest: &Path,
+    src: &Path,
+    sorted: bool,
+    skipped: &mut Vec<SkippedFile>,
+) -> io::Result<()> {
+    entries.push(PlannedEntry::Dir {
+        dest: dest.to_path_buf(),
+        src: src.to_path_buf(),
+    });
+    let mut children = fs::read_dir(src)?.collect::<Result<Vec<_>, _>>()?;
+    if sorted {
+        children.sort_by_key(|entry| entry.file_name());
+    }
+    for child in children {
+        let path = child.path();
+        let child_dest = dest.join(child.file_name());
+        // Symlinks are followed and special files left out, like the packaged tarballs do
+        match special::classify(&path)? {
+            EntryKind::Dir => plan_folder(entries, &child_dest, &path, sorted, skipped)?,
+            EntryKind::File(metadata) => entries.push(PlannedEntry::File {
+                dest: child_dest,
+                src: path,
+                size: metadata.len(),
+            }),
+            EntryKind::Skip(reason) => skipped.push(SkippedFile::new(&path, reason)),
+        }
+    }
+    Ok(())
+}
+
+// Counts what's written to it, for sizing the archive without building it.
+#[derive(Default)]
+struct Counter(u64);
+
+impl Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// The archive's size: every header (including long-name extensions) as `tar` writes it,
+// every file's data padded to the 512-byte block, and the end-of-archive blocks.
+fn archive_size(entries: &[PlannedEntry]) -> io::Result<u64> {
+    let mut tar = Builder::new(Counter::default());
+    let mut data = 0;
+    for entry in entries {
+        let (dest, kind) = match entry {
+            PlannedEntry::Dir { dest, .. } => (dest, EntryType::Directory),
+            PlannedEntry::File { dest, size, .. } => {
+                data += size.div_ceil(512) * 512;
+                (dest, EntryType::Regular)
+            }
+        };
+        let mut header = Header::new_gnu();
+        header.set_entry_type(kind);
+        header.set_size(0);
+        tar.append_data(&mut header, dest, io::empty())?;
+    }
+    Ok(tar.into_inner()?.0 + data)
+}
+
+fn write_archive<W: Write>(
+    plan: &ArchivePlan,
+    out: W,
+    cancel: CancellationToken,
+) -> io::Result<()> {
+    let mut tar = Builder::new(CancellableWriter::new(out, cancel));
+    for entry in &plan.entries {
+        let mut header = Header::new_gnu();
+        match entry {
+            PlannedEntry::Dir { dest, src } => {
+                header.set_metadata_in_mode(&fs::metadata(src)?, plan.mode);
+                header.set_size(0);
+                tar.append_data(&mut header, dest, io::empty())?;
+            }
+            PlannedEntry::File { dest, src, size } => {
+                let file = fs::File::open(src)?;
+                header.set_metadata_in_mode(&file.metadata()?, plan.mode);
+                header.set_size(*size);
+                let data = file.take(*size).chain(io::repeat(0)).take(*size);
+                tar.append_data(&mut header, dest, data)?;
+            }
+        }
+    }
+    tar.into_inner()?.flush()
+}
+
+// The writing end of the pipe: buffers into chunks and hands each to the reading end,
+// blocking while the pipe is full. Fails once the reader is gone.
+struct PipeWriter {
+    tx: mpsc::Sender<io::Result<Vec<u8>>>,
+    buf: Vec<u8>,
+}
+
+impl PipeWriter {
+    fn send_chunk(&mut self) -> io::Result<()> {
+        let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(CHUNK_SIZE));
+        futures::executor::block_on(self.tx.send(Ok(chunk)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Transfer ended"))
+    }
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(CHUNK_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&buf[..n]);
+        if self.buf.len() == CHUNK_SIZE {
+            self.send_chunk()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.send_chunk()
+    }
+}
@@ -0,0 +1,245 @@
+// This file implements folder sync sends: each send of a folder records a manifest of its
+// files' hashes, and the next one packages only the files added or changed since, as
+// `<folder>.sync.tar.gz`. A receiver with `merge_folder_updates` on extracts that into the
+// earlier folder instead of a new "folder(1)". Deletions aren't carried over; it's a
+// lightweight one-way sync for sending the same folder again and again. Single files go
+// through delta.rs instead.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::context::AppContext;
+use crate::delta;
+use crate::events;
+use crate::files;
+use crate::special::{self, EntryKind};
+use crate::verify;
+
+/// File name suffix marking a tarball as a sync update rather than a whole folder.
+pub const SYNC_SUFFIX: &str = ".sync.tar.gz";
+
+/// A folder's files at the time of a send: "/"-separated relative path -> SHA-256 (hex).
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Hash every file under `folder`.
+    pub fn build(folder: &Path) -> Result<Manifest, String> {
+        let mut manifest = Manifest::default();
+        manifest.add_dir(folder, "")?;
+        Ok(manifest)
+    }
+
+    fn add_dir(&mut self, dir: &Path, prefix: &str) -> Result<(), String> {
+        let entries =
+            fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+            let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+            let path = entry.path();
+            let kind = special::classify(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            match kind {
+                EntryKind::Dir => self.add_dir(&path, &format!("{}/", name))?,
+                EntryKind::File(_) => {
+                    self.files.insert(name, hash_file(&path)?);
+                }
+                // Never sent, so never part of what changed (see special.rs)
+                EntryKind::Skip(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Files in `self` that are new or different from `previous`.
+    pub fn changed_since(&self, previous: &Manifest) -> Vec<String> {
+        self.files
+            .iter()
+            .filter(|(path, hash)| previous.files.get(*path) != Some(*hash))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+}
+
+fn hash_file(path: &Path) -> Result<String, String> {
+    verify::hash_file(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+// Manifests live in the data directory, named by a hash of the folder's absolute path.
+fn manifest_path(ctx: &AppContext, folder: &Path) -> PathBuf {
+    let key = Sha256::digest(folder.to_string_lossy().as_bytes());
+    ctx.paths()
+        .data_dir
+        .join("sync_manifests")
+        .join(format!("{:x}.json", key))
+}
+
+fn load_manifest(path: &Path) -> Manifest {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(path: &Path, manifest: &Manifest) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to save sync manifest: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to save sync manifest: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to save sync manifest: {}", e))
+}
+
+/// Write `relative_paths` from `folder` into a tarball at `output_path`, under `folder_name/`
+/// like a whole-folder send. Returns the tarball's size.
+pub fn create_sync_tarball(
+    folder: &Path,
+    folder_name: &str,
+    relative_paths: &[String],
+    output_path: &Path,
+    options: files::TarballOptions,
+) -> Result<u64, String> {
+    let mut tar = files::tarball_builder(output_path, options, Default::default())?;
+    for relative in relative_paths {
+        tar.append_path_with_name(
+            folder.join(relative),
+            format!("{}/{}", folder_name, relative),
+        )
+        .map_err(|e| format!("Failed to add file to tarball: {}", e))?;
+    }
+    tar.into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(|e| format!("Failed to finish tarball: {}", e))?;
+    let size = fs::metadata(output_path)
+        .map_err(|e| format!("Failed to get tarball metadata: {}", e))?
+        .len();
+    Ok(size)
+}
+
+// Send what changed in `folder` since it was last sent this way (everything, the first
+// time). The manifest is only updated once the send succeeds.
+async fn send_folder_changes(
+    ctx: AppContext,
+    folder: PathBuf,
+    send_id: String,
+) -> Result<String, String> {
+    let folder_path = folder.display().to_string();
+    let folder_name = folder
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "folder".to_string());
+    let tarball_name = format!("{}{}", folder_name, SYNC_SUFFIX);
+    let send_error = |error: String| {
+        log_line!("[magic-wormhole][sync][error] {}", error);
+        let _ = ctx.emit(
+            events::SEND_ERROR,
+            events::SendError {
+                id: send_id.clone(),
+                file_name: tarball_name.clone(),
+                error: error.clone(),
+                code: None,
+            },
+        );
+        error
+    };
+    let _ = ctx.emit(
+        events::SEND_PROGRESS,
+        events::SendProgress {
+            id: send_id.clone(),
+            file_name: tarball_name.clone(),
+            sent: 0,
+            total: 0,
+            percentage: 0,
+            code: String::new(),
+            status: events::TransferState::Packaging,
+        },
+    );
+
+    let manifest_path = manifest_path(&ctx, &folder);
+    let options = files::TarballOptions::from_settings(&ctx, None).await;
+    let temp_dir = std::env::temp_dir().join(format!("wyrmhole_sync_{}", Uuid::new_v4()));
+    let tarball_path = temp_dir.join(&tarball_name);
+    let packaged = tokio::task::spawn_blocking({
+        let manifest_path = manifest_path.clone();
+        let temp_dir = temp_dir.clone();
+        let tarball_path = tarball_path.clone();
+        move || {
+            let current = Manifest::build(&folder)?;
+            let changed = current.changed_since(&load_manifest(&manifest_path));
+            if changed.is_empty() {
+                return Err(format!(
+                    "Nothing in {} changed since the last sync",
+                    folder_name
+                ));
+            }
+            fs::create_dir_all(&temp_dir)
+                .map_err(|e| format!("Failed to create tarball folder: {}", e))?;
+            create_sync_tarball(&folder, &folder_name, &changed, &tarball_path, options)?;
+            Ok((current, changed.len()))
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to create tarball: {}", e))
+    .and_then(|result| result);
+
+    let result = match packaged {
+        Ok((manifest, count)) => {
+            log_line!(
+                "[magic-wormhole][sync][info] Sending {} changed file(s) from {}",
+                count,
+                folder_path
+            );
+            let sent =
+                files::send_file_call(ctx.clone(), &tarball_path.to_string_lossy(), send_id).await;
+            if sent.is_ok()
+                && let Err(e) = save_manifest(&manifest_path, &manifest)
+            {
+                log_line!("[magic-wormhole][sync][error] {}", e);
+            }
+            sent
+        }
+        Err(e) => Err(send_error(e)),
+    };
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    result
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+/// Send only what changed in `path` since it was last sent this way: the changed files of a
+/// folder, or a delta of a file (see delta.rs). The first send of a path sends all of it.
+pub async fn send_changes_call(
+    ctx: AppContext,
+    path: String,
+    send_id: String,
+) -> Result<String, String> {
+    let path = PathBuf::from(path);
+    if path.is_dir() {
+        send_folder_changes(ctx, path, send_id).await
+    } else if path.is_file() {
+        delta::send_file_changes(ctx, &path, send_id).await
+    } else {
+        Err(format!("{} doesn't exist", path.display()))
+    }
+}
+
+/// Forget what was sent from `path`, so its next send of changes sends everything again.
+pub async fn reset_sync(ctx: AppContext, path: String) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    let manifest = manifest_path(&ctx, &path);
+    tokio::task::spawn_blocking(move || {
+        match fs::remove_file(&manifest) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => delta::forget_signature(&ctx, &path),
+        }
+        .map_err(|e| format!("Failed to reset sync: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Failed to reset sync: {}", e))?
+}
@@ -0,0 +1,257 @@
+// This file implements bandwidth limiting for transfers. A download's `ThrottledWriter` wraps
+// the stream it writes into and paces it to a fixed rate; because magic-wormhole only reads
+// from the network as fast as we write, the peer's sending slows down to match. A send's
+// `ThrottledReader` paces the file it reads from the same way, so the upload goes no faster.
+//
+// Both rates can follow a schedule (the `rate_limit_schedule` setting), e.g. 5 MB/s during
+// work hours and unlimited at night. Transfers look their rate up again as they go,
+// so a long one picks up the new limit when a window starts or ends, or when the setting
+// changes.
+
+use chrono::{Local, NaiveTime};
+use futures::{AsyncRead, AsyncWrite};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use std::time::{Duration, Instant};
+use tokio::time::Sleep;
+
+// How often a scheduled writer looks up the rate in force.
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A time window with its own speed cap, e.g. 09:00-17:00 at 5120 KiB/s.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+pub struct RateWindow {
+    /// Local time the window starts, "HH:MM".
+    pub start: String,
+    /// Local time the window ends, "HH:MM"; earlier than `start` for windows past midnight.
+    pub end: String,
+    /// Cap in KiB/s while the window is on; None means unlimited.
+    pub limit_kib: Option<u64>,
+}
+
+impl RateWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| format!("'{}' is not a time like 09:30", value))
+}
+
+/// Check a window before it's saved.
+pub fn validate(window: &RateWindow) -> Result<(), String> {
+    if parse_time(&window.start)? == parse_time(&window.end)? {
+        return Err(format!(
+            "The window starting at {} needs a different end time",
+            window.start
+        ));
+    }
+    Ok(())
+}
+
+/// A speed cap: `default_kib` outside the schedule, and the first matching window's limit
+/// inside it.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimit {
+    pub default_kib: Option<u64>,
+    pub schedule: Vec<RateWindow>,
+}
+
+impl RateLimit {
+    /// The cap in KiB/s at `time`; None means unlimited.
+    pub fn kib_at(&self, time: NaiveTime) -> Option<u64> {
+        match self.schedule.iter().find(|w| w.contains(time)) {
+            Some(window) => window.limit_kib,
+            None => self.default_kib,
+        }
+    }
+
+    /// True when no time of day is capped, so there's nothing to throttle.
+    pub fn is_unlimited(&self) -> bool {
+        self.default_kib.is_none() && self.schedule.iter().all(|w| w.limit_kib.is_none())
+    }
+
+    fn bytes_per_sec_now(&self) -> Option<u64> {
+        self.kib_at(Local::now().time())
+            .map(|kib| kib.saturating_mul(1024).max(1))
+    }
+}
+
+/// Looks up the cap a throttled stream should follow (see `ThrottledWriter::live`); None
+/// keeps the rate it has, e.g. while the settings are busy.
+pub type LimitLookup = Box<dyn FnMut() -> Option<RateLimit> + Send>;
+
+// Paces a stream: each chunk waits until everything before it could have gone out at the
+// rate in force.
+struct Pacer {
+    // The rate in force; None lets bytes straight through.
+    bytes_per_sec: Option<u64>,
+    // Set for streams that follow a schedule or the settings, with when the rate was last
+    // looked up.
+    lookup: Option<(LimitLookup, Instant)>,
+    start: Instant,
+    done: u64,
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl Pacer {
+    fn new(bytes_per_sec: Option<u64>, lookup: Option<LimitLookup>) -> Self {
+        Pacer {
+            bytes_per_sec,
+            lookup: lookup.map(|lookup| (lookup, Instant::now())),
+            start: Instant::now(),
+            done: 0,
+            delay: None,
+        }
+    }
+
+    // Look the rate up again, restarting the pacing from now when it changed.
+    fn refresh_rate(&mut self) {
+        let Some((lookup, checked)) = self.lookup.as_mut() else {
+            return;
+        };
+        if checked.elapsed() < SCHEDULE_CHECK_INTERVAL {
+            return;
+        }
+        *checked = Instant::now();
+        let Some(limit) = lookup() else {
+            return;
+        };
+        let rate = limit.bytes_per_sec_now();
+        if rate != self.bytes_per_sec {
+            self.bytes_per_sec = rate;
+            self.start = Instant::now();
+            self.done = 0;
+            self.delay = None;
+        }
+    }
+
+    // Ready once the next chunk may go, with how big it may be (about a tenth of a second's
+    // worth, so the pace stays smooth); None when there's no cap.
+    fn poll_next_chunk(&mut self, cx: &mut Context<'_>) -> Poll<Option<usize>> {
+        self.refresh_rate();
+        let Some(bytes_per_sec) = self.bytes_per_sec else {
+            return Poll::Ready(None);
+        };
+        loop {
+            if let Some(delay) = self.delay.as_mut() {
+                ready!(delay.as_mut().poll(cx));
+                self.delay = None;
+            }
+            // When everything so far is allowed to have gone out at our rate.
+            let due = self.start + Duration::from_secs_f64(self.done as f64 / bytes_per_sec as f64);
+            if Instant::now() >= due {
+                break;
+            }
+            self.delay = Some(Box::pin(tokio::time::sleep_until(due.into())));
+        }
+        Poll::Ready(Some((bytes_per_sec / 10).max(1024) as usize))
+    }
+}
+
+/// An `AsyncWrite` that never goes faster than its cap on average.
+pub struct ThrottledWriter<W> {
+    inner: W,
+    pacer: Pacer,
+}
+
+impl<W> ThrottledWriter<W> {
+    pub fn new(inner: W, bytes_per_sec: u64) -> Self {
+        ThrottledWriter {
+            inner,
+            pacer: Pacer::new(Some(bytes_per_sec.max(1)), None),
+        }
+    }
+
+    /// A writer paced by `limit`, switching rate as its schedule's windows start and end.
+    pub fn scheduled(inner: W, limit: RateLimit) -> Self {
+        let lookup = limit.clone();
+        Self::live(inner, limit, Box::new(move || Some(lookup.clone())))
+    }
+
+    /// A writer paced by `limit` for now, then by whatever `lookup` returns, checked every
+    /// few seconds so a changed setting reaches a transfer already under way.
+    pub fn live(inner: W, limit: RateLimit, lookup: LimitLookup) -> Self {
+        ThrottledWriter {
+            inner,
+            pacer: Pacer::new(limit.bytes_per_sec_now(), Some(lookup)),
+        }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for ThrottledWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let Some(max) = ready!(this.pacer.poll_next_chunk(cx)) else {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        };
+        let n = ready!(Pin::new(&mut this.inner).poll_write(cx, &buf[..buf.len().min(max)]))?;
+        this.pacer.done += n as u64;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
+
+/// An `AsyncRead` paced like `ThrottledWriter`, for what a send hands to the peer.
+pub struct ThrottledReader<R> {
+    inner: R,
+    pacer: Pacer,
+}
+
+impl<R> ThrottledReader<R> {
+    pub fn new(inner: R, bytes_per_sec: u64) -> Self {
+        ThrottledReader {
+            inner,
+            pacer: Pacer::new(Some(bytes_per_sec.max(1)), None),
+        }
+    }
+
+    /// See `ThrottledWriter::live`.
+    pub fn live(inner: R, limit: RateLimit, lookup: LimitLookup) -> Self {
+        ThrottledReader {
+            inner,
+            pacer: Pacer::new(limit.bytes_per_sec_now(), Some(lookup)),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ThrottledReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let Some(max) = ready!(this.pacer.poll_next_chunk(cx)) else {
+            return Pin::new(&mut this.inner).poll_read(cx, buf);
+        };
+        let len = buf.len().min(max);
+        let n = ready!(Pin::new(&mut this.inner).poll_read(cx, &mut buf[..len]))?;
+        this.pacer.done += n as u64;
+        Poll::Ready(Ok(n))
+    }
+}
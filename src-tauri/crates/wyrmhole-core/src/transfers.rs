@@ -0,0 +1,395 @@
+// This file holds the state of every transfer in flight: offers waiting for an answer, the
+// sends, downloads and connection attempts that can be cancelled, packaging jobs, multi-part
+// transfers, size computations, the send slots under `max_concurrent_sends`, and the session
+// secrets of finished transfers. It lives in the `AppContext`, so each host (and each test) has its
+// own, and it all sits behind one lock, so updates never interleave and there's no lock order
+// to get wrong. (The send slot count is an atomic instead, as slots are freed on drop.)
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::futures::Notified;
+use tokio::sync::{Mutex, Notify};
+use tokio_util::sync::CancellationToken;
+
+use crate::jobs::Job;
+use crate::wormhole::IncomingOffer;
+
+// Session secrets of finished transfers kept for replies and further parts; the oldest go
+// first. A reply needs the peer to still be around, so old ones are of little use anyway.
+const MAX_REMEMBERED_SECRETS: usize = 256;
+
+/// The ids of everything in flight at one moment, each list sorted.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Type)]
+pub struct TransferSnapshot {
+    pub sends: Vec<String>,
+    pub downloads: Vec<String>,
+    pub connections: Vec<String>,
+    /// Offers (files and text messages) waiting to be accepted or denied.
+    pub offers: Vec<String>,
+}
+
+/// What `TransferManager::cancel_all` cancelled, by id.
+pub(crate) struct Cancelled {
+    pub sends: Vec<String>,
+    pub downloads: Vec<String>,
+    pub connections: Vec<String>,
+}
+
+// A send holds its code (empty while queued or offline) and cancellation token; the send,
+// its packaging and anything else watching it hold clones of the token, so one `cancel()`
+// reaches them all. Downloads and connections only need the token.
+struct ActiveSend {
+    code: String,
+    cancel: CancellationToken,
+}
+
+#[derive(Default)]
+struct Transfers {
+    offers: HashMap<String, Box<dyn IncomingOffer>>,
+    // Text messages waiting to be saved or dismissed: id -> (file name, text).
+    text_offers: HashMap<String, (String, String)>,
+    sends: HashMap<String, ActiveSend>,
+    downloads: HashMap<String, CancellationToken>,
+    connections: HashMap<String, CancellationToken>,
+    // Session secrets (see `PeerConnection::session_secret`) of finished sends and received
+    // offers, by transfer id, for replies (`files::reply_code`) and further parts of a
+    // multi-part transfer (`parts::part_code`), with the ids in the order they were remembered.
+    secrets: HashMap<String, String>,
+    secret_order: VecDeque<String>,
+    // Packaging jobs (see jobs.rs), by job id.
+    jobs: HashMap<String, Job>,
+    // Multi-part sends and downloads (see parts.rs), by id, and whether the user cancelled them.
+    multipart: HashMap<String, bool>,
+    // Size computations (see sizes.rs), by id.
+    sizes: HashMap<String, CancellationToken>,
+    // The last code each send showed, kept past the send's removal until `take_shown_code`,
+    // so a send that fails still knows which code to announce as expired.
+    shown_codes: HashMap<String, String>,
+}
+
+// Sends holding a slot under `max_concurrent_sends`, and the wakeup for queued ones.
+#[derive(Default)]
+struct SendSlots {
+    running: AtomicUsize,
+    freed: Notify,
+}
+
+/// Held for the whole of a send; frees the slot for the next queued send when dropped.
+pub(crate) struct SendSlot(Arc<SendSlots>);
+
+impl Drop for SendSlot {
+    fn drop(&mut self) {
+        self.0.running.fetch_sub(1, Ordering::SeqCst);
+        self.0.freed.notify_waiters();
+    }
+}
+
+/// Owns all transfer state; see the top of this file.
+#[derive(Default)]
+pub struct TransferManager {
+    state: Mutex<Transfers>,
+    send_slots: Arc<SendSlots>,
+}
+
+impl TransferManager {
+    /// Everything in flight right now.
+    pub async fn snapshot(&self) -> TransferSnapshot {
+        let state = self.state.lock().await;
+        let sorted = |ids: Vec<&String>| {
+            let mut ids: Vec<String> = ids.into_iter().cloned().collect();
+            ids.sort();
+            ids
+        };
+        TransferSnapshot {
+            sends: sorted(state.sends.keys().collect()),
+            downloads: sorted(state.downloads.keys().collect()),
+            connections: sorted(state.connections.keys().collect()),
+            offers: sorted(
+                state
+                    .offers
+                    .keys()
+                    .chain(state.text_offers.keys())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// True while any send, receive, pending offer or connection attempt is in flight.
+    pub async fn is_busy(&self) -> bool {
+        let state = self.state.lock().await;
+        !state.sends.is_empty()
+            || !state.downloads.is_empty()
+            || !state.connections.is_empty()
+            || !state.offers.is_empty()
+            || !state.text_offers.is_empty()
+    }
+
+    /// Take a send slot if fewer than `limit` sends are running (0 means no limit).
+    pub(crate) fn try_take_send_slot(&self, limit: u32) -> Option<SendSlot> {
+        self.send_slots
+            .running
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| {
+                (limit == 0 || running < limit as usize).then_some(running + 1)
+            })
+            .ok()
+            .map(|_| SendSlot(self.send_slots.clone()))
+    }
+
+    pub(crate) fn running_sends(&self) -> usize {
+        self.send_slots.running.load(Ordering::SeqCst)
+    }
+
+    /// Resolves the next time a slot is freed or the limit changes. Create it before checking
+    /// for a slot, so one freed in between isn't missed.
+    pub(crate) fn send_slot_freed(&self) -> Notified<'_> {
+        self.send_slots.freed.notified()
+    }
+
+    /// Wake queued sends so they re-check the limit.
+    pub(crate) fn wake_queued_sends(&self) {
+        self.send_slots.freed.notify_waiters();
+    }
+
+    pub(crate) async fn add_send(&self, id: &str, code: String, cancel: CancellationToken) {
+        let mut state = self.state.lock().await;
+        if !code.is_empty() {
+            state.shown_codes.insert(id.to_string(), code.clone());
+        }
+        state
+            .sends
+            .insert(id.to_string(), ActiveSend { code, cancel });
+    }
+
+    pub(crate) async fn set_send_code(&self, id: &str, code: String) {
+        let mut state = self.state.lock().await;
+        if let Some(send) = state.sends.get_mut(id) {
+            send.code = code.clone();
+            state.shown_codes.insert(id.to_string(), code);
+        }
+    }
+
+    /// The last code send `id` showed, if any, forgetting it.
+    pub(crate) async fn take_shown_code(&self, id: &str) -> Option<String> {
+        self.state.lock().await.shown_codes.remove(id)
+    }
+
+    /// The code of send `id`, once it has one.
+    pub(crate) async fn send_code(&self, id: &str) -> Option<String> {
+        self.state
+            .lock()
+            .await
+            .sends
+            .get(id)
+            .map(|send| send.code.clone())
+            .filter(|code| !code.is_empty())
+    }
+
+    pub(crate) async fn has_send(&self, id: &str) -> bool {
+        self.state.lock().await.sends.contains_key(id)
+    }
+
+    /// Take send `id` out, returning its token (cancelling it is up to the caller).
+    pub(crate) async fn remove_send(&self, id: &str) -> Option<CancellationToken> {
+        self.state
+            .lock()
+            .await
+            .sends
+            .remove(id)
+            .map(|send| send.cancel)
+    }
+
+    pub(crate) async fn add_download(&self, id: &str, cancel: CancellationToken) {
+        self.state
+            .lock()
+            .await
+            .downloads
+            .insert(id.to_string(), cancel);
+    }
+
+    pub(crate) async fn remove_download(&self, id: &str) -> Option<CancellationToken> {
+        self.state.lock().await.downloads.remove(id)
+    }
+
+    pub(crate) async fn add_connection(&self, id: &str, cancel: CancellationToken) {
+        self.state
+            .lock()
+            .await
+            .connections
+            .insert(id.to_string(), cancel);
+    }
+
+    pub(crate) async fn remove_connection(&self, id: &str) -> Option<CancellationToken> {
+        self.state.lock().await.connections.remove(id)
+    }
+
+    /// Cancel and take out every send, download and connection, in one step.
+    pub(crate) async fn cancel_all(&self) -> Cancelled {
+        let mut state = self.state.lock().await;
+        let sends = state
+            .sends
+            .drain()
+            .map(|(id, send)| {
+                send.cancel.cancel();
+                id
+            })
+            .collect();
+        let mut cancel = |tokens: &mut HashMap<String, CancellationToken>| -> Vec<String> {
+            tokens
+                .drain()
+                .map(|(id, token)| {
+                    token.cancel();
+                    id
+                })
+                .collect()
+        };
+        let downloads = cancel(&mut state.downloads);
+        let connections = cancel(&mut state.connections);
+        Cancelled {
+            sends,
+            downloads,
+            connections,
+        }
+    }
+
+    /// Hold offer `id`, whose connection had `secret`, until it's accepted or denied.
+    pub(crate) async fn add_offer(
+        &self,
+        id: &str,
+        offer: Box<dyn IncomingOffer>,
+        secret: Option<String>,
+    ) {
+        let mut state = self.state.lock().await;
+        state.offers.insert(id.to_string(), offer);
+        if let Some(secret) = secret {
+            state.remember_secret(id, secret);
+        }
+    }
+
+    /// Take offer `id` out to accept or deny it.
+    pub(crate) async fn take_offer(&self, id: &str) -> Option<Box<dyn IncomingOffer>> {
+        self.state.lock().await.offers.remove(id)
+    }
+
+    /// The ids of the file offers (not text messages) waiting for an answer, sorted.
+    pub(crate) async fn offer_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.state.lock().await.offers.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    pub(crate) async fn offer_name(&self, id: &str) -> Option<String> {
+        self.state
+            .lock()
+            .await
+            .offers
+            .get(id)
+            .map(|offer| offer.file_name())
+    }
+
+    pub(crate) async fn offer_size(&self, id: &str) -> Option<u64> {
+        self.state
+            .lock()
+            .await
+            .offers
+            .get(id)
+            .map(|offer| offer.file_size())
+    }
+
+    pub(crate) async fn add_text_offer(&self, id: &str, file_name: String, text: String) {
+        self.state
+            .lock()
+            .await
+            .text_offers
+            .insert(id.to_string(), (file_name, text));
+    }
+
+    /// Take text offer `id` out, as (file name, text).
+    pub(crate) async fn take_text_offer(&self, id: &str) -> Option<(String, String)> {
+        self.state.lock().await.text_offers.remove(id)
+    }
+
+    /// Remember the session secret of transfer `id`.
+    pub(crate) async fn remember_secret(&self, id: &str, secret: String) {
+        self.state.lock().await.remember_secret(id, secret);
+    }
+
+    /// The session secret of the finished send or received offer `id`.
+    pub(crate) async fn secret(&self, id: &str) -> Option<String> {
+        self.state.lock().await.secrets.get(id).cloned()
+    }
+
+    pub(crate) async fn forget_secret(&self, id: &str) {
+        let mut state = self.state.lock().await;
+        state.secrets.remove(id);
+        state.secret_order.retain(|kept| kept != id);
+    }
+
+    /// Run `f` on the packaging jobs, by job id.
+    pub(crate) async fn with_jobs<R>(&self, f: impl FnOnce(&mut HashMap<String, Job>) -> R) -> R {
+        f(&mut self.state.lock().await.jobs)
+    }
+
+    pub(crate) async fn add_multipart(&self, id: &str) {
+        self.state
+            .lock()
+            .await
+            .multipart
+            .insert(id.to_string(), false);
+    }
+
+    pub(crate) async fn remove_multipart(&self, id: &str) {
+        self.state.lock().await.multipart.remove(id);
+    }
+
+    /// Note that the user cancelled multi-part transfer `id`, if it is one.
+    pub(crate) async fn cancel_multipart(&self, id: &str) {
+        if let Some(cancelled) = self.state.lock().await.multipart.get_mut(id) {
+            *cancelled = true;
+        }
+    }
+
+    pub(crate) async fn multipart_cancelled(&self, id: &str) -> bool {
+        self.state
+            .lock()
+            .await
+            .multipart
+            .get(id)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    pub(crate) async fn add_size_computation(&self, id: &str, cancel: CancellationToken) {
+        self.state.lock().await.sizes.insert(id.to_string(), cancel);
+    }
+
+    pub(crate) async fn remove_size_computation(&self, id: &str) {
+        self.state.lock().await.sizes.remove(id);
+    }
+
+    /// Cancel size computation `id`; false if there's none.
+    pub(crate) async fn cancel_size_computation(&self, id: &str) -> bool {
+        match self.state.lock().await.sizes.get(id) {
+            Some(cancel) => {
+                cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Transfers {
+    fn remember_secret(&mut self, id: &str, secret: String) {
+        if self.secrets.insert(id.to_string(), secret).is_none() {
+            self.secret_order.push_back(id.to_string());
+        }
+        while self.secret_order.len() > MAX_REMEMBERED_SECRETS {
+            if let Some(oldest) = self.secret_order.pop_front() {
+                self.secrets.remove(&oldest);
+            }
+        }
+    }
+}
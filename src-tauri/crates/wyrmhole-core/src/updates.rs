@@ -0,0 +1,144 @@
+// This file checks GitHub releases for a newer wyrmhole. It only reads the public releases
+// API; the host decides when to call it (on launch if `check_updates_automatically` is on,
+// or from the Settings "Check now" button). The `release_channel` setting picks whether
+// pre-releases count: "stable" only sees the latest full release, "beta" sees both.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/ClaytonWas/wyrmhole/releases/latest";
+// Recent releases, newest first, including pre-releases (used by the beta channel).
+const RELEASES_URL: &str = "https://api.github.com/repos/ClaytonWas/wyrmhole/releases?per_page=20";
+
+/// Accepted values of the `release_channel` setting.
+pub const RELEASE_CHANNELS: &[&str] = &["stable", "beta"];
+
+/// Result of an update check.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    /// Git tag of the latest release, e.g. "v1.2.0-beta.1".
+    pub release_tag: String,
+    pub update_available: bool,
+    /// Release body as written on GitHub (Markdown).
+    pub release_notes: String,
+    /// Installer for this platform if the release has one, otherwise the release page.
+    pub download_url: String,
+    pub published_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+// Installer extensions to look for, best first, for the platform we're running on.
+fn platform_installer_extensions() -> &'static [&'static str] {
+    match std::env::consts::OS {
+        "windows" => &[".msi", "-setup.exe", ".exe"],
+        "macos" => &[".dmg"],
+        "linux" => &[".AppImage", ".deb", ".rpm"],
+        _ => &[],
+    }
+}
+
+fn pick_download_url(release: &GithubRelease) -> String {
+    platform_installer_extensions()
+        .iter()
+        .find_map(|ext| {
+            release
+                .assets
+                .iter()
+                .find(|asset| asset.name.ends_with(ext))
+                .map(|asset| asset.browser_download_url.clone())
+        })
+        .unwrap_or_else(|| release.html_url.clone())
+}
+
+fn parse_version(version: &str) -> Result<semver::Version, String> {
+    let trimmed = version.trim().trim_start_matches('v');
+    semver::Version::parse(trimmed).map_err(|e| format!("Invalid version '{}': {}", version, e))
+}
+
+async fn fetch_json<T: DeserializeOwned>(client: &reqwest::Client, url: &str) -> Result<T, String> {
+    client
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to reach GitHub releases: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to read release information: {}", e))
+}
+
+// Newest non-draft release by version. GitHub lists releases by creation date, which
+// isn't always version order when a stable fix ships after a beta.
+fn newest_release(releases: Vec<GithubRelease>) -> Option<(semver::Version, GithubRelease)> {
+    releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        .filter_map(|release| Some((parse_version(&release.tag_name).ok()?, release)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+}
+
+// Public API functions - called by the host (the Tauri command bindings, headless mode, the CLI)
+
+/// Compare `current_version` (the host's version) against the latest GitHub release on
+/// `channel` (one of `RELEASE_CHANNELS`).
+pub async fn check_for_updates(current_version: &str, channel: &str) -> Result<UpdateInfo, String> {
+    let current = parse_version(current_version)?;
+
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("wyrmhole/", env!("CARGO_PKG_VERSION")))
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let (latest, release) = match channel {
+        "stable" => {
+            let release: GithubRelease = fetch_json(&client, LATEST_RELEASE_URL).await?;
+            (parse_version(&release.tag_name)?, release)
+        }
+        "beta" => {
+            let releases: Vec<GithubRelease> = fetch_json(&client, RELEASES_URL).await?;
+            newest_release(releases).ok_or("No releases found")?
+        }
+        other => return Err(format!("Unknown release channel '{}'", other)),
+    };
+
+    log_line!(
+        "[magic-wormhole][updates][info] Running {}, latest {} release is {}",
+        current,
+        channel,
+        latest
+    );
+
+    Ok(UpdateInfo {
+        current_version: current.to_string(),
+        latest_version: latest.to_string(),
+        release_tag: release.tag_name.clone(),
+        update_available: latest > current,
+        release_notes: release.body.clone().unwrap_or_default(),
+        download_url: pick_download_url(&release),
+        published_at: release.published_at.clone(),
+    })
+}
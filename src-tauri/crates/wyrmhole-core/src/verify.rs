@@ -0,0 +1,134 @@
+// This file checksums transfers as they go by. `HashingWriter` wraps the stream a download
+// writes into, and `HashingReader` the one a send reads from, so every transfer gets a SHA-256
+// for its history entry without a second pass over the file. With the `verify_disk_writes`
+// setting on, `verify_written` then re-reads a saved file and compares, catching a disk that
+// silently wrote something else before the download is reported done; `verify_received_file`
+// in `files_json` does the same for a history entry later on. Everything else that hashes a
+// whole file (snapshots, sync manifests, the package cache) goes through `hash_file`, so
+// checksums taken in different places always compare equal.
+
+use futures::{AsyncRead, AsyncWrite};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+/// An `AsyncWrite` that hashes everything written through it.
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W> HashingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        HashingWriter {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// SHA-256 (hex) of what was written.
+    pub fn checksum(self) -> String {
+        hex(self.hasher)
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for HashingWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let n = ready!(Pin::new(&mut this.inner).poll_write(cx, buf))?;
+        this.hasher.update(&buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
+
+/// An `AsyncRead` that hashes everything read through it.
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R> HashingReader<R> {
+    pub fn new(inner: R) -> Self {
+        HashingReader {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// SHA-256 (hex) of what was read.
+    pub fn checksum(self) -> String {
+        hex(self.hasher)
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for HashingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let n = ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        this.hasher.update(&buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+}
+
+/// SHA-256 (hex) of the file at `path`. Fails for anything that isn't a regular file.
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    if !path.is_file() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a file"));
+    }
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hex(hasher))
+}
+
+/// The finished digest of `hasher`, in the lowercase hex every checksum is stored as.
+pub(crate) fn hex(hasher: Sha256) -> String {
+    format!("{:x}", hasher.finalize())
+}
+
+/// Re-read `path` from disk and check it hashes to `expected` (from a `HashingWriter`).
+pub async fn verify_written(path: &Path, expected: &str) -> Result<(), String> {
+    let on_disk = tokio::task::spawn_blocking({
+        let path: PathBuf = path.to_path_buf();
+        // Synced first, so what's read back is what the disk was asked to keep
+        move || {
+            // (opened for writing: Windows won't flush a read-only handle)
+            std::fs::OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.sync_all())
+                .ok()?;
+            hash_file(&path).ok()
+        }
+    })
+    .await
+    .ok()
+    .flatten()
+    .ok_or_else(|| format!("Couldn't read back {} to verify it", path.display()))?;
+    if on_disk != expected {
+        return Err(format!(
+            "Verification failed: {} on disk doesn't match what was received",
+            path.display()
+        ));
+    }
+    Ok(())
+}
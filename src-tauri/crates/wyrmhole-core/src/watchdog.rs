@@ -0,0 +1,168 @@
+// This file watches running transfers for stalls. Progress handlers `touch` a transfer's
+// `Activity`; if no bytes move for `STALL_AFTER` a `transfer-stalled` event goes out, and
+// with an idle timeout set (`idle_timeout_secs`) the transfer is aborted through its cancel
+// future instead of hanging forever.
+//
+// It also notices the machine's network changing under a transfer (Wi-Fi to Ethernet, a VPN
+// coming up) and emits `network-changed`, so a sudden stall can be explained. The transit
+// connection can't be moved to the new network, so a transfer that then fails says so.
+
+use futures::FutureExt;
+use futures::future;
+use std::net::{IpAddr, UdpSocket};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::context::AppContext;
+use crate::events;
+use crate::wormhole::CancelFuture;
+
+/// How long a transfer can go without progress before it's reported as stalled.
+pub const STALL_AFTER: Duration = Duration::from_secs(20);
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The local address outgoing connections use right now, or `None` with no route out.
+/// "Connecting" a UDP socket picks the route without sending anything, and polling this is
+/// portable where OS network change notifications aren't.
+pub fn local_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+/// When a transfer last moved bytes. Cheap to clone into progress handlers.
+#[derive(Clone)]
+pub struct Activity {
+    started: Instant,
+    last_progress_ms: Arc<AtomicU64>,
+}
+
+impl Activity {
+    pub fn touch(&self) {
+        let now = self.started.elapsed().as_millis() as u64;
+        self.last_progress_ms.store(now, Ordering::Relaxed);
+    }
+
+    fn idle(&self) -> Duration {
+        let last = Duration::from_millis(self.last_progress_ms.load(Ordering::Relaxed));
+        self.started.elapsed().saturating_sub(last)
+    }
+}
+
+pub struct Watchdog {
+    activity: Activity,
+    idle_timeout: Option<Duration>,
+    timed_out: Arc<AtomicBool>,
+    network_changed: Arc<AtomicBool>,
+}
+
+impl Watchdog {
+    /// A watchdog for a transfer starting now, using the configured idle timeout.
+    pub async fn new(ctx: &AppContext) -> Self {
+        let secs = ctx.settings().lock().await.get_idle_timeout_secs();
+        Watchdog {
+            activity: Activity {
+                started: Instant::now(),
+                last_progress_ms: Arc::default(),
+            },
+            idle_timeout: (secs > 0).then(|| Duration::from_secs(secs)),
+            timed_out: Arc::default(),
+            network_changed: Arc::default(),
+        }
+    }
+
+    pub fn activity(&self) -> Activity {
+        self.activity.clone()
+    }
+
+    /// `cancel`, also resolving once the transfer has been idle for the timeout. Watches for
+    /// stalls (emitting `transfer-stalled` once per stall) and network changes (emitting
+    /// `network-changed`) for as long as it's polled.
+    pub fn guard(&self, ctx: AppContext, id: String, cancel: CancelFuture) -> CancelFuture {
+        let activity = self.activity.clone();
+        let idle_timeout = self.idle_timeout;
+        let timed_out = self.timed_out.clone();
+        let network_changed = self.network_changed.clone();
+        let monitor = async move {
+            let mut stalled = false;
+            let mut address = local_address();
+            loop {
+                tokio::time::sleep(CHECK_INTERVAL).await;
+                let current = local_address();
+                if current != address {
+                    log_line!(
+                        "[magic-wormhole][watchdog][warn] Network changed during transfer {}: {:?} -> {:?}",
+                        id,
+                        address,
+                        current
+                    );
+                    network_changed.store(true, Ordering::Relaxed);
+                    let _ = ctx.emit(
+                        events::NETWORK_CHANGED,
+                        events::NetworkChanged {
+                            id: id.clone(),
+                            previous: address.map(|ip| ip.to_string()),
+                            current: current.map(|ip| ip.to_string()),
+                        },
+                    );
+                    address = current;
+                }
+                let idle = activity.idle();
+                if idle_timeout.is_some_and(|timeout| idle >= timeout) {
+                    log_line!(
+                        "[magic-wormhole][watchdog][error] Aborting transfer {} after {:?} idle",
+                        id,
+                        idle
+                    );
+                    timed_out.store(true, Ordering::Relaxed);
+                    return;
+                }
+                if idle < STALL_AFTER {
+                    stalled = false;
+                    continue;
+                }
+                if !stalled {
+                    stalled = true;
+                    log_line!(
+                        "[magic-wormhole][watchdog][warn] Transfer {} stalled for {:?}",
+                        id,
+                        idle
+                    );
+                    let _ = ctx.emit(
+                        events::TRANSFER_STALLED,
+                        events::TransferStalled {
+                            id: id.clone(),
+                            idle_secs: idle.as_secs(),
+                        },
+                    );
+                }
+            }
+        };
+        async move {
+            future::select(cancel, monitor.boxed()).await;
+        }
+        .boxed()
+    }
+
+    /// The error to report for a failed transfer: the idle timeout if that's what ended it,
+    /// noting a network change that happened during it.
+    pub fn describe_failure(&self, error: impl std::fmt::Display) -> String {
+        let message = match self.idle_timeout {
+            Some(timeout) if self.timed_out.load(Ordering::Relaxed) => format!(
+                "Transfer stalled: no data moved for {} seconds",
+                timeout.as_secs()
+            ),
+            _ => error.to_string(),
+        };
+        if self.network_changed.load(Ordering::Relaxed) {
+            format!(
+                "{} (the network changed during the transfer; try it again)",
+                message
+            )
+        } else {
+            message
+        }
+    }
+}
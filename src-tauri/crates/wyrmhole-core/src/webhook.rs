@@ -0,0 +1,83 @@
+// This file posts a JSON summary of each completed transfer to the `webhook_url` setting, for
+// home automation or logging. The post happens in the background after the transfer is
+// recorded; a webhook that's down or slow only costs a log line.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::time::Duration;
+
+use crate::context::AppContext;
+
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The body posted to the webhook.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+pub struct TransferSummary {
+    /// "sent" or "received".
+    pub direction: String,
+    /// The send or download id.
+    pub id: String,
+    pub name: String,
+    pub size: u64,
+    /// SHA-256 (hex) as recorded in the history: of the bytes sent (a folder's archive) or
+    /// the file saved. `None` for extracted archives, applied deltas and joined parts.
+    pub checksum: Option<String>,
+    pub duration_ms: u64,
+    pub finished_at: DateTime<Local>,
+}
+
+async fn post(url: &str, summary: &TransferSummary) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("wyrmhole/", env!("CARGO_PKG_VERSION")))
+        .timeout(POST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    client
+        .post(url)
+        .json(summary)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| format!("Webhook post failed: {}", e))
+}
+
+/// Report a completed transfer to the webhook, if one is set. `checksum` is the one taken
+/// while the transfer ran (see verify.rs), so nothing is read again. Returns at once; the
+/// post runs in the background.
+pub(crate) fn notify(
+    ctx: &AppContext,
+    direction: &str,
+    id: &str,
+    name: &str,
+    size: u64,
+    checksum: Option<String>,
+    duration: Duration,
+) {
+    let ctx = ctx.clone();
+    let summary = TransferSummary {
+        direction: direction.to_string(),
+        id: id.to_string(),
+        name: name.to_string(),
+        size,
+        checksum,
+        duration_ms: duration.as_millis() as u64,
+        finished_at: Local::now(),
+    };
+    tokio::spawn(async move {
+        let Some(url) = ctx
+            .settings()
+            .lock()
+            .await
+            .get_webhook_url()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+        else {
+            return;
+        };
+        if let Err(e) = post(&url, &summary).await {
+            log_line!("[magic-wormhole][webhook][warn] {}", e);
+        }
+    });
+}
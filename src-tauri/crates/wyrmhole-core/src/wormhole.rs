@@ -0,0 +1,403 @@
+// This file puts a trait seam over the magic-wormhole calls the engine makes (mailbox
+// creation/joining, the file transfer itself, answering an offer). `MagicWormhole` is the
+// real implementation; tests swap in a fake peer through `AppContext::with_wormhole`.
+
+use futures::future::BoxFuture;
+use futures::{AsyncRead, AsyncWrite, FutureExt};
+use magic_wormhole::transfer::{self, TransferError};
+use magic_wormhole::transit::{self, Abilities, RelayHint};
+use magic_wormhole::{AppConfig, Code, MailboxConnection, Wormhole, WormholeError};
+use std::fmt;
+use std::net::SocketAddr;
+
+/// Called with (bytes transferred, total bytes) as data moves.
+pub type ProgressHandler = Box<dyn FnMut(u64, u64) + Send + 'static>;
+/// Called once when the transit connection to the peer is established.
+pub type TransitHandler<'a> = Box<dyn FnOnce(TransitDetails) + Send + 'a>;
+/// Resolves when the transfer should be cancelled.
+pub type CancelFuture = BoxFuture<'static, ()>;
+
+/// How the peer was reached, flattened from `transit::TransitInfo`.
+#[derive(Debug, Clone)]
+pub struct TransitDetails {
+    pub connection_type: String,
+    pub peer_address: SocketAddr,
+}
+
+impl From<&transit::TransitInfo> for TransitDetails {
+    fn from(info: &transit::TransitInfo) -> Self {
+        let connection_type = match info.conn_type {
+            transit::ConnectionType::Direct => "direct".to_string(),
+            transit::ConnectionType::Relay { ref name } => {
+                if let Some(n) = name {
+                    format!("relay ({})", n)
+                } else {
+                    "relay".to_string()
+                }
+            }
+            _ => "unknown".to_string(),
+        };
+        TransitDetails {
+            connection_type,
+            peer_address: info.peer_addr,
+        }
+    }
+}
+
+/// Failure from the wormhole layer, keeping which stage failed.
+#[derive(Debug)]
+pub enum PeerError {
+    /// Creating or joining the mailbox on the rendezvous server.
+    Mailbox(WormholeError),
+    /// Key exchange with the peer.
+    Wormhole(WormholeError),
+    /// The file-transfer protocol after the peer connected.
+    Transfer(TransferError),
+    /// The connection to the rendezvous server dropped while waiting for the peer; the
+    /// mailbox may still be there to reclaim.
+    Disconnected(String),
+    Other(String),
+}
+
+impl fmt::Display for PeerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerError::Mailbox(e) | PeerError::Wormhole(e) => write!(f, "{}", e),
+            PeerError::Transfer(e) => write!(f, "{}", e),
+            PeerError::Disconnected(e) => {
+                write!(f, "Lost the connection to the rendezvous server: {}", e)
+            }
+            PeerError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Entry point into the wormhole layer.
+pub trait WormholeBackend: Send + Sync {
+    /// Allocate a new mailbox (and code) for a send.
+    fn create_mailbox(&self) -> BoxFuture<'static, Result<Box<dyn SendMailbox>, PeerError>>;
+
+    /// Join the mailbox for `code` and complete the key exchange with the sender.
+    fn connect(&self, code: Code)
+    -> BoxFuture<'static, Result<Box<dyn PeerConnection>, PeerError>>;
+
+    /// Rejoin a send's mailbox after the rendezvous connection dropped, so the code that was
+    /// already shared keeps working. Backends that can't reclaim fail, and the send gets a new
+    /// code instead.
+    fn reclaim_mailbox(
+        &self,
+        _code: Code,
+    ) -> BoxFuture<'static, Result<Box<dyn SendMailbox>, PeerError>> {
+        async {
+            Err(PeerError::Other(
+                "Reclaiming a mailbox is not supported".to_string(),
+            ))
+        }
+        .boxed()
+    }
+
+    /// Open the mailbox for a code both sides already know, claiming its nameplate if the
+    /// other side hasn't yet. Used for replies, where either side may get there first.
+    fn claim_mailbox(
+        &self,
+        _code: Code,
+    ) -> BoxFuture<'static, Result<Box<dyn SendMailbox>, PeerError>> {
+        async { Err(PeerError::Other("Replies are not supported".to_string())) }.boxed()
+    }
+}
+
+/// A mailbox whose code has been allocated but not yet claimed.
+pub trait SendMailbox: Send {
+    fn code(&self) -> String;
+
+    /// Wait for the receiver to claim the code and complete the key exchange.
+    fn wait_for_peer(
+        self: Box<Self>,
+    ) -> BoxFuture<'static, Result<Box<dyn PeerConnection>, PeerError>>;
+}
+
+/// An established wormhole with a peer.
+pub trait PeerConnection: Send {
+    #[allow(clippy::too_many_arguments)]
+    fn send_file<'a>(
+        self: Box<Self>,
+        relay_hints: Vec<RelayHint>,
+        abilities: Abilities,
+        file: &'a mut (dyn AsyncRead + Unpin + Send),
+        file_name: String,
+        file_size: u64,
+        on_transit: TransitHandler<'a>,
+        progress: ProgressHandler,
+        cancel: CancelFuture,
+    ) -> BoxFuture<'a, Result<(), PeerError>>;
+
+    /// Wait for the sender's offer. `None` means the sender went away without offering.
+    fn request_file(
+        self: Box<Self>,
+        relay_hints: Vec<RelayHint>,
+        abilities: Abilities,
+        cancel: CancelFuture,
+    ) -> BoxFuture<'static, Result<Option<PeerOffer>, PeerError>>;
+
+    /// A secret only the two sides of this connection know: the key they agreed on, which
+    /// never crosses the wire. Replies and further parts derive their codes from it (see
+    /// `files::follow_up_code`). Backends without such a key have no follow-ups.
+    fn session_secret(&self) -> Option<String> {
+        None
+    }
+}
+
+/// What the sender offered.
+pub enum PeerOffer {
+    File(Box<dyn IncomingOffer>),
+    /// A text message (`wormhole send --text`). It arrives whole with the offer, so there
+    /// is nothing left to accept or reject on the wire.
+    Text(String),
+}
+
+/// A file offer waiting for the user to accept or deny it.
+pub trait IncomingOffer: Send {
+    fn file_name(&self) -> String;
+    fn file_size(&self) -> u64;
+
+    fn accept<'a>(
+        self: Box<Self>,
+        on_transit: TransitHandler<'a>,
+        progress: ProgressHandler,
+        writer: &'a mut (dyn AsyncWrite + Unpin + Send),
+        cancel: CancelFuture,
+    ) -> BoxFuture<'a, Result<(), PeerError>>;
+
+    fn reject(self: Box<Self>) -> BoxFuture<'static, Result<(), PeerError>>;
+}
+
+/// The real wormhole layer, talking to the public (or configured) rendezvous server.
+pub struct MagicWormhole {
+    config: AppConfig<transfer::AppVersion>,
+}
+
+impl MagicWormhole {
+    /// Use the rendezvous (mailbox) server at `url` instead of the public one,
+    /// e.g. `ws://127.0.0.1:4000/v1` for a local server.
+    pub fn with_rendezvous_url(url: impl Into<String>) -> Self {
+        MagicWormhole {
+            config: transfer::APP_CONFIG
+                .clone()
+                .rendezvous_url(url.into().into()),
+        }
+    }
+}
+
+impl Default for MagicWormhole {
+    fn default() -> Self {
+        MagicWormhole {
+            config: transfer::APP_CONFIG.clone(),
+        }
+    }
+}
+
+impl WormholeBackend for MagicWormhole {
+    fn create_mailbox(&self) -> BoxFuture<'static, Result<Box<dyn SendMailbox>, PeerError>> {
+        let config = self.config.clone();
+        async move {
+            let mailbox = MailboxConnection::create(config, 2)
+                .await
+                .map_err(PeerError::Mailbox)?;
+            Ok(Box::new(mailbox) as Box<dyn SendMailbox>)
+        }
+        .boxed()
+    }
+
+    fn connect(
+        &self,
+        code: Code,
+    ) -> BoxFuture<'static, Result<Box<dyn PeerConnection>, PeerError>> {
+        let config = self.config.clone();
+        async move {
+            let mailbox = MailboxConnection::connect(config, code, false)
+                .await
+                .map_err(PeerError::Mailbox)?;
+            let wormhole = Wormhole::connect(mailbox)
+                .await
+                .map_err(PeerError::Wormhole)?;
+            Ok(Box::new(wormhole) as Box<dyn PeerConnection>)
+        }
+        .boxed()
+    }
+
+    fn reclaim_mailbox(
+        &self,
+        code: Code,
+    ) -> BoxFuture<'static, Result<Box<dyn SendMailbox>, PeerError>> {
+        let config = self.config.clone();
+        async move {
+            let mailbox = MailboxConnection::connect(config, code, false)
+                .await
+                .map_err(PeerError::Mailbox)?;
+            Ok(Box::new(mailbox) as Box<dyn SendMailbox>)
+        }
+        .boxed()
+    }
+
+    fn claim_mailbox(
+        &self,
+        code: Code,
+    ) -> BoxFuture<'static, Result<Box<dyn SendMailbox>, PeerError>> {
+        let config = self.config.clone();
+        async move {
+            let mailbox = MailboxConnection::connect(config, code, true)
+                .await
+                .map_err(PeerError::Mailbox)?;
+            Ok(Box::new(mailbox) as Box<dyn SendMailbox>)
+        }
+        .boxed()
+    }
+}
+
+impl SendMailbox for MailboxConnection<transfer::AppVersion> {
+    fn code(&self) -> String {
+        MailboxConnection::code(self).to_string()
+    }
+
+    fn wait_for_peer(
+        self: Box<Self>,
+    ) -> BoxFuture<'static, Result<Box<dyn PeerConnection>, PeerError>> {
+        async move {
+            let wormhole = Wormhole::connect(*self).await.map_err(|e| match e {
+                // The websocket to the rendezvous server went away, not the peer.
+                WormholeError::ServerError(_) => PeerError::Disconnected(e.to_string()),
+                e => PeerError::Wormhole(e),
+            })?;
+            Ok(Box::new(wormhole) as Box<dyn PeerConnection>)
+        }
+        .boxed()
+    }
+}
+
+impl PeerConnection for Wormhole {
+    fn send_file<'a>(
+        self: Box<Self>,
+        relay_hints: Vec<RelayHint>,
+        abilities: Abilities,
+        mut file: &'a mut (dyn AsyncRead + Unpin + Send),
+        file_name: String,
+        file_size: u64,
+        on_transit: TransitHandler<'a>,
+        progress: ProgressHandler,
+        cancel: CancelFuture,
+    ) -> BoxFuture<'a, Result<(), PeerError>> {
+        async move {
+            transfer::send_file(
+                *self,
+                relay_hints,
+                &mut file,
+                file_name,
+                file_size,
+                abilities,
+                |info| on_transit(TransitDetails::from(&info)),
+                progress,
+                cancel,
+            )
+            .await
+            .map_err(PeerError::Transfer)
+        }
+        .boxed()
+    }
+
+    fn request_file(
+        self: Box<Self>,
+        relay_hints: Vec<RelayHint>,
+        abilities: Abilities,
+        cancel: CancelFuture,
+    ) -> BoxFuture<'static, Result<Option<PeerOffer>, PeerError>> {
+        async move {
+            match transfer::request_file(*self, relay_hints, abilities, cancel).await {
+                Ok(request) => {
+                    Ok(request.map(|r| PeerOffer::File(Box::new(r) as Box<dyn IncomingOffer>)))
+                }
+                Err(e) => match text_offer_from_error(&e) {
+                    Some(text) => Ok(Some(PeerOffer::Text(text))),
+                    None => Err(PeerError::Transfer(e)),
+                },
+            }
+        }
+        .boxed()
+    }
+
+    fn session_secret(&self) -> Option<String> {
+        Some(self.key().to_hex())
+    }
+}
+
+// magic-wormhole's file API doesn't take text messages: it fails with a protocol error
+// that carries the offer it got instead, e.g. `... but got: 'Offer(Message("hi"))'`.
+// Recover the message from there so text sends still arrive.
+fn text_offer_from_error(error: &TransferError) -> Option<String> {
+    const MARKER: &str = "Offer(Message(\"";
+    let rendered = error.to_string();
+    let start = rendered.find(MARKER)? + MARKER.len();
+    unescape_debug_str(&rendered[start..])
+}
+
+// The contents of a `{:?}`-formatted string up to its closing quote, unescaped.
+fn unescape_debug_str(escaped: &str) -> Option<String> {
+    let mut text = String::new();
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(text),
+            '\\' => match chars.next()? {
+                'n' => text.push('\n'),
+                'r' => text.push('\r'),
+                't' => text.push('\t'),
+                '0' => text.push('\0'),
+                'u' => {
+                    // \u{1f600}
+                    let rest = chars.as_str().strip_prefix('{')?;
+                    let (hex, after) = rest.split_once('}')?;
+                    text.push(char::from_u32(u32::from_str_radix(hex, 16).ok()?)?);
+                    chars = after.chars();
+                }
+                other => text.push(other),
+            },
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+impl IncomingOffer for transfer::ReceiveRequest {
+    fn file_name(&self) -> String {
+        transfer::ReceiveRequest::file_name(self)
+    }
+
+    fn file_size(&self) -> u64 {
+        transfer::ReceiveRequest::file_size(self)
+    }
+
+    fn accept<'a>(
+        self: Box<Self>,
+        on_transit: TransitHandler<'a>,
+        progress: ProgressHandler,
+        writer: &'a mut (dyn AsyncWrite + Unpin + Send),
+        cancel: CancelFuture,
+    ) -> BoxFuture<'a, Result<(), PeerError>> {
+        async move {
+            let mut writer = writer;
+            (*self)
+                .accept(
+                    |info| on_transit(TransitDetails::from(&info)),
+                    progress,
+                    &mut writer,
+                    cancel,
+                )
+                .await
+                .map_err(PeerError::Transfer)
+        }
+        .boxed()
+    }
+
+    fn reject(self: Box<Self>) -> BoxFuture<'static, Result<(), PeerError>> {
+        async move { (*self).reject().await.map_err(PeerError::Transfer) }.boxed()
+    }
+}
@@ -0,0 +1,110 @@
+// Helpers shared by the engine and loopback tests: an event recorder, in-memory
+// settings, and a context rooted in a throwaway directory.
+#![allow(dead_code)]
+
+use serde_json::Value;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+
+use wyrmhole_core::context::{AppContext, AppPaths, EventSink};
+use wyrmhole_core::events;
+use wyrmhole_core::settings::{AppSettings, SettingsStore};
+use wyrmhole_core::wormhole::WormholeBackend;
+
+// Records every emitted event in order.
+#[derive(Default)]
+pub struct RecordingEvents(Mutex<Vec<(String, Value)>>);
+
+impl RecordingEvents {
+    pub fn named(&self, event: &str) -> Vec<Value> {
+        let events = self.0.lock().unwrap();
+        events
+            .iter()
+            .filter(|(name, _)| name == event)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+
+    pub fn send_statuses(&self, send_id: &str) -> Vec<String> {
+        let mut statuses: Vec<String> = Vec::new();
+        for payload in self.named(events::SEND_PROGRESS) {
+            if payload["id"] != send_id {
+                continue;
+            }
+            let status = payload["status"].as_str().unwrap().to_string();
+            if statuses.last() != Some(&status) {
+                statuses.push(status);
+            }
+        }
+        statuses
+    }
+
+    /// Poll until the connection code for a send has been emitted.
+    pub async fn wait_for_code(&self, send_id: &str) -> String {
+        loop {
+            let code = self
+                .named(events::CONNECTION_CODE)
+                .into_iter()
+                .find(|payload| payload["send_id"] == send_id);
+            if let Some(payload) = code {
+                return payload["code"].as_str().unwrap().to_string();
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+}
+
+impl EventSink for RecordingEvents {
+    fn emit_value(&self, event: &str, payload: Value) -> Result<(), String> {
+        self.0.lock().unwrap().push((event.to_string(), payload));
+        Ok(())
+    }
+}
+
+// Keeps settings in memory so tests never touch the real settings.json.
+pub struct MemorySettings(pub AppSettings);
+
+impl SettingsStore for MemorySettings {
+    fn load(&self) -> AppSettings {
+        self.0.clone()
+    }
+
+    fn save(&self, _settings: &AppSettings) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// A context rooted in a fresh temp directory, wired to `wormhole`.
+pub fn test_context(
+    wormhole: Arc<dyn WormholeBackend>,
+    configure: impl FnOnce(&mut AppSettings),
+) -> (AppContext, Arc<RecordingEvents>, TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = AppPaths {
+        config_dir: dir.path().join("config"),
+        data_dir: dir.path().join("data"),
+        default_download_dir: dir.path().join("downloads"),
+    };
+    let mut settings = AppSettings::defaults(paths.default_download_dir.clone());
+    configure(&mut settings);
+    let events = Arc::new(RecordingEvents::default());
+    let ctx = AppContext::new(
+        events.clone(),
+        paths,
+        Arc::new(MemorySettings(settings.clone())),
+        settings,
+    )
+    .with_wormhole(wormhole);
+    (ctx, events, dir)
+}
+
+pub fn unique_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+pub fn write_file(path: &Path, contents: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+}
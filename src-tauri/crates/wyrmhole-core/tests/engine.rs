@@ -0,0 +1,3318 @@
+// Engine tests: drive files.rs end to end against a fake peer, checking the status
+// events it emits, what lands in history, cancellation, and temp tarball cleanup.
+
+use futures::future::BoxFuture;
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt};
+use magic_wormhole::transit::{Abilities, RelayHint};
+use magic_wormhole::{Code, WormholeError};
+use sha2::Digest;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio_util::sync::CancellationToken;
+
+use wyrmhole_core::content::ContentWarning;
+use wyrmhole_core::context::AppContext;
+use wyrmhole_core::delta::{self, Signature};
+use wyrmhole_core::errors::{self, ErrorCode};
+use wyrmhole_core::extract::{ArchiveFormat, Extractor};
+use wyrmhole_core::hooks::{self, HookAction, ReceiveHook};
+use wyrmhole_core::parts::{self, PartName};
+use wyrmhole_core::receive::{Download, Flow, ReceiveStep};
+use wyrmhole_core::relays;
+use wyrmhole_core::sensitive::{self, SensitiveKind};
+use wyrmhole_core::throttle::{RateLimit, RateWindow, ThrottledReader};
+use wyrmhole_core::wormhole::{
+    CancelFuture, IncomingOffer, PeerConnection, PeerError, PeerOffer, ProgressHandler,
+    SendMailbox, TransitDetails, TransitHandler, WormholeBackend,
+};
+use wyrmhole_core::{
+    api, archive, cache, events, files, files_json, jobs, report, settings, share, sizes, sync,
+    verify, webhook,
+};
+
+mod common;
+use common::{RecordingEvents, test_context, unique_id, write_file};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Outcome {
+    Complete,
+    Fail,
+    WaitForCancel,
+}
+
+// A peer that completes, fails, or hangs until cancelled. On the receive side it
+// offers `offer` and writes its bytes into the download, or sends `text` as a message.
+// While `drops` is above zero, waiting for it fails as if the rendezvous server went away;
+// while `offline` is, so does creating a mailbox. An `absent` receiver never shows up.
+#[derive(Clone)]
+struct FakePeer {
+    outcome: Outcome,
+    offer: Option<(String, Vec<u8>)>,
+    text: Option<String>,
+    received: Arc<Mutex<Vec<u8>>>,
+    // Relay hints and abilities of the last send or receive, as `{:?}` strings.
+    relay_hints: Arc<Mutex<Vec<String>>>,
+    abilities: Arc<Mutex<String>>,
+    drops: Arc<AtomicUsize>,
+    offline: Arc<AtomicUsize>,
+    absent: bool,
+}
+
+impl FakePeer {
+    fn new(outcome: Outcome) -> Self {
+        FakePeer {
+            outcome,
+            offer: None,
+            text: None,
+            received: Arc::default(),
+            relay_hints: Arc::default(),
+            abilities: Arc::default(),
+            drops: Arc::default(),
+            offline: Arc::default(),
+            absent: false,
+        }
+    }
+
+    fn offering(name: &str, data: &[u8]) -> Self {
+        FakePeer {
+            offer: Some((name.to_string(), data.to_vec())),
+            ..FakePeer::new(Outcome::Complete)
+        }
+    }
+
+    fn texting(text: &str) -> Self {
+        FakePeer {
+            text: Some(text.to_string()),
+            ..FakePeer::new(Outcome::Complete)
+        }
+    }
+
+    async fn finish(
+        outcome: Outcome,
+        on_transit: TransitHandler<'_>,
+        mut progress: ProgressHandler,
+        cancel: CancelFuture,
+        size: u64,
+    ) -> Result<(), PeerError> {
+        match outcome {
+            Outcome::Complete => {
+                on_transit(TransitDetails {
+                    connection_type: "direct".to_string(),
+                    peer_address: peer_address(),
+                });
+                progress(size / 2, size);
+                progress(size, size);
+                Ok(())
+            }
+            Outcome::Fail => Err(PeerError::Other("peer went away".to_string())),
+            Outcome::WaitForCancel => {
+                cancel.await;
+                Err(PeerError::Other("transfer cancelled".to_string()))
+            }
+        }
+    }
+}
+
+fn peer_address() -> SocketAddr {
+    "192.0.2.7:4001".parse().unwrap()
+}
+
+impl WormholeBackend for FakePeer {
+    fn create_mailbox(&self) -> BoxFuture<'static, Result<Box<dyn SendMailbox>, PeerError>> {
+        let peer = self.clone();
+        async move {
+            let offline = peer
+                .offline
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if offline {
+                return Err(PeerError::Disconnected("network unreachable".to_string()));
+            }
+            Ok(Box::new(peer) as Box<dyn SendMailbox>)
+        }
+        .boxed()
+    }
+
+    fn connect(
+        &self,
+        _code: Code,
+    ) -> BoxFuture<'static, Result<Box<dyn PeerConnection>, PeerError>> {
+        let peer = self.clone();
+        async move { Ok(Box::new(peer) as Box<dyn PeerConnection>) }.boxed()
+    }
+
+    fn reclaim_mailbox(
+        &self,
+        _code: Code,
+    ) -> BoxFuture<'static, Result<Box<dyn SendMailbox>, PeerError>> {
+        let peer = self.clone();
+        async move { Ok(Box::new(peer) as Box<dyn SendMailbox>) }.boxed()
+    }
+
+    fn claim_mailbox(
+        &self,
+        code: Code,
+    ) -> BoxFuture<'static, Result<Box<dyn SendMailbox>, PeerError>> {
+        self.reclaim_mailbox(code)
+    }
+}
+
+impl SendMailbox for FakePeer {
+    fn code(&self) -> String {
+        "7-guitarist-revenge".to_string()
+    }
+
+    fn wait_for_peer(
+        self: Box<Self>,
+    ) -> BoxFuture<'static, Result<Box<dyn PeerConnection>, PeerError>> {
+        async move {
+            let dropped = self
+                .drops
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if dropped {
+                return Err(PeerError::Disconnected("connection reset".to_string()));
+            }
+            if self.absent {
+                futures::future::pending::<()>().await;
+            }
+            Ok(self as Box<dyn PeerConnection>)
+        }
+        .boxed()
+    }
+}
+
+impl PeerConnection for FakePeer {
+    fn send_file<'a>(
+        self: Box<Self>,
+        relay_hints: Vec<RelayHint>,
+        abilities: Abilities,
+        file: &'a mut (dyn futures::AsyncRead + Unpin + Send),
+        _file_name: String,
+        file_size: u64,
+        on_transit: TransitHandler<'a>,
+        progress: ProgressHandler,
+        cancel: CancelFuture,
+    ) -> BoxFuture<'a, Result<(), PeerError>> {
+        async move {
+            *self.relay_hints.lock().unwrap() =
+                relay_hints.iter().map(|h| format!("{:?}", h)).collect();
+            *self.abilities.lock().unwrap() = format!("{:?}", abilities);
+            if self.outcome == Outcome::Complete {
+                let mut data = Vec::new();
+                file.read_to_end(&mut data)
+                    .await
+                    .map_err(|e| PeerError::Other(e.to_string()))?;
+                *self.received.lock().unwrap() = data;
+            }
+            FakePeer::finish(self.outcome, on_transit, progress, cancel, file_size).await
+        }
+        .boxed()
+    }
+
+    fn request_file(
+        self: Box<Self>,
+        relay_hints: Vec<RelayHint>,
+        abilities: Abilities,
+        _cancel: CancelFuture,
+    ) -> BoxFuture<'static, Result<Option<PeerOffer>, PeerError>> {
+        async move {
+            *self.relay_hints.lock().unwrap() =
+                relay_hints.iter().map(|h| format!("{:?}", h)).collect();
+            *self.abilities.lock().unwrap() = format!("{:?}", abilities);
+            if let Some(text) = self.text.clone() {
+                return Ok(Some(PeerOffer::Text(text)));
+            }
+            Ok(self.offer.is_some().then(|| PeerOffer::File(self)))
+        }
+        .boxed()
+    }
+
+    fn session_secret(&self) -> Option<String> {
+        Some("fake session key".to_string())
+    }
+}
+
+impl IncomingOffer for FakePeer {
+    fn file_name(&self) -> String {
+        self.offer.as_ref().unwrap().0.clone()
+    }
+
+    fn file_size(&self) -> u64 {
+        self.offer.as_ref().unwrap().1.len() as u64
+    }
+
+    fn accept<'a>(
+        self: Box<Self>,
+        on_transit: TransitHandler<'a>,
+        progress: ProgressHandler,
+        writer: &'a mut (dyn futures::AsyncWrite + Unpin + Send),
+        cancel: CancelFuture,
+    ) -> BoxFuture<'a, Result<(), PeerError>> {
+        async move {
+            let data = self.offer.as_ref().unwrap().1.clone();
+            writer
+                .write_all(&data)
+                .await
+                .map_err(|e| PeerError::Other(e.to_string()))?;
+            writer
+                .flush()
+                .await
+                .map_err(|e| PeerError::Other(e.to_string()))?;
+            FakePeer::finish(
+                self.outcome,
+                on_transit,
+                progress,
+                cancel,
+                data.len() as u64,
+            )
+            .await
+        }
+        .boxed()
+    }
+
+    fn reject(self: Box<Self>) -> BoxFuture<'static, Result<(), PeerError>> {
+        async { Ok(()) }.boxed()
+    }
+}
+
+fn fake_context(peer: FakePeer) -> (AppContext, Arc<RecordingEvents>, TempDir) {
+    test_context(Arc::new(peer), |_| {})
+}
+
+fn leftover_tarballs(folder_name: &str) -> Vec<String> {
+    let suffix = format!("_{}.tar.gz", folder_name);
+    std::fs::read_dir(std::env::temp_dir())
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with("wyrmhole_send_") && name.ends_with(&suffix))
+        .collect()
+}
+
+#[tokio::test]
+async fn send_file_reports_statuses_and_records_history() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, events, dir) = fake_context(peer);
+    let path = dir.path().join("notes.txt");
+    write_file(&path, b"hello wormhole");
+    let id = unique_id();
+
+    files::send_file_call(ctx.clone(), path.to_str().unwrap(), id.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        events.send_statuses(&id),
+        [
+            "preparing",
+            "connecting",
+            "waiting",
+            "transit-negotiating",
+            "sending",
+            "verifying",
+            "finalizing"
+        ]
+    );
+    assert_eq!(*received.lock().unwrap(), b"hello wormhole");
+
+    let code = events.named(events::CONNECTION_CODE);
+    assert_eq!(code[0]["status"], "success");
+    assert_eq!(code[0]["code"], "7-guitarist-revenge");
+
+    let connected = events.named(events::PEER_CONNECTED);
+    assert_eq!(connected.len(), 1);
+    assert_eq!(connected[0]["id"], id.as_str());
+    assert_eq!(connected[0]["file_name"], "notes.txt");
+
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].file_name, "notes");
+    assert_eq!(sent[0].file_extension, "txt");
+    assert_eq!(sent[0].file_size, 14);
+    assert_eq!(sent[0].connection_code, "7-guitarist-revenge");
+    assert_eq!(sent[0].connection_type, "direct");
+    assert_eq!(sent[0].peer_address, peer_address());
+    assert_eq!(events.named(events::SENT_FILE_ADDED).len(), 1);
+}
+
+#[tokio::test]
+async fn sent_entries_can_be_checked_against_their_snapshot() {
+    let (ctx, _events, dir) = fake_context(FakePeer::new(Outcome::Complete));
+    let path = dir.path().join("ledger.csv");
+    write_file(&path, b"2026,100");
+
+    files::send_file_call(ctx.clone(), path.to_str().unwrap(), unique_id())
+        .await
+        .unwrap();
+    let entry = files_json::init_sent_files(&ctx).remove(0);
+    assert_eq!(entry.snapshots.len(), 1);
+
+    let state = |checks: Vec<files_json::SentPathCheck>| checks[0].state;
+    let checks = files_json::verify_sent_entry(&ctx, &entry.id)
+        .await
+        .unwrap();
+    assert_eq!(state(checks), files_json::SnapshotState::Unchanged);
+
+    write_file(&path, b"2026,999");
+    let checks = files_json::verify_sent_entry(&ctx, &entry.id)
+        .await
+        .unwrap();
+    assert_eq!(state(checks), files_json::SnapshotState::Changed);
+
+    std::fs::remove_file(&path).unwrap();
+    let checks = files_json::verify_sent_entry(&ctx, &entry.id)
+        .await
+        .unwrap();
+    assert_eq!(state(checks), files_json::SnapshotState::Missing);
+}
+
+#[tokio::test]
+async fn transfers_record_their_checksum_and_received_files_can_be_rechecked() {
+    let digest = format!("{:x}", sha2::Sha256::digest(b"2026,100"));
+    let (ctx, events, dir) = fake_context(FakePeer::new(Outcome::Complete));
+    let path = dir.path().join("ledger.csv");
+    write_file(&path, b"2026,100");
+    files::send_file_call(ctx.clone(), path.to_str().unwrap(), unique_id())
+        .await
+        .unwrap();
+    let sent = files_json::init_sent_files(&ctx).remove(0);
+    assert_eq!(sent.checksum.as_deref(), Some(digest.as_str()));
+    let verified = events.named(events::VERIFICATION_COMPLETE);
+    assert_eq!(verified[0]["direction"], "sent");
+    assert_eq!(verified[0]["checksum"], digest.as_str());
+    // Nothing was read back, so there's no verdict.
+    assert!(verified[0]["intact"].is_null());
+
+    let (ctx, events, _dir) = fake_context(FakePeer::offering("ledger.csv", b"2026,100"));
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    let received = files_json::init_received_files(&ctx).remove(0);
+    assert_eq!(received.checksum.as_deref(), Some(digest.as_str()));
+    assert!(
+        files_json::verify_received_file(&ctx, &received.id)
+            .await
+            .unwrap()
+    );
+
+    write_file(&received.saved_path(), b"2026,999");
+    assert!(
+        !files_json::verify_received_file(&ctx, &received.id)
+            .await
+            .unwrap()
+    );
+    let verified = events.named(events::VERIFICATION_COMPLETE);
+    assert_eq!(verified.len(), 3);
+    assert!(
+        verified
+            .iter()
+            .all(|event| event["direction"] == "received")
+    );
+    assert!(verified[0]["intact"].is_null());
+    assert_eq!(verified[1]["intact"], true);
+    assert_eq!(verified[2]["intact"], false);
+
+    std::fs::remove_file(received.saved_path()).unwrap();
+    assert!(
+        files_json::verify_received_file(&ctx, &received.id)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn sends_can_override_the_compression_level() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, _events, dir) = fake_context(peer);
+    let path = dir.path().join("zeros.bin");
+    write_file(&path, &[0u8; 100_000]);
+    let paths = vec![path.to_string_lossy().into_owned()];
+
+    files::send_multiple_files_call(ctx.clone(), paths.clone(), unique_id(), None, None)
+        .await
+        .unwrap();
+    assert!(received.lock().unwrap().len() < 10_000);
+
+    files::send_multiple_files_call(ctx.clone(), paths.clone(), unique_id(), None, Some(0))
+        .await
+        .unwrap();
+    assert!(received.lock().unwrap().len() > 100_000);
+
+    assert!(
+        files::send_multiple_files_call(ctx, paths.clone(), unique_id(), None, Some(10))
+            .await
+            .is_err()
+    );
+
+    // Streamed archives are plain tar, unless the send asks for a level.
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_stream_archives(true));
+    files::send_multiple_files_call(ctx.clone(), paths.clone(), unique_id(), None, None)
+        .await
+        .unwrap();
+    assert!(received.lock().unwrap().len() > 100_000);
+    files::send_multiple_files_call(ctx.clone(), paths, unique_id(), None, Some(9))
+        .await
+        .unwrap();
+    assert!(received.lock().unwrap().len() < 10_000);
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent[0].file_extension, "tar");
+    assert_eq!(sent[1].file_extension, "tar.gz");
+}
+
+#[tokio::test]
+async fn send_folder_packages_and_removes_temp_tarball() {
+    let (ctx, events, dir) = fake_context(FakePeer::new(Outcome::Complete));
+    let folder_name = format!("album-{}", unique_id());
+    let folder = dir.path().join(&folder_name);
+    write_file(&folder.join("a.txt"), b"a");
+    write_file(&folder.join("nested/b.txt"), b"b");
+    let id = unique_id();
+
+    files::send_file_call(ctx.clone(), folder.to_str().unwrap(), id.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        events.send_statuses(&id),
+        [
+            "preparing",
+            "connecting",
+            "waiting",
+            "packaging",
+            "transit-negotiating",
+            "sending",
+            "verifying",
+            "finalizing"
+        ]
+    );
+    assert!(leftover_tarballs(&folder_name).is_empty());
+
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].file_name, folder_name);
+    assert_eq!(sent[0].file_extension, "tar.gz");
+    assert_eq!(sent[0].connection_type, "direct");
+}
+
+#[tokio::test]
+async fn deterministic_archives_are_byte_identical() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, _events, dir) = test_context(Arc::new(peer), |s| s.set_deterministic_archives(true));
+    let folder = dir.path().join("album");
+    for name in ["c.txt", "a.txt", "nested/b.txt"] {
+        write_file(&folder.join(name), name.as_bytes());
+    }
+    let folder_path = folder.to_str().unwrap();
+
+    files::send_file_call(ctx.clone(), folder_path, unique_id())
+        .await
+        .unwrap();
+    let first = received.lock().unwrap().clone();
+
+    let a = std::fs::File::options()
+        .write(true)
+        .open(folder.join("a.txt"))
+        .unwrap();
+    a.set_modified(std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(86_400))
+        .unwrap();
+    files::send_file_call(ctx.clone(), folder_path, unique_id())
+        .await
+        .unwrap();
+
+    assert!(!first.is_empty());
+    assert_eq!(*received.lock().unwrap(), first);
+}
+
+#[tokio::test]
+async fn repeated_folder_sends_reuse_the_cached_tarball() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, _events, dir) = test_context(Arc::new(peer), |s| s.set_package_cache_mib(16));
+    let folder = dir.path().join("album");
+    write_file(&folder.join("a.txt"), b"a");
+    write_file(&folder.join("nested/b.txt"), b"b");
+    let folder_path = folder.to_str().unwrap();
+
+    files::send_file_call(ctx.clone(), folder_path, unique_id())
+        .await
+        .unwrap();
+    let first = received.lock().unwrap().clone();
+    files::send_file_call(ctx.clone(), folder_path, unique_id())
+        .await
+        .unwrap();
+
+    assert!(!first.is_empty());
+    assert_eq!(*received.lock().unwrap(), first);
+    assert_eq!(cache::package_cache_info(&ctx).await.unwrap().entries, 1);
+
+    cache::clear_package_cache(&ctx).await.unwrap();
+    assert_eq!(cache::package_cache_info(&ctx).await.unwrap().entries, 0);
+}
+
+#[tokio::test]
+async fn streamed_folders_are_sent_as_a_plain_tar() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, events, dir) = test_context(Arc::new(peer), |s| s.set_stream_archives(true));
+    let folder_name = format!("album-{}", unique_id());
+    let folder = dir.path().join(&folder_name);
+    write_file(&folder.join("a.txt"), b"a");
+    write_file(&folder.join("nested/b.txt"), &incompressible(70_000));
+    let long_name = format!("nested/{}.txt", "x".repeat(120));
+    write_file(&folder.join(&long_name), b"long");
+    let id = unique_id();
+
+    files::send_file_call(ctx.clone(), folder.to_str().unwrap(), id.clone())
+        .await
+        .unwrap();
+
+    assert!(!events.send_statuses(&id).contains(&"packaging".to_string()));
+    assert!(leftover_tarballs(&folder_name).is_empty());
+    let data = received.lock().unwrap().clone();
+    let finished = events.named(events::SEND_PROGRESS);
+    let offered = finished.iter().rfind(|e| e["id"] == id.as_str()).unwrap()["total"].clone();
+    assert_eq!(offered, data.len() as u64);
+
+    let mut archive = tar::Archive::new(data.as_slice());
+    let mut entries: Vec<String> = archive
+        .entries()
+        .unwrap()
+        .map(|entry| entry.unwrap())
+        .filter(|entry| entry.header().entry_type().is_file())
+        .map(|entry| entry.path().unwrap().to_string_lossy().into_owned())
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        [
+            format!("{}/a.txt", folder_name),
+            format!("{}/nested/b.txt", folder_name),
+            format!("{}/{}", folder_name, long_name),
+        ]
+    );
+
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent[0].file_extension, "tar");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn special_files_in_folders_are_skipped_and_reported() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, events, dir) = fake_context(peer);
+    let folder_name = format!("dev-{}", unique_id());
+    let folder = dir.path().join(&folder_name);
+    write_file(&folder.join("a.txt"), b"a");
+    write_file(&folder.join("empty.txt"), b"");
+    let socket = folder.join("app.sock");
+    let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+    std::os::unix::fs::symlink(folder.join("gone.txt"), folder.join("dangling")).unwrap();
+    let id = unique_id();
+
+    files::send_file_call(ctx.clone(), folder.to_str().unwrap(), id.clone())
+        .await
+        .unwrap();
+
+    let data = received.lock().unwrap().clone();
+    let mut entries = tarball_entries(&data);
+    entries.retain(|entry| entry.trim_end_matches('/') != folder_name);
+    assert_eq!(
+        entries,
+        [
+            format!("{}/a.txt", folder_name),
+            format!("{}/empty.txt", folder_name),
+        ]
+    );
+    let skipped = events.named(events::FILES_SKIPPED);
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0]["id"], id.as_str());
+    let mut reasons: Vec<_> = skipped[0]["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| file["reason"].as_str().unwrap().to_string())
+        .collect();
+    reasons.sort();
+    assert_eq!(reasons, ["broken-link", "socket"]);
+
+    // Picked directly, a special file is refused rather than skipped
+    let result = files::send_file_call(ctx.clone(), socket.to_str().unwrap(), unique_id()).await;
+    assert!(result.unwrap_err().contains("socket"));
+}
+
+#[tokio::test]
+async fn path_size_totals_a_folder() {
+    let (ctx, events, dir) = fake_context(FakePeer::new(Outcome::Complete));
+    let folder = dir.path().join("album");
+    write_file(&folder.join("a.txt"), b"hello");
+    write_file(&folder.join("nested/b.txt"), &[0; 1000]);
+    let id = unique_id();
+
+    let size = sizes::compute_path_size(
+        ctx.clone(),
+        folder.to_str().unwrap().to_string(),
+        id.clone(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        size,
+        sizes::PathSize {
+            bytes: 1005,
+            files: 2
+        }
+    );
+    let last = events.named(events::PATH_SIZE_PROGRESS).pop().unwrap();
+    assert_eq!(last["id"], id.as_str());
+    assert_eq!(last["bytes"], 1005);
+    assert_eq!(last["done"], true);
+    assert!(sizes::cancel_path_size(&ctx, id).await.is_err());
+    assert!(
+        sizes::compute_path_size(
+            ctx,
+            dir.path().join("missing").display().to_string(),
+            unique_id()
+        )
+        .await
+        .is_err()
+    );
+}
+
+#[tokio::test]
+async fn picked_folders_are_checked_and_normalized() {
+    let dir = TempDir::new().unwrap();
+    let folder = dir.path().join("album");
+    write_file(&folder.join("a.txt"), b"hello");
+    let file = folder.join("a.txt");
+
+    let unnormalized = folder.join("..").join("album");
+    let checked = sizes::check_folder_for_send(unnormalized.display().to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        std::path::PathBuf::from(&checked.path),
+        std::fs::canonicalize(&folder).unwrap()
+    );
+    assert_eq!(checked.size, sizes::PathSize { bytes: 5, files: 1 });
+    assert!(
+        sizes::check_folder_for_send(file.display().to_string())
+            .await
+            .is_err()
+    );
+}
+
+#[test]
+fn verbatim_windows_paths_are_simplified() {
+    let simplify = files::simplify_verbatim_path;
+    assert_eq!(
+        simplify(r"\\?\UNC\server\share\photos").as_deref(),
+        Some(r"\\server\share\photos")
+    );
+    assert_eq!(
+        simplify(r"\\?\Z:\photos\a.jpg").as_deref(),
+        Some(r"Z:\photos\a.jpg")
+    );
+    assert_eq!(simplify(r"\\?\C:").as_deref(), Some("C:"));
+    // Already usual, or only usable verbatim
+    assert_eq!(simplify(r"\\server\share\photos"), None);
+    assert_eq!(simplify(r"C:\photos"), None);
+    assert_eq!(
+        simplify(r"\\?\Volume{4c1b02c1-d990-11dc-99ae-806e6f6e6963}\a"),
+        None
+    );
+    let long = format!(r"\\?\UNC\server\share\{}", "a".repeat(300));
+    assert_eq!(simplify(&long), None);
+}
+
+#[test]
+fn relay_addresses_are_parsed() {
+    let address = |url: &str| relays::relay_address(url).map(|(h, p)| format!("{}:{}", h, p));
+    assert_eq!(
+        address("tcp://transit.magic-wormhole.io:4001").as_deref(),
+        Some("transit.magic-wormhole.io:4001")
+    );
+    assert_eq!(
+        address("tcp:relay.example:4001").as_deref(),
+        Some("relay.example:4001")
+    );
+    assert_eq!(
+        address("wss://relay.example/v1").as_deref(),
+        Some("relay.example:443")
+    );
+    assert_eq!(address("tcp://[::1]:4001").as_deref(), Some("::1:4001"));
+    assert_eq!(address("tcp://relay.example"), None);
+}
+
+#[tokio::test]
+async fn relay_benchmark_puts_reachable_relays_first() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let reachable = format!("tcp://{}", listener.local_addr().unwrap());
+    let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let unreachable = format!("tcp://{}", closed.local_addr().unwrap());
+    drop(closed);
+    let (ctx, _events, _dir) = test_context(Arc::new(FakePeer::new(Outcome::Complete)), |s| {
+        s.set_relay_server_url(Some(unreachable.clone()));
+        s.set_extra_relay_urls(vec![reachable.clone()]);
+    });
+
+    let results = relays::benchmark_relays(ctx.clone()).await.unwrap();
+
+    assert_eq!(results[0].url, reachable);
+    assert!(results[0].latency_ms.is_some());
+    assert!(results[1].error.is_some());
+    let settings = ctx.settings().lock().await;
+    assert_eq!(settings.get_relay_server_url(), Some(reachable.as_str()));
+    assert_eq!(settings.get_extra_relay_urls(), &vec![unreachable]);
+}
+
+#[tokio::test]
+async fn sends_offer_the_fastest_relay_first() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let reachable_port = listener.local_addr().unwrap().port();
+    let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let unreachable_port = closed.local_addr().unwrap().port();
+    drop(closed);
+    let peer = FakePeer::new(Outcome::Complete);
+    let relay_hints = peer.relay_hints.clone();
+    let (ctx, _events, dir) = test_context(Arc::new(peer), |s| {
+        s.set_relay_server_url(Some(format!("tcp://127.0.0.1:{}", unreachable_port)));
+        s.set_extra_relay_urls(vec![format!("tcp://127.0.0.1:{}", reachable_port)]);
+    });
+    let file = dir.path().join("notes.txt");
+    write_file(&file, b"hello");
+
+    files::send_file_call(ctx, file.to_str().unwrap(), unique_id())
+        .await
+        .unwrap();
+
+    let hints = relay_hints.lock().unwrap().clone();
+    assert_eq!(hints.len(), 2);
+    assert!(hints[0].contains(&reachable_port.to_string()));
+    assert!(hints[1].contains(&unreachable_port.to_string()));
+}
+
+#[tokio::test]
+async fn receives_offer_the_configured_relay() {
+    let peer = FakePeer::offering("photo.jpg", b"jpeg bytes");
+    let relay_hints = peer.relay_hints.clone();
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| {
+        s.set_relay_server_url(Some("tcp://relay.example.org:4001".to_string()))
+    });
+
+    files::request_file_call(ctx, "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+
+    let hints = relay_hints.lock().unwrap().clone();
+    assert_eq!(hints.len(), 1);
+    assert!(hints[0].contains("relay.example.org"));
+}
+
+#[tokio::test]
+async fn transit_mode_applies_to_sends_and_receives() {
+    let peer = FakePeer::offering("photo.jpg", b"jpeg bytes");
+    let abilities = peer.abilities.clone();
+    let (ctx, _events, dir) = fake_context(peer);
+    let path = dir.path().join("notes.txt");
+    write_file(&path, b"hello wormhole");
+
+    assert!(
+        settings::set_transit_mode(&ctx, "sideways".to_string())
+            .await
+            .is_err()
+    );
+    settings::set_transit_mode(&ctx, "relay".to_string())
+        .await
+        .unwrap();
+    files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    assert_eq!(
+        *abilities.lock().unwrap(),
+        format!("{:?}", Abilities::FORCE_RELAY)
+    );
+
+    settings::set_transit_mode(&ctx, "direct".to_string())
+        .await
+        .unwrap();
+    files::send_file_call(ctx, path.to_str().unwrap(), unique_id())
+        .await
+        .unwrap();
+    assert_eq!(
+        *abilities.lock().unwrap(),
+        format!("{:?}", Abilities::FORCE_DIRECT)
+    );
+}
+
+#[tokio::test]
+async fn offline_sends_wait_for_the_network() {
+    let peer = FakePeer::new(Outcome::Complete);
+    peer.offline.store(2, Ordering::SeqCst);
+    let (ctx, events, dir) = test_context(Arc::new(peer), |s| s.set_queue_offline_sends(true));
+    let file = dir.path().join("notes.txt");
+    write_file(&file, b"hello");
+    let send_id = unique_id();
+
+    files::send_file_call(ctx, file.to_str().unwrap(), send_id.clone())
+        .await
+        .unwrap();
+
+    let statuses = events.send_statuses(&send_id);
+    let offline = statuses.iter().position(|s| s == "offline").unwrap();
+    let waiting = statuses.iter().position(|s| s == "waiting").unwrap();
+    assert!(offline < waiting);
+    assert_eq!(events.named(events::CONNECTION_CODE).len(), 1);
+}
+
+#[tokio::test]
+async fn multi_file_sends_wait_for_the_network_too() {
+    let peer = FakePeer::new(Outcome::Complete);
+    peer.offline.store(1, Ordering::SeqCst);
+    let received = peer.received.clone();
+    let (ctx, events, dir) = test_context(Arc::new(peer), |s| s.set_queue_offline_sends(true));
+    let a = dir.path().join("a.txt");
+    let b = dir.path().join("b.txt");
+    write_file(&a, b"a");
+    write_file(&b, b"b");
+    let paths = vec![a.display().to_string(), b.display().to_string()];
+    let send_id = unique_id();
+
+    files::send_multiple_files_call(
+        ctx.clone(),
+        paths,
+        send_id.clone(),
+        Some("pair".into()),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let statuses = events.send_statuses(&send_id);
+    assert_eq!(statuses[..3], ["preparing", "connecting", "offline"]);
+    assert_eq!(
+        tarball_entries(&received.lock().unwrap()),
+        ["pair/a.txt", "pair/b.txt"]
+    );
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent[0].file_name, "pair");
+    assert_eq!(sent[0].file_extension, "tar.gz");
+    assert_eq!(sent[0].file_paths, [a, b]);
+}
+
+#[tokio::test]
+async fn offline_sends_fail_without_the_setting() {
+    let peer = FakePeer::new(Outcome::Complete);
+    peer.offline.store(1, Ordering::SeqCst);
+    let (ctx, events, dir) = fake_context(peer);
+    let file = dir.path().join("notes.txt");
+    write_file(&file, b"hello");
+    let send_id = unique_id();
+
+    assert!(
+        files::send_file_call(ctx, file.to_str().unwrap(), send_id.clone())
+            .await
+            .is_err()
+    );
+    assert!(
+        !events
+            .send_statuses(&send_id)
+            .contains(&"offline".to_string())
+    );
+}
+
+// Accept one HTTP request on `listener`, answer 200 and return its body.
+async fn receive_post(listener: &tokio::net::TcpListener) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let accepted = tokio::time::timeout(Duration::from_secs(10), listener.accept());
+    let (mut stream, _) = accepted.await.unwrap().unwrap();
+    let mut request = Vec::new();
+    let mut chunk = [0; 4096];
+    loop {
+        let len = stream.read(&mut chunk).await.unwrap();
+        request.extend_from_slice(&chunk[..len]);
+        let text = String::from_utf8_lossy(&request).to_string();
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let length: usize = head
+                .lines()
+                .find_map(|l| {
+                    l.to_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(str::to_string)
+                })
+                .map(|v| v.trim().parse().unwrap())
+                .unwrap_or(0);
+            if body.len() >= length {
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .await
+                    .unwrap();
+                return body.to_string();
+            }
+        }
+        assert!(len > 0, "connection closed mid-request");
+    }
+}
+
+#[tokio::test]
+async fn completed_sends_are_posted_to_the_webhook() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let (ctx, _events, dir) = test_context(Arc::new(FakePeer::new(Outcome::Complete)), |s| {
+        s.set_webhook_url(Some(url.clone()))
+    });
+    let file = dir.path().join("notes.txt");
+    write_file(&file, b"hello");
+    let send_id = unique_id();
+
+    files::send_file_call(ctx, file.to_str().unwrap(), send_id.clone())
+        .await
+        .unwrap();
+
+    let summary: webhook::TransferSummary =
+        serde_json::from_str(&receive_post(&listener).await).unwrap();
+    assert_eq!(summary.direction, "sent");
+    assert_eq!(summary.id, send_id);
+    assert_eq!(summary.name, "notes.txt");
+    assert_eq!(summary.size, 5);
+    assert_eq!(
+        summary.checksum.as_deref(),
+        Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+    );
+}
+
+#[tokio::test]
+async fn local_api_needs_its_token_and_starts_sends() {
+    let (ctx, events, dir) = test_context(Arc::new(FakePeer::new(Outcome::Complete)), |s| {
+        s.set_local_api_port(0)
+    });
+    let file = dir.path().join("notes.txt");
+    write_file(&file, b"hello");
+
+    settings::set_local_api_enabled(&ctx, true).await.unwrap();
+    let addr = api::address(&ctx).await.unwrap();
+    let token = settings::get_local_api_token(&ctx).await.unwrap();
+    assert!(!token.is_empty());
+    let client = reqwest::Client::new();
+    let url = |path: &str| format!("http://{}{}", addr, path);
+
+    let unauthorized = client.get(url("/status")).send().await.unwrap();
+    assert_eq!(unauthorized.status(), 401);
+    let wrong = client
+        .get(url("/status"))
+        .bearer_auth("not-the-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(wrong.status(), 401);
+
+    let status = client
+        .get(url("/status"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(status.status(), 200);
+
+    let sent = client
+        .post(url("/send"))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "paths": [file] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(sent.status(), 202);
+    let send_id = sent.json::<serde_json::Value>().await.unwrap()["send_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let code = tokio::time::timeout(Duration::from_secs(10), events.wait_for_code(&send_id))
+        .await
+        .unwrap();
+    assert_eq!(code, "7-guitarist-revenge");
+
+    // Sensitive selections wait for confirmation, and unknown offers aren't "accepted".
+    let key = dir.path().join("id_ed25519");
+    write_file(&key, b"key");
+    let refused = client
+        .post(url("/send"))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "paths": [key] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(refused.status(), 409);
+    let refused = refused.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(refused["sensitive"][0]["kind"], "secrets-file");
+    let unknown = client
+        .post(url("/offer/no-such-offer/accept"))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unknown.status(), 404);
+
+    settings::set_local_api_enabled(&ctx, false).await.unwrap();
+    assert_eq!(api::address(&ctx).await, None);
+}
+
+#[tokio::test]
+async fn local_api_downloads_stay_in_the_download_directory() {
+    let (ctx, _events, dir) = test_context(
+        Arc::new(FakePeer::offering("ledger.csv", b"2026,100")),
+        |s| s.set_local_api_port(0),
+    );
+    settings::set_local_api_enabled(&ctx, true).await.unwrap();
+    let addr = api::address(&ctx).await.unwrap();
+    let token = settings::get_local_api_token(&ctx).await.unwrap();
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    let client = reqwest::Client::new();
+    let accept = |destination: &Path| {
+        client
+            .post(format!("http://{}/offer/{}/accept", addr, offer.id))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({ "destination": destination }))
+            .send()
+    };
+
+    let elsewhere = dir.path().join("elsewhere");
+    std::fs::create_dir_all(&elsewhere).unwrap();
+    assert_eq!(accept(Path::new("elsewhere")).await.unwrap().status(), 400);
+    assert_eq!(accept(&elsewhere).await.unwrap().status(), 403);
+    let escaping = dir.path().join("downloads").join("..").join("elsewhere");
+    assert_eq!(accept(&escaping).await.unwrap().status(), 403);
+
+    let inside = dir.path().join("downloads").join("ledgers");
+    std::fs::create_dir_all(&inside).unwrap();
+    assert_eq!(accept(&inside).await.unwrap().status(), 202);
+    let saved = inside.join("ledger.csv");
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while !saved.exists() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert!(std::fs::read_dir(&elsewhere).unwrap().next().is_none());
+
+    settings::set_local_api_enabled(&ctx, false).await.unwrap();
+}
+
+#[test]
+fn code_shares_carry_the_code() {
+    let email = share::compose("7-guitarist-revenge", "email").unwrap();
+    assert!(email.text.contains("7-guitarist-revenge"));
+    assert!(email.text.contains(share::DOWNLOAD_PAGE));
+    let url = email.url.unwrap();
+    assert!(url.starts_with("mailto:?subject="));
+    assert!(url.contains("7-guitarist-revenge"));
+    assert!(!url.contains(' '));
+
+    assert!(
+        share::compose("7-guitarist-revenge", "sms")
+            .unwrap()
+            .url
+            .unwrap()
+            .starts_with("sms:")
+    );
+    assert_eq!(
+        share::compose("7-guitarist-revenge", "clipboard")
+            .unwrap()
+            .url,
+        None
+    );
+    assert!(share::compose("7-guitarist-revenge", "pigeon").is_err());
+}
+
+// Bytes gzip can't shrink, so a tarball of them stays about `len` long.
+fn incompressible(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+#[test]
+fn parts_split_and_join_back() {
+    assert_eq!(
+        PartName::parse("album.tar.gz.part2of3"),
+        Some(PartName {
+            base: "album.tar.gz".to_string(),
+            index: 2,
+            count: 3
+        })
+    );
+    assert_eq!(PartName::parse("album.tar.gz.part4of3"), None);
+    assert_eq!(PartName::parse("album.tar.gz.part1of4294967295"), None);
+    assert!(PartName::parse(&format!("album.tar.gz.part1of{}", parts::MAX_PARTS)).is_some());
+    assert_eq!(PartName::parse("notes.partial"), None);
+    // Part codes come from the session, not the code part 1 went out on.
+    let part_code = parts::part_code("session key", 2);
+    assert!(part_code.parse::<Code>().is_ok());
+    assert!(!part_code.starts_with("7-"));
+    assert_ne!(part_code, parts::part_code("session key", 3));
+    assert_ne!(part_code, parts::part_code("another session", 2));
+
+    let dir = TempDir::new().unwrap();
+    let data = incompressible(2500);
+    let path = dir.path().join("album.tar.gz");
+    write_file(&path, &data);
+    let split = parts::split_file(&path, "album.tar.gz", 1000).unwrap();
+    assert_eq!(split.len(), 3);
+    assert!(split[2].ends_with("album.tar.gz.part3of3"));
+    assert!(!path.exists());
+
+    let joined = dir.path().join("joined.tar.gz");
+    assert_eq!(parts::join_parts(&split, &joined).unwrap(), 2500);
+    assert_eq!(std::fs::read(&joined).unwrap(), data);
+    assert!(split.iter().all(|part| !part.exists()));
+
+    // Nothing is split into more parts than a receiver would take.
+    assert!(parts::split_file(&joined, "album.tar.gz", 1).is_err());
+    assert!(joined.exists());
+}
+
+#[tokio::test]
+async fn large_folders_are_sent_in_parts() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, events, dir) = test_context(Arc::new(peer), |s| s.set_archive_part_size_mib(1));
+    let folder = dir.path().join("footage");
+    write_file(&folder.join("clip.bin"), &incompressible(2_500_000));
+
+    files::send_file_call(ctx.clone(), folder.to_str().unwrap(), unique_id())
+        .await
+        .unwrap();
+
+    // One history entry and one code per part; the last part holds the remainder.
+    assert_eq!(files_json::init_sent_files(&ctx).len(), 3);
+    assert_eq!(events.named(events::CONNECTION_CODE).len(), 3);
+    assert!(received.lock().unwrap().len() < 1024 * 1024);
+}
+
+#[tokio::test]
+async fn failed_folder_send_removes_temp_tarball_and_is_recorded_as_failed() {
+    let (ctx, events, dir) = fake_context(FakePeer::new(Outcome::Fail));
+    let folder_name = format!("album-{}", unique_id());
+    let folder = dir.path().join(&folder_name);
+    write_file(&folder.join("a.txt"), b"a");
+    let id = unique_id();
+
+    let result = files::send_file_call(ctx.clone(), folder.to_str().unwrap(), id.clone()).await;
+
+    assert!(result.unwrap_err().contains("peer went away"));
+    assert_eq!(events.named(events::SEND_ERROR).len(), 1);
+    // The code it showed is announced as dead, and the send is gone
+    let code = events.named(events::CONNECTION_CODE)[0]["code"].clone();
+    let expired = events.named(events::CODE_EXPIRED);
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0]["id"], id.as_str());
+    assert_eq!(expired[0]["code"], code);
+    assert!(ctx.transfers().snapshot().await.sends.is_empty());
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].status, files_json::TransferStatus::Failed);
+    assert_eq!(sent[0].connection_type, "");
+    assert!(
+        sent[0]
+            .reason
+            .as_deref()
+            .unwrap()
+            .contains("peer went away")
+    );
+
+    // Cleanup after a failed send happens on a spawned task.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(leftover_tarballs(&folder_name).is_empty());
+}
+
+#[tokio::test]
+async fn send_multiple_files_records_one_bundle() {
+    let (ctx, events, dir) = fake_context(FakePeer::new(Outcome::Complete));
+    let first = dir.path().join("one.txt");
+    let second = dir.path().join("two.txt");
+    write_file(&first, b"1");
+    write_file(&second, b"2");
+    let id = unique_id();
+    let folder_name = format!("bundle-{}", unique_id());
+
+    files::send_multiple_files_call(
+        ctx.clone(),
+        vec![
+            first.to_string_lossy().into_owned(),
+            second.to_string_lossy().into_owned(),
+        ],
+        id.clone(),
+        Some(folder_name.clone()),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let statuses = events.send_statuses(&id);
+    assert_eq!(statuses.first().map(String::as_str), Some("preparing"));
+    assert_eq!(statuses.last().map(String::as_str), Some("finalizing"));
+    assert!(leftover_tarballs(&folder_name).is_empty());
+
+    let inspected: Vec<_> = events
+        .named(events::PREPARING_DETAILS)
+        .into_iter()
+        .filter(|e| e["id"] == id.as_str())
+        .map(|e| (e["path"].as_str().unwrap().to_string(), e["index"].clone()))
+        .collect();
+    assert_eq!(
+        inspected,
+        [
+            (first.to_string_lossy().into_owned(), 0.into()),
+            (second.to_string_lossy().into_owned(), 1.into())
+        ]
+    );
+
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].file_paths, vec![first, second]);
+    assert_eq!(sent[0].connection_type, "direct");
+    assert_eq!(sent[0].peer_address, peer_address());
+}
+
+#[tokio::test]
+async fn sent_paths_can_be_kept_as_names_or_hashes() {
+    let (ctx, _events, dir) = fake_context(FakePeer::new(Outcome::Complete));
+    let file = dir.path().join("private.txt");
+    write_file(&file, b"secret");
+
+    settings::set_sent_path_privacy(&ctx, "names".to_string())
+        .await
+        .unwrap();
+    files::send_file_call(ctx.clone(), file.to_str().unwrap(), unique_id())
+        .await
+        .unwrap();
+
+    settings::set_sent_path_privacy(&ctx, "hashed".to_string())
+        .await
+        .unwrap();
+    files::send_file_call(ctx.clone(), file.to_str().unwrap(), unique_id())
+        .await
+        .unwrap();
+
+    assert!(
+        settings::set_sent_path_privacy(&ctx, "everything".to_string())
+            .await
+            .is_err()
+    );
+
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 2);
+    assert_eq!(
+        sent[0].file_paths,
+        vec![std::path::PathBuf::from("private.txt")]
+    );
+    let salt = ctx.settings().lock().await.get_sent_path_salt().to_string();
+    assert!(!salt.is_empty());
+    assert_eq!(
+        sent[1].file_paths,
+        vec![std::path::PathBuf::from(files_json::hash_sent_path(
+            &salt, &file
+        ))]
+    );
+}
+
+#[tokio::test]
+async fn encrypted_archive_is_sent_as_a_zip() {
+    let (ctx, events, dir) = fake_context(FakePeer::new(Outcome::Complete));
+    let folder = dir.path().join("photos");
+    write_file(&folder.join("a.jpg"), b"a");
+    let id = unique_id();
+
+    archive::send_encrypted_archive_call(
+        ctx.clone(),
+        vec![folder.to_string_lossy().into_owned()],
+        id.clone(),
+        None,
+        "hunter2".to_string(),
+    )
+    .await
+    .unwrap();
+
+    // The send is registered (and has a code) before the zip is packed.
+    let statuses = events.send_statuses(&id);
+    let position = |status: &str| statuses.iter().position(|s| s == status);
+    assert!(position("waiting").unwrap() < position("packaging").unwrap());
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].file_name, "photos");
+    assert_eq!(sent[0].file_extension, "zip");
+    // The history lists what was picked, not the temporary zip.
+    assert_eq!(sent[0].file_paths, vec![folder]);
+}
+
+#[tokio::test]
+async fn encrypted_archive_requires_a_password() {
+    let (ctx, _events, dir) = fake_context(FakePeer::new(Outcome::Complete));
+    let path = dir.path().join("notes.txt");
+    write_file(&path, b"notes");
+
+    let result = archive::send_encrypted_archive_call(
+        ctx.clone(),
+        vec![path.to_string_lossy().into_owned()],
+        unique_id(),
+        None,
+        String::new(),
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert!(files_json::init_sent_files(&ctx).is_empty());
+}
+
+#[test]
+fn encrypted_zip_only_opens_with_the_password() {
+    let dir = tempfile::tempdir().unwrap();
+    let folder = dir.path().join("docs");
+    write_file(&folder.join("nested").join("secret.txt"), b"top secret");
+    let zip_path = dir.path().join("docs.zip");
+
+    archive::create_encrypted_zip(
+        &[folder.to_string_lossy().into_owned()],
+        &zip_path,
+        "bundle",
+        "hunter2",
+        CancellationToken::new(),
+        &mut Vec::new(),
+    )
+    .unwrap();
+
+    let mut zip = zip::ZipArchive::new(std::fs::File::open(&zip_path).unwrap()).unwrap();
+    let name = "bundle/docs/nested/secret.txt";
+    assert!(zip.by_name(name).is_err());
+    assert!(
+        zip.by_name_decrypt(name, b"wrong")
+            .and_then(|mut entry| {
+                let mut contents = Vec::new();
+                std::io::Read::read_to_end(&mut entry, &mut contents)?;
+                Ok(contents)
+            })
+            .is_err()
+    );
+    let mut contents = String::new();
+    std::io::Read::read_to_string(
+        &mut zip.by_name_decrypt(name, b"hunter2").unwrap(),
+        &mut contents,
+    )
+    .unwrap();
+    assert_eq!(contents, "top secret");
+}
+
+#[test]
+fn sensitive_sends_are_flagged() {
+    let dir = tempfile::tempdir().unwrap();
+    let home = dir.path().join("home").join("me");
+    write_file(&home.join("notes.txt"), b"shopping list");
+    write_file(
+        &home.join("project").join("deploy").join(".env"),
+        b"TOKEN=1",
+    );
+    write_file(&home.join(".ssh").join("config"), b"Host *");
+    write_file(&home.join("photos").join("cat.jpg"), b"jpg");
+    let path = |p: &Path| p.to_string_lossy().into_owned();
+    let kinds = |paths: &[String]| {
+        sensitive::find_sensitive(paths, Some(&home))
+            .into_iter()
+            .map(|found| found.kind)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(kinds(&[path(&home)]), vec![SensitiveKind::HomeDirectory]);
+    assert_eq!(
+        kinds(&[path(&home.join(".ssh"))]),
+        vec![SensitiveKind::KeysDirectory]
+    );
+    // Secrets are found inside a selected folder, not only when picked directly.
+    assert_eq!(
+        kinds(&[path(&home.join("project"))]),
+        vec![SensitiveKind::SecretsFile]
+    );
+    assert!(kinds(&[path(&home.join("photos")), path(&home.join("notes.txt"))]).is_empty());
+}
+
+// Paused time skips the reconnect backoff.
+#[tokio::test(start_paused = true)]
+async fn send_reclaims_its_mailbox_after_a_rendezvous_drop() {
+    let peer = FakePeer::new(Outcome::Complete);
+    peer.drops.store(1, Ordering::SeqCst);
+    let received = peer.received.clone();
+    let (ctx, events, dir) = fake_context(peer);
+    let path = dir.path().join("notes.txt");
+    write_file(&path, b"still here");
+    let id = unique_id();
+
+    files::send_file_call(ctx.clone(), path.to_str().unwrap(), id.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        events.send_statuses(&id),
+        [
+            "preparing",
+            "connecting",
+            "waiting",
+            "reconnecting",
+            "waiting",
+            "transit-negotiating",
+            "sending",
+            "verifying",
+            "finalizing"
+        ]
+    );
+    assert_eq!(*received.lock().unwrap(), b"still here");
+    // The same code was reclaimed, so it was only announced once.
+    assert_eq!(events.named(events::CONNECTION_CODE).len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn unclaimed_codes_count_down_and_expire() {
+    let peer = FakePeer {
+        absent: true,
+        ..FakePeer::new(Outcome::Complete)
+    };
+    let (ctx, events, dir) = test_context(Arc::new(peer), |s| s.set_code_expiry_mins(1));
+    let path = dir.path().join("notes.txt");
+    write_file(&path, b"nobody came");
+    let id = unique_id();
+
+    let result = files::send_file_call(ctx.clone(), path.to_str().unwrap(), id.clone()).await;
+
+    assert!(result.unwrap_err().contains("expired"));
+    let ages = events.named(events::CODE_AGE);
+    assert_eq!(ages.len(), 60);
+    assert!(ages.iter().all(|age| age["id"] == id.as_str()));
+    assert_eq!(ages[0]["age_secs"], 1);
+    assert_eq!(ages[0]["expires_in_secs"], 59);
+    assert_eq!(ages[59]["expires_in_secs"], 0);
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].status, files_json::TransferStatus::Failed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn send_gives_up_after_repeated_rendezvous_drops() {
+    let peer = FakePeer::new(Outcome::Complete);
+    peer.drops.store(usize::MAX, Ordering::SeqCst);
+    let (ctx, events, dir) = fake_context(peer);
+    let path = dir.path().join("notes.txt");
+    write_file(&path, b"gone");
+    let id = unique_id();
+
+    let result = files::send_file_call(ctx.clone(), path.to_str().unwrap(), id.clone()).await;
+
+    assert!(result.unwrap_err().contains("rendezvous server"));
+    let errors = events.named(events::SEND_ERROR);
+    assert_eq!(errors[0]["code"], "rendezvous-unreachable");
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].status, files_json::TransferStatus::Failed);
+}
+
+#[test]
+fn well_known_failures_get_error_codes() {
+    let wrong_code = PeerError::Wormhole(WormholeError::PakeFailed);
+    assert_eq!(ErrorCode::classify(&wrong_code), Some(ErrorCode::WrongCode));
+    assert!(errors::describe(&wrong_code).contains("typos"));
+    assert_eq!(
+        ErrorCode::classify(&PeerError::Disconnected("connection reset".to_string())),
+        Some(ErrorCode::RendezvousUnreachable)
+    );
+    let other = PeerError::Other("disk full".to_string());
+    assert_eq!(ErrorCode::classify(&other), None);
+    assert_eq!(errors::describe(&other), "disk full");
+}
+
+#[test]
+fn disk_errors_while_receiving_get_error_codes() {
+    let path = Path::new("downloads").join("photo.jpg");
+    let full = std::io::Error::from_raw_os_error(if cfg!(windows) { 112 } else { 28 });
+    assert_eq!(
+        ErrorCode::classify_io(&full, &path),
+        Some(ErrorCode::DiskFull)
+    );
+    assert!(errors::describe_io("Failed to write", &full, &path).starts_with("The disk is full."));
+    let too_long = std::io::Error::from_raw_os_error(if cfg!(windows) {
+        206
+    } else if cfg!(target_os = "linux") {
+        36
+    } else {
+        63
+    });
+    assert_eq!(
+        ErrorCode::classify_io(&too_long, &path),
+        Some(ErrorCode::PathTooLong)
+    );
+    let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+    assert_eq!(
+        ErrorCode::classify_io(&denied, &path),
+        Some(ErrorCode::PermissionDenied)
+    );
+
+    let other = std::io::Error::other("boom");
+    assert_eq!(ErrorCode::classify_io(&other, &path), None);
+    assert_eq!(
+        errors::describe_io("Failed to write", &other, &path),
+        format!("Failed to write {}: boom", path.display())
+    );
+}
+
+#[tokio::test]
+async fn hidden_codes_stay_out_of_progress_until_revealed() {
+    let peer = FakePeer::new(Outcome::WaitForCancel);
+    let (ctx, events, dir) = test_context(Arc::new(peer), |s| s.set_hide_send_codes(true));
+    let path = dir.path().join("secret.txt");
+    write_file(&path, b"shh");
+    let id = unique_id();
+
+    let send = tokio::spawn({
+        let ctx = ctx.clone();
+        let id = id.clone();
+        async move { files::send_file_call(ctx, path.to_str().unwrap(), id).await }
+    });
+    while !events.send_statuses(&id).contains(&"waiting".to_string()) {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let progress = events.named(events::SEND_PROGRESS);
+    assert!(
+        progress
+            .iter()
+            .filter(|p| p["id"] == id.as_str())
+            .all(|p| p["code"] == "")
+    );
+    assert_eq!(
+        files::reveal_code(id.clone(), ctx.clone()).await.unwrap(),
+        "7-guitarist-revenge"
+    );
+
+    files::cancel_send(id.clone(), ctx.clone()).await.unwrap();
+    assert!(send.await.unwrap().is_err());
+    assert!(files::reveal_code(id, ctx).await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn idle_timeout_aborts_a_stalled_transfer() {
+    let peer = FakePeer::new(Outcome::WaitForCancel);
+    let (ctx, events, dir) = test_context(Arc::new(peer), |s| s.set_idle_timeout_secs(30));
+    let path = dir.path().join("stuck.bin");
+    write_file(&path, &[0u8; 1024]);
+    let id = unique_id();
+
+    let result = files::send_file_call(ctx.clone(), path.to_str().unwrap(), id.clone()).await;
+
+    assert!(result.unwrap_err().contains("Transfer stalled"));
+    let stalls = events.named(events::TRANSFER_STALLED);
+    assert_eq!(stalls.len(), 1);
+    assert_eq!(stalls[0]["id"], id.as_str());
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].status, files_json::TransferStatus::Failed);
+}
+
+#[tokio::test]
+async fn cancel_send_stops_a_waiting_transfer() {
+    let (ctx, events, dir) = fake_context(FakePeer::new(Outcome::WaitForCancel));
+    let path = dir.path().join("big.bin");
+    write_file(&path, &[0u8; 1024]);
+    let id = unique_id();
+
+    let send = tokio::spawn({
+        let ctx = ctx.clone();
+        let id = id.clone();
+        async move { files::send_file_call(ctx, path.to_str().unwrap(), id).await }
+    });
+
+    // Wait until the code is out and the send is registered.
+    while !events.send_statuses(&id).contains(&"waiting".to_string()) {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(
+        files::active_transfers(ctx.clone()).await.sends,
+        [id.clone()]
+    );
+    // Transfer state belongs to its context; another one can't see or cancel this send.
+    let (other, _other_events, _other_dir) = fake_context(FakePeer::new(Outcome::Complete));
+    assert!(!files::has_active_transfers(&other).await);
+    assert!(files::cancel_send(id.clone(), other).await.is_err());
+
+    files::cancel_send(id.clone(), ctx.clone()).await.unwrap();
+    assert!(send.await.unwrap().is_err());
+    assert!(!files::has_active_transfers(&ctx).await);
+
+    let errors = events.named(events::SEND_ERROR);
+    assert!(
+        errors
+            .iter()
+            .any(|e| e["id"] == id.as_str() && e["error"] == "Transfer cancelled by user")
+    );
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].status, files_json::TransferStatus::Cancelled);
+    assert_eq!(sent[0].file_name, "big");
+    assert_eq!(sent[0].file_size, 1024);
+    assert!(files::cancel_send(id, ctx).await.is_err());
+}
+
+#[tokio::test]
+async fn cancelling_while_packaging_stops_and_cleans_up() {
+    let (ctx, events, dir) = fake_context(FakePeer::new(Outcome::WaitForCancel));
+    let id = unique_id();
+    let folder = dir.path().join(format!("pack-{}", id));
+    std::fs::create_dir_all(&folder).unwrap();
+    // Incompressible data so packaging takes long enough to interrupt.
+    let mut state = 0x2545_f491_u32;
+    for i in 0..8 {
+        let data: Vec<u8> = (0..4 * 1024 * 1024)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        write_file(&folder.join(format!("part{}.bin", i)), &data);
+    }
+
+    let send = tokio::spawn({
+        let ctx = ctx.clone();
+        let id = id.clone();
+        let folder = folder.clone();
+        async move { files::send_file_call(ctx, folder.to_str().unwrap(), id).await }
+    });
+    while events.named(events::PACKAGING_JOB).is_empty() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    files::cancel_send(id.clone(), ctx.clone()).await.unwrap();
+    assert!(send.await.unwrap().is_err());
+
+    assert!(leftover_tarballs(&format!("pack-{}", id)).is_empty());
+    let jobs = events.named(events::PACKAGING_JOB);
+    assert_eq!(jobs.last().unwrap()["state"], "cancelled");
+}
+
+#[tokio::test]
+async fn folders_are_packaged_while_waiting_for_the_receiver() {
+    let peer = FakePeer {
+        absent: true,
+        ..FakePeer::new(Outcome::Complete)
+    };
+    let (ctx, events, dir) = fake_context(peer);
+    let folder_name = format!("early-{}", unique_id());
+    write_file(&dir.path().join(&folder_name).join("a.txt"), b"a");
+    let folder = dir.path().join(&folder_name);
+    let id = unique_id();
+
+    let send = tokio::spawn({
+        let ctx = ctx.clone();
+        let id = id.clone();
+        async move { files::send_file_call(ctx, folder.to_str().unwrap(), id).await }
+    });
+    let done = |jobs: Vec<serde_json::Value>| jobs.iter().any(|job| job["state"] == "done");
+    while !done(events.named(events::PACKAGING_JOB)) {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    // Nobody has connected, yet the tarball is ready to go.
+    assert_eq!(events.send_statuses(&id).last().unwrap(), "waiting");
+    let tarballs = leftover_tarballs(&folder_name);
+    assert_eq!(tarballs.len(), 1);
+
+    send.abort();
+    std::fs::remove_file(std::env::temp_dir().join(&tarballs[0])).unwrap();
+}
+
+#[tokio::test]
+async fn failed_packaging_can_be_retried_on_the_same_code() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, events, dir) = test_context(Arc::new(peer), |s| s.set_packaging_retry_secs(120));
+    let present = dir.path().join("present.txt");
+    let missing = dir.path().join("missing.txt");
+    write_file(&present, b"here");
+    let id = unique_id();
+
+    let send = tokio::spawn({
+        let ctx = ctx.clone();
+        let id = id.clone();
+        let paths = vec![
+            present.to_str().unwrap().to_string(),
+            missing.to_str().unwrap().to_string(),
+        ];
+        async move {
+            files::send_multiple_files_call(ctx, paths, id, Some("bundle".to_string()), None).await
+        }
+    });
+    let failed = |events: &Vec<serde_json::Value>| {
+        events
+            .iter()
+            .any(|job| job["send_id"] == id.as_str() && job["state"] == "failed")
+    };
+    while !failed(&events.named(events::PACKAGING_JOB)) {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let listed = jobs::list_jobs(&ctx).await;
+    let job = listed.iter().find(|job| job.send_id == id).unwrap();
+    assert_eq!(job.state, events::JobState::Failed);
+    assert!(job.error.as_ref().unwrap().contains("does not exist"));
+    assert!(events.named(events::SEND_ERROR).is_empty());
+
+    write_file(&missing, b"found it");
+    jobs::retry_job(&ctx, job.job_id.clone()).await.unwrap();
+    send.await.unwrap().unwrap();
+
+    assert_eq!(
+        tarball_entries(&received.lock().unwrap()),
+        ["bundle/missing.txt", "bundle/present.txt"]
+    );
+    let codes: Vec<_> = events.named(events::CONNECTION_CODE);
+    assert_eq!(codes.len(), 1);
+    assert!(
+        jobs::list_jobs(&ctx)
+            .await
+            .iter()
+            .all(|job| job.send_id != id)
+    );
+}
+
+#[tokio::test]
+async fn packaging_fails_the_send_without_a_retry_window() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let (ctx, events, dir) = test_context(Arc::new(peer), |s| s.set_packaging_retry_secs(0));
+    let present = dir.path().join("present.txt");
+    write_file(&present, b"here");
+    let paths = vec![
+        present.to_str().unwrap().to_string(),
+        dir.path().join("missing.txt").to_str().unwrap().to_string(),
+    ];
+    let id = unique_id();
+
+    let result = files::send_multiple_files_call(ctx, paths, id.clone(), None, None).await;
+
+    assert!(result.unwrap_err().contains("does not exist"));
+    let errors = events.named(events::SEND_ERROR);
+    assert!(errors.iter().any(|e| e["id"] == id.as_str()));
+    // No retry is offered that couldn't be taken.
+    assert!(
+        events
+            .named(events::PACKAGING_JOB)
+            .iter()
+            .all(|job| job["state"] != "failed")
+    );
+}
+
+#[tokio::test]
+async fn receive_accept_writes_file_and_records_history() {
+    let (ctx, events, _dir) = fake_context(FakePeer::offering("photo.jpg", b"jpeg bytes"));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    assert_eq!(offer.file_name, "photo.jpg");
+    assert_eq!(offer.file_size, 10);
+
+    files::receiving_file_accept(offer.id.clone(), ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    let download_dir = ctx.paths().default_download_dir.clone();
+    assert_eq!(
+        std::fs::read(download_dir.join("photo.jpg")).unwrap(),
+        b"jpeg bytes"
+    );
+
+    let progress = events.named(events::DOWNLOAD_PROGRESS);
+    assert_eq!(progress.last().unwrap()["percentage"], 100);
+    assert_eq!(progress[0]["status"], "transit-negotiating");
+    assert_eq!(progress.last().unwrap()["status"], "finalizing");
+
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].file_name, "photo");
+    assert_eq!(received[0].file_extension, "jpg");
+    assert_eq!(received[0].connection_type, "direct");
+    assert_eq!(received[0].peer_address, peer_address());
+
+    // The offer was consumed.
+    assert!(
+        files::receiving_file_accept(offer.id, ctx, None, false, false)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn unwritable_download_folder_keeps_the_offer_for_another_folder() {
+    let (ctx, events, dir) = fake_context(FakePeer::offering("photo.jpg", b"jpeg bytes"));
+    // A file where a folder should be fails the same way a read-only or unplugged drive does
+    write_file(&dir.path().join("blocker"), b"");
+    ctx.settings()
+        .lock()
+        .await
+        .set_download_directory(dir.path().join("blocker").join("downloads"));
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+
+    let result =
+        files::receiving_file_accept(offer.id.clone(), ctx.clone(), None, false, false).await;
+
+    assert!(result.unwrap_err().contains("can't be written to"));
+    let errors = events.named(events::DOWNLOAD_ERROR);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["code"], "destination-unwritable");
+    assert!(events.named(events::DOWNLOAD_PROGRESS).is_empty());
+
+    let elsewhere = dir.path().join("elsewhere");
+    files::receiving_file_accept(
+        offer.id,
+        ctx.clone(),
+        Some(elsewhere.to_string_lossy().into_owned()),
+        false,
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        std::fs::read(elsewhere.join("photo.jpg")).unwrap(),
+        b"jpeg bytes"
+    );
+}
+
+#[tokio::test]
+async fn received_entries_can_be_removed_from_history() {
+    let (ctx, _events, _dir) = fake_context(FakePeer::offering("photo.jpg", b"jpeg bytes"));
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    let entry = files_json::init_received_files(&ctx).remove(0);
+
+    files_json::remove_received_entry(&ctx, &entry.id, false)
+        .await
+        .unwrap();
+
+    assert!(files_json::init_received_files(&ctx).is_empty());
+    // Only the entry went; the file stays unless asked to go to the trash
+    assert!(entry.saved_path().is_file());
+    assert!(
+        files_json::remove_received_entry(&ctx, &entry.id, true)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn duplicate_receives_are_detected_and_can_be_skipped() {
+    let (ctx, events, _dir) = fake_context(FakePeer::offering("photo.jpg", b"jpeg bytes"));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    let receive = |ctx: AppContext| async move {
+        let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+            .await
+            .unwrap();
+        files::receiving_file_accept(offer.id, ctx, None, false, false)
+            .await
+            .unwrap();
+    };
+
+    receive(ctx.clone()).await;
+    assert!(events.named(events::DUPLICATE_DETECTED).is_empty());
+
+    // Detected but kept while skipping is off.
+    receive(ctx.clone()).await;
+    let duplicates = events.named(events::DUPLICATE_DETECTED);
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0]["skipped"], false);
+    assert_eq!(
+        std::path::PathBuf::from(duplicates[0]["existing_path"].as_str().unwrap()),
+        download_dir.join("photo.jpg")
+    );
+    assert!(download_dir.join("photo(1).jpg").exists());
+
+    settings::set_skip_duplicate_receives(&ctx, true)
+        .await
+        .unwrap();
+    receive(ctx.clone()).await;
+    let duplicates = events.named(events::DUPLICATE_DETECTED);
+    assert_eq!(duplicates.len(), 2);
+    assert_eq!(duplicates[1]["skipped"], true);
+    assert!(!download_dir.join("photo(2).jpg").exists());
+
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received.len(), 3);
+    assert!(received[0].checksum.is_some());
+    assert_eq!(received[2].checksum, received[0].checksum);
+    assert_eq!(received[2].file_name, "photo");
+    assert_eq!(received[2].download_url, download_dir);
+}
+
+#[tokio::test]
+async fn verified_receives_check_the_file_on_disk() {
+    let peer = FakePeer::offering("photo.jpg", b"jpeg bytes");
+    let (ctx, events, dir) = test_context(Arc::new(peer), |s| s.set_verify_disk_writes(true));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    let received = files_json::init_received_files(&ctx);
+    let expected = format!("{:x}", sha2::Sha256::digest(b"jpeg bytes"));
+    assert_eq!(received[0].checksum.as_deref(), Some(expected.as_str()));
+    assert_eq!(
+        events.named(events::VERIFICATION_COMPLETE)[0]["intact"],
+        true
+    );
+    assert_eq!(
+        verify::hash_file(&received[0].saved_path()).unwrap(),
+        expected
+    );
+    verify::verify_written(&received[0].saved_path(), &expected)
+        .await
+        .unwrap();
+
+    // A file that changed after it was written fails verification.
+    let changed = dir.path().join("changed.jpg");
+    write_file(&changed, b"jpeg bytez");
+    assert!(
+        verify::verify_written(&changed, &expected)
+            .await
+            .unwrap_err()
+            .contains("doesn't match")
+    );
+}
+
+#[tokio::test]
+async fn offers_over_the_size_limit_need_confirming() {
+    let data = vec![7u8; 1024 * 1024 + 1];
+    let peer = FakePeer::offering("big.bin", &data);
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_max_incoming_size_mib(1));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    assert!(offer.over_size_limit);
+
+    let refused =
+        files::receiving_file_accept(offer.id.clone(), ctx.clone(), None, false, false).await;
+    assert!(refused.unwrap_err().contains("over the 1 MiB limit"));
+    // Still pending, and nothing recorded
+    assert!(files_json::init_received_files(&ctx).is_empty());
+
+    files::receiving_file_accept(offer.id, ctx.clone(), None, true, false)
+        .await
+        .unwrap();
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].file_size, data.len() as u64);
+}
+
+#[tokio::test]
+async fn programs_and_scripts_need_confirming() {
+    let peer = FakePeer::offering("setup.exe", b"MZ");
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |_| {});
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    assert_eq!(offer.content_warning, Some(ContentWarning::Executable));
+
+    let refused =
+        files::receiving_file_accept(offer.id.clone(), ctx.clone(), None, false, false).await;
+    assert!(refused.unwrap_err().contains("setup.exe is a program"));
+    // Still pending, and nothing recorded
+    assert!(files_json::init_received_files(&ctx).is_empty());
+
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, true)
+        .await
+        .unwrap();
+    assert_eq!(files_json::init_received_files(&ctx).len(), 1);
+
+    // Allowlisted extensions don't warn
+    let peer = FakePeer::offering("setup.exe", b"MZ");
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| {
+        s.set_content_allowlist(vec![".EXE".to_string()])
+    });
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    assert_eq!(offer.content_warning, None);
+}
+
+#[tokio::test]
+async fn disguised_programs_are_kept_as_saved() {
+    let data = tarball(&[("tools/readme.txt", "hi"), ("tools/install.sh", "echo hi")]);
+    let peer = FakePeer::offering("photos.tar.gz", &data);
+    let (ctx, events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    assert_eq!(offer.content_warning, None);
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    // Not extracted
+    assert!(download_dir.join("photos.tar.gz").exists());
+    assert!(!download_dir.join("tools").exists());
+    let flagged = events.named(events::CONTENT_FLAGGED);
+    assert_eq!(flagged.len(), 1);
+    assert_eq!(flagged[0]["warning"], "archive-with-executable");
+
+    // An ELF binary under an innocent name
+    let peer = FakePeer::offering("notes.txt", b"\x7fELF\x02\x01\x01");
+    let (ctx, events, _dir) = test_context(Arc::new(peer), |_| {});
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    let flagged = events.named(events::CONTENT_FLAGGED);
+    assert_eq!(flagged[0]["warning"], "executable");
+}
+
+#[tokio::test]
+async fn archives_too_large_to_check_are_flagged() {
+    // A program behind more filler entries than are scanned.
+    let names: Vec<String> = (0..10_000).map(|i| format!("filler/{}.txt", i)).collect();
+    let mut entries: Vec<(&str, &str)> = names.iter().map(|name| (name.as_str(), "")).collect();
+    entries.push(("filler/zz.txt", "#!/bin/sh\necho hi"));
+    let peer = FakePeer::offering("filler.tar.gz", &tarball(&entries));
+    let (ctx, events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    assert!(download_dir.join("filler.tar.gz").exists());
+    assert!(!download_dir.join("filler").exists());
+    let flagged = events.named(events::CONTENT_FLAGGED);
+    assert_eq!(flagged[0]["warning"], "unchecked-archive");
+}
+
+#[tokio::test]
+async fn receive_with_date_subfolders_saves_under_today() {
+    let peer = FakePeer::offering("notes.txt", b"notes");
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_date_subfolders(true));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let dated_dir = ctx
+        .paths()
+        .default_download_dir
+        .join("wyrmhole")
+        .join(&today);
+    assert_eq!(
+        std::fs::read(dated_dir.join("notes.txt")).unwrap(),
+        b"notes"
+    );
+    assert_eq!(
+        files_json::init_received_files(&ctx)[0].download_url,
+        dated_dir
+    );
+}
+
+#[tokio::test]
+async fn receive_can_be_sent_elsewhere_when_accepting() {
+    let peer = FakePeer::offering("notes.txt", b"notes");
+    let (ctx, _events, dir) = test_context(Arc::new(peer), |s| s.set_date_subfolders(true));
+    let elsewhere = dir.path().join("elsewhere");
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(
+        offer.id,
+        ctx.clone(),
+        Some(elsewhere.to_string_lossy().into_owned()),
+        false,
+        false,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        std::fs::read(elsewhere.join("notes.txt")).unwrap(),
+        b"notes"
+    );
+    assert_eq!(
+        files_json::init_received_files(&ctx)[0].download_url,
+        elsewhere
+    );
+    // The setting itself is untouched.
+    assert_eq!(
+        ctx.settings().lock().await.get_download_directory(),
+        &ctx.paths().default_download_dir
+    );
+}
+
+#[tokio::test]
+async fn receive_applies_file_name_template() {
+    let peer = FakePeer::offering("archive.tar.gz", b"not really a tarball");
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| {
+        s.set_received_file_name_template("{date}_{name}".to_string())
+    });
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let expected = format!("{}_archive.tar.gz", today);
+    assert!(ctx.paths().default_download_dir.join(expected).exists());
+}
+
+fn window(start: &str, end: &str, limit_kib: Option<u64>) -> RateWindow {
+    RateWindow {
+        start: start.to_string(),
+        end: end.to_string(),
+        limit_kib,
+    }
+}
+
+#[tokio::test]
+async fn rate_limit_schedule_picks_the_window_in_force() {
+    let limit = RateLimit {
+        default_kib: Some(100),
+        schedule: vec![
+            window("09:00", "17:00", Some(5120)),
+            window("22:00", "06:00", None),
+        ],
+    };
+    let at = |time: &str| limit.kib_at(chrono::NaiveTime::parse_from_str(time, "%H:%M").unwrap());
+    assert_eq!(at("08:59"), Some(100));
+    assert_eq!(at("09:00"), Some(5120));
+    assert_eq!(at("17:00"), Some(100));
+    // Windows past midnight wrap around.
+    assert_eq!(at("23:30"), None);
+    assert_eq!(at("03:00"), None);
+    assert!(!limit.is_unlimited());
+    assert!(RateLimit::default().is_unlimited());
+
+    let peer = FakePeer::offering("photo.jpg", b"jpeg bytes");
+    let (ctx, _events, _dir) = fake_context(peer);
+    for bad in [
+        window("9am", "17:00", None),
+        window("10:00", "10:00", Some(1)),
+    ] {
+        assert!(
+            settings::set_rate_limit_schedule(&ctx, vec![bad])
+                .await
+                .is_err()
+        );
+    }
+    // A schedule capping every hour still lets a download through.
+    settings::set_rate_limit_schedule(&ctx, vec![window("00:00", "23:59", Some(64))])
+        .await
+        .unwrap();
+    // Sends follow it too.
+    let noon = chrono::NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+    let upload_limit = ctx.settings().lock().await.upload_rate_limit();
+    assert_eq!(upload_limit.kib_at(noon), Some(64));
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    assert!(ctx.paths().default_download_dir.join("photo.jpg").exists());
+}
+
+#[tokio::test]
+async fn uploads_are_paced_to_their_limit() {
+    // 32 KiB at 64 KiB/s: the last slice can't go before about 0.4s
+    let data = vec![7u8; 32 * 1024];
+    let mut reader = ThrottledReader::new(futures::io::Cursor::new(data.clone()), 64 * 1024);
+    let started = std::time::Instant::now();
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).await.unwrap();
+    assert_eq!(read, data);
+    assert!(started.elapsed() >= Duration::from_millis(300));
+
+    let (ctx, _events, _dir) = fake_context(FakePeer::new(Outcome::Complete));
+    settings::set_upload_rate_limit_kib(&ctx, Some(0))
+        .await
+        .unwrap();
+    assert_eq!(
+        settings::get_upload_rate_limit_kib(&ctx).await.unwrap(),
+        None
+    );
+    settings::set_upload_rate_limit_kib(&ctx, Some(512))
+        .await
+        .unwrap();
+    // The download schedule doesn't apply to sends
+    settings::set_rate_limit_schedule(&ctx, vec![window("00:00", "23:59", Some(1))])
+        .await
+        .unwrap();
+    let limit = ctx.settings().lock().await.upload_rate_limit();
+    assert_eq!(limit.default_kib, Some(512));
+    assert!(limit.schedule.is_empty());
+}
+
+#[tokio::test]
+async fn receive_hooks_move_matching_files() {
+    let invoices = tempfile::tempdir().unwrap();
+    let rule = ReceiveHook {
+        pattern: "*.txt, *.PDF".to_string(),
+        action: HookAction::MoveTo,
+        target: invoices.path().to_string_lossy().into_owned(),
+    };
+    let peer = FakePeer::offering("invoice-42.pdf", b"%PDF");
+    let (ctx, events, _dir) = test_context(Arc::new(peer), |s| s.set_receive_hooks(vec![rule]));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    let moved = invoices.path().join("invoice-42.pdf");
+    assert!(moved.exists());
+    assert!(
+        !ctx.paths()
+            .default_download_dir
+            .join("invoice-42.pdf")
+            .exists()
+    );
+    let complete = events.named(events::DOWNLOAD_COMPLETE);
+    assert_eq!(complete[0]["path"], moved.to_string_lossy().as_ref());
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received[0].download_url, invoices.path());
+}
+
+// Holds back executables instead of recording them.
+struct RefuseExecutables;
+
+impl ReceiveStep for RefuseExecutables {
+    fn run<'a>(
+        &'a self,
+        _ctx: &'a AppContext,
+        download: Download,
+    ) -> BoxFuture<'a, Result<Flow, String>> {
+        async move {
+            if download.file_name.ends_with(".exe") {
+                return Err(format!("{} is an executable", download.file_name));
+            }
+            Ok(Flow::Next(download))
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test]
+async fn injected_receive_steps_run_before_recording() {
+    let peer = FakePeer::offering("setup.exe", b"MZ");
+    let (ctx, events, _dir) = fake_context(peer);
+    let ctx = ctx.with_receive_step(Arc::new(RefuseExecutables));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    let result = files::receiving_file_accept(offer.id, ctx.clone(), None, false, true).await;
+
+    assert_eq!(result.unwrap_err(), "setup.exe is an executable");
+    let errors = events.named(events::DOWNLOAD_ERROR);
+    assert_eq!(errors[0]["error"], "setup.exe is an executable");
+    assert!(events.named(events::DOWNLOAD_COMPLETE).is_empty());
+    // The file stays put; only the failure is in the history.
+    assert!(ctx.paths().default_download_dir.join("setup.exe").exists());
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received.len(), 1);
+    assert_ne!(received[0].status, files_json::TransferStatus::Completed);
+}
+
+#[test]
+fn receive_hook_patterns_and_command_placeholders() {
+    assert!(hooks::glob_matches("*.pdf", "Invoice.PDF"));
+    assert!(hooks::glob_matches("scan-??.*", "scan-01.png"));
+    assert!(!hooks::glob_matches("*.pdf", "notes.pdf.txt"));
+
+    // A hostile file name stays a single argument; nothing goes through a shell.
+    let path = Path::new("/downloads/a; rm -rf ~.jpg");
+    assert_eq!(
+        hooks::command_args(r#"convert "{path}" "{dir}/small-{name}""#, path),
+        vec![
+            "convert".to_string(),
+            "/downloads/a; rm -rf ~.jpg".to_string(),
+            "/downloads/small-a; rm -rf ~.jpg".to_string(),
+        ]
+    );
+    // Nor can a name pass as an option where it starts an argument.
+    assert_eq!(
+        hooks::command_args("mv {name} {dir}/done", Path::new("/downloads/-rf")),
+        vec![
+            "mv".to_string(),
+            "./-rf".to_string(),
+            "/downloads/done".to_string(),
+        ]
+    );
+    // Placeholders inside a name or folder are left as they are.
+    assert_eq!(
+        hooks::command_args(
+            "cp {path} {dir}/copy-{name}",
+            Path::new("/downloads/{path}/{dir}x")
+        ),
+        vec![
+            "cp".to_string(),
+            "/downloads/{path}/{dir}x".to_string(),
+            "/downloads/{path}/copy-{dir}x".to_string(),
+        ]
+    );
+}
+
+// A gzipped tarball holding `files` (path, contents).
+fn tarball(files: &[(&str, &str)]) -> Vec<u8> {
+    let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    let mut tar = tar::Builder::new(gz);
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, path, contents.as_bytes())
+            .unwrap();
+    }
+    tar.into_inner().unwrap().finish().unwrap()
+}
+
+#[tokio::test]
+async fn extract_does_not_merge_into_existing_folder() {
+    let data = tarball(&[("photos/beach.txt", "new beach")]);
+    let peer = FakePeer::offering("photos.tar.gz", &data);
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    write_file(&download_dir.join("photos/beach.txt"), b"old beach");
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        std::fs::read(download_dir.join("photos/beach.txt")).unwrap(),
+        b"old beach"
+    );
+    assert_eq!(
+        std::fs::read(download_dir.join("photos(1)/beach.txt")).unwrap(),
+        b"new beach"
+    );
+}
+
+#[tokio::test]
+async fn gz_files_unpack_as_folders_or_single_files() {
+    let data = tarball(&[("photos/beach.txt", "beach")]);
+    let peer = FakePeer::offering("photos.gz", &data);
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read(download_dir.join("photos/beach.txt")).unwrap(),
+        b"beach"
+    );
+
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    std::io::Write::write_all(&mut gz, b"plain log lines").unwrap();
+    let peer = FakePeer::offering("server.log.gz", &gz.finish().unwrap());
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read(download_dir.join("server.log")).unwrap(),
+        b"plain log lines"
+    );
+    assert!(!download_dir.join("server.log.gz").exists());
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received[0].file_name, "server");
+    assert_eq!(received[0].file_extension, "log");
+}
+
+#[tokio::test]
+async fn gz_files_ignore_the_name_in_their_header() {
+    // A gzipped program whose header names it setup.exe is flagged, not unpacked.
+    let mut gz = flate2::GzBuilder::new()
+        .filename("setup.exe")
+        .write(Vec::new(), flate2::Compression::fast());
+    std::io::Write::write_all(&mut gz, b"MZ\x90\x00").unwrap();
+    let peer = FakePeer::offering("payload.gz", &gz.finish().unwrap());
+    let (ctx, events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    assert!(download_dir.join("payload.gz").exists());
+    assert!(!download_dir.join("setup.exe").exists());
+    let flagged = events.named(events::CONTENT_FLAGGED);
+    assert_eq!(flagged[0]["warning"], "executable");
+
+    // Anything else unpacks under the offer's name, whatever the header says.
+    let mut gz = flate2::GzBuilder::new()
+        .filename("run.sh")
+        .write(Vec::new(), flate2::Compression::fast());
+    std::io::Write::write_all(&mut gz, b"rm -rf ~").unwrap();
+    let peer = FakePeer::offering("notes.txt.gz", &gz.finish().unwrap());
+    let (ctx, events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read(download_dir.join("notes.txt")).unwrap(),
+        b"rm -rf ~"
+    );
+    assert!(!download_dir.join("run.sh").exists());
+    assert!(events.named(events::CONTENT_FLAGGED).is_empty());
+
+    // A gzipped file that unpacks to a script's name is flagged by that name.
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    std::io::Write::write_all(&mut gz, b"echo hi").unwrap();
+    let peer = FakePeer::offering("install.sh.gz", &gz.finish().unwrap());
+    let (ctx, events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    assert!(!download_dir.join("install.sh").exists());
+    assert_eq!(
+        events.named(events::CONTENT_FLAGGED)[0]["warning"],
+        "script"
+    );
+}
+
+#[tokio::test]
+async fn received_tarballs_keep_their_double_extension() {
+    let data = tarball(&[("photos/beach.txt", "beach")]);
+    let peer = FakePeer::offering("photos.tar.gz", &data);
+    let (ctx, _events, _dir) = fake_context(peer);
+    let download_dir = ctx.paths().default_download_dir.clone();
+    write_file(&download_dir.join("photos.tar.gz"), b"an earlier copy");
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    assert!(download_dir.join("photos(1).tar.gz").exists());
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received[0].file_name, "photos(1)");
+    assert_eq!(received[0].file_extension, "tar.gz");
+}
+
+// A zip holding `files`, each written as (path, contents), AES-encrypted with `password`.
+fn zipped(files: &[(&str, &str)], password: Option<&str>) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (path, contents) in files {
+        let mut options = zip::write::SimpleFileOptions::default();
+        if let Some(password) = password {
+            options = options.with_aes_encryption(zip::AesMode::Aes256, password);
+        }
+        zip.start_file(*path, options).unwrap();
+        std::io::Write::write_all(&mut zip, contents.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+#[tokio::test]
+async fn zips_extract_unless_password_protected() {
+    let data = zipped(&[("report/summary.txt", "all good")], None);
+    let peer = FakePeer::offering("report.zip", &data);
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read(download_dir.join("report/summary.txt")).unwrap(),
+        b"all good"
+    );
+    assert!(!download_dir.join("report.zip").exists());
+
+    let data = zipped(&[("secret/plans.txt", "hush")], Some("hunter2"));
+    let peer = FakePeer::offering("secret.zip", &data);
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    assert!(download_dir.join("secret.zip").exists());
+    assert!(!download_dir.join("secret").exists());
+}
+
+// Stands in for a host's 7z support: "extracts" any archive into one marker file.
+struct MarkerExtractor;
+
+impl Extractor for MarkerExtractor {
+    fn extract(
+        &self,
+        _archive: &Path,
+        output_dir: &Path,
+        _merge: bool,
+    ) -> Result<Vec<(String, u64)>, String> {
+        std::fs::write(output_dir.join("unpacked.txt"), b"7z").map_err(|e| e.to_string())?;
+        Ok(vec![("unpacked.txt".to_string(), 2)])
+    }
+}
+
+#[tokio::test]
+async fn hosts_can_add_extractors_for_other_formats() {
+    let peer = FakePeer::offering("bundle.bin", b"7z\xbc\xaf\x27\x1c and the rest");
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+    let ctx = ctx.with_extractor(ArchiveFormat::SevenZip, Arc::new(MarkerExtractor));
+    let download_dir = ctx.paths().default_download_dir.clone();
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    assert!(download_dir.join("unpacked.txt").exists());
+    assert!(!download_dir.join("bundle.bin").exists());
+}
+
+#[tokio::test]
+async fn extracted_files_are_recorded_in_one_batch() {
+    let data = tarball(&[
+        ("docs/a.txt", "a"),
+        ("docs/b.txt", "b"),
+        ("docs/c.txt", "c"),
+    ]);
+    let peer = FakePeer::offering("docs.tar.gz", &data);
+    let (ctx, events, _dir) = test_context(Arc::new(peer), |s| s.set_auto_extract_tarballs(true));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    let batches = events.named(events::RECEIVED_FILES_ADDED);
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0]["files"].as_array().unwrap().len(), 3);
+    assert!(events.named(events::RECEIVED_FILE_ADDED).is_empty());
+    assert_eq!(files_json::init_received_files(&ctx).len(), 3);
+}
+
+// The paths inside a gzipped tarball.
+fn tarball_entries(data: &[u8]) -> Vec<String> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data));
+    let mut entries: Vec<String> = archive
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            entry
+                .unwrap()
+                .path()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    entries.sort();
+    entries
+}
+
+#[tokio::test]
+async fn folder_sync_sends_only_changed_files() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, events, dir) = fake_context(peer);
+    let folder = dir.path().join("notes");
+    write_file(&folder.join("a.txt"), b"a");
+    write_file(&folder.join("sub/b.txt"), b"b");
+    let folder_path = folder.to_str().unwrap().to_string();
+
+    sync::send_changes_call(ctx.clone(), folder_path.clone(), unique_id())
+        .await
+        .unwrap();
+    assert_eq!(
+        tarball_entries(&received.lock().unwrap()),
+        ["notes/a.txt", "notes/sub/b.txt"]
+    );
+
+    write_file(&folder.join("sub/b.txt"), b"b, edited");
+    write_file(&folder.join("c.txt"), b"c");
+    sync::send_changes_call(ctx.clone(), folder_path.clone(), unique_id())
+        .await
+        .unwrap();
+    assert_eq!(
+        tarball_entries(&received.lock().unwrap()),
+        ["notes/c.txt", "notes/sub/b.txt"]
+    );
+
+    let id = unique_id();
+    assert!(
+        sync::send_changes_call(ctx.clone(), folder_path.clone(), id.clone())
+            .await
+            .is_err()
+    );
+    let errors = events.named(events::SEND_ERROR);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["id"], id);
+
+    sync::reset_sync(ctx.clone(), folder_path.clone())
+        .await
+        .unwrap();
+    sync::send_changes_call(ctx, folder_path, unique_id())
+        .await
+        .unwrap();
+    assert_eq!(tarball_entries(&received.lock().unwrap()).len(), 3);
+}
+
+#[tokio::test]
+async fn folder_sync_updates_merge_into_the_existing_folder() {
+    let data = tarball(&[("notes/b.txt", "new b")]);
+    let peer = FakePeer::offering("notes.sync.tar.gz", &data);
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_merge_folder_updates(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    write_file(&download_dir.join("notes/a.txt"), b"a");
+    write_file(&download_dir.join("notes/b.txt"), b"old b");
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        std::fs::read(download_dir.join("notes/a.txt")).unwrap(),
+        b"a"
+    );
+    assert_eq!(
+        std::fs::read(download_dir.join("notes/b.txt")).unwrap(),
+        b"new b"
+    );
+    assert!(!download_dir.join("notes(1)").exists());
+}
+
+// Two and a half blocks of patterned bytes, so each block hashes differently.
+fn disk_image() -> Vec<u8> {
+    let len = delta::BLOCK_SIZE as usize * 5 / 2;
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+fn delta_patches_only_the_changed_blocks() {
+    let dir = TempDir::new().unwrap();
+    let (old_path, new_path) = (dir.path().join("old.img"), dir.path().join("new.img"));
+    let old = disk_image();
+    let mut new = old.clone();
+    new[delta::BLOCK_SIZE as usize + 7] ^= 0xff;
+    new.extend_from_slice(b"appended");
+    write_file(&old_path, &old);
+    write_file(&new_path, &new);
+
+    let previous = Signature::build(&old_path).unwrap();
+    let current = Signature::build(&new_path).unwrap();
+    assert_eq!(current.changed_blocks(&previous), [1, 2]);
+
+    let delta_path = dir.path().join("new.img.wyrmdelta");
+    let size = delta::create_delta(&new_path, &previous, &current, &delta_path).unwrap();
+    assert!(size < new.len() as u64);
+    delta::apply_delta(&delta_path, &old_path).unwrap();
+    assert_eq!(std::fs::read(&old_path).unwrap(), new);
+
+    // The base is now the new file, which the delta wasn't made from.
+    assert!(delta::apply_delta(&delta_path, &old_path).is_err());
+    assert_eq!(std::fs::read(&old_path).unwrap(), new);
+}
+
+#[tokio::test]
+async fn file_changes_are_sent_as_a_delta_after_the_first_send() {
+    let peer = FakePeer::new(Outcome::Complete);
+    let received = peer.received.clone();
+    let (ctx, _events, dir) = fake_context(peer);
+    let path = dir.path().join("disk.img");
+    let mut data = disk_image();
+    write_file(&path, &data);
+    let path_str = path.to_str().unwrap().to_string();
+
+    sync::send_changes_call(ctx.clone(), path_str.clone(), unique_id())
+        .await
+        .unwrap();
+    assert_eq!(*received.lock().unwrap(), data);
+
+    data[0] ^= 0xff;
+    write_file(&path, &data);
+    sync::send_changes_call(ctx.clone(), path_str.clone(), unique_id())
+        .await
+        .unwrap();
+    let sent = received.lock().unwrap().clone();
+    assert!(sent.starts_with(b"{\"base_hash\""));
+    assert!(sent.len() < delta::BLOCK_SIZE as usize * 2);
+
+    // Unchanged since the delta: nothing to send.
+    assert!(
+        sync::send_changes_call(ctx.clone(), path_str, unique_id())
+            .await
+            .is_err()
+    );
+    assert_eq!(files_json::init_sent_files(&ctx).len(), 2);
+}
+
+#[tokio::test]
+async fn received_deltas_patch_the_earlier_copy() {
+    let dir = TempDir::new().unwrap();
+    let (old_path, new_path) = (dir.path().join("old.img"), dir.path().join("new.img"));
+    let old = disk_image();
+    let mut new = old.clone();
+    new[3] = 0;
+    write_file(&old_path, &old);
+    write_file(&new_path, &new);
+    let delta_path = dir.path().join("delta");
+    delta::create_delta(
+        &new_path,
+        &Signature::build(&old_path).unwrap(),
+        &Signature::build(&new_path).unwrap(),
+        &delta_path,
+    )
+    .unwrap();
+
+    let peer = FakePeer::offering("disk.img.wyrmdelta", &std::fs::read(&delta_path).unwrap());
+    let (ctx, _events, _dir) = test_context(Arc::new(peer), |s| s.set_apply_file_deltas(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    write_file(&download_dir.join("disk.img"), &old);
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(download_dir.join("disk.img")).unwrap(), new);
+    assert!(!download_dir.join("disk.img.wyrmdelta").exists());
+    assert_eq!(files_json::init_received_files(&ctx)[0].file_name, "disk");
+}
+
+#[tokio::test]
+async fn deltas_that_make_a_program_are_flagged() {
+    let dir = TempDir::new().unwrap();
+    let (old_path, new_path) = (dir.path().join("old.img"), dir.path().join("new.img"));
+    let old = disk_image();
+    let mut new = old.clone();
+    new[..4].copy_from_slice(b"\x7fELF");
+    write_file(&old_path, &old);
+    write_file(&new_path, &new);
+    let delta_path = dir.path().join("delta");
+    delta::create_delta(
+        &new_path,
+        &Signature::build(&old_path).unwrap(),
+        &Signature::build(&new_path).unwrap(),
+        &delta_path,
+    )
+    .unwrap();
+
+    let peer = FakePeer::offering("disk.img.wyrmdelta", &std::fs::read(&delta_path).unwrap());
+    let (ctx, events, _dir) = test_context(Arc::new(peer), |s| s.set_apply_file_deltas(true));
+    let download_dir = ctx.paths().default_download_dir.clone();
+    write_file(&download_dir.join("disk.img"), &old);
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    let flagged = events.named(events::CONTENT_FLAGGED);
+    assert_eq!(flagged[0]["warning"], "executable");
+    assert_eq!(flagged[0]["file_name"], "disk.img");
+}
+
+#[tokio::test]
+async fn finished_transfers_can_be_replied_to() {
+    let peer = FakePeer::offering("report.pdf", b"first");
+    let (ctx, _events, dir) = fake_context(peer);
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id.clone(), ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+
+    let path = dir.path().join("answer.txt");
+    write_file(&path, b"thanks");
+    files::send_reply_call(ctx.clone(), &offer.id, path.to_str().unwrap(), unique_id())
+        .await
+        .unwrap();
+    // Either side can instead wait for the other to reply.
+    let reply = files::await_reply_call(ctx.clone(), &offer.id, unique_id())
+        .await
+        .unwrap();
+    assert_eq!(reply.file_name, "report.pdf");
+
+    assert!(
+        files::send_reply_call(ctx, "never-sent", path.to_str().unwrap(), unique_id())
+            .await
+            .is_err()
+    );
+    // The reply code has its own nameplate, and only the two sides know its secret.
+    let reply_code = files::reply_code("session key");
+    assert!(reply_code.parse::<Code>().is_ok());
+    assert_ne!(reply_code, files::reply_code("another session"));
+    assert_ne!(reply_code, parts::part_code("session key", 2));
+}
+
+#[tokio::test]
+async fn receive_deny_drops_the_offer() {
+    let (ctx, _events, _dir) = fake_context(FakePeer::offering("photo.jpg", b"jpeg bytes"));
+
+    let offer = files::request_file_call(
+        ctx.clone(),
+        "wormhole receive 7-guitarist-revenge",
+        unique_id(),
+    )
+    .await
+    .unwrap();
+    files::receiving_file_deny(offer.id.clone(), ctx.clone(), None)
+        .await
+        .unwrap();
+
+    assert!(
+        files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+            .await
+            .is_err()
+    );
+    // Only the rejection is recorded; the second accept never started.
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].status, files_json::TransferStatus::Rejected);
+}
+
+#[tokio::test]
+async fn pending_offers_can_be_answered_all_at_once() {
+    let (ctx, _events, _dir) = fake_context(FakePeer::offering("photo.jpg", b"jpeg bytes"));
+    for _ in 0..3 {
+        files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+            .await
+            .unwrap();
+    }
+    let summary = files::accept_all_pending(ctx.clone(), None).await.unwrap();
+    assert_eq!(summary, "Accepted 3 offer(s)");
+    assert_eq!(files_json::init_received_files(&ctx).len(), 3);
+    assert!(ctx.transfers().snapshot().await.offers.is_empty());
+    assert!(files::accept_all_pending(ctx.clone(), None).await.is_err());
+
+    // Offers that need confirming are left for the user, and denied with the rest
+    let (ctx, _events, _dir) = fake_context(FakePeer::offering("setup.exe", b"MZ"));
+    files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    let summary = files::accept_all_pending(ctx.clone(), None).await.unwrap();
+    assert_eq!(summary, "Accepted 0 offer(s), 1 left to confirm");
+    assert_eq!(ctx.transfers().snapshot().await.offers.len(), 1);
+
+    let summary = files::deny_all_pending(ctx.clone(), Some("Not expecting these".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(summary, "Denied 1 offer(s)");
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received[0].status, files_json::TransferStatus::Rejected);
+    assert_eq!(received[0].reason.as_deref(), Some("Not expecting these"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn unanswered_offers_expire() {
+    let (ctx, events, _dir) = test_context(
+        Arc::new(FakePeer::offering("photo.jpg", b"jpeg bytes")),
+        |s| s.set_offer_expiry_mins(1),
+    );
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_secs(61)).await;
+
+    assert!(ctx.transfers().snapshot().await.offers.is_empty());
+    let expired = events.named(events::OFFER_EXPIRED);
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0]["id"], offer.id.as_str());
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received[0].status, files_json::TransferStatus::Rejected);
+    assert!(
+        files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn received_files_can_be_sent_back() {
+    let (ctx, events, _dir) = fake_context(FakePeer::offering("draft.txt", b"draft"));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    let received = files_json::init_received_files(&ctx);
+    assert!(!received[0].id.is_empty());
+
+    let send_id = unique_id();
+    files::send_back(ctx.clone(), &received[0].id, send_id.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        events.send_statuses(&send_id).last().map(String::as_str),
+        Some("finalizing")
+    );
+    let sent = files_json::init_sent_files(&ctx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].file_paths, vec![received[0].saved_path()]);
+
+    assert!(
+        files::send_back(ctx.clone(), "no-such-entry", unique_id())
+            .await
+            .is_err()
+    );
+    std::fs::remove_file(received[0].saved_path()).unwrap();
+    assert!(
+        files::send_back(ctx, &received[0].id, unique_id())
+            .await
+            .unwrap_err()
+            .contains("no longer there")
+    );
+}
+
+#[test]
+fn received_entries_from_before_ids_get_one_that_sticks() {
+    let (ctx, _events, _dir) = fake_context(FakePeer::new(Outcome::Complete));
+    write_file(
+        &settings::get_received_files_path(&ctx.history_dir()),
+        br#"[{"file_name":"old","file_size":3,"file_extension":"txt","download_url":"/tmp",
+        "download_time":"2024-01-01T00:00:00+00:00","connection_type":"direct",
+        "peer_address":"192.0.2.7:4001"}]"#,
+    );
+
+    let first = files_json::init_received_files(&ctx);
+    assert_eq!(first.len(), 1);
+    assert!(!first[0].id.is_empty());
+    assert_eq!(files_json::init_received_files(&ctx)[0].id, first[0].id);
+}
+
+#[tokio::test]
+async fn denied_offers_are_recorded_with_their_reason() {
+    let (ctx, events, _dir) = fake_context(FakePeer::offering("photo.jpg", b"jpeg bytes"));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    files::receiving_file_deny(
+        offer.id,
+        ctx.clone(),
+        Some("  not expecting this ".to_string()),
+    )
+    .await
+    .unwrap();
+
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].file_name, "photo");
+    assert_eq!(received[0].file_extension, "jpg");
+    assert_eq!(received[0].file_size, 10);
+    assert_eq!(received[0].status, files_json::TransferStatus::Rejected);
+    assert_eq!(received[0].reason.as_deref(), Some("not expecting this"));
+    assert_eq!(events.named(events::RECEIVED_FILE_ADDED).len(), 1);
+    // Nothing was saved, so nothing there may be opened as a received file.
+    assert!(
+        files_json::check_received_location(&ctx, "/etc/passwd")
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn failed_receives_are_recorded() {
+    let peer = FakePeer {
+        outcome: Outcome::Fail,
+        ..FakePeer::offering("photo.jpg", b"jpeg bytes")
+    };
+    let (ctx, _events, _dir) = fake_context(peer);
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+    assert!(
+        files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+            .await
+            .is_err()
+    );
+
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].status, files_json::TransferStatus::Failed);
+    assert!(
+        received[0]
+            .reason
+            .as_deref()
+            .unwrap()
+            .contains("peer went away")
+    );
+}
+
+#[tokio::test]
+async fn text_offer_is_previewed_and_saved_on_accept() {
+    let (ctx, events, _dir) = fake_context(FakePeer::texting("meet at 5"));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+
+    assert_eq!(offer.text.as_deref(), Some("meet at 5"));
+    let previews = events.named(events::TEXT_OFFER);
+    assert_eq!(previews[0]["text"], "meet at 5");
+    assert!(previews[0]["saved_path"].is_null());
+    assert!(files_json::init_received_files(&ctx).is_empty());
+    assert!(files::has_active_transfers(&ctx).await);
+
+    files::receiving_file_accept(offer.id, ctx.clone(), None, false, false)
+        .await
+        .unwrap();
+    assert!(!files::has_active_transfers(&ctx).await);
+
+    let received = files_json::init_received_files(&ctx);
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].file_extension, "txt");
+    let saved = received[0]
+        .download_url
+        .join(format!("{}.txt", received[0].file_name));
+    assert_eq!(std::fs::read_to_string(saved).unwrap(), "meet at 5");
+}
+
+#[tokio::test]
+async fn text_offer_is_saved_right_away_when_enabled() {
+    let peer = FakePeer::texting("meet at 5");
+    let (ctx, events, _dir) = test_context(Arc::new(peer), |s| s.set_save_text_offers(true));
+
+    let offer = files::request_file_call(ctx.clone(), "7-guitarist-revenge", unique_id())
+        .await
+        .unwrap();
+
+    let saved_path = events.named(events::TEXT_OFFER)[0]["saved_path"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(std::fs::read_to_string(saved_path).unwrap(), "meet at 5");
+    assert_eq!(files_json::init_received_files(&ctx).len(), 1);
+    // Nothing is left to accept.
+    assert!(
+        files::receiving_file_accept(offer.id, ctx, None, false, false)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn receive_without_offer_is_an_error() {
+    let (ctx, _events, _dir) = fake_context(FakePeer::new(Outcome::Complete));
+
+    let result = files::request_file_call(ctx, "7-guitarist-revenge", unique_id()).await;
+
+    assert!(result.unwrap_err().contains("No file was offered"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_history_writes_keep_every_entry() {
+    let (ctx, _events, _dir) = fake_context(FakePeer::new(Outcome::Complete));
+
+    // Each writer stands in for another instance appending to the same history.
+    let writers: Vec<_> = (0..8)
+        .map(|writer| {
+            let ctx = ctx.clone();
+            tokio::spawn(async move {
+                for entry in 0..5 {
+                    files_json::add_sent_file(
+                        &ctx,
+                        files_json::SentFile {
+                            file_name: format!("file-{}-{}", writer, entry),
+                            file_size: 1,
+                            file_extension: "txt".to_string(),
+                            file_paths: Vec::new(),
+                            send_time: chrono::Local::now(),
+                            connection_code: "7-guitarist-revenge".to_string(),
+                            connection_type: "direct".to_string(),
+                            peer_address: peer_address(),
+                            status: files_json::TransferStatus::Completed,
+                            reason: None,
+                            id: files_json::new_entry_id(),
+                            snapshots: Vec::new(),
+                            checksum: None,
+                        },
+                    )
+                    .await
+                    .unwrap();
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.await.unwrap();
+    }
+
+    assert_eq!(files_json::init_sent_files(&ctx).len(), 40);
+}
+
+#[tokio::test]
+async fn history_export_only_filters_by_date_within_a_range() {
+    let (ctx, _events, dir) = fake_context(FakePeer::new(Outcome::Complete));
+    let history = serde_json::json!([
+        { "file_name": "old", "send_time": "2020-01-01T12:00:00+00:00" },
+        { "file_name": "new", "send_time": "2024-06-01T12:00:00+00:00" },
+        { "file_name": "undated" },
+    ]);
+    std::fs::write(
+        settings::get_sent_files_path(&ctx.history_dir()),
+        history.to_string(),
+    )
+    .unwrap();
+    let export = |range: settings::ExportRange| {
+        let ctx = ctx.clone();
+        let file_path = dir.path().join("export.json");
+        async move {
+            settings::export_sent_files_json(
+                &ctx,
+                file_path.to_string_lossy().into_owned(),
+                range,
+                None,
+                unique_id(),
+            )
+            .await
+            .unwrap();
+            let exported: Vec<serde_json::Value> =
+                serde_json::from_str(&std::fs::read_to_string(file_path).unwrap()).unwrap();
+            exported
+                .iter()
+                .map(|entry| entry["file_name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(
+        export(settings::ExportRange::default()).await,
+        ["old", "new", "undated"]
+    );
+    let since_2023 = settings::ExportRange {
+        from: chrono::NaiveDate::from_ymd_opt(2023, 1, 1),
+        to: None,
+    };
+    assert_eq!(export(since_2023).await, ["new"]);
+}
+
+#[test]
+fn history_report_escapes_names_and_totals_sizes() {
+    let sent = |name: &str, size| files_json::SentFile {
+        file_name: name.to_string(),
+        file_size: size,
+        file_extension: "txt".to_string(),
+        file_paths: Vec::new(),
+        send_time: chrono::Local::now(),
+        connection_code: "7-guitarist-revenge".to_string(),
+        connection_type: "direct".to_string(),
+        peer_address: peer_address(),
+        status: files_json::TransferStatus::Completed,
+        reason: None,
+        id: files_json::new_entry_id(),
+        snapshots: Vec::new(),
+        checksum: None,
+    };
+
+    let failed = files_json::SentFile {
+        status: files_json::TransferStatus::Failed,
+        reason: Some("peer went away".to_string()),
+        ..sent("b", 1024)
+    };
+
+    let html = report::render_history_html(
+        vec![sent("<b>notes</b>", 1024), sent("a", 1024), failed],
+        vec![],
+    );
+
+    assert!(html.contains("&lt;b&gt;notes&lt;/b&gt;.txt"));
+    assert!(!html.contains("<b>notes</b>"));
+    assert!(html.contains("<b>2</b>sent &middot; 2.0 KB"));
+    assert!(html.contains("Sent (3)"));
+    assert!(html.contains("failed: peer went away"));
+    assert!(html.contains("Received (0)"));
+}
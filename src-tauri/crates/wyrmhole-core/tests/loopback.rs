@@ -0,0 +1,162 @@
+// Loopback tests: real transfers between two engine contexts on this machine, through a
+// local mailbox and transit relay, to catch protocol regressions a fake peer can't.
+//
+// Start the servers with scripts/local-wormhole-servers.sh, then (in src-tauri/):
+//   cargo test -p wyrmhole-core --features integration-tests --test loopback
+//
+// WYRMHOLE_TEST_MAILBOX / WYRMHOLE_TEST_RELAY point the tests at other servers.
+#![cfg(feature = "integration-tests")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use wyrmhole_core::context::AppContext;
+use wyrmhole_core::wormhole::MagicWormhole;
+use wyrmhole_core::{files, files_json};
+
+mod common;
+use common::{RecordingEvents, test_context, unique_id, write_file};
+
+const DEFAULT_MAILBOX: &str = "ws://127.0.0.1:4000/v1";
+const DEFAULT_RELAY: &str = "tcp://127.0.0.1:4001";
+
+// Fail rather than hang if a server is missing or the protocol wedges.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn local_context(auto_extract: bool) -> (AppContext, Arc<RecordingEvents>, tempfile::TempDir) {
+    let mailbox = std::env::var("WYRMHOLE_TEST_MAILBOX").unwrap_or(DEFAULT_MAILBOX.to_string());
+    let relay = std::env::var("WYRMHOLE_TEST_RELAY").unwrap_or(DEFAULT_RELAY.to_string());
+    test_context(
+        Arc::new(MagicWormhole::with_rendezvous_url(mailbox)),
+        |settings| {
+            settings.set_relay_server_url(Some(relay));
+            settings.set_auto_extract_tarballs(auto_extract);
+        },
+    )
+}
+
+// Run a send on `sender` and receive it on `receiver`, returning the receive result.
+async fn transfer(
+    sender: &AppContext,
+    sender_events: &RecordingEvents,
+    receiver: &AppContext,
+    paths: Vec<String>,
+    folder_name: Option<String>,
+) -> String {
+    let send_id = unique_id();
+    let send = tokio::spawn({
+        let sender = sender.clone();
+        let send_id = send_id.clone();
+        async move {
+            match paths.as_slice() {
+                [single] if folder_name.is_none() => {
+                    files::send_file_call(sender, single, send_id).await
+                }
+                _ => {
+                    files::send_multiple_files_call(sender, paths, send_id, folder_name, None).await
+                }
+            }
+        }
+    });
+
+    let received = tokio::time::timeout(TRANSFER_TIMEOUT, async {
+        let code = sender_events.wait_for_code(&send_id).await;
+        let offer = files::request_file_call(receiver.clone(), &code, unique_id()).await?;
+        files::receiving_file_accept(offer.id, receiver.clone(), None, false, false).await
+    })
+    .await
+    .expect("receive timed out")
+    .expect("receive failed");
+
+    tokio::time::timeout(TRANSFER_TIMEOUT, send)
+        .await
+        .expect("send timed out")
+        .unwrap()
+        .expect("send failed");
+
+    received
+}
+
+#[tokio::test]
+async fn loopback_file() {
+    let (sender, sender_events, sender_dir) = local_context(false);
+    let (receiver, _receiver_events, _receiver_dir) = local_context(false);
+    let contents: Vec<u8> = (0..256 * 1024).map(|i| (i % 251) as u8).collect();
+    let path = sender_dir.path().join("payload.bin");
+    write_file(&path, &contents);
+
+    transfer(
+        &sender,
+        &sender_events,
+        &receiver,
+        vec![path.to_string_lossy().into_owned()],
+        None,
+    )
+    .await;
+
+    let download_dir = receiver.paths().default_download_dir.clone();
+    assert_eq!(
+        std::fs::read(download_dir.join("payload.bin")).unwrap(),
+        contents
+    );
+
+    let sent = files_json::init_sent_files(&sender);
+    let received = files_json::init_received_files(&receiver);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].file_size, contents.len() as u64);
+}
+
+#[tokio::test]
+async fn loopback_folder_is_extracted() {
+    let (sender, sender_events, sender_dir) = local_context(false);
+    let (receiver, _receiver_events, _receiver_dir) = local_context(true);
+    let folder = sender_dir.path().join("album");
+    write_file(&folder.join("cover.txt"), b"cover");
+    write_file(&folder.join("tracks/01.txt"), b"first track");
+
+    transfer(
+        &sender,
+        &sender_events,
+        &receiver,
+        vec![folder.to_string_lossy().into_owned()],
+        None,
+    )
+    .await;
+
+    let extracted = receiver.paths().default_download_dir.join("album");
+    assert_eq!(
+        std::fs::read(extracted.join("cover.txt")).unwrap(),
+        b"cover"
+    );
+    assert_eq!(
+        std::fs::read(extracted.join("tracks/01.txt")).unwrap(),
+        b"first track"
+    );
+    assert_eq!(files_json::init_received_files(&receiver).len(), 2);
+}
+
+#[tokio::test]
+async fn loopback_multiple_files_as_tarball() {
+    let (sender, sender_events, sender_dir) = local_context(false);
+    let (receiver, _receiver_events, _receiver_dir) = local_context(false);
+    let first = sender_dir.path().join("one.txt");
+    let second = sender_dir.path().join("two.txt");
+    write_file(&first, b"1");
+    write_file(&second, b"2");
+
+    transfer(
+        &sender,
+        &sender_events,
+        &receiver,
+        vec![
+            first.to_string_lossy().into_owned(),
+            second.to_string_lossy().into_owned(),
+        ],
+        Some("bundle".to_string()),
+    )
+    .await;
+
+    let download_dir = receiver.paths().default_download_dir.clone();
+    assert!(download_dir.join("bundle.tar.gz").exists());
+}
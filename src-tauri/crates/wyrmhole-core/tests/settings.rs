@@ -0,0 +1,149 @@
+// Settings tests: moving the history directory, relay presets, history exports. Kept apart from the engine tests because
+// moving history is refused while any transfer in the process is active.
+
+use std::sync::Arc;
+
+use wyrmhole_core::demo::DemoWormhole;
+use wyrmhole_core::events;
+use wyrmhole_core::settings::{self, ExportRange};
+
+mod common;
+use common::{test_context, write_file};
+
+#[tokio::test]
+async fn history_directory_moves_existing_history() {
+    let (ctx, _events, dir) = test_context(Arc::new(DemoWormhole::default()), |_| {});
+    let data_dir = ctx.paths().data_dir.clone();
+    write_file(&data_dir.join("received_files.json"), b"[]");
+    write_file(&data_dir.join("sent_files.json"), b"[]");
+    let new_dir = dir.path().join("history");
+    std::fs::create_dir_all(&new_dir).unwrap();
+
+    settings::set_history_directory(&ctx, Some(new_dir.to_string_lossy().into_owned()))
+        .await
+        .unwrap();
+
+    assert_eq!(ctx.history_dir(), new_dir);
+    assert!(new_dir.join("received_files.json").exists());
+    assert!(new_dir.join("sent_files.json").exists());
+    assert!(!data_dir.join("received_files.json").exists());
+
+    // Resetting moves it back to the data directory.
+    settings::set_history_directory(&ctx, None).await.unwrap();
+    assert_eq!(ctx.history_dir(), data_dir);
+    assert!(data_dir.join("sent_files.json").exists());
+}
+
+#[tokio::test]
+async fn history_directory_refuses_to_overwrite() {
+    let (ctx, _events, dir) = test_context(Arc::new(DemoWormhole::default()), |_| {});
+    let data_dir = ctx.paths().data_dir.clone();
+    write_file(&data_dir.join("received_files.json"), b"[]");
+    let new_dir = dir.path().join("history");
+    write_file(&new_dir.join("received_files.json"), b"[]");
+
+    let result =
+        settings::set_history_directory(&ctx, Some(new_dir.to_string_lossy().into_owned())).await;
+
+    assert!(result.unwrap_err().contains("already exists"));
+    assert_eq!(ctx.history_dir(), data_dir);
+    assert!(data_dir.join("received_files.json").exists());
+}
+
+#[tokio::test]
+async fn history_export_keeps_the_date_range_and_reports_progress() {
+    let (ctx, events, dir) = test_context(Arc::new(DemoWormhole::default()), |_| {});
+    write_file(
+        &ctx.history_dir().join("sent_files.json"),
+        br#"[
+            {"file_name": "march", "send_time": "2026-03-01T12:00:00+00:00"},
+            {"file_name": "june", "send_time": "2026-06-01T12:00:00+00:00"}
+        ]"#,
+    );
+    let out = dir.path().join("export.json");
+    let range = ExportRange {
+        from: "2026-05-01".parse().ok(),
+        to: "2026-07-01".parse().ok(),
+    };
+
+    settings::export_sent_files_json(
+        &ctx,
+        out.to_string_lossy().into_owned(),
+        range,
+        None,
+        "export-1".to_string(),
+    )
+    .await
+    .unwrap();
+
+    let exported: Vec<serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+    assert_eq!(exported.len(), 1);
+    assert_eq!(exported[0]["file_name"], "june");
+    let progress = events.named(events::EXPORT_PROGRESS);
+    let last = progress.last().unwrap();
+    assert_eq!(last["id"], "export-1");
+    assert_eq!(last["entries"], 1);
+    assert_eq!(last["done"], true);
+    assert!(last["bytes"].as_u64().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn history_export_can_be_encrypted() {
+    let (ctx, _events, dir) = test_context(Arc::new(DemoWormhole::default()), |_| {});
+    write_file(
+        &ctx.history_dir().join("received_files.json"),
+        br#"[{"file_name": "report", "peer_address": "203.0.113.7:4000", "download_time": "2026-06-01T12:00:00+00:00"}]"#,
+    );
+    let out = dir.path().join("export.json.age");
+
+    settings::export_received_files_json(
+        &ctx,
+        out.to_string_lossy().into_owned(),
+        ExportRange::default(),
+        Some("correct horse".to_string()),
+        "export-2".to_string(),
+    )
+    .await
+    .unwrap();
+
+    let encrypted = std::fs::read(&out).unwrap();
+    assert!(encrypted.starts_with(b"age-encryption.org/v1"));
+    assert!(!String::from_utf8_lossy(&encrypted).contains("203.0.113.7"));
+
+    let identity = age::scrypt::Identity::new(age::secrecy::SecretString::from(
+        "correct horse".to_string(),
+    ));
+    let decryptor = age::Decryptor::new(&encrypted[..]).unwrap();
+    let mut reader = decryptor
+        .decrypt(std::iter::once(&identity as &dyn age::Identity))
+        .unwrap();
+    let mut json = String::new();
+    std::io::Read::read_to_string(&mut reader, &mut json).unwrap();
+    assert!(json.contains("203.0.113.7:4000"));
+}
+
+#[tokio::test]
+async fn relay_presets_set_and_reset_the_relay() {
+    let (ctx, _events, _dir) = test_context(Arc::new(DemoWormhole::default()), |_| {});
+    let presets = settings::list_relay_presets();
+    assert_eq!(presets[0].url, None);
+    let custom = presets.iter().find(|p| p.url.is_some()).unwrap();
+
+    let url = settings::apply_relay_preset(&ctx, custom.id.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(url, custom.url);
+    assert_eq!(
+        settings::get_relay_server_url(&ctx).await.unwrap(),
+        custom.url
+    );
+    assert!(
+        settings::apply_relay_preset(&ctx, "nope".to_string())
+            .await
+            .is_err()
+    );
+    settings::reset_relay_server(&ctx).await.unwrap();
+    assert_eq!(settings::get_relay_server_url(&ctx).await.unwrap(), None);
+}
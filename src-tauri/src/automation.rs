@@ -0,0 +1,136 @@
+// `wyrmhole://` URLs, so OS automation tools (Shortcuts, AppleScript's `open location`,
+// PowerShell's `Start-Process`, xdg-open scripts) can start transfers:
+//
+//   wyrmhole://send?path=/home/me/report.pdf&path=/home/me/photos
+//   wyrmhole://receive?code=7-guitarist-revenge
+//
+// Links other wormhole clients hand out for a code are opened the same way as a receive:
+//
+//   wormhole-transfer:7-guitarist-revenge?version=0&rendezvous=ws%3A%2F%2F...
+//   wormhole:7-guitarist-revenge
+//
+// A link naming a rendezvous server other than magic-wormhole's default is refused: wyrmhole
+// only connects through the default one, where that code doesn't exist.
+//
+// The schemes are registered through the deep-link plugin; lib.rs hands each opened URL here.
+// A receive goes on as if the code had been typed into the receive box. A send only fills in
+// the selection: any web page can open these URLs, so nothing is sent until the user presses
+// Send, and the paths aren't approved for sending (see scope.rs) until then.
+
+use tauri::Url;
+
+pub const SCHEME: &str = "wyrmhole";
+
+/// Schemes of shared wormhole links: magic-wormhole's `wormhole-transfer:` URI format and
+/// the shorter `wormhole:`. Opening one receives with the code it carries.
+pub const TRANSFER_SCHEMES: &[&str] = &["wormhole-transfer", "wormhole"];
+
+// The only version of the `wormhole-transfer:` format there is.
+const TRANSFER_URI_VERSION: &str = "0";
+
+// magic-wormhole's public rendezvous server, the one receives connect through.
+const DEFAULT_RENDEZVOUS_URL: &str = "ws://relay.magic-wormhole.io:4000/v1";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutomationCommand {
+    /// Select these paths for the user to send as one batch.
+    Send(Vec<String>),
+    /// Connect with this code and show the offer.
+    Receive(String),
+}
+
+/// Parse a `wyrmhole://` URL, or a shared wormhole link, into the command it asks for.
+pub fn parse(url: &Url) -> Result<AutomationCommand, String> {
+    if TRANSFER_SCHEMES.contains(&url.scheme()) {
+        return parse_transfer_link(url).map(AutomationCommand::Receive);
+    }
+    if url.scheme() != SCHEME {
+        return Err(format!("Not a {}:// URL: {}", SCHEME, url));
+    }
+    let values = |key: &str| -> Vec<String> {
+        url.query_pairs()
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect()
+    };
+    match url.host_str().unwrap_or_default() {
+        "send" => {
+            let paths = values("path");
+            if paths.is_empty() {
+                return Err("wyrmhole://send needs at least one path=".to_string());
+            }
+            // Nothing but absolute paths: there's no working directory to resolve against.
+            if let Some(relative) = paths
+                .iter()
+                .find(|p| !std::path::Path::new(p).is_absolute())
+            {
+                return Err(format!("Send paths must be absolute: {}", relative));
+            }
+            Ok(AutomationCommand::Send(paths))
+        }
+        "receive" => values("code")
+            .into_iter()
+            .next()
+            .map(AutomationCommand::Receive)
+            .ok_or_else(|| "wyrmhole://receive needs a code=".to_string()),
+        other => Err(format!("Unknown wyrmhole:// command '{}'", other)),
+    }
+}
+
+// The code in a `wormhole-transfer:<code>` or `wormhole:<code>` link. Written as
+// `wormhole-transfer://<code>` too, so the code may come in as the host instead of the path.
+fn parse_transfer_link(url: &Url) -> Result<String, String> {
+    if let Some((_, version)) = url.query_pairs().find(|(k, _)| k == "version")
+        && version != TRANSFER_URI_VERSION
+    {
+        return Err(format!(
+            "Unsupported {}: link version {}",
+            url.scheme(),
+            version
+        ));
+    }
+    if let Some((_, rendezvous)) = url.query_pairs().find(|(k, _)| k == "rendezvous")
+        && rendezvous.trim_end_matches('/') != DEFAULT_RENDEZVOUS_URL
+    {
+        return Err(format!(
+            "This link's code is on the rendezvous server {}; wyrmhole can only receive through {}",
+            rendezvous, DEFAULT_RENDEZVOUS_URL
+        ));
+    }
+    let raw = format!("{}{}", url.host_str().unwrap_or_default(), url.path());
+    let code = percent_decode(raw.trim_matches('/')).trim().to_string();
+    // A code starts with its numeric nameplate: `7-guitarist-revenge`
+    let nameplate = code.split('-').next().unwrap_or_default();
+    if code.contains('-') && !nameplate.is_empty() && nameplate.bytes().all(|b| b.is_ascii_digit())
+    {
+        Ok(code)
+    } else {
+        Err(format!("No wormhole code in {}", url))
+    }
+}
+
+/// Undo `%XX` escapes; anything malformed is kept as it is, and bytes that don't form UTF-8
+/// become U+FFFD.
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -0,0 +1,274 @@
+// Companion command-line client. Runs the same transfer engine, settings and history as
+// the GUI (via `AppContext::standalone`), so anything sent or received here shows up in
+// the app's File History too.
+//
+//   wyrmhole-cli send <path>... [--name <folder name>] [--password <password>] [--compression <0-9>]
+//   wyrmhole-cli receive <code> [--yes] [--allow-large] [--allow-executables] [--output <folder>]
+//   wyrmhole-cli history [sent|received]
+//   wyrmhole-cli --help | --version
+
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use wyrmhole_core::context::{AppContext, EventSink};
+use wyrmhole_core::{archive, events, files, files_json, jobs, settings};
+
+const USAGE: &str = "Usage:
+  wyrmhole-cli send <path>... [--name <folder name>] [--password <password>] [--compression <0-9>]
+  wyrmhole-cli receive <code> [--yes] [--allow-large] [--allow-executables] [--output <folder>]
+  wyrmhole-cli history [sent|received]
+  wyrmhole-cli --help | --version";
+
+// Renders engine events as terminal output: the code on its own line, progress
+// redrawn in place on stderr, and errors as they arrive. Packaging jobs the user asks to
+// retry are passed back on `retries`, as the sink can't reach the context it's part of.
+struct TerminalEvents {
+    retries: mpsc::UnboundedSender<String>,
+}
+
+impl EventSink for TerminalEvents {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) -> Result<(), String> {
+        match event {
+            events::CONNECTION_CODE => {
+                if let Ok(events::ConnectionCode::Success { code, .. }) =
+                    serde_json::from_value(payload)
+                {
+                    println!("Wormhole code is: {}", code);
+                    eprintln!("On the other computer, run: wyrmhole-cli receive {}", code);
+                }
+            }
+            events::SEND_PROGRESS => {
+                if let Ok(p) = serde_json::from_value::<events::SendProgress>(payload) {
+                    eprint!("\r{} [{}] {}%   ", p.file_name, p.status, p.percentage);
+                }
+            }
+            events::DOWNLOAD_PROGRESS => {
+                if let Ok(p) = serde_json::from_value::<events::DownloadProgress>(payload) {
+                    eprint!("\r{} {}%   ", p.file_name, p.percentage);
+                }
+            }
+            events::TRANSFER_STALLED => {
+                if let Ok(stall) = serde_json::from_value::<events::TransferStalled>(payload) {
+                    eprintln!("\nNo data moved for {}s, still trying...", stall.idle_secs);
+                }
+            }
+            events::PACKAGING_JOB => {
+                if let Ok(job) = serde_json::from_value::<events::PackagingJob>(payload)
+                    && job.state == events::JobState::Failed
+                {
+                    eprintln!(
+                        "\nCouldn't package {}: {}",
+                        job.name,
+                        job.error.unwrap_or_default()
+                    );
+                    eprintln!(
+                        "The code still works. Fix it and press Enter to retry (Ctrl+C gives up)."
+                    );
+                    let retries = self.retries.clone();
+                    std::thread::spawn(move || {
+                        let mut line = String::new();
+                        if std::io::stdin().lock().read_line(&mut line).is_ok() {
+                            let _ = retries.send(job.job_id);
+                        }
+                    });
+                }
+            }
+            events::CONTENT_FLAGGED => {
+                if let Ok(flagged) = serde_json::from_value::<events::ContentFlagged>(payload) {
+                    eprintln!(
+                        "\n{} is {}; it was kept as saved, without extracting it",
+                        flagged.path.display(),
+                        flagged.warning.message()
+                    );
+                }
+            }
+            events::SEND_ERROR | events::DOWNLOAD_ERROR => {
+                if let Some(error) = payload.get("error").and_then(|e| e.as_str()) {
+                    eprintln!("\nError: {}", error);
+                }
+            }
+            _ => {}
+        }
+        let _ = std::io::stderr().flush();
+        Ok(())
+    }
+}
+
+async fn send(ctx: AppContext, args: &[String]) -> Result<String, String> {
+    let mut paths = Vec::new();
+    let mut folder_name = None;
+    let mut password = None;
+    let mut compression = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--name" {
+            folder_name = Some(iter.next().ok_or("--name requires a value")?.clone());
+        } else if arg == "--password" {
+            password = Some(iter.next().ok_or("--password requires a value")?.clone());
+        } else if arg == "--compression" {
+            let level = iter.next().ok_or("--compression requires a value")?;
+            compression = Some(
+                level
+                    .parse()
+                    .map_err(|_| format!("Invalid compression level: {}", level))?,
+            );
+        } else {
+            paths.push(arg.clone());
+        }
+    }
+
+    let send_id = uuid::Uuid::new_v4().to_string();
+    if let Some(password) = password {
+        return archive::send_encrypted_archive_call(ctx, paths, send_id, folder_name, password)
+            .await;
+    }
+    match paths.as_slice() {
+        [] => Err("No files provided".to_string()),
+        [single] if folder_name.is_none() && compression.is_none() => {
+            files::send_file_call(ctx, single, send_id).await
+        }
+        _ => files::send_multiple_files_call(ctx, paths, send_id, folder_name, compression).await,
+    }
+}
+
+async fn receive(ctx: AppContext, args: &[String]) -> Result<String, String> {
+    let mut code = None;
+    let mut assume_yes = false;
+    // Offers over `max_incoming_size_mib` need confirming even with --yes
+    let mut allow_large = false;
+    // So do programs and scripts (see content.rs)
+    let mut allow_executables = false;
+    let mut output = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--yes" || arg == "-y" {
+            assume_yes = true;
+        } else if arg == "--allow-large" {
+            allow_large = true;
+        } else if arg == "--allow-executables" {
+            allow_executables = true;
+        } else if arg == "--output" || arg == "-o" {
+            output = Some(iter.next().ok_or("--output requires a value")?.clone());
+        } else if !arg.starts_with('-') && code.is_none() {
+            code = Some(arg);
+        }
+    }
+    let code = code.ok_or("No code provided for receiving file.")?;
+
+    let connection_id = uuid::Uuid::new_v4().to_string();
+    let offer = files::request_file_call(ctx.clone(), code, connection_id).await?;
+
+    // A text message: print it, then offer to keep a copy like the app does.
+    if let Some(text) = &offer.text {
+        println!("{}", text);
+        if settings::get_save_text_offers(&ctx).await? {
+            return Ok("Message saved to the download directory".to_string());
+        }
+        if !assume_yes {
+            eprint!("Save this message to {}? [y/N] ", offer.file_name);
+            let _ = std::io::stderr().flush();
+            let mut answer = String::new();
+            let _ = std::io::stdin().lock().read_line(&mut answer);
+            if !matches!(answer.trim(), "y" | "Y" | "yes") {
+                return files::receiving_file_deny(offer.id, ctx, None).await;
+            }
+        }
+        return files::receiving_file_accept(offer.id, ctx, output, false, false).await;
+    }
+
+    if !assume_yes {
+        eprint!(
+            "Receive {} ({} bytes){}{}? [y/N] ",
+            offer.file_name,
+            offer.file_size,
+            if offer.over_size_limit {
+                ", over your incoming size limit"
+            } else {
+                ""
+            },
+            offer
+                .content_warning
+                .map(|warning| format!(", {} from an unknown sender", warning.message()))
+                .unwrap_or_default()
+        );
+        let _ = std::io::stderr().flush();
+        let mut answer = String::new();
+        let _ = std::io::stdin().lock().read_line(&mut answer);
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            files::receiving_file_deny(offer.id, ctx, None).await?;
+            return Ok("Transfer declined".to_string());
+        }
+        allow_large = true;
+        allow_executables = true;
+    }
+
+    files::receiving_file_accept(offer.id, ctx, output, allow_large, allow_executables).await
+}
+
+async fn history(ctx: AppContext, args: &[String]) -> Result<String, String> {
+    let entries = match args.first().map(String::as_str) {
+        Some("sent") => {
+            serde_json::to_string_pretty(&files_json::get_sent_files_json_data(&ctx).await?)
+        }
+        Some("received") | None => {
+            serde_json::to_string_pretty(&files_json::get_received_files_json_data(&ctx).await?)
+        }
+        Some(other) => return Err(format!("Unknown history kind: {}", other)),
+    };
+    entries.map_err(|e| e.to_string())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    };
+    // Answered without loading settings or history, so scripts can probe for the CLI
+    match command.as_str() {
+        "--help" | "-h" | "help" => {
+            println!("{}", USAGE);
+            return;
+        }
+        "--version" | "-V" => {
+            println!("wyrmhole-cli {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        _ => {}
+    }
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to start tokio runtime");
+
+    let result = runtime.block_on(async {
+        let (retries, mut retry_rx) = mpsc::unbounded_channel();
+        let ctx = AppContext::standalone(Arc::new(TerminalEvents { retries }));
+        tokio::spawn({
+            let ctx = ctx.clone();
+            async move {
+                while let Some(job_id) = retry_rx.recv().await {
+                    if let Err(e) = jobs::retry_job(&ctx, job_id).await {
+                        eprintln!("\n{}", e);
+                    }
+                }
+            }
+        });
+        match command.as_str() {
+            "send" => send(ctx, rest).await,
+            "receive" => receive(ctx, rest).await,
+            "history" => history(ctx, rest).await,
+            _ => Err(USAGE.to_string()),
+        }
+    });
+
+    match result {
+        Ok(message) => println!("\n{}", message),
+        Err(e) => {
+            eprintln!("\n{}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+// Screenshot-and-send: captures the primary monitor to a PNG in a staging folder (see
+// staging.rs) for `capture_and_send`. The window hides for the capture so the screenshot
+// shows what's behind it rather than wyrmhole itself.
+
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::staging::{self, Staged};
+
+// Long enough for the compositor to finish hiding the window.
+const HIDE_DELAY: Duration = Duration::from_millis(300);
+
+fn capture_primary_monitor() -> Result<Staged, String> {
+    let monitors =
+        xcap::Monitor::all().map_err(|e| format!("Screen capture unavailable: {}", e))?;
+    let monitor = monitors
+        .iter()
+        .find(|monitor| monitor.is_primary())
+        .or(monitors.first())
+        .ok_or("No monitor to capture")?;
+    let image = monitor
+        .capture_image()
+        .map_err(|e| format!("Failed to capture the screen: {}", e))?;
+
+    let dir = staging::new_dir()?;
+    let path = dir.join(staging::file_name("screenshot", "png"));
+    let staged = Staged::file(dir, &path);
+    if let Err(e) = staging::write_png(&path, image.width(), image.height(), image.as_raw()) {
+        staged.cleanup();
+        return Err(e);
+    }
+    Ok(staged)
+}
+
+/// Capture the screen with the main window out of the way, restoring it afterwards if it
+/// was showing.
+pub async fn capture_screen(app: &AppHandle) -> Result<Staged, String> {
+    let window = app.get_webview_window("main");
+    let was_visible = window
+        .as_ref()
+        .is_some_and(|window| window.is_visible().unwrap_or(false));
+    if was_visible && let Some(window) = &window {
+        let _ = window.hide();
+        tokio::time::sleep(HIDE_DELAY).await;
+    }
+
+    let result = tokio::task::spawn_blocking(capture_primary_monitor)
+        .await
+        .map_err(|e| format!("Failed to capture the screen: {}", e))
+        .and_then(|result| result);
+
+    if was_visible && let Some(window) = &window {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    result
+}
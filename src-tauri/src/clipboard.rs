@@ -0,0 +1,64 @@
+// Sending whatever is on the clipboard. An image is saved as a PNG and text as a .txt file
+// (see staging.rs). Copied files reach us as text with one path or file:// URI per line
+// (how file managers expose them to plain-text readers) and are sent as-is.
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::staging::{self, Staged};
+
+// Every non-empty line as an existing path, or None if any line isn't one.
+fn as_file_paths(text: &str) -> Option<Vec<String>> {
+    let paths: Option<Vec<String>> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let path = if line.starts_with("file://") {
+                tauri::Url::parse(line).ok()?.to_file_path().ok()?
+            } else {
+                PathBuf::from(line)
+            };
+            (path.is_absolute() && path.exists()).then(|| path.to_string_lossy().into_owned())
+        })
+        .collect();
+    paths.filter(|paths| !paths.is_empty())
+}
+
+/// Read the clipboard and prepare its contents for sending: copied files first, then an
+/// image, then text. Blocking; call from `spawn_blocking`.
+pub fn stage() -> Result<Staged, String> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| format!("Clipboard unavailable: {}", e))?;
+    let text = clipboard.get_text().ok().filter(|t| !t.trim().is_empty());
+
+    if let Some(paths) = text.as_deref().and_then(as_file_paths) {
+        return Ok(Staged::existing(paths));
+    }
+
+    if let Ok(image) = clipboard.get_image() {
+        let dir = staging::new_dir()?;
+        let path = dir.join(staging::file_name("clipboard", "png"));
+        let staged = Staged::file(dir, &path);
+        if let Err(e) =
+            staging::write_png(&path, image.width as u32, image.height as u32, &image.bytes)
+        {
+            staged.cleanup();
+            return Err(e);
+        }
+        return Ok(staged);
+    }
+
+    if let Some(text) = text {
+        let dir = staging::new_dir()?;
+        let path = dir.join(staging::file_name("clipboard", "txt"));
+        let staged = Staged::file(dir, &path);
+        if let Err(e) = fs::write(&path, text) {
+            staged.cleanup();
+            return Err(format!("Failed to save text: {}", e));
+        }
+        return Ok(staged);
+    }
+
+    Err("The clipboard is empty".to_string())
+}
@@ -0,0 +1,44 @@
+// Dragging a received file out of File History into another app. The native drag is started
+// by the frontend through tauri-plugin-drag; `stage` first checks that the history entry
+// still points at a file wyrmhole received, and hands back its absolute path and a drag icon.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use specta::Type;
+use wyrmhole_core::context::AppContext;
+use wyrmhole_core::files_json;
+
+const DRAG_ICON: &[u8] = include_bytes!("../icons/32x32.png");
+
+/// What the frontend passes to the plugin's `startDrag`.
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct DragOut {
+    pub path: String,
+    pub icon: String,
+}
+
+pub async fn stage(ctx: &AppContext, received_entry_id: &str) -> Result<DragOut, String> {
+    let path = files_json::received_file_path(ctx, received_entry_id).await?;
+    let path = files_json::check_received_location(ctx, &path.to_string_lossy()).await?;
+    let path = std::path::absolute(&path)
+        .map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))?;
+    Ok(DragOut {
+        path: path.to_string_lossy().into_owned(),
+        icon: drag_icon()?.to_string_lossy().into_owned(),
+    })
+}
+
+// The app icon, written into the staging folder the first time something is dragged.
+fn drag_icon() -> Result<PathBuf, String> {
+    let path = std::env::temp_dir()
+        .join("wyrmhole-staging")
+        .join("drag-icon.png");
+    if !path.exists() {
+        fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))
+            .and_then(|_| fs::write(&path, DRAG_ICON))
+            .map_err(|e| format!("Failed to prepare the drag icon: {}", e))?;
+    }
+    Ok(path)
+}
@@ -0,0 +1,329 @@
+// This file implements `--headless` mode: the transfer engine driven over JSON-RPC 2.0
+// on stdin/stdout instead of through the webview. Each request is one line of JSON;
+// responses and engine events (as `event` notifications) are written one per line.
+// Engine logging goes to stderr so stdout carries nothing but protocol messages.
+//
+//   -> {"jsonrpc":"2.0","id":1,"method":"request_file","params":{"receive_code":"7-guitarist-revenge","connection_id":"c1"}}
+//   <- {"jsonrpc":"2.0","method":"event","params":{"event":"download-progress","payload":{...}}}
+//   <- {"jsonrpc":"2.0","id":1,"result":{"id":"...","file_name":"photo.jpg","file_size":1024}}
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::io::Write;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use wyrmhole_core::context::{AppContext, EventSink};
+use wyrmhole_core::{archive, files, files_json, jobs, settings, share, sizes, sync};
+
+// JSON-RPC 2.0 error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const SERVER_ERROR: i64 = -32000;
+
+// Serializes whole lines onto stdout so responses and events never interleave.
+struct StdoutEvents(std::sync::Mutex<std::io::Stdout>);
+
+impl StdoutEvents {
+    fn write_line(&self, message: &Value) {
+        let mut out = self.0.lock().unwrap();
+        let _ = writeln!(out, "{}", message);
+        let _ = out.flush();
+    }
+}
+
+impl EventSink for StdoutEvents {
+    fn emit_value(&self, event: &str, payload: Value) -> Result<(), String> {
+        self.write_line(&json!({
+            "jsonrpc": "2.0",
+            "method": "event",
+            "params": { "event": event, "payload": payload }
+        }));
+        Ok(())
+    }
+}
+
+// Request envelope; `method` and `params` are re-parsed into `Call`.
+#[derive(Deserialize)]
+struct Envelope {
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+// The operations exposed over JSON-RPC, mirroring the Tauri commands in lib.rs.
+#[derive(Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+enum Call {
+    SendFile {
+        file_path: String,
+        send_id: String,
+    },
+    SendMultipleFiles {
+        file_paths: Vec<String>,
+        send_id: String,
+        folder_name: Option<String>,
+        compression: Option<u32>,
+    },
+    SendEncryptedArchive {
+        file_paths: Vec<String>,
+        send_id: String,
+        folder_name: Option<String>,
+        password: String,
+    },
+    SendChanges {
+        path: String,
+        send_id: String,
+    },
+    CancelSend {
+        send_id: String,
+    },
+    ListPackagingJobs,
+    RetryPackagingJob {
+        job_id: String,
+    },
+    CancelPackagingJob {
+        job_id: String,
+    },
+    RevealCode {
+        send_id: String,
+    },
+    ComposeCodeShare {
+        send_id: String,
+        channel: String,
+    },
+    ComputePathSize {
+        path: String,
+        id: String,
+    },
+    CancelPathSize {
+        id: String,
+    },
+    RequestFile {
+        receive_code: String,
+        connection_id: String,
+    },
+    SendReply {
+        transfer_id: String,
+        file_path: String,
+        send_id: String,
+    },
+    SendBack {
+        received_entry_id: String,
+        send_id: String,
+    },
+    AwaitReply {
+        transfer_id: String,
+        connection_id: String,
+    },
+    CancelConnection {
+        connection_id: String,
+    },
+    ReceivingFileAccept {
+        id: String,
+        #[serde(default)]
+        destination: Option<String>,
+        #[serde(default)]
+        confirm_oversize: bool,
+        #[serde(default)]
+        confirm_content: bool,
+    },
+    ReceivingFileDeny {
+        id: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    AcceptAllPending {
+        #[serde(default)]
+        destination: Option<String>,
+    },
+    DenyAllPending {
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    CancelDownload {
+        download_id: String,
+    },
+    CancelAllTransfers,
+    ActiveTransfers,
+    ReceivedFilesData,
+    SentFilesData,
+    GetDownloadPath,
+    SetDownloadDirectory {
+        new_path: String,
+    },
+}
+
+async fn dispatch(ctx: AppContext, call: Call) -> Result<Value, String> {
+    match call {
+        Call::SendFile { file_path, send_id } => files::send_file_call(ctx, &file_path, send_id)
+            .await
+            .map(Value::from),
+        Call::SendMultipleFiles {
+            file_paths,
+            send_id,
+            folder_name,
+            compression,
+        } => files::send_multiple_files_call(ctx, file_paths, send_id, folder_name, compression)
+            .await
+            .map(Value::from),
+        Call::SendEncryptedArchive {
+            file_paths,
+            send_id,
+            folder_name,
+            password,
+        } => archive::send_encrypted_archive_call(ctx, file_paths, send_id, folder_name, password)
+            .await
+            .map(Value::from),
+        Call::SendChanges { path, send_id } => sync::send_changes_call(ctx, path, send_id)
+            .await
+            .map(Value::from),
+        Call::CancelSend { send_id } => files::cancel_send(send_id, ctx).await.map(Value::from),
+        Call::ListPackagingJobs => {
+            serde_json::to_value(jobs::list_jobs(&ctx).await).map_err(|e| e.to_string())
+        }
+        Call::RetryPackagingJob { job_id } => {
+            jobs::retry_job(&ctx, job_id).await.map(|_| Value::Null)
+        }
+        Call::CancelPackagingJob { job_id } => jobs::cancel_job(job_id, ctx).await.map(Value::from),
+        Call::RevealCode { send_id } => files::reveal_code(send_id, ctx).await.map(Value::from),
+        Call::ComposeCodeShare { send_id, channel } => {
+            let share = share::compose_code_share(send_id, channel, ctx).await?;
+            serde_json::to_value(share).map_err(|e| e.to_string())
+        }
+        Call::ComputePathSize { path, id } => {
+            let size = sizes::compute_path_size(ctx, path, id).await?;
+            serde_json::to_value(size).map_err(|e| e.to_string())
+        }
+        Call::CancelPathSize { id } => sizes::cancel_path_size(&ctx, id).await.map(|_| Value::Null),
+        Call::RequestFile {
+            receive_code,
+            connection_id,
+        } => {
+            let offer = files::request_file_call(ctx, &receive_code, connection_id).await?;
+            serde_json::to_value(offer).map_err(|e| e.to_string())
+        }
+        Call::SendReply {
+            transfer_id,
+            file_path,
+            send_id,
+        } => files::send_reply_call(ctx, &transfer_id, &file_path, send_id)
+            .await
+            .map(Value::from),
+        Call::SendBack {
+            received_entry_id,
+            send_id,
+        } => files::send_back(ctx, &received_entry_id, send_id)
+            .await
+            .map(Value::from),
+        Call::AwaitReply {
+            transfer_id,
+            connection_id,
+        } => {
+            let offer = files::await_reply_call(ctx, &transfer_id, connection_id).await?;
+            serde_json::to_value(offer).map_err(|e| e.to_string())
+        }
+        Call::CancelConnection { connection_id } => files::cancel_connection(connection_id, ctx)
+            .await
+            .map(Value::from),
+        Call::ReceivingFileAccept {
+            id,
+            destination,
+            confirm_oversize,
+            confirm_content,
+        } => files::receiving_file_accept(id, ctx, destination, confirm_oversize, confirm_content)
+            .await
+            .map(Value::from),
+        Call::ReceivingFileDeny { id, reason } => files::receiving_file_deny(id, ctx, reason)
+            .await
+            .map(Value::from),
+        Call::AcceptAllPending { destination } => files::accept_all_pending(ctx, destination)
+            .await
+            .map(Value::from),
+        Call::DenyAllPending { reason } => {
+            files::deny_all_pending(ctx, reason).await.map(Value::from)
+        }
+        Call::CancelDownload { download_id } => files::cancel_download(download_id, ctx)
+            .await
+            .map(Value::from),
+        Call::CancelAllTransfers => files::cancel_all_transfers(ctx).await.map(Value::from),
+        Call::ActiveTransfers => {
+            serde_json::to_value(files::active_transfers(ctx).await).map_err(|e| e.to_string())
+        }
+        Call::ReceivedFilesData => files_json::get_received_files_json_data(&ctx)
+            .await
+            .and_then(|files| serde_json::to_value(files).map_err(|e| e.to_string())),
+        Call::SentFilesData => files_json::get_sent_files_json_data(&ctx)
+            .await
+            .and_then(|files| serde_json::to_value(files).map_err(|e| e.to_string())),
+        Call::GetDownloadPath => settings::get_download_path(&ctx).await.map(Value::from),
+        Call::SetDownloadDirectory { new_path } => settings::set_download_directory(&ctx, new_path)
+            .await
+            .map(|_| Value::Null),
+    }
+}
+
+fn error_response(id: Option<Value>, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message }
+    })
+}
+
+// Parse one request line, run it, and write the response. Notifications (no id)
+// still run but get no response, per the JSON-RPC spec.
+async fn handle_line(ctx: AppContext, out: Arc<StdoutEvents>, line: String) {
+    let envelope = match serde_json::from_str::<Envelope>(&line) {
+        Ok(envelope) => envelope,
+        Err(e) => {
+            out.write_line(&error_response(None, PARSE_ERROR, e.to_string()));
+            return;
+        }
+    };
+
+    let call = serde_json::from_value::<Call>(json!({
+        "method": envelope.method,
+        "params": envelope.params,
+    }));
+
+    let response = match call {
+        Ok(call) => match dispatch(ctx, call).await {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": envelope.id, "result": result }),
+            Err(e) => error_response(envelope.id.clone(), SERVER_ERROR, e),
+        },
+        Err(e) => error_response(envelope.id.clone(), INVALID_REQUEST, e.to_string()),
+    };
+
+    if envelope.id.is_some() {
+        out.write_line(&response);
+    }
+}
+
+/// Run the engine headless until stdin closes.
+pub fn run() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to start tokio runtime");
+
+    runtime.block_on(async {
+        let out = Arc::new(StdoutEvents(std::sync::Mutex::new(std::io::stdout())));
+        let ctx = AppContext::standalone(out.clone());
+
+        eprintln!("[magic-wormhole][headless][info] Ready for JSON-RPC requests on stdin");
+
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            // Requests run concurrently so a long send never blocks a cancel.
+            tokio::spawn(handle_line(ctx.clone(), out.clone(), line));
+        }
+
+        // stdin closed: stop anything still running before exiting.
+        let _ = files::cancel_all_transfers(ctx).await;
+    });
+}
@@ -1,16 +1,75 @@
-// This file provides secure Tauri command bindings that delegate to specialized modules.
-// All file transfer logic is in files.rs, settings logic is in settings.rs, etc.
+// This file provides secure Tauri command bindings that delegate to the wyrmhole-core engine.
+// All file transfer logic is in core's files.rs, settings logic is in settings.rs, etc.
 
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::{
-    AppHandle, Emitter, Manager, WindowEvent,
+    AppHandle, DragDropEvent, Emitter, Manager, State, WindowEvent,
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
 };
 use tauri_plugin_autostart::ManagerExt;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tauri_plugin_notification::NotificationExt;
-use tokio::sync::Mutex;
+use tauri_plugin_opener::OpenerExt;
+
+use wyrmhole_core::cache::{self, PackageCacheInfo};
+use wyrmhole_core::context::{AppContext, AppPaths, EventSink};
+use wyrmhole_core::crash::{self, CrashReport};
+use wyrmhole_core::demo::{self, DemoWormhole};
+use wyrmhole_core::hooks::{self, HookAction, ReceiveHook};
+use wyrmhole_core::metrics::{self, UsageMetrics};
+use wyrmhole_core::profiles;
+use wyrmhole_core::relays;
+use wyrmhole_core::report;
+use wyrmhole_core::sensitive::{self, SensitivePath};
+use wyrmhole_core::settings::{ExportRange, JsonSettingsStore, SettingsStore};
+use wyrmhole_core::throttle::RateWindow;
+use wyrmhole_core::transfers::TransferSnapshot;
+use wyrmhole_core::updates::{self, UpdateInfo};
+use wyrmhole_core::{api, archive, events, files, files_json, jobs, settings, share, sizes, sync};
+
+use automation::AutomationCommand;
+use scope::{CommandError, ScopeError};
+use sounds::Sound;
+
+// Forwards engine events to the webview through Tauri's event system.
+struct TauriEvents(AppHandle);
+
+impl EventSink for TauriEvents {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) -> Result<(), String> {
+        if event == events::DOWNLOAD_COMPLETE || event == events::SENT_FILE_ADDED {
+            sounds::play(Sound::Complete);
+        }
+        transfer_windows::route(&self.0, event, &payload);
+        self.0.emit(event, payload).map_err(|e| e.to_string())
+    }
+}
+
+// Resolve the engine's directories through Tauri's path resolver, falling back to
+// the OS lookups headless mode uses if any of them is unavailable.
+fn app_paths(app: &AppHandle) -> AppPaths {
+    let fallback = AppPaths::from_os();
+    let resolver = app.path();
+    AppPaths {
+        config_dir: resolver.app_config_dir().unwrap_or(fallback.config_dir),
+        data_dir: resolver.app_data_dir().unwrap_or(fallback.data_dir),
+        default_download_dir: resolver
+            .download_dir()
+            .unwrap_or(fallback.default_download_dir),
+    }
+}
+
+// Top-level directories that profiles live under: the OS locations, or the throwaway
+// demo directories under `--demo`.
+pub(crate) fn base_paths(app: &AppHandle) -> AppPaths {
+    if std::env::args().any(|a| a == "--demo") {
+        demo::demo_paths()
+    } else {
+        app_paths(app)
+    }
+}
 
 // Sync mirror of `minimize_on_close` so the window-close event handler (which is
 // not async) can read it without locking the tokio Mutex around AppSettings.
@@ -21,6 +80,15 @@ struct MinimizeOnClose(Arc<AtomicBool>);
 // paths. The payload is the full batch, which the frontend sends as one package.
 const SEND_FROM_OS_EVENT: &str = "send-files-from-os";
 
+// Event carrying a receive code from a `wyrmhole://receive` URL or a `wormhole-transfer:`
+// link (see automation.rs); the frontend connects with it as if it had been typed in.
+const RECEIVE_FROM_OS_EVENT: &str = "receive-code-from-os";
+
+// Event carrying paths from a `wyrmhole://send` URL. Any web page or local app can open such
+// a URL, so unlike the paths above these aren't approved or sent: the frontend shows them as
+// a selection, and only `confirm_link_send` (when the user presses Send) approves them.
+const SEND_REQUEST_FROM_LINK_EVENT: &str = "send-request-from-link";
+
 // How long to wait for more paths before dispatching a batch. Windows launches
 // one process per file on a multi-selection, so those arrive as separate
 // single-instance forwards within a few milliseconds; this window coalesces
@@ -37,6 +105,12 @@ struct OsSendQueue(std::sync::Mutex<OsSendQueueInner>);
 #[derive(Default)]
 struct OsSendQueueInner {
     paths: Vec<String>,
+    // Receive codes from `wyrmhole://receive` URLs, flushed with the paths.
+    codes: Vec<String>,
+    // Paths from `wyrmhole://send` URLs, flushed with the paths, then held in
+    // `awaiting_confirmation` until the user sends or discards them.
+    link_paths: Vec<String>,
+    awaiting_confirmation: Vec<String>,
     generation: u64,
     frontend_ready: bool,
 }
@@ -53,6 +127,21 @@ fn extract_file_paths(args: &[String]) -> Vec<String> {
         .collect()
 }
 
+// Make non-flag arguments absolute against `cwd`, the working directory of the launch
+// they came from (which isn't ours when forwarded from a second instance).
+fn resolve_relative_args(args: &[String], cwd: &std::path::Path) -> Vec<String> {
+    args.iter()
+        .enumerate()
+        .map(|(i, a)| {
+            if i == 0 || a.starts_with('-') || std::path::Path::new(a).is_absolute() {
+                a.clone()
+            } else {
+                cwd.join(a).to_string_lossy().into_owned()
+            }
+        })
+        .collect()
+}
+
 // Add OS-provided paths to the batch and schedule a debounced flush. Safe to
 // call before the frontend is ready (paths just wait in the queue).
 fn enqueue_os_paths(app: &AppHandle, new_paths: Vec<String>) {
@@ -60,6 +149,8 @@ fn enqueue_os_paths(app: &AppHandle, new_paths: Vec<String>) {
         return;
     }
     show_main_window(app);
+    // The OS handed these over on the user's behalf, so they're approved for sending.
+    scope::grant(app, &new_paths);
 
     let generation = {
         let queue = app.state::<OsSendQueue>();
@@ -73,154 +164,1228 @@ fn enqueue_os_paths(app: &AppHandle, new_paths: Vec<String>) {
         q.generation
     };
 
-    schedule_flush(app.clone(), generation);
+    schedule_flush(app.clone(), generation);
+}
+
+// Queue a receive code from an automation URL, flushed like OS-provided paths so a code
+// that cold-started the app waits for the frontend.
+fn enqueue_os_code(app: &AppHandle, code: String) {
+    show_main_window(app);
+
+    let generation = {
+        let queue = app.state::<OsSendQueue>();
+        let mut q = queue.0.lock().unwrap();
+        if !q.codes.contains(&code) {
+            q.codes.push(code);
+        }
+        q.generation += 1;
+        q.generation
+    };
+
+    schedule_flush(app.clone(), generation);
+}
+
+// Queue the paths of a `wyrmhole://send` URL for the user to confirm; nothing is approved
+// for sending until they do.
+fn enqueue_link_paths(app: &AppHandle, new_paths: Vec<String>) {
+    if new_paths.is_empty() {
+        return;
+    }
+    show_main_window(app);
+
+    let generation = {
+        let queue = app.state::<OsSendQueue>();
+        let mut q = queue.0.lock().unwrap();
+        for p in new_paths {
+            if !q.link_paths.contains(&p) {
+                q.link_paths.push(p);
+            }
+        }
+        q.generation += 1;
+        q.generation
+    };
+
+    schedule_flush(app.clone(), generation);
+}
+
+// Act on `wyrmhole://` URLs and wormhole code links opened through the deep-link plugin.
+fn handle_automation_urls(app: &AppHandle, urls: Vec<tauri::Url>) {
+    for url in urls {
+        match automation::parse(&url) {
+            Ok(AutomationCommand::Send(paths)) => enqueue_link_paths(app, paths),
+            Ok(AutomationCommand::Receive(code)) => enqueue_os_code(app, code),
+            Err(e) => eprintln!("Ignoring automation URL: {}", e),
+        }
+    }
+}
+
+// After the debounce window, dispatch the batch if no newer path arrived and the
+// frontend is listening; otherwise leave it for the next trigger to flush.
+fn schedule_flush(app: AppHandle, generation: u64) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(BATCH_DEBOUNCE_MS)).await;
+
+        let (paths, codes, link_paths) = {
+            let queue = app.state::<OsSendQueue>();
+            let mut q = queue.0.lock().unwrap();
+            if q.generation != generation || !q.frontend_ready {
+                return;
+            }
+            let link_paths = std::mem::take(&mut q.link_paths);
+            if !link_paths.is_empty() {
+                // A newer link replaces one still waiting
+                q.awaiting_confirmation = link_paths.clone();
+            }
+            (
+                std::mem::take(&mut q.paths),
+                std::mem::take(&mut q.codes),
+                link_paths,
+            )
+        };
+
+        if !paths.is_empty() {
+            let _ = app.emit(SEND_FROM_OS_EVENT, paths);
+            show_main_window(&app);
+        }
+        for code in codes {
+            let _ = app.emit(RECEIVE_FROM_OS_EVENT, code);
+            show_main_window(&app);
+        }
+        if !link_paths.is_empty() {
+            let _ = app.emit(SEND_REQUEST_FROM_LINK_EVENT, link_paths);
+            show_main_window(&app);
+        }
+    });
+}
+
+pub mod automation;
+pub mod capture;
+pub mod clipboard;
+pub mod context_menu;
+pub mod drag_out;
+pub mod headless;
+pub mod scope;
+pub mod sounds;
+pub mod staging;
+pub mod transfer_windows;
+pub mod updater;
+
+// Secure bindings - these are the only functions exposed to the frontend
+// All actual logic is delegated to the appropriate modules
+
+// Every send checks its paths against the approved scope (see scope.rs), and sends of
+// sensitive locations (home folders, browser profiles, keys) need `confirm_sensitive`.
+async fn check_send(
+    app_handle: &AppHandle,
+    ctx: &AppContext,
+    paths: &[String],
+    confirm_sensitive: bool,
+) -> Result<(), CommandError> {
+    scope::check_send_paths(app_handle, ctx, paths).await?;
+    if !confirm_sensitive {
+        let found = sensitive::check_sensitive_paths(paths.to_vec()).await?;
+        if !found.is_empty() {
+            return Err(CommandError::Sensitive { sensitive: found });
+        }
+    }
+    Ok(())
+}
+
+// What the send confirmation lists for `paths`, checked before a send starts.
+#[tauri::command]
+#[specta::specta]
+async fn check_sensitive_paths(paths: Vec<String>) -> Result<Vec<SensitivePath>, String> {
+    sensitive::check_sensitive_paths(paths).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn send_file_call(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    file_path: &str,
+    send_id: String,
+    confirm_sensitive: bool,
+) -> Result<String, CommandError> {
+    check_send(
+        &app_handle,
+        &ctx,
+        &[file_path.to_string()],
+        confirm_sensitive,
+    )
+    .await?;
+    Ok(files::send_file_call(ctx.inner().clone(), file_path, send_id).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn send_multiple_files_call(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    file_paths: Vec<String>,
+    send_id: String,
+    folder_name: Option<String>,
+    compression: Option<u32>,
+    confirm_sensitive: bool,
+) -> Result<String, CommandError> {
+    check_send(&app_handle, &ctx, &file_paths, confirm_sensitive).await?;
+    Ok(files::send_multiple_files_call(
+        ctx.inner().clone(),
+        file_paths,
+        send_id,
+        folder_name,
+        compression,
+    )
+    .await?)
+}
+
+// Sends files and folders as a password-protected zip (see wyrmhole-core's archive.rs).
+#[tauri::command]
+#[specta::specta]
+async fn send_encrypted_archive_call(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    file_paths: Vec<String>,
+    send_id: String,
+    folder_name: Option<String>,
+    password: String,
+    confirm_sensitive: bool,
+) -> Result<String, CommandError> {
+    check_send(&app_handle, &ctx, &file_paths, confirm_sensitive).await?;
+    Ok(archive::send_encrypted_archive_call(
+        ctx.inner().clone(),
+        file_paths,
+        send_id,
+        folder_name,
+        password,
+    )
+    .await?)
+}
+
+// Sends only what changed in a folder or file since it was last sent this way (see
+// wyrmhole-core's sync.rs and delta.rs).
+#[tauri::command]
+#[specta::specta]
+async fn send_changes_call(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    path: String,
+    send_id: String,
+    confirm_sensitive: bool,
+) -> Result<String, CommandError> {
+    check_send(
+        &app_handle,
+        &ctx,
+        std::slice::from_ref(&path),
+        confirm_sensitive,
+    )
+    .await?;
+    Ok(sync::send_changes_call(ctx.inner().clone(), path, send_id).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn reset_sync(ctx: State<'_, AppContext>, path: String) -> Result<(), String> {
+    sync::reset_sync(ctx.inner().clone(), path).await
+}
+
+// Sends the clipboard's copied files, image or text (see clipboard.rs).
+#[tauri::command]
+#[specta::specta]
+async fn send_clipboard(ctx: State<'_, AppContext>, send_id: String) -> Result<String, String> {
+    let staged = tokio::task::spawn_blocking(clipboard::stage)
+        .await
+        .map_err(|e| format!("Failed to read the clipboard: {}", e))??;
+    staging::send(ctx.inner().clone(), staged, send_id).await
+}
+
+// Screenshots the primary monitor and sends it (see capture.rs).
+#[tauri::command]
+#[specta::specta]
+async fn capture_and_send(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    send_id: String,
+) -> Result<String, String> {
+    let staged = capture::capture_screen(&app_handle).await?;
+    staging::send(ctx.inner().clone(), staged, send_id).await
+}
+
+// The code of an active send when `hide_send_codes` keeps it out of progress events.
+#[tauri::command]
+#[specta::specta]
+async fn reveal_code(ctx: State<'_, AppContext>, send_id: String) -> Result<String, String> {
+    files::reveal_code(send_id, ctx.inner().clone()).await
+}
+
+// A ready-made email/SMS/clipboard message carrying the send's code.
+#[tauri::command]
+#[specta::specta]
+async fn compose_code_share(
+    ctx: State<'_, AppContext>,
+    send_id: String,
+    channel: String,
+) -> Result<share::CodeShare, String> {
+    share::compose_code_share(send_id, channel, ctx.inner().clone()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn cancel_send(send_id: String, ctx: State<'_, AppContext>) -> Result<String, String> {
+    files::cancel_send(send_id, ctx.inner().clone()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn list_packaging_jobs(
+    ctx: State<'_, AppContext>,
+) -> Result<Vec<events::PackagingJob>, String> {
+    Ok(jobs::list_jobs(&ctx).await)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn retry_packaging_job(ctx: State<'_, AppContext>, job_id: String) -> Result<(), String> {
+    jobs::retry_job(&ctx, job_id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn cancel_packaging_job(
+    job_id: String,
+    ctx: State<'_, AppContext>,
+) -> Result<String, String> {
+    jobs::cancel_job(job_id, ctx.inner().clone()).await
+}
+
+// Walks `path` in the background for the selection size preview; progress arrives as
+// `path-size-progress` events tagged with `id`.
+#[tauri::command]
+#[specta::specta]
+async fn compute_path_size(
+    ctx: State<'_, AppContext>,
+    path: String,
+    id: String,
+) -> Result<sizes::PathSize, String> {
+    sizes::compute_path_size(ctx.inner().clone(), path, id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn cancel_path_size(ctx: State<'_, AppContext>, id: String) -> Result<(), String> {
+    sizes::cancel_path_size(&ctx, id).await
+}
+
+// Opens the native folder dialog from here rather than the webview, so network shares come
+// back as ordinary paths; `None` when the dialog is dismissed.
+#[tauri::command]
+#[specta::specta]
+async fn pick_folder_for_send(
+    app_handle: AppHandle,
+) -> Result<Option<sizes::FolderForSend>, String> {
+    let dialog_app = app_handle.clone();
+    let picked =
+        tokio::task::spawn_blocking(move || dialog_app.dialog().file().blocking_pick_folder())
+            .await
+            .map_err(|e| format!("Failed to open folder dialog: {}", e))?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| format!("Unsupported folder location: {}", e))?;
+    scope::grant(&app_handle, [&path]);
+    sizes::check_folder_for_send(path.to_string_lossy().into_owned())
+        .await
+        .map(Some)
+}
+
+// Folder picker for the download and history directory settings, opened here so the
+// folder is approved for `set_download_directory` / `set_history_directory` (see scope.rs);
+// `None` when the dialog is dismissed.
+#[tauri::command]
+#[specta::specta]
+async fn pick_directory(app_handle: AppHandle) -> Result<Option<String>, String> {
+    let dialog_app = app_handle.clone();
+    let picked =
+        tokio::task::spawn_blocking(move || dialog_app.dialog().file().blocking_pick_folder())
+            .await
+            .map_err(|e| format!("Failed to open folder dialog: {}", e))?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| format!("Unsupported folder location: {}", e))?;
+    scope::grant(&app_handle, [&path]);
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+// File picker for sends, opened here so what the user picks is approved for sending (see
+// scope.rs); `None` when the dialog is dismissed.
+#[tauri::command]
+#[specta::specta]
+async fn pick_files_for_send(
+    app_handle: AppHandle,
+    multiple: bool,
+) -> Result<Option<Vec<String>>, String> {
+    let dialog_app = app_handle.clone();
+    let picked = tokio::task::spawn_blocking(move || {
+        let dialog = dialog_app.dialog().file();
+        if multiple {
+            dialog.blocking_pick_files()
+        } else {
+            dialog.blocking_pick_file().map(|file| vec![file])
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to open file dialog: {}", e))?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let paths = picked
+        .into_iter()
+        .map(|file| file.into_path())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Unsupported file location: {}", e))?;
+    scope::grant(&app_handle, &paths);
+    Ok(Some(
+        paths
+            .into_iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect(),
+    ))
+}
+
+// Save dialog for exports, approving the chosen destination (see scope.rs); `None` when
+// the dialog is dismissed.
+#[tauri::command]
+#[specta::specta]
+async fn pick_export_path(
+    app_handle: AppHandle,
+    default_name: String,
+    extension: String,
+) -> Result<Option<String>, String> {
+    let dialog_app = app_handle.clone();
+    let picked = tokio::task::spawn_blocking(move || {
+        dialog_app
+            .dialog()
+            .file()
+            .add_filter(extension.to_uppercase(), &[extension.as_str()])
+            .set_file_name(default_name)
+            .blocking_save_file()
+    })
+    .await
+    .map_err(|e| format!("Failed to open save dialog: {}", e))?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| format!("Unsupported save location: {}", e))?;
+    scope::grant_export_path(&app_handle, &path);
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn cancel_download(
+    ctx: State<'_, AppContext>,
+    download_id: String,
+) -> Result<String, String> {
+    files::cancel_download(download_id, ctx.inner().clone()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn cancel_all_transfers(ctx: State<'_, AppContext>) -> Result<String, String> {
+    files::cancel_all_transfers(ctx.inner().clone()).await
+}
+
+// Ids of everything in flight, e.g. to check before quitting.
+#[tauri::command]
+#[specta::specta]
+async fn active_transfers(ctx: State<'_, AppContext>) -> Result<TransferSnapshot, String> {
+    Ok(files::active_transfers(ctx.inner().clone()).await)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn request_file_call(
+    ctx: State<'_, AppContext>,
+    receive_code: &str,
+    connection_id: String,
+) -> Result<events::FileOffer, String> {
+    let offer = files::request_file_call(ctx.inner().clone(), receive_code, connection_id).await?;
+    sounds::play(Sound::Offer);
+    Ok(offer)
+}
+
+// Replies to a finished transfer without a new code (see `files::reply_code`).
+#[tauri::command]
+#[specta::specta]
+async fn send_reply_call(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    transfer_id: &str,
+    file_path: &str,
+    send_id: String,
+    confirm_sensitive: bool,
+) -> Result<String, CommandError> {
+    check_send(
+        &app_handle,
+        &ctx,
+        &[file_path.to_string()],
+        confirm_sensitive,
+    )
+    .await?;
+    Ok(files::send_reply_call(ctx.inner().clone(), transfer_id, file_path, send_id).await?)
+}
+
+// Checks a received history entry before the frontend drags it out (see drag_out.rs).
+#[tauri::command]
+#[specta::specta]
+async fn stage_drag_out(
+    ctx: State<'_, AppContext>,
+    received_entry_id: &str,
+) -> Result<drag_out::DragOut, String> {
+    drag_out::stage(ctx.inner(), received_entry_id).await
+}
+
+// Sends a received history entry's file again under a new code.
+#[tauri::command]
+#[specta::specta]
+async fn send_back(
+    ctx: State<'_, AppContext>,
+    received_entry_id: &str,
+    send_id: String,
+) -> Result<String, String> {
+    files::send_back(ctx.inner().clone(), received_entry_id, send_id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn remove_received_entry(
+    ctx: State<'_, AppContext>,
+    received_entry_id: &str,
+    delete_file: bool,
+) -> Result<(), String> {
+    files_json::remove_received_entry(&ctx, received_entry_id, delete_file).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn await_reply_call(
+    ctx: State<'_, AppContext>,
+    transfer_id: &str,
+    connection_id: String,
+) -> Result<events::FileOffer, String> {
+    let offer = files::await_reply_call(ctx.inner().clone(), transfer_id, connection_id).await?;
+    sounds::play(Sound::Offer);
+    Ok(offer)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn cancel_connection(
+    ctx: State<'_, AppContext>,
+    connection_id: String,
+) -> Result<String, String> {
+    files::cancel_connection(connection_id, ctx.inner().clone()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn receiving_file_accept(
+    app_handle: AppHandle,
+    id: String,
+    destination: Option<String>,
+    confirm_oversize: Option<bool>,
+    confirm_content: Option<bool>,
+    ctx: State<'_, AppContext>,
+) -> Result<String, CommandError> {
+    check_receive_destination(&app_handle, &ctx, destination.as_deref()).await?;
+    Ok(files::receiving_file_accept(
+        id,
+        ctx.inner().clone(),
+        destination,
+        confirm_oversize.unwrap_or(false),
+        confirm_content.unwrap_or(false),
+    )
+    .await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn receiving_file_deny(
+    id: String,
+    reason: Option<String>,
+    ctx: State<'_, AppContext>,
+) -> Result<String, String> {
+    files::receiving_file_deny(id, ctx.inner().clone(), reason).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn accept_all_pending(
+    app_handle: AppHandle,
+    destination: Option<String>,
+    ctx: State<'_, AppContext>,
+) -> Result<String, CommandError> {
+    check_receive_destination(&app_handle, &ctx, destination.as_deref()).await?;
+    Ok(files::accept_all_pending(ctx.inner().clone(), destination).await?)
+}
+
+// A folder to receive into instead of the download directory must be in scope, usually
+// by having been picked in `pick_directory`; blank means the download directory.
+async fn check_receive_destination(
+    app_handle: &AppHandle,
+    ctx: &AppContext,
+    destination: Option<&str>,
+) -> Result<(), ScopeError> {
+    match destination.map(str::trim).filter(|dir| !dir.is_empty()) {
+        Some(dir) => scope::check_directory(app_handle, ctx, dir).await,
+        None => Ok(()),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn deny_all_pending(
+    reason: Option<String>,
+    ctx: State<'_, AppContext>,
+) -> Result<String, String> {
+    files::deny_all_pending(ctx.inner().clone(), reason).await
+}
+
+// Open a received file (or extracted folder) in its default app. Used by the
+// completion toast; only paths wyrmhole has received into are accepted.
+#[tauri::command]
+#[specta::specta]
+async fn open_received_file(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    path: String,
+) -> Result<(), String> {
+    let path = files_json::check_received_location(&ctx, &path).await?;
+    app_handle
+        .opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+// Reveal a received file in the OS file manager (Explorer, Finder, ...).
+#[tauri::command]
+#[specta::specta]
+async fn show_in_folder(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    path: String,
+) -> Result<(), String> {
+    let path = files_json::check_received_location(&ctx, &path).await?;
+    app_handle
+        .opener()
+        .reveal_item_in_dir(path)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_download_directory(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    new_path: String,
+) -> Result<(), CommandError> {
+    scope::check_directory(&app_handle, &ctx, &new_path).await?;
+    Ok(settings::set_download_directory(&ctx, new_path).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_download_path(ctx: State<'_, AppContext>) -> Result<String, String> {
+    settings::get_download_path(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_history_directory(ctx: State<'_, AppContext>) -> Result<String, String> {
+    settings::get_history_directory(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_history_directory(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    new_path: Option<String>,
+) -> Result<(), CommandError> {
+    if let Some(path) = &new_path {
+        scope::check_directory(&app_handle, &ctx, path).await?;
+    }
+    Ok(settings::set_history_directory(&ctx, new_path).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_auto_extract_tarballs(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_auto_extract_tarballs(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_auto_extract_tarballs(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_auto_extract_tarballs(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_date_subfolders(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_date_subfolders(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_date_subfolders(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_date_subfolders(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_receive_hooks(ctx: State<'_, AppContext>) -> Result<Vec<ReceiveHook>, String> {
+    settings::get_receive_hooks(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_receive_hooks(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    value: Vec<ReceiveHook>,
+) -> Result<(), CommandError> {
+    // Rules already saved were checked when they were added; only new or edited ones are.
+    let saved = settings::get_receive_hooks(&ctx).await?;
+    let added: Vec<&ReceiveHook> = value.iter().filter(|hook| !saved.contains(hook)).collect();
+    for hook in added
+        .iter()
+        .filter(|hook| hook.action == HookAction::MoveTo)
+    {
+        let folder = hooks::expand_home(hook.target.trim());
+        scope::check_directory(&app_handle, &ctx, &folder.to_string_lossy()).await?;
+    }
+    let programs: Vec<&ReceiveHook> = added
+        .into_iter()
+        .filter(|hook| hook.action != HookAction::MoveTo)
+        .collect();
+    if !programs.is_empty() && !confirm_program_rules(&app_handle, &programs).await? {
+        return Err("The rules weren't saved".to_string().into());
+    }
+    Ok(settings::set_receive_hooks(&ctx, value).await?)
+}
+
+// Rules that run commands or open apps run on files strangers send, so saving them takes a
+// yes in a native dialog the webview can't answer for the user.
+async fn confirm_program_rules(
+    app_handle: &AppHandle,
+    rules: &[&ReceiveHook],
+) -> Result<bool, String> {
+    let listed: Vec<String> = rules
+        .iter()
+        .map(|hook| match hook.action {
+            HookAction::OpenWith => format!("{}: open with {}", hook.pattern, hook.target),
+            _ => format!("{}: run {}", hook.pattern, hook.target),
+        })
+        .collect();
+    let message = format!(
+        "These rules will run on matching files anyone sends you:\n\n{}",
+        listed.join("\n")
+    );
+    let dialog_app = app_handle.clone();
+    tokio::task::spawn_blocking(move || {
+        dialog_app
+            .dialog()
+            .message(message)
+            .title("Save rules that run programs?")
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                "Save".to_string(),
+                "Cancel".to_string(),
+            ))
+            .blocking_show()
+    })
+    .await
+    .map_err(|e| format!("Failed to open confirmation dialog: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_save_text_offers(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_save_text_offers(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_save_text_offers(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_save_text_offers(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_default_folder_name_format(ctx: State<'_, AppContext>) -> Result<String, String> {
+    settings::get_default_folder_name_format(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_default_folder_name_format(
+    ctx: State<'_, AppContext>,
+    value: String,
+) -> Result<(), String> {
+    settings::set_default_folder_name_format(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_received_file_name_template(ctx: State<'_, AppContext>) -> Result<String, String> {
+    settings::get_received_file_name_template(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_received_file_name_template(
+    ctx: State<'_, AppContext>,
+    value: String,
+) -> Result<(), String> {
+    settings::set_received_file_name_template(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_relay_server_url(ctx: State<'_, AppContext>) -> Result<Option<String>, String> {
+    settings::get_relay_server_url(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_relay_server_url(
+    ctx: State<'_, AppContext>,
+    value: Option<String>,
+) -> Result<(), String> {
+    settings::set_relay_server_url(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+fn list_relay_presets() -> Vec<settings::RelayPreset> {
+    settings::list_relay_presets()
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn apply_relay_preset(
+    ctx: State<'_, AppContext>,
+    id: String,
+) -> Result<Option<String>, String> {
+    settings::apply_relay_preset(&ctx, id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn reset_relay_server(ctx: State<'_, AppContext>) -> Result<(), String> {
+    settings::reset_relay_server(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_download_rate_limit_kib(ctx: State<'_, AppContext>) -> Result<Option<u64>, String> {
+    settings::get_download_rate_limit_kib(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_download_rate_limit_kib(
+    ctx: State<'_, AppContext>,
+    value: Option<u64>,
+) -> Result<(), String> {
+    settings::set_download_rate_limit_kib(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_upload_rate_limit_kib(ctx: State<'_, AppContext>) -> Result<Option<u64>, String> {
+    settings::get_upload_rate_limit_kib(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_upload_rate_limit_kib(
+    ctx: State<'_, AppContext>,
+    value: Option<u64>,
+) -> Result<(), String> {
+    settings::set_upload_rate_limit_kib(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_rate_limit_schedule(ctx: State<'_, AppContext>) -> Result<Vec<RateWindow>, String> {
+    settings::get_rate_limit_schedule(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_rate_limit_schedule(
+    ctx: State<'_, AppContext>,
+    value: Vec<RateWindow>,
+) -> Result<(), String> {
+    settings::set_rate_limit_schedule(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_max_concurrent_sends(ctx: State<'_, AppContext>) -> Result<u32, String> {
+    settings::get_max_concurrent_sends(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_max_concurrent_sends(ctx: State<'_, AppContext>, value: u32) -> Result<(), String> {
+    settings::set_max_concurrent_sends(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_hide_send_codes(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_hide_send_codes(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_hide_send_codes(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_hide_send_codes(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_idle_timeout_secs(ctx: State<'_, AppContext>) -> Result<u64, String> {
+    settings::get_idle_timeout_secs(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_idle_timeout_secs(ctx: State<'_, AppContext>, value: u64) -> Result<(), String> {
+    settings::set_idle_timeout_secs(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_merge_folder_updates(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_merge_folder_updates(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_merge_folder_updates(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_merge_folder_updates(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_apply_file_deltas(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_apply_file_deltas(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_apply_file_deltas(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_apply_file_deltas(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_compression_level(ctx: State<'_, AppContext>) -> Result<u32, String> {
+    settings::get_compression_level(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_compression_level(ctx: State<'_, AppContext>, value: u32) -> Result<(), String> {
+    settings::set_compression_level(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_deterministic_archives(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_deterministic_archives(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_deterministic_archives(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_deterministic_archives(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_stream_archives(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_stream_archives(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_stream_archives(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_stream_archives(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_archive_part_size_mib(ctx: State<'_, AppContext>) -> Result<u64, String> {
+    settings::get_archive_part_size_mib(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_archive_part_size_mib(ctx: State<'_, AppContext>, value: u64) -> Result<(), String> {
+    settings::set_archive_part_size_mib(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_extra_relay_urls(ctx: State<'_, AppContext>) -> Result<Vec<String>, String> {
+    settings::get_extra_relay_urls(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_extra_relay_urls(
+    ctx: State<'_, AppContext>,
+    value: Vec<String>,
+) -> Result<(), String> {
+    settings::set_extra_relay_urls(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_transit_mode(ctx: State<'_, AppContext>) -> Result<String, String> {
+    settings::get_transit_mode(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_transit_mode(ctx: State<'_, AppContext>, value: String) -> Result<(), String> {
+    settings::set_transit_mode(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_queue_offline_sends(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_queue_offline_sends(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_queue_offline_sends(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_queue_offline_sends(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_webhook_url(ctx: State<'_, AppContext>) -> Result<Option<String>, String> {
+    settings::get_webhook_url(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_webhook_url(ctx: State<'_, AppContext>, value: Option<String>) -> Result<(), String> {
+    settings::set_webhook_url(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_code_expiry_mins(ctx: State<'_, AppContext>) -> Result<u64, String> {
+    settings::get_code_expiry_mins(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_code_expiry_mins(ctx: State<'_, AppContext>, value: u64) -> Result<(), String> {
+    settings::set_code_expiry_mins(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_offer_expiry_mins(ctx: State<'_, AppContext>) -> Result<u64, String> {
+    settings::get_offer_expiry_mins(&ctx).await
 }
 
-// After the debounce window, dispatch the batch if no newer path arrived and the
-// frontend is listening; otherwise leave it for the next trigger to flush.
-fn schedule_flush(app: AppHandle, generation: u64) {
-    tauri::async_runtime::spawn(async move {
-        tokio::time::sleep(std::time::Duration::from_millis(BATCH_DEBOUNCE_MS)).await;
+#[tauri::command]
+#[specta::specta]
+async fn set_offer_expiry_mins(ctx: State<'_, AppContext>, value: u64) -> Result<(), String> {
+    settings::set_offer_expiry_mins(&ctx, value).await
+}
 
-        let paths = {
-            let queue = app.state::<OsSendQueue>();
-            let mut q = queue.0.lock().unwrap();
-            if q.generation != generation || !q.frontend_ready {
-                return;
-            }
-            std::mem::take(&mut q.paths)
-        };
+#[tauri::command]
+#[specta::specta]
+async fn get_sent_path_privacy(ctx: State<'_, AppContext>) -> Result<String, String> {
+    settings::get_sent_path_privacy(&ctx).await
+}
 
-        if !paths.is_empty() {
-            let _ = app.emit(SEND_FROM_OS_EVENT, paths);
-            show_main_window(&app);
-        }
-    });
+#[tauri::command]
+#[specta::specta]
+async fn set_sent_path_privacy(ctx: State<'_, AppContext>, value: String) -> Result<(), String> {
+    settings::set_sent_path_privacy(&ctx, value).await
 }
 
-pub mod context_menu;
-pub mod files;
-pub mod files_json;
-pub mod settings;
+#[tauri::command]
+#[specta::specta]
+async fn get_skip_duplicate_receives(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_skip_duplicate_receives(&ctx).await
+}
 
-// Secure bindings - these are the only functions exposed to the frontend
-// All actual logic is delegated to the appropriate modules
+#[tauri::command]
+#[specta::specta]
+async fn set_skip_duplicate_receives(
+    ctx: State<'_, AppContext>,
+    value: bool,
+) -> Result<(), String> {
+    settings::set_skip_duplicate_receives(&ctx, value).await
+}
 
 #[tauri::command]
-async fn send_file_call(
-    app_handle: AppHandle,
-    file_path: &str,
-    send_id: String,
-) -> Result<String, String> {
-    files::send_file_call(app_handle, file_path, send_id).await
+#[specta::specta]
+async fn get_verify_disk_writes(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_verify_disk_writes(&ctx).await
 }
 
 #[tauri::command]
-async fn send_multiple_files_call(
-    app_handle: AppHandle,
-    file_paths: Vec<String>,
-    send_id: String,
-    folder_name: Option<String>,
-) -> Result<String, String> {
-    files::send_multiple_files_call(app_handle, file_paths, send_id, folder_name).await
+#[specta::specta]
+async fn set_verify_disk_writes(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_verify_disk_writes(&ctx, value).await
 }
 
 #[tauri::command]
-async fn cancel_send(send_id: String, app_handle: AppHandle) -> Result<String, String> {
-    files::cancel_send(send_id, app_handle).await
+#[specta::specta]
+async fn get_max_incoming_size_mib(ctx: State<'_, AppContext>) -> Result<u64, String> {
+    settings::get_max_incoming_size_mib(&ctx).await
 }
 
 #[tauri::command]
-async fn cancel_download(download_id: String) -> Result<String, String> {
-    files::cancel_download(download_id).await
+#[specta::specta]
+async fn set_max_incoming_size_mib(ctx: State<'_, AppContext>, value: u64) -> Result<(), String> {
+    settings::set_max_incoming_size_mib(&ctx, value).await
 }
 
 #[tauri::command]
-async fn cancel_all_transfers(app_handle: AppHandle) -> Result<String, String> {
-    files::cancel_all_transfers(app_handle).await
+#[specta::specta]
+async fn get_content_allowlist(ctx: State<'_, AppContext>) -> Result<Vec<String>, String> {
+    settings::get_content_allowlist(&ctx).await
 }
 
 #[tauri::command]
-async fn request_file_call(receive_code: &str, connection_id: String) -> Result<String, String> {
-    files::request_file_call(receive_code, connection_id).await
+#[specta::specta]
+async fn set_content_allowlist(
+    ctx: State<'_, AppContext>,
+    value: Vec<String>,
+) -> Result<(), String> {
+    settings::set_content_allowlist(&ctx, value).await
 }
 
 #[tauri::command]
-async fn cancel_connection(connection_id: String) -> Result<String, String> {
-    files::cancel_connection(connection_id).await
+#[specta::specta]
+async fn get_packaging_retry_secs(ctx: State<'_, AppContext>) -> Result<u64, String> {
+    settings::get_packaging_retry_secs(&ctx).await
 }
 
 #[tauri::command]
-async fn receiving_file_accept(id: String, app_handle: AppHandle) -> Result<String, String> {
-    files::receiving_file_accept(id, app_handle).await
+#[specta::specta]
+async fn set_packaging_retry_secs(ctx: State<'_, AppContext>, value: u64) -> Result<(), String> {
+    settings::set_packaging_retry_secs(&ctx, value).await
 }
 
 #[tauri::command]
-async fn receiving_file_deny(id: String) -> Result<String, String> {
-    files::receiving_file_deny(id).await
+#[specta::specta]
+async fn get_package_cache_mib(ctx: State<'_, AppContext>) -> Result<u64, String> {
+    settings::get_package_cache_mib(&ctx).await
 }
 
 #[tauri::command]
-async fn set_download_directory(app_handle: AppHandle, new_path: String) -> Result<(), String> {
-    settings::set_download_directory(app_handle, new_path).await
+#[specta::specta]
+async fn set_package_cache_mib(ctx: State<'_, AppContext>, value: u64) -> Result<(), String> {
+    settings::set_package_cache_mib(&ctx, value).await
 }
 
 #[tauri::command]
-async fn get_download_path(app_handle: AppHandle) -> Result<String, String> {
-    settings::get_download_path(app_handle).await
+#[specta::specta]
+async fn get_package_cache_info(ctx: State<'_, AppContext>) -> Result<PackageCacheInfo, String> {
+    cache::package_cache_info(&ctx).await
 }
 
 #[tauri::command]
-async fn get_auto_extract_tarballs(app_handle: AppHandle) -> Result<bool, String> {
-    settings::get_auto_extract_tarballs(app_handle).await
+#[specta::specta]
+async fn clear_package_cache(ctx: State<'_, AppContext>) -> Result<(), String> {
+    cache::clear_package_cache(&ctx).await
 }
 
 #[tauri::command]
-async fn set_auto_extract_tarballs(app_handle: AppHandle, value: bool) -> Result<(), String> {
-    settings::set_auto_extract_tarballs(app_handle, value).await
+#[specta::specta]
+async fn get_local_api_enabled(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_local_api_enabled(&ctx).await
 }
 
 #[tauri::command]
-async fn get_default_folder_name_format(app_handle: AppHandle) -> Result<String, String> {
-    settings::get_default_folder_name_format(app_handle).await
+#[specta::specta]
+async fn set_local_api_enabled(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_local_api_enabled(&ctx, value).await
 }
 
 #[tauri::command]
-async fn set_default_folder_name_format(
-    app_handle: AppHandle,
-    value: String,
-) -> Result<(), String> {
-    settings::set_default_folder_name_format(app_handle, value).await
+#[specta::specta]
+async fn get_local_api_port(ctx: State<'_, AppContext>) -> Result<u16, String> {
+    settings::get_local_api_port(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_local_api_port(ctx: State<'_, AppContext>, value: u16) -> Result<(), String> {
+    settings::set_local_api_port(&ctx, value).await
 }
 
 #[tauri::command]
-async fn get_relay_server_url(app_handle: AppHandle) -> Result<Option<String>, String> {
-    settings::get_relay_server_url(app_handle).await
+#[specta::specta]
+async fn get_local_api_token(ctx: State<'_, AppContext>) -> Result<String, String> {
+    settings::get_local_api_token(&ctx).await
 }
 
 #[tauri::command]
-async fn set_relay_server_url(app_handle: AppHandle, value: Option<String>) -> Result<(), String> {
-    settings::set_relay_server_url(app_handle, value).await
+#[specta::specta]
+async fn regenerate_local_api_token(ctx: State<'_, AppContext>) -> Result<String, String> {
+    settings::regenerate_local_api_token(&ctx).await
 }
 
 #[tauri::command]
-async fn get_minimize_on_start(app_handle: AppHandle) -> Result<bool, String> {
-    settings::get_minimize_on_start(app_handle).await
+#[specta::specta]
+async fn get_minimize_on_start(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_minimize_on_start(&ctx).await
 }
 
 #[tauri::command]
-async fn set_minimize_on_start(app_handle: AppHandle, value: bool) -> Result<(), String> {
-    settings::set_minimize_on_start(app_handle, value).await
+#[specta::specta]
+async fn set_minimize_on_start(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_minimize_on_start(&ctx, value).await
 }
 
 #[tauri::command]
-async fn get_minimize_on_close(app_handle: AppHandle) -> Result<bool, String> {
-    settings::get_minimize_on_close(app_handle).await
+#[specta::specta]
+async fn get_minimize_on_close(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_minimize_on_close(&ctx).await
 }
 
 #[tauri::command]
-async fn set_minimize_on_close(app_handle: AppHandle, value: bool) -> Result<(), String> {
-    settings::set_minimize_on_close(app_handle.clone(), value).await?;
+#[specta::specta]
+async fn set_minimize_on_close(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    value: bool,
+) -> Result<(), String> {
+    settings::set_minimize_on_close(&ctx, value).await?;
     // Keep the sync mirror used by the close handler in step with the setting.
     app_handle
         .state::<MinimizeOnClose>()
@@ -230,6 +1395,7 @@ async fn set_minimize_on_close(app_handle: AppHandle, value: bool) -> Result<(),
 }
 
 #[tauri::command]
+#[specta::specta]
 async fn get_autostart(app_handle: AppHandle) -> Result<bool, String> {
     // Source of truth is the OS (registry / launch agent), not settings.json.
     app_handle
@@ -239,6 +1405,7 @@ async fn get_autostart(app_handle: AppHandle) -> Result<bool, String> {
 }
 
 #[tauri::command]
+#[specta::specta]
 async fn set_autostart(app_handle: AppHandle, value: bool) -> Result<(), String> {
     let manager = app_handle.autolaunch();
     if value {
@@ -249,39 +1416,324 @@ async fn set_autostart(app_handle: AppHandle, value: bool) -> Result<(), String>
 }
 
 #[tauri::command]
-async fn received_files_data(app_handle: AppHandle) -> Result<Vec<serde_json::Value>, String> {
-    let files = files_json::get_received_files_json_data(app_handle).await?;
-    Ok(files)
+#[specta::specta]
+async fn received_files_data(
+    ctx: State<'_, AppContext>,
+) -> Result<Vec<files_json::ReceivedFile>, String> {
+    files_json::get_received_files_json_data(&ctx).await
 }
 
+// Progress arrives as `export-progress` events tagged with `id`; with a `passphrase` the
+// export is an age-encrypted file.
 #[tauri::command]
+#[specta::specta]
 async fn export_received_files_json(
     app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    file_path: String,
+    range: Option<ExportRange>,
+    passphrase: Option<String>,
+    id: String,
+) -> Result<(), CommandError> {
+    scope::check_export_path(&app_handle, &ctx, &file_path).await?;
+    let range = range.unwrap_or_default();
+    Ok(settings::export_received_files_json(&ctx, file_path, range, passphrase, id).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn sent_files_data(ctx: State<'_, AppContext>) -> Result<Vec<files_json::SentFile>, String> {
+    files_json::get_sent_files_json_data(&ctx).await
+}
+
+// Whether sent entry `id`'s files changed since they were sent (see `SentFile::snapshots`).
+#[tauri::command]
+#[specta::specta]
+async fn verify_sent_entry(
+    ctx: State<'_, AppContext>,
+    id: String,
+) -> Result<Vec<files_json::SentPathCheck>, String> {
+    files_json::verify_sent_entry(&ctx, &id).await
+}
+
+// Whether received entry `id`'s file still matches the checksum taken when it arrived.
+#[tauri::command]
+#[specta::specta]
+async fn verify_received_file(ctx: State<'_, AppContext>, id: String) -> Result<bool, String> {
+    files_json::verify_received_file(&ctx, &id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn export_sent_files_json(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    file_path: String,
+    range: Option<ExportRange>,
+    passphrase: Option<String>,
+    id: String,
+) -> Result<(), CommandError> {
+    scope::check_export_path(&app_handle, &ctx, &file_path).await?;
+    let range = range.unwrap_or_default();
+    Ok(settings::export_sent_files_json(&ctx, file_path, range, passphrase, id).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn export_history_html(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    file_path: String,
+) -> Result<(), CommandError> {
+    scope::check_export_path(&app_handle, &ctx, &file_path).await?;
+    Ok(report::export_history_html(&ctx, file_path).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_usage_metrics_enabled(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_usage_metrics_enabled(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_usage_metrics_enabled(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_usage_metrics_enabled(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_check_updates_automatically(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_check_updates_automatically(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_check_updates_automatically(
+    ctx: State<'_, AppContext>,
+    value: bool,
+) -> Result<(), String> {
+    settings::set_check_updates_automatically(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_release_channel(ctx: State<'_, AppContext>) -> Result<String, String> {
+    settings::get_release_channel(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_release_channel(ctx: State<'_, AppContext>, value: String) -> Result<(), String> {
+    settings::set_release_channel(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn check_for_updates(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+) -> Result<UpdateInfo, String> {
+    let version = app_handle.package_info().version.to_string();
+    let channel = settings::get_release_channel(&ctx).await?;
+    updates::check_for_updates(&version, &channel).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_auto_download_updates(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_auto_download_updates(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_auto_download_updates(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_auto_download_updates(&ctx, value).await
+}
+
+// Download the latest signed update, emitting `update-download-progress` as it arrives.
+// Returns the new version, or None when already up to date.
+#[tauri::command]
+#[specta::specta]
+async fn download_update(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+) -> Result<Option<String>, String> {
+    updater::download(&app_handle, &ctx).await
+}
+
+// Install the update fetched by `download_update` and restart into it.
+#[tauri::command]
+#[specta::specta]
+async fn install_update(app_handle: AppHandle) -> Result<(), String> {
+    updater::install(&app_handle).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_crash_reports_enabled(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_crash_reports_enabled(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_crash_reports_enabled(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_crash_reports_enabled(&ctx, value).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_sounds_enabled(ctx: State<'_, AppContext>) -> Result<bool, String> {
+    settings::get_sounds_enabled(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_sounds_enabled(ctx: State<'_, AppContext>, value: bool) -> Result<(), String> {
+    settings::set_sounds_enabled(&ctx, value).await?;
+    sounds::set_enabled(value);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_sound_volume(ctx: State<'_, AppContext>) -> Result<u8, String> {
+    settings::get_sound_volume(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_sound_volume(ctx: State<'_, AppContext>, value: u8) -> Result<(), String> {
+    settings::set_sound_volume(&ctx, value).await?;
+    sounds::set_volume(value);
+    Ok(())
+}
+
+// Plays the completion sound at the current volume, even while sounds are off.
+#[tauri::command]
+#[specta::specta]
+fn play_sound_preview() {
+    sounds::play_now(Sound::Complete);
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn list_crash_reports(ctx: State<'_, AppContext>) -> Result<Vec<CrashReport>, String> {
+    crash::list_crash_reports(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn export_crash_report(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    file_name: String,
+    file_path: String,
+) -> Result<(), CommandError> {
+    scope::check_export_path(&app_handle, &ctx, &file_path).await?;
+    Ok(crash::export_crash_report(&ctx, file_name, file_path).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn clear_crash_reports(ctx: State<'_, AppContext>) -> Result<(), String> {
+    crash::clear_crash_reports(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_usage_metrics(ctx: State<'_, AppContext>) -> Result<UsageMetrics, String> {
+    metrics::get_usage_metrics(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn export_usage_metrics(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
     file_path: String,
+) -> Result<(), CommandError> {
+    scope::check_export_path(&app_handle, &ctx, &file_path).await?;
+    Ok(metrics::export_usage_metrics(&ctx, file_path).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn reset_usage_metrics(ctx: State<'_, AppContext>) -> Result<(), String> {
+    metrics::reset_usage_metrics(&ctx).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn list_profiles(app_handle: AppHandle) -> Result<Vec<String>, String> {
+    profiles::list_profiles(&base_paths(&app_handle))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_active_profile(app_handle: AppHandle) -> Result<String, String> {
+    Ok(profiles::active_profile(&base_paths(&app_handle)))
+}
+
+// Settings and history are loaded once at startup, so switching restarts the app into
+// the new profile.
+#[tauri::command]
+#[specta::specta]
+async fn switch_profile(
+    app_handle: AppHandle,
+    ctx: State<'_, AppContext>,
+    name: String,
 ) -> Result<(), String> {
-    settings::export_received_files_json(app_handle, file_path).await
+    profiles::switch_profile(ctx.inner(), &base_paths(&app_handle), &name).await?;
+    app_handle.restart();
 }
 
 #[tauri::command]
-async fn sent_files_data(app_handle: AppHandle) -> Result<Vec<serde_json::Value>, String> {
-    let files = files_json::get_sent_files_json_data(app_handle).await?;
-    Ok(files)
+#[specta::specta]
+async fn test_relay_server(ctx: State<'_, AppContext>) -> Result<String, String> {
+    files::test_relay_server(ctx.inner().clone()).await
 }
 
+// Times each configured relay and saves them fastest first.
 #[tauri::command]
-async fn export_sent_files_json(app_handle: AppHandle, file_path: String) -> Result<(), String> {
-    settings::export_sent_files_json(app_handle, file_path).await
+#[specta::specta]
+async fn benchmark_relays(
+    ctx: State<'_, AppContext>,
+) -> Result<Vec<relays::RelayBenchmark>, String> {
+    relays::benchmark_relays(ctx.inner().clone()).await
+}
+
+// The user pressed Send on a selection that came from a `wyrmhole://send` URL: approve those
+// of `paths` the link asked for, so the send passes the scope check. Paths the user removed
+// from the selection stay unapproved.
+#[tauri::command]
+#[specta::specta]
+fn confirm_link_send(app: AppHandle, paths: Vec<String>) -> Result<(), String> {
+    let awaiting = {
+        let queue = app.state::<OsSendQueue>();
+        let mut q = queue.0.lock().unwrap();
+        std::mem::take(&mut q.awaiting_confirmation)
+    };
+    if awaiting.is_empty() {
+        return Err("No send from a link is waiting".to_string());
+    }
+    scope::grant(&app, paths.iter().filter(|path| awaiting.contains(path)));
+    Ok(())
 }
 
+// The user cleared a selection that came from a `wyrmhole://send` URL.
 #[tauri::command]
-async fn test_relay_server(app_handle: AppHandle) -> Result<String, String> {
-    files::test_relay_server(app_handle).await
+#[specta::specta]
+fn discard_link_send(app: AppHandle) {
+    let queue = app.state::<OsSendQueue>();
+    queue.0.lock().unwrap().awaiting_confirmation.clear();
 }
 
 // Called by the frontend once its `send-files-from-os` listener is attached.
 // Marks the queue ready and triggers a flush so any paths buffered during a
 // cold start get dispatched as one batch.
 #[tauri::command]
+#[specta::specta]
 fn frontend_ready(app: AppHandle) {
     let generation = {
         let queue = app.state::<OsSendQueue>();
@@ -296,6 +1748,7 @@ fn frontend_ready(app: AppHandle) {
 // Whether the OS "Send via wyrmhole" context-menu entry is currently registered
 // for this user. Reads live OS state so the Settings toggle reflects reality.
 #[tauri::command]
+#[specta::specta]
 fn get_context_menu_enabled() -> Result<bool, String> {
     context_menu::is_enabled()
 }
@@ -303,10 +1756,24 @@ fn get_context_menu_enabled() -> Result<bool, String> {
 // Opt-in registration of the context-menu entry, driven by the Settings toggle.
 // The installer never modifies this; only an explicit user action does.
 #[tauri::command]
+#[specta::specta]
 fn set_context_menu_enabled(value: bool) -> Result<(), String> {
     context_menu::set_enabled(value)
 }
 
+// Pops a send or download out into its own small window (see transfer_windows.rs).
+// Async so the window isn't created on the main thread, which deadlocks on Windows.
+#[tauri::command]
+#[specta::specta]
+async fn open_transfer_window(
+    app: AppHandle,
+    id: String,
+    kind: String,
+    file_name: String,
+) -> Result<(), String> {
+    transfer_windows::open(&app, &id, &kind, &file_name)
+}
+
 // Reveal and focus the main window (used by the tray menu and left-click).
 fn show_main_window(app: &AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
@@ -316,8 +1783,252 @@ fn show_main_window(app: &AppHandle) {
     }
 }
 
+// Collects every command and event payload type so tauri-specta can serve the
+// invoke handler and generate matching TypeScript bindings for the frontend.
+fn specta_builder() -> tauri_specta::Builder<tauri::Wry> {
+    tauri_specta::Builder::<tauri::Wry>::new()
+        .commands(tauri_specta::collect_commands![
+            send_file_call,
+            send_multiple_files_call,
+            send_encrypted_archive_call,
+            send_changes_call,
+            reset_sync,
+            send_clipboard,
+            capture_and_send,
+            reveal_code,
+            compose_code_share,
+            compute_path_size,
+            cancel_path_size,
+            pick_folder_for_send,
+            pick_directory,
+            pick_files_for_send,
+            pick_export_path,
+            check_sensitive_paths,
+            cancel_send,
+            list_packaging_jobs,
+            retry_packaging_job,
+            cancel_packaging_job,
+            cancel_download,
+            cancel_all_transfers,
+            active_transfers,
+            request_file_call,
+            send_reply_call,
+            send_back,
+            remove_received_entry,
+            stage_drag_out,
+            await_reply_call,
+            cancel_connection,
+            receiving_file_accept,
+            receiving_file_deny,
+            accept_all_pending,
+            deny_all_pending,
+            open_received_file,
+            show_in_folder,
+            set_download_directory,
+            received_files_data,
+            sent_files_data,
+            get_download_path,
+            get_history_directory,
+            set_history_directory,
+            get_auto_extract_tarballs,
+            set_auto_extract_tarballs,
+            get_date_subfolders,
+            set_date_subfolders,
+            get_receive_hooks,
+            set_receive_hooks,
+            get_save_text_offers,
+            set_save_text_offers,
+            get_default_folder_name_format,
+            set_default_folder_name_format,
+            get_received_file_name_template,
+            set_received_file_name_template,
+            get_relay_server_url,
+            set_relay_server_url,
+            list_relay_presets,
+            apply_relay_preset,
+            reset_relay_server,
+            get_download_rate_limit_kib,
+            set_download_rate_limit_kib,
+            get_upload_rate_limit_kib,
+            set_upload_rate_limit_kib,
+            get_rate_limit_schedule,
+            set_rate_limit_schedule,
+            get_max_concurrent_sends,
+            set_max_concurrent_sends,
+            get_hide_send_codes,
+            set_hide_send_codes,
+            get_idle_timeout_secs,
+            set_idle_timeout_secs,
+            get_merge_folder_updates,
+            set_merge_folder_updates,
+            get_apply_file_deltas,
+            set_apply_file_deltas,
+            get_compression_level,
+            set_compression_level,
+            get_deterministic_archives,
+            set_deterministic_archives,
+            get_stream_archives,
+            set_stream_archives,
+            get_archive_part_size_mib,
+            set_archive_part_size_mib,
+            get_extra_relay_urls,
+            set_extra_relay_urls,
+            get_transit_mode,
+            set_transit_mode,
+            get_queue_offline_sends,
+            set_queue_offline_sends,
+            get_webhook_url,
+            set_webhook_url,
+            get_code_expiry_mins,
+            set_code_expiry_mins,
+            get_offer_expiry_mins,
+            set_offer_expiry_mins,
+            get_sent_path_privacy,
+            set_sent_path_privacy,
+            get_skip_duplicate_receives,
+            set_skip_duplicate_receives,
+            get_verify_disk_writes,
+            set_verify_disk_writes,
+            get_max_incoming_size_mib,
+            set_max_incoming_size_mib,
+            get_content_allowlist,
+            set_content_allowlist,
+            get_packaging_retry_secs,
+            set_packaging_retry_secs,
+            get_package_cache_mib,
+            set_package_cache_mib,
+            get_package_cache_info,
+            clear_package_cache,
+            get_local_api_enabled,
+            set_local_api_enabled,
+            get_local_api_port,
+            set_local_api_port,
+            get_local_api_token,
+            regenerate_local_api_token,
+            get_minimize_on_start,
+            set_minimize_on_start,
+            get_minimize_on_close,
+            set_minimize_on_close,
+            get_autostart,
+            set_autostart,
+            export_received_files_json,
+            export_sent_files_json,
+            verify_sent_entry,
+            verify_received_file,
+            export_history_html,
+            get_usage_metrics_enabled,
+            set_usage_metrics_enabled,
+            get_usage_metrics,
+            export_usage_metrics,
+            reset_usage_metrics,
+            get_check_updates_automatically,
+            set_check_updates_automatically,
+            get_release_channel,
+            set_release_channel,
+            check_for_updates,
+            get_auto_download_updates,
+            set_auto_download_updates,
+            download_update,
+            install_update,
+            get_crash_reports_enabled,
+            set_crash_reports_enabled,
+            list_crash_reports,
+            export_crash_report,
+            clear_crash_reports,
+            get_sounds_enabled,
+            set_sounds_enabled,
+            get_sound_volume,
+            set_sound_volume,
+            play_sound_preview,
+            list_profiles,
+            get_active_profile,
+            switch_profile,
+            test_relay_server,
+            benchmark_relays,
+            frontend_ready,
+            confirm_link_send,
+            discard_link_send,
+            open_transfer_window,
+            get_context_menu_enabled,
+            set_context_menu_enabled
+        ])
+        .typ::<events::SendProgress>()
+        .typ::<events::SendError>()
+        .typ::<events::ConnectionCode>()
+        .typ::<events::DownloadProgress>()
+        .typ::<events::DownloadError>()
+        .typ::<events::DownloadComplete>()
+        .typ::<events::TextOffer>()
+        .typ::<events::TransferStalled>()
+        .typ::<events::NetworkChanged>()
+        .typ::<events::CodeAge>()
+        .typ::<events::CodeExpired>()
+        .typ::<events::PeerConnected>()
+        .typ::<events::OfferDeclined>()
+        .typ::<events::OfferExpired>()
+        .typ::<events::DuplicateDetected>()
+        .typ::<events::ContentFlagged>()
+        .typ::<events::VerificationComplete>()
+        .typ::<events::PathSizeProgress>()
+        .typ::<events::PreparingDetails>()
+        .typ::<events::FilesSkipped>()
+        .typ::<events::PackagingJob>()
+        .typ::<TransferSnapshot>()
+        .typ::<sizes::PathSize>()
+        .typ::<sizes::FolderForSend>()
+        .typ::<settings::RelayPreset>()
+        .typ::<relays::RelayBenchmark>()
+        .typ::<share::CodeShare>()
+        .typ::<events::ReceivedFileAdded>()
+        .typ::<events::ReceivedFilesAdded>()
+        .typ::<events::SentFileAdded>()
+        .typ::<events::DefaultFolderNameFormatUpdated>()
+        .typ::<updater::UpdateDownloadProgress>()
+        .constant("SEND_PROGRESS", events::SEND_PROGRESS)
+        .constant("SEND_ERROR", events::SEND_ERROR)
+        .constant("CONNECTION_CODE", events::CONNECTION_CODE)
+        .constant("DOWNLOAD_PROGRESS", events::DOWNLOAD_PROGRESS)
+        .constant("DOWNLOAD_ERROR", events::DOWNLOAD_ERROR)
+        .constant("DOWNLOAD_COMPLETE", events::DOWNLOAD_COMPLETE)
+        .constant("TEXT_OFFER", events::TEXT_OFFER)
+        .constant("TRANSFER_STALLED", events::TRANSFER_STALLED)
+        .constant("NETWORK_CHANGED", events::NETWORK_CHANGED)
+        .constant("CODE_AGE", events::CODE_AGE)
+        .constant("CODE_EXPIRED", events::CODE_EXPIRED)
+        .constant("PEER_CONNECTED", events::PEER_CONNECTED)
+        .constant("OFFER_DECLINED", events::OFFER_DECLINED)
+        .constant("OFFER_EXPIRED", events::OFFER_EXPIRED)
+        .constant("DUPLICATE_DETECTED", events::DUPLICATE_DETECTED)
+        .constant("CONTENT_FLAGGED", events::CONTENT_FLAGGED)
+        .constant("VERIFICATION_COMPLETE", events::VERIFICATION_COMPLETE)
+        .constant("PATH_SIZE_PROGRESS", events::PATH_SIZE_PROGRESS)
+        .constant("PREPARING_DETAILS", events::PREPARING_DETAILS)
+        .constant("FILES_SKIPPED", events::FILES_SKIPPED)
+        .constant("PACKAGING_JOB", events::PACKAGING_JOB)
+        .constant("RECEIVED_FILE_ADDED", events::RECEIVED_FILE_ADDED)
+        .constant("RECEIVED_FILES_ADDED", events::RECEIVED_FILES_ADDED)
+        .constant("SENT_FILE_ADDED", events::SENT_FILE_ADDED)
+        .constant(
+            "DEFAULT_FOLDER_NAME_FORMAT_UPDATED",
+            events::DEFAULT_FOLDER_NAME_FORMAT_UPDATED,
+        )
+        .constant("SEND_FROM_OS_EVENT", SEND_FROM_OS_EVENT)
+        .constant("SEND_REQUEST_FROM_LINK_EVENT", SEND_REQUEST_FROM_LINK_EVENT)
+        .constant(
+            "UPDATE_DOWNLOAD_PROGRESS",
+            updater::UPDATE_DOWNLOAD_PROGRESS,
+        )
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // `--headless` runs the transfer engine over stdin/stdout JSON-RPC without
+    // creating a webview, for scripting and unattended receive boxes.
+    if std::env::args().any(|a| a == "--headless") {
+        headless::run();
+        return;
+    }
+
     // WebKitGTK's DMABUF renderer causes a blank window on some Linux setups
     // (certain GPU drivers, compositors, and VMs — e.g. KDE/X11 on Debian).
     // Disable it unless the user has set the variable themselves, so the
@@ -332,26 +2043,73 @@ pub fn run() {
         }
     }
 
+    let specta_builder = specta_builder();
+
+    // Regenerate the frontend bindings on every debug run so the TypeScript
+    // types always match the Rust payloads; the result is committed so release
+    // builds don't depend on a debug run. u64 sizes are exported as `number`.
+    #[cfg(debug_assertions)]
+    specta_builder
+        .export(
+            specta_typescript::Typescript::default()
+                .header("// @ts-nocheck")
+                .bigint(specta_typescript::BigIntExportBehavior::Number),
+            "../src/bindings.ts",
+        )
+        .expect("Failed to export TypeScript bindings");
+
     tauri::Builder::default()
         // Must be the FIRST plugin registered. When a second launch happens
         // (e.g. the user picks "Send via wyrmhole" while the app is already in
         // the tray), its argv is forwarded here instead of starting a new
-        // process; we turn the paths into a send on the existing instance.
-        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+        // process, so only one instance ever owns the settings and history.
+        // The existing window is raised and any paths become a send on it;
+        // relative paths are resolved against the second launch's directory.
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            show_main_window(app);
+            let argv = resolve_relative_args(&argv, std::path::Path::new(&cwd));
             enqueue_os_paths(app, extract_file_paths(&argv));
         }))
+        // `wyrmhole://` automation URLs and `wormhole-transfer:` / `wormhole:` code links
+        // (see automation.rs). With single-instance's `deep-link` feature, URLs opened while
+        // the app runs reach `on_open_url` below.
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_drag::init())
         .plugin(tauri_plugin_window_state::Builder::default().build())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             None,
         ))
+        .plugin(tauri_plugin_updater::Builder::new().build())
         .setup(|app| {
-            let app_settings = settings::init_settings(app.handle());
+            // `--demo` swaps real transfers for a simulator (see wyrmhole_core::demo) and
+            // keeps its settings, history and downloads out of the real ones.
+            let demo_mode = std::env::args().any(|a| a == "--demo");
+            let paths = profiles::resolve_paths(&base_paths(app.handle()));
+            let store = Arc::new(JsonSettingsStore::new(paths.clone()));
+            let app_settings = store.load();
+            // Crash reports land in the data directory; the hook only writes while
+            // the setting is on.
+            crash::install_panic_hook(&paths, env!("CARGO_PKG_VERSION"));
+            crash::set_enabled(app_settings.get_crash_reports_enabled());
+            sounds::set_enabled(app_settings.get_sounds_enabled());
+            sounds::set_volume(app_settings.get_sound_volume());
             let minimize_on_start = app_settings.get_minimize_on_start();
             let minimize_on_close = app_settings.get_minimize_on_close();
-            app.manage(Mutex::new(app_settings));
+            let mut ctx = AppContext::new(
+                Arc::new(TauriEvents(app.handle().clone())),
+                paths,
+                store,
+                app_settings,
+            );
+            if demo_mode {
+                ctx = ctx.with_wormhole(Arc::new(DemoWormhole::default()));
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.set_title("wyrmhole (demo)");
+                }
+            }
 
             // Sync mirror read by the (non-async) window-close handler.
             app.manage(MinimizeOnClose(Arc::new(AtomicBool::new(
@@ -364,12 +2122,38 @@ pub fn run() {
             let launch_paths = extract_file_paths(&std::env::args().collect::<Vec<_>>());
             let launched_with_files = !launch_paths.is_empty();
             app.manage(OsSendQueue::default());
+            app.manage(scope::ApprovedPaths::default());
+            app.manage(updater::PendingUpdate::default());
             if launched_with_files {
                 enqueue_os_paths(app.handle(), launch_paths);
             }
 
-            files_json::init_received_files(app.handle());
-            files_json::init_sent_files(app.handle());
+            // Installed builds register the schemes at install time; this covers dev runs
+            // and portable Linux/Windows builds.
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                eprintln!("Failed to register URL schemes: {}", e);
+            }
+            let deep_link_app = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                handle_automation_urls(&deep_link_app, event.urls());
+            });
+            // A URL that cold-started the app.
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                handle_automation_urls(app.handle(), urls);
+            }
+
+            files_json::init_received_files(&ctx);
+            files_json::init_sent_files(&ctx);
+
+            // The localhost API for other apps, when the user has turned it on.
+            let api_ctx = ctx.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = api::restart(&api_ctx).await {
+                    eprintln!("{}", e);
+                }
+            });
+            app.manage(ctx);
 
             // System tray: a menu with Show / Quit, plus left-click to reveal.
             let show_item = MenuItem::with_id(app, "show", "Show wyrmhole", true, None::<&str>)?;
@@ -417,7 +2201,14 @@ pub fn run() {
         // tray instead of quitting; otherwise the close proceeds and the app exits.
         // The tray's "Quit" item always exits.
         .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { api, .. } = event {
+            // Dropped files and folders are approved for sending (see scope.rs).
+            if let WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) = event {
+                scope::grant(window.app_handle(), paths);
+            }
+            // Transfer windows (see transfer_windows.rs) just close.
+            if let WindowEvent::CloseRequested { api, .. } = event
+                && !transfer_windows::is_transfer_window(window.label())
+            {
                 let minimize_on_close = window
                     .app_handle()
                     .state::<MinimizeOnClose>()
@@ -439,39 +2230,7 @@ pub fn run() {
             }
         })
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![
-            send_file_call,
-            send_multiple_files_call,
-            cancel_send,
-            cancel_download,
-            cancel_all_transfers,
-            request_file_call,
-            cancel_connection,
-            receiving_file_accept,
-            receiving_file_deny,
-            set_download_directory,
-            received_files_data,
-            sent_files_data,
-            get_download_path,
-            get_auto_extract_tarballs,
-            set_auto_extract_tarballs,
-            get_default_folder_name_format,
-            set_default_folder_name_format,
-            get_relay_server_url,
-            set_relay_server_url,
-            get_minimize_on_start,
-            set_minimize_on_start,
-            get_minimize_on_close,
-            set_minimize_on_close,
-            get_autostart,
-            set_autostart,
-            export_received_files_json,
-            export_sent_files_json,
-            test_relay_server,
-            frontend_ready,
-            get_context_menu_enabled,
-            set_context_menu_enabled
-        ])
+        .invoke_handler(specta_builder.invoke_handler())
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|_app_handle, _event| {
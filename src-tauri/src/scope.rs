@@ -0,0 +1,300 @@
+// Path scoping for the commands that read or write wherever the frontend says: send paths,
+// export destinations, folders to receive into and the folders of "move to" rules must
+// resolve inside somewhere the user approved, so a compromised or buggy webview can't
+// quietly send ~/.ssh or write files outside what was picked.
+//
+// Approved means one of:
+//   - a path the user handed over through the backend's own dialogs (`pick_files_for_send`,
+//     `pick_folder_for_send`, `pick_export_path`), a drag-and-drop onto the window, or the
+//     OS ("Send via wyrmhole" entries, Finder "Open With");
+//   - a path from a `wyrmhole://send` URL, once the user has pressed Send on it (any page
+//     can open such a URL, so it's only a suggestion until then);
+//   - one of the configured directories: downloads, history, and the app's own data
+//     directory. The download and history directories can only be changed to a folder
+//     picked in `pick_directory` or already in scope, so the webview can't widen the scope
+//     by pointing them at `/` or `~/.ssh`.
+//
+// Independently of that, anything inside another user's home directory is refused, and so
+// are the profiles' settings.json files (and folders containing them), which hold the local
+// API token; on macOS and Windows they sit in the data directory.
+
+use serde::Serialize;
+use specta::Type;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use wyrmhole_core::context::AppContext;
+use wyrmhole_core::profiles;
+use wyrmhole_core::sensitive::SensitivePath;
+
+use crate::base_paths;
+
+/// Why a path was refused. Sent to the frontend as `code` on a `ScopeError`.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScopeErrorCode {
+    /// Relative paths have nothing to resolve against.
+    NotAbsolute,
+    /// The path (or the folder an export would be written to) doesn't exist.
+    Unresolvable,
+    /// The path is inside someone else's home directory.
+    OtherUsersHome,
+    /// The path wasn't picked, dropped or configured by the user.
+    OutsideScope,
+    /// The path is, or contains, one of wyrmhole's settings files.
+    AppConfig,
+}
+
+impl ScopeErrorCode {
+    pub fn message(self) -> &'static str {
+        match self {
+            ScopeErrorCode::NotAbsolute => "Paths must be absolute.",
+            ScopeErrorCode::Unresolvable => "That location doesn't exist.",
+            ScopeErrorCode::OtherUsersHome => "That location is in another user's home folder.",
+            ScopeErrorCode::OutsideScope => {
+                "That location wasn't chosen in wyrmhole. Pick it with the file dialog or drop it on the window."
+            }
+            ScopeErrorCode::AppConfig => {
+                "That is or contains wyrmhole's settings file, which holds its access token."
+            }
+        }
+    }
+}
+
+/// A refused path, as the frontend receives it.
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct ScopeError {
+    pub code: ScopeErrorCode,
+    pub path: String,
+    pub message: String,
+}
+
+impl ScopeError {
+    fn new(code: ScopeErrorCode, path: &Path) -> Self {
+        ScopeError {
+            code,
+            path: path.to_string_lossy().into_owned(),
+            message: code.message().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ScopeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.path)
+    }
+}
+
+/// Error of the commands that check paths: a refusal arrives as a `ScopeError` object, a
+/// send that needs confirming as `{ sensitive: [...] }`, anything else as the usual string.
+#[derive(Debug, Serialize, Type)]
+#[serde(untagged)]
+pub enum CommandError {
+    Scope(ScopeError),
+    /// The selection looks sensitive (see wyrmhole-core's sensitive.rs); resend with
+    /// `confirm_sensitive` once the user agrees.
+    Sensitive {
+        sensitive: Vec<SensitivePath>,
+    },
+    Other(String),
+}
+
+impl From<ScopeError> for CommandError {
+    fn from(e: ScopeError) -> Self {
+        CommandError::Scope(e)
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(e: String) -> Self {
+        CommandError::Other(e)
+    }
+}
+
+/// Paths the user approved this session, canonicalized. Managed state.
+#[derive(Default)]
+pub struct ApprovedPaths(Mutex<Vec<PathBuf>>);
+
+impl ApprovedPaths {
+    /// Approve `paths` (and everything under them). Paths that don't resolve are skipped.
+    pub fn grant<P: AsRef<Path>>(&self, paths: impl IntoIterator<Item = P>) {
+        let mut approved = self.0.lock().unwrap();
+        for path in paths {
+            if let Ok(path) = std::fs::canonicalize(path.as_ref())
+                && !approved.contains(&path)
+            {
+                approved.push(path);
+            }
+        }
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Approve `paths` (and everything under them) for sends and exports. Paths that don't
+/// resolve are skipped.
+pub fn grant<P: AsRef<Path>>(app: &AppHandle, paths: impl IntoIterator<Item = P>) {
+    app.state::<ApprovedPaths>().grant(paths);
+}
+
+/// Where paths may and may not resolve: under one of `roots`, not at or above one of the
+/// `excluded` files, and not in another user's home (relative to `home`).
+pub struct Scope {
+    pub roots: Vec<PathBuf>,
+    pub excluded: Vec<PathBuf>,
+    pub home: Option<PathBuf>,
+}
+
+impl Scope {
+    /// Check the existing `path`: it must be absolute and, once `..` and symlinks are
+    /// resolved, in scope. Returns the resolved path.
+    pub fn resolve(&self, path: &Path) -> Result<PathBuf, ScopeError> {
+        if !path.is_absolute() {
+            return Err(ScopeError::new(ScopeErrorCode::NotAbsolute, path));
+        }
+        let resolved = std::fs::canonicalize(path)
+            .map_err(|_| ScopeError::new(ScopeErrorCode::Unresolvable, path))?;
+        self.check(&resolved)?;
+        Ok(resolved)
+    }
+
+    /// Check the already resolved `path`.
+    pub fn check(&self, path: &Path) -> Result<(), ScopeError> {
+        if self
+            .home
+            .as_deref()
+            .is_some_and(|home| in_other_users_home(path, home))
+        {
+            return Err(ScopeError::new(ScopeErrorCode::OtherUsersHome, path));
+        }
+        if self
+            .excluded
+            .iter()
+            .any(|file| path.starts_with(file) || file.starts_with(path))
+        {
+            return Err(ScopeError::new(ScopeErrorCode::AppConfig, path));
+        }
+        if !self.roots.iter().any(|root| path.starts_with(root)) {
+            return Err(ScopeError::new(ScopeErrorCode::OutsideScope, path));
+        }
+        Ok(())
+    }
+}
+
+// The scope right now: the session's grants plus the configured directories.
+async fn current(app: &AppHandle, ctx: &AppContext) -> Scope {
+    let mut roots = app.state::<ApprovedPaths>().paths();
+    let download_dir = ctx.settings().lock().await.get_download_directory().clone();
+    let configured = [
+        download_dir,
+        ctx.history_dir(),
+        ctx.paths().data_dir.clone(),
+    ];
+    roots.extend(
+        configured
+            .iter()
+            .filter_map(|dir| std::fs::canonicalize(dir).ok()),
+    );
+    Scope {
+        roots,
+        excluded: profiles::settings_files(&base_paths(app))
+            .iter()
+            .filter_map(|file| std::fs::canonicalize(file).ok())
+            .collect(),
+        home: home_dir(app),
+    }
+}
+
+/// True when `path` is under the directory holding home folders (`/home`, `/Users`,
+/// `C:\Users`) but not under `home` itself or a shared folder there.
+pub fn in_other_users_home(path: &Path, home: &Path) -> bool {
+    // A home at the filesystem root's top level (`/root`) says nothing about where
+    // other users live.
+    let Some(users_root) = home.parent().filter(|root| root.parent().is_some()) else {
+        return false;
+    };
+    if path.starts_with(home) {
+        return false;
+    }
+    match path
+        .strip_prefix(users_root)
+        .ok()
+        .map(|rest| rest.components().next())
+    {
+        Some(Some(Component::Normal(name))) => {
+            let name = name.to_string_lossy().to_lowercase();
+            name != "shared" && name != "public"
+        }
+        _ => false,
+    }
+}
+
+fn home_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .home_dir()
+        .ok()
+        .and_then(|home| std::fs::canonicalize(home).ok())
+}
+
+/// Check paths about to be sent: each must exist and resolve inside an approved location.
+pub async fn check_send_paths(
+    app: &AppHandle,
+    ctx: &AppContext,
+    paths: &[String],
+) -> Result<(), ScopeError> {
+    let scope = current(app, ctx).await;
+    for path in paths {
+        scope.resolve(Path::new(path))?;
+    }
+    Ok(())
+}
+
+/// Check a folder about to become the download or history directory, or to receive or move
+/// files into: it must be in scope already, usually by having been picked in `pick_directory`.
+pub async fn check_directory(
+    app: &AppHandle,
+    ctx: &AppContext,
+    path: &str,
+) -> Result<(), ScopeError> {
+    current(app, ctx).await.resolve(Path::new(path)).map(|_| ())
+}
+
+/// Check an export destination. The file usually doesn't exist yet, so its folder is
+/// resolved instead and the destination itself must be one the save dialog approved or
+/// lie in a configured directory.
+pub async fn check_export_path(
+    app: &AppHandle,
+    ctx: &AppContext,
+    file_path: &str,
+) -> Result<(), ScopeError> {
+    let path = Path::new(file_path);
+    if !path.is_absolute() {
+        return Err(ScopeError::new(ScopeErrorCode::NotAbsolute, path));
+    }
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(ScopeError::new(ScopeErrorCode::Unresolvable, path));
+    };
+    let resolved = std::fs::canonicalize(parent)
+        .map_err(|_| ScopeError::new(ScopeErrorCode::Unresolvable, path))?
+        .join(name);
+    current(app, ctx).await.check(&resolved)
+}
+
+/// Approve a destination chosen in the save dialog. It's recorded by folder plus name,
+/// since the file itself doesn't exist until the export writes it.
+pub fn grant_export_path(app: &AppHandle, file_path: &Path) {
+    let (Some(parent), Some(name)) = (file_path.parent(), file_path.file_name()) else {
+        return;
+    };
+    if let Ok(parent) = std::fs::canonicalize(parent) {
+        let approved = app.state::<ApprovedPaths>();
+        let mut approved = approved.0.lock().unwrap();
+        let path = parent.join(name);
+        if !approved.contains(&path) {
+            approved.push(path);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// Notification sounds, played from the backend so they're heard while the window is hidden
+// in the tray. The tones are synthesized rather than bundled: a rising two-note chime for
+// an incoming offer and a three-note one for a finished transfer.
+//
+// `ENABLED` and `VOLUME` mirror the `sounds_enabled` / `sound_volume` settings so the
+// (non-async) event sink can check them without locking the settings.
+
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::Duration;
+
+use rodio::source::{SineWave, Source};
+use rodio::{OutputStream, Sink};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static VOLUME: AtomicU8 = AtomicU8::new(70);
+
+#[derive(Debug, Clone, Copy)]
+pub enum Sound {
+    Offer,
+    Complete,
+}
+
+impl Sound {
+    // (frequency in Hz, duration in ms) for each note.
+    fn notes(self) -> &'static [(f32, u64)] {
+        match self {
+            Sound::Offer => &[(660.0, 110), (880.0, 160)],
+            Sound::Complete => &[(523.25, 100), (659.25, 100), (783.99, 200)],
+        }
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn set_volume(volume: u8) {
+    VOLUME.store(volume.min(100), Ordering::Relaxed);
+}
+
+/// Play `sound` if sounds are enabled.
+pub fn play(sound: Sound) {
+    if ENABLED.load(Ordering::Relaxed) {
+        play_now(sound);
+    }
+}
+
+/// Play `sound` at the current volume regardless of the mute setting (the Settings preview).
+pub fn play_now(sound: Sound) {
+    let volume = VOLUME.load(Ordering::Relaxed);
+    if volume == 0 {
+        return;
+    }
+    // The output stream has to live until playback ends, so give it its own thread.
+    std::thread::spawn(move || {
+        if let Err(e) = play_blocking(sound, volume) {
+            wyrmhole_core::log_line!("[magic-wormhole][sounds][warn] Failed to play sound: {}", e);
+        }
+    });
+}
+
+fn play_blocking(sound: Sound, volume: u8) -> Result<(), String> {
+    let (_stream, handle) = OutputStream::try_default().map_err(|e| e.to_string())?;
+    let sink = Sink::try_new(&handle).map_err(|e| e.to_string())?;
+    // Full volume is kept well below clipping; pure sine tones are loud.
+    sink.set_volume(volume as f32 / 100.0 * 0.25);
+    for &(frequency, millis) in sound.notes() {
+        sink.append(
+            SineWave::new(frequency)
+                .take_duration(Duration::from_millis(millis))
+                .fade_in(Duration::from_millis(8)),
+        );
+    }
+    sink.sleep_until_end();
+    Ok(())
+}
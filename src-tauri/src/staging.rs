@@ -0,0 +1,81 @@
+// Files made for a send rather than picked by the user: clipboard images and text, and
+// screenshots. The engine only sends files from disk, so each send gets its own folder
+// under the temp directory, removed once the send finishes.
+
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use wyrmhole_core::context::AppContext;
+use wyrmhole_core::files;
+
+/// What a send will transfer, and the staging folder to remove afterwards (if any).
+pub struct Staged {
+    pub paths: Vec<String>,
+    dir: Option<PathBuf>,
+}
+
+impl Staged {
+    /// Files that already exist on disk; nothing to clean up.
+    pub fn existing(paths: Vec<String>) -> Self {
+        Staged { paths, dir: None }
+    }
+
+    /// A single file written into `dir` (from `new_dir`).
+    pub fn file(dir: PathBuf, path: &Path) -> Self {
+        Staged {
+            paths: vec![path.to_string_lossy().into_owned()],
+            dir: Some(dir),
+        }
+    }
+
+    pub fn cleanup(&self) {
+        if let Some(dir) = &self.dir {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+pub fn new_dir() -> Result<PathBuf, String> {
+    let dir = std::env::temp_dir()
+        .join("wyrmhole-staging")
+        .join(uuid::Uuid::new_v4().to_string());
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create staging folder: {}", e))?;
+    Ok(dir)
+}
+
+/// Named by time so the receiver sees something meaningful, e.g.
+/// "screenshot-2025-01-31-142005.png".
+pub fn file_name(prefix: &str, extension: &str) -> String {
+    format!(
+        "{}-{}.{}",
+        prefix,
+        Local::now().format("%Y-%m-%d-%H%M%S"),
+        extension
+    )
+}
+
+/// Write 8-bit RGBA pixels as a PNG.
+pub fn write_png(path: &Path, width: u32, height: u32, rgba: &[u8]) -> Result<(), String> {
+    let file = fs::File::create(path).map_err(|e| format!("Failed to save image: {}", e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba))
+        .map_err(|e| format!("Failed to save image: {}", e))
+}
+
+/// Send `staged` like the Send button would (one path as-is, several as a folder), then
+/// remove anything staged for it.
+pub async fn send(ctx: AppContext, staged: Staged, send_id: String) -> Result<String, String> {
+    let result = if let [path] = staged.paths.as_slice() {
+        files::send_file_call(ctx, path, send_id).await
+    } else {
+        files::send_multiple_files_call(ctx, staged.paths.clone(), send_id, None, None).await
+    };
+    staged.cleanup();
+    result
+}
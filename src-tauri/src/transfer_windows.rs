@@ -0,0 +1,68 @@
+// Pop-out windows for single transfers: a small always-on-top window per send or download,
+// opened from its card, so a long transfer can be watched while the main window is closed
+// to the tray. Each window gets only its own transfer's events: `route` (called from the
+// event sink in lib.rs) re-emits them to it alone as `TRANSFER_WINDOW_EVENT`.
+
+use serde_json::{Value, json};
+use tauri::{AppHandle, Emitter, EventTarget, Manager, WebviewUrl, WebviewWindowBuilder};
+
+/// Labels of transfer windows start with this; capabilities/default.json grants them the
+/// same permissions as the main window.
+pub const LABEL_PREFIX: &str = "transfer-";
+
+/// The event a transfer window listens for, carrying `{ event, payload }` of the engine
+/// event it was routed from.
+pub const TRANSFER_WINDOW_EVENT: &str = "transfer-window-update";
+
+// Window labels only allow a few characters; send and download ids are UUIDs.
+fn label(id: &str) -> Option<String> {
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .then(|| format!("{}{}", LABEL_PREFIX, id))
+}
+
+/// Open (or bring forward) the window for transfer `id`. `kind` is "send" or "download".
+pub fn open(app: &AppHandle, id: &str, kind: &str, file_name: &str) -> Result<(), String> {
+    if kind != "send" && kind != "download" {
+        return Err(format!("Unknown transfer kind '{}'", kind));
+    }
+    let label = label(id).ok_or_else(|| format!("Invalid transfer id '{}'", id))?;
+    if let Some(window) = app.get_webview_window(&label) {
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+
+    let url = format!("index.html?transfer={}&kind={}", id, kind);
+    WebviewWindowBuilder::new(app, &label, WebviewUrl::App(url.into()))
+        .title(format!("{} - wyrmhole", file_name))
+        .inner_size(340.0, 150.0)
+        .resizable(false)
+        .always_on_top(true)
+        .build()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open transfer window: {}", e))
+}
+
+/// Forward an engine event to the window of the transfer it's about, if one is open.
+pub fn route(app: &AppHandle, event: &str, payload: &Value) {
+    let Some(id) = payload
+        .get("id")
+        .or_else(|| payload.get("send_id"))
+        .and_then(Value::as_str)
+    else {
+        return;
+    };
+    let Some(label) = label(id) else {
+        return;
+    };
+    if app.get_webview_window(&label).is_some() {
+        let _ = app.emit_to(
+            EventTarget::webview_window(label),
+            TRANSFER_WINDOW_EVENT,
+            json!({ "event": event, "payload": payload }),
+        );
+    }
+}
+
+/// True for a transfer window's label, so closing one never hides the app to the tray.
+pub fn is_transfer_window(label: &str) -> bool {
+    label.starts_with(LABEL_PREFIX)
+}
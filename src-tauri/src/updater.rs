@@ -0,0 +1,108 @@
+// In-app updates through the Tauri updater plugin. `download` fetches and verifies the
+// signed update for this platform (reporting progress to the frontend) and keeps the
+// bytes; `install` applies them and restarts. Release builds must be signed with the
+// key whose public half is `plugins.updater.pubkey` in tauri.conf.json.
+//
+// The configured endpoint is the latest full release's manifest. On the beta channel the
+// newest pre-release is found through wyrmhole_core::updates and its manifest used instead.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_updater::{Update, UpdaterExt};
+use tokio::sync::Mutex;
+
+use wyrmhole_core::context::AppContext;
+use wyrmhole_core::{events, settings, updates};
+
+pub const UPDATE_DOWNLOAD_PROGRESS: &str = "update-download-progress";
+
+const RELEASE_DOWNLOAD_URL: &str = "https://github.com/ClaytonWas/wyrmhole/releases/download";
+
+/// Progress of `download_update`. `total` is missing if the server didn't send a length.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct UpdateDownloadProgress {
+    pub version: String,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub percentage: u64,
+}
+
+/// A downloaded update waiting for `install`.
+#[derive(Default)]
+pub struct PendingUpdate(Mutex<Option<(Update, Vec<u8>)>>);
+
+/// Download the latest update on the configured release channel if there is one. Returns
+/// its version, or `None` when already up to date.
+pub async fn download(app: &AppHandle, ctx: &AppContext) -> Result<Option<String>, String> {
+    let channel = settings::get_release_channel(ctx).await?;
+    let mut builder = app.updater_builder();
+    if channel != "stable" {
+        let current_version = app.package_info().version.to_string();
+        let info = updates::check_for_updates(&current_version, &channel).await?;
+        if !info.update_available {
+            return Ok(None);
+        }
+        let manifest = format!("{}/{}/latest.json", RELEASE_DOWNLOAD_URL, info.release_tag)
+            .parse()
+            .map_err(|e| format!("Invalid update manifest URL: {}", e))?;
+        builder = builder
+            .endpoints(vec![manifest])
+            .map_err(|e| format!("Updater unavailable: {}", e))?;
+    }
+    let updater = builder
+        .build()
+        .map_err(|e| format!("Updater unavailable: {}", e))?;
+    let Some(update) = updater
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?
+    else {
+        return Ok(None);
+    };
+
+    let version = update.version.clone();
+    let progress_app = app.clone();
+    let progress_version = version.clone();
+    let mut downloaded: u64 = 0;
+    let bytes = update
+        .download(
+            move |chunk_length, content_length| {
+                downloaded += chunk_length as u64;
+                let _ = progress_app.emit(
+                    UPDATE_DOWNLOAD_PROGRESS,
+                    UpdateDownloadProgress {
+                        version: progress_version.clone(),
+                        downloaded,
+                        total: content_length,
+                        percentage: content_length
+                            .map(|total| events::percentage(downloaded, total))
+                            .unwrap_or(0),
+                    },
+                );
+            },
+            || {},
+        )
+        .await
+        .map_err(|e| format!("Failed to download update: {}", e))?;
+
+    wyrmhole_core::log_line!(
+        "[magic-wormhole][updater][info] Downloaded update {} ({} bytes)",
+        version,
+        bytes.len()
+    );
+    *app.state::<PendingUpdate>().0.lock().await = Some((update, bytes));
+    Ok(Some(version))
+}
+
+/// Install the update fetched by `download` and restart into it.
+pub async fn install(app: &AppHandle) -> Result<(), String> {
+    let pending = app.state::<PendingUpdate>().0.lock().await.take();
+    let Some((update, bytes)) = pending else {
+        return Err("No downloaded update to install".to_string());
+    };
+    update
+        .install(bytes)
+        .map_err(|e| format!("Failed to install update: {}", e))?;
+    app.restart();
+}
@@ -0,0 +1,97 @@
+// Automation URL tests: what automation.rs makes of `wyrmhole://` URLs and shared wormhole
+// links, and how it decodes escapes in them.
+
+use tauri::Url;
+
+use wyrmhole_lib::automation::{AutomationCommand, parse, percent_decode};
+
+fn parse_str(url: &str) -> Result<AutomationCommand, String> {
+    parse(&Url::parse(url).unwrap())
+}
+
+fn receive(code: &str) -> Result<AutomationCommand, String> {
+    Ok(AutomationCommand::Receive(code.to_string()))
+}
+
+#[test]
+fn transfer_links_receive_their_code() {
+    assert_eq!(
+        parse_str("wormhole-transfer:7-guitarist-revenge?version=0"),
+        receive("7-guitarist-revenge")
+    );
+    assert_eq!(
+        parse_str("wormhole-transfer://7-guitarist-revenge/"),
+        receive("7-guitarist-revenge")
+    );
+    assert_eq!(
+        parse_str("wormhole:7-guitarist-revenge"),
+        receive("7-guitarist-revenge")
+    );
+    // Escaped code words are decoded.
+    assert_eq!(
+        parse_str("wormhole:7-guitarist%2Drevenge"),
+        receive("7-guitarist-revenge")
+    );
+}
+
+#[test]
+fn transfer_links_without_a_code_are_refused() {
+    assert!(parse_str("wormhole:guitarist-revenge").is_err());
+    assert!(parse_str("wormhole:7").is_err());
+    assert!(parse_str("wormhole:-guitarist-revenge").is_err());
+    assert!(parse_str("wormhole-transfer:7-guitarist-revenge?version=1").is_err());
+}
+
+#[test]
+fn transfer_links_only_use_the_default_rendezvous_server() {
+    assert_eq!(
+        parse_str(
+            "wormhole-transfer:7-guitarist-revenge?version=0\
+             &rendezvous=ws%3A%2F%2Frelay.magic-wormhole.io%3A4000%2Fv1"
+        ),
+        receive("7-guitarist-revenge")
+    );
+    let other = parse_str(
+        "wormhole-transfer:7-guitarist-revenge?version=0\
+         &rendezvous=ws%3A%2F%2Fmailbox.example.com%3A4000%2Fv1",
+    )
+    .unwrap_err();
+    assert!(other.contains("ws://mailbox.example.com:4000/v1"));
+}
+
+#[test]
+fn wyrmhole_urls_send_and_receive() {
+    assert_eq!(
+        parse_str("wyrmhole://send?path=/home/me/report.pdf&path=/home/me/photos"),
+        Ok(AutomationCommand::Send(vec![
+            "/home/me/report.pdf".to_string(),
+            "/home/me/photos".to_string(),
+        ]))
+    );
+    assert!(parse_str("wyrmhole://send?path=report.pdf").is_err());
+    assert!(parse_str("wyrmhole://send").is_err());
+    assert_eq!(
+        parse_str("wyrmhole://receive?code=7-guitarist-revenge"),
+        receive("7-guitarist-revenge")
+    );
+    assert!(parse_str("wyrmhole://receive").is_err());
+    assert!(parse_str("wyrmhole://delete?path=/").is_err());
+    assert!(parse_str("https://example.com/").is_err());
+}
+
+#[test]
+fn percent_decode_keeps_malformed_escapes() {
+    assert_eq!(percent_decode("a%20b%2Fc"), "a b/c");
+    assert_eq!(percent_decode("100%"), "100%");
+    assert_eq!(percent_decode("%4"), "%4");
+    assert_eq!(percent_decode("%zz%41"), "%zzA");
+    // A `%` before a multi-byte character isn't followed by two hex digits.
+    assert_eq!(percent_decode("%é1"), "%é1");
+}
+
+#[test]
+fn percent_decode_replaces_bytes_that_are_not_utf8() {
+    assert_eq!(percent_decode("%C3%A9"), "é");
+    assert_eq!(percent_decode("a%FFb"), "a\u{FFFD}b");
+    assert_eq!(percent_decode("%C3"), "\u{FFFD}");
+}
@@ -0,0 +1,145 @@
+// Scope tests: which paths scope.rs lets the webview send or export, including `..` and
+// symlink escapes out of an approved folder.
+
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use wyrmhole_lib::scope::{ApprovedPaths, Scope, ScopeErrorCode, in_other_users_home};
+
+// A scope whose only roots are what `approved` holds.
+fn scope_with(approved: &ApprovedPaths) -> Scope {
+    Scope {
+        roots: approved.paths(),
+        excluded: Vec::new(),
+        home: None,
+    }
+}
+
+fn code(scope: &Scope, path: &Path) -> Option<ScopeErrorCode> {
+    scope.resolve(path).err().map(|e| e.code)
+}
+
+#[test]
+fn other_users_homes_are_detected() {
+    let home = Path::new("/home/alice");
+    assert!(in_other_users_home(
+        Path::new("/home/bob/.ssh/id_ed25519"),
+        home
+    ));
+    assert!(in_other_users_home(Path::new("/home/bob"), home));
+    assert!(!in_other_users_home(
+        Path::new("/home/alice/Documents"),
+        home
+    ));
+    assert!(!in_other_users_home(
+        Path::new("/home/Shared/notes.txt"),
+        home
+    ));
+    assert!(!in_other_users_home(Path::new("/home/public"), home));
+    assert!(!in_other_users_home(Path::new("/home"), home));
+    assert!(!in_other_users_home(Path::new("/srv/data"), home));
+    // A top-level home like /root says nothing about where other users live.
+    assert!(!in_other_users_home(
+        Path::new("/home/bob"),
+        Path::new("/root")
+    ));
+}
+
+#[test]
+fn granted_paths_are_in_scope() {
+    let dir = TempDir::new().unwrap();
+    let picked = dir.path().join("picked");
+    fs::create_dir_all(picked.join("nested")).unwrap();
+    fs::write(picked.join("nested/a.txt"), b"a").unwrap();
+    fs::write(dir.path().join("other.txt"), b"b").unwrap();
+
+    let approved = ApprovedPaths::default();
+    approved.grant([&picked, &dir.path().join("missing")]);
+    // Only paths that resolve are recorded, once each.
+    approved.grant([&picked]);
+    assert_eq!(approved.paths(), vec![fs::canonicalize(&picked).unwrap()]);
+
+    let scope = scope_with(&approved);
+    assert_eq!(code(&scope, &picked.join("nested/a.txt")), None);
+    assert_eq!(
+        code(&scope, &dir.path().join("other.txt")),
+        Some(ScopeErrorCode::OutsideScope)
+    );
+    assert_eq!(
+        code(&scope, Path::new("picked/nested/a.txt")),
+        Some(ScopeErrorCode::NotAbsolute)
+    );
+    assert_eq!(
+        code(&scope, &picked.join("gone.txt")),
+        Some(ScopeErrorCode::Unresolvable)
+    );
+}
+
+#[test]
+fn dot_dot_cannot_leave_a_granted_folder() {
+    let dir = TempDir::new().unwrap();
+    let picked = dir.path().join("picked");
+    fs::create_dir(&picked).unwrap();
+    fs::write(dir.path().join("secret.txt"), b"s").unwrap();
+
+    let approved = ApprovedPaths::default();
+    approved.grant([&picked]);
+    let scope = scope_with(&approved);
+    assert_eq!(
+        code(&scope, &picked.join("..").join("secret.txt")),
+        Some(ScopeErrorCode::OutsideScope)
+    );
+    assert_eq!(
+        code(&scope, &picked.join("..")),
+        Some(ScopeErrorCode::OutsideScope)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_are_checked_where_they_point() {
+    let dir = TempDir::new().unwrap();
+    let picked = dir.path().join("picked");
+    fs::create_dir(&picked).unwrap();
+    fs::write(dir.path().join("secret.txt"), b"s").unwrap();
+    fs::write(picked.join("inside.txt"), b"i").unwrap();
+    std::os::unix::fs::symlink(dir.path().join("secret.txt"), picked.join("out")).unwrap();
+    std::os::unix::fs::symlink(picked.join("inside.txt"), picked.join("in")).unwrap();
+
+    let approved = ApprovedPaths::default();
+    approved.grant([&picked]);
+    let scope = scope_with(&approved);
+    assert_eq!(
+        code(&scope, &picked.join("out")),
+        Some(ScopeErrorCode::OutsideScope)
+    );
+    assert_eq!(code(&scope, &picked.join("in")), None);
+
+    // Granting through a symlink approves where it points, not the link.
+    let approved = ApprovedPaths::default();
+    approved.grant([picked.join("out")]);
+    assert_eq!(
+        approved.paths(),
+        vec![fs::canonicalize(dir.path().join("secret.txt")).unwrap()]
+    );
+}
+
+#[test]
+fn settings_files_stay_out_of_scope() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("settings.json"), b"{}").unwrap();
+    fs::write(dir.path().join("received_files.json"), b"[]").unwrap();
+
+    let approved = ApprovedPaths::default();
+    approved.grant([dir.path()]);
+    let settings = fs::canonicalize(dir.path().join("settings.json")).unwrap();
+    let scope = Scope {
+        excluded: vec![settings.clone()],
+        ..scope_with(&approved)
+    };
+    assert_eq!(code(&scope, &settings), Some(ScopeErrorCode::AppConfig));
+    // Nor can the folder holding it be sent whole.
+    assert_eq!(code(&scope, dir.path()), Some(ScopeErrorCode::AppConfig));
+    assert_eq!(code(&scope, &dir.path().join("received_files.json")), None);
+}
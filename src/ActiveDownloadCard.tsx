@@ -1,10 +1,17 @@
 import { useState } from "react";
-import { invoke } from "@tauri-apps/api/core";
 import { toast } from "sonner";
 import { FileIcon } from "./FileIcon";
 import { LoadingDots } from "./LoadingDots";
 import { DetailModal } from "./DetailModal";
 import { XIcon } from "./Icons";
+import { commands } from "./bindings";
+import { unwrap } from "./commands";
+
+const STATUS_TEXT: Record<string, string> = {
+  "transit-negotiating": "Connecting to sender",
+  receiving: "Downloading",
+  verifying: "Verifying",
+};
 
 type Props = {
   id: string;
@@ -12,7 +19,9 @@ type Props = {
   transferred: number;
   total: number;
   percentage: number;
+  status?: string;
   error?: string;
+  stalled?: boolean;
   onDismiss?: (id: string) => void;
 };
 
@@ -30,27 +39,41 @@ const ActiveDownloadCard = ({
   transferred,
   total,
   percentage,
+  status: statusProp,
   error,
+  stalled,
   onDismiss,
 }: Props) => {
   const [isOpen, setIsOpen] = useState(false);
   const hasError = !!error;
+  // A finalizing download has all its bytes; what's left is extraction and history.
+  const isComplete = statusProp === "finalizing";
   const status = hasError ? (
     "Failed"
-  ) : percentage >= 100 ? (
+  ) : isComplete ? (
     "Completed"
   ) : (
     <>
-      Downloading
+      {stalled ? "Stalled" : STATUS_TEXT[statusProp ?? ""] ?? "Downloading"}
       <LoadingDots />
     </>
   );
   const progressBarColor = hasError ? "bg-red-600" : "bg-green-600";
-  const isComplete = percentage >= 100;
+
+  // Keeps showing progress with the main window closed to the tray.
+  async function popOut() {
+    try {
+      await unwrap(commands.openTransferWindow(id, "download", file_name));
+      setIsOpen(false);
+    } catch (err) {
+      console.error("Error opening transfer window:", err);
+      toast.error("Failed to open transfer window");
+    }
+  }
 
   async function handleCancel() {
     try {
-      await invoke("cancel_download", { downloadId: id });
+      await unwrap(commands.cancelDownload(id));
       // Dismiss immediately when cancelled
       if (onDismiss) {
         onDismiss(id);
@@ -127,10 +150,17 @@ const ActiveDownloadCard = ({
         subtitle="Downloading file"
         footer={
           !isComplete && !hasError ? (
-            <div className="px-6 py-4 border-t border-gray-200">
+            <div className="px-6 py-4 border-t border-gray-200 flex gap-2">
+              <button
+                onClick={popOut}
+                className="flex-1 px-4 py-2.5 text-blue-600 hover:bg-blue-50 text-sm font-semibold rounded-2xl transition-all duration-200 cursor-pointer"
+                title="Watch this transfer in its own small window"
+              >
+                Pop Out
+              </button>
               <button
                 onClick={handleCancel}
-                className="modal-btn-danger w-full px-4 py-2.5 text-red-600 text-sm font-semibold rounded-2xl transition-all duration-200"
+                className="modal-btn-danger flex-1 px-4 py-2.5 text-red-600 text-sm font-semibold rounded-2xl transition-all duration-200"
               >
                 Cancel Download
               </button>
@@ -1,10 +1,12 @@
 import { useState } from "react";
-import { invoke } from "@tauri-apps/api/core";
+import { openUrl } from "@tauri-apps/plugin-opener";
 import { toast } from "sonner";
 import { FileIcon } from "./FileIcon";
 import { LoadingDots } from "./LoadingDots";
 import { DetailModal } from "./DetailModal";
 import { XIcon } from "./Icons";
+import { commands } from "./bindings";
+import { unwrap } from "./commands";
 
 type Props = {
   id: string;
@@ -15,9 +17,29 @@ type Props = {
   error?: string;
   code?: string;
   status?: string;
+  code_age_secs?: number;
+  code_expires_in_secs?: number | null;
+  preparing?: { path: string; index: number; count: number };
+  // The send failed after showing its code, which no longer works.
+  code_expired?: boolean;
   onDismiss?: (id: string) => void;
 };
 
+const STATUS_TEXT: Record<string, string> = {
+  queued: "Queued",
+  preparing: "Preparing",
+  connecting: "Connecting",
+  waiting: "Waiting",
+  reconnecting: "Reconnecting",
+  "peer-connected": "Receiver connected",
+  offline: "Offline, retrying",
+  stalled: "Stalled",
+  packaging: "Packaging",
+  "transit-negotiating": "Connecting to receiver",
+  sending: "Sending",
+  verifying: "Verifying",
+};
+
 function formatBytes(bytes: number): string {
   if (bytes === 0) return "0 B";
   const k = 1024;
@@ -26,6 +48,18 @@ function formatBytes(bytes: number): string {
   return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + " " + sizes[i];
 }
 
+function formatClock(secs: number): string {
+  const minutes = Math.floor(secs / 60);
+  return `${minutes}:${String(secs % 60).padStart(2, "0")}`;
+}
+
+// Ways to pass the code on, as messages the backend composes (`compose_code_share`).
+const SHARE_CHANNELS = [
+  { channel: "email", label: "Email" },
+  { channel: "sms", label: "Text message" },
+  { channel: "clipboard", label: "Copy message" },
+];
+
 const ActiveSendCard = ({
   id,
   file_name,
@@ -35,32 +69,67 @@ const ActiveSendCard = ({
   error,
   code,
   status: statusProp,
+  code_age_secs,
+  code_expires_in_secs,
+  preparing,
+  code_expired,
   onDismiss,
 }: Props) => {
   const [isOpen, setIsOpen] = useState(false);
+  // Fetched on request when progress events leave the code out ("Hide Codes" setting).
+  const [revealedCode, setRevealedCode] = useState<string>();
   const hasError = !!error;
+  const shownCode = code || revealedCode;
 
-  // Determine status text based on statusProp
-  let statusText = "Preparing"; // Default to "Preparing" instead of "Sending..."
-  let showDots = false;
+  async function revealCode() {
+    try {
+      setRevealedCode(await unwrap(commands.revealCode(id)));
+    } catch (err) {
+      toast.error(String(err ?? "No code yet"));
+    }
+  }
+
+  async function shareCode(channel: string) {
+    try {
+      const share = await unwrap(commands.composeCodeShare(id, channel));
+      if (share.url) {
+        await openUrl(share.url);
+      } else {
+        await navigator.clipboard.writeText(share.text);
+        toast.success("Message copied");
+      }
+    } catch (err) {
+      toast.error(String(err ?? "Couldn't share the code"));
+    }
+  }
+
+  // Status text for the backend's TransferState (plus the UI-only "stalled"); a finalizing
+  // send has delivered everything.
+  const isComplete = statusProp === "finalizing";
+  let statusText = STATUS_TEXT[statusProp ?? ""] ?? "Preparing";
+  let showDots = true;
+  if (statusProp === "waiting" && code_age_secs !== undefined) {
+    // "Code valid, waiting 12:34", with the time left when unclaimed codes expire.
+    statusText =
+      code_expires_in_secs != null
+        ? `Code valid, expires in ${formatClock(code_expires_in_secs)}`
+        : `Code valid, waiting ${formatClock(code_age_secs)}`;
+    showDots = false;
+  }
+  if (preparing && (statusProp === "preparing" || statusProp === "queued")) {
+    // "Reading report.pdf (3/40)" while a slow drive answers
+    const name = preparing.path.split(/[/\\]/).pop() || preparing.path;
+    statusText =
+      preparing.count > 1
+        ? `Reading ${name} (${preparing.index + 1}/${preparing.count})`
+        : `Reading ${name}`;
+  }
   if (hasError) {
     statusText = "Failed";
     showDots = false;
-  } else if (percentage >= 100) {
+  } else if (isComplete) {
     statusText = "Completed";
     showDots = false;
-  } else if (statusProp === "preparing") {
-    statusText = "Preparing";
-    showDots = true;
-  } else if (statusProp === "waiting") {
-    statusText = "Waiting";
-    showDots = true;
-  } else if (statusProp === "packaging") {
-    statusText = "Packaging";
-    showDots = true;
-  } else if (statusProp === "sending") {
-    statusText = "Sending";
-    showDots = true;
   }
 
   const status = showDots ? (
@@ -72,11 +141,21 @@ const ActiveSendCard = ({
     statusText
   );
   const progressBarColor = hasError ? "bg-red-600" : "bg-blue-600";
-  const isComplete = percentage >= 100;
+
+  // Keeps showing progress with the main window closed to the tray.
+  async function popOut() {
+    try {
+      await unwrap(commands.openTransferWindow(id, "send", file_name));
+      setIsOpen(false);
+    } catch (err) {
+      console.error("Error opening transfer window:", err);
+      toast.error("Failed to open transfer window");
+    }
+  }
 
   async function handleCancel() {
     try {
-      await invoke("cancel_send", { sendId: id });
+      await unwrap(commands.cancelSend(id));
       toast.success("Send cancelled");
       if (onDismiss) {
         onDismiss(id);
@@ -153,10 +232,17 @@ const ActiveSendCard = ({
         subtitle="Sending file"
         footer={
           !isComplete && !hasError ? (
-            <div className="px-6 py-4 border-t border-gray-200">
+            <div className="px-6 py-4 border-t border-gray-200 flex gap-2">
+              <button
+                onClick={popOut}
+                className="flex-1 px-4 py-2.5 text-blue-600 hover:bg-blue-50 text-sm font-semibold rounded-2xl transition-all duration-200 cursor-pointer"
+                title="Watch this transfer in its own small window"
+              >
+                Pop Out
+              </button>
               <button
                 onClick={handleCancel}
-                className="modal-btn-danger w-full px-4 py-2.5 text-red-600 text-sm font-semibold rounded-2xl transition-all duration-200"
+                className="modal-btn-danger flex-1 px-4 py-2.5 text-red-600 text-sm font-semibold rounded-2xl transition-all duration-200"
               >
                 Cancel Send
               </button>
@@ -202,7 +288,28 @@ const ActiveSendCard = ({
           )}
 
           {/* Connection Code - Refined */}
-          {code && (
+          {!shownCode && !hasError && percentage < 100 && (
+            <button
+              onClick={revealCode}
+              className="w-full text-sm font-medium text-blue-600 hover:text-blue-700 rounded-xl px-4 py-3 border border-gray-200 hover:bg-blue-50 transition-all cursor-pointer"
+            >
+              Reveal connection code
+            </button>
+          )}
+          {shownCode && code_expired && (
+            <div className="pt-2">
+              <p className="text-xs font-medium text-gray-500 mb-2.5 uppercase tracking-wide">
+                Connection Code
+              </p>
+              <p className="text-sm font-mono text-gray-400 line-through rounded-xl px-4 py-3 border border-gray-200 select-none">
+                {shownCode}
+              </p>
+              <p className="text-[11px] text-gray-500 mt-1.5">
+                Expired. Start a new send for a new code; this one won't connect.
+              </p>
+            </div>
+          )}
+          {shownCode && !code_expired && (
             <div className="pt-2">
               <p className="text-xs font-medium text-gray-500 mb-2.5 uppercase tracking-wide">
                 Connection Code
@@ -211,7 +318,7 @@ const ActiveSendCard = ({
                 <input
                   type="text"
                   readOnly
-                  value={code}
+                  value={shownCode}
                   className="w-full text-sm font-mono text-gray-900 rounded-xl px-4 py-3 pr-10 cursor-pointer transition-all"
                   style={{
                     background: "#ffffff",
@@ -242,7 +349,7 @@ const ActiveSendCard = ({
                     const input = e.target as HTMLInputElement;
                     input.select();
                     try {
-                      await navigator.clipboard.writeText(code);
+                      await navigator.clipboard.writeText(shownCode);
                       toast.success("Code copied");
                     } catch (err) {
                       console.error("Failed to copy:", err);
@@ -263,6 +370,19 @@ const ActiveSendCard = ({
                   </svg>
                 </div>
               </div>
+              {!hasError && percentage < 100 && (
+                <div className="grid grid-cols-3 gap-2 mt-2.5">
+                  {SHARE_CHANNELS.map(({ channel, label }) => (
+                    <button
+                      key={channel}
+                      onClick={() => shareCode(channel)}
+                      className="text-xs font-medium text-blue-600 hover:text-blue-700 rounded-lg px-2 py-1.5 border border-gray-200 hover:bg-blue-50 transition-all cursor-pointer"
+                    >
+                      {label}
+                    </button>
+                  ))}
+                </div>
+              )}
             </div>
           )}
         </div>
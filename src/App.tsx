@@ -1,16 +1,27 @@
-import { invoke } from "@tauri-apps/api/core";
-import { open } from "@tauri-apps/plugin-dialog";
+import { ask } from "@tauri-apps/plugin-dialog";
+import { startDrag } from "@crabnebula/tauri-plugin-drag";
 import { listen } from "@tauri-apps/api/event";
 import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { useEffect, useMemo, useRef, useState, type CSSProperties } from "react";
 import { Toaster, toast } from "sonner";
 import ReceiveFileCard from "./RecieveFileCardComponent";
 import SentFileCard from "./SentFileCard";
+import RejectedOfferCard from "./RejectedOfferCard";
 import ActiveDownloadCard from "./ActiveDownloadCard";
 import ActiveSendCard from "./ActiveSendCard";
 import PendingFileOfferCard from "./PendingFileOfferCard";
 import ConnectingCard from "./ConnectingCard";
 import SettingsMenu from "./SettingsMenu";
+import { checkForUpdates, downloadUpdateInBackground } from "./updates";
+import {
+  commands,
+  type ContentWarning,
+  type FileOffer,
+  type ReceivedFile,
+  type Result,
+  type SentFile,
+} from "./bindings";
+import { unwrap } from "./commands";
 import { FileIcon } from "./FileIcon";
 import { XIcon } from "./Icons";
 import "./App.css";
@@ -97,33 +108,15 @@ function useMapState<K, V>() {
   return [value, ops] as const;
 }
 
-interface ReceivedFile {
-  connection_type: string;
-  download_time: string;
-  download_url: string;
-  file_extension: string;
-  file_name: string;
-  file_size: number;
-  peer_address: string;
-}
-
-interface SentFile {
-  file_name: string;
-  file_size: number;
-  file_extension: string;
-  file_paths?: string[];
-  file_path?: string; // For backward compatibility with old data
-  send_time: string;
-  connection_code: string;
-}
-
 interface DownloadProgress {
   id: string;
   file_name: string;
   transferred: number;
   total: number;
   percentage: number;
+  status?: string;
   error?: string;
+  stalled?: boolean;
 }
 
 interface SendProgress {
@@ -135,37 +128,79 @@ interface SendProgress {
   error?: string;
   code?: string;
   status?: string;
+  code_age_secs?: number;
+  code_expires_in_secs?: number | null;
+  preparing?: { path: string; index: number; count: number };
+  code_expired?: boolean;
 }
 
-interface PendingFileOffer {
-  id: string;
-  file_name: string;
-  file_size?: number;
+function formatBytes(bytes: number): string {
+  if (bytes === 0) return "0 B";
+  const k = 1024;
+  const sizes = ["B", "KB", "MB", "GB", "TB"];
+  const i = Math.floor(Math.log(bytes) / Math.log(k));
+  return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + " " + sizes[i];
+}
+
+// Command errors are strings, except paths refused by scope.rs, which come back as
+// `{ code, path, message }`.
+function errorText(err: unknown): string {
+  if (err instanceof Error) return err.message;
+  if (err && typeof err === "object" && "sensitive" in err) {
+    const { sensitive } = err as { sensitive: { path: string; message: string }[] };
+    return sensitive.map((s) => `${s.message} (${s.path})`).join(" ");
+  }
+  if (err && typeof err === "object" && "message" in err) {
+    const { message, path } = err as { message: string; path?: string };
+    return path ? `${message} (${path})` : message;
+  }
+  return String(err);
 }
 
 function App() {
   const [receiveCode, setReceiveCode] = useState("");
   const [selectedFiles, setSelectedFiles] = useState<string[] | null>(null);
+  // The selection came from a `wyrmhole://send` link and still needs the user to press Send.
+  const [linkSend, setLinkSend] = useState(false);
   const [folderName, setFolderName] = useState<string>("");
+  // When set, the selection is sent as a password-protected zip instead.
+  const [archivePassword, setArchivePassword] = useState<string>("");
+  // When set, a single selected folder or file sends only what changed since it was last sent.
+  const [syncChanges, setSyncChanges] = useState(false);
+  const [storeOnly, setStoreOnly] = useState(false);
   const [receivedFiles, setReceivedFiles] = useState<ReceivedFile[]>([]);
   const [sentFiles, setSentFiles] = useState<SentFile[]>([]);
-  const [historyTab, setHistoryTab] = useState<"received" | "sent">("received");
+  const [historyTab, setHistoryTab] = useState<"received" | "sent" | "rejected">(
+    "received",
+  );
   const [historySearch, setHistorySearch] = useState("");
   const [historyMinSizeMb, setHistoryMinSizeMb] = useState("");
   const [historySizeMode, setHistorySizeMode] = useState<"atLeast" | "atMost">("atLeast");
   const [historyDateFrom, setHistoryDateFrom] = useState("");
   const [historyDateMode, setHistoryDateMode] = useState<"after" | "before">("after");
+  // Declined offers live in the received history; the Rejected tab shows them on their own.
+  const rejectedFiles = receivedFiles.filter((file) => file.status === "rejected");
   const [dateButtonAnimating, setDateButtonAnimating] = useState(false);
   const [sizeButtonAnimating, setSizeButtonAnimating] = useState(false);
   const [downloadProgress, downloadOps] = useMapState<string, DownloadProgress>();
   const [sendProgress, sendOps] = useMapState<string, SendProgress>();
-  const [pendingFileOffers, offerOps] = useMapState<string, PendingFileOffer>();
+  const [pendingFileOffers, offerOps] = useMapState<string, FileOffer>();
   const [defaultFolderNameFormat, setDefaultFolderNameFormat] =
     useState<string>("#-files-via-wyrmhole");
   const [connectingCodes, connectingOps] = useMapState<string, string>(); // Map<id, code>
+  // Running size of each selected path, keyed by its compute_path_size id.
+  const [selectionSizes, selectionSizeOps] = useMapState<
+    string,
+    { bytes: number; done: boolean }
+  >();
+  const selectionSizeIds = useRef<Set<string>>(new Set());
+  const selectionSize = [...selectionSizes.values()].reduce((sum, s) => sum + s.bytes, 0);
   const [isDragging, setIsDragging] = useState(false);
   const cancelledConnections = useRef<Set<string>>(new Set()); // Track cancelled connection IDs
   const connectionCodeToasts = useRef<Map<string | number, string>>(new Map()); // Map<toastId, code>
+  // Offers being accepted, kept until their download ends so one whose folder can't be
+  // written to goes back to the pending list (see the `download-error` handler).
+  const acceptingOffers = useRef<Map<string, FileOffer>>(new Map());
 
   function prepare_resend_from_history(paths: string[]) {
     if (!paths || paths.length === 0) {
@@ -182,9 +217,9 @@ function App() {
     );
   }
 
-  async function deny_file_receive(id: string) {
+  async function deny_file_receive(id: string, reason?: string) {
     try {
-      await invoke("receiving_file_deny", { id });
+      await unwrap(commands.receivingFileDeny(id, reason ?? null));
       console.log("Denied file:", id);
       offerOps.delete(id);
     } catch (error) {
@@ -192,7 +227,15 @@ function App() {
     }
   }
 
-  async function accept_file_receive(id: string, file_name?: string) {
+  async function accept_file_receive(
+    id: string,
+    file_name?: string,
+    destination?: string,
+    confirmOversize = false,
+    confirmContent = false,
+  ) {
+    const offer = pendingFileOffers.get(id);
+    if (offer) acceptingOffers.current.set(id, offer);
     try {
       offerOps.delete(id);
       downloadOps.set(id, {
@@ -203,11 +246,15 @@ function App() {
         percentage: 0,
       });
 
-      await invoke("receiving_file_accept", { id });
+      await unwrap(
+        commands.receivingFileAccept(id, destination ?? null, confirmOversize, confirmContent),
+      );
       console.log("Accepted file:", id);
     } catch (error) {
       console.error("Error accepting file:", error);
-      const errorMessage = error instanceof Error ? error.message : String(error);
+      // Already back in the pending list, to be accepted into another folder
+      if (offer && !acceptingOffers.current.has(id)) return;
+      const errorMessage = errorText(error);
       // Don't show toast here - the download-error event handler will show it
       downloadOps.update(
         id,
@@ -221,22 +268,76 @@ function App() {
           error: errorMessage,
         },
       );
+    } finally {
+      acceptingOffers.current.delete(id);
+    }
+  }
+
+  // Accept `offer` into a folder picked now instead of the download directory. The backend
+  // opens the dialog, so the folder it returns is one it will accept (see scope.rs).
+  async function accept_file_receive_to(offer: FileOffer) {
+    const folder = await unwrap(commands.pickDirectory()).catch((error) => {
+      toast.error(errorText(error));
+      return null;
+    });
+    if (folder) {
+      accept_file_receive(
+        offer.id,
+        offer.file_name,
+        folder,
+        offer.over_size_limit,
+        !!offer.content_warning,
+      );
+    }
+  }
+
+  // Accept every pending offer at once. Oversize offers, programs and scripts stay pending,
+  // since each needs its own confirmation; the rest go like single accepts.
+  async function accept_all_offers() {
+    const offers = Array.from(pendingFileOffers.values()).filter(
+      (offer) => !offer.over_size_limit && !offer.content_warning,
+    );
+    for (const offer of offers) {
+      acceptingOffers.current.set(offer.id, offer);
+      offerOps.delete(offer.id);
+      downloadOps.set(offer.id, {
+        id: offer.id,
+        file_name: offer.file_name,
+        transferred: 0,
+        total: 0,
+        percentage: 0,
+      });
+    }
+    try {
+      toast.info(await unwrap(commands.acceptAllPending(null)));
+    } catch (error) {
+      toast.error(errorText(error));
+    } finally {
+      for (const offer of offers) acceptingOffers.current.delete(offer.id);
+    }
+  }
+
+  async function deny_all_offers() {
+    const ids = Array.from(pendingFileOffers.keys());
+    try {
+      await unwrap(commands.denyAllPending(null));
+      for (const id of ids) offerOps.delete(id);
+    } catch (error) {
+      console.error("Error denying offers:", error);
     }
   }
 
   async function select_files() {
     try {
-      const selected = await open({ multiple: true });
+      // Picked by the backend, which approves the files for sending (see scope.rs).
+      const selected = await unwrap(commands.pickFilesForSend(true));
       if (!selected) {
         setSelectedFiles(null);
         setFolderName("");
         return;
       }
 
-      const files = Array.isArray(selected) ? selected : [selected];
-      // Filter out non-strings for safety
-      const stringFiles = files.filter((f) => typeof f === "string") as string[];
-      setSelectedFiles(stringFiles);
+      setSelectedFiles(selected);
       setFolderName(""); // Clear folder name when selecting new files
     } catch (err) {
       console.error("Error selecting files:", err);
@@ -245,18 +346,15 @@ function App() {
 
   async function append_files() {
     try {
-      const selected = await open({ multiple: true });
+      const selected = await unwrap(commands.pickFilesForSend(true));
       if (!selected) {
         return;
       }
 
-      const files = Array.isArray(selected) ? selected : [selected];
-      const stringFiles = files.filter((f) => typeof f === "string") as string[];
-
       setSelectedFiles((prev) => {
         const existing = prev ?? [];
         const merged = [...existing];
-        for (const f of stringFiles) {
+        for (const f of selected) {
           if (!merged.includes(f)) merged.push(f);
         }
         return merged.length > 0 ? merged : null;
@@ -267,42 +365,96 @@ function App() {
   }
 
   // Native dialogs can't mix files and folders in a single picker, so folders get
-  // their own picker. The selected directory paths are merged into the same
-  // selectedFiles state the file picker uses; the backend already tarballs any
-  // directory path it receives.
+  // their own picker. It's opened by the backend, which checks the folder can be
+  // read and returns a normalized path (the webview's paths for network shares
+  // aren't always usable). The folder is merged into the same selectedFiles state
+  // the file picker uses; the backend already tarballs any directory path it receives.
   async function append_folders() {
     try {
-      const selected = await open({ directory: true, multiple: true });
-      if (!selected) {
+      const picked = await unwrap(commands.pickFolderForSend());
+      if (!picked) {
         return;
       }
 
-      const folders = Array.isArray(selected) ? selected : [selected];
-      const stringFolders = folders.filter((f) => typeof f === "string") as string[];
-
       setSelectedFiles((prev) => {
         const existing = prev ?? [];
-        const merged = [...existing];
-        for (const f of stringFolders) {
-          if (!merged.includes(f)) merged.push(f);
-        }
-        return merged.length > 0 ? merged : null;
+        return existing.includes(picked.path) ? existing : [...existing, picked.path];
       });
     } catch (err) {
       console.error("Error selecting folders:", err);
+      toast.error(String(err));
+    }
+  }
+
+  // Sends of a home folder, browser profile, keys or secrets files (see sensitive.rs) go
+  // out only after the user confirms; the backend refuses them without `confirmSensitive`.
+  async function confirmSensitive(paths: string[]): Promise<boolean> {
+    try {
+      const found = await unwrap(commands.checkSensitivePaths(paths));
+      if (found.length === 0) return true;
+      const details = found.map((f) => `${f.path}\n${f.message}`).join("\n\n");
+      return await ask(`${details}\n\nSend it anyway?`, {
+        title: "This may be sensitive",
+        kind: "warning",
+        okLabel: "Send anyway",
+        cancelLabel: "Cancel",
+      });
+    } catch (err) {
+      console.error("Error checking selection:", err);
+      return false;
     }
   }
 
   // Core send routine shared by the manual "Send" button and the OS
   // context-menu ("Send via wyrmhole") entry. Takes explicit paths so it
   // doesn't depend on the async `selectedFiles` state having settled.
-  async function startSend(paths: string[], name: string) {
+  // With `replyTo` (a finished transfer's id) a single path goes back on its reply code.
+  async function startSend(
+    paths: string[],
+    name: string,
+    password = "",
+    replyTo?: string,
+    compression: number | null = null,
+  ) {
     if (!paths || paths.length === 0) return;
+    if (!(await confirmSensitive(paths))) return;
 
     const sendId = crypto.randomUUID();
     let displayName = "files";
 
-    if (paths.length === 1) {
+    if (password) {
+      // Encrypted archive - the backend zips the selection first, replacing the
+      // "Preparing..." placeholder via the first send-progress event.
+      sendOps.set(sendId, {
+        id: sendId,
+        file_name: "Preparing...",
+        sent: 0,
+        total: 0,
+        percentage: 0,
+      });
+
+      try {
+        const response = await unwrap(
+          commands.sendEncryptedArchiveCall(paths, sendId, name.trim() || null, password, true),
+        );
+        console.log("Sent encrypted archive:", response);
+      } catch (err) {
+        console.error("Error sending encrypted archive:", err);
+        const errorMessage = errorText(err);
+        sendOps.update(
+          sendId,
+          { error: errorMessage },
+          {
+            id: sendId,
+            file_name: displayName,
+            sent: 0,
+            total: 0,
+            percentage: 0,
+            error: errorMessage,
+          },
+        );
+      }
+    } else if (paths.length === 1) {
       const filePath = paths[0];
       displayName = filePath.split(/[/\\]/).pop() || "Unknown file";
 
@@ -316,11 +468,13 @@ function App() {
       });
 
       try {
-        const response = await invoke("send_file_call", { filePath, sendId });
+        const response = replyTo
+          ? await unwrap(commands.sendReplyCall(replyTo, filePath, sendId, true))
+          : await unwrap(commands.sendFileCall(filePath, sendId, true));
         console.log("Sent file:", response);
       } catch (err) {
         console.error("Error sending file:", err);
-        const errorMessage = err instanceof Error ? err.message : String(err);
+        const errorMessage = errorText(err);
         sendOps.update(
           sendId,
           { error: errorMessage },
@@ -346,15 +500,13 @@ function App() {
       });
 
       try {
-        const response = await invoke("send_multiple_files_call", {
-          filePaths: paths,
-          sendId,
-          folderName: name.trim() || null,
-        });
+        const response = await unwrap(
+          commands.sendMultipleFilesCall(paths, sendId, name.trim() || null, compression, true),
+        );
         console.log("Sent files:", response);
       } catch (err) {
         console.error("Error sending files:", err);
-        const errorMessage = err instanceof Error ? err.message : String(err);
+        const errorMessage = errorText(err);
         sendOps.update(
           sendId,
           { error: errorMessage },
@@ -371,10 +523,122 @@ function App() {
     }
   }
 
+  // Sends what changed in `path` since it was last sent this way (see sync.rs and delta.rs).
+  async function startChangesSend(path: string) {
+    if (!(await confirmSensitive([path]))) return;
+    const sendId = crypto.randomUUID();
+    sendOps.set(sendId, {
+      id: sendId,
+      file_name: "Preparing...",
+      sent: 0,
+      total: 0,
+      percentage: 0,
+    });
+
+    try {
+      const response = await unwrap(commands.sendChangesCall(path, sendId, true));
+      console.log("Sent changes:", response);
+    } catch (err) {
+      console.error("Error sending changes:", err);
+      const errorMessage = errorText(err);
+      sendOps.update(
+        sendId,
+        { error: errorMessage },
+        {
+          id: sendId,
+          file_name: path.split(/[/\\]/).pop() || "Unknown file",
+          sent: 0,
+          total: 0,
+          percentage: 0,
+          error: errorMessage,
+        },
+      );
+    }
+  }
+
+  // Sends a received file on again under a new code (see `files::send_back`).
+  async function send_back(file: ReceivedFile) {
+    const sendId = crypto.randomUUID();
+    const displayName = file.file_extension
+      ? `${file.file_name}.${file.file_extension}`
+      : file.file_name;
+    sendOps.set(sendId, {
+      id: sendId,
+      file_name: displayName,
+      sent: 0,
+      total: 0,
+      percentage: 0,
+      status: "preparing",
+    });
+
+    try {
+      const response = await unwrap(commands.sendBack(file.id, sendId));
+      console.log("Sent back:", response);
+    } catch (err) {
+      console.error("Error sending back:", err);
+      const errorMessage = errorText(err);
+      sendOps.update(
+        sendId,
+        { error: errorMessage },
+        {
+          id: sendId,
+          file_name: displayName,
+          sent: 0,
+          total: 0,
+          percentage: 0,
+          error: errorMessage,
+        },
+      );
+    }
+  }
+
+  // Drops a received entry from the history; with `deleteFile` its file goes to the OS trash.
+  async function remove_received(file: ReceivedFile, deleteFile: boolean) {
+    try {
+      await unwrap(commands.removeReceivedEntry(file.id, deleteFile));
+      setReceivedFiles((prev) => prev.filter((entry) => entry.id !== file.id));
+      if (deleteFile) toast.success(`Moved ${file.file_name} to the trash`);
+    } catch (err) {
+      console.error("Error removing history entry:", err);
+      toast.error(errorText(err));
+    }
+  }
+
+  // Drags a received file out of the window into another app (see drag_out.rs).
+  async function drag_out(file: ReceivedFile) {
+    try {
+      const staged = await unwrap(commands.stageDragOut(file.id));
+      await startDrag({ item: [staged.path], icon: staged.icon });
+    } catch (err) {
+      console.error("Error dragging file:", err);
+      toast.error(errorText(err));
+    }
+  }
+
   async function send_files() {
     if (!selectedFiles || selectedFiles.length === 0) return;
-    await startSend(selectedFiles, folderName);
+    if (linkSend) {
+      // Pressing Send is the confirmation; only now are the link's paths approved
+      try {
+        await unwrap(commands.confirmLinkSend(selectedFiles));
+      } catch (err) {
+        console.error("Error confirming link send:", err);
+      }
+      setLinkSend(false);
+    }
+    if (syncChanges && selectedFiles.length === 1 && !archivePassword) {
+      await startChangesSend(selectedFiles[0]);
+    } else {
+      await startSend(
+        selectedFiles,
+        folderName,
+        archivePassword,
+        undefined,
+        storeOnly ? 0 : null,
+      );
+    }
     if (selectedFiles.length > 1) setFolderName(""); // Clear after sending
+    setArchivePassword("");
   }
 
   // Files handed to the app from a file-manager "Send via wyrmhole" entry.
@@ -387,20 +651,103 @@ function App() {
     startSend(paths, "");
   }
 
+  // Paths from a `wyrmhole://send` link. Any page can open one, so they're only selected;
+  // the user reviews them and presses Send (or clears them).
+  function select_files_from_link(paths: string[]) {
+    if (!paths || paths.length === 0) return;
+    setSelectedFiles(paths);
+    setFolderName("");
+    setLinkSend(true);
+    toast.warning("A link asked to send files", {
+      description: "Check the selection, then press Send to send it or Clear to discard it",
+      duration: 8000,
+    });
+  }
+
+  // Sends something the backend stages itself: the clipboard's copied files, image or text
+  // (`commands.sendClipboard`) or a screenshot (`commands.captureAndSend`). The card starts
+  // as a placeholder until the first send-progress event names the file.
+  async function send_staged(
+    send: (sendId: string) => Promise<Result<string, string>>,
+    label: string,
+  ) {
+    const sendId = crypto.randomUUID();
+    sendOps.set(sendId, {
+      id: sendId,
+      file_name: label,
+      sent: 0,
+      total: 0,
+      percentage: 0,
+      status: "preparing",
+    });
+
+    try {
+      const response = await unwrap(send(sendId));
+      console.log(`Sent ${label}:`, response);
+    } catch (err) {
+      console.error(`Error sending ${label}:`, err);
+      const errorMessage = errorText(err);
+      sendOps.update(
+        sendId,
+        { error: errorMessage },
+        {
+          id: sendId,
+          file_name: label,
+          sent: 0,
+          total: 0,
+          percentage: 0,
+          error: errorMessage,
+        },
+      );
+    }
+  }
+
   async function request_file() {
     if (!receiveCode.trim()) {
       return;
     }
 
     const codeToUse = receiveCode.trim();
+    setReceiveCode("");
+    await receive_with_code(codeToUse);
+  }
+
+  // Also used for codes from `wyrmhole://receive` automation URLs and wormhole code links.
+  async function receive_with_code(codeToUse: string) {
     const connectionId = `conn-${Date.now()}-${Math.random().toString(36).substr(2, 9)}`;
+    await awaitOffer(
+      connectionId,
+      codeToUse,
+      unwrap(commands.requestFileCall(codeToUse, connectionId)),
+    );
+  }
 
-    connectingOps.set(connectionId, codeToUse);
-    setReceiveCode("");
+  // Waits for the other side of a finished send to reply on the shared reply code.
+  async function await_reply(transferId: string, fileName: string) {
+    const connectionId = `conn-${Date.now()}-${Math.random().toString(36).substr(2, 9)}`;
+    await awaitOffer(
+      connectionId,
+      `Reply to ${fileName}`,
+      unwrap(commands.awaitReplyCall(transferId, connectionId)),
+    );
+  }
+
+  async function reply_with_file(transferId: string) {
+    const selected = await unwrap(commands.pickFilesForSend(false));
+    if (!selected || selected.length === 0) return;
+    await startSend([selected[0]], "", "", transferId);
+  }
+
+  // Shows `label` as connecting until `request` resolves, then lists the offer.
+  async function awaitOffer(
+    connectionId: string,
+    label: string,
+    request: Promise<FileOffer>,
+  ) {
+    connectingOps.set(connectionId, label);
 
     try {
-      const response = await invoke("request_file_call", { receiveCode: codeToUse, connectionId });
-      const data = JSON.parse(response as string);
+      const data = await request;
 
       const wasCancelled = cancelledConnections.current.has(connectionId);
       cancelledConnections.current.delete(connectionId);
@@ -416,7 +763,7 @@ function App() {
       // If the connection was cancelled, automatically deny the file offer
       if (wasCancelled) {
         try {
-          await invoke("receiving_file_deny", { id: data.id });
+          await unwrap(commands.receivingFileDeny(data.id, "Cancelled before the offer arrived"));
           console.log("Automatically denied file offer from cancelled connection:", data.id);
         } catch (error) {
           console.error("Error denying file from cancelled connection:", error);
@@ -424,11 +771,9 @@ function App() {
         return;
       }
 
-      offerOps.set(data.id, {
-        id: data.id,
-        file_name: data.file_name,
-        file_size: data.file_size,
-      });
+      if (data.text != null) return;
+
+      offerOps.set(data.id, data);
     } catch (e) {
       const errorMessage = e instanceof Error ? e.message : String(e);
 
@@ -456,7 +801,7 @@ function App() {
 
     if (connectionId) {
       try {
-        await invoke("cancel_connection", { connectionId });
+        await unwrap(commands.cancelConnection(connectionId));
         toast.success("Connection cancelled");
       } catch (err) {
         console.error("Error cancelling connection:", err);
@@ -467,9 +812,7 @@ function App() {
 
   async function recieved_files_data() {
     try {
-      const response = await invoke("received_files_data");
-      if (Array.isArray(response)) setReceivedFiles(response as ReceivedFile[]);
-      else setReceivedFiles([]);
+      setReceivedFiles(await unwrap(commands.receivedFilesData()));
     } catch (error) {
       console.error("Error getting received files data:", error);
     }
@@ -477,9 +820,7 @@ function App() {
 
   async function sent_files_data() {
     try {
-      const response = await invoke("sent_files_data");
-      if (Array.isArray(response)) setSentFiles(response as SentFile[]);
-      else setSentFiles([]);
+      setSentFiles(await unwrap(commands.sentFilesData()));
     } catch (error) {
       console.error("Error getting sent files data:", error);
     }
@@ -495,7 +836,7 @@ function App() {
 
   async function get_default_folder_name_format() {
     try {
-      const value = await invoke<string>("get_default_folder_name_format");
+      const value = await unwrap(commands.getDefaultFolderNameFormat());
       setDefaultFolderNameFormat(value);
     } catch (error) {
       console.error("Error getting default folder name format:", error);
@@ -504,7 +845,7 @@ function App() {
 
   async function cancel_all_transfers() {
     try {
-      await invoke("cancel_all_transfers");
+      await unwrap(commands.cancelAllTransfers());
 
       sendOps.clear();
       downloadOps.clear();
@@ -519,14 +860,64 @@ function App() {
     }
   }
 
+  // Offer to export crash reports left by a previous session (only written when
+  // crash reports are enabled in settings).
+  async function offer_crash_reports() {
+    try {
+      const reports = await unwrap(commands.listCrashReports());
+      if (reports.length === 0) return;
+      const latest = reports[reports.length - 1];
+      toast.warning("wyrmhole crashed during a previous session", {
+        description: "A crash report was saved locally. Export it to attach to a bug report.",
+        duration: Infinity,
+        action: {
+          label: "Export",
+          onClick: async () => {
+            const filePath = await unwrap(commands.pickExportPath(latest.file_name, "txt"));
+            if (!filePath) return;
+            try {
+              await unwrap(commands.exportCrashReport(latest.file_name, filePath));
+              await unwrap(commands.clearCrashReports());
+              toast.success("Crash report exported");
+            } catch (error) {
+              console.error("Error exporting crash report:", error);
+              toast.error("Failed to export crash report");
+            }
+          },
+        },
+        cancel: {
+          label: "Dismiss",
+          onClick: () => {
+            unwrap(commands.clearCrashReports()).catch((error) =>
+              console.error("Error clearing crash reports:", error),
+            );
+          },
+        },
+      });
+    } catch (error) {
+      console.error("Error listing crash reports:", error);
+    }
+  }
+
   useEffect(() => {
     recieved_files_data();
     sent_files_data();
     get_default_folder_name_format();
+    offer_crash_reports();
+    // Downloading implies checking, so only fall back to the notify-only check.
+    Promise.all([
+      unwrap(commands.getAutoDownloadUpdates()),
+      unwrap(commands.getCheckUpdatesAutomatically()),
+    ])
+      .then(([download, check]) => {
+        if (download) downloadUpdateInBackground();
+        else if (check) checkForUpdates(true);
+      })
+      .catch((err) => console.error("Error reading update settings:", err));
     // Signal that our `send-files-from-os` listener (below) is active so the
     // backend can flush any paths the app was cold-started with from a
     // file-manager "Send via wyrmhole" entry — dispatched as one batch.
-    invoke("frontend_ready").catch((err) => console.error("Error signaling frontend ready:", err));
+    commands.frontendReady().catch((err) => console.error("Error signaling frontend ready:", err));
   }, []);
 
   // Listen for native file drag-and-drop events
@@ -558,14 +949,60 @@ function App() {
     };
   }, []);
 
+  // Ctrl/Cmd+V outside a text field sends the clipboard. Ref so the listener isn't
+  // re-bound on every render.
+  const sendStagedRef = useRef(send_staged);
+  sendStagedRef.current = send_staged;
+  useEffect(() => {
+    const handlePaste = (e: ClipboardEvent) => {
+      const target = e.target as HTMLElement | null;
+      if (target?.closest("input, textarea, [contenteditable='true']")) return;
+      e.preventDefault();
+      sendStagedRef.current(commands.sendClipboard, "Clipboard");
+    };
+    document.addEventListener("paste", handlePaste);
+    return () => document.removeEventListener("paste", handlePaste);
+  }, []);
+
+  // Size up the selection in the background; a new selection cancels the old walks.
+  useEffect(() => {
+    selectionSizeOps.clear();
+    if (!selectedFiles) return;
+    const ids = selectedFiles.map((path) => {
+      const id = crypto.randomUUID();
+      unwrap(commands.computePathSize(path, id)).catch(() => {});
+      return id;
+    });
+    selectionSizeIds.current = new Set(ids);
+    return () => {
+      selectionSizeIds.current = new Set();
+      ids.forEach((id) => unwrap(commands.cancelPathSize(id)).catch(() => {}));
+    };
+  }, [selectedFiles, selectionSizeOps]);
+
+  useTauriEvent<{ id: string; bytes: number; files: number; done: boolean }>(
+    "path-size-progress",
+    (payload) => {
+      if (!selectionSizeIds.current.has(payload.id)) return;
+      selectionSizeOps.set(payload.id, { bytes: payload.bytes, done: payload.done });
+    },
+  );
+
   // Refresh history tables when backend emits add events.
   useTauriEvent("received-file-added", () => recieved_files_data());
+  useTauriEvent("received-files-added", () => recieved_files_data());
   useTauriEvent("sent-file-added", () => sent_files_data());
 
   // Files forwarded from a file-manager "Send via wyrmhole" entry while the
   // app is already running in the tray.
   useTauriEvent<string[]>("send-files-from-os", (paths) => send_files_from_os(paths));
 
+  // Paths from `wyrmhole://send?path=` URLs, waiting for the user to confirm.
+  useTauriEvent<string[]>("send-request-from-link", (paths) => select_files_from_link(paths));
+
+  // Codes from `wyrmhole://receive?code=` URLs opened by OS automation tools.
+  useTauriEvent<string>("receive-code-from-os", (code) => receive_with_code(code));
+
   useTauriEvent<{ value: string }>("default-folder-name-format-updated", (payload) => {
     setDefaultFolderNameFormat(payload.value);
   });
@@ -575,8 +1012,18 @@ function App() {
     code?: string;
     message?: string;
     send_id?: string;
-  }>("connection-code", (payload) => {
+  }>("connection-code", async (payload) => {
     if (payload.status === "success" && payload.send_id) {
+      // With "Hide Codes" on, the code stays off screen until revealed from the send card.
+      const hidden = await unwrap(commands.getHideSendCodes()).catch(() => false);
+      if (hidden) {
+        toast("📨 Connection code ready", {
+          duration: 5000,
+          description: "Open the send to reveal it",
+        });
+        return;
+      }
+
       const existing = sendProgress.get(payload.send_id);
       if (existing) {
         sendOps.set(payload.send_id, { ...existing, code: payload.code });
@@ -613,66 +1060,354 @@ function App() {
   useTauriEvent<DownloadProgress>("download-progress", (payload) => {
     downloadOps.set(payload.id, payload);
 
-    if (payload.percentage >= 100) {
-      setTimeout(() => {
-        toast.success(`Downloaded ${payload.file_name}`, { duration: 5000 });
-        downloadOps.delete(payload.id);
-      }, 500);
+    if (payload.status === "finalizing") {
+      setTimeout(() => downloadOps.delete(payload.id), 500);
     }
   });
 
-  useTauriEvent<{ id: string; file_name: string; error: string }>("download-error", (payload) => {
-    if (payload.error === "Transfer cancelled by user") {
-      downloadOps.delete(payload.id);
-      return;
-    }
-
-    downloadOps.update(
-      payload.id,
-      { error: payload.error },
-      {
-        id: payload.id,
-        file_name: payload.file_name,
-        transferred: 0,
-        total: 0,
-        percentage: 0,
-        error: payload.error,
+  // Completion toast with shortcuts to the saved file (backend checks the path is a received one).
+  useTauriEvent<{ id: string; file_name: string; path: string }>("download-complete", (payload) => {
+    const onError = (err: unknown) => {
+      console.error("Error opening received file:", err);
+      toast.error(String(err ?? "Failed to open file"));
+    };
+    toast.success(`Downloaded ${payload.file_name}`, {
+      duration: 8000,
+      description: (
+        <button
+          onClick={() => reply_with_file(payload.id).catch(onError)}
+          className="text-blue-600 hover:text-blue-700 underline cursor-pointer"
+        >
+          Reply with a file
+        </button>
+      ),
+      action: {
+        label: "Open",
+        onClick: () => unwrap(commands.openReceivedFile(payload.path)).catch(onError),
       },
-    );
-
-    toast.error(`Download failed: ${payload.file_name}`, { duration: 5000 });
+      cancel: {
+        label: "Show in folder",
+        onClick: () => unwrap(commands.showInFolder(payload.path)).catch(onError),
+      },
+    });
   });
 
+  // Text messages are previewed here. Unless the "save text messages" setting already saved
+  // it, the message can be saved as a .txt (accepting the offer) or dismissed (denying it).
+  useTauriEvent<{ id: string; text: string; saved_path: string | null }>(
+    "text-offer",
+    (payload) => {
+      const preview = payload.text.length > 300 ? `${payload.text.slice(0, 300)}…` : payload.text;
+      const onError = (err: unknown) => {
+        console.error("Error handling text message:", err);
+        toast.error(String(err ?? "Failed to save message"));
+      };
+      const savedPath = payload.saved_path;
+      if (savedPath) {
+        toast.success("Message received and saved", {
+          description: preview,
+          duration: 10000,
+          action: {
+            label: "Open",
+            onClick: () => unwrap(commands.openReceivedFile(savedPath)).catch(onError),
+          },
+        });
+        return;
+      }
+      const dismiss = () =>
+        unwrap(commands.receivingFileDeny(payload.id, null)).catch(() => {});
+      toast("Message received", {
+        description: preview,
+        duration: Infinity,
+        action: {
+          label: "Save",
+          onClick: () =>
+            unwrap(commands.receivingFileAccept(payload.id, null, null, null))
+              .then(() => toast.success("Message saved"))
+              .catch(onError),
+        },
+        cancel: { label: "Dismiss", onClick: dismiss },
+        onDismiss: dismiss,
+      });
+    },
+  );
+
+  useTauriEvent<{ id: string; file_name: string; error: string; code?: string | null }>(
+    "download-error",
+    (payload) => {
+      if (payload.error === "Transfer cancelled by user") {
+        downloadOps.delete(payload.id);
+        return;
+      }
+
+      // The download folder is read-only or its drive is gone. The offer is still waiting,
+      // so it goes back to the pending list to be saved somewhere else.
+      if (payload.code === "destination-unwritable") {
+        const offer = acceptingOffers.current.get(payload.id);
+        acceptingOffers.current.delete(payload.id);
+        downloadOps.delete(payload.id);
+        if (offer) offerOps.set(payload.id, offer);
+        toast.error(`Can't save ${payload.file_name}`, {
+          description: payload.error,
+          duration: 15000,
+          action: offer && {
+            label: "Choose Folder",
+            onClick: () => accept_file_receive_to(offer),
+          },
+        });
+        return;
+      }
+
+      downloadOps.update(
+        payload.id,
+        { error: payload.error },
+        {
+          id: payload.id,
+          file_name: payload.file_name,
+          transferred: 0,
+          total: 0,
+          percentage: 0,
+          error: payload.error,
+        },
+      );
+
+      // Recognised failures (disk full, path too long, ...) say what to do about them
+      toast.error(`Download failed: ${payload.file_name}`, {
+        description: payload.code ? payload.error : undefined,
+        duration: payload.code ? 10000 : 5000,
+      });
+    },
+  );
+
   useTauriEvent<SendProgress>("send-progress", (payload) => {
     sendOps.set(payload.id, payload);
 
-    if (payload.percentage >= 100) {
+    if (payload.status === "finalizing") {
       setTimeout(() => {
-        toast.success(`Sent ${payload.file_name}`, { duration: 5000 });
+        toast.success(`Sent ${payload.file_name}`, {
+          duration: 5000,
+          action: {
+            label: "Wait for reply",
+            onClick: () => await_reply(payload.id, payload.file_name),
+          },
+        });
         sendOps.delete(payload.id);
       }, 500);
     }
   });
 
-  useTauriEvent<{ id: string; file_name: string; error: string }>("send-error", (payload) => {
-    sendOps.update(
-      payload.id,
-      { error: payload.error },
-      {
-        id: payload.id,
-        file_name: payload.file_name,
+  // No data has moved for a while; the next progress event replaces the card and clears it.
+  useTauriEvent<{ id: string; idle_secs: number }>("transfer-stalled", (payload) => {
+    const send = sendProgress.get(payload.id);
+    if (send) sendOps.set(payload.id, { ...send, status: "stalled" });
+    const download = downloadProgress.get(payload.id);
+    if (download) downloadOps.set(payload.id, { ...download, stalled: true });
+  });
+
+  // Ticks every second while a code waits for the receiver.
+  useTauriEvent<{ id: string; age_secs: number; expires_in_secs: number | null }>(
+    "code-age",
+    (payload) => {
+      const send = sendProgress.get(payload.id);
+      if (send) {
+        sendOps.set(payload.id, {
+          ...send,
+          code_age_secs: payload.age_secs,
+          code_expires_in_secs: payload.expires_in_secs,
+        });
+      }
+    },
+  );
+
+  // The path a send is reading before it connects. Arrives before the send's first progress
+  // event, so it may have to make the card; that progress event replaces it.
+  useTauriEvent<{ id: string; path: string; index: number; count: number }>(
+    "preparing-details",
+    ({ id, path, index, count }) => {
+      const send = sendProgress.get(id) ?? {
+        id,
+        file_name: path.split(/[/\\]/).pop() || path,
         sent: 0,
         total: 0,
         percentage: 0,
-        error: payload.error,
+        status: "preparing",
+      };
+      sendOps.set(id, { ...send, preparing: { path, index, count } });
+    },
+  );
+
+  // Named pipes, sockets, device nodes and broken symlinks found in a sent folder are left
+  // out of the archive rather than failing the send.
+  useTauriEvent<{ id: string; files: { path: string; reason: string }[] }>(
+    "files-skipped",
+    ({ id, files }) => {
+      const name = sendProgress.get(id)?.file_name ?? "this send";
+      const shown = files
+        .slice(0, 5)
+        .map((file) => `${file.path} (${file.reason.replace("-", " ")})`);
+      if (files.length > shown.length) shown.push(`and ${files.length - shown.length} more`);
+      const count = files.length === 1 ? "1 special file" : `${files.length} special files`;
+      toast.warning(`Left ${count} out of ${name}`, {
+        description: shown.join("\n"),
+        duration: 10000,
+      });
+    },
+  );
+
+  // The receiver claimed the code; shown until packaging or transit reports in.
+  useTauriEvent<{ id: string; file_name: string }>("peer-connected", (payload) => {
+    const send = sendProgress.get(payload.id);
+    if (send) sendOps.set(payload.id, { ...send, status: "peer-connected" });
+  });
+
+  useTauriEvent<{
+    id: string;
+    file_name: string;
+    existing_path: string;
+    checksum: string;
+    skipped: boolean;
+  }>("duplicate-detected", (payload) => {
+    if (payload.skipped) {
+      toast.info(`${payload.file_name} was already received`, {
+        description: `Kept the earlier copy at ${payload.existing_path}`,
+      });
+      return;
+    }
+    toast.info(`${payload.file_name} matches a file you already received`, {
+      description: payload.existing_path,
+      duration: 10000,
+      action: {
+        label: "Skip duplicates",
+        onClick: () =>
+          unwrap(commands.setSkipDuplicateReceives(true))
+            .then(() => toast.success("Future duplicates won't be saved again"))
+            .catch((e) => toast.error(String(e))),
       },
-    );
+    });
+  });
+
+  // A received file turned out to be a program or script, or an archive holding some; it was
+  // kept as saved instead of being extracted or run through the After Receiving rules.
+  useTauriEvent<{
+    id: string;
+    file_name: string;
+    path: string;
+    warning: ContentWarning;
+  }>("content-flagged", (payload) => {
+    const what = {
+      executable: "is a program",
+      script: "is a script",
+      "archive-with-executable": "contains programs or scripts",
+      "unchecked-archive": "is an archive too large or damaged to check",
+    }[payload.warning];
+    toast.warning(`${payload.file_name} ${what}`, {
+      description: `Kept as received at ${payload.path}. Only open it if you trust the sender.`,
+      duration: 10000,
+    });
+  });
+
+  // A failed packaging step keeps its send (and code) open until it's retried or cancelled.
+  useTauriEvent<{
+    job_id: string;
+    send_id: string;
+    name: string;
+    state: "running" | "done" | "failed" | "cancelled";
+    written: number;
+    error: string | null;
+  }>("packaging-job", (payload) => {
+    const toastId = `packaging-${payload.job_id}`;
+    if (payload.state !== "failed") {
+      toast.dismiss(toastId);
+      return;
+    }
+    toast.error(`Couldn't package ${payload.name}`, {
+      id: toastId,
+      description: `${payload.error ?? "Unknown error"}. The code still works; fix it and retry.`,
+      duration: Infinity,
+      action: {
+        label: "Retry",
+        onClick: () =>
+          unwrap(commands.retryPackagingJob(payload.job_id)).catch((e) =>
+            toast.error(String(e)),
+          ),
+      },
+      cancel: {
+        label: "Cancel send",
+        onClick: () =>
+          unwrap(commands.cancelPackagingJob(payload.job_id)).catch((e) =>
+            toast.error(String(e)),
+          ),
+      },
+    });
+  });
+
+  // One toast however many transfers noticed the change.
+  useTauriEvent<{ id: string; previous: string | null; current: string | null }>(
+    "network-changed",
+    (payload) => {
+      toast.warning(
+        payload.current
+          ? "Your network changed. Running transfers may stall; if one fails, try it again."
+          : "Your network connection dropped. Running transfers will stall until it's back.",
+        { id: "network-changed", duration: 8000 },
+      );
+    },
+  );
+
+  useTauriEvent<{ id: string; file_name: string; error: string; code: string | null }>(
+    "send-error",
+    (payload) => {
+      sendOps.update(
+        payload.id,
+        { error: payload.error },
+        {
+          id: payload.id,
+          file_name: payload.file_name,
+          sent: 0,
+          total: 0,
+          percentage: 0,
+          error: payload.error,
+        },
+      );
+
+      // Declined offers get their own toast from `offer-declined`
+      if (
+        payload.error !== "Transfer cancelled by user" &&
+        payload.code !== "offer-declined"
+      ) {
+        toast.error(`Send failed: ${payload.file_name}`, { duration: 5000 });
+      }
+    },
+  );
 
-    if (payload.error !== "Transfer cancelled by user") {
-      toast.error(`Send failed: ${payload.file_name}`, { duration: 5000 });
+  // A send that showed a code ended without delivering; its code no longer works, so it
+  // mustn't be read out again for a retry.
+  useTauriEvent<{ id: string; code: string }>("code-expired", ({ id, code }) => {
+    const send = sendProgress.get(id);
+    if (send) sendOps.set(id, { ...send, code_expired: true });
+    for (const [toastId, storedCode] of connectionCodeToasts.current.entries()) {
+      if (storedCode === code) {
+        toast.dismiss(toastId);
+        connectionCodeToasts.current.delete(toastId);
+      }
     }
   });
 
+  useTauriEvent<{ id: string; file_name: string }>("offer-declined", (payload) => {
+    toast.info(`${payload.file_name} was declined`, {
+      description: "The receiver turned down the transfer",
+      duration: 5000,
+    });
+  });
+
+  useTauriEvent<{ id: string; file_name: string }>("offer-expired", (payload) => {
+    offerOps.delete(payload.id);
+    toast.info(`Offer for ${payload.file_name} expired`, {
+      description: "It went unanswered, so the transfer was declined",
+      duration: 5000,
+    });
+  });
+
   // Set up event delegation for connection code toasts
   useEffect(() => {
     const handleToastClick = async (e: MouseEvent) => {
@@ -869,6 +1604,52 @@ function App() {
                           title="Custom name for the folder when sending multiple files. Leave empty to use the default format."
                         />
                       )}
+                      {selectedFiles && selectedFiles.length > 1 && !archivePassword && (
+                        <label
+                          className="flex items-center gap-1 text-xs xl:text-sm text-gray-600 cursor-pointer flex-shrink-0"
+                          title="Pack without compressing, for photos, videos and other files that are already compressed. Faster, and hardly any bigger."
+                        >
+                          <input
+                            type="checkbox"
+                            checked={storeOnly}
+                            onChange={(e) => setStoreOnly(e.target.checked)}
+                            className="cursor-pointer"
+                          />
+                          Store only
+                        </label>
+                      )}
+                      {selectedFiles && selectedFiles.length === 1 && !archivePassword && (
+                        <label
+                          className="flex items-center gap-1 text-xs xl:text-sm text-gray-600 cursor-pointer flex-shrink-0"
+                          title="For a folder or large file you send repeatedly: only send the files or blocks that changed since the last time. Receivers who merge updates (see Settings) get them applied to their copy."
+                        >
+                          <input
+                            type="checkbox"
+                            checked={syncChanges}
+                            onChange={(e) => setSyncChanges(e.target.checked)}
+                            className="cursor-pointer"
+                          />
+                          Changes only
+                        </label>
+                      )}
+                      {selectedFiles && (
+                        <input
+                          type="password"
+                          value={archivePassword}
+                          onChange={(e) => setArchivePassword(e.target.value)}
+                          placeholder="Password (optional)"
+                          autoComplete="new-password"
+                          className="w-28 xl:w-36 px-2 py-1 text-xs xl:text-sm rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500/20 transition-all border border-gray-200"
+                          style={{
+                            background: "rgba(255, 255, 255, 0.7)",
+                            backdropFilter: "blur(16px)",
+                            WebkitBackdropFilter: "blur(16px)",
+                            boxShadow:
+                              "0 2px 8px 0 rgba(0, 0, 0, 0.05), inset 0 1px 0 0 rgba(255, 255, 255, 0.3)",
+                          }}
+                          title="Send the selection as a password-protected (AES-256) zip. Share the password with the receiver separately, not alongside the code."
+                        />
+                      )}
                       {selectedFiles && (
                         <button
                           onClick={send_files}
@@ -967,12 +1748,46 @@ function App() {
                           Select a folder instead
                         </button>
                       )}
+                      {!isDragging && (
+                        <button
+                          onClick={(e) => {
+                            e.preventDefault();
+                            e.stopPropagation();
+                            send_staged(commands.sendClipboard, "Clipboard");
+                          }}
+                          className="mt-1.5 text-[10px] xl:text-xs font-medium text-blue-600 hover:text-blue-700 cursor-pointer"
+                          title="Send the copied files, image or text (Ctrl/Cmd+V)"
+                        >
+                          Send clipboard
+                        </button>
+                      )}
+                      {!isDragging && (
+                        <button
+                          onClick={(e) => {
+                            e.preventDefault();
+                            e.stopPropagation();
+                            send_staged(commands.captureAndSend, "Screenshot");
+                          }}
+                          className="mt-1 text-[10px] xl:text-xs font-medium text-blue-600 hover:text-blue-700 cursor-pointer"
+                          title="Capture the screen and send it right away"
+                        >
+                          Send screenshot
+                        </button>
+                      )}
                     </label>
                   ) : (
                     <div className="flex-1 flex flex-col min-h-0">
                       <div className="flex items-center justify-between mb-2 flex-shrink-0">
                         <span className="text-xs xl:text-sm font-medium text-gray-700">
                           {selectedFiles.length} {selectedFiles.length === 1 ? "file" : "files"}
+                          {selectionSizes.size > 0 && (
+                            <span className="ml-1 font-normal text-gray-500">
+                              {selectionSizes.size === selectedFiles.length &&
+                              [...selectionSizes.values()].every((s) => s.done)
+                                ? `· ${formatBytes(selectionSize)}`
+                                : `· calculating… ${formatBytes(selectionSize)} so far`}
+                            </span>
+                          )}
                         </span>
                         <div className="flex items-center gap-2">
                           <button
@@ -1000,6 +1815,13 @@ function App() {
                               e.stopPropagation();
                               setSelectedFiles(null);
                               setFolderName("");
+                              setArchivePassword("");
+                              if (linkSend) {
+                                setLinkSend(false);
+                                commands.discardLinkSend().catch((err) =>
+                                  console.error("Error discarding link send:", err),
+                                );
+                              }
                             }}
                             className="text-[10px] xl:text-xs text-gray-500 hover:text-red-600 transition-colors cursor-pointer"
                             title="Clear"
@@ -1008,6 +1830,11 @@ function App() {
                           </button>
                         </div>
                       </div>
+                      {linkSend && (
+                        <p className="mb-2 flex-shrink-0 rounded-xl px-3 py-2 text-[11px] text-amber-800 bg-amber-50 border border-amber-200">
+                          A link asked to send these. Nothing is sent until you press Send.
+                        </p>
+                      )}
                       <div
                         className="flex-1 overflow-y-auto pr-1 min-h-0"
                         style={{ scrollbarWidth: "thin" }}
@@ -1128,12 +1955,31 @@ function App() {
                   {connectingCodes.size > 0 || pendingFileOffers.size > 0 ? (
                     <div className="flex flex-col">
                       {(connectingCodes.size > 0 || pendingFileOffers.size > 0) && (
-                        <div className="text-[10px] sm:text-xs xl:text-sm font-semibold text-gray-700 uppercase tracking-wide mb-2 flex-shrink-0">
-                          {connectingCodes.size > 0 && pendingFileOffers.size > 0
-                            ? `Connecting (${connectingCodes.size}) • Pending Offers (${pendingFileOffers.size})`
-                            : connectingCodes.size > 0
-                              ? `Connecting (${connectingCodes.size})`
-                              : `Pending Offers (${pendingFileOffers.size})`}
+                        <div className="flex items-center justify-between gap-2 mb-2 flex-shrink-0">
+                          <div className="text-[10px] sm:text-xs xl:text-sm font-semibold text-gray-700 uppercase tracking-wide">
+                            {connectingCodes.size > 0 && pendingFileOffers.size > 0
+                              ? `Connecting (${connectingCodes.size}) • Pending Offers (${pendingFileOffers.size})`
+                              : connectingCodes.size > 0
+                                ? `Connecting (${connectingCodes.size})`
+                                : `Pending Offers (${pendingFileOffers.size})`}
+                          </div>
+                          {pendingFileOffers.size > 1 && (
+                            <div className="flex items-center gap-1 flex-shrink-0">
+                              <button
+                                onClick={accept_all_offers}
+                                className="px-2 py-0.5 text-[10px] xl:text-xs font-semibold text-green-700 hover:bg-green-50 rounded transition-colors cursor-pointer"
+                                title="Accept every offer that doesn't need confirming"
+                              >
+                                Accept all
+                              </button>
+                              <button
+                                onClick={deny_all_offers}
+                                className="px-2 py-0.5 text-[10px] xl:text-xs font-semibold text-red-600 hover:bg-red-50 rounded transition-colors cursor-pointer"
+                              >
+                                Deny all
+                              </button>
+                            </div>
+                          )}
                         </div>
                       )}
                       <div
@@ -1153,11 +1999,23 @@ function App() {
                             onAccept={(id) => {
                               const offer = pendingFileOffers.get(id);
                               if (offer) {
-                                accept_file_receive(id, offer.file_name);
+                                // The card only lets oversize offers, programs and scripts
+                                // through from its confirmation view.
+                                accept_file_receive(
+                                  id,
+                                  offer.file_name,
+                                  undefined,
+                                  offer.over_size_limit,
+                                  !!offer.content_warning,
+                                );
                               }
                             }}
-                            onDeny={(id) => {
-                              deny_file_receive(id);
+                            onAcceptTo={(id) => {
+                              const offer = pendingFileOffers.get(id);
+                              if (offer) accept_file_receive_to(offer);
+                            }}
+                            onDeny={(id, reason) => {
+                              deny_file_receive(id, reason);
                             }}
                           />
                         ))}
@@ -1212,6 +2070,22 @@ function App() {
                   >
                     Sent
                   </button>
+                  <span className="text-gray-400">/</span>
+                  <button
+                    onClick={() => {
+                      if (historyTab !== "rejected") {
+                        setHistoryTab("rejected");
+                        recieved_files_data();
+                      }
+                    }}
+                    className={`px-2 py-1 rounded-xl transition-all duration-200 ${
+                      historyTab === "rejected"
+                        ? "text-blue-700 font-semibold"
+                        : "text-gray-500 hover:text-blue-600"
+                    }`}
+                  >
+                    Rejected
+                  </button>
                 </div>
               </div>
               <div className="flex items-center gap-1.5 text-[10px] sm:text-xs">
@@ -1292,6 +2166,7 @@ function App() {
                   receivedFiles.length > 0 ? (
                     <div className="divide-y divide-gray-100">
                       {receivedFiles
+                        .filter((file) => file.status !== "rejected")
                         .slice()
                         .reverse()
                         .filter((file) => {
@@ -1324,7 +2199,13 @@ function App() {
                           return nameMatch && sizeMatch && dateMatch;
                         })
                         .map((file, idx) => (
-                          <ReceiveFileCard key={idx} {...file} />
+                          <ReceiveFileCard
+                            key={idx}
+                            {...file}
+                            onSendBack={() => send_back(file)}
+                            onDragOut={() => drag_out(file)}
+                            onRemove={(deleteFile) => remove_received(file, deleteFile)}
+                          />
                         ))}
                     </div>
                   ) : (
@@ -1332,6 +2213,26 @@ function App() {
                       No Received File History
                     </div>
                   )
+                ) : historyTab === "rejected" ? (
+                  rejectedFiles.length > 0 ? (
+                    <div className="divide-y divide-gray-100">
+                      {rejectedFiles
+                        .slice()
+                        .reverse()
+                        .filter((file) =>
+                          historySearch
+                            ? file.file_name.toLowerCase().includes(historySearch.toLowerCase())
+                            : true,
+                        )
+                        .map((file, idx) => (
+                          <RejectedOfferCard key={idx} {...file} />
+                        ))}
+                    </div>
+                  ) : (
+                    <div className="flex items-center justify-center h-48 sm:h-64 text-xs sm:text-sm text-gray-400">
+                      No Rejected Offers
+                    </div>
+                  )
                 ) : sentFiles.length > 0 ? (
                   <div className="divide-y divide-gray-100">
                     {sentFiles
@@ -1367,17 +2268,10 @@ function App() {
                         return nameMatch && sizeMatch && dateMatch;
                       })
                       .map((file, idx) => {
-                        const fileWithPaths: { file_paths: string[] } & Omit<
-                          SentFile,
-                          "file_path" | "file_paths"
-                        > = {
-                          ...file,
-                          file_paths: file.file_paths || (file.file_path ? [file.file_path] : []),
-                        };
                         return (
                           <SentFileCard
                             key={idx}
-                            {...fileWithPaths}
+                            {...file}
                             onResend={(paths) => prepare_resend_from_history(paths)}
                           />
                         );
@@ -1,13 +1,28 @@
 import { useState } from "react";
 import { FileIcon } from "./FileIcon";
 import { DetailModal } from "./DetailModal";
+import type { ContentWarning } from "./bindings";
 
 type Props = {
   id: string;
   file_name: string;
   file_size?: number;
+  // Over the incoming size limit: accepting only happens from the detail view, which says so.
+  over_size_limit?: boolean;
+  // A program or script by its name (see content.rs); also only accepted from the detail view.
+  content_warning?: ContentWarning | null;
   onAccept: (id: string) => void;
-  onDeny: (id: string) => void;
+  // Accept into a folder picked for this offer instead of the download directory.
+  onAcceptTo?: (id: string) => void;
+  // `reason` is kept in the Rejected history; the sender only hears the offer was declined.
+  onDeny: (id: string, reason?: string) => void;
+};
+
+const CONTENT_WARNING_TEXT: Record<ContentWarning, string> = {
+  executable: "This file is a program",
+  script: "This file is a script",
+  "archive-with-executable": "This archive contains programs or scripts",
+  "unchecked-archive": "This archive is too large or damaged to check",
 };
 
 function formatBytes(bytes: number | undefined): string {
@@ -18,14 +33,25 @@ function formatBytes(bytes: number | undefined): string {
   return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + " " + sizes[i];
 }
 
-const PendingFileOfferCard = ({ id, file_name, file_size, onAccept, onDeny }: Props) => {
+const PendingFileOfferCard = ({
+  id,
+  file_name,
+  file_size,
+  over_size_limit = false,
+  content_warning = null,
+  onAccept,
+  onAcceptTo,
+  onDeny,
+}: Props) => {
   const [isOpen, setIsOpen] = useState(false);
+  const [reason, setReason] = useState("");
+  const needsConfirming = over_size_limit || !!content_warning;
 
   return (
     <>
       <div
         onClick={() => setIsOpen(true)}
-        className="grid grid-cols-[1fr_auto] items-center gap-2 px-2 py-1.5 border-b border-gray-200 last:border-b-0 cursor-pointer transition-all rounded-xl bg-yellow-50 hover:bg-yellow-100"
+        className={`grid grid-cols-[1fr_auto] items-center gap-2 px-2 py-1.5 border-b border-gray-200 last:border-b-0 cursor-pointer transition-all rounded-xl ${needsConfirming ? "bg-red-50 hover:bg-red-100" : "bg-yellow-50 hover:bg-yellow-100"}`}
         style={{
           WebkitBackdropFilter: "blur(8px)",
           border: "1px solid rgb(229, 231, 235)",
@@ -34,12 +60,24 @@ const PendingFileOfferCard = ({ id, file_name, file_size, onAccept, onDeny }: Pr
         <div className="flex items-center gap-1.5 text-gray-700 min-w-0">
           <FileIcon fileName={file_name} className="w-3.5 h-3.5 flex-shrink-0" />
           <span className="text-[11px] xl:text-xs truncate font-medium">{file_name}</span>
+          {over_size_limit && (
+            <span className="text-[10px] font-semibold text-red-600 flex-shrink-0">Large</span>
+          )}
+          {content_warning && (
+            <span className="text-[10px] font-semibold text-red-600 flex-shrink-0">
+              {content_warning === "script" ? "Script" : "Program"}
+            </span>
+          )}
         </div>
         <div className="flex items-center gap-1">
           <button
             onClick={(e) => {
               e.stopPropagation();
-              onAccept(id);
+              if (needsConfirming) {
+                setIsOpen(true);
+              } else {
+                onAccept(id);
+              }
             }}
             className="p-1 bg-green-600 hover:bg-green-700 text-white text-[10px] rounded transition-colors cursor-pointer"
             title="Accept"
@@ -105,11 +143,22 @@ const PendingFileOfferCard = ({ id, file_name, file_size, onAccept, onDeny }: Pr
               >
                 <path strokeLinecap="round" strokeLinejoin="round" d="M4.5 12.75l6 6 9-13.5" />
               </svg>
-              Accept
+              {needsConfirming ? "Accept anyway" : "Accept"}
             </button>
+            {onAcceptTo && (
+              <button
+                onClick={() => {
+                  onAcceptTo(id);
+                  setIsOpen(false);
+                }}
+                className="flex-1 px-4 py-2.5 text-gray-700 text-sm font-semibold rounded-2xl border border-gray-200 hover:bg-gray-50 transition-all duration-200"
+              >
+                Save to…
+              </button>
+            )}
             <button
               onClick={() => {
-                onDeny(id);
+                onDeny(id, reason.trim() || undefined);
                 setIsOpen(false);
               }}
               className="modal-btn-danger flex-1 px-4 py-2.5 text-red-600 text-sm font-semibold rounded-2xl transition-all duration-200 flex items-center justify-center gap-2"
@@ -130,6 +179,27 @@ const PendingFileOfferCard = ({ id, file_name, file_size, onAccept, onDeny }: Pr
         }
       >
         <div className="px-6 py-5 space-y-4">
+          {over_size_limit && (
+            <div className="rounded-xl px-4 py-3 bg-red-50 border border-red-100">
+              <p className="text-xs font-semibold text-red-700">
+                Larger than your incoming size limit
+              </p>
+              <p className="text-xs text-red-600 mt-1">
+                Make sure there&apos;s room for {formatBytes(file_size)} before accepting.
+              </p>
+            </div>
+          )}
+          {content_warning && (
+            <div className="rounded-xl px-4 py-3 bg-red-50 border border-red-100">
+              <p className="text-xs font-semibold text-red-700">
+                {CONTENT_WARNING_TEXT[content_warning]}
+              </p>
+              <p className="text-xs text-red-600 mt-1">
+                Wormhole codes don&apos;t say who&apos;s sending. Only accept it if you know what it
+                is and who it&apos;s from.
+              </p>
+            </div>
+          )}
           <div className="grid grid-cols-2 gap-3">
             <div>
               <p className="text-xs text-gray-500 mb-1">Size</p>
@@ -142,6 +212,20 @@ const PendingFileOfferCard = ({ id, file_name, file_size, onAccept, onDeny }: Pr
               </p>
             </div>
           </div>
+          <div>
+            <label htmlFor={`deny-reason-${id}`} className="text-xs text-gray-500 mb-1 block">
+              Reason if declining (optional, kept in your history)
+            </label>
+            <input
+              id={`deny-reason-${id}`}
+              type="text"
+              maxLength={200}
+              value={reason}
+              onChange={(e) => setReason(e.target.value)}
+              placeholder="e.g. Not expecting this"
+              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
+            />
+          </div>
         </div>
       </DetailModal>
     </>
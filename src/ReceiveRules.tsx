@@ -0,0 +1,124 @@
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { XIcon } from "./Icons";
+import { commands, type HookAction, type ReceiveHook } from "./bindings";
+import { unwrap } from "./commands";
+
+const TARGET_PLACEHOLDERS: Record<HookAction, string> = {
+  move_to: "~/Documents/Invoices",
+  run_command: 'convert "{path}" "{dir}/small-{name}"',
+  open_with: "Preview",
+};
+
+// Post-receive rules for the Settings menu: each received file whose name matches a
+// rule's pattern is moved, handed to a command or opened (see hooks.rs). Rows still being
+// filled in are kept locally and only complete ones are saved.
+export default function ReceiveRules() {
+  const [rules, setRules] = useState<ReceiveHook[]>([]);
+
+  useEffect(() => {
+    unwrap(commands.getReceiveHooks())
+      .then(setRules)
+      .catch((e) => console.error("Error getting get_receive_hooks:", e));
+  }, []);
+
+  async function save(next: ReceiveHook[]) {
+    setRules(next);
+    const complete = next.filter((rule) => rule.pattern.trim() && rule.target.trim());
+    try {
+      await unwrap(commands.setReceiveHooks(complete));
+    } catch (e) {
+      // Folders that weren't picked come back as a ScopeError, with a message of their own
+      toast.error(
+        e && typeof e === "object" && "message" in e
+          ? String(e.message)
+          : String(e ?? "Failed to save rules"),
+      );
+    }
+  }
+
+  function edit(index: number, change: Partial<ReceiveHook>) {
+    setRules((prev) => prev.map((rule, i) => (i === index ? { ...rule, ...change } : rule)));
+  }
+
+  // Picked through the backend, so the folder is one `set_receive_hooks` accepts.
+  async function chooseFolder(index: number) {
+    const selected = await unwrap(commands.pickDirectory()).catch(() => null);
+    if (!selected) return;
+    save(rules.map((rule, i) => (i === index ? { ...rule, target: selected } : rule)));
+  }
+
+  return (
+    <div className="space-y-1.5">
+      <div className="flex items-center justify-between">
+        <span className="text-xs font-medium text-gray-700">After Receiving</span>
+        <button
+          onClick={() =>
+            setRules((prev) => [...prev, { pattern: "", action: "move_to", target: "" }])
+          }
+          className="text-[11px] text-blue-600 hover:text-blue-700 cursor-pointer"
+        >
+          + Add rule
+        </button>
+      </div>
+      {rules.map((rule, index) => (
+        <div key={index} className="flex items-center gap-1">
+          <input
+            type="text"
+            value={rule.pattern}
+            onChange={(e) => edit(index, { pattern: e.target.value })}
+            onBlur={() => save(rules)}
+            placeholder="*.pdf"
+            aria-label="File pattern"
+            className="w-16 px-2 py-1 bg-white border border-gray-200 rounded-lg text-xs text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50"
+          />
+          <select
+            value={rule.action}
+            onChange={(e) =>
+              save(
+                rules.map((r, i) =>
+                  i === index ? { ...r, action: e.target.value as HookAction } : r,
+                ),
+              )
+            }
+            aria-label="Action"
+            className="px-1 py-1 bg-white border border-gray-200 rounded-lg text-xs text-gray-900 cursor-pointer focus:outline-none"
+          >
+            <option value="move_to">Move to</option>
+            <option value="run_command">Run</option>
+            <option value="open_with">Open with</option>
+          </select>
+          <input
+            type="text"
+            value={rule.target}
+            onChange={(e) => edit(index, { target: e.target.value })}
+            onBlur={() => save(rules)}
+            onDoubleClick={() => rule.action === "move_to" && chooseFolder(index)}
+            placeholder={TARGET_PLACEHOLDERS[rule.action]}
+            aria-label="Target"
+            title={rule.action === "move_to" ? "Double-click to choose a folder" : undefined}
+            className="flex-1 min-w-0 px-2 py-1 bg-white border border-gray-200 rounded-lg text-xs text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50"
+          />
+          <button
+            onClick={() => save(rules.filter((_, i) => i !== index))}
+            title="Remove rule"
+            className="p-0.5 rounded hover:bg-gray-100 cursor-pointer flex-shrink-0"
+          >
+            <XIcon className="w-3.5 h-3.5 fill-gray-500 hover:fill-gray-700" />
+          </button>
+        </div>
+      ))}
+      <p className="text-[11px] text-gray-500">
+        Matching rules run in order on each received file. Commands can use{" "}
+        <code className="font-mono bg-gray-100 px-1 rounded">{"{path}"}</code>,{" "}
+        <code className="font-mono bg-gray-100 px-1 rounded">{"{name}"}</code> and{" "}
+        <code className="font-mono bg-gray-100 px-1 rounded">{"{dir}"}</code>. Rules only apply
+        to files kept as received: archives that are unpacked automatically, and duplicates that
+        are skipped, are left as they are. The name is the sender's choice, so prefer{" "}
+        <code className="font-mono bg-gray-100 px-1 rounded">{"{path}"}</code>; commands run in the
+        file's folder. Folders to move to are chosen by double-clicking, and rules that run or
+        open something ask before they're saved
+      </p>
+    </div>
+  );
+}
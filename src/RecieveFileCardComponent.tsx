@@ -3,6 +3,8 @@ import { openPath } from "@tauri-apps/plugin-opener";
 import { toast } from "sonner";
 import { FileIcon } from "./FileIcon";
 import { DetailModal } from "./DetailModal";
+import { commands, type TransferStatus } from "./bindings";
+import { unwrap } from "./commands";
 
 type Props = {
   connection_type: string;
@@ -12,6 +14,19 @@ type Props = {
   file_name: string;
   file_size: number;
   peer_address: string;
+  // "completed" when missing (entries from before statuses were recorded).
+  status?: TransferStatus;
+  reason?: string | null;
+  // SHA-256 taken as the file arrived; missing for folders and older entries.
+  checksum?: string | null;
+  // Missing only until the history has been loaded once by this version.
+  id?: string;
+  // Starts a new send of this file (completed entries only).
+  onSendBack?: () => void;
+  // Starts a native drag of the saved file (completed entries only).
+  onDragOut?: () => void;
+  // Removes the entry from history, moving the saved file to the trash with `deleteFile`.
+  onRemove?: (deleteFile: boolean) => void;
 };
 
 function formatFileSize(bytes: number): string {
@@ -30,8 +45,27 @@ const ReceiveFileCard = ({
   file_name,
   file_size,
   peer_address,
+  status = "completed",
+  reason,
+  checksum,
+  id,
+  onSendBack,
+  onDragOut,
+  onRemove,
 }: Props) => {
   const [isOpen, setIsOpen] = useState(false);
+  const [deleteFile, setDeleteFile] = useState(false);
+  const [intact, setIntact] = useState<boolean | null>(null);
+
+  // Re-hashes the saved file and compares it with the checksum taken when it arrived.
+  async function verify() {
+    try {
+      setIntact(await unwrap(commands.verifyReceivedFile(id)));
+    } catch (err) {
+      console.error("Error verifying received file:", err);
+      toast.error(String(err));
+    }
+  }
 
   const handleOpenPath = async () => {
     try {
@@ -46,13 +80,27 @@ const ReceiveFileCard = ({
     <>
       <div
         onClick={() => setIsOpen(true)}
+        draggable={Boolean(onDragOut) && status === "completed"}
+        onDragStart={(e) => {
+          // The native drag replaces the webview's own
+          e.preventDefault();
+          onDragOut?.();
+        }}
         className="grid grid-cols-[2fr_1fr_1fr] items-center select-none px-2 sm:px-4 py-2 sm:py-3 cursor-pointer text-gray-700 transition-all duration-200 border-b border-gray-200 last:border-b-0 group m-0 bg-transparent hover:bg-blue-50"
       >
         <div className="flex items-center gap-1.5 sm:gap-2 font-medium truncate text-[10px] sm:text-xs xl:text-sm">
           <FileIcon fileName={`${file_name}.${file_extension}`} className="w-4 h-4 flex-shrink-0" />
           <span>{file_name}</span>
         </div>
-        <div className="text-[9px] sm:text-[10px] xl:text-xs text-gray-500">.{file_extension}</div>
+        {status === "completed" ? (
+          <div className="text-[9px] sm:text-[10px] xl:text-xs text-gray-500">
+            .{file_extension}
+          </div>
+        ) : (
+          <div className="text-[9px] sm:text-[10px] xl:text-xs text-red-600 capitalize">
+            {status}
+          </div>
+        )}
         <div className="text-[9px] sm:text-[10px] xl:text-xs font-medium text-gray-600">
           {formatFileSize(file_size)}
         </div>
@@ -72,6 +120,12 @@ const ReceiveFileCard = ({
         subtitle="Received file"
       >
         <div className="px-6 py-5 space-y-4">
+          {status !== "completed" && (
+            <div className="rounded-xl px-4 py-3 bg-red-50 border border-red-100">
+              <p className="text-xs font-semibold text-red-700 capitalize">{status}</p>
+              {reason && <p className="text-xs text-red-600 mt-1 break-words">{reason}</p>}
+            </div>
+          )}
           {/* File Info Grid */}
           <div className="grid grid-cols-2 gap-3">
             <div>
@@ -85,41 +139,86 @@ const ReceiveFileCard = ({
           </div>
 
           {/* Download Path - Refined */}
-          <div>
-            <p className="text-xs font-medium text-gray-500 mb-2.5 uppercase tracking-wide">
-              Downloaded To
-            </p>
-            <button
-              type="button"
-              onClick={handleOpenPath}
-              title="Open folder"
-              className="w-full flex items-center gap-2 text-left rounded-xl px-4 py-3 cursor-pointer transition-colors hover:bg-gray-50 focus:outline-none focus-visible:ring-2 focus-visible:ring-blue-400"
-              style={{
-                background: "#ffffff",
-                border: "1px solid rgb(229, 231, 235)",
-                boxShadow: "0 2px 8px 0 rgba(0, 0, 0, 0.05)",
-              }}
-            >
-              <svg
-                xmlns="http://www.w3.org/2000/svg"
-                viewBox="0 0 20 20"
-                fill="currentColor"
-                aria-hidden="true"
-                className="w-4 h-4 flex-shrink-0 text-blue-600"
+          {status === "completed" && (
+            <div>
+              <p className="text-xs font-medium text-gray-500 mb-2.5 uppercase tracking-wide">
+                Downloaded To
+              </p>
+              <button
+                type="button"
+                onClick={handleOpenPath}
+                title="Open folder"
+                className="w-full flex items-center gap-2 text-left rounded-xl px-4 py-3 cursor-pointer transition-colors hover:bg-gray-50 focus:outline-none focus-visible:ring-2 focus-visible:ring-blue-400"
+                style={{
+                  background: "#ffffff",
+                  border: "1px solid rgb(229, 231, 235)",
+                  boxShadow: "0 2px 8px 0 rgba(0, 0, 0, 0.05)",
+                }}
               >
-                <path d="M3.75 3A1.75 1.75 0 0 0 2 4.75v10.5C2 16.216 2.784 17 3.75 17h12.5A1.75 1.75 0 0 0 18 15.25v-8.5A1.75 1.75 0 0 0 16.25 5h-6.19l-1.2-1.2A1.75 1.75 0 0 0 7.62 3H3.75Z" />
-              </svg>
-              <span className="text-sm font-mono text-gray-900 break-words whitespace-pre-wrap">
-                {download_url}
-              </span>
-            </button>
-          </div>
+                <svg
+                  xmlns="http://www.w3.org/2000/svg"
+                  viewBox="0 0 20 20"
+                  fill="currentColor"
+                  aria-hidden="true"
+                  className="w-4 h-4 flex-shrink-0 text-blue-600"
+                >
+                  <path d="M3.75 3A1.75 1.75 0 0 0 2 4.75v10.5C2 16.216 2.784 17 3.75 17h12.5A1.75 1.75 0 0 0 18 15.25v-8.5A1.75 1.75 0 0 0 16.25 5h-6.19l-1.2-1.2A1.75 1.75 0 0 0 7.62 3H3.75Z" />
+                </svg>
+                <span className="text-sm font-mono text-gray-900 break-words whitespace-pre-wrap">
+                  {download_url}
+                </span>
+              </button>
+            </div>
+          )}
+
+          {status === "completed" && checksum && (
+            <div>
+              <p className="text-xs text-gray-500 mb-1">SHA-256</p>
+              <p className="text-[11px] font-mono text-gray-900 break-all">{checksum}</p>
+              {intact !== null && (
+                <p className={`text-[11px] mt-1 ${intact ? "text-green-600" : "text-red-600"}`}>
+                  {intact ? "Matches what was received" : "Changed or corrupted since it arrived"}
+                </p>
+              )}
+              {id && (
+                <button
+                  type="button"
+                  onClick={verify}
+                  className="mt-2 text-[11px] font-medium text-blue-600 hover:text-blue-700 px-2 py-1 rounded-xl transition-colors cursor-pointer"
+                  style={{
+                    background: "rgb(239, 246, 255)",
+                    border: "1px solid rgba(191, 219, 254, 0.9)",
+                  }}
+                >
+                  Verify Integrity
+                </button>
+              )}
+            </div>
+          )}
 
           {/* Connection Info */}
           <div className="pt-2 border-t border-gray-200">
-            <p className="text-xs font-medium text-gray-500 mb-3 uppercase tracking-wide">
-              Connection
-            </p>
+            <div className="flex items-center justify-between gap-2 mb-3">
+              <p className="text-xs font-medium text-gray-500 uppercase tracking-wide">
+                Connection
+              </p>
+              {onSendBack && status === "completed" && (
+                <button
+                  type="button"
+                  onClick={() => {
+                    onSendBack();
+                    setIsOpen(false);
+                  }}
+                  className="text-[11px] font-medium text-blue-600 hover:text-blue-700 px-2 py-1 rounded-xl transition-colors cursor-pointer"
+                  style={{
+                    background: "rgb(239, 246, 255)",
+                    border: "1px solid rgba(191, 219, 254, 0.9)",
+                  }}
+                >
+                  Send back
+                </button>
+              )}
+            </div>
             <div className="grid grid-cols-2 gap-3">
               <div>
                 <p className="text-xs text-gray-500 mb-1">IP Address</p>
@@ -137,6 +236,33 @@ const ReceiveFileCard = ({
               </p>
             </div>
           </div>
+
+          {onRemove && (
+            <div className="pt-2 border-t border-gray-200 flex items-center justify-between gap-2">
+              {status === "completed" ? (
+                <label className="flex items-center gap-1.5 text-[11px] text-gray-600 cursor-pointer">
+                  <input
+                    type="checkbox"
+                    checked={deleteFile}
+                    onChange={(e) => setDeleteFile(e.target.checked)}
+                  />
+                  Also move the file to the trash
+                </label>
+              ) : (
+                <span />
+              )}
+              <button
+                type="button"
+                onClick={() => {
+                  onRemove(status === "completed" && deleteFile);
+                  setIsOpen(false);
+                }}
+                className="text-[11px] font-medium text-red-600 hover:text-red-700 px-2 py-1 rounded-xl hover:bg-red-50 transition-colors cursor-pointer"
+              >
+                Remove from History
+              </button>
+            </div>
+          )}
         </div>
       </DetailModal>
     </>
@@ -0,0 +1,52 @@
+import { FileIcon } from "./FileIcon";
+
+type Props = {
+  file_name: string;
+  file_extension: string;
+  file_size: number;
+  reason?: string | null;
+  download_time: string;
+};
+
+function format_file_size(bytes: number): string {
+  if (bytes === 0) return "0 B";
+  const k = 1024;
+  const sizes = ["B", "KB", "MB", "GB", "TB"];
+  const i = Math.floor(Math.log(bytes) / Math.log(k));
+  return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + " " + sizes[i];
+}
+
+// A declined offer in the File History "Rejected" tab.
+const RejectedOfferCard = ({
+  file_name,
+  file_extension,
+  file_size,
+  reason,
+  download_time,
+}: Props) => {
+  const fullName = file_extension ? `${file_name}.${file_extension}` : file_name;
+  const when = new Date(download_time);
+
+  return (
+    <div
+      className="grid grid-cols-[2fr_1fr_1fr] items-center select-none px-2 sm:px-4 py-2 sm:py-3 text-gray-700 border-b border-gray-200 last:border-b-0 m-0 bg-transparent"
+      title={`Declined ${when.toLocaleString()}`}
+    >
+      <div className="flex items-center gap-1.5 sm:gap-2 font-medium truncate text-[10px] sm:text-xs xl:text-sm">
+        <FileIcon fileName={fullName} className="w-4 h-4 flex-shrink-0" />
+        <span className="truncate">{fullName}</span>
+      </div>
+      <div
+        className="text-[9px] sm:text-[10px] xl:text-xs text-gray-500 truncate"
+        title={reason ?? undefined}
+      >
+        {reason ?? "No reason given"}
+      </div>
+      <div className="text-[9px] sm:text-[10px] xl:text-xs font-medium text-gray-600">
+        {format_file_size(file_size)}
+      </div>
+    </div>
+  );
+};
+
+export default RejectedOfferCard;
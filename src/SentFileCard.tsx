@@ -1,6 +1,8 @@
 import { useState } from "react";
 import { FileIcon } from "./FileIcon";
 import { DetailModal } from "./DetailModal";
+import { commands, type SentPathCheck, type SnapshotState, type TransferStatus } from "./bindings";
+import { unwrap } from "./commands";
 
 type Props = {
   file_name: string;
@@ -9,7 +11,23 @@ type Props = {
   file_paths: string[];
   send_time: string;
   connection_code: string;
+  // Empty for sends that never connected and entries from before this was recorded.
+  connection_type?: string;
+  peer_address?: string;
   onResend?: (paths: string[]) => void;
+  // "completed" when missing (entries from before statuses were recorded).
+  status?: TransferStatus;
+  reason?: string | null;
+  // Missing only until the history has been loaded once by this version.
+  id?: string;
+};
+
+const CHECK_TEXT: Record<SnapshotState, string> = {
+  unchanged: "Unchanged since sent",
+  touched: "Saved again, same contents",
+  changed: "Changed since sent",
+  missing: "No longer there",
+  "not-recorded": "Not recorded when sent",
 };
 
 function format_file_size(bytes: number): string {
@@ -27,9 +45,24 @@ const SentFileCard = ({
   file_paths,
   send_time,
   connection_code,
+  connection_type,
+  peer_address,
   onResend,
+  status = "completed",
+  reason,
+  id,
 }: Props) => {
   const [isOpen, setIsOpen] = useState(false);
+  const [checks, setChecks] = useState<SentPathCheck[] | null>(null);
+
+  // Compares the local files with the snapshot taken when they were sent.
+  async function verify() {
+    try {
+      setChecks(await unwrap(commands.verifySentEntry(id)));
+    } catch (err) {
+      console.error("Error checking sent files:", err);
+    }
+  }
 
   const displayName = file_name.endsWith(`.${file_extension}`)
     ? file_name.slice(0, -(file_extension.length + 1))
@@ -48,7 +81,15 @@ const SentFileCard = ({
           />
           <span>{displayName}</span>
         </div>
-        <div className="text-[9px] sm:text-[10px] xl:text-xs text-gray-500">.{file_extension}</div>
+        {status === "completed" ? (
+          <div className="text-[9px] sm:text-[10px] xl:text-xs text-gray-500">
+            .{file_extension}
+          </div>
+        ) : (
+          <div className="text-[9px] sm:text-[10px] xl:text-xs text-red-600 capitalize">
+            {status}
+          </div>
+        )}
         <div className="text-[9px] sm:text-[10px] xl:text-xs font-medium text-gray-600">
           {format_file_size(file_size)}
         </div>
@@ -68,6 +109,12 @@ const SentFileCard = ({
         subtitle="Sent file"
       >
         <div className="px-6 py-5 space-y-4">
+          {status !== "completed" && (
+            <div className="rounded-xl px-4 py-3 bg-red-50 border border-red-100">
+              <p className="text-xs font-semibold text-red-700 capitalize">{status}</p>
+              {reason && <p className="text-xs text-red-600 mt-1 break-words">{reason}</p>}
+            </div>
+          )}
           {/* File Info Grid */}
           <div className="grid grid-cols-2 gap-3">
             <div>
@@ -93,9 +140,11 @@ const SentFileCard = ({
                 {file_paths.map((path, idx) => {
                   // Extract just the filename from the path
                   const fileName = path.split(/[/\\]/).pop() || path;
+                  const check = checks?.[idx];
                   return (
                     <div
                       key={idx}
+                      title={check ? CHECK_TEXT[check.state] : undefined}
                       className="px-3 py-2 text-sm font-mono text-gray-900 rounded-xl"
                       style={{
                         background: "#ffffff",
@@ -105,6 +154,17 @@ const SentFileCard = ({
                       }}
                     >
                       {fileName}
+                      {check && (
+                        <span
+                          className={`block text-[11px] font-sans ${
+                            check.state === "unchanged" || check.state === "touched"
+                              ? "text-green-600"
+                              : "text-red-600"
+                          }`}
+                        >
+                          {CHECK_TEXT[check.state]}
+                        </span>
+                      )}
                     </div>
                   );
                 })}
@@ -112,6 +172,19 @@ const SentFileCard = ({
             ) : (
               <p className="text-sm text-gray-400 italic">No file names available</p>
             )}
+            {id && status === "completed" && file_paths.length > 0 && (
+              <button
+                type="button"
+                onClick={verify}
+                className="mt-2 text-[11px] font-medium text-blue-600 hover:text-blue-700 px-2 py-1 rounded-xl transition-colors cursor-pointer"
+                style={{
+                  background: "rgb(239, 246, 255)",
+                  border: "1px solid rgba(191, 219, 254, 0.9)",
+                }}
+              >
+                Check for Changes
+              </button>
+            )}
           </div>
 
           {/* Connection Info */}
@@ -182,6 +255,18 @@ const SentFileCard = ({
                 title="Click to copy"
               />
             </div>
+            {connection_type && (
+              <div className="grid grid-cols-2 gap-3">
+                <div>
+                  <p className="text-xs text-gray-500 mb-1">IP Address</p>
+                  <p className="text-sm font-semibold text-gray-900 truncate">{peer_address}</p>
+                </div>
+                <div>
+                  <p className="text-xs text-gray-500 mb-1">Type</p>
+                  <p className="text-sm font-semibold text-gray-900">{connection_type}</p>
+                </div>
+              </div>
+            )}
             <div>
               <p className="text-xs text-gray-500 mb-1">Connection Code</p>
               <p className="text-sm font-semibold text-gray-900">
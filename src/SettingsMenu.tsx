@@ -1,82 +1,371 @@
-import { invoke } from "@tauri-apps/api/core";
-import { open, save } from "@tauri-apps/plugin-dialog";
+import { listen } from "@tauri-apps/api/event";
 import { useEffect, useRef, useState } from "react";
 import { toast } from "sonner";
 import { XIcon } from "./Icons";
+import ReceiveRules from "./ReceiveRules";
+import { checkForUpdates } from "./updates";
+import {
+  commands,
+  type CommandError,
+  type ExportRange,
+  type RateWindow,
+  type RelayPreset,
+  type Result,
+} from "./bindings";
+import { unwrap } from "./commands";
 
 // Loads a Tauri-backed value once on mount. Caller drives writes.
-function useTauriValue<T>(getCmd: string, initial: T) {
+function useTauriValue<T>(get: () => Promise<Result<T, string>>, initial: T) {
   const [value, setValue] = useState<T>(initial);
   useEffect(() => {
-    invoke<T>(getCmd)
+    unwrap(get())
       .then(setValue)
-      .catch((e) => console.error(`Error getting ${getCmd}:`, e));
-  }, [getCmd]);
+      .catch((e) => console.error(`Error getting ${get.name}:`, e));
+  }, [get]);
   return [value, setValue] as const;
 }
 
-function saveTauri(setCmd: string, args: Record<string, unknown>) {
-  invoke(setCmd, args).catch((e) => console.error(`Error saving ${setCmd}:`, e));
+function saveTauri<V>(set: (value: V) => Promise<Result<null, string>>, value: V) {
+  unwrap(set(value)).catch((e) => console.error(`Error saving ${set.name}:`, e));
 }
 
-async function exportHistory(cmd: string, defaultPath: string, label: string) {
+// The directory setters refuse folders outside scope.rs's scope with `{ code, path, message }`.
+function directoryError(e: unknown, fallback: string): string {
+  if (e instanceof Error) return e.message;
+  if (e && typeof e === "object" && "message" in e) return (e as { message: string }).message;
+  return String(e ?? fallback);
+}
+
+// One of the history exports; the HTML one ignores everything but `filePath`.
+type Export = (
+  filePath: string,
+  range: ExportRange,
+  passphrase: string | null,
+  id: string,
+) => Promise<Result<null, CommandError>>;
+
+// JSON exports take a date range and report `export-progress` while they write; with a
+// passphrase they're saved as age-encrypted `.age` files.
+async function exportHistory(
+  run: Export,
+  defaultPath: string,
+  label: string,
+  range: ExportRange,
+  passphrase: string,
+) {
+  const json = defaultPath.endsWith(".json");
+  const encrypt = json && passphrase !== "";
+  const extension = encrypt ? "age" : (defaultPath.split(".").pop() ?? "json");
+  const id = crypto.randomUUID();
+  let unlisten: (() => void) | undefined;
   try {
-    const filePath = await save({
-      filters: [{ name: "JSON", extensions: ["json"] }],
-      defaultPath,
-    });
+    // The backend's save dialog approves the destination for the export (see scope.rs).
+    const filePath = await unwrap(
+      commands.pickExportPath(encrypt ? `${defaultPath}.age` : defaultPath, extension),
+    );
     if (!filePath) return;
-    await invoke(cmd, { filePath });
-    toast.success(`${label} history exported`);
+    if (json) {
+      unlisten = await listen<{ id: string; entries: number; bytes: number; elapsed_ms: number }>(
+        "export-progress",
+        ({ payload }) => {
+          if (payload.id !== id) return;
+          toast.loading(
+            `Exporting ${payload.entries} ${label.toLowerCase()} entries: ` +
+              `${(payload.bytes / 1024).toFixed(0)} KB, ${(payload.elapsed_ms / 1000).toFixed(1)}s`,
+            { id },
+          );
+        },
+      );
+    }
+    await unwrap(run(filePath, range, encrypt ? passphrase : null, id));
+    toast.success(`${label} history exported`, { id });
   } catch (e) {
     console.error(`Error exporting ${label}:`, e);
-    toast.error(`Failed to export ${label} history`);
+    toast.error(`Failed to export ${label} history`, { id });
+  } finally {
+    unlisten?.();
+  }
+}
+
+// The backend rejects unknown placeholders, so surface that instead of failing silently.
+function saveNameTemplate(value: string) {
+  unwrap(commands.setReceivedFileNameTemplate(value.trim())).catch((e) =>
+    toast.error(e instanceof Error ? e.message : String(e ?? "Invalid file name template")),
+  );
+}
+
+async function exportMetrics() {
+  try {
+    const filePath = await unwrap(commands.pickExportPath("wyrmhole_usage_metrics.json", "json"));
+    if (!filePath) return;
+    await unwrap(commands.exportUsageMetrics(filePath));
+    toast.success("Usage metrics exported");
+  } catch (e) {
+    console.error("Error exporting usage metrics:", e);
+    toast.error("Failed to export usage metrics");
   }
 }
 
-const EXPORTS = [
-  { label: "Received", cmd: "export_received_files_json", path: "received_files_export.json" },
-  { label: "Sent", cmd: "export_sent_files_json", path: "sent_files_export.json" },
-] as const;
+// One window per line, "09:00-17:00 5120" (KiB/s) or "22:00-06:00 unlimited".
+function scheduleText(windows: RateWindow[]) {
+  return windows
+    .map((w) => `${w.start}-${w.end} ${w.limit_kib ?? "unlimited"}`)
+    .join("\n");
+}
+
+function parseSchedule(text: string): RateWindow[] {
+  return text
+    .split("\n")
+    .map((line) => line.trim())
+    .filter((line) => line.length > 0)
+    .map((line) => {
+      const [range = "", limit = ""] = line.split(/\s+/);
+      const [start = "", end = ""] = range.split("-");
+      const kib = parseInt(limit, 10);
+      return { start, end, limit_kib: kib > 0 ? kib : null };
+    });
+}
+
+// The backend rejects malformed times, so surface that instead of failing silently.
+function saveSchedule(text: string) {
+  unwrap(commands.setRateLimitSchedule(parseSchedule(text))).catch((e) =>
+    toast.error(e instanceof Error ? e.message : String(e ?? "Invalid speed schedule")),
+  );
+}
+
+const EXPORTS: { label: string; run: Export; path: string }[] = [
+  { label: "Received", run: commands.exportReceivedFilesJson, path: "received_files_export.json" },
+  { label: "Sent", run: commands.exportSentFilesJson, path: "sent_files_export.json" },
+  {
+    label: "HTML",
+    run: (filePath) => commands.exportHistoryHtml(filePath),
+    path: "wyrmhole_history.html",
+  },
+];
 
 export default function SettingsMenu() {
   const [isOpen, setIsOpen] = useState(false);
   const wrapperRef = useRef<HTMLDivElement>(null);
 
-  const [downloadDir, setDownloadDir] = useTauriValue<string>("get_download_path", "");
-  const [autoExtract, setAutoExtract] = useTauriValue<boolean>("get_auto_extract_tarballs", false);
+  const [downloadDir, setDownloadDir] = useTauriValue<string>(commands.getDownloadPath, "");
+  const [historyDir, setHistoryDir] = useTauriValue<string>(commands.getHistoryDirectory, "");
+  const [exportRange, setExportRange] = useState<ExportRange>({ from: null, to: null });
+  const [exportPassphrase, setExportPassphrase] = useState("");
+  const [sentPathPrivacy, setSentPathPrivacy] = useTauriValue<string>(
+    commands.getSentPathPrivacy,
+    "full",
+  );
+  const [transitMode, setTransitMode] = useTauriValue<string>(commands.getTransitMode, "any");
+  const [autoExtract, setAutoExtract] = useTauriValue<boolean>(
+    commands.getAutoExtractTarballs,
+    false,
+  );
+  const [mergeFolderUpdates, setMergeFolderUpdates] = useTauriValue<boolean>(
+    commands.getMergeFolderUpdates,
+    false,
+  );
+  const [applyFileDeltas, setApplyFileDeltas] = useTauriValue<boolean>(
+    commands.getApplyFileDeltas,
+    false,
+  );
+  const [dateSubfolders, setDateSubfolders] = useTauriValue<boolean>(
+    commands.getDateSubfolders,
+    false,
+  );
+  const [saveTextOffers, setSaveTextOffers] = useTauriValue<boolean>(
+    commands.getSaveTextOffers,
+    false,
+  );
+  const [hideCodes, setHideCodes] = useTauriValue<boolean>(commands.getHideSendCodes, false);
+  const [deterministicArchives, setDeterministicArchives] = useTauriValue<boolean>(
+    commands.getDeterministicArchives,
+    false,
+  );
+  const [streamArchives, setStreamArchives] = useTauriValue<boolean>(
+    commands.getStreamArchives,
+    false,
+  );
+  const [queueOfflineSends, setQueueOfflineSends] = useTauriValue<boolean>(
+    commands.getQueueOfflineSends,
+    false,
+  );
+  const [localApi, setLocalApi] = useTauriValue<boolean>(commands.getLocalApiEnabled, false);
+  const [localApiPort, setLocalApiPort] = useTauriValue<number>(commands.getLocalApiPort, 47613);
+  const [localApiToken, setLocalApiToken] = useTauriValue<string>(commands.getLocalApiToken, "");
+  const [skipDuplicates, setSkipDuplicates] = useTauriValue<boolean>(
+    commands.getSkipDuplicateReceives,
+    false,
+  );
+  const [verifyDiskWrites, setVerifyDiskWrites] = useTauriValue<boolean>(
+    commands.getVerifyDiskWrites,
+    false,
+  );
   const [minimizeOnStart, setMinimizeOnStart] = useTauriValue<boolean>(
-    "get_minimize_on_start",
+    commands.getMinimizeOnStart,
     false,
   );
   const [minimizeOnClose, setMinimizeOnClose] = useTauriValue<boolean>(
-    "get_minimize_on_close",
+    commands.getMinimizeOnClose,
     true,
   );
-  const [autostart, setAutostart] = useTauriValue<boolean>("get_autostart", false);
-  const [contextMenu, setContextMenu] = useTauriValue<boolean>("get_context_menu_enabled", false);
+  const [autostart, setAutostart] = useTauriValue<boolean>(commands.getAutostart, false);
+  const [contextMenu, setContextMenu] = useTauriValue<boolean>(
+    commands.getContextMenuEnabled,
+    false,
+  );
   const [folderFormat, setFolderFormat] = useTauriValue<string>(
-    "get_default_folder_name_format",
+    commands.getDefaultFolderNameFormat,
     "#-files-via-wyrmhole",
   );
+  const [usageMetrics, setUsageMetrics] = useTauriValue<boolean>(
+    commands.getUsageMetricsEnabled,
+    false,
+  );
+  const [autoUpdateCheck, setAutoUpdateCheck] = useTauriValue<boolean>(
+    commands.getCheckUpdatesAutomatically,
+    false,
+  );
+  const [autoDownloadUpdates, setAutoDownloadUpdates] = useTauriValue<boolean>(
+    commands.getAutoDownloadUpdates,
+    false,
+  );
+  const [releaseChannel, setReleaseChannel] = useTauriValue<string>(
+    commands.getReleaseChannel,
+    "stable",
+  );
+  const [crashReports, setCrashReports] = useTauriValue<boolean>(
+    commands.getCrashReportsEnabled,
+    false,
+  );
+  const [soundsEnabled, setSoundsEnabled] = useTauriValue<boolean>(
+    commands.getSoundsEnabled,
+    false,
+  );
+  const [soundVolume, setSoundVolume] = useTauriValue<number>(commands.getSoundVolume, 70);
+  const [profiles] = useTauriValue<string[]>(commands.listProfiles, []);
+  const [activeProfile] = useTauriValue<string>(commands.getActiveProfile, "default");
+  const [newProfile, setNewProfile] = useState("");
+  const [nameTemplate, setNameTemplate] = useTauriValue<string>(
+    commands.getReceivedFileNameTemplate,
+    "",
+  );
   const [relayUrl, setRelayUrl] = useState("");
+  const [extraRelays, setExtraRelays] = useState("");
+  const [webhookUrl, setWebhookUrl] = useTauriValue<string | null>(commands.getWebhookUrl, null);
+  const [benchmarking, setBenchmarking] = useState(false);
+  const [relayPresets, setRelayPresets] = useState<RelayPreset[]>([]);
+  const [downloadLimit, setDownloadLimit] = useState("");
+  const [uploadLimit, setUploadLimit] = useState("");
+  const [schedule, setSchedule] = useState("");
+  const [maxSends, setMaxSends] = useTauriValue<number>(commands.getMaxConcurrentSends, 0);
+  const [idleTimeout, setIdleTimeout] = useTauriValue<number>(commands.getIdleTimeoutSecs, 0);
+  const [codeExpiry, setCodeExpiry] = useTauriValue<number>(commands.getCodeExpiryMins, 0);
+  const [offerExpiry, setOfferExpiry] = useTauriValue<number>(commands.getOfferExpiryMins, 0);
+  const [maxIncomingSize, setMaxIncomingSize] = useTauriValue<number>(
+    commands.getMaxIncomingSizeMib,
+    0,
+  );
+  const [contentAllowlist, setContentAllowlist] = useState("");
+  const [partSize, setPartSize] = useTauriValue<number>(commands.getArchivePartSizeMib, 0);
+  const [compressionLevel, setCompressionLevel] = useTauriValue<number>(
+    commands.getCompressionLevel,
+    1,
+  );
+  const [packagingRetry, setPackagingRetry] = useTauriValue<number>(
+    commands.getPackagingRetrySecs,
+    0,
+  );
+  const [packageCacheMib, setPackageCacheMib] = useTauriValue<number>(
+    commands.getPackageCacheMib,
+    0,
+  );
+  const [packageCache, setPackageCache] = useState<{ entries: number; bytes: number } | null>(
+    null,
+  );
+
+  function refreshPackageCache() {
+    unwrap(commands.getPackageCacheInfo())
+      .then(setPackageCache)
+      .catch((e) => console.error("Error reading package cache:", e));
+  }
 
   useEffect(() => {
-    invoke<string | null>("get_relay_server_url")
+    unwrap(commands.getRelayServerUrl())
       .then((v) => setRelayUrl(v ?? ""))
       .catch((e) => console.error("Error getting relay URL:", e));
+    unwrap(commands.getExtraRelayUrls())
+      .then((v) => setExtraRelays(v.join("\n")))
+      .catch((e) => console.error("Error getting extra relays:", e));
+    unwrap(commands.getContentAllowlist())
+      .then((v) => setContentAllowlist(v.join(", ")))
+      .catch((e) => console.error("Error getting trusted file types:", e));
+    commands.listRelayPresets()
+      .then(setRelayPresets)
+      .catch((e) => console.error("Error getting relay presets:", e));
+    unwrap(commands.getDownloadRateLimitKib())
+      .then((v) => setDownloadLimit(v ? String(v) : ""))
+      .catch((e) => console.error("Error getting download limit:", e));
+    unwrap(commands.getUploadRateLimitKib())
+      .then((v) => setUploadLimit(v ? String(v) : ""))
+      .catch((e) => console.error("Error getting upload limit:", e));
+    unwrap(commands.getRateLimitSchedule())
+      .then((v) => setSchedule(scheduleText(v)))
+      .catch((e) => console.error("Error getting speed schedule:", e));
   }, []);
 
+  async function applyRelayPreset(id: string) {
+    try {
+      const url = await unwrap(commands.applyRelayPreset(id));
+      setRelayUrl(url ?? "");
+    } catch (e) {
+      toast.error(e instanceof Error ? e.message : String(e ?? "Failed to set relay"));
+    }
+  }
+
+  async function resetRelay() {
+    try {
+      await unwrap(commands.resetRelayServer());
+      setRelayUrl("");
+    } catch (e) {
+      toast.error(e instanceof Error ? e.message : String(e ?? "Failed to reset relay"));
+    }
+  }
+
+  function saveDownloadLimit(value: string) {
+    const kib = parseInt(value, 10);
+    saveTauri(commands.setDownloadRateLimitKib, kib > 0 ? kib : null);
+  }
+
+  function saveUploadLimit(value: string) {
+    const kib = parseInt(value, 10);
+    saveTauri(commands.setUploadRateLimitKib, kib > 0 ? kib : null);
+  }
+
   // Refs so close handler reads latest edit without re-binding listeners per keystroke.
   const folderFormatRef = useRef(folderFormat);
   folderFormatRef.current = folderFormat;
+  const nameTemplateRef = useRef(nameTemplate);
+  nameTemplateRef.current = nameTemplate;
   const relayUrlRef = useRef(relayUrl);
   relayUrlRef.current = relayUrl;
+  const extraRelaysRef = useRef(extraRelays);
+  extraRelaysRef.current = extraRelays;
+  const downloadLimitRef = useRef(downloadLimit);
+  downloadLimitRef.current = downloadLimit;
+  const uploadLimitRef = useRef(uploadLimit);
+  uploadLimitRef.current = uploadLimit;
+  const scheduleRef = useRef(schedule);
+  scheduleRef.current = schedule;
 
   const handleClose = () => {
-    saveTauri("set_default_folder_name_format", { value: folderFormatRef.current });
+    saveTauri(commands.setDefaultFolderNameFormat, folderFormatRef.current);
+    saveNameTemplate(nameTemplateRef.current);
     const trimmed = relayUrlRef.current.trim();
-    saveTauri("set_relay_server_url", { value: trimmed.length > 0 ? trimmed : null });
+    saveTauri(commands.setRelayServerUrl, trimmed.length > 0 ? trimmed : null);
+    saveExtraRelays(extraRelaysRef.current);
+    saveDownloadLimit(downloadLimitRef.current);
+    saveUploadLimit(uploadLimitRef.current);
+    saveSchedule(scheduleRef.current);
     setIsOpen(false);
   };
 
@@ -96,36 +385,207 @@ export default function SettingsMenu() {
     };
   }, [isOpen]);
 
+  // Picked through `pick_directory`, which approves the folder for the setter.
   async function chooseDownloadDir() {
-    const selected = await open({ directory: true, multiple: false });
-    if (typeof selected !== "string") return;
-    setDownloadDir(selected);
-    saveTauri("set_download_directory", { newPath: selected });
+    try {
+      const selected = await unwrap(commands.pickDirectory());
+      if (!selected) return;
+      await unwrap(commands.setDownloadDirectory(selected));
+      setDownloadDir(selected);
+    } catch (e) {
+      toast.error(directoryError(e, "Failed to change the download folder"));
+    }
+  }
+
+  // Moving history can fail (e.g. the target already has history files), so report it.
+  async function moveHistory(newPath: string | null) {
+    try {
+      await unwrap(commands.setHistoryDirectory(newPath));
+      setHistoryDir(await unwrap(commands.getHistoryDirectory()));
+      toast.success("History moved");
+    } catch (e) {
+      toast.error(directoryError(e, "Failed to move history"));
+    }
+  }
+
+  async function chooseHistoryDir() {
+    const selected = await unwrap(commands.pickDirectory()).catch((e) => {
+      toast.error(directoryError(e, "Failed to open folder dialog"));
+      return null;
+    });
+    if (!selected) return;
+    moveHistory(selected);
   }
 
   function toggleAutoExtract() {
     const next = !autoExtract;
     setAutoExtract(next);
-    saveTauri("set_auto_extract_tarballs", { value: next });
+    saveTauri(commands.setAutoExtractTarballs, next);
+  }
+
+  function toggleMergeFolderUpdates() {
+    const next = !mergeFolderUpdates;
+    setMergeFolderUpdates(next);
+    saveTauri(commands.setMergeFolderUpdates, next);
+  }
+
+  function toggleApplyFileDeltas() {
+    const next = !applyFileDeltas;
+    setApplyFileDeltas(next);
+    saveTauri(commands.setApplyFileDeltas, next);
+  }
+
+  function toggleDateSubfolders() {
+    const next = !dateSubfolders;
+    setDateSubfolders(next);
+    saveTauri(commands.setDateSubfolders, next);
+  }
+
+  function toggleSaveTextOffers() {
+    const next = !saveTextOffers;
+    setSaveTextOffers(next);
+    saveTauri(commands.setSaveTextOffers, next);
+  }
+
+  function toggleHideCodes() {
+    const next = !hideCodes;
+    setHideCodes(next);
+    saveTauri(commands.setHideSendCodes, next);
+  }
+
+  function toggleDeterministicArchives() {
+    const next = !deterministicArchives;
+    setDeterministicArchives(next);
+    saveTauri(commands.setDeterministicArchives, next);
+  }
+
+  function toggleStreamArchives() {
+    const next = !streamArchives;
+    setStreamArchives(next);
+    saveTauri(commands.setStreamArchives, next);
+  }
+
+  function toggleQueueOfflineSends() {
+    const next = !queueOfflineSends;
+    setQueueOfflineSends(next);
+    saveTauri(commands.setQueueOfflineSends, next);
+  }
+
+  // Enabling starts the server straight away (and makes a token the first time), so a busy
+  // port is reported here.
+  async function toggleLocalApi() {
+    const next = !localApi;
+    setLocalApi(next);
+    try {
+      await unwrap(commands.setLocalApiEnabled(next));
+      setLocalApiToken(await unwrap(commands.getLocalApiToken()));
+    } catch (e) {
+      toast.error(e instanceof Error ? e.message : String(e ?? "Failed to start the local API"));
+    }
+  }
+
+  async function saveLocalApiPort(port: number) {
+    try {
+      await unwrap(commands.setLocalApiPort(port));
+    } catch (e) {
+      toast.error(e instanceof Error ? e.message : String(e ?? "Failed to change the API port"));
+    }
+  }
+
+  async function regenerateLocalApiToken() {
+    try {
+      setLocalApiToken(await unwrap(commands.regenerateLocalApiToken()));
+      toast.success("New API token created; apps using the old one are cut off");
+    } catch (e) {
+      toast.error(e instanceof Error ? e.message : String(e ?? "Failed to create a new token"));
+    }
+  }
+
+  function toggleSkipDuplicates() {
+    const next = !skipDuplicates;
+    setSkipDuplicates(next);
+    saveTauri(commands.setSkipDuplicateReceives, next);
+  }
+
+  function toggleVerifyDiskWrites() {
+    const next = !verifyDiskWrites;
+    setVerifyDiskWrites(next);
+    saveTauri(commands.setVerifyDiskWrites, next);
   }
 
   function toggleMinimizeOnStart() {
     const next = !minimizeOnStart;
     setMinimizeOnStart(next);
-    saveTauri("set_minimize_on_start", { value: next });
+    saveTauri(commands.setMinimizeOnStart, next);
   }
 
   function toggleMinimizeOnClose() {
     const next = !minimizeOnClose;
     setMinimizeOnClose(next);
-    saveTauri("set_minimize_on_close", { value: next });
+    saveTauri(commands.setMinimizeOnClose, next);
+  }
+
+  function toggleSounds() {
+    const next = !soundsEnabled;
+    setSoundsEnabled(next);
+    saveTauri(commands.setSoundsEnabled, next);
+  }
+
+  // Save on release and play a sample so the new level can be heard.
+  async function commitSoundVolume() {
+    try {
+      await unwrap(commands.setSoundVolume(soundVolume));
+      await commands.playSoundPreview();
+    } catch (e) {
+      console.error("Error saving sound volume:", e);
+    }
+  }
+
+  function toggleUsageMetrics() {
+    const next = !usageMetrics;
+    setUsageMetrics(next);
+    saveTauri(commands.setUsageMetricsEnabled, next);
+  }
+
+  function toggleAutoUpdateCheck() {
+    const next = !autoUpdateCheck;
+    setAutoUpdateCheck(next);
+    saveTauri(commands.setCheckUpdatesAutomatically, next);
+  }
+
+  function toggleAutoDownloadUpdates() {
+    const next = !autoDownloadUpdates;
+    setAutoDownloadUpdates(next);
+    saveTauri(commands.setAutoDownloadUpdates, next);
+  }
+
+  function toggleBetaChannel() {
+    const next = releaseChannel === "beta" ? "stable" : "beta";
+    setReleaseChannel(next);
+    saveTauri(commands.setReleaseChannel, next);
+  }
+
+  function toggleCrashReports() {
+    const next = !crashReports;
+    setCrashReports(next);
+    saveTauri(commands.setCrashReportsEnabled, next);
+  }
+
+  async function resetMetrics() {
+    try {
+      await unwrap(commands.resetUsageMetrics());
+      toast.success("Usage metrics reset");
+    } catch (e) {
+      console.error("Error resetting usage metrics:", e);
+      toast.error("Failed to reset usage metrics");
+    }
   }
 
   async function toggleAutostart() {
     const next = !autostart;
     setAutostart(next);
     try {
-      await invoke("set_autostart", { value: next });
+      await unwrap(commands.setAutostart(next));
     } catch (e) {
       setAutostart(!next); // revert on failure
       console.error("Error setting autostart:", e);
@@ -137,7 +597,7 @@ export default function SettingsMenu() {
     const next = !contextMenu;
     setContextMenu(next);
     try {
-      await invoke("set_context_menu_enabled", { value: next });
+      await unwrap(commands.setContextMenuEnabled(next));
       toast.success(
         next ? "Added to your file manager's right-click menu" : "Removed from right-click menu",
       );
@@ -150,9 +610,71 @@ export default function SettingsMenu() {
     }
   }
 
+  // The backend restarts the app into the chosen profile, so only failures come back.
+  async function switchProfile(name: string) {
+    try {
+      await unwrap(commands.switchProfile(name));
+    } catch (e) {
+      toast.error(e instanceof Error ? e.message : String(e ?? "Failed to switch profile"));
+    }
+  }
+
+  function relayList(value: string) {
+    return value
+      .split("\n")
+      .map((u) => u.trim())
+      .filter((u) => u.length > 0);
+  }
+
+  function saveExtraRelays(value: string) {
+    saveTauri(commands.setExtraRelayUrls, relayList(value));
+  }
+
+  function saveContentAllowlist(value: string) {
+    const extensions = value
+      .split(/[\s,]+/)
+      .map((ext) => ext.replace(/^\.+/, "").toLowerCase())
+      .filter((ext) => ext.length > 0);
+    saveTauri(commands.setContentAllowlist, extensions);
+  }
+
+  // Times every relay; the backend saves them fastest first, so reload both fields.
+  async function benchmarkRelays() {
+    setBenchmarking(true);
+    try {
+      await unwrap(commands.setExtraRelayUrls(relayList(extraRelays)));
+      const results = await unwrap(commands.benchmarkRelays());
+      const [relay, extras] = await Promise.all([
+        unwrap(commands.getRelayServerUrl()),
+        unwrap(commands.getExtraRelayUrls()),
+      ]);
+      setRelayUrl(relay ?? "");
+      setExtraRelays(extras.join("\n"));
+      const best = results[0];
+      if (best && best.latency_ms !== null) {
+        toast.success(`Fastest relay: ${best.url} (${best.latency_ms} ms)`);
+      } else {
+        toast.error("No relay answered");
+      }
+    } catch (e) {
+      toast.error(e instanceof Error ? e.message : String(e ?? "Failed to benchmark relays"));
+    } finally {
+      setBenchmarking(false);
+    }
+  }
+
+  async function saveWebhookUrl(value: string) {
+    const trimmed = value.trim();
+    try {
+      await unwrap(commands.setWebhookUrl(trimmed.length > 0 ? trimmed : null));
+    } catch (e) {
+      toast.error(e instanceof Error ? e.message : String(e ?? "Failed to save webhook"));
+    }
+  }
+
   async function testRelay() {
     try {
-      const msg = await invoke<string>("test_relay_server");
+      const msg = await unwrap(commands.testRelayServer());
       toast.success(msg);
     } catch (e) {
       toast.error(e instanceof Error ? e.message : String(e ?? "Failed to test relay"));
@@ -194,6 +716,44 @@ export default function SettingsMenu() {
             </button>
           </div>
 
+          <div className="space-y-1.5">
+            <label htmlFor="new-profile" className="text-xs font-medium text-gray-700 block">
+              Profile
+            </label>
+            <div className="flex flex-wrap gap-1.5">
+              {profiles.map((name) => (
+                <button
+                  key={name}
+                  type="button"
+                  onClick={() => name !== activeProfile && switchProfile(name)}
+                  className={`px-2.5 py-1 text-xs font-medium rounded-lg transition-colors ${name === activeProfile ? "bg-blue-500 text-white" : "bg-gray-100 text-gray-700 hover:bg-gray-200 cursor-pointer"}`}
+                >
+                  {name}
+                </button>
+              ))}
+            </div>
+            <div className="flex gap-2">
+              <input
+                id="new-profile"
+                type="text"
+                value={newProfile}
+                onChange={(e) => setNewProfile(e.target.value)}
+                placeholder="New profile, e.g. work"
+                className="flex-1 min-w-0 px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
+              />
+              <button
+                type="button"
+                onClick={() => newProfile.trim() && switchProfile(newProfile.trim())}
+                className="px-3 py-2 text-sm font-medium text-blue-600 hover:text-blue-700 hover:bg-blue-50 rounded-lg transition-colors cursor-pointer flex-shrink-0"
+              >
+                Create
+              </button>
+            </div>
+            <p className="text-[11px] text-gray-500">
+              Each profile keeps its own settings and history; switching restarts wyrmhole
+            </p>
+          </div>
+
           <div className="space-y-1.5">
             <label className="text-xs font-medium text-gray-700">Download Location</label>
             <button
@@ -212,7 +772,7 @@ export default function SettingsMenu() {
               <label htmlFor="auto-extract" className="text-xs font-medium text-gray-700 block">
                 Auto-Extract Archives
               </label>
-              <p className="text-[11px] text-gray-500 mt-0.5">Extract multi-file transfers</p>
+              <p className="text-[11px] text-gray-500 mt-0.5">Extract received tar and zip archives</p>
             </div>
             <button
               id="auto-extract"
@@ -227,59 +787,66 @@ export default function SettingsMenu() {
 
           <div className="flex items-center justify-between gap-3">
             <div className="flex-1 min-w-0">
-              <label htmlFor="autostart" className="text-xs font-medium text-gray-700 block">
-                Launch on Startup
+              <label
+                htmlFor="merge-folder-updates"
+                className="text-xs font-medium text-gray-700 block"
+              >
+                Merge Folder Updates
               </label>
-              <p className="text-[11px] text-gray-500 mt-0.5">Open wyrmhole when you log in</p>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Sync sends overwrite files in the earlier copy
+              </p>
             </div>
             <button
-              id="autostart"
-              onClick={toggleAutostart}
-              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${autostart ? "bg-blue-500" : "bg-gray-300"}`}
+              id="merge-folder-updates"
+              onClick={toggleMergeFolderUpdates}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${mergeFolderUpdates ? "bg-blue-500" : "bg-gray-300"}`}
             >
               <span
-                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${autostart ? "translate-x-4" : "translate-x-0.5"}`}
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${mergeFolderUpdates ? "translate-x-4" : "translate-x-0.5"}`}
               />
             </button>
           </div>
 
           <div className="flex items-center justify-between gap-3">
             <div className="flex-1 min-w-0">
-              <label htmlFor="context-menu" className="text-xs font-medium text-gray-700 block">
-                Right-Click "Send via wyrmhole"
+              <label
+                htmlFor="apply-file-deltas"
+                className="text-xs font-medium text-gray-700 block"
+              >
+                Apply File Updates
               </label>
               <p className="text-[11px] text-gray-500 mt-0.5">
-                Add a context-menu entry to send files/folders
+                Patch the earlier copy with changed blocks
               </p>
             </div>
             <button
-              id="context-menu"
-              onClick={toggleContextMenu}
-              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${contextMenu ? "bg-blue-500" : "bg-gray-300"}`}
+              id="apply-file-deltas"
+              onClick={toggleApplyFileDeltas}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${applyFileDeltas ? "bg-blue-500" : "bg-gray-300"}`}
             >
               <span
-                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${contextMenu ? "translate-x-4" : "translate-x-0.5"}`}
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${applyFileDeltas ? "translate-x-4" : "translate-x-0.5"}`}
               />
             </button>
           </div>
 
           <div className="flex items-center justify-between gap-3">
             <div className="flex-1 min-w-0">
-              <label
-                htmlFor="minimize-on-start"
-                className="text-xs font-medium text-gray-700 block"
-              >
-                Minimize on Start
+              <label htmlFor="skip-duplicates" className="text-xs font-medium text-gray-700 block">
+                Skip Duplicate Files
               </label>
-              <p className="text-[11px] text-gray-500 mt-0.5">Launch hidden in the system tray</p>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Remove a received file that matches one you already have and keep the earlier copy
+              </p>
             </div>
             <button
-              id="minimize-on-start"
-              onClick={toggleMinimizeOnStart}
-              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${minimizeOnStart ? "bg-blue-500" : "bg-gray-300"}`}
+              id="skip-duplicates"
+              onClick={toggleSkipDuplicates}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${skipDuplicates ? "bg-blue-500" : "bg-gray-300"}`}
             >
               <span
-                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${minimizeOnStart ? "translate-x-4" : "translate-x-0.5"}`}
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${skipDuplicates ? "translate-x-4" : "translate-x-0.5"}`}
               />
             </button>
           </div>
@@ -287,57 +854,607 @@ export default function SettingsMenu() {
           <div className="flex items-center justify-between gap-3">
             <div className="flex-1 min-w-0">
               <label
-                htmlFor="minimize-on-close"
+                htmlFor="verify-disk-writes"
                 className="text-xs font-medium text-gray-700 block"
               >
-                Minimize on Close
+                Verify Disk Writes
               </label>
               <p className="text-[11px] text-gray-500 mt-0.5">
-                Closing hides to the tray instead of quitting
+                Read each received file back and check its checksum before finishing
               </p>
             </div>
             <button
-              id="minimize-on-close"
-              onClick={toggleMinimizeOnClose}
-              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${minimizeOnClose ? "bg-blue-500" : "bg-gray-300"}`}
+              id="verify-disk-writes"
+              onClick={toggleVerifyDiskWrites}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${verifyDiskWrites ? "bg-blue-500" : "bg-gray-300"}`}
             >
               <span
-                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${minimizeOnClose ? "translate-x-4" : "translate-x-0.5"}`}
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${verifyDiskWrites ? "translate-x-4" : "translate-x-0.5"}`}
               />
             </button>
           </div>
 
-          <div className="space-y-1.5">
-            <label htmlFor="folder-format" className="text-xs font-medium text-gray-700 block">
-              Folder Name Pattern
-            </label>
-            <input
-              id="folder-format"
-              type="text"
-              value={folderFormat}
-              onChange={(e) => setFolderFormat(e.target.value)}
-              onBlur={() => saveTauri("set_default_folder_name_format", { value: folderFormat })}
-              placeholder="#-files-via-wyrmhole"
-              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
-            />
-            <p className="text-[11px] text-gray-500">
-              <code className="font-mono bg-gray-100 px-1 rounded">#</code> = file count
-            </p>
-          </div>
-
-          <div className="space-y-1.5">
-            <label htmlFor="relay-url" className="text-xs font-medium text-gray-700 block">
-              Custom Relay Server
-            </label>
-            <div className="flex gap-2">
-              <input
-                id="relay-url"
-                type="text"
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="date-subfolders" className="text-xs font-medium text-gray-700 block">
+                Date Subfolders
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Save into wyrmhole/YYYY-MM-DD inside the download location
+              </p>
+            </div>
+            <button
+              id="date-subfolders"
+              onClick={toggleDateSubfolders}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${dateSubfolders ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${dateSubfolders ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="save-text-offers" className="text-xs font-medium text-gray-700 block">
+                Save Text Messages
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Keep received messages as .txt files without asking
+              </p>
+            </div>
+            <button
+              id="save-text-offers"
+              onClick={toggleSaveTextOffers}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${saveTextOffers ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${saveTextOffers ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="autostart" className="text-xs font-medium text-gray-700 block">
+                Launch on Startup
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">Open wyrmhole when you log in</p>
+            </div>
+            <button
+              id="autostart"
+              onClick={toggleAutostart}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${autostart ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${autostart ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="context-menu" className="text-xs font-medium text-gray-700 block">
+                Right-Click "Send via wyrmhole"
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Add a context-menu entry to send files/folders
+              </p>
+            </div>
+            <button
+              id="context-menu"
+              onClick={toggleContextMenu}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${contextMenu ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${contextMenu ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="hide-codes" className="text-xs font-medium text-gray-700 block">
+                Hide Codes
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Keep connection codes off screen until you reveal them, e.g. when screen sharing
+              </p>
+            </div>
+            <button
+              id="hide-codes"
+              onClick={toggleHideCodes}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${hideCodes ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${hideCodes ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label
+                htmlFor="deterministic-archives"
+                className="text-xs font-medium text-gray-700 block"
+              >
+                Reproducible Archives
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Unchanged folders always pack into identical archives
+              </p>
+            </div>
+            <button
+              id="deterministic-archives"
+              onClick={toggleDeterministicArchives}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${deterministicArchives ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${deterministicArchives ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="stream-archives" className="text-xs font-medium text-gray-700 block">
+                Stream Folders
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Send folders as an uncompressed .tar while packing, with no temp file
+              </p>
+            </div>
+            <button
+              id="stream-archives"
+              onClick={toggleStreamArchives}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${streamArchives ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${streamArchives ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="space-y-1.5">
+            <label htmlFor="compression-level" className="text-xs font-medium text-gray-700">
+              Archive Compression
+            </label>
+            <select
+              id="compression-level"
+              value={compressionLevel}
+              onChange={(e) => {
+                const value = Number(e.target.value);
+                setCompressionLevel(value);
+                saveTauri(commands.setCompressionLevel, value);
+              }}
+              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all cursor-pointer"
+            >
+              <option value={0}>Store only</option>
+              <option value={1}>Fast</option>
+              <option value={6}>Balanced</option>
+              <option value={9}>Smallest</option>
+            </select>
+            <p className="text-[11px] text-gray-500">
+              For folders and multi-file sends; smaller archives take longer to pack
+            </p>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label
+                htmlFor="queue-offline-sends"
+                className="text-xs font-medium text-gray-700 block"
+              >
+                Queue Sends While Offline
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Hold sends until the rendezvous server is reachable again instead of failing them
+              </p>
+            </div>
+            <button
+              id="queue-offline-sends"
+              onClick={toggleQueueOfflineSends}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${queueOfflineSends ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${queueOfflineSends ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label
+                htmlFor="minimize-on-start"
+                className="text-xs font-medium text-gray-700 block"
+              >
+                Minimize on Start
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">Launch hidden in the system tray</p>
+            </div>
+            <button
+              id="minimize-on-start"
+              onClick={toggleMinimizeOnStart}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${minimizeOnStart ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${minimizeOnStart ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label
+                htmlFor="minimize-on-close"
+                className="text-xs font-medium text-gray-700 block"
+              >
+                Minimize on Close
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Closing hides to the tray instead of quitting
+              </p>
+            </div>
+            <button
+              id="minimize-on-close"
+              onClick={toggleMinimizeOnClose}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${minimizeOnClose ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${minimizeOnClose ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="sounds" className="text-xs font-medium text-gray-700 block">
+                Sounds
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Chime for incoming offers and finished transfers, even from the tray
+              </p>
+            </div>
+            <button
+              id="sounds"
+              onClick={toggleSounds}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${soundsEnabled ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${soundsEnabled ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          {soundsEnabled && (
+            <div className="flex items-center justify-between gap-3">
+              <label htmlFor="sound-volume" className="text-xs font-medium text-gray-700">
+                Volume
+              </label>
+              <input
+                id="sound-volume"
+                type="range"
+                min={0}
+                max={100}
+                value={soundVolume}
+                onChange={(e) => setSoundVolume(parseInt(e.target.value, 10))}
+                onPointerUp={commitSoundVolume}
+                onKeyUp={commitSoundVolume}
+                className="flex-1 max-w-40 accent-blue-500 cursor-pointer"
+              />
+            </div>
+          )}
+
+          <div className="space-y-1.5">
+            <label htmlFor="folder-format" className="text-xs font-medium text-gray-700 block">
+              Folder Name Pattern
+            </label>
+            <input
+              id="folder-format"
+              type="text"
+              value={folderFormat}
+              onChange={(e) => setFolderFormat(e.target.value)}
+              onBlur={() => saveTauri(commands.setDefaultFolderNameFormat, folderFormat)}
+              placeholder="#-files-via-wyrmhole"
+              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
+            />
+            <p className="text-[11px] text-gray-500">
+              <code className="font-mono bg-gray-100 px-1 rounded">#</code> = file count
+            </p>
+          </div>
+
+          <div className="space-y-1.5">
+            <label htmlFor="name-template" className="text-xs font-medium text-gray-700 block">
+              Received File Names
+            </label>
+            <input
+              id="name-template"
+              type="text"
+              value={nameTemplate}
+              onChange={(e) => setNameTemplate(e.target.value)}
+              onBlur={() => saveNameTemplate(nameTemplate)}
+              placeholder="{date}_{name}"
+              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
+            />
+            <p className="text-[11px] text-gray-500">
+              <code className="font-mono bg-gray-100 px-1 rounded">{"{name}"}</code>,{" "}
+              <code className="font-mono bg-gray-100 px-1 rounded">{"{date}"}</code>,{" "}
+              <code className="font-mono bg-gray-100 px-1 rounded">{"{time}"}</code>; the
+              extension is kept. Leave empty to keep the sender&apos;s name
+            </p>
+          </div>
+
+          <ReceiveRules />
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="max-sends" className="text-xs font-medium text-gray-700 block">
+                Simultaneous Sends
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Extra sends wait in a queue; 0 = no limit
+              </p>
+            </div>
+            <input
+              id="max-sends"
+              type="number"
+              min={0}
+              value={maxSends}
+              onChange={(e) => setMaxSends(Math.max(0, parseInt(e.target.value, 10) || 0))}
+              onBlur={() => saveTauri(commands.setMaxConcurrentSends, maxSends)}
+              className="w-16 px-2 py-1 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all flex-shrink-0"
+            />
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="idle-timeout" className="text-xs font-medium text-gray-700 block">
+                Stalled Transfer Timeout
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Seconds without progress before giving up; 0 = keep waiting
+              </p>
+            </div>
+            <input
+              id="idle-timeout"
+              type="number"
+              min={0}
+              value={idleTimeout}
+              onChange={(e) => setIdleTimeout(Math.max(0, parseInt(e.target.value, 10) || 0))}
+              onBlur={() => saveTauri(commands.setIdleTimeoutSecs, idleTimeout)}
+              className="w-16 px-2 py-1 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all flex-shrink-0"
+            />
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="code-expiry" className="text-xs font-medium text-gray-700 block">
+                Unused Code Expiry
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Minutes before an unclaimed code is closed; 0 = never
+              </p>
+            </div>
+            <input
+              id="code-expiry"
+              type="number"
+              min={0}
+              value={codeExpiry}
+              onChange={(e) => setCodeExpiry(Math.max(0, parseInt(e.target.value, 10) || 0))}
+              onBlur={() => saveTauri(commands.setCodeExpiryMins, codeExpiry)}
+              className="w-16 px-2 py-1 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all flex-shrink-0"
+            />
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="offer-expiry" className="text-xs font-medium text-gray-700 block">
+                Unanswered Offer Expiry
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Minutes before an incoming offer is declined; 0 = never
+              </p>
+            </div>
+            <input
+              id="offer-expiry"
+              type="number"
+              min={0}
+              value={offerExpiry}
+              onChange={(e) => setOfferExpiry(Math.max(0, parseInt(e.target.value, 10) || 0))}
+              onBlur={() => saveTauri(commands.setOfferExpiryMins, offerExpiry)}
+              className="w-16 px-2 py-1 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all flex-shrink-0"
+            />
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label
+                htmlFor="max-incoming-size"
+                className="text-xs font-medium text-gray-700 block"
+              >
+                Incoming Size Limit
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                MiB; bigger offers are flagged and need confirming; 0 = no limit
+              </p>
+            </div>
+            <input
+              id="max-incoming-size"
+              type="number"
+              min={0}
+              value={maxIncomingSize}
+              onChange={(e) => setMaxIncomingSize(Math.max(0, parseInt(e.target.value, 10) || 0))}
+              onBlur={() => saveTauri(commands.setMaxIncomingSizeMib, maxIncomingSize)}
+              className="w-16 px-2 py-1 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all flex-shrink-0"
+            />
+          </div>
+
+          <div className="space-y-1">
+            <label htmlFor="content-allowlist" className="text-xs font-medium text-gray-700 block">
+              Trusted File Types
+            </label>
+            <p className="text-[11px] text-gray-500">
+              Programs and scripts need confirming unless their extension is listed here; * trusts
+              everything
+            </p>
+            <input
+              id="content-allowlist"
+              type="text"
+              value={contentAllowlist}
+              onChange={(e) => setContentAllowlist(e.target.value)}
+              onBlur={() => saveContentAllowlist(contentAllowlist)}
+              placeholder="e.g. exe, sh"
+              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
+            />
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="part-size" className="text-xs font-medium text-gray-700 block">
+                Split Large Folders
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Part size in MiB, each retried on its own; 0 = send in one piece
+              </p>
+            </div>
+            <input
+              id="part-size"
+              type="number"
+              min={0}
+              value={partSize}
+              onChange={(e) => setPartSize(Math.max(0, parseInt(e.target.value, 10) || 0))}
+              onBlur={() => saveTauri(commands.setArchivePartSizeMib, partSize)}
+              className="w-16 px-2 py-1 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all flex-shrink-0"
+            />
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label
+                htmlFor="packaging-retry"
+                className="text-xs font-medium text-gray-700 block"
+              >
+                Packaging Retry Window
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Seconds a send whose packing failed keeps its code for a retry; 0 = fail at once
+              </p>
+            </div>
+            <input
+              id="packaging-retry"
+              type="number"
+              min={0}
+              value={packagingRetry}
+              onChange={(e) => setPackagingRetry(Math.max(0, parseInt(e.target.value, 10) || 0))}
+              onBlur={() => saveTauri(commands.setPackagingRetrySecs, packagingRetry)}
+              className="w-16 px-2 py-1 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all flex-shrink-0"
+            />
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="package-cache" className="text-xs font-medium text-gray-700 block">
+                Packaging Cache
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                MiB of packed folders kept so re-sending one skips packing; 0 = off
+                {packageCache &&
+                  ` (${packageCache.entries} kept, ${(packageCache.bytes / 1048576).toFixed(1)} MiB)`}
+              </p>
+              <button
+                onClick={() =>
+                  unwrap(commands.clearPackageCache())
+                    .then(refreshPackageCache)
+                    .catch((e) => toast.error(String(e)))
+                }
+                className="text-[11px] font-medium text-blue-600 hover:text-blue-700 cursor-pointer"
+              >
+                Clear
+              </button>
+            </div>
+            <input
+              id="package-cache"
+              type="number"
+              min={0}
+              value={packageCacheMib}
+              onFocus={refreshPackageCache}
+              onChange={(e) => setPackageCacheMib(Math.max(0, parseInt(e.target.value, 10) || 0))}
+              onBlur={() =>
+                unwrap(commands.setPackageCacheMib(packageCacheMib))
+                  .then(refreshPackageCache)
+                  .catch((e) => console.error("Error saving set_package_cache_mib:", e))
+              }
+              className="w-16 px-2 py-1 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all flex-shrink-0"
+            />
+          </div>
+
+          <div className="space-y-1.5">
+            <label htmlFor="upload-limit" className="text-xs font-medium text-gray-700 block">
+              Upload Speed Limit
+            </label>
+            <div className="flex items-center gap-2">
+              <input
+                id="upload-limit"
+                type="number"
+                min={0}
+                value={uploadLimit}
+                onChange={(e) => setUploadLimit(e.target.value)}
+                onBlur={() => saveUploadLimit(uploadLimit)}
+                placeholder="Unlimited"
+                className="flex-1 min-w-0 px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
+              />
+              <span className="text-xs text-gray-500 flex-shrink-0">KiB/s</span>
+            </div>
+            <p className="text-[11px] text-gray-500">Running sends pick up a change within seconds</p>
+          </div>
+
+          <div className="space-y-1.5">
+            <label htmlFor="download-limit" className="text-xs font-medium text-gray-700 block">
+              Download Speed Limit
+            </label>
+            <div className="flex items-center gap-2">
+              <input
+                id="download-limit"
+                type="number"
+                min={0}
+                value={downloadLimit}
+                onChange={(e) => setDownloadLimit(e.target.value)}
+                onBlur={() => saveDownloadLimit(downloadLimit)}
+                placeholder="Unlimited"
+                className="flex-1 min-w-0 px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
+              />
+              <span className="text-xs text-gray-500 flex-shrink-0">KiB/s</span>
+            </div>
+            <p className="text-[11px] text-gray-500">
+              Running downloads pick up a change within seconds
+            </p>
+            <textarea
+              id="speed-schedule"
+              value={schedule}
+              onChange={(e) => setSchedule(e.target.value)}
+              onBlur={() => saveSchedule(schedule)}
+              rows={2}
+              placeholder={"Schedule, e.g. 09:00-17:00 5120\n22:00-06:00 unlimited"}
+              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all resize-none"
+            />
+            <p className="text-[11px] text-gray-500">
+              Scheduled windows override both limits above, including for running transfers
+            </p>
+          </div>
+
+          <div className="space-y-1.5">
+            <label htmlFor="relay-url" className="text-xs font-medium text-gray-700 block">
+              Custom Relay Server
+            </label>
+            <div className="flex gap-2">
+              <input
+                id="relay-url"
+                type="text"
                 value={relayUrl}
                 onChange={(e) => setRelayUrl(e.target.value)}
                 onBlur={() => {
                   const t = relayUrl.trim();
-                  saveTauri("set_relay_server_url", { value: t.length > 0 ? t : null });
+                  saveTauri(commands.setRelayServerUrl, t.length > 0 ? t : null);
                 }}
                 placeholder="tcp:host:port"
                 className="flex-1 min-w-0 px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
@@ -350,21 +1467,368 @@ export default function SettingsMenu() {
                 Test
               </button>
             </div>
+            <div className="flex gap-2">
+              <select
+                value={relayPresets.find((p) => (p.url ?? "") === relayUrl.trim())?.id ?? ""}
+                onChange={(e) => e.target.value && applyRelayPreset(e.target.value)}
+                title={
+                  relayPresets.find((p) => (p.url ?? "") === relayUrl.trim())?.description ??
+                  "Pick a public relay"
+                }
+                className="flex-1 min-w-0 px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all cursor-pointer"
+              >
+                <option value="">Custom</option>
+                {relayPresets.map((p) => (
+                  <option key={p.id} value={p.id}>
+                    {p.name}
+                  </option>
+                ))}
+              </select>
+              <button
+                type="button"
+                onClick={resetRelay}
+                className="px-3 py-2 text-sm font-medium text-blue-600 hover:text-blue-700 hover:bg-blue-50 rounded-lg transition-colors cursor-pointer flex-shrink-0"
+              >
+                Reset
+              </button>
+            </div>
+            <textarea
+              id="extra-relays"
+              value={extraRelays}
+              onChange={(e) => setExtraRelays(e.target.value)}
+              onBlur={() => saveExtraRelays(extraRelays)}
+              rows={2}
+              placeholder="More relays, one per line"
+              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all resize-none"
+            />
+            <button
+              type="button"
+              onClick={benchmarkRelays}
+              disabled={benchmarking}
+              className="w-full px-3 py-2 text-sm font-medium text-blue-600 hover:text-blue-700 hover:bg-blue-50 rounded-lg transition-colors cursor-pointer disabled:opacity-50 disabled:cursor-default"
+            >
+              {benchmarking ? "Timing relays…" : "Find fastest relay"}
+            </button>
+          </div>
+
+          <div className="space-y-1.5">
+            <label htmlFor="transit-mode" className="text-xs font-medium text-gray-700">
+              Connection Type
+            </label>
+            <select
+              id="transit-mode"
+              value={transitMode}
+              onChange={(e) => {
+                setTransitMode(e.target.value);
+                saveTauri(commands.setTransitMode, e.target.value);
+              }}
+              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all cursor-pointer"
+            >
+              <option value="any">Direct or relay</option>
+              <option value="direct">Direct only</option>
+              <option value="relay">Relay only</option>
+            </select>
+            <p className="text-[11px] text-gray-500">
+              Relay only hides your address from the peer; applies to sends and receives
+            </p>
+          </div>
+
+          <div className="space-y-1.5">
+            <label htmlFor="webhook-url" className="text-xs font-medium text-gray-700 block">
+              Completion Webhook
+            </label>
+            <input
+              id="webhook-url"
+              type="text"
+              value={webhookUrl ?? ""}
+              onChange={(e) => setWebhookUrl(e.target.value)}
+              onBlur={() => saveWebhookUrl(webhookUrl ?? "")}
+              placeholder="https://example.com/hook"
+              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
+            />
+            <p className="text-[11px] text-gray-500">
+              Gets a JSON summary (name, size, SHA-256, duration) of each finished transfer
+            </p>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="local-api" className="text-xs font-medium text-gray-700 block">
+                Local API
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Let scripts and apps on this computer start transfers over HTTP on 127.0.0.1
+              </p>
+            </div>
+            <button
+              id="local-api"
+              onClick={toggleLocalApi}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${localApi ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${localApi ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+          {localApi && (
+            <div className="space-y-1.5">
+              <div className="flex items-center gap-2">
+                <label htmlFor="local-api-port" className="text-xs text-gray-700 flex-shrink-0">
+                  Port
+                </label>
+                <input
+                  id="local-api-port"
+                  type="number"
+                  min={1}
+                  max={65535}
+                  value={localApiPort}
+                  onChange={(e) => setLocalApiPort(parseInt(e.target.value, 10) || 0)}
+                  onBlur={() => saveLocalApiPort(localApiPort)}
+                  className="w-24 px-2 py-1 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all"
+                />
+              </div>
+              <div className="flex gap-2">
+                <input
+                  type="text"
+                  readOnly
+                  value={localApiToken}
+                  aria-label="Local API token"
+                  className="flex-1 min-w-0 px-3 py-2 bg-gray-50 border border-gray-200 rounded-lg text-xs font-mono text-gray-700"
+                />
+                <button
+                  type="button"
+                  onClick={() => navigator.clipboard.writeText(localApiToken)}
+                  className="px-3 py-2 text-sm font-medium text-blue-600 hover:text-blue-700 hover:bg-blue-50 rounded-lg transition-colors cursor-pointer flex-shrink-0"
+                >
+                  Copy
+                </button>
+                <button
+                  type="button"
+                  onClick={regenerateLocalApiToken}
+                  className="px-3 py-2 text-sm font-medium text-blue-600 hover:text-blue-700 hover:bg-blue-50 rounded-lg transition-colors cursor-pointer flex-shrink-0"
+                >
+                  New
+                </button>
+              </div>
+              <p className="text-[11px] text-gray-500">
+                Send it as <code>Authorization: Bearer &lt;token&gt;</code> with every request
+              </p>
+            </div>
+          )}
+
+          <div className="space-y-1.5">
+            <label className="text-xs font-medium text-gray-700">History Location</label>
+            <div className="flex gap-2">
+              <button
+                onClick={chooseHistoryDir}
+                className="flex-1 min-w-0 text-left px-3 py-2 bg-white hover:bg-blue-50/40 border border-gray-200 rounded-lg text-sm text-gray-900 truncate cursor-pointer transition-colors"
+                title={historyDir}
+              >
+                {historyDir.split(/[/\\]/).pop() || historyDir}
+              </button>
+              <button
+                type="button"
+                onClick={() => moveHistory(null)}
+                className="px-3 py-2 text-sm font-medium text-blue-600 hover:text-blue-700 hover:bg-blue-50 rounded-lg transition-colors cursor-pointer flex-shrink-0"
+              >
+                Reset
+              </button>
+            </div>
+            <p className="text-[11px] text-gray-500">Existing history files move with it</p>
+          </div>
+
+          <div className="space-y-1.5">
+            <label htmlFor="sent-path-privacy" className="text-xs font-medium text-gray-700">
+              Sent File Paths in History
+            </label>
+            <select
+              id="sent-path-privacy"
+              value={sentPathPrivacy}
+              onChange={(e) => {
+                setSentPathPrivacy(e.target.value);
+                saveTauri(commands.setSentPathPrivacy, e.target.value);
+              }}
+              className="w-full px-3 py-2 bg-white border border-gray-200 rounded-lg text-sm text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50 transition-all cursor-pointer"
+            >
+              <option value="full">Full paths</option>
+              <option value="names">File names only</option>
+              <option value="hashed">Salted hashes</option>
+            </select>
+            <p className="text-[11px] text-gray-500">
+              Applies to new entries; without full paths, history can&apos;t resend files
+            </p>
           </div>
 
           <div className="space-y-1.5">
             <label className="text-xs font-medium text-gray-700 block">Export History</label>
-            <div className="grid grid-cols-2 gap-2">
+            <div className="flex items-center gap-2">
+              <input
+                type="date"
+                aria-label="Export from"
+                value={exportRange.from ?? ""}
+                onChange={(e) => setExportRange({ ...exportRange, from: e.target.value || null })}
+                className="flex-1 min-w-0 px-2 py-1.5 bg-white border border-gray-200 rounded-lg text-xs text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50"
+              />
+              <span className="text-xs text-gray-500">to</span>
+              <input
+                type="date"
+                aria-label="Export to"
+                value={exportRange.to ?? ""}
+                onChange={(e) => setExportRange({ ...exportRange, to: e.target.value || null })}
+                className="flex-1 min-w-0 px-2 py-1.5 bg-white border border-gray-200 rounded-lg text-xs text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50"
+              />
+            </div>
+            <input
+              type="password"
+              aria-label="Export passphrase"
+              placeholder="Passphrase to encrypt JSON exports (optional)"
+              value={exportPassphrase}
+              onChange={(e) => setExportPassphrase(e.target.value)}
+              className="w-full px-2 py-1.5 bg-white border border-gray-200 rounded-lg text-xs text-gray-900 focus:outline-none focus:ring-2 focus:ring-blue-400/50"
+            />
+            <div className="grid grid-cols-3 gap-2">
               {EXPORTS.map((e) => (
                 <button
-                  key={e.cmd}
-                  onClick={() => exportHistory(e.cmd, e.path, e.label)}
+                  key={e.label}
+                  onClick={() =>
+                    exportHistory(e.run, e.path, e.label, exportRange, exportPassphrase)
+                  }
                   className="glass-primary-btn px-3 py-2 text-sm font-medium text-white rounded-lg transition-all cursor-pointer"
                 >
                   {e.label}
                 </button>
               ))}
             </div>
+            <p className="text-[11px] text-gray-500">
+              Leave the dates empty to export everything. The range and passphrase apply to JSON
+              exports; encrypted ones open with <code>age -d</code>
+            </p>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="update-check" className="text-xs font-medium text-gray-700 block">
+                Check for Updates
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Look for a new release on launch.{" "}
+                <button
+                  type="button"
+                  onClick={() => checkForUpdates(false)}
+                  className="text-blue-600 hover:text-blue-700 cursor-pointer"
+                >
+                  Check now
+                </button>
+              </p>
+            </div>
+            <button
+              id="update-check"
+              onClick={toggleAutoUpdateCheck}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${autoUpdateCheck ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${autoUpdateCheck ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="beta-channel" className="text-xs font-medium text-gray-700 block">
+                Beta Releases
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Include pre-releases when checking for and downloading updates
+              </p>
+            </div>
+            <button
+              id="beta-channel"
+              onClick={toggleBetaChannel}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${releaseChannel === "beta" ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${releaseChannel === "beta" ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label
+                htmlFor="auto-download-updates"
+                className="text-xs font-medium text-gray-700 block"
+              >
+                Download Updates Automatically
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Fetch new releases in the background and ask before restarting to install
+              </p>
+            </div>
+            <button
+              id="auto-download-updates"
+              onClick={toggleAutoDownloadUpdates}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${autoDownloadUpdates ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${autoDownloadUpdates ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="flex items-center justify-between gap-3">
+            <div className="flex-1 min-w-0">
+              <label htmlFor="crash-reports" className="text-xs font-medium text-gray-700 block">
+                Crash Reports
+              </label>
+              <p className="text-[11px] text-gray-500 mt-0.5">
+                Save a local report if wyrmhole crashes; offered for export on next launch
+              </p>
+            </div>
+            <button
+              id="crash-reports"
+              onClick={toggleCrashReports}
+              className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${crashReports ? "bg-blue-500" : "bg-gray-300"}`}
+            >
+              <span
+                className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${crashReports ? "translate-x-4" : "translate-x-0.5"}`}
+              />
+            </button>
+          </div>
+
+          <div className="space-y-1.5">
+            <div className="flex items-center justify-between gap-3">
+              <div className="flex-1 min-w-0">
+                <label htmlFor="usage-metrics" className="text-xs font-medium text-gray-700 block">
+                  Usage Metrics
+                </label>
+                <p className="text-[11px] text-gray-500 mt-0.5">
+                  Count transfers locally; never uploaded, export to share
+                </p>
+              </div>
+              <button
+                id="usage-metrics"
+                onClick={toggleUsageMetrics}
+                className={`relative inline-flex h-5 w-9 items-center rounded-full transition-colors cursor-pointer flex-shrink-0 ${usageMetrics ? "bg-blue-500" : "bg-gray-300"}`}
+              >
+                <span
+                  className={`inline-block h-4 w-4 transform rounded-full bg-white transition-transform ${usageMetrics ? "translate-x-4" : "translate-x-0.5"}`}
+                />
+              </button>
+            </div>
+            <div className="grid grid-cols-2 gap-2">
+              <button
+                onClick={exportMetrics}
+                className="px-3 py-2 text-sm font-medium text-blue-600 hover:text-blue-700 hover:bg-blue-50 rounded-lg transition-colors cursor-pointer"
+              >
+                Export
+              </button>
+              <button
+                onClick={resetMetrics}
+                className="px-3 py-2 text-sm font-medium text-gray-600 hover:text-gray-800 hover:bg-gray-100 rounded-lg transition-colors cursor-pointer"
+              >
+                Reset
+              </button>
+            </div>
           </div>
         </div>
       )}
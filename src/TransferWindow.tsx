@@ -0,0 +1,143 @@
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+import { useEffect, useState } from "react";
+import { FileIcon } from "./FileIcon";
+import { commands } from "./bindings";
+import { unwrap } from "./commands";
+import "./App.css";
+
+type Props = {
+  id: string;
+  kind: "send" | "download";
+};
+
+type State = {
+  file_name: string;
+  done: number;
+  total: number;
+  percentage: number;
+  status: string;
+  code?: string;
+  error?: string;
+  finished: boolean;
+};
+
+const STATUS_TEXT: Record<string, string> = {
+  queued: "Queued",
+  preparing: "Preparing",
+  connecting: "Connecting",
+  waiting: "Waiting for receiver",
+  reconnecting: "Reconnecting",
+  offline: "Offline, retrying",
+  packaging: "Packaging",
+  "transit-negotiating": "Connecting to peer",
+  sending: "Sending",
+  receiving: "Downloading",
+  verifying: "Verifying",
+  finalizing: "Completed",
+};
+
+function formatBytes(bytes: number): string {
+  if (bytes === 0) return "0 B";
+  const k = 1024;
+  const sizes = ["B", "KB", "MB", "GB", "TB"];
+  const i = Math.floor(Math.log(bytes) / Math.log(k));
+  return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + " " + sizes[i];
+}
+
+// A popped-out send or download (see transfer_windows.rs). The backend routes only this
+// transfer's events here, wrapped as `transfer-window-update`.
+export default function TransferWindow({ id, kind }: Props) {
+  const [state, setState] = useState<State>({
+    file_name: "",
+    done: 0,
+    total: 0,
+    percentage: 0,
+    status: "",
+    finished: false,
+  });
+
+  useEffect(() => {
+    const unlisten = getCurrentWebviewWindow().listen<{
+      event: string;
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      payload: any;
+    }>("transfer-window-update", ({ payload: { event, payload } }) => {
+      setState((prev) => {
+        switch (event) {
+          case "send-progress":
+            return {
+              ...prev,
+              file_name: payload.file_name,
+              done: payload.sent,
+              total: payload.total,
+              percentage: payload.percentage,
+              status: payload.status,
+              code: payload.code || prev.code,
+              finished: payload.status === "finalizing",
+            };
+          case "download-progress":
+            return {
+              ...prev,
+              file_name: payload.file_name,
+              done: payload.transferred,
+              total: payload.total,
+              percentage: payload.percentage,
+              status: payload.status,
+            };
+          case "connection-code":
+            return { ...prev, code: payload.code ?? prev.code };
+          case "code-expired":
+            return { ...prev, code: undefined };
+          case "download-complete":
+            return { ...prev, status: "finalizing", percentage: 100, finished: true };
+          case "send-error":
+          case "download-error":
+            return { ...prev, error: payload.error, finished: true };
+          default:
+            return prev;
+        }
+      });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  async function cancel() {
+    const cancelled = kind === "send" ? commands.cancelSend(id) : commands.cancelDownload(id);
+    await unwrap(cancelled).catch((e) => console.error(`Error cancelling ${kind}:`, e));
+  }
+
+  const progressBarColor = state.error ? "bg-red-600" : "bg-green-600";
+  return (
+    <main className="p-4 space-y-2 select-none">
+      <div className="flex items-center gap-2 min-w-0 text-gray-800">
+        <FileIcon fileName={state.file_name} className="w-4 h-4 flex-shrink-0" />
+        <span className="text-sm font-medium truncate">{state.file_name || "Waiting…"}</span>
+      </div>
+      <div className="w-full bg-gray-200 rounded-full h-2 shadow-inner">
+        <div
+          className={`${progressBarColor} h-2 rounded-full transition-all duration-300`}
+          style={{ width: `${Math.min(state.percentage, 100)}%` }}
+        />
+      </div>
+      <div className="flex items-center justify-between gap-2 text-xs text-gray-600">
+        <span className={`truncate ${state.error ? "text-red-600" : ""}`} title={state.error}>
+          {state.error ?? STATUS_TEXT[state.status] ?? state.status}
+          {state.code && !state.finished && kind === "send" ? ` · ${state.code}` : ""}
+        </span>
+        <span className="whitespace-nowrap">
+          {formatBytes(state.done)} / {formatBytes(state.total)}
+        </span>
+      </div>
+      {!state.finished && (
+        <button
+          onClick={cancel}
+          className="w-full px-3 py-1.5 text-xs font-semibold text-red-600 hover:bg-red-50 rounded-lg transition-colors cursor-pointer"
+        >
+          Cancel
+        </button>
+      )}
+    </main>
+  );
+}
@@ -0,0 +1,1801 @@
+// @ts-nocheck
+// This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
+
+/** user-defined commands **/
+
+
+export const commands = {
+async sendFileCall(filePath: string, sendId: string, confirmSensitive: boolean) : Promise<Result<string, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("send_file_call", { filePath, sendId, confirmSensitive }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async sendMultipleFilesCall(filePaths: string[], sendId: string, folderName: string | null, compression: number | null, confirmSensitive: boolean) : Promise<Result<string, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("send_multiple_files_call", { filePaths, sendId, folderName, compression, confirmSensitive }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async sendEncryptedArchiveCall(filePaths: string[], sendId: string, folderName: string | null, password: string, confirmSensitive: boolean) : Promise<Result<string, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("send_encrypted_archive_call", { filePaths, sendId, folderName, password, confirmSensitive }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async sendChangesCall(path: string, sendId: string, confirmSensitive: boolean) : Promise<Result<string, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("send_changes_call", { path, sendId, confirmSensitive }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async resetSync(path: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reset_sync", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async sendClipboard(sendId: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("send_clipboard", { sendId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async captureAndSend(sendId: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("capture_and_send", { sendId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async revealCode(sendId: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reveal_code", { sendId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async composeCodeShare(sendId: string, channel: string) : Promise<Result<CodeShare, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("compose_code_share", { sendId, channel }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async computePathSize(path: string, id: string) : Promise<Result<PathSize, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("compute_path_size", { path, id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelPathSize(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_path_size", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async pickFolderForSend() : Promise<Result<FolderForSend | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pick_folder_for_send") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async pickDirectory() : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pick_directory") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async pickFilesForSend(multiple: boolean) : Promise<Result<string[] | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pick_files_for_send", { multiple }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async pickExportPath(defaultName: string, extension: string) : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pick_export_path", { defaultName, extension }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async checkSensitivePaths(paths: string[]) : Promise<Result<SensitivePath[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("check_sensitive_paths", { paths }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelSend(sendId: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_send", { sendId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listPackagingJobs() : Promise<Result<PackagingJob[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_packaging_jobs") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async retryPackagingJob(jobId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("retry_packaging_job", { jobId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelPackagingJob(jobId: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_packaging_job", { jobId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelDownload(downloadId: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_download", { downloadId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelAllTransfers() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_all_transfers") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async activeTransfers() : Promise<Result<TransferSnapshot, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("active_transfers") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async requestFileCall(receiveCode: string, connectionId: string) : Promise<Result<FileOffer, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("request_file_call", { receiveCode, connectionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async sendReplyCall(transferId: string, filePath: string, sendId: string, confirmSensitive: boolean) : Promise<Result<string, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("send_reply_call", { transferId, filePath, sendId, confirmSensitive }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async sendBack(receivedEntryId: string, sendId: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("send_back", { receivedEntryId, sendId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeReceivedEntry(receivedEntryId: string, deleteFile: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_received_entry", { receivedEntryId, deleteFile }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stageDragOut(receivedEntryId: string) : Promise<Result<DragOut, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stage_drag_out", { receivedEntryId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async awaitReplyCall(transferId: string, connectionId: string) : Promise<Result<FileOffer, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("await_reply_call", { transferId, connectionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelConnection(connectionId: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_connection", { connectionId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async receivingFileAccept(id: string, destination: string | null, confirmOversize: boolean | null, confirmContent: boolean | null) : Promise<Result<string, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("receiving_file_accept", { id, destination, confirmOversize, confirmContent }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async receivingFileDeny(id: string, reason: string | null) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("receiving_file_deny", { id, reason }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async acceptAllPending(destination: string | null) : Promise<Result<string, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("accept_all_pending", { destination }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async denyAllPending(reason: string | null) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("deny_all_pending", { reason }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openReceivedFile(path: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_received_file", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async showInFolder(path: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("show_in_folder", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setDownloadDirectory(newPath: string) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_download_directory", { newPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async receivedFilesData() : Promise<Result<ReceivedFile[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("received_files_data") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async sentFilesData() : Promise<Result<SentFile[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("sent_files_data") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDownloadPath() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_download_path") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getHistoryDirectory() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_history_directory") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setHistoryDirectory(newPath: string | null) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_history_directory", { newPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAutoExtractTarballs() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_auto_extract_tarballs") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setAutoExtractTarballs(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_auto_extract_tarballs", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDateSubfolders() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_date_subfolders") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setDateSubfolders(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_date_subfolders", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getReceiveHooks() : Promise<Result<ReceiveHook[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_receive_hooks") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setReceiveHooks(value: ReceiveHook[]) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_receive_hooks", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSaveTextOffers() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_save_text_offers") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSaveTextOffers(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_save_text_offers", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDefaultFolderNameFormat() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_default_folder_name_format") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setDefaultFolderNameFormat(value: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_default_folder_name_format", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getReceivedFileNameTemplate() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_received_file_name_template") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setReceivedFileNameTemplate(value: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_received_file_name_template", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRelayServerUrl() : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_relay_server_url") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setRelayServerUrl(value: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_relay_server_url", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listRelayPresets() : Promise<RelayPreset[]> {
+    return await TAURI_INVOKE("list_relay_presets");
+},
+async applyRelayPreset(id: string) : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("apply_relay_preset", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async resetRelayServer() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reset_relay_server") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDownloadRateLimitKib() : Promise<Result<number | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_download_rate_limit_kib") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setDownloadRateLimitKib(value: number | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_download_rate_limit_kib", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getUploadRateLimitKib() : Promise<Result<number | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_upload_rate_limit_kib") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setUploadRateLimitKib(value: number | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_upload_rate_limit_kib", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRateLimitSchedule() : Promise<Result<RateWindow[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_rate_limit_schedule") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setRateLimitSchedule(value: RateWindow[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_rate_limit_schedule", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMaxConcurrentSends() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_max_concurrent_sends") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setMaxConcurrentSends(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_max_concurrent_sends", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getHideSendCodes() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_hide_send_codes") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setHideSendCodes(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_hide_send_codes", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getIdleTimeoutSecs() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_idle_timeout_secs") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setIdleTimeoutSecs(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_idle_timeout_secs", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMergeFolderUpdates() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_merge_folder_updates") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setMergeFolderUpdates(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_merge_folder_updates", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getApplyFileDeltas() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_apply_file_deltas") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setApplyFileDeltas(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_apply_file_deltas", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCompressionLevel() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_compression_level") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setCompressionLevel(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_compression_level", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDeterministicArchives() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_deterministic_archives") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setDeterministicArchives(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_deterministic_archives", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getStreamArchives() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_stream_archives") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setStreamArchives(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_stream_archives", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getArchivePartSizeMib() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_archive_part_size_mib") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setArchivePartSizeMib(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_archive_part_size_mib", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getExtraRelayUrls() : Promise<Result<string[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_extra_relay_urls") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setExtraRelayUrls(value: string[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_extra_relay_urls", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTransitMode() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_transit_mode") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setTransitMode(value: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_transit_mode", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getQueueOfflineSends() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_queue_offline_sends") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setQueueOfflineSends(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_queue_offline_sends", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getWebhookUrl() : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_webhook_url") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setWebhookUrl(value: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_webhook_url", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCodeExpiryMins() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_code_expiry_mins") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setCodeExpiryMins(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_code_expiry_mins", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getOfferExpiryMins() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_offer_expiry_mins") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setOfferExpiryMins(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_offer_expiry_mins", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSentPathPrivacy() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sent_path_privacy") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSentPathPrivacy(value: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_sent_path_privacy", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSkipDuplicateReceives() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_skip_duplicate_receives") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSkipDuplicateReceives(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_skip_duplicate_receives", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getVerifyDiskWrites() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_verify_disk_writes") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setVerifyDiskWrites(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_verify_disk_writes", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMaxIncomingSizeMib() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_max_incoming_size_mib") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setMaxIncomingSizeMib(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_max_incoming_size_mib", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getContentAllowlist() : Promise<Result<string[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_content_allowlist") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setContentAllowlist(value: string[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_content_allowlist", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getPackagingRetrySecs() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_packaging_retry_secs") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setPackagingRetrySecs(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_packaging_retry_secs", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getPackageCacheMib() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_package_cache_mib") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setPackageCacheMib(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_package_cache_mib", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getPackageCacheInfo() : Promise<Result<PackageCacheInfo, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_package_cache_info") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async clearPackageCache() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_package_cache") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLocalApiEnabled() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_local_api_enabled") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLocalApiEnabled(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_local_api_enabled", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLocalApiPort() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_local_api_port") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLocalApiPort(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_local_api_port", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLocalApiToken() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_local_api_token") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async regenerateLocalApiToken() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("regenerate_local_api_token") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMinimizeOnStart() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_minimize_on_start") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setMinimizeOnStart(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_minimize_on_start", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMinimizeOnClose() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_minimize_on_close") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setMinimizeOnClose(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_minimize_on_close", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAutostart() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_autostart") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setAutostart(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_autostart", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async exportReceivedFilesJson(filePath: string, range: ExportRange | null, passphrase: string | null, id: string) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_received_files_json", { filePath, range, passphrase, id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async exportSentFilesJson(filePath: string, range: ExportRange | null, passphrase: string | null, id: string) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_sent_files_json", { filePath, range, passphrase, id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async verifySentEntry(id: string) : Promise<Result<SentPathCheck[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("verify_sent_entry", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async verifyReceivedFile(id: string) : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("verify_received_file", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async exportHistoryHtml(filePath: string) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_history_html", { filePath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getUsageMetricsEnabled() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_usage_metrics_enabled") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setUsageMetricsEnabled(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_usage_metrics_enabled", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getUsageMetrics() : Promise<Result<UsageMetrics, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_usage_metrics") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async exportUsageMetrics(filePath: string) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_usage_metrics", { filePath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async resetUsageMetrics() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reset_usage_metrics") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCheckUpdatesAutomatically() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_check_updates_automatically") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setCheckUpdatesAutomatically(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_check_updates_automatically", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getReleaseChannel() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_release_channel") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setReleaseChannel(value: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_release_channel", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async checkForUpdates() : Promise<Result<UpdateInfo, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("check_for_updates") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAutoDownloadUpdates() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_auto_download_updates") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setAutoDownloadUpdates(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_auto_download_updates", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async downloadUpdate() : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("download_update") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async installUpdate() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("install_update") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCrashReportsEnabled() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_crash_reports_enabled") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setCrashReportsEnabled(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_crash_reports_enabled", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listCrashReports() : Promise<Result<CrashReport[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_crash_reports") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async exportCrashReport(fileName: string, filePath: string) : Promise<Result<null, CommandError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_crash_report", { fileName, filePath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async clearCrashReports() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_crash_reports") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSoundsEnabled() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sounds_enabled") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSoundsEnabled(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_sounds_enabled", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSoundVolume() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sound_volume") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSoundVolume(value: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_sound_volume", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async playSoundPreview() : Promise<null> {
+    return await TAURI_INVOKE("play_sound_preview");
+},
+async listProfiles() : Promise<Result<string[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_profiles") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getActiveProfile() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_active_profile") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async switchProfile(name: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("switch_profile", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async testRelayServer() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("test_relay_server") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async benchmarkRelays() : Promise<Result<RelayBenchmark[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("benchmark_relays") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async frontendReady() : Promise<null> {
+    return await TAURI_INVOKE("frontend_ready");
+},
+async confirmLinkSend(paths: string[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("confirm_link_send", { paths }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async discardLinkSend() : Promise<null> {
+    return await TAURI_INVOKE("discard_link_send");
+},
+async openTransferWindow(id: string, kind: string, fileName: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_transfer_window", { id, kind, fileName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getContextMenuEnabled() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_context_menu_enabled") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setContextMenuEnabled(value: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_context_menu_enabled", { value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+}
+}
+
+/** user-defined events **/
+
+
+
+/** user-defined constants **/
+
+export const SEND_PROGRESS = "send-progress" as const;
+export const SEND_ERROR = "send-error" as const;
+export const CONNECTION_CODE = "connection-code" as const;
+export const DOWNLOAD_PROGRESS = "download-progress" as const;
+export const DOWNLOAD_ERROR = "download-error" as const;
+export const DOWNLOAD_COMPLETE = "download-complete" as const;
+export const TEXT_OFFER = "text-offer" as const;
+export const TRANSFER_STALLED = "transfer-stalled" as const;
+export const NETWORK_CHANGED = "network-changed" as const;
+export const CODE_AGE = "code-age" as const;
+export const CODE_EXPIRED = "code-expired" as const;
+export const PEER_CONNECTED = "peer-connected" as const;
+export const OFFER_DECLINED = "offer-declined" as const;
+export const OFFER_EXPIRED = "offer-expired" as const;
+export const DUPLICATE_DETECTED = "duplicate-detected" as const;
+export const CONTENT_FLAGGED = "content-flagged" as const;
+export const VERIFICATION_COMPLETE = "verification-complete" as const;
+export const PATH_SIZE_PROGRESS = "path-size-progress" as const;
+export const PREPARING_DETAILS = "preparing-details" as const;
+export const FILES_SKIPPED = "files-skipped" as const;
+export const PACKAGING_JOB = "packaging-job" as const;
+export const RECEIVED_FILE_ADDED = "received-file-added" as const;
+export const RECEIVED_FILES_ADDED = "received-files-added" as const;
+export const SENT_FILE_ADDED = "sent-file-added" as const;
+export const DEFAULT_FOLDER_NAME_FORMAT_UPDATED = "default-folder-name-format-updated" as const;
+export const SEND_FROM_OS_EVENT = "send-files-from-os" as const;
+export const SEND_REQUEST_FROM_LINK_EVENT = "send-request-from-link" as const;
+export const UPDATE_DOWNLOAD_PROGRESS = "update-download-progress" as const;
+
+/** user-defined types **/
+
+/**
+ * How long send `id`'s code has been waiting for the receiver, every second while it waits.
+ * `expires_in_secs` is set when `code_expiry_mins` will close the mailbox.
+ */
+export type CodeAge = { id: string; age_secs: number; expires_in_secs: number | null }
+/**
+ * Send `id` ended without delivering, so `code` no longer leads anywhere. Hosts should stop
+ * showing it as usable, so nobody reads out a stale code for a retry.
+ */
+export type CodeExpired = { id: string; code: string }
+/**
+ * A composed message. `url` opens the message in a mail or messaging app; it's `None` for
+ * the clipboard, where `text` is what to copy.
+ */
+export type CodeShare = { channel: string; text: string; url: string | null }
+/**
+ * Error of the commands that check paths: a refusal arrives as a `ScopeError` object, a
+ * send that needs confirming as `{ sensitive: [...] }`, anything else as the usual string.
+ */
+export type CommandError = ScopeError | { sensitive: SensitivePath[] } | string
+/**
+ * Result of creating the mailbox for a send. Serialized with a `status` tag so
+ * the payload shape matches what the frontend already checks for.
+ */
+export type ConnectionCode = { status: "success"; code: string; send_id: string } | { status: "error"; message: string }
+/**
+ * Download `id` turned out to hold code its name didn't warn about (see content.rs). It was
+ * kept at `path` as saved, without extracting it or running the After Receiving rules.
+ */
+export type ContentFlagged = { id: string; file_name: string; path: string; warning: ContentWarning }
+/**
+ * Why a received file needs a second look.
+ */
+export type ContentWarning = "executable" | "script" | "archive-with-executable" | "unchecked-archive"
+/**
+ * A crash report waiting in the crash directory.
+ */
+export type CrashReport = { file_name: string; created: string }
+/**
+ * The default folder name format setting changed.
+ */
+export type DefaultFolderNameFormatUpdated = { value: string }
+/**
+ * An incoming transfer finished. `path` is the saved file, or the folder it was
+ * extracted into.
+ */
+export type DownloadComplete = { id: string; file_name: string; path: string }
+/**
+ * An incoming transfer failed. `code` is as for `SendError`.
+ */
+export type DownloadError = { id: string; file_name: string; error: string; code: ErrorCode | null }
+/**
+ * Progress updates for an incoming transfer.
+ */
+export type DownloadProgress = { id: string; file_name: string; transferred: number; total: number; percentage: number; status: TransferState }
+/**
+ * What the frontend passes to the plugin's `startDrag`.
+ */
+export type DragOut = { path: string; icon: string }
+/**
+ * Download `id` saved a file with the same checksum as one already in the received history,
+ * still at `existing_path`. With `skip_duplicate_receives` on, the new copy was removed
+ * (`skipped`) and the history entry points at the existing one.
+ */
+export type DuplicateDetected = { id: string; file_name: string; existing_path: string; checksum: string; skipped: boolean }
+/**
+ * A failure the user can act on. Sent as `code` on `send-error` and `download-error`.
+ */
+export type ErrorCode = "wrong-code" | "code-claimed" | "rendezvous-unreachable" | "relay-unreachable" | "peer-too-old" | "offer-declined" | "destination-unwritable" | "disk-full" | "path-too-long" | "permission-denied"
+/**
+ * Inclusive date range (local dates) for history exports; either end may be left open.
+ */
+export type ExportRange = { from: string | null; to: string | null }
+/**
+ * Response of `request_file_call`: an incoming offer waiting to be accepted or denied.
+ */
+export type FileOffer = {
+  id: string;
+  file_name: string;
+  file_size: number;
+  /**
+   * The message, when the sender offered text rather than a file (also sent as a
+   * `TEXT_OFFER` event).
+   */
+  text: string | null;
+  /**
+   * Bigger than `max_incoming_size_mib`; accepting it needs `confirm_oversize`.
+   */
+  over_size_limit: boolean;
+  /**
+   * A program, script or the like by its name (see content.rs); accepting it needs
+   * `confirm_content`.
+   */
+  content_warning: ContentWarning | null;
+}
+/**
+ * A sent file or folder at the time of the send: its modified time, and the SHA-256 (hex)
+ * of the file, or for a folder of its manifest (every file's relative path and hash).
+ */
+export type FileSnapshot = { modified: string | null; sha256: string | null }
+/**
+ * Entries of a folder send (by its id) left out of the archive: special files and broken
+ * symlinks (see special.rs). Sent once, when there are any.
+ */
+export type FilesSkipped = { id: string; files: SkippedFile[] }
+/**
+ * A folder checked by `check_folder_for_send`.
+ */
+export type FolderForSend = {
+  /**
+   * Absolute path with symlinks resolved; on Windows without the `\\?\` prefix, so UNC
+   * shares come back as `\\server\share\...`.
+   */
+  path: string;
+  size: PathSize;
+}
+export type HookAction = "move_to" | "run_command" | "open_with"
+/**
+ * Where a packaging job is (see jobs.rs).
+ */
+export type JobState = "running" | "done" | "failed" | "cancelled"
+/**
+ * This machine's network changed (a new local address, or none) while transfer `id` ran.
+ * The transfer's connection was made on the old one, so it may stall or fail.
+ */
+export type NetworkChanged = { id: string; previous: string | null; current: string | null }
+/**
+ * The receiver declined send `id`. Follows its `send-error`, whose `code` is `offer-declined`.
+ */
+export type OfferDeclined = { id: string; file_name: string }
+/**
+ * Incoming offer `id` went unanswered for `offer_expiry_mins` and was declined, closing the
+ * sender's wormhole. Hosts should drop it from their pending offers.
+ */
+export type OfferExpired = { id: string; file_name: string }
+/**
+ * What's in the cache, for the settings page.
+ */
+export type PackageCacheInfo = { entries: number; bytes: number }
+/**
+ * A packaging job for send `send_id`, building `name`. `written` is the tarball's size
+ * so far. Emitted as it runs and when its state changes, and listed by `jobs::list_jobs`.
+ */
+export type PackagingJob = { job_id: string; send_id: string; name: string; state: JobState; written: number; error: string | null }
+/**
+ * Response of `compute_path_size`.
+ */
+export type PathSize = { bytes: number; files: number }
+/**
+ * Running total of a `compute_path_size` call (by its id); the last one has `done` set.
+ */
+export type PathSizeProgress = { id: string; bytes: number; files: number; done: boolean }
+/**
+ * The receiver claimed send `id`'s code and the wormhole is up; transit (and packaging, if
+ * still running) comes next.
+ */
+export type PeerConnected = { id: string; file_name: string }
+/**
+ * Send `id` is reading the metadata of `path`, the `index`th of its `count` selected paths.
+ * Sent before each one, so a stat stuck on a slow network share shows which path it's on.
+ */
+export type PreparingDetails = { id: string; path: string; index: number; count: number }
+/**
+ * A time window with its own speed cap, e.g. 09:00-17:00 at 5120 KiB/s.
+ */
+export type RateWindow = {
+  /**
+   * Local time the window starts, "HH:MM".
+   */
+  start: string;
+  /**
+   * Local time the window ends, "HH:MM"; earlier than `start` for windows past midnight.
+   */
+  end: string;
+  /**
+   * Cap in KiB/s while the window is on; None means unlimited.
+   */
+  limit_kib: number | null;
+}
+export type ReceiveHook = {
+  /**
+   * Comma-separated file name globs (`*` and `?`, case-insensitive), e.g. "*.jpg, *.png".
+   */
+  pattern: string;
+  action: HookAction;
+  target: string;
+}
+export type ReceivedFile = {
+  file_name: string;
+  file_size: number;
+  file_extension: string;
+  download_url: string;
+  download_time: string;
+  connection_type: string;
+  peer_address: string;
+  status: TransferStatus;
+  /**
+   * The error for cancelled and failed transfers, the reason given for rejected ones.
+   */
+  reason: string | null;
+  /**
+   * SHA-256 (hex) of the saved file; `None` for folders, extracted archives and entries
+   * from before checksums were recorded.
+   */
+  checksum: string | null;
+  /**
+   * Identifies the entry for commands like `send_back` and `verify_received_file`. Entries
+   * from before ids were recorded get one the first time the history is loaded.
+   */
+  id: string;
+}
+/**
+ * A new entry was appended to the received files history.
+ */
+export type ReceivedFileAdded = { file: ReceivedFile }
+/**
+ * Several entries were appended to the received files history at once (e.g. the
+ * contents of an extracted folder).
+ */
+export type ReceivedFilesAdded = { files: ReceivedFile[] }
+/**
+ * Result of timing one relay.
+ */
+export type RelayBenchmark = {
+  url: string;
+  /**
+   * Time to open a TCP connection, or `None` if it failed (see `error`).
+   */
+  latency_ms: number | null;
+  error: string | null;
+}
+/**
+ * A public relay server offered in settings so it doesn't have to be typed in.
+ */
+export type RelayPreset = {
+  id: string;
+  name: string;
+  /**
+   * `None` for magic-wormhole's built-in default relay.
+   */
+  url: string | null;
+  description: string;
+}
+/**
+ * A refused path, as the frontend receives it.
+ */
+export type ScopeError = { code: ScopeErrorCode; path: string; message: string }
+/**
+ * Why a path was refused. Sent to the frontend as `code` on a `ScopeError`.
+ */
+export type ScopeErrorCode = "not-absolute" | "unresolvable" | "other-users-home" | "outside-scope" | "app-config"
+/**
+ * An outgoing transfer failed or was cancelled. `code` is set for the well-known
+ * failures (see errors.rs), whose `error` then carries a hint.
+ */
+export type SendError = { id: string; file_name: string; error: string; code: ErrorCode | null }
+/**
+ * Progress and status updates for an outgoing transfer.
+ */
+export type SendProgress = { id: string; file_name: string; sent: number; total: number; percentage: number; code: string; status: TransferState }
+/**
+ * What makes a path worth a second look.
+ */
+export type SensitiveKind = "home-directory" | "browser-profile" | "keys-directory" | "secrets-file"
+/**
+ * One finding: `path` is what matched, which may be a file inside a selected folder.
+ */
+export type SensitivePath = { path: string; kind: SensitiveKind; message: string }
+export type SentFile = {
+  file_name: string;
+  file_size: number;
+  file_extension: string;
+  file_paths: string[];
+  send_time: string;
+  connection_code: string;
+  connection_type: string;
+  peer_address: string;
+  status: TransferStatus;
+  /**
+   * The error for cancelled and failed sends.
+   */
+  reason: string | null;
+  /**
+   * Identifies the entry for commands like `verify_sent_entry`. Entries from before ids
+   * were recorded get one the first time the history is loaded.
+   */
+  id: string;
+  /**
+   * Each of `file_paths` as it was when sent, in the same order. Empty for unfinished
+   * sends, entries from before snapshots were recorded, and when `sent_path_privacy`
+   * doesn't keep full paths (there'd be nothing to check them against).
+   */
+  snapshots: FileSnapshot[];
+  /**
+   * SHA-256 (hex) of the bytes sent, taken as they went out: the file, or for a folder or
+   * bundle the archive it was sent as. `None` for unfinished sends and entries from before
+   * checksums were recorded.
+   */
+  checksum: string | null;
+}
+/**
+ * A new entry was appended to the sent files history.
+ */
+export type SentFileAdded = { file: SentFile }
+export type SentPathCheck = { path: string; state: SnapshotState }
+/**
+ * Why a folder entry was left out.
+ */
+export type SkipReason = "fifo" | "socket" | "device" | "broken-link" | "other"
+/**
+ * An entry a walker left out of a send.
+ */
+export type SkippedFile = { path: string; reason: SkipReason }
+/**
+ * How a sent path compares with its snapshot, as reported by `verify_sent_entry`.
+ */
+export type SnapshotState = "unchanged" | "touched" | "changed" | "missing" | "not-recorded"
+/**
+ * The sender sent a text message instead of a file. `saved_path` is set when the
+ * `save_text_offers` setting already saved it; otherwise accepting the offer saves it
+ * and denying discards it.
+ */
+export type TextOffer = { id: string; text: string; saved_path: string | null }
+/**
+ * The ids of everything in flight at one moment, each list sorted.
+ */
+export type TransferSnapshot = {
+  sends: string[];
+  downloads: string[];
+  connections: string[];
+  /**
+   * Offers (files and text messages) waiting to be accepted or denied.
+   */
+  offers: string[];
+}
+/**
+ * A send or download (by its id) has moved no data for `idle_secs`. Cleared by its next
+ * progress event.
+ */
+export type TransferStalled = { id: string; idle_secs: number }
+/**
+ * Where a transfer is. Sends go `queued` (only when over the concurrent send limit),
+ * `preparing`, `connecting`, `waiting` (for the receiver, via `reconnecting` if the
+ * rendezvous connection drops), `packaging` (folders and bundles), `transit-negotiating`,
+ * `sending`, `verifying`, `finalizing`. Downloads go `transit-negotiating`, `receiving`,
+ * `verifying`, `finalizing`.
+ */
+export type TransferState = "queued" | "preparing" | "connecting" | "waiting" | "reconnecting" | "offline" | "packaging" | "transit-negotiating" | "sending" | "receiving" | "verifying" | "finalizing"
+/**
+ * How a transfer in the history ended. Entries written before statuses were recorded are
+ * `completed`, as only finished transfers were kept then.
+ */
+export type TransferStatus = "completed" | "cancelled" | "failed" | "rejected"
+/**
+ * Progress of `download_update`. `total` is missing if the server didn't send a length.
+ */
+export type UpdateDownloadProgress = { version: string; downloaded: number; total: number | null; percentage: number }
+/**
+ * Result of an update check.
+ */
+export type UpdateInfo = {
+  current_version: string;
+  latest_version: string;
+  /**
+   * Git tag of the latest release, e.g. "v1.2.0-beta.1".
+   */
+  release_tag: string;
+  update_available: boolean;
+  /**
+   * Release body as written on GitHub (Markdown).
+   */
+  release_notes: string;
+  /**
+   * Installer for this platform if the release has one, otherwise the release page.
+   */
+  download_url: string;
+  published_at: string | null;
+}
+/**
+ * The counters kept on disk. Contains no file names, codes, paths or addresses.
+ */
+export type UsageMetrics = {
+  /**
+   * When counting started (first recorded event after enabling or resetting).
+   */
+  since: string | null;
+  sends_started: number;
+  sends_completed: number;
+  sends_failed: number;
+  receives_started: number;
+  receives_completed: number;
+  receives_failed: number;
+  direct_connections: number;
+  relay_connections: number;
+}
+/**
+ * `checksum` (SHA-256, hex) of what send or download `id` transferred is known, with
+ * `direction` "sent" or "received"; or received history entry `id` was re-read by
+ * `verify_received_file`.
+ */
+export type VerificationComplete = {
+  id: string;
+  direction: string;
+  file_name: string;
+  checksum: string;
+  /**
+   * Whether the saved file hashed to `checksum` when read back; `None` when nothing was
+   * read back (sends, and downloads without `verify_disk_writes`).
+   */
+  intact: boolean | null;
+}
+
+/** tauri-specta globals **/
+
+import {
+	invoke as TAURI_INVOKE,
+	Channel as TAURI_CHANNEL,
+} from "@tauri-apps/api/core";
+import * as TAURI_API_EVENT from "@tauri-apps/api/event";
+import { type WebviewWindow as __WebviewWindow__ } from "@tauri-apps/api/webviewWindow";
+
+type __EventObj__<T> = {
+	listen: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.listen<T>>;
+	once: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.once<T>>;
+	emit: null extends T
+		? (payload?: T) => ReturnType<typeof TAURI_API_EVENT.emit>
+		: (payload: T) => ReturnType<typeof TAURI_API_EVENT.emit>;
+};
+
+export type Result<T, E> =
+	| { status: "ok"; data: T }
+	| { status: "error"; error: E };
+
+function __makeEvents__<T extends Record<string, any>>(
+	mappings: Record<keyof T, string>,
+) {
+	return new Proxy(
+		{} as unknown as {
+			[K in keyof T]: __EventObj__<T[K]> & {
+				(handle: __WebviewWindow__): __EventObj__<T[K]>;
+			};
+		},
+		{
+			get: (_, event) => {
+				const name = mappings[event as keyof T];
+
+				return new Proxy((() => {}) as any, {
+					apply: (_, __, [window]: [__WebviewWindow__]) => ({
+						listen: (arg: any) => window.listen(name, arg),
+						once: (arg: any) => window.once(name, arg),
+						emit: (arg: any) => window.emit(name, arg),
+					}),
+					get: (_, command: keyof __EventObj__<any>) => {
+						switch (command) {
+							case "listen":
+								return (arg: any) => TAURI_API_EVENT.listen(name, arg);
+							case "once":
+								return (arg: any) => TAURI_API_EVENT.once(name, arg);
+							case "emit":
+								return (arg: any) => TAURI_API_EVENT.emit(name, arg);
+						}
+					},
+				});
+			},
+		},
+	);
+}
@@ -0,0 +1,10 @@
+import type { Result } from "./bindings";
+
+// Fallible commands in bindings.ts resolve to a `Result` rather than throwing. `unwrap` turns
+// one back into a promise that rejects with the command's error, for callers that handle
+// failures with try/catch or `.catch`.
+export async function unwrap<T, E>(result: Promise<Result<T, E>>): Promise<T> {
+  const outcome = await result;
+  if (outcome.status === "error") throw outcome.error;
+  return outcome.data;
+}
@@ -1,9 +1,15 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import TransferWindow from "./TransferWindow";
+
+// Pop-out transfer windows load the same page with `?transfer=<id>&kind=send|download`.
+const params = new URLSearchParams(window.location.search);
+const transferId = params.get("transfer");
+const transferKind = params.get("kind") === "send" ? "send" : "download";
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {transferId ? <TransferWindow id={transferId} kind={transferKind} /> : <App />}
   </React.StrictMode>,
 );
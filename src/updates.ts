@@ -0,0 +1,90 @@
+import { listen } from "@tauri-apps/api/event";
+import { openUrl } from "@tauri-apps/plugin-opener";
+import { toast } from "sonner";
+import { commands, UPDATE_DOWNLOAD_PROGRESS, type UpdateDownloadProgress } from "./bindings";
+import { unwrap } from "./commands";
+
+// Offer to restart into an update that has already been downloaded.
+function promptRestart(version: string) {
+  toast(`wyrmhole ${version} is ready to install`, {
+    duration: Infinity,
+    action: {
+      label: "Restart to update",
+      onClick: () => {
+        unwrap(commands.installUpdate()).catch((e) => {
+          console.error("Error installing update:", e);
+          toast.error(String(e ?? "Failed to install update"));
+        });
+      },
+    },
+  });
+}
+
+// Download the update through the Tauri updater with a progress toast, then install it
+// (which restarts the app). Falls back to opening `fallbackUrl` if the updater fails,
+// e.g. for installs the updater can't replace such as distro packages.
+export async function installUpdate(fallbackUrl?: string) {
+  const toastId = toast.loading("Downloading update...");
+  const unlisten = await listen<UpdateDownloadProgress>(UPDATE_DOWNLOAD_PROGRESS, (event) => {
+    toast.loading(`Downloading wyrmhole ${event.payload.version}... ${event.payload.percentage}%`, {
+      id: toastId,
+    });
+  });
+  try {
+    const version = await unwrap(commands.downloadUpdate());
+    if (!version) {
+      toast.success("wyrmhole is up to date", { id: toastId });
+      return;
+    }
+    toast.loading(`Installing wyrmhole ${version}...`, { id: toastId });
+    await unwrap(commands.installUpdate());
+  } catch (e) {
+    console.error("Error updating:", e);
+    toast.error(String(e ?? "Failed to update"), { id: toastId });
+    if (fallbackUrl) {
+      openUrl(fallbackUrl).catch((e) => console.error("Error opening URL:", e));
+    }
+  } finally {
+    unlisten();
+  }
+}
+
+// Launch-time background download (the `auto_download_updates` setting). Stays silent
+// unless an update is ready, then asks before restarting.
+export async function downloadUpdateInBackground() {
+  try {
+    const version = await unwrap(commands.downloadUpdate());
+    if (version) {
+      promptRestart(version);
+    }
+  } catch (e) {
+    console.error("Error downloading update:", e);
+  }
+}
+
+// Ask the backend for the latest GitHub release and surface it as a toast.
+// `quiet` (launch-time checks) skips the "up to date" and error toasts.
+export async function checkForUpdates(quiet: boolean) {
+  try {
+    const info = await unwrap(commands.checkForUpdates());
+    if (info.update_available) {
+      toast(`wyrmhole ${info.latest_version} is available`, {
+        description: info.release_notes.split("\n")[0] || `You have ${info.current_version}`,
+        duration: Infinity,
+        action: {
+          label: "Update",
+          onClick: () => {
+            installUpdate(info.download_url);
+          },
+        },
+      });
+    } else if (!quiet) {
+      toast.success(`wyrmhole ${info.current_version} is up to date`);
+    }
+  } catch (e) {
+    console.error("Error checking for updates:", e);
+    if (!quiet) {
+      toast.error(e instanceof Error ? e.message : String(e ?? "Failed to check for updates"));
+    }
+  }
+}